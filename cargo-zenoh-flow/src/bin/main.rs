@@ -15,6 +15,7 @@
 use async_std::process::exit;
 use clap::Parser;
 use colored::*;
+use std::collections::HashMap;
 
 use cargo_zenoh_flow::error::CZFError;
 use zenoh_flow::model::descriptor::{OperatorDescriptor, SinkDescriptor, SourceDescriptor};
@@ -22,6 +23,7 @@ use zenoh_flow::model::registry::{
     NodeKind, RegistryNode, RegistryNodeArchitecture, RegistryNodeTag,
 };
 use zenoh_flow::types::NodeId;
+use zenoh_flow_node_sdk::testing::{run_fixture, Fixture};
 
 #[cfg(feature = "local_registry")]
 use rand::seq::SliceRandom;
@@ -84,6 +86,14 @@ pub enum ZFCtl {
         #[clap(short = 'l', long = "l", default_value = "rust")]
         language: Languages,
     },
+    /// Runs the built node on the inputs of a YAML `fixture` and prints its outputs.
+    Test {
+        fixture: std::path::PathBuf,
+        #[clap(short, long)]
+        package: Option<String>,
+        #[clap(short = 'm', long = "manifest-path", default_value = "Cargo.toml")]
+        manifest_path: std::path::PathBuf,
+    },
     List,
     Push {
         graph_id: String,
@@ -245,7 +255,13 @@ async fn main() {
                         id: NodeId::from(node_info.id.clone()),
                         inputs: inputs.clone(),
                         outputs: outputs.clone(),
-                        uri: Some(uri.clone()),
+                        side_outputs: vec![],
+                        input_policies: HashMap::new(),
+                        input_types: HashMap::new(),
+                        input_units: HashMap::new(),
+                        output_types: HashMap::new(),
+                        output_units: HashMap::new(),
+                        uri: Some(uri.clone().into()),
                         configuration: None,
                     };
 
@@ -304,7 +320,9 @@ async fn main() {
                     let descriptor = SourceDescriptor {
                         id: NodeId::from(node_info.id.clone()),
                         outputs: outputs.clone(),
-                        uri: Some(uri.clone()),
+                        output_types: HashMap::new(),
+                        output_units: HashMap::new(),
+                        uri: Some(uri.clone().into()),
                         configuration: None,
                     };
 
@@ -363,7 +381,10 @@ async fn main() {
                     let descriptor = SinkDescriptor {
                         id: NodeId::from(node_info.id.clone()),
                         inputs: inputs.clone(),
-                        uri: Some(uri.clone()),
+                        input_policies: HashMap::new(),
+                        input_types: HashMap::new(),
+                        input_units: HashMap::new(),
+                        uri: Some(uri.clone().into()),
                         configuration: None,
                     };
 
@@ -534,6 +555,76 @@ async fn main() {
                 }
             }
         },
+        ZFCtl::Test {
+            fixture,
+            package,
+            manifest_path,
+        } => {
+            let (node_info, target_dir, _manifest_dir) =
+                match cargo_zenoh_flow::utils::from_manifest(&manifest_path, package) {
+                    Ok(res) => res,
+                    Err(_e) => {
+                        println!("{}: unable to parse Cargo.toml", "error".red().bold());
+                        exit(-1);
+                    }
+                };
+
+            let descriptor =
+                cargo_zenoh_flow::utils::zf_descriptor_path(&target_dir, &node_info.id);
+            if !descriptor.exists() {
+                println!(
+                    "{}: node {} is not built, run `cargo zenoh-flow build` first",
+                    "error".red().bold(),
+                    node_info.id
+                );
+                exit(-1);
+            }
+
+            let fixture = match Fixture::from_file(&fixture) {
+                Ok(fixture) => fixture,
+                Err(e) => {
+                    println!("{}: invalid fixture {:?}", "error".red().bold(), e);
+                    exit(-1);
+                }
+            };
+
+            println!(
+                "{} Node {} - Kind {}",
+                "Testing".green().bold(),
+                node_info.id,
+                node_info.kind.to_string()
+            );
+            let outputs = match run_fixture(
+                &descriptor,
+                node_info.kind.clone(),
+                &node_info.inputs.unwrap_or_default(),
+                &node_info.outputs.unwrap_or_default(),
+                &fixture,
+            )
+            .await
+            {
+                Ok(outputs) => outputs,
+                Err(e) => {
+                    println!("{}: node failed {:?}", "error".red().bold(), e);
+                    exit(-1);
+                }
+            };
+
+            for (port, data) in &outputs {
+                println!(
+                    "{} [{}] {}",
+                    "Output".green().bold(),
+                    port,
+                    cargo_zenoh_flow::utils::display_data(data)
+                );
+            }
+            println!(
+                "{} node {}: {} output(s)",
+                "Finished".green().bold(),
+                node_info.id,
+                outputs.len()
+            );
+        }
         ZFCtl::List => {
             #[cfg(feature = "local_registry")]
             match client {
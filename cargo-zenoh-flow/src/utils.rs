@@ -427,12 +427,7 @@ pub fn store_zf_metadata(metadata: &RegistryNode, target_dir: &Path) -> CZFResul
 }
 
 pub fn store_zf_descriptor(descriptor: &str, target_dir: &Path, id: &str) -> CZFResult<String> {
-    let target_descriptor = format!(
-        "{}/{}/descriptor-{}.yml",
-        target_dir.display(),
-        ZF_OUTPUT_DIRECTORY,
-        id
-    );
+    let target_descriptor = zf_descriptor_path(target_dir, id);
     let mut file = std::fs::OpenOptions::new()
         .write(true)
         .create_new(true)
@@ -440,5 +435,20 @@ pub fn store_zf_descriptor(descriptor: &str, target_dir: &Path, id: &str) -> CZF
 
     write!(file, "{descriptor}")?;
 
-    Ok(target_descriptor)
+    Ok(target_descriptor.display().to_string())
+}
+
+/// Returns the path of the descriptor of the node `id`, as stored by `cargo zenoh-flow build`.
+pub fn zf_descriptor_path(target_dir: &Path, id: &str) -> PathBuf {
+    target_dir
+        .join(ZF_OUTPUT_DIRECTORY)
+        .join(format!("descriptor-{id}.yml"))
+}
+
+/// Returns the data as text if it is valid UTF-8, as a list of bytes otherwise.
+pub fn display_data(data: &[u8]) -> String {
+    match std::str::from_utf8(data) {
+        Ok(text) => text.to_string(),
+        Err(_) => format!("{data:?}"),
+    }
 }
@@ -0,0 +1,52 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Generates, with cbindgen, the header of the C API in `$OUT_DIR/zenoh_flow.h`.
+//!
+//! The nodes are built against the header checked in `include/zenoh_flow.h`: the build does not
+//! write in the sources, it only warns when the checked-in header differs from the generated one.
+//! The checked-in header is regenerated on demand:
+//!
+//! ```text
+//! ZENOH_FLOW_C_UPDATE_HEADER=1 cargo build -p zenoh-flow-c
+//! ```
+
+use std::env;
+use std::path::PathBuf;
+
+/// The environment variable that, when set, copies the generated header in `include`.
+const UPDATE_HEADER: &str = "ZENOH_FLOW_C_UPDATE_HEADER";
+
+fn main() {
+    let crate_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let generated = PathBuf::from(env::var("OUT_DIR").unwrap()).join("zenoh_flow.h");
+    cbindgen::generate(&crate_dir)
+        .expect("Unable to generate the C bindings")
+        .write_to_file(&generated);
+
+    let header = crate_dir.join("include").join("zenoh_flow.h");
+    if env::var_os(UPDATE_HEADER).is_some() {
+        std::fs::copy(&generated, &header).expect("Unable to update include/zenoh_flow.h");
+    } else if std::fs::read(&generated).ok() != std::fs::read(&header).ok() {
+        println!(
+            "cargo:warning=include/zenoh_flow.h is out of date, regenerate it with `{}=1 cargo build -p zenoh-flow-c`",
+            UPDATE_HEADER
+        );
+    }
+
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-changed=include/zenoh_flow.h");
+    println!("cargo:rerun-if-env-changed={}", UPDATE_HEADER);
+}
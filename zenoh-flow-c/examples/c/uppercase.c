@@ -0,0 +1,50 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// An Operator, implemented in C, that forwards the text received on its input `in` in upper case
+// on its output `out`.
+//
+//   cc -shared -fPIC -I../../include uppercase.c -o libuppercase.zfc
+//
+// Its descriptor:
+//
+//   id: uppercase
+//   uri: file:///path/to/libuppercase.zfc
+//   inputs: [in]
+//   outputs: [out]
+
+#include <ctype.h>
+#include <stdlib.h>
+
+#include "zenoh_flow.h"
+
+static int32_t uppercase_on_input(void *state, const char *port, const uint8_t *data, size_t len,
+                                  uint64_t timestamp, zf_outputs *outputs) {
+  (void)state;
+  (void)port;
+
+  uint8_t *upper = malloc(len > 0 ? len : 1);
+  if (upper == NULL) {
+    return ZF_ERROR;
+  }
+  for (size_t i = 0; i < len; i++) {
+    upper[i] = (uint8_t)toupper(data[i]);
+  }
+
+  int32_t result = zf_outputs_send(outputs, "out", upper, len, timestamp);
+  free(upper);
+  return result;
+}
+
+ZF_EXPORT_OPERATOR(NULL, uppercase_on_input, NULL)
@@ -0,0 +1,51 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// An Operator, implemented in C++, that counts the text received on its input `in` and sends, on
+// its output `count`, the number of texts received so far.
+//
+// Its descriptor:
+//
+//   id: counter
+//   uri: file:///path/to/build/libcounter.zfc
+//   inputs: [in]
+//   outputs: [count]
+
+#include <cstdint>
+#include <iostream>
+#include <optional>
+#include <string>
+#include <string_view>
+
+#include "zenoh_flow.hpp"
+
+class Counter {
+public:
+  Counter(const zenoh_flow::Context &context, [[maybe_unused]] std::optional<std::string_view> configuration) {
+    std::cout << "[counter] created in < " << context.flow_name << " >" << std::endl;
+  }
+
+  void on_input([[maybe_unused]] std::string_view port, zenoh_flow::Payload data, uint64_t timestamp,
+                zenoh_flow::Outputs &outputs) {
+    auto text = zenoh_flow::get_input<std::string>(data);
+    count_ += 1;
+    std::cout << "[counter] " << count_ << ": " << text << std::endl;
+    outputs.emit<uint64_t>("count", count_, timestamp);
+  }
+
+private:
+  uint64_t count_ = 0;
+};
+
+ZF_CPP_EXPORT_OPERATOR(Counter)
@@ -0,0 +1,151 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+#ifndef ZENOH_FLOW_H
+#define ZENOH_FLOW_H
+
+// Generated by cbindgen from the sources of `zenoh-flow-c`: do not edit it manually.
+
+#include <stddef.h>
+#include <stdint.h>
+
+// The function succeeded.
+#define ZF_OK 0
+
+// The function failed. The node can return any other negative value.
+#define ZF_ERROR -1
+
+// The port does not exist.
+#define ZF_UNKNOWN_PORT -2
+
+// An argument is NULL or not valid.
+#define ZF_INVALID_ARGUMENT -3
+
+// The version of the C API: a library built against another version cannot be loaded.
+#define ZF_C_ABI_VERSION 2
+
+// The data sent by a node during a call: they are sent, in order, once the call returned.
+typedef struct zf_outputs_state zf_outputs_state;
+
+// The context in which a node is created. The strings are only valid during the call.
+typedef struct {
+  // The name of the runtime running the node.
+  const char *runtime_name;
+  // The name of the data flow.
+  const char *flow_name;
+  // The unique identifier of the instance of the data flow.
+  const char *instance_id;
+} zf_context;
+
+// Creates the state of a node from its configuration, serialized in JSON (or NULL if it has none).
+//
+// The state is then given to all the other functions of the node. Returns `ZF_OK` on success.
+typedef int32_t (*zf_new_fn)(const zf_context *context, const char *configuration, void **state);
+
+// Releases the state of a node, once it is stopped.
+typedef void (*zf_drop_fn)(void *state);
+
+// Sends the `len` bytes of `data` on the output `port`, with the `timestamp` (in the NTP64 format)
+// or, if it is 0, the current time. Returns `ZF_OK` on success.
+//
+// The data are copied: they can be released once the function returned.
+typedef int32_t (*zf_send_fn)(zf_outputs_state *state,
+                              const char *port,
+                              const uint8_t *data,
+                              size_t len,
+                              uint64_t timestamp);
+
+// The outputs of a node, only valid during the call they are given to.
+//
+// The library of a node is loaded with its symbols kept local: it calls the functions of
+// Zenoh-Flow through the pointers it is given, never by their name (see `zf_outputs_send`).
+typedef struct {
+  // Sends data on an output, called with `state`.
+  zf_send_fn send;
+  zf_outputs_state *state;
+} zf_outputs;
+
+// The table of functions of a Source, exported by its library as `zf_source` (see
+// `ZF_EXPORT_SOURCE`).
+typedef struct {
+  // Must be `ZF_C_ABI_VERSION`.
+  uint32_t abi_version;
+  zf_new_fn new_;
+  // Produces data, with `zf_outputs_send`. Returns `ZF_OK` on success.
+  int32_t (*iteration)(void *state, zf_outputs *outputs);
+  zf_drop_fn drop;
+} zf_source_t;
+
+// The table of functions of an Operator, exported by its library as `zf_operator` (see
+// `ZF_EXPORT_OPERATOR`).
+typedef struct {
+  // Must be `ZF_C_ABI_VERSION`.
+  uint32_t abi_version;
+  zf_new_fn new_;
+  // Processes the `len` bytes of `data` received on the input `port` (and timestamped with
+  // `timestamp`, in the NTP64 format), producing data with `zf_outputs_send`. Returns `ZF_OK` on
+  // success.
+  int32_t (*on_input)(void *state,
+                      const char *port,
+                      const uint8_t *data,
+                      size_t len,
+                      uint64_t timestamp,
+                      zf_outputs *outputs);
+  zf_drop_fn drop;
+} zf_operator_t;
+
+// The table of functions of a Sink, exported by its library as `zf_sink` (see `ZF_EXPORT_SINK`).
+typedef struct {
+  // Must be `ZF_C_ABI_VERSION`.
+  uint32_t abi_version;
+  zf_new_fn new_;
+  // Consumes the `len` bytes of `data` received on the input `port` (and timestamped with
+  // `timestamp`, in the NTP64 format). Returns `ZF_OK` on success.
+  int32_t (*on_input)(void *state,
+                      const char *port,
+                      const uint8_t *data,
+                      size_t len,
+                      uint64_t timestamp);
+  zf_drop_fn drop;
+} zf_sink_t;
+
+#endif // ZENOH_FLOW_H
+
+#if defined(_WIN32)
+#define ZF_VISIBILITY __declspec(dllexport)
+#else
+#define ZF_VISIBILITY __attribute__((visibility("default")))
+#endif
+
+#if defined(__cplusplus)
+#define ZF_LINKAGE extern "C"
+#else
+#define ZF_LINKAGE
+#endif
+
+// Sends the `len` bytes of `data` on the output `port`, through the `outputs` given to the node
+// (see `zf_send_fn`).
+#define zf_outputs_send(outputs, port, data, len, timestamp) \
+  ((outputs) == NULL ? ZF_INVALID_ARGUMENT \
+                     : (outputs)->send((outputs)->state, (port), (data), (len), (timestamp)))
+
+// Declares the Source, Operator or Sink implemented by the library, e.g.:
+//
+//   ZF_EXPORT_OPERATOR(my_new, my_on_input, my_drop)
+#define ZF_EXPORT_SOURCE(new_, iteration, drop) \
+  ZF_LINKAGE ZF_VISIBILITY const zf_source_t zf_source = {ZF_C_ABI_VERSION, new_, iteration, drop};
+#define ZF_EXPORT_OPERATOR(new_, on_input, drop) \
+  ZF_LINKAGE ZF_VISIBILITY const zf_operator_t zf_operator = {ZF_C_ABI_VERSION, new_, on_input, drop};
+#define ZF_EXPORT_SINK(new_, on_input, drop) \
+  ZF_LINKAGE ZF_VISIBILITY const zf_sink_t zf_sink = {ZF_C_ABI_VERSION, new_, on_input, drop};
@@ -0,0 +1,225 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// The C++ API of Zenoh-Flow: a header-only wrapper, requiring C++20, around the C API of
+// `zenoh_flow.h`.
+//
+// A node is a class constructed from the `zenoh_flow::Context` and the configuration of the node
+// (serialized in JSON, if any) and exported with `ZF_CPP_EXPORT_SOURCE`, `ZF_CPP_EXPORT_OPERATOR`
+// or `ZF_CPP_EXPORT_SINK`:
+//
+//   class Uppercase {
+//   public:
+//     Uppercase(const zenoh_flow::Context &context, std::optional<std::string_view> configuration);
+//     void on_input(std::string_view port, zenoh_flow::Payload data, uint64_t timestamp,
+//                   zenoh_flow::Outputs &outputs);
+//   };
+//
+//   ZF_CPP_EXPORT_OPERATOR(Uppercase)
+//
+// A Source implements `void iteration(zenoh_flow::Outputs &outputs)` and a Sink
+// `void on_input(std::string_view port, zenoh_flow::Payload data, uint64_t timestamp)`.
+//
+// The instance of the class is the state of the node: it is destroyed once the node is stopped. An
+// exception thrown by a node is caught and reported as an error to Zenoh-Flow.
+
+#ifndef ZENOH_FLOW_HPP
+#define ZENOH_FLOW_HPP
+
+#include <cstdint>
+#include <cstring>
+#include <exception>
+#include <optional>
+#include <span>
+#include <stdexcept>
+#include <string>
+#include <string_view>
+#include <type_traits>
+#include <vector>
+
+#include "zenoh_flow.h"
+
+namespace zenoh_flow {
+
+// The bytes of a data received by a node: they are only valid during the call.
+using Payload = std::span<const uint8_t>;
+
+// The error thrown when a function of the C API fails.
+class Error : public std::runtime_error {
+public:
+  Error(const std::string &what, int32_t code) : std::runtime_error(what), code_(code) {}
+
+  int32_t code() const noexcept { return code_; }
+
+private:
+  int32_t code_;
+};
+
+// How a `T` is converted from, and to, bytes by `get_input` and `Outputs::emit`.
+//
+// The trivially copyable types are copied as is, `std::string` and `std::vector<uint8_t>` as their
+// content. Specialize it to support other types.
+template <typename T, typename Enable = void> struct Codec;
+
+template <typename T> struct Codec<T, std::enable_if_t<std::is_trivially_copyable_v<T>>> {
+  static T decode(Payload data) {
+    if (data.size() != sizeof(T)) {
+      throw Error("The size of the data does not match the size of the type", ZF_INVALID_ARGUMENT);
+    }
+    T value;
+    std::memcpy(&value, data.data(), sizeof(T));
+    return value;
+  }
+
+  static std::vector<uint8_t> encode(const T &value) {
+    std::vector<uint8_t> bytes(sizeof(T));
+    std::memcpy(bytes.data(), &value, sizeof(T));
+    return bytes;
+  }
+};
+
+template <> struct Codec<std::string> {
+  static std::string decode(Payload data) {
+    return std::string(reinterpret_cast<const char *>(data.data()), data.size());
+  }
+
+  static std::vector<uint8_t> encode(const std::string &value) {
+    return std::vector<uint8_t>(value.begin(), value.end());
+  }
+};
+
+template <> struct Codec<std::vector<uint8_t>> {
+  static std::vector<uint8_t> decode(Payload data) {
+    return std::vector<uint8_t>(data.begin(), data.end());
+  }
+
+  static std::vector<uint8_t> encode(const std::vector<uint8_t> &value) { return value; }
+};
+
+// Converts the bytes of a data received by a node to a `T` (see `Codec`).
+template <typename T> T get_input(Payload data) { return Codec<T>::decode(data); }
+
+// The context in which a node is created.
+struct Context {
+  std::string runtime_name;
+  std::string flow_name;
+  std::string instance_id;
+
+  explicit Context(const zf_context &context)
+      : runtime_name(context.runtime_name), flow_name(context.flow_name),
+        instance_id(context.instance_id) {}
+};
+
+// The outputs of a node: the data are sent, in order, once the function of the node returned.
+class Outputs {
+public:
+  explicit Outputs(zf_outputs *outputs) noexcept : outputs_(outputs) {}
+
+  Outputs(const Outputs &) = delete;
+  Outputs &operator=(const Outputs &) = delete;
+
+  // Sends the bytes of `data` on the output `port`, with the `timestamp` (in the NTP64 format) or,
+  // if it is 0, the current time.
+  void send(std::string_view port, Payload data, uint64_t timestamp = 0) {
+    std::string port_(port);
+    int32_t result =
+        outputs_->send(outputs_->state, port_.c_str(), data.data(), data.size(), timestamp);
+    if (result != ZF_OK) {
+      throw Error("Unable to send on the output < " + port_ + " >", result);
+    }
+  }
+
+  // Converts `value` to bytes (see `Codec`) and sends them on the output `port`.
+  template <typename T> void emit(std::string_view port, const T &value, uint64_t timestamp = 0) {
+    std::vector<uint8_t> bytes = Codec<T>::encode(value);
+    send(port, Payload(bytes.data(), bytes.size()), timestamp);
+  }
+
+private:
+  zf_outputs *outputs_;
+};
+
+namespace detail {
+
+// Runs `function`, converting the exceptions it throws into an error code.
+template <typename F> int32_t guard(F &&function) noexcept {
+  try {
+    function();
+    return ZF_OK;
+  } catch (const Error &error) {
+    return error.code();
+  } catch (...) {
+    return ZF_ERROR;
+  }
+}
+
+template <typename Node>
+int32_t new_node(const zf_context *context, const char *configuration, void **state) noexcept {
+  return guard([&]() {
+    std::optional<std::string_view> configuration_;
+    if (configuration != nullptr) {
+      configuration_ = std::string_view(configuration);
+    }
+    *state = new Node(Context(*context), configuration_);
+  });
+}
+
+template <typename Node> void drop_node(void *state) noexcept { delete static_cast<Node *>(state); }
+
+template <typename Node> int32_t iteration(void *state, zf_outputs *outputs) noexcept {
+  return guard([&]() {
+    Outputs outputs_(outputs);
+    static_cast<Node *>(state)->iteration(outputs_);
+  });
+}
+
+template <typename Node>
+int32_t operator_on_input(void *state, const char *port, const uint8_t *data, size_t len,
+                          uint64_t timestamp, zf_outputs *outputs) noexcept {
+  return guard([&]() {
+    Outputs outputs_(outputs);
+    static_cast<Node *>(state)->on_input(std::string_view(port), Payload(data, len), timestamp,
+                                         outputs_);
+  });
+}
+
+template <typename Node>
+int32_t sink_on_input(void *state, const char *port, const uint8_t *data, size_t len,
+                      uint64_t timestamp) noexcept {
+  return guard([&]() {
+    static_cast<Node *>(state)->on_input(std::string_view(port), Payload(data, len), timestamp);
+  });
+}
+
+} // namespace detail
+
+} // namespace zenoh_flow
+
+// Declares the class `Node` as the Source implemented by the library.
+#define ZF_CPP_EXPORT_SOURCE(Node)                                                                 \
+  ZF_EXPORT_SOURCE(zenoh_flow::detail::new_node<Node>, zenoh_flow::detail::iteration<Node>,        \
+                   zenoh_flow::detail::drop_node<Node>)
+
+// Declares the class `Node` as the Operator implemented by the library.
+#define ZF_CPP_EXPORT_OPERATOR(Node)                                                               \
+  ZF_EXPORT_OPERATOR(zenoh_flow::detail::new_node<Node>,                                           \
+                     zenoh_flow::detail::operator_on_input<Node>,                                  \
+                     zenoh_flow::detail::drop_node<Node>)
+
+// Declares the class `Node` as the Sink implemented by the library.
+#define ZF_CPP_EXPORT_SINK(Node)                                                                   \
+  ZF_EXPORT_SINK(zenoh_flow::detail::new_node<Node>, zenoh_flow::detail::sink_on_input<Node>,      \
+                 zenoh_flow::detail::drop_node<Node>)
+
+#endif // ZENOH_FLOW_HPP
@@ -0,0 +1,50 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The C API of Zenoh-Flow: the Sources, Operators and Sinks implemented in C (or in any language
+//! that can export a C symbol) are shared libraries that export a table of functions, described in
+//! `include/zenoh_flow.h`.
+//!
+//! This crate is the shared library the daemons load, through the extension `etc/c.zfext`, for the
+//! nodes whose library has the `.zfc` extension. It exports a Source, an Operator and a Sink that
+//! load the C library and forward the data to, and from, its functions:
+//! - a Source is iterated: it sends data through its [zf_outputs] and returns,
+//! - an Operator, or a Sink, is called with each data received on one of its inputs.
+//!
+//! The data sent by a node are only sent once its function returned. The functions of a node are
+//! never called concurrently, the node does not have to be thread-safe.
+//!
+//! To run a C node:
+//! 1. build it, against `include/zenoh_flow.h`, as a shared library with the `.zfc` extension (see
+//!    `examples/c/uppercase.c`) or, in C++, against the header-only wrapper
+//!    `include/zenoh_flow.hpp` (see `examples/cpp`),
+//! 2. install this library as `/usr/lib/libzenoh_flow_c.so` and `etc/c.zfext` in the extensions
+//!    directory of the daemons,
+//! 3. set the `uri` of the node, in its descriptor, to `file:///path/to/libnode.zfc`.
+
+#![allow(non_camel_case_types)]
+
+mod node;
+mod operator;
+mod sink;
+mod source;
+mod types;
+
+pub use operator::COperator;
+pub use sink::CSink;
+pub use source::CSource;
+pub use types::*;
+
+/// The version of the C API: a library built against another version cannot be loaded.
+pub const ZF_C_ABI_VERSION: u32 = 2;
@@ -0,0 +1,236 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::types::{zf_context, zf_drop_fn, zf_new_fn, zf_operator_t, zf_sink_t, zf_source_t};
+use crate::{ZF_C_ABI_VERSION, ZF_OK};
+use futures::Future;
+use libloading::Library;
+use std::ffi::{c_void, CString};
+use std::pin::Pin;
+use zenoh_flow::prelude::{zferror, Configuration, Context, ErrorKind, InputRaw, PortId};
+use zenoh_flow::types::LinkMessage;
+use zenoh_flow::{bail, Result};
+
+/// The key, set by the extension `etc/c.zfext`, of the path of the C library of the node.
+const KEY_LIBRARY: &str = "c-library";
+
+/// The key, set by the extension, of the configuration of the node.
+const KEY_CONFIGURATION: &str = "configuration";
+
+/// The table of functions exported by the library of a node.
+pub(crate) trait VTable {
+    /// The symbol under which the table is exported.
+    const SYMBOL: &'static [u8];
+
+    fn abi_version(&self) -> u32;
+    fn new_fn(&self) -> Option<zf_new_fn>;
+    fn drop_fn(&self) -> Option<zf_drop_fn>;
+}
+
+macro_rules! impl_vtable {
+    ($vtable: ty, $symbol: literal) => {
+        impl VTable for $vtable {
+            const SYMBOL: &'static [u8] = $symbol;
+
+            fn abi_version(&self) -> u32 {
+                self.abi_version
+            }
+
+            fn new_fn(&self) -> Option<zf_new_fn> {
+                self.new_
+            }
+
+            fn drop_fn(&self) -> Option<zf_drop_fn> {
+                self.drop
+            }
+        }
+    };
+}
+
+impl_vtable!(zf_source_t, b"zf_source\0");
+impl_vtable!(zf_operator_t, b"zf_operator\0");
+impl_vtable!(zf_sink_t, b"zf_sink\0");
+
+/// A node implemented in C: its library, its table of functions and its state.
+///
+/// The state is released, with the `drop` function of the node, before the library is unloaded.
+pub(crate) struct CNode<T: VTable + 'static> {
+    state: *mut c_void,
+    vtable: *const T,
+    _library: Library,
+}
+
+// SAFETY: the state is only accessed through the functions of the node, which are never called
+// concurrently (the wrappers hold a lock while calling them).
+unsafe impl<T: VTable> Send for CNode<T> {}
+unsafe impl<T: VTable> Sync for CNode<T> {}
+
+impl<T: VTable> CNode<T> {
+    /// Loads the C library designated by the `configuration` and creates the state of the node.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the library could not be loaded, if it does not export the table of
+    /// functions of the node, if it was built against another version of the C API or if the node
+    /// could not be created.
+    pub(crate) fn try_new(context: &Context, configuration: Option<Configuration>) -> Result<Self> {
+        let configuration = configuration.ok_or_else(|| {
+            zferror!(
+                ErrorKind::MissingConfiguration,
+                "The C nodes must be loaded through the `c` extension"
+            )
+        })?;
+        let path = configuration
+            .get(KEY_LIBRARY)
+            .and_then(|path| path.as_str())
+            .ok_or_else(|| {
+                zferror!(
+                    ErrorKind::MissingConfiguration,
+                    "Missing < {} > in the configuration of the C node",
+                    KEY_LIBRARY
+                )
+            })?;
+        let node_configuration = configuration
+            .get(KEY_CONFIGURATION)
+            .map(|configuration| to_c_string(&configuration.to_string()))
+            .transpose()?;
+
+        // SAFETY: loading a library runs its initialisation routines, which we have to trust.
+        let library = unsafe { Library::new(path) }.map_err(|e| {
+            zferror!(
+                ErrorKind::LoadingError,
+                "Unable to load the C library < {} >: {}",
+                path,
+                e
+            )
+        })?;
+        // SAFETY: the symbol is a `T`, as declared by `include/zenoh_flow.h`.
+        let vtable = unsafe { library.get::<*const T>(T::SYMBOL) }
+            .map(|symbol| *symbol)
+            .map_err(|e| {
+                zferror!(
+                    ErrorKind::LoadingError,
+                    "The C library < {} > does not export < {} >: {}",
+                    path,
+                    String::from_utf8_lossy(&T::SYMBOL[..T::SYMBOL.len() - 1]),
+                    e
+                )
+            })?;
+
+        // SAFETY: the table lives as long as the library.
+        let table = unsafe { &*vtable };
+        if table.abi_version() != ZF_C_ABI_VERSION {
+            bail!(
+                ErrorKind::LoadingError,
+                "The C library < {} > was built against the version {} of the C API, expected {}",
+                path,
+                table.abi_version(),
+                ZF_C_ABI_VERSION
+            );
+        }
+
+        let mut state = std::ptr::null_mut();
+        if let Some(new) = table.new_fn() {
+            let runtime_name = to_c_string(context.get_runtime_name())?;
+            let flow_name = to_c_string(context.get_flow_name())?;
+            let instance_id = to_c_string(&context.get_instance_id().to_string())?;
+            let c_context = zf_context {
+                runtime_name: runtime_name.as_ptr(),
+                flow_name: flow_name.as_ptr(),
+                instance_id: instance_id.as_ptr(),
+            };
+            let configuration_ptr = node_configuration
+                .as_ref()
+                .map_or(std::ptr::null(), |configuration| configuration.as_ptr());
+
+            // SAFETY: the strings outlive the call.
+            let result = unsafe { new(&c_context, configuration_ptr, &mut state) };
+            if result != ZF_OK {
+                bail!(
+                    ErrorKind::LoadingError,
+                    "The C node of < {} > could not be created: {}",
+                    path,
+                    result
+                );
+            }
+        }
+
+        Ok(Self {
+            state,
+            vtable,
+            _library: library,
+        })
+    }
+
+    /// Returns the table of functions of the node.
+    pub(crate) fn vtable(&self) -> &T {
+        // SAFETY: the table lives as long as the library, which lives as long as `self`.
+        unsafe { &*self.vtable }
+    }
+
+    /// Returns the state of the node, to give to its functions.
+    pub(crate) fn state(&self) -> *mut c_void {
+        self.state
+    }
+}
+
+impl<T: VTable> Drop for CNode<T> {
+    fn drop(&mut self) {
+        if let Some(drop) = self.vtable().drop_fn() {
+            // SAFETY: the state was created by the node and is not used afterwards.
+            unsafe { drop(self.state) };
+        }
+    }
+}
+
+/// Converts `value` to a NUL-terminated string.
+///
+/// # Errors
+///
+/// An error is returned if `value` contains a NUL byte.
+pub(crate) fn to_c_string(value: &str) -> Result<CString> {
+    CString::new(value).map_err(|e| {
+        zferror!(
+            ErrorKind::InvalidData,
+            "< {} > cannot be given to a C node: {}",
+            value,
+            e
+        )
+        .into()
+    })
+}
+
+/// The reception of a message on an input, polled along with the other inputs of the node.
+pub(crate) type InputFut =
+    Pin<Box<dyn Future<Output = (PortId, Result<LinkMessage>)> + Send + Sync>>;
+
+/// Returns the future receiving the next message on `input`.
+pub(crate) fn wait_input(id: PortId, input: &InputRaw) -> InputFut {
+    let input = input.clone();
+    Box::pin(async move { (id, input.recv().await) })
+}
+
+/// Returns an error if the `result` of a function of a node is not `ZF_OK`.
+pub(crate) fn check(function: &str, result: i32) -> Result<()> {
+    if result != ZF_OK {
+        bail!(
+            ErrorKind::GenericError,
+            "The C function `{}` failed: {}",
+            function,
+            result
+        );
+    }
+
+    Ok(())
+}
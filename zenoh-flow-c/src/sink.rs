@@ -0,0 +1,117 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::node::{check, to_c_string, wait_input, CNode, InputFut};
+use crate::types::zf_sink_t;
+use async_lock::Mutex;
+use async_trait::async_trait;
+use futures::future::select_all;
+use std::collections::HashMap;
+use std::mem;
+use zenoh_flow::prelude::*;
+use zenoh_flow::types::LinkMessage;
+
+/// The Sink wrapping a Sink implemented in C.
+///
+/// Its `on_input` function is called with each data received on one of its inputs.
+#[export_sink]
+pub struct CSink {
+    inputs: HashMap<PortId, InputRaw>,
+    futs: Mutex<Vec<InputFut>>,
+    node: Mutex<CNode<zf_sink_t>>,
+}
+
+#[async_trait]
+impl Sink for CSink {
+    async fn new(
+        context: Context,
+        configuration: Option<Configuration>,
+        mut inputs: Inputs,
+    ) -> Result<Self> {
+        let ports: Vec<PortId> = inputs.keys().cloned().collect();
+        let inputs: HashMap<PortId, InputRaw> = ports
+            .into_iter()
+            .filter_map(|id| inputs.take(&id).map(|input| (id, input.raw())))
+            .collect();
+        // The inputs are polled with `select_all`, which panics if there are none.
+        if inputs.is_empty() {
+            bail!(
+                ErrorKind::ConfigurationError,
+                "A C Sink must have at least one input"
+            );
+        }
+
+        let node = CNode::try_new(&context, configuration)?;
+
+        let futs = inputs
+            .iter()
+            .map(|(id, input)| wait_input(id.clone(), input))
+            .collect();
+
+        Ok(Self {
+            inputs,
+            futs: Mutex::new(futs),
+            node: Mutex::new(node),
+        })
+    }
+}
+
+#[async_trait]
+impl Node for CSink {
+    async fn iteration(&self) -> Result<()> {
+        // Getting the list of futures to poll in a temporary variable (that `select_all` can take
+        // ownership of)
+        let mut futs = self.futs.lock().await;
+        let tmp = mem::take(&mut *futs);
+
+        let ((id, result), _index, mut remaining) = select_all(tmp).await;
+
+        // Add back the input that got polled
+        let input = self.inputs.get(&id).ok_or_else(|| {
+            zferror!(
+                ErrorKind::RecvError,
+                "Unable to find input < {} > of the C Sink",
+                id
+            )
+        })?;
+        remaining.push(wait_input(id.clone(), input));
+        *futs = remaining;
+        drop(futs);
+
+        let data_message = match result? {
+            LinkMessage::Data(data_message) => data_message,
+            _ => return Ok(()), // Not the right message, ignore it.
+        };
+        let data = data_message.try_as_bytes()?;
+        let timestamp = data_message.get_timestamp().get_time().as_u64();
+        let port = to_c_string(&id)?;
+
+        let node = self.node.lock().await;
+        if let Some(on_input) = node.vtable().on_input {
+            // SAFETY: the state, the port and the data outlive the call.
+            let result = unsafe {
+                on_input(
+                    node.state(),
+                    port.as_ptr(),
+                    data.as_ptr(),
+                    data.len(),
+                    timestamp,
+                )
+            };
+            check("on_input", result)?;
+        }
+
+        Ok(())
+    }
+}
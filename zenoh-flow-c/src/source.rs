@@ -0,0 +1,76 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::node::{check, CNode};
+use crate::types::{zf_outputs, zf_outputs_state, zf_source_t};
+use async_lock::Mutex;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use zenoh_flow::prelude::*;
+
+/// The Source wrapping a Source implemented in C.
+///
+/// Its `iteration` function is called at each iteration: it should not block for long, the
+/// executor of the daemon being blocked meanwhile.
+#[export_source]
+pub struct CSource {
+    outputs: HashMap<PortId, OutputRaw>,
+    node: Mutex<CNode<zf_source_t>>,
+}
+
+#[async_trait]
+impl Source for CSource {
+    async fn new(
+        context: Context,
+        configuration: Option<Configuration>,
+        mut outputs: Outputs,
+    ) -> Result<Self> {
+        let node = CNode::try_new(&context, configuration)?;
+        let ports: Vec<PortId> = outputs.keys().cloned().collect();
+        let outputs = ports
+            .into_iter()
+            .filter_map(|id| outputs.take(&id).map(|output| (id, output.raw())))
+            .collect();
+
+        Ok(Self {
+            outputs,
+            node: Mutex::new(node),
+        })
+    }
+}
+
+#[async_trait]
+impl Node for CSource {
+    async fn iteration(&self) -> Result<()> {
+        let mut state = zf_outputs_state::new(self.outputs.keys().cloned().collect());
+        {
+            let node = self.node.lock().await;
+            if let Some(iteration) = node.vtable().iteration {
+                // The outputs point at `state`: they are not kept across an `await`.
+                let mut outputs = zf_outputs::new(&mut state);
+                // SAFETY: the state and the outputs outlive the call.
+                let result = unsafe { iteration(node.state(), &mut outputs) };
+                check("iteration", result)?;
+            }
+        }
+
+        for (port, data, timestamp) in state.pending {
+            if let Some(output) = self.outputs.get(&port) {
+                output.send(data, timestamp).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::types::{zf_outputs, zf_outputs_state, ZF_INVALID_ARGUMENT, ZF_OK, ZF_UNKNOWN_PORT};
+use std::ffi::CString;
+use std::ptr;
+
+#[test]
+fn test_outputs_send() {
+    let mut state = zf_outputs_state::new(vec!["out".into()]);
+    let outputs = zf_outputs::new(&mut state);
+    let port = CString::new("out").unwrap();
+    let data = [1u8, 2, 3];
+
+    let result =
+        unsafe { (outputs.send)(outputs.state, port.as_ptr(), data.as_ptr(), data.len(), 42) };
+    assert_eq!(result, ZF_OK);
+
+    let result = unsafe { (outputs.send)(outputs.state, port.as_ptr(), ptr::null(), 0, 0) };
+    assert_eq!(result, ZF_OK);
+
+    assert_eq!(
+        state.pending,
+        vec![
+            ("out".into(), vec![1, 2, 3], Some(42)),
+            ("out".into(), vec![], None)
+        ]
+    );
+}
+
+#[test]
+fn test_outputs_send_unknown_port() {
+    let mut state = zf_outputs_state::new(vec!["out".into()]);
+    let outputs = zf_outputs::new(&mut state);
+    let port = CString::new("in").unwrap();
+    let data = [1u8];
+
+    let result =
+        unsafe { (outputs.send)(outputs.state, port.as_ptr(), data.as_ptr(), data.len(), 0) };
+    assert_eq!(result, ZF_UNKNOWN_PORT);
+    assert!(state.pending.is_empty());
+}
+
+#[test]
+fn test_outputs_send_invalid_arguments() {
+    let mut state = zf_outputs_state::new(vec!["out".into()]);
+    let outputs = zf_outputs::new(&mut state);
+    let port = CString::new("out").unwrap();
+    let data = [1u8];
+
+    let result = unsafe { (outputs.send)(ptr::null_mut(), port.as_ptr(), data.as_ptr(), 1, 0) };
+    assert_eq!(result, ZF_INVALID_ARGUMENT);
+
+    let result = unsafe { (outputs.send)(outputs.state, ptr::null(), data.as_ptr(), 1, 0) };
+    assert_eq!(result, ZF_INVALID_ARGUMENT);
+
+    let result = unsafe { (outputs.send)(outputs.state, port.as_ptr(), ptr::null(), 1, 0) };
+    assert_eq!(result, ZF_INVALID_ARGUMENT);
+
+    assert!(state.pending.is_empty());
+}
@@ -0,0 +1,198 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use std::ffi::{c_void, CStr};
+use std::os::raw::c_char;
+use zenoh_flow::prelude::PortId;
+
+/// The function succeeded.
+pub const ZF_OK: i32 = 0;
+/// The function failed. The node can return any other negative value.
+pub const ZF_ERROR: i32 = -1;
+/// The port does not exist.
+pub const ZF_UNKNOWN_PORT: i32 = -2;
+/// An argument is NULL or not valid.
+pub const ZF_INVALID_ARGUMENT: i32 = -3;
+
+/// The context in which a node is created. The strings are only valid during the call.
+#[repr(C)]
+pub struct zf_context {
+    /// The name of the runtime running the node.
+    pub runtime_name: *const c_char,
+    /// The name of the data flow.
+    pub flow_name: *const c_char,
+    /// The unique identifier of the instance of the data flow.
+    pub instance_id: *const c_char,
+}
+
+/// Creates the state of a node from its configuration, serialized in JSON (or NULL if it has none).
+///
+/// The state is then given to all the other functions of the node. Returns `ZF_OK` on success.
+pub type zf_new_fn = unsafe extern "C" fn(
+    context: *const zf_context,
+    configuration: *const c_char,
+    state: *mut *mut c_void,
+) -> i32;
+
+/// Releases the state of a node, once it is stopped.
+pub type zf_drop_fn = unsafe extern "C" fn(state: *mut c_void);
+
+/// The table of functions of a Source, exported by its library as `zf_source` (see
+/// `ZF_EXPORT_SOURCE`).
+#[repr(C)]
+pub struct zf_source_t {
+    /// Must be `ZF_C_ABI_VERSION`.
+    pub abi_version: u32,
+    pub new_: Option<zf_new_fn>,
+    /// Produces data, with `zf_outputs_send`. Returns `ZF_OK` on success.
+    pub iteration:
+        Option<unsafe extern "C" fn(state: *mut c_void, outputs: *mut zf_outputs) -> i32>,
+    pub drop: Option<zf_drop_fn>,
+}
+
+/// The table of functions of an Operator, exported by its library as `zf_operator` (see
+/// `ZF_EXPORT_OPERATOR`).
+#[repr(C)]
+pub struct zf_operator_t {
+    /// Must be `ZF_C_ABI_VERSION`.
+    pub abi_version: u32,
+    pub new_: Option<zf_new_fn>,
+    /// Processes the `len` bytes of `data` received on the input `port` (and timestamped with
+    /// `timestamp`, in the NTP64 format), producing data with `zf_outputs_send`. Returns `ZF_OK` on
+    /// success.
+    pub on_input: Option<
+        unsafe extern "C" fn(
+            state: *mut c_void,
+            port: *const c_char,
+            data: *const u8,
+            len: usize,
+            timestamp: u64,
+            outputs: *mut zf_outputs,
+        ) -> i32,
+    >,
+    pub drop: Option<zf_drop_fn>,
+}
+
+/// The table of functions of a Sink, exported by its library as `zf_sink` (see `ZF_EXPORT_SINK`).
+#[repr(C)]
+pub struct zf_sink_t {
+    /// Must be `ZF_C_ABI_VERSION`.
+    pub abi_version: u32,
+    pub new_: Option<zf_new_fn>,
+    /// Consumes the `len` bytes of `data` received on the input `port` (and timestamped with
+    /// `timestamp`, in the NTP64 format). Returns `ZF_OK` on success.
+    pub on_input: Option<
+        unsafe extern "C" fn(
+            state: *mut c_void,
+            port: *const c_char,
+            data: *const u8,
+            len: usize,
+            timestamp: u64,
+        ) -> i32,
+    >,
+    pub drop: Option<zf_drop_fn>,
+}
+
+/// The data sent by a node during a call: they are sent, in order, once the call returned.
+pub struct zf_outputs_state {
+    pub(crate) ports: Vec<PortId>,
+    pub(crate) pending: Vec<(PortId, Vec<u8>, Option<u64>)>,
+}
+
+impl zf_outputs_state {
+    pub(crate) fn new(ports: Vec<PortId>) -> Self {
+        Self {
+            ports,
+            pending: Vec::new(),
+        }
+    }
+}
+
+/// Sends the `len` bytes of `data` on the output `port`, with the `timestamp` (in the NTP64 format)
+/// or, if it is 0, the current time. Returns `ZF_OK` on success.
+///
+/// The data are copied: they can be released once the function returned.
+pub type zf_send_fn = unsafe extern "C" fn(
+    state: *mut zf_outputs_state,
+    port: *const c_char,
+    data: *const u8,
+    len: usize,
+    timestamp: u64,
+) -> i32;
+
+/// The outputs of a node, only valid during the call they are given to.
+///
+/// The library of a node is loaded with its symbols kept local: it calls the functions of
+/// Zenoh-Flow through the pointers it is given, never by their name (see `zf_outputs_send`).
+#[repr(C)]
+pub struct zf_outputs {
+    /// Sends data on an output, called with `state`.
+    pub send: zf_send_fn,
+    pub state: *mut zf_outputs_state,
+}
+
+impl zf_outputs {
+    pub(crate) fn new(state: &mut zf_outputs_state) -> Self {
+        Self {
+            send: outputs_send,
+            state,
+        }
+    }
+}
+
+/// The `send` function of the outputs.
+///
+/// # Safety
+///
+/// `state` must be the pointer given to the node, `port` a NUL-terminated string and `data` must
+/// point at `len` bytes (it can be NULL if `len` is 0).
+pub(crate) unsafe extern "C" fn outputs_send(
+    state: *mut zf_outputs_state,
+    port: *const c_char,
+    data: *const u8,
+    len: usize,
+    timestamp: u64,
+) -> i32 {
+    if state.is_null() || port.is_null() || (data.is_null() && len > 0) {
+        return ZF_INVALID_ARGUMENT;
+    }
+
+    let outputs = &mut *state;
+    let port = match CStr::from_ptr(port).to_str() {
+        Ok(port) => port,
+        Err(_) => return ZF_INVALID_ARGUMENT,
+    };
+    let port = match outputs.ports.iter().find(|id| id.as_ref() == port) {
+        Some(port) => port.clone(),
+        None => return ZF_UNKNOWN_PORT,
+    };
+
+    let data = if len == 0 {
+        Vec::new()
+    } else {
+        std::slice::from_raw_parts(data, len).to_vec()
+    };
+    let timestamp = if timestamp == 0 {
+        None
+    } else {
+        Some(timestamp)
+    };
+    outputs.pending.push((port, data, timestamp));
+
+    ZF_OK
+}
+
+#[cfg(test)]
+#[path = "./tests/types-tests.rs"]
+mod tests;
@@ -0,0 +1,166 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Loads the C Operator `examples/c/uppercase.c` through the `Loader`, as a daemon does: with the
+//! extension `c`, its library and this one are opened with their symbols kept local.
+
+#![cfg(target_family = "unix")]
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+use tempdir::TempDir;
+
+use zenoh_flow::model::descriptor::DataFlowDescriptor;
+use zenoh_flow::runtime::dataflow::loader::{ExtensibleImplementation, LoaderConfig};
+use zenoh_flow::runtime::Runtime;
+
+/// Returns the path of this library, built by Cargo next to the directory of the tests.
+fn zenoh_flow_c_library() -> PathBuf {
+    let mut path = std::env::current_exe().unwrap();
+    path.pop(); // the name of the test
+    path.pop(); // `deps`
+    path.push(format!(
+        "{}zenoh_flow_c{}",
+        std::env::consts::DLL_PREFIX,
+        std::env::consts::DLL_SUFFIX
+    ));
+    assert!(path.exists(), "{} was not built", path.display());
+    path
+}
+
+/// Builds `examples/c/uppercase.c` in `directory` and returns the path of its library.
+fn build_uppercase(directory: &Path) -> PathBuf {
+    let manifest = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let library = directory.join("libuppercase.zfc");
+    let status = Command::new(std::env::var("CC").unwrap_or_else(|_| "cc".to_string()))
+        .arg("-shared")
+        .arg("-fPIC")
+        .arg("-I")
+        .arg(manifest.join("include"))
+        .arg(manifest.join("examples/c/uppercase.c"))
+        .arg("-o")
+        .arg(&library)
+        .status()
+        .expect("A C compiler is needed to build `examples/c/uppercase.c`");
+    assert!(status.success());
+    library
+}
+
+fn loader_config() -> LoaderConfig {
+    let library = zenoh_flow_c_library();
+    let extension: ExtensibleImplementation = serde_yaml::from_str(&format!(
+        r#"
+name: c
+file_extension: zfc
+source_lib: {library}
+sink_lib: {library}
+operator_lib: {library}
+config_lib_key: c-library
+"#,
+        library = library.display()
+    ))
+    .unwrap();
+
+    let mut config = LoaderConfig::new();
+    config.try_add_extension(extension).unwrap();
+    config
+}
+
+async fn c_operator() {
+    let directory = TempDir::new("zenoh-flow-c").unwrap();
+    let library = build_uppercase(directory.path());
+
+    let operator = directory.path().join("uppercase.yml");
+    std::fs::write(
+        &operator,
+        format!(
+            r#"
+id: uppercase
+uri: file://{}
+inputs: [in]
+outputs: [out]
+"#,
+            library.display()
+        ),
+    )
+    .unwrap();
+
+    let descriptor = DataFlowDescriptor::from_yaml(&format!(
+        r#"
+flow: c-operator
+
+sources:
+  - id: host-source
+    descriptor: builtin://host
+    configuration:
+      ports: [text]
+
+operators:
+  - id: uppercase
+    descriptor: file://{}
+
+sinks:
+  - id: host-sink
+    descriptor: builtin://host
+    configuration:
+      ports: [text]
+
+links:
+  - from:
+      node: host-source
+      output: text
+    to:
+      node: uppercase
+      input: in
+  - from:
+      node: uppercase
+      output: out
+    to:
+      node: host-sink
+      input: text
+"#,
+        operator.display()
+    ))
+    .unwrap();
+
+    let mut runtime = Runtime::builder()
+        .descriptor(descriptor)
+        .loader_config(loader_config())
+        .build()
+        .await
+        .unwrap();
+
+    let input = runtime.take_input("host-source").unwrap();
+    let output = runtime.take_output("host-sink").unwrap();
+
+    runtime.start().await.unwrap();
+
+    input.send("text", b"zenoh-flow".to_vec()).await.unwrap();
+    let (port, data) = async_std::future::timeout(Duration::from_secs(5), output.recv())
+        .await
+        .expect("No data received from the C Operator")
+        .unwrap();
+    assert_eq!(port.as_ref(), "text");
+    assert_eq!(data, b"ZENOH-FLOW".to_vec());
+
+    runtime.stop().await.unwrap();
+}
+
+#[test]
+fn run_c_operator() {
+    let _ = env_logger::try_init();
+
+    async_std::task::block_on(c_operator())
+}
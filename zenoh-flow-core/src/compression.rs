@@ -0,0 +1,70 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::message::DecodeError;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use lz4_flex::block::DecompressError;
+
+/// The tag, in place of the variant of a [`LinkMessage`](crate::LinkMessage), announcing that the
+/// message is compressed: it is followed by the LZ4 block of the encoded message, prefixed with its
+/// size (a little-endian `u32`).
+///
+/// No message starts with it, the connectors of a link that compresses its messages can thus send
+/// the small ones as they are. [`LinkMessage::decode`](crate::LinkMessage::decode) decompresses
+/// the messages that start with it.
+pub const COMPRESSED_TAG: u32 = u32::MAX;
+
+/// The largest ratio between the size of a LZ4 block and the size it expands to.
+const MAX_EXPANSION: usize = 255;
+
+/// Decompresses the LZ4 block, prefixed with its size, that follows [COMPRESSED_TAG] in a
+/// compressed message.
+///
+/// # Errors
+///
+/// An error is returned if the block ends early, or if it does not expand to the announced size.
+pub fn decompress(message: &[u8]) -> Result<Vec<u8>, DecodeError> {
+    if message.len() < 4 {
+        return Err(DecodeError::UnexpectedEnd);
+    }
+    let (size, block) = message.split_at(4);
+    let mut bytes = [0u8; 4];
+    bytes.copy_from_slice(size);
+    let size =
+        usize::try_from(u32::from_le_bytes(bytes)).map_err(|_| DecodeError::InvalidCompression)?;
+    // The size is announced by the sender: a corrupted one must not allocate more than the block
+    // can expand to, each of its bytes expanding to at most 255 bytes.
+    if size > block.len().saturating_mul(MAX_EXPANSION) {
+        return Err(DecodeError::InvalidCompression);
+    }
+
+    let mut output = vec![0u8; size];
+    let decompressed =
+        lz4_flex::block::decompress_into(block, &mut output).map_err(|e| match e {
+            DecompressError::LiteralOutOfBounds | DecompressError::ExpectedAnotherByte => {
+                DecodeError::UnexpectedEnd
+            }
+            _ => DecodeError::InvalidCompression,
+        })?;
+    if decompressed != size {
+        return Err(DecodeError::InvalidCompression);
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+#[path = "./tests/compression.rs"]
+mod tests;
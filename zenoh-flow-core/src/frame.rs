@@ -0,0 +1,138 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::message::DecodeError;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// The number of bytes of the sequence number that prefixes the messages published by a connector.
+pub const SEQUENCE_SIZE: usize = core::mem::size_of::<u64>();
+
+/// The number of bytes that prefix a fragment: the sequence number of the message, the index of the
+/// fragment and the number of fragments of the message.
+pub const FRAGMENT_HEADER_SIZE: usize = SEQUENCE_SIZE + 2 * core::mem::size_of::<u32>();
+
+/// A fragment of a message, published by a connector whose link sets a `fragment_size`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fragment<'a> {
+    /// The sequence number of the message.
+    pub sequence: u64,
+    /// The index of the fragment, from 0.
+    pub index: u32,
+    /// The number of fragments of the message.
+    pub count: u32,
+    /// The bytes of the encoded message carried by this fragment.
+    pub chunk: &'a [u8],
+}
+
+/// Prefixes the encoded `message` with its `sequence` number, as published by a connector.
+///
+/// The messages are numbered, per publisher, from 0: the receiving connector reports the numbers it
+/// did not receive as lost messages.
+pub fn frame(sequence: u64, message: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(SEQUENCE_SIZE + message.len());
+    frame.extend_from_slice(&sequence.to_le_bytes());
+    frame.extend_from_slice(message);
+    frame
+}
+
+/// Splits a `frame` published by a connector into its sequence number and its encoded message.
+///
+/// # Errors
+///
+/// An error is returned if the frame is too short to contain a sequence number.
+pub fn unframe(frame: &[u8]) -> Result<(u64, &[u8]), DecodeError> {
+    if frame.len() < SEQUENCE_SIZE {
+        return Err(DecodeError::UnexpectedEnd);
+    }
+
+    let (sequence, message) = frame.split_at(SEQUENCE_SIZE);
+    let mut bytes = [0u8; SEQUENCE_SIZE];
+    bytes.copy_from_slice(sequence);
+    Ok((u64::from_le_bytes(bytes), message))
+}
+
+/// Splits the encoded `message` numbered `sequence` into fragments of at most `fragment_size` bytes,
+/// header included. A message always has at least one fragment.
+///
+/// # Errors
+///
+/// An error is returned if `fragment_size` cannot hold the header and at least one byte, or if the
+/// message needs more than `u32::MAX` fragments.
+pub fn fragments(
+    sequence: u64,
+    message: &[u8],
+    fragment_size: usize,
+) -> Result<Vec<Vec<u8>>, DecodeError> {
+    if fragment_size <= FRAGMENT_HEADER_SIZE {
+        return Err(DecodeError::InvalidFragment);
+    }
+
+    let chunks = if message.is_empty() {
+        alloc::vec![message]
+    } else {
+        message
+            .chunks(fragment_size - FRAGMENT_HEADER_SIZE)
+            .collect::<Vec<_>>()
+    };
+    let count = u32::try_from(chunks.len()).map_err(|_| DecodeError::InvalidFragment)?;
+
+    let mut fragments = Vec::with_capacity(chunks.len());
+    for (index, chunk) in (0..count).zip(chunks) {
+        let mut fragment = Vec::with_capacity(FRAGMENT_HEADER_SIZE + chunk.len());
+        fragment.extend_from_slice(&sequence.to_le_bytes());
+        fragment.extend_from_slice(&index.to_le_bytes());
+        fragment.extend_from_slice(&count.to_le_bytes());
+        fragment.extend_from_slice(chunk);
+        fragments.push(fragment);
+    }
+
+    Ok(fragments)
+}
+
+/// Parses a `fragment` published by a connector whose link sets a `fragment_size`.
+///
+/// # Errors
+///
+/// An error is returned if the fragment is too short to contain its header or if its index is not
+/// lower than the number of fragments.
+pub fn unfragment(fragment: &[u8]) -> Result<Fragment<'_>, DecodeError> {
+    let (sequence, rest) = unframe(fragment)?;
+    if rest.len() < FRAGMENT_HEADER_SIZE - SEQUENCE_SIZE {
+        return Err(DecodeError::UnexpectedEnd);
+    }
+
+    let (index, rest) = rest.split_at(core::mem::size_of::<u32>());
+    let (count, chunk) = rest.split_at(core::mem::size_of::<u32>());
+    let mut bytes = [0u8; 4];
+    bytes.copy_from_slice(index);
+    let index = u32::from_le_bytes(bytes);
+    bytes.copy_from_slice(count);
+    let count = u32::from_le_bytes(bytes);
+
+    if index >= count {
+        return Err(DecodeError::InvalidFragment);
+    }
+
+    Ok(Fragment {
+        sequence,
+        index,
+        count,
+        chunk,
+    })
+}
+
+#[cfg(test)]
+#[path = "./tests/frame.rs"]
+mod tests;
@@ -0,0 +1,54 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The core types of Zenoh-Flow: the identifiers of its model and the messages exchanged, through
+//! Zenoh, between the nodes running on different daemons.
+//!
+//! This crate is `no_std` and only requires an allocator, such that the devices that cannot run a
+//! daemon (e.g. a microcontroller publishing with Zenoh-pico) can produce, or consume, the messages
+//! received, or sent, by the connectors of a data flow:
+//!
+//! ```ignore
+//! let message = LinkMessage::Data {
+//!     payload: Payload::Bytes(reading.to_le_bytes().to_vec()),
+//!     timestamp: Timestamp::new(now, device_id),
+//!     event_time: None,
+//!     variant: None,
+//! };
+//! publisher.put(&frame(sequence, &message.encode()));
+//! ```
+
+#![no_std]
+
+extern crate alloc;
+
+pub mod compression;
+pub use compression::{decompress, COMPRESSED_TAG};
+pub mod frame;
+pub use frame::{
+    fragments, frame, unfragment, unframe, Fragment, FRAGMENT_HEADER_SIZE, SEQUENCE_SIZE,
+};
+pub mod message;
+pub use message::{DecodeError, LinkMessage, Payload, Timestamp};
+
+use alloc::sync::Arc;
+
+/// A NodeId identifies a node inside a Zenoh Flow graph
+pub type NodeId = Arc<str>;
+/// A PortId identifies a port within an node.
+pub type PortId = Arc<str>;
+/// A RuntimeId identifies a runtime within the Zenoh Flow infrastructure.
+pub type RuntimeId = Arc<str>;
+/// A FlowId identifies a Zenoh Flow graph within Zenoh Flow
+pub type FlowId = Arc<str>;
@@ -0,0 +1,351 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use crate::compression::{decompress, COMPRESSED_TAG};
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+
+/// The maximum size, in bytes, of the identifier of the clock that generated a [Timestamp].
+pub const ID_MAX_SIZE: usize = 16;
+
+/// The number of fractions of a second in the NTP64 format.
+const NTP64_FRACTIONS: u64 = 1 << 32;
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+/// A timestamp generated by a Hybrid Logical Clock: a time, in the NTP64 format (the seconds since
+/// the UNIX epoch in the 32 upper bits, the fraction of the second in the 32 lower bits), and the
+/// identifier of the clock.
+///
+/// It is encoded as the timestamps of the `uhlc` crate used by the daemons.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Timestamp {
+    pub time: u64,
+    id_size: usize,
+    id: [u8; ID_MAX_SIZE],
+}
+
+impl Timestamp {
+    /// Creates a new `Timestamp` for the `time`, in the NTP64 format (see [Timestamp::ntp64]),
+    /// generated by the clock identified by `id`.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the identifier is empty or longer than [ID_MAX_SIZE] bytes.
+    pub fn new(time: u64, id: &[u8]) -> Result<Self, DecodeError> {
+        if id.is_empty() || id.len() > ID_MAX_SIZE {
+            return Err(DecodeError::InvalidClockId(id.len()));
+        }
+
+        let mut bytes = [0u8; ID_MAX_SIZE];
+        bytes[..id.len()].copy_from_slice(id);
+        Ok(Self {
+            time,
+            id_size: id.len(),
+            id: bytes,
+        })
+    }
+
+    /// Converts a time elapsed since the UNIX epoch in the NTP64 format.
+    pub fn ntp64(seconds: u32, nanoseconds: u32) -> u64 {
+        let fraction = (nanoseconds as u64 % NANOS_PER_SECOND) * NTP64_FRACTIONS / NANOS_PER_SECOND;
+        ((seconds as u64) << 32) + fraction
+    }
+
+    /// Returns the identifier of the clock that generated this `Timestamp`.
+    pub fn id(&self) -> &[u8] {
+        &self.id[..self.id_size]
+    }
+
+    fn encode(&self, buffer: &mut Vec<u8>) {
+        buffer.extend_from_slice(&self.time.to_le_bytes());
+        buffer.extend_from_slice(&(self.id_size as u64).to_le_bytes());
+        buffer.extend_from_slice(&self.id);
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, DecodeError> {
+        let time = reader.u64()?;
+        let id_size = reader.u64()? as usize;
+        let id = reader.bytes(ID_MAX_SIZE)?;
+        if id_size == 0 || id_size > ID_MAX_SIZE {
+            return Err(DecodeError::InvalidClockId(id_size));
+        }
+
+        let mut bytes = [0u8; ID_MAX_SIZE];
+        bytes.copy_from_slice(id);
+        Ok(Self {
+            time,
+            id_size,
+            id: bytes,
+        })
+    }
+}
+
+/// The data carried by a [LinkMessage::Data].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Payload {
+    /// The serialized data.
+    Bytes(Vec<u8>),
+    /// The key expression under which the serialized data is stored in Zenoh.
+    Reference(String),
+}
+
+/// A message exchanged, through Zenoh, between the connectors of a data flow.
+///
+/// It is encoded as the `LinkMessage` of the `zenoh-flow` crate: see there for the meaning of each
+/// variant. The `variant` of a `Data` is the index, in its union, of the data type of the payload
+/// sent on a union port. The `epoch` of a `Control` is the checkpoint it belongs to, if it is a
+/// checkpoint barrier.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LinkMessage {
+    Data {
+        payload: Payload,
+        timestamp: Timestamp,
+        event_time: Option<Timestamp>,
+        variant: Option<u32>,
+    },
+    Watermark(Timestamp),
+    EndOfStream(Timestamp),
+    Live(Timestamp),
+    Control {
+        timestamp: Timestamp,
+        payload: Vec<u8>,
+        epoch: Option<u64>,
+    },
+}
+
+impl LinkMessage {
+    /// Returns the timestamp of the message.
+    pub fn timestamp(&self) -> &Timestamp {
+        match self {
+            LinkMessage::Data { timestamp, .. }
+            | LinkMessage::Watermark(timestamp)
+            | LinkMessage::EndOfStream(timestamp)
+            | LinkMessage::Live(timestamp)
+            | LinkMessage::Control { timestamp, .. } => timestamp,
+        }
+    }
+
+    /// Encodes the message, as the daemons do before publishing it.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        match self {
+            LinkMessage::Data {
+                payload,
+                timestamp,
+                event_time,
+                variant,
+            } => {
+                buffer.extend_from_slice(&0u32.to_le_bytes());
+                match payload {
+                    Payload::Bytes(bytes) => {
+                        buffer.extend_from_slice(&0u32.to_le_bytes());
+                        encode_bytes(&mut buffer, bytes);
+                    }
+                    Payload::Reference(key_expr) => {
+                        buffer.extend_from_slice(&1u32.to_le_bytes());
+                        encode_bytes(&mut buffer, key_expr.as_bytes());
+                    }
+                }
+                timestamp.encode(&mut buffer);
+                match event_time {
+                    Some(event_time) => {
+                        buffer.push(1);
+                        event_time.encode(&mut buffer);
+                    }
+                    None => buffer.push(0),
+                }
+                match variant {
+                    Some(variant) => {
+                        buffer.push(1);
+                        buffer.extend_from_slice(&variant.to_le_bytes());
+                    }
+                    None => buffer.push(0),
+                }
+            }
+            LinkMessage::Watermark(timestamp) => {
+                buffer.extend_from_slice(&1u32.to_le_bytes());
+                timestamp.encode(&mut buffer);
+            }
+            LinkMessage::EndOfStream(timestamp) => {
+                buffer.extend_from_slice(&2u32.to_le_bytes());
+                timestamp.encode(&mut buffer);
+            }
+            LinkMessage::Live(timestamp) => {
+                buffer.extend_from_slice(&3u32.to_le_bytes());
+                timestamp.encode(&mut buffer);
+            }
+            LinkMessage::Control {
+                timestamp,
+                payload,
+                epoch,
+            } => {
+                buffer.extend_from_slice(&4u32.to_le_bytes());
+                timestamp.encode(&mut buffer);
+                encode_bytes(&mut buffer, payload);
+                match epoch {
+                    Some(epoch) => {
+                        buffer.push(1);
+                        buffer.extend_from_slice(&epoch.to_le_bytes());
+                    }
+                    None => buffer.push(0),
+                }
+            }
+        }
+        buffer
+    }
+
+    /// Decodes a message published by a daemon, decompressing it first if it starts with
+    /// [COMPRESSED_TAG].
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the bytes are not a valid encoding of a message, or of a compressed
+    /// message.
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        match bytes.split_at(bytes.len().min(core::mem::size_of::<u32>())) {
+            (tag, compressed) if tag == COMPRESSED_TAG.to_le_bytes() => {
+                Self::decode_uncompressed(&decompress(compressed)?)
+            }
+            _ => Self::decode_uncompressed(bytes),
+        }
+    }
+
+    /// Decodes a message that is not compressed: a compressed message inside is rejected.
+    fn decode_uncompressed(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut reader = Reader { bytes, position: 0 };
+        let message = match reader.u32()? {
+            0 => {
+                let payload = match reader.u32()? {
+                    0 => Payload::Bytes(reader.sized_bytes()?.to_vec()),
+                    1 => Payload::Reference(
+                        String::from_utf8(reader.sized_bytes()?.to_vec())
+                            .map_err(|_| DecodeError::InvalidUtf8)?,
+                    ),
+                    variant => return Err(DecodeError::UnknownVariant(variant)),
+                };
+                let timestamp = Timestamp::decode(&mut reader)?;
+                let event_time = match reader.bytes(1)?[0] {
+                    0 => None,
+                    1 => Some(Timestamp::decode(&mut reader)?),
+                    tag => return Err(DecodeError::UnknownVariant(tag as u32)),
+                };
+                let variant = match reader.bytes(1)?[0] {
+                    0 => None,
+                    1 => Some(reader.u32()?),
+                    tag => return Err(DecodeError::UnknownVariant(tag as u32)),
+                };
+                LinkMessage::Data {
+                    payload,
+                    timestamp,
+                    event_time,
+                    variant,
+                }
+            }
+            1 => LinkMessage::Watermark(Timestamp::decode(&mut reader)?),
+            2 => LinkMessage::EndOfStream(Timestamp::decode(&mut reader)?),
+            3 => LinkMessage::Live(Timestamp::decode(&mut reader)?),
+            4 => LinkMessage::Control {
+                timestamp: Timestamp::decode(&mut reader)?,
+                payload: reader.sized_bytes()?.to_vec(),
+                epoch: match reader.bytes(1)?[0] {
+                    0 => None,
+                    1 => Some(reader.u64()?),
+                    tag => return Err(DecodeError::UnknownVariant(tag as u32)),
+                },
+            },
+            variant => return Err(DecodeError::UnknownVariant(variant)),
+        };
+
+        Ok(message)
+    }
+}
+
+/// The reasons why bytes could not be decoded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The bytes ended before the message was complete.
+    UnexpectedEnd,
+    /// The variant of an enumeration is not known.
+    UnknownVariant(u32),
+    /// The identifier of a clock has an invalid size.
+    InvalidClockId(usize),
+    /// A key expression is not valid UTF-8.
+    InvalidUtf8,
+    /// A fragment is not valid, or a message cannot be split into fragments of that size.
+    InvalidFragment,
+    /// A compressed message is not a valid LZ4 block, or does not expand to its announced size.
+    InvalidCompression,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::UnexpectedEnd => write!(f, "unexpected end of the message"),
+            DecodeError::UnknownVariant(variant) => write!(f, "unknown variant {variant}"),
+            DecodeError::InvalidClockId(size) => {
+                write!(f, "invalid clock identifier of {size} bytes")
+            }
+            DecodeError::InvalidUtf8 => write!(f, "invalid UTF-8 key expression"),
+            DecodeError::InvalidFragment => write!(f, "invalid fragment"),
+            DecodeError::InvalidCompression => write!(f, "invalid compressed message"),
+        }
+    }
+}
+
+fn encode_bytes(buffer: &mut Vec<u8>, bytes: &[u8]) {
+    buffer.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+    buffer.extend_from_slice(bytes);
+}
+
+/// Reads, in order, the fields of an encoded message.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(DecodeError::UnexpectedEnd)?;
+        let bytes = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, DecodeError> {
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(self.bytes(4)?);
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn u64(&mut self) -> Result<u64, DecodeError> {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(self.bytes(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    fn sized_bytes(&mut self) -> Result<&'a [u8], DecodeError> {
+        let len = self.u64()?;
+        self.bytes(usize::try_from(len).map_err(|_| DecodeError::UnexpectedEnd)?)
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/message.rs"]
+mod tests;
@@ -0,0 +1,76 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::{decompress, COMPRESSED_TAG};
+use crate::message::{DecodeError, LinkMessage, Timestamp};
+
+// "abc", then a match of 9 bytes 3 bytes back (overlapping the bytes it copies), then "!".
+const BLOCK: [u8; 12] = [13, 0, 0, 0, 0x35, b'a', b'b', b'c', 3, 0, 0x10, b'!'];
+
+#[test]
+fn test_decompress() {
+    assert_eq!(decompress(&BLOCK).unwrap(), b"abcabcabcabc!");
+
+    let mut larger = BLOCK;
+    larger[0] = 14;
+    assert_eq!(decompress(&larger), Err(DecodeError::InvalidCompression));
+
+    let mut smaller = BLOCK;
+    smaller[0] = 12;
+    assert_eq!(decompress(&smaller), Err(DecodeError::InvalidCompression));
+
+    let mut offset = BLOCK;
+    offset[8] = 4;
+    assert_eq!(decompress(&offset), Err(DecodeError::InvalidCompression));
+
+    // The announced size cannot allocate more than the block expands to.
+    let mut huge = BLOCK;
+    huge[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert_eq!(decompress(&huge), Err(DecodeError::InvalidCompression));
+
+    assert_eq!(decompress(&BLOCK[..7]), Err(DecodeError::UnexpectedEnd));
+    assert_eq!(decompress(&BLOCK[..2]), Err(DecodeError::UnexpectedEnd));
+}
+
+// A compressed message is decoded as the message it contains, but only once.
+#[test]
+fn test_decode_compressed() {
+    let message = LinkMessage::Watermark(Timestamp::new(42, &[7]).unwrap());
+    let encoded = message.encode();
+
+    // The encoded message only has literals.
+    let compress = |bytes: &[u8]| {
+        let mut compressed = COMPRESSED_TAG.to_le_bytes().to_vec();
+        compressed.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+        compressed.push(0xf0);
+        compressed.push((bytes.len() - 15) as u8);
+        compressed.extend_from_slice(bytes);
+        compressed
+    };
+
+    let compressed = compress(&encoded);
+    assert_eq!(LinkMessage::decode(&compressed).unwrap(), message);
+    assert_eq!(
+        LinkMessage::decode(&compressed[..compressed.len() - 1]),
+        Err(DecodeError::UnexpectedEnd)
+    );
+    assert_eq!(
+        LinkMessage::decode(&compress(&compressed)),
+        Err(DecodeError::UnknownVariant(COMPRESSED_TAG))
+    );
+    assert_eq!(
+        LinkMessage::decode(&COMPRESSED_TAG.to_le_bytes()),
+        Err(DecodeError::UnexpectedEnd)
+    );
+}
@@ -0,0 +1,59 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::{fragments, frame, unfragment, FRAGMENT_HEADER_SIZE};
+use crate::message::DecodeError;
+use alloc::vec::Vec;
+
+#[test]
+fn test_fragments_round_trip() {
+    let message = (0..100u8).collect::<Vec<_>>();
+    let fragment_size = FRAGMENT_HEADER_SIZE + 30;
+
+    let parts = fragments(42, &message, fragment_size).unwrap();
+    assert_eq!(parts.len(), 4);
+    assert!(parts.iter().all(|fragment| fragment.len() <= fragment_size));
+
+    let mut reassembled = Vec::new();
+    for (expected_index, fragment) in parts.iter().enumerate() {
+        let fragment = unfragment(fragment).unwrap();
+        assert_eq!(fragment.sequence, 42);
+        assert_eq!(fragment.index as usize, expected_index);
+        assert_eq!(fragment.count, 4);
+        reassembled.extend_from_slice(fragment.chunk);
+    }
+    assert_eq!(reassembled, message);
+
+    // A message always has a fragment, even empty.
+    let parts = fragments(0, &[], fragment_size).unwrap();
+    assert_eq!(parts.len(), 1);
+    assert_eq!(unfragment(&parts[0]).unwrap().chunk, &[] as &[u8]);
+}
+
+#[test]
+fn test_fragments_errors() {
+    assert_eq!(
+        fragments(0, &[1, 2, 3], FRAGMENT_HEADER_SIZE),
+        Err(DecodeError::InvalidFragment)
+    );
+    assert_eq!(
+        unfragment(&frame(0, &[0u8; 4])),
+        Err(DecodeError::UnexpectedEnd)
+    );
+
+    // The index must be lower than the number of fragments.
+    let mut fragment = frame(0, &1u32.to_le_bytes());
+    fragment.extend_from_slice(&1u32.to_le_bytes());
+    assert_eq!(unfragment(&fragment), Err(DecodeError::InvalidFragment));
+}
@@ -0,0 +1,77 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::{DecodeError, LinkMessage, Payload, Timestamp};
+use crate::frame::{frame, unframe};
+use alloc::vec;
+
+#[test]
+fn test_round_trip() {
+    let timestamp = Timestamp::new(Timestamp::ntp64(1, 500_000_000), &[0xca, 0xfe]).unwrap();
+    assert_eq!(timestamp.time, (1 << 32) + (1 << 31));
+    assert_eq!(timestamp.id(), &[0xca, 0xfe]);
+
+    let messages = [
+        LinkMessage::Data {
+            payload: Payload::Bytes(vec![1, 2, 3]),
+            timestamp,
+            event_time: Some(timestamp),
+            variant: Some(1),
+        },
+        LinkMessage::Data {
+            payload: Payload::Reference("sensors/lidar/42".into()),
+            timestamp,
+            event_time: None,
+            variant: None,
+        },
+        LinkMessage::Watermark(timestamp),
+        LinkMessage::EndOfStream(timestamp),
+        LinkMessage::Live(timestamp),
+        LinkMessage::Control {
+            timestamp,
+            payload: vec![4, 2],
+            epoch: None,
+        },
+        LinkMessage::Control {
+            timestamp,
+            payload: vec![],
+            epoch: Some(7),
+        },
+    ];
+
+    for message in messages {
+        let framed = frame(7, &message.encode());
+        let (sequence, bytes) = unframe(&framed).unwrap();
+        assert_eq!(sequence, 7);
+        assert_eq!(LinkMessage::decode(bytes).unwrap(), message);
+    }
+}
+
+#[test]
+fn test_decode_errors() {
+    assert!(Timestamp::new(0, &[]).is_err());
+    assert!(Timestamp::new(0, &[0u8; 17]).is_err());
+
+    let timestamp = Timestamp::new(0, &[1]).unwrap();
+    let bytes = LinkMessage::Watermark(timestamp).encode();
+    assert_eq!(
+        LinkMessage::decode(&bytes[..bytes.len() - 1]),
+        Err(DecodeError::UnexpectedEnd)
+    );
+    assert_eq!(
+        LinkMessage::decode(&[9, 0, 0, 0]),
+        Err(DecodeError::UnknownVariant(9))
+    );
+    assert_eq!(unframe(&[0u8; 4]), Err(DecodeError::UnexpectedEnd));
+}
@@ -12,6 +12,7 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::fs;
 use std::path::Path;
@@ -20,23 +21,30 @@ use async_std::sync::RwLock;
 // use futures::stream::{AbortHandle, Abortable, Aborted};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use uhlc::{HLCBuilder, ID};
+use std::time::Duration;
+use uhlc::{HLCBuilder, Timestamp, ID};
 use uuid::Uuid;
 
 use zenoh_flow::model::descriptor::{
-    FlattenDataFlowDescriptor, OperatorDescriptor, SinkDescriptor, SourceDescriptor,
+    FlattenDataFlowDescriptor, InputDescriptor, OperatorDescriptor, OutputDescriptor,
+    SinkDescriptor, SourceDescriptor, TransportDescriptor,
 };
 use zenoh_flow::model::record::DataFlowRecord;
 use zenoh_flow::prelude::{zferror, ErrorKind, Result as ZFResult};
 
+use zenoh_flow::runtime::clock::ClockSkew;
+use zenoh_flow::runtime::dataflow::cache::LibraryCacheConfig;
+use zenoh_flow::runtime::dataflow::instance::record_sink::RecordingBackend;
+use zenoh_flow::runtime::dataflow::instance::recording::RecordingManifest;
 use zenoh_flow::runtime::dataflow::loader::{
     ExtensibleImplementation, Loader, LoaderConfig, EXT_FILE_EXTENSION,
 };
+use zenoh_flow::runtime::gpu::GpuInventory;
 
 use zenoh_flow::runtime::resources::DataStore;
 use zenoh_flow::runtime::worker_pool::{WorkerPool, WorkerTrait};
 use zenoh_flow::runtime::{
-    DaemonInterface, DaemonInterfaceInternal, RuntimeConfig, RuntimeContext,
+    DaemonInterface, DaemonInterfaceInternal, Event, RuntimeConfig, RuntimeContext,
 };
 use zenoh_flow::types::ControlMessage;
 use zenoh_flow::utils::{deserialize_size, deserialize_time};
@@ -67,8 +75,16 @@ pub struct DaemonConfig {
     pub uuid: Option<Uuid>,
     /// Where to find the Zenoh configuration file
     pub zenoh_config: Option<String>,
+    /// The transport configuration (mode, locators, TLS certificates) applied on top of the Zenoh
+    /// configuration file, ignored when the daemon runs as a Zenoh plugin.
+    #[serde(default)]
+    pub zenoh_transport: Option<TransportDescriptor>,
     /// Where to locate the extension files.
     pub extensions: String,
+    /// Where the libraries of the nodes fetched over HTTP are stored, and the maximum size they
+    /// can occupy. By default they are stored in the temporary directory of the system.
+    #[serde(default)]
+    pub library_cache: Option<LibraryCacheConfig>,
     /// The size of the worker pool.
     pub worker_pool_size: usize,
     /// The default size of the shared memory element.
@@ -83,6 +99,16 @@ pub struct DaemonConfig {
     pub default_shared_memory_backoff: Option<u64>,
     // Whether or not Shared Memory is enabled.
     pub use_shm: Option<bool>,
+    /// Where the recordings of the outputs are stored, recording being disabled by default.
+    #[serde(default)]
+    pub recording_backend: RecordingBackend,
+    /// Where to find the Zenoh configuration files of the additional sessions the connectors can
+    /// use, indexed by session name.
+    #[serde(default)]
+    pub zenoh_sessions: HashMap<String, String>,
+    /// The indexes of the GPUs of the machine the nodes can request, none by default.
+    #[serde(default)]
+    pub gpus: Vec<u32>,
 }
 
 /// The Zenoh flow daemon
@@ -124,7 +150,10 @@ impl TryFrom<DaemonConfig> for Daemon {
 
         if let Some(zenoh_config) = &config.zenoh_config {
             // Loading Zenoh configuration
-            let zconfig = get_zenoh_config(zenoh_config)?;
+            let mut zconfig = get_zenoh_config(zenoh_config)?;
+            if let Some(transport) = &config.zenoh_transport {
+                transport.apply(&mut zconfig)?;
+            }
 
             // Creates the zenoh session.
             let session = Arc::new(zenoh::open(zconfig).res()?);
@@ -194,6 +223,9 @@ impl Daemon {
         }
 
         let mut extensions = LoaderConfig::new();
+        if let Some(library_cache) = config.library_cache {
+            extensions.set_library_cache(library_cache);
+        }
 
         let ext_dir = Path::new(&config.extensions);
 
@@ -287,6 +319,13 @@ impl Daemon {
         // Creates the loader.
         let loader = Arc::new(Loader::new(extensions));
 
+        // Loads the configurations of the additional Zenoh sessions.
+        let zenoh_configs = config
+            .zenoh_sessions
+            .iter()
+            .map(|(name, path)| Ok((name.clone(), get_zenoh_config(path)?)))
+            .collect::<ZFResult<HashMap<_, _>>>()?;
+
         let ctx = RuntimeContext {
             session: z.clone(),
             hlc,
@@ -303,6 +342,9 @@ impl Daemon {
                 .default_shared_memory_backoff
                 .unwrap_or(DEFAULT_SHM_ALLOCATION_BACKOFF_NS),
             use_shm: config.use_shm.unwrap_or(DEFAULT_USE_SHM),
+            recording_backend: config.recording_backend,
+            zenoh_configs: Arc::new(zenoh_configs),
+            gpus: Arc::new(GpuInventory::new(config.gpus)),
         };
 
         Ok(Self::new(z, ctx, rt_config, pool_size))
@@ -642,6 +684,101 @@ impl DaemonInterface for Daemon {
         Ok(())
     }
 
+    async fn restart_node(&self, instance_id: Uuid, node: String) -> DaemonResult<()> {
+        let res = self
+            .worker_pool
+            .read()
+            .await
+            .submit_restart_node(&instance_id, &node)
+            .await?;
+        log::info!(
+            "[Daemon: {}][Job: {}] Restart node < {}:{} >",
+            self.ctx.runtime_uuid,
+            res.get_id(),
+            instance_id,
+            node,
+        );
+
+        Ok(())
+    }
+
+    async fn tap(
+        &self,
+        instance_id: Uuid,
+        node: String,
+        port: String,
+        decode: bool,
+    ) -> DaemonResult<String> {
+        self.runtime.tap(instance_id, node, port, decode).await
+    }
+
+    async fn untap(&self, instance_id: Uuid, node: String, port: String) -> DaemonResult<bool> {
+        self.runtime.untap(instance_id, node, port).await
+    }
+
+    async fn record(
+        &self,
+        instance_id: Uuid,
+        enabled: bool,
+    ) -> DaemonResult<Vec<RecordingManifest>> {
+        self.runtime.record(instance_id, enabled).await
+    }
+
+    async fn get_sent_messages(
+        &self,
+        instance_id: Uuid,
+    ) -> DaemonResult<Vec<(OutputDescriptor, u64)>> {
+        self.runtime.get_sent_messages(instance_id).await
+    }
+
+    async fn breakpoint(
+        &self,
+        instance_id: Uuid,
+        node: String,
+        port: Option<String>,
+        enabled: bool,
+    ) -> DaemonResult<()> {
+        self.runtime
+            .breakpoint(instance_id, node, port, enabled)
+            .await
+    }
+
+    async fn resume(&self, instance_id: Uuid, node: String, step: bool) -> DaemonResult<()> {
+        self.runtime.resume(instance_id, node, step).await
+    }
+
+    async fn get_events(&self, instance_id: Option<Uuid>) -> DaemonResult<Vec<Event>> {
+        let mut events = self.runtime.store.get_events().await?;
+        if let Some(instance_id) = instance_id {
+            events.retain(|event| event.instance_id == instance_id);
+        }
+        Ok(events)
+    }
+
+    async fn get_clock_skews(&self) -> DaemonResult<Vec<ClockSkew>> {
+        self.runtime.get_clock_skews().await
+    }
+
+    async fn compose(
+        &self,
+        from_flow: String,
+        output: OutputDescriptor,
+        to_flow: String,
+        input: InputDescriptor,
+    ) -> DaemonResult<String> {
+        self.runtime
+            .compose(from_flow, output, to_flow, input)
+            .await
+    }
+
+    async fn decompose(
+        &self,
+        to_flow: String,
+        input: InputDescriptor,
+    ) -> DaemonResult<Option<String>> {
+        self.runtime.decompose(to_flow, input).await
+    }
+
     // async fn start_record(&self, instance_id: Uuid, source_id: NodeId) -> DaemonResult<String> {
     //     Err(ErrorKind::Unimplemented)
     // }
@@ -695,6 +832,26 @@ impl DaemonInterfaceInternal for Daemon {
         self.runtime.stop_sources(instance_id).await
     }
 
+    async fn connect_import(
+        &self,
+        instance_id: Uuid,
+        input: InputDescriptor,
+        from_instance: Uuid,
+        output: OutputDescriptor,
+    ) -> DaemonResult<String> {
+        self.runtime
+            .connect_import(instance_id, input, from_instance, output)
+            .await
+    }
+
+    async fn disconnect_import(
+        &self,
+        instance_id: Uuid,
+        input: InputDescriptor,
+    ) -> DaemonResult<Option<String>> {
+        self.runtime.disconnect_import(instance_id, input).await
+    }
+
     async fn notify_runtime(
         &self,
         instance_id: Uuid,
@@ -720,4 +877,24 @@ impl DaemonInterfaceInternal for Daemon {
     async fn check_sink_compatibility(&self, sink: SinkDescriptor) -> DaemonResult<bool> {
         self.runtime.check_sink_compatibility(sink).await
     }
+
+    async fn clock(&self) -> DaemonResult<Timestamp> {
+        Ok(self.ctx.hlc.new_timestamp())
+    }
+
+    async fn idle_time(&self, instance_id: Uuid) -> DaemonResult<Duration> {
+        self.runtime.idle_time(instance_id).await
+    }
+
+    async fn record_session(
+        &self,
+        instance_id: Uuid,
+        enabled: bool,
+    ) -> DaemonResult<RecordingManifest> {
+        self.runtime.record_session(instance_id, enabled).await
+    }
+
+    async fn sent_messages(&self, instance_id: Uuid) -> DaemonResult<Vec<(OutputDescriptor, u64)>> {
+        self.runtime.sent_messages(instance_id).await
+    }
 }
@@ -14,31 +14,48 @@
 use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use async_std::sync::Mutex;
 use uuid::Uuid;
 use zenoh_flow::model::{
-    descriptor::{FlattenDataFlowDescriptor, OperatorDescriptor, SinkDescriptor, SourceDescriptor},
+    descriptor::{
+        ExpiryDescriptor, FlattenDataFlowDescriptor, InputDescriptor, MissingRuntimePolicy,
+        OperatorDescriptor, OutputDescriptor, PreemptionPolicy, SinkDescriptor, SourceDescriptor,
+    },
     record::DataFlowRecord,
 };
+use zenoh_flow::runtime::clock::{ClockSkew, CLOCK_SKEW_WARNING};
+use zenoh_flow::runtime::dataflow::instance::recording::RecordingManifest;
 use zenoh_flow::runtime::dataflow::instance::DataFlowInstance;
+use zenoh_flow::runtime::dataflow::readiness::wait_until_ready;
 use zenoh_flow::runtime::dataflow::DataFlow;
 use zenoh_flow::runtime::resources::DataStore;
 use zenoh_flow::runtime::{
-    DaemonInterfaceInternalClient, RuntimeConfig, RuntimeContext, RuntimeInfo, RuntimeStatus,
-    RuntimeStatusKind,
+    DaemonInterfaceInternalClient, Event, EventAction, EventResult, RuntimeConfig, RuntimeContext,
+    RuntimeInfo, RuntimeStatus, RuntimeStatusKind,
 };
-use zenoh_flow::types::ControlMessage;
+use zenoh_flow::types::{ControlMessage, NodeId, PortId, RecordingLabels, RuntimeId};
 use zenoh_flow::zferror;
-use zenoh_flow::zfresult::ErrorKind;
+use zenoh_flow::zfresult::{ErrorKind, ZFError};
 use zenoh_flow::DaemonResult;
 use zenoh_flow::Result as ZFResult;
 
+/// Interval at which the runtimes are listed again while waiting for the missing ones (1s).
+const MISSING_RUNTIME_POLLING_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Interval at which the expiry of an instance is checked (1s).
+const EXPIRY_POLLING_INTERVAL: Duration = Duration::from_secs(1);
+
 /// The internal runtime state.
 ///
 /// It keeps track of running instances and runtime configuration.
+///
+/// Each instance has its own lock, such that a long operation on an instance (e.g. rescaling it)
+/// does not block the other ones: the state is only locked to look the instance up, and is never
+/// locked while waiting for the lock of an instance.
 pub struct RTState {
-    pub graphs: HashMap<Uuid, DataFlowInstance>,
+    pub graphs: HashMap<Uuid, Arc<Mutex<DataFlowInstance>>>,
     pub config: RuntimeConfig,
 }
 
@@ -121,7 +138,7 @@ impl Runtime {
 
     pub(crate) async fn create_instance(
         &self,
-        flow: FlattenDataFlowDescriptor,
+        mut flow: FlattenDataFlowDescriptor,
         record_uuid: Uuid,
     ) -> DaemonResult<DataFlowRecord> {
         //TODO: workaround - it should just take the ID of the flow (when
@@ -139,30 +156,32 @@ impl Runtime {
 
         // TODO: flatting of a descriptor, when the registry will be in place
 
-        // Mapping to infrastructure
-        let mapped =
-            zenoh_flow::runtime::map_to_infrastructure(flow, &self.ctx.runtime_name).await?;
+        // Mapping to infrastructure, preferring this runtime for the nodes that are not mapped.
+        let (runtimes, absent) = self.available_runtimes(&mut flow, record_uuid).await?;
+        let mapped = zenoh_flow::runtime::map_to_runtimes(flow, &runtimes).await?;
 
-        // Getting runtime involved in this instance
+        // Getting runtime involved in this instance, the ones that are not running are attached
+        // once they join.
         let involved_runtimes = mapped.get_runtimes();
         let involved_runtimes = involved_runtimes
             .into_iter()
-            .filter(|rt| *rt != self.ctx.runtime_name);
+            .filter(|rt| *rt != self.ctx.runtime_name && !absent.contains(rt));
 
         // Creating the record
         let dfr = DataFlowRecord::try_from((mapped, record_uuid))?;
 
-        self.store
-            .add_runtime_flow(&self.ctx.runtime_uuid, &dfr)
-            .await?;
-
         // Creating clients to talk with other runtimes
         for rt in involved_runtimes {
             let rt_info = self.store.get_runtime_info_by_name(&rt).await?;
             let client = DaemonInterfaceInternalClient::new(self.ctx.session.clone(), rt_info.id);
+            self.check_clock_skew(&dfr, rt, &client).await?;
             rt_clients.push(client);
         }
 
+        self.store
+            .add_runtime_flow(&self.ctx.runtime_uuid, &dfr)
+            .await?;
+
         // remote prepare
         for client in rt_clients.iter() {
             client.prepare(dfr.uuid).await??;
@@ -171,6 +190,14 @@ impl Runtime {
         // self prepare
         self.prepare(dfr.uuid).await?;
 
+        if !absent.is_empty() {
+            self.spawn_attacher(dfr.uuid, absent);
+        }
+
+        if let Some(expiry) = dfr.expiry.clone() {
+            self.spawn_expirer(dfr.uuid, expiry);
+        }
+
         log::info!(
             "Created Flow {} - Instance UUID: {}",
             flow_name,
@@ -180,6 +207,223 @@ impl Runtime {
         Ok(dfr)
     }
 
+    /// Checks the offset between the HLC of this runtime and the one of the runtime `name`,
+    /// reached through the `client`: deadlines and ordering are meaningless if the clocks of the
+    /// runtimes diverge.
+    ///
+    /// A warning is logged above [CLOCK_SKEW_WARNING]; an error is returned above the
+    /// `max_clock_skew` of the data flow.
+    async fn check_clock_skew(
+        &self,
+        dfr: &DataFlowRecord,
+        name: RuntimeId,
+        client: &DaemonInterfaceInternalClient,
+    ) -> DaemonResult<()> {
+        let skew = self.measure_clock_skew(name, client).await?;
+        if skew.exceeds(CLOCK_SKEW_WARNING) {
+            log::warn!(
+                "Flow {} - Instance UUID: {} - The clock of runtime < {} > is {} by {:?} (round trip: {:?})",
+                dfr.flow,
+                dfr.uuid,
+                skew.runtime,
+                if skew.ahead { "ahead" } else { "behind" },
+                skew.offset,
+                skew.round_trip
+            );
+        }
+        if let Some(max_clock_skew) = dfr.max_clock_skew {
+            if skew.exceeds(max_clock_skew) {
+                return Err(zferror!(
+                    ErrorKind::ClockSkew(skew.runtime, skew.offset),
+                    "The clock skew exceeds the maximum of the data flow, {:?}",
+                    max_clock_skew
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Spawns the task attaching the nodes of the instance mapped to the `runtimes` that were not
+    /// running when it was created (see [MissingRuntimePolicy]). Every
+    /// [MISSING_RUNTIME_POLLING_INTERVAL], the runtimes that joined prepare the instance and, if it
+    /// is running, start their nodes then their Sources. The task ends once all the runtimes
+    /// joined or the instance is cleaned.
+    fn spawn_attacher(&self, instance_id: Uuid, mut runtimes: Vec<RuntimeId>) {
+        let runtime = self.clone();
+        async_std::task::spawn(async move {
+            while !runtimes.is_empty() {
+                async_std::task::sleep(MISSING_RUNTIME_POLLING_INTERVAL).await;
+                let is_running = match runtime.instance(instance_id).await {
+                    Ok(instance) => instance.lock().await.is_running(),
+                    Err(_) => return,
+                };
+
+                let mut joined = Vec::new();
+                for name in runtimes.iter() {
+                    if let Ok(info) = runtime.store.get_runtime_info_by_name(name).await {
+                        joined.push((name.clone(), info.id));
+                    }
+                }
+                for (name, id) in joined {
+                    runtimes.retain(|rt| *rt != name);
+                    if let Err(e) = runtime
+                        .attach(instance_id, name.clone(), id, is_running)
+                        .await
+                    {
+                        log::error!(
+                            "Instance UUID {} - Failed to attach the nodes mapped to runtime < {} >: {:?}",
+                            instance_id,
+                            name,
+                            e
+                        );
+                    }
+                }
+            }
+        });
+    }
+
+    /// Deploys, on the runtime `name` that just joined, the nodes of the instance mapped to it and,
+    /// if the instance `is_running`, starts them.
+    async fn attach(
+        &self,
+        instance_id: Uuid,
+        name: RuntimeId,
+        id: Uuid,
+        is_running: bool,
+    ) -> DaemonResult<()> {
+        let dfr = self.store.get_flow_by_instance(&instance_id).await?;
+        let client = DaemonInterfaceInternalClient::new(self.ctx.session.clone(), id);
+        self.check_clock_skew(&dfr, name.clone(), &client).await?;
+
+        client.prepare(instance_id).await??;
+        if is_running {
+            client.start(instance_id).await??;
+            client.start_sources(instance_id).await??;
+        }
+
+        log::info!(
+            "Instance UUID {} - Attached the nodes mapped to runtime < {} >",
+            instance_id,
+            name
+        );
+        Ok(())
+    }
+
+    /// Returns the names of the runtimes available, this one first, once the `missing_runtime`
+    /// policy of the `flow` was applied to the runtimes its `mapping` names that are not running
+    /// (see [MissingRuntimePolicy]), along with the ones that are attached once they join.
+    async fn available_runtimes(
+        &self,
+        flow: &mut FlattenDataFlowDescriptor,
+        record_uuid: Uuid,
+    ) -> DaemonResult<(Vec<RuntimeId>, Vec<RuntimeId>)> {
+        let start = Instant::now();
+        loop {
+            let mut runtimes = vec![self.ctx.runtime_name.clone()];
+            runtimes.extend(
+                self.store
+                    .get_all_runtime_info()
+                    .await?
+                    .into_iter()
+                    .map(|info| info.name)
+                    .filter(|name| *name != self.ctx.runtime_name),
+            );
+
+            let missing = zenoh_flow::runtime::missing_runtimes(flow, &runtimes);
+            if missing.is_empty() {
+                return Ok((runtimes, missing));
+            }
+            let listed = missing
+                .iter()
+                .map(|runtime| runtime.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            match flow.missing_runtime {
+                MissingRuntimePolicy::Fail => {
+                    return Err(zferror!(
+                        ErrorKind::MissingRuntimes(missing),
+                        "Flow {} - Instance UUID: {} - The mapping names runtime(s) that are not running: {}",
+                        flow.flow,
+                        record_uuid,
+                        listed
+                    ))
+                }
+                MissingRuntimePolicy::Reschedule => {
+                    let nodes = zenoh_flow::runtime::unmap_runtimes(flow, &missing);
+                    log::warn!(
+                        "Flow {} - Instance UUID: {} - Runtime(s) not running: {}, rescheduling {} node(s): {}",
+                        flow.flow,
+                        record_uuid,
+                        listed,
+                        nodes.len(),
+                        nodes
+                            .iter()
+                            .map(|node| node.to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    );
+                    return Ok((runtimes, Vec::new()));
+                }
+                MissingRuntimePolicy::Attach => {
+                    log::warn!(
+                        "Flow {} - Instance UUID: {} - Runtime(s) not running: {}, their nodes are attached once they join",
+                        flow.flow,
+                        record_uuid,
+                        listed
+                    );
+                    return Ok((runtimes, missing));
+                }
+                MissingRuntimePolicy::Wait(timeout) => {
+                    if start.elapsed() >= timeout {
+                        return Err(zferror!(
+                            ErrorKind::MissingRuntimes(missing),
+                            "Flow {} - Instance UUID: {} - Runtime(s) still not running after {:?}: {}",
+                            flow.flow,
+                            record_uuid,
+                            timeout,
+                            listed
+                        ));
+                    }
+                    log::info!(
+                        "Flow {} - Instance UUID: {} - Waiting for runtime(s): {}",
+                        flow.flow,
+                        record_uuid,
+                        listed
+                    );
+                    async_std::task::sleep(MISSING_RUNTIME_POLLING_INTERVAL).await;
+                }
+            }
+        }
+    }
+
+    /// Measures the offset between the HLC of this runtime and the one of the runtime `name`,
+    /// reached through the `client`.
+    async fn measure_clock_skew(
+        &self,
+        name: RuntimeId,
+        client: &DaemonInterfaceInternalClient,
+    ) -> DaemonResult<ClockSkew> {
+        let sent = self.ctx.hlc.new_timestamp();
+        let remote = client.clock().await??;
+        let received = self.ctx.hlc.new_timestamp();
+        Ok(ClockSkew::estimate(name, &sent, &remote, &received))
+    }
+
+    /// Measures the offset between the HLC of this runtime and the one of every other runtime.
+    pub(crate) async fn get_clock_skews(&self) -> DaemonResult<Vec<ClockSkew>> {
+        let mut skews = Vec::new();
+        for info in self.store.get_all_runtime_info().await? {
+            if info.id == self.ctx.runtime_uuid {
+                continue;
+            }
+            let client = DaemonInterfaceInternalClient::new(self.ctx.session.clone(), info.id);
+            skews.push(self.measure_clock_skew(info.name, &client).await?);
+        }
+        Ok(skews)
+    }
+
     pub(crate) async fn delete_instance(&self, instance_id: Uuid) -> DaemonResult<DataFlowRecord> {
         log::info!("Delete Instance UUID: {}", instance_id);
         let record = self.store.get_flow_by_instance(&instance_id).await?;
@@ -262,13 +506,44 @@ impl Runtime {
 
         let dfr = self.store.get_flow_by_instance(&instance_id).await?;
 
-        let data_flow = DataFlow::try_new(dfr.clone(), self.ctx.clone())?;
-        let instance = DataFlowInstance::try_instantiate(data_flow, self.ctx.hlc.clone()).await?;
+        // The instances with a lower priority holding the resources needed are preempted, one at a
+        // time, until the instance can be created.
+        let instance = loop {
+            let data_flow = DataFlow::try_new(dfr.clone(), self.ctx.clone())?;
+            let instance = DataFlowInstance::try_instantiate(data_flow, self.ctx.hlc.clone()).await;
+            if let Err(e) = &instance {
+                let is_unavailable = matches!(
+                    e.downcast_ref::<ZFError>().map(ZFError::get_kind),
+                    Some(ErrorKind::ResourceUnavailable)
+                );
+                if is_unavailable {
+                    if let Some((victim, policy)) = self.preemption_candidate(dfr.priority).await {
+                        log::warn!(
+                            "Preempting ({}) Instance UUID {} for Instance UUID {}: {:?}",
+                            policy,
+                            victim,
+                            instance_id,
+                            e
+                        );
+                        self.preempt(victim, policy).await?;
+                        continue;
+                    }
+                }
+            }
+            break instance?;
+        };
 
+        let autoscaling_interval = instance.autoscaling_interval();
         let mut self_state = self.state.lock().await;
-        self_state.graphs.insert(dfr.uuid, instance);
+        self_state
+            .graphs
+            .insert(dfr.uuid, Arc::new(Mutex::new(instance)));
         drop(self_state);
 
+        if let Some(interval) = autoscaling_interval {
+            self.spawn_autoscaler(dfr.uuid, interval);
+        }
+
         self.store
             .add_runtime_flow(&self.ctx.runtime_uuid, &dfr)
             .await?;
@@ -278,14 +553,272 @@ impl Runtime {
         Ok(dfr)
     }
 
+    /// Spawns the task checking, every `interval`, the load of the autoscaled Operators of the
+    /// instance and rescaling them. The task ends once the instance is cleaned.
+    fn spawn_autoscaler(&self, instance_id: Uuid, interval: Duration) {
+        let runtime = self.clone();
+        async_std::task::spawn(async move {
+            loop {
+                async_std::task::sleep(interval).await;
+                // Only the instance is locked while it is rescaled.
+                let instance = match runtime.instance(instance_id).await {
+                    Ok(instance) => instance,
+                    Err(_) => return,
+                };
+                let rescaled = instance.lock().await.autoscale().await;
+                for (node_id, replicas) in rescaled {
+                    log::info!(
+                        "Autoscaled < {} > of Instance UUID {} to {} replicas",
+                        node_id,
+                        instance_id,
+                        replicas
+                    );
+                }
+            }
+        });
+    }
+
+    /// Spawns the task tearing down the instance once it expires (see [ExpiryDescriptor]), after a
+    /// warning is logged and added to the event log. The task ends once the instance is deleted.
+    ///
+    /// The lifetime of the instance is counted from now, i.e. from its creation.
+    fn spawn_expirer(&self, instance_id: Uuid, expiry: ExpiryDescriptor) {
+        let runtime = self.clone();
+        let created = Instant::now();
+        async_std::task::spawn(async move {
+            let mut warned = false;
+            loop {
+                async_std::task::sleep(EXPIRY_POLLING_INTERVAL).await;
+                if runtime
+                    .store
+                    .get_flow_by_instance(&instance_id)
+                    .await
+                    .is_err()
+                {
+                    return;
+                }
+
+                // The instance is deemed active if its activity could not be checked.
+                let idle_time = match expiry.idle {
+                    Some(_) => match runtime.instance_idle_time(instance_id).await {
+                        Ok(idle_time) => idle_time,
+                        Err(e) => {
+                            log::warn!(
+                                "Unable to check the activity of Instance UUID {}: {:?}",
+                                instance_id,
+                                e
+                            );
+                            Duration::ZERO
+                        }
+                    },
+                    None => Duration::ZERO,
+                };
+
+                let (remaining, reason) = match expiry.remaining(created.elapsed(), idle_time) {
+                    Some(remaining) => remaining,
+                    None => return,
+                };
+
+                if remaining.is_zero() {
+                    log::warn!(
+                        "Instance UUID {} expired ({}), tearing it down",
+                        instance_id,
+                        reason
+                    );
+                    let result = match runtime.teardown(instance_id).await {
+                        Ok(_) => EventResult::Done,
+                        Err(e) => {
+                            log::error!(
+                                "Error while tearing down the expired Instance UUID {}: {:?}",
+                                instance_id,
+                                e
+                            );
+                            EventResult::Failed(format!("{e:?}"))
+                        }
+                    };
+                    runtime
+                        .store_event(instance_id, EventAction::Expire, result)
+                        .await;
+                    return;
+                }
+
+                // An idle instance that became active again is warned again when it is idle.
+                if remaining > expiry.warning {
+                    warned = false;
+                } else if !warned {
+                    warned = true;
+                    log::warn!(
+                        "Instance UUID {} expires ({}) in {:?}",
+                        instance_id,
+                        reason,
+                        remaining
+                    );
+                    runtime
+                        .store_event(instance_id, EventAction::ExpiryWarning, EventResult::Done)
+                        .await;
+                }
+            }
+        });
+    }
+
+    /// Returns the time elapsed since a node of the instance last sent a message, on any of the
+    /// runtimes involved.
+    async fn instance_idle_time(&self, instance_id: Uuid) -> DaemonResult<Duration> {
+        let mut idle_times = vec![];
+        for rt in self.store.get_flow_instance_runtimes(&instance_id).await? {
+            let idle_time = if rt == self.ctx.runtime_uuid {
+                self.idle_time(instance_id).await?
+            } else {
+                DaemonInterfaceInternalClient::new(self.ctx.session.clone(), rt)
+                    .idle_time(instance_id)
+                    .await??
+            };
+            idle_times.push(idle_time);
+        }
+        Ok(idle_times.into_iter().min().unwrap_or_default())
+    }
+
+    /// Adds the `action` performed by this runtime on the instance, without being requested, to
+    /// the event log.
+    async fn store_event(&self, instance_id: Uuid, action: EventAction, result: EventResult) {
+        let event = Event {
+            timestamp: self.ctx.hlc.new_timestamp(),
+            actor: self.ctx.runtime_name.clone(),
+            runtime: self.ctx.runtime_uuid,
+            action,
+            instance_id,
+            node: None,
+            result,
+        };
+        if let Err(e) = self
+            .store
+            .add_event(&self.ctx.runtime_uuid, &Uuid::new_v4(), &event)
+            .await
+        {
+            log::error!(
+                "Unable to add {:?} of Instance UUID {} to the event log: {:?}",
+                action,
+                instance_id,
+                e
+            );
+        }
+    }
+
+    /// Returns the instance running on this runtime, holding GPUs, whose priority is the lowest
+    /// below `priority` and whose policy allows preempting it.
+    async fn preemption_candidate(&self, priority: u32) -> Option<(Uuid, PreemptionPolicy)> {
+        let instances = self
+            .state
+            .lock()
+            .await
+            .graphs
+            .iter()
+            .map(|(instance_id, instance)| (*instance_id, instance.clone()))
+            .collect::<Vec<_>>();
+
+        let mut priorities = vec![];
+        for (instance_id, instance) in instances {
+            let instance = instance.lock().await;
+            if instance.holds_gpus() {
+                priorities.push((instance_id, instance.priority()));
+            }
+        }
+
+        priorities
+            .into_iter()
+            .filter(|(_, (other_priority, policy))| {
+                *other_priority < priority && *policy != PreemptionPolicy::Never
+            })
+            .min_by_key(|(_, (other_priority, _))| *other_priority)
+            .map(|(instance_id, (_, policy))| (instance_id, policy))
+    }
+
+    /// Stops and deletes the instance `instance_id` to free the resources it holds on this
+    /// runtime. If the `policy` is to migrate it, its nodes running on this runtime are moved to
+    /// another runtime and it is started again.
+    async fn preempt(&self, instance_id: Uuid, policy: PreemptionPolicy) -> DaemonResult<()> {
+        let record = self.teardown(instance_id).await?;
+        if policy == PreemptionPolicy::Migrate {
+            // The instance to create must not fail because the preempted one could not be moved.
+            if let Err(e) = self.migrate(record).await {
+                log::error!(
+                    "Error while migrating Instance UUID {}, it remains stopped: {:?}",
+                    instance_id,
+                    e
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Moves the nodes of the stopped instance `record` running on this runtime to another
+    /// runtime, and starts it again.
+    async fn migrate(&self, mut record: DataFlowRecord) -> DaemonResult<()> {
+        let instance_id = record.uuid;
+        let target = self
+            .store
+            .get_all_runtime_info()
+            .await?
+            .into_iter()
+            .find(|info| info.name != self.ctx.runtime_name);
+        let target = match target {
+            Some(target) => target,
+            None => {
+                log::warn!(
+                    "No other runtime to migrate Instance UUID {} to, it remains stopped",
+                    instance_id
+                );
+                return Ok(());
+            }
+        };
+
+        record.relocate(&self.ctx.runtime_name, &target.name);
+        self.store.add_runtime_flow(&target.id, &record).await?;
+        for runtime in record.runtimes() {
+            let info = self.store.get_runtime_info_by_name(&runtime).await?;
+            DaemonInterfaceInternalClient::new(self.ctx.session.clone(), info.id)
+                .prepare(instance_id)
+                .await??;
+        }
+        self.start_instance(instance_id).await?;
+
+        log::info!(
+            "Migrated Instance UUID {} from {} to {}",
+            instance_id,
+            self.ctx.runtime_name,
+            target.name
+        );
+
+        Ok(())
+    }
+
     pub(crate) async fn clean(&self, instance_id: Uuid) -> DaemonResult<DataFlowRecord> {
         log::info!("Cleaning for Instance UUID: {}", instance_id);
 
-        let mut _state = self.state.lock().await;
-        let data = _state.graphs.remove(&instance_id);
+        let data = self.state.lock().await.graphs.remove(&instance_id);
         match data {
-            Some(_dfi) => {
-                // Calling finalize on all nodes of a the graph.
+            Some(mut instance) => {
+                // The operations in progress on the instance (e.g. a rescaling) are waited for.
+                let dfi = loop {
+                    match Arc::try_unwrap(instance) {
+                        Ok(dfi) => break dfi.into_inner(),
+                        Err(shared) => {
+                            drop(shared.lock().await);
+                            instance = shared;
+                            async_std::task::yield_now().await;
+                        }
+                    }
+                };
+
+                // Stopping (if still running) and cleaning all the nodes of the graph.
+                if let Err(e) = dfi.stop().await {
+                    log::error!(
+                        "Error while cleaning Instance UUID {}: {:?}",
+                        instance_id,
+                        e
+                    );
+                }
 
                 let record = self
                     .store
@@ -406,67 +939,69 @@ impl Runtime {
             instance_id
         );
 
-        let mut _state = self.state.lock().await;
+        let instance = self.instance(instance_id).await?;
+        let mut instance = instance.lock().await;
+        // The state is locked while the status of the runtime is updated.
+        let _state = self.state.lock().await;
 
         let mut rt_status = self
             .store
             .get_runtime_status(&self.ctx.runtime_uuid)
             .await?;
 
-        match _state.graphs.get_mut(&instance_id) {
-            Some(instance) => {
-                for id in instance.get_sinks() {
-                    instance.start_node(&id)?;
-                    rt_status.running_sinks += 1;
-                }
+        for id in instance.get_sinks() {
+            instance.start_node(&id)?;
+            rt_status.running_sinks += 1;
+        }
 
-                for id in instance.get_operators() {
-                    instance.start_node(&id)?;
-                    rt_status.running_operators += 1;
-                }
+        for id in instance.get_operators() {
+            instance.start_node(&id)?;
+            rt_status.running_operators += 1;
+        }
 
-                for id in instance.get_connectors() {
-                    instance.start_node(&id)?;
-                    rt_status.running_connectors += 1;
-                }
+        for id in instance.get_connectors() {
+            instance.start_node(&id)?;
+            rt_status.running_connectors += 1;
+        }
 
-                self.store
-                    .add_runtime_status(&self.ctx.runtime_uuid, &rt_status)
-                    .await?;
+        self.store
+            .add_runtime_status(&self.ctx.runtime_uuid, &rt_status)
+            .await?;
 
-                Ok(())
-            }
-            None => Err(zferror!(ErrorKind::InstanceNotFound(instance_id))),
-        }
+        Ok(())
     }
 
     pub(crate) async fn start_sources(&self, instance_id: Uuid) -> DaemonResult<()> {
         log::info!("Starting sources for Instance UUID: {}", instance_id);
 
-        let mut _state = self.state.lock().await;
+        // The lock is not held while waiting for the external services the instance depends on.
+        let instance = self.instance(instance_id).await?;
+        let readiness = instance.lock().await.readiness().cloned();
+        if let Some(readiness) = readiness {
+            wait_until_ready(&self.ctx.session, &readiness).await?;
+        }
+
+        let mut instance = instance.lock().await;
+        // The state is locked while the status of the runtime is updated.
+        let _state = self.state.lock().await;
 
         let mut rt_status = self
             .store
             .get_runtime_status(&self.ctx.runtime_uuid)
             .await?;
 
-        match _state.graphs.get_mut(&instance_id) {
-            Some(instance) => {
-                for id in instance.get_sources() {
-                    instance.start_node(&id)?;
-                    rt_status.running_sources += 1;
-                }
+        for id in instance.get_sources() {
+            instance.start_node(&id)?;
+            rt_status.running_sources += 1;
+        }
 
-                rt_status.running_flows += 1;
+        rt_status.running_flows += 1;
 
-                self.store
-                    .add_runtime_status(&self.ctx.runtime_uuid, &rt_status)
-                    .await?;
+        self.store
+            .add_runtime_status(&self.ctx.runtime_uuid, &rt_status)
+            .await?;
 
-                Ok(())
-            }
-            None => Err(zferror!(ErrorKind::InstanceNotFound(instance_id))),
-        }
+        Ok(())
     }
 
     pub(crate) async fn stop_nodes(&self, instance_id: Uuid) -> DaemonResult<()> {
@@ -475,96 +1010,218 @@ impl Runtime {
             instance_id
         );
 
-        let mut _state = self.state.lock().await;
+        let instance = self.instance(instance_id).await?;
+        let mut instance = instance.lock().await;
+        // The state is locked while the status of the runtime is updated.
+        let _state = self.state.lock().await;
 
         let mut rt_status = self
             .store
             .get_runtime_status(&self.ctx.runtime_uuid)
             .await?;
 
-        match _state.graphs.get_mut(&instance_id) {
-            Some(instance) => {
-                for id in instance.get_sinks() {
-                    instance.stop_node(&id).await?;
-                    rt_status.running_sinks -= 1;
-                }
+        for id in instance.get_sinks() {
+            instance.stop_node(&id).await?;
+            rt_status.running_sinks -= 1;
+        }
 
-                for id in instance.get_operators() {
-                    instance.stop_node(&id).await?;
-                    rt_status.running_operators -= 1;
-                }
+        for id in instance.get_operators() {
+            instance.stop_node(&id).await?;
+            rt_status.running_operators -= 1;
+        }
 
-                for id in instance.get_connectors() {
-                    instance.stop_node(&id).await?;
-                    rt_status.running_connectors -= 1;
-                }
+        for id in instance.get_connectors() {
+            instance.stop_node(&id).await?;
+            rt_status.running_connectors -= 1;
+        }
 
-                self.store
-                    .add_runtime_status(&self.ctx.runtime_uuid, &rt_status)
-                    .await?;
+        self.store
+            .add_runtime_status(&self.ctx.runtime_uuid, &rt_status)
+            .await?;
 
-                Ok(())
-            }
-            None => Err(zferror!(ErrorKind::InstanceNotFound(instance_id))),
-        }
+        Ok(())
     }
 
     pub(crate) async fn stop_sources(&self, instance_id: Uuid) -> DaemonResult<()> {
         log::info!("Stopping sources for Instance UUID: {}", instance_id);
 
-        let mut _state = self.state.lock().await;
+        let instance = self.instance(instance_id).await?;
+        let mut instance = instance.lock().await;
+        // The state is locked while the status of the runtime is updated.
+        let _state = self.state.lock().await;
         let mut rt_status = self
             .store
             .get_runtime_status(&self.ctx.runtime_uuid)
             .await?;
 
-        match _state.graphs.get_mut(&instance_id) {
-            Some(instance) => {
-                for id in instance.get_sources() {
-                    instance.stop_node(&id).await?;
-                    rt_status.running_sources -= 1;
-                }
+        for id in instance.get_sources() {
+            instance.stop_node(&id).await?;
+            rt_status.running_sources -= 1;
+        }
 
-                rt_status.running_flows -= 1;
+        rt_status.running_flows -= 1;
 
-                self.store
-                    .add_runtime_status(&self.ctx.runtime_uuid, &rt_status)
-                    .await?;
+        self.store
+            .add_runtime_status(&self.ctx.runtime_uuid, &rt_status)
+            .await?;
 
-                Ok(())
-            }
-            None => Err(zferror!(ErrorKind::InstanceNotFound(instance_id))),
-        }
+        Ok(())
     }
 
     #[allow(dead_code)]
     pub(crate) async fn start_node(&self, instance_id: Uuid, node: String) -> DaemonResult<()> {
-        let mut _state = self.state.lock().await;
+        let instance = self.instance(instance_id).await?;
+        let mut instance = instance.lock().await;
         // let mut rt_status = self
         //     .store
         //     .get_runtime_status(&self.ctx.runtime_uuid)
         //     .await?;
 
-        match _state.graphs.get_mut(&instance_id) {
-            Some(instance) => Ok(instance.start_node(&node.into())?),
-            None => Err(zferror!(ErrorKind::InstanceNotFound(instance_id))),
-        }
+        Ok(instance.start_node(&node.into())?)
     }
 
     #[allow(dead_code)]
     pub(crate) async fn stop_node(&self, instance_id: Uuid, node: String) -> DaemonResult<()> {
-        let mut _state = self.state.lock().await;
+        let instance = self.instance(instance_id).await?;
+        let mut instance = instance.lock().await;
         // let mut rt_status = self
         //     .store
         //     .get_runtime_status(&self.ctx.runtime_uuid)
         //     .await?;
 
-        match _state.graphs.get_mut(&instance_id) {
-            Some(instance) => Ok(instance.stop_node(&node.into()).await?),
-            None => Err(zferror!(ErrorKind::InstanceNotFound(instance_id))),
+        Ok(instance.stop_node(&node.into()).await?)
+    }
+
+    pub(crate) async fn restart_node(&self, instance_id: Uuid, node: String) -> DaemonResult<()> {
+        let instance = self.instance(instance_id).await?;
+        let mut instance = instance.lock().await;
+
+        Ok(instance.restart_node(&node.into()).await?)
+    }
+
+    pub(crate) async fn tap(
+        &self,
+        instance_id: Uuid,
+        node: String,
+        port: String,
+        decode: bool,
+    ) -> DaemonResult<String> {
+        let instance = self.instance(instance_id).await?;
+        let mut instance = instance.lock().await;
+
+        Ok(instance.tap(&node.into(), &port.into(), decode).await?)
+    }
+
+    pub(crate) async fn untap(
+        &self,
+        instance_id: Uuid,
+        node: String,
+        port: String,
+    ) -> DaemonResult<bool> {
+        let instance = self.instance(instance_id).await?;
+        let mut instance = instance.lock().await;
+
+        Ok(instance.untap(&node.into(), &port.into()).await)
+    }
+
+    pub(crate) async fn record(
+        &self,
+        instance_id: Uuid,
+        enabled: bool,
+    ) -> DaemonResult<Vec<RecordingManifest>> {
+        let mut manifests = vec![];
+        for rt in self.store.get_flow_instance_runtimes(&instance_id).await? {
+            let manifest = if rt == self.ctx.runtime_uuid {
+                self.record_session(instance_id, enabled).await?
+            } else {
+                DaemonInterfaceInternalClient::new(self.ctx.session.clone(), rt)
+                    .record_session(instance_id, enabled)
+                    .await??
+            };
+            manifests.push(manifest);
+        }
+
+        Ok(manifests)
+    }
+
+    pub(crate) async fn record_session(
+        &self,
+        instance_id: Uuid,
+        enabled: bool,
+    ) -> DaemonResult<RecordingManifest> {
+        let instance = self.instance(instance_id).await?;
+        let mut instance = instance.lock().await;
+
+        if enabled {
+            Ok(instance
+                .start_recording_all(RecordingLabels::default())
+                .await?)
+        } else {
+            Ok(instance.stop_recording_all().await?)
         }
     }
 
+    pub(crate) async fn get_sent_messages(
+        &self,
+        instance_id: Uuid,
+    ) -> DaemonResult<Vec<(OutputDescriptor, u64)>> {
+        let mut sent = vec![];
+        for rt in self.store.get_flow_instance_runtimes(&instance_id).await? {
+            if rt == self.ctx.runtime_uuid {
+                sent.extend(self.sent_messages(instance_id).await?);
+            } else {
+                sent.extend(
+                    DaemonInterfaceInternalClient::new(self.ctx.session.clone(), rt)
+                        .sent_messages(instance_id)
+                        .await??,
+                );
+            }
+        }
+
+        Ok(sent)
+    }
+
+    pub(crate) async fn sent_messages(
+        &self,
+        instance_id: Uuid,
+    ) -> DaemonResult<Vec<(OutputDescriptor, u64)>> {
+        let instance = self.instance(instance_id).await?;
+        let instance = instance.lock().await;
+
+        Ok(instance.sent_messages().into_iter().collect())
+    }
+
+    pub(crate) async fn breakpoint(
+        &self,
+        instance_id: Uuid,
+        node: String,
+        port: Option<String>,
+        enabled: bool,
+    ) -> DaemonResult<()> {
+        let instance = self.instance(instance_id).await?;
+        let instance = instance.lock().await;
+
+        let node = node.into();
+        let port = port.map(PortId::from);
+        if enabled {
+            Ok(instance.set_breakpoint(&node, port.as_ref())?)
+        } else {
+            Ok(instance.remove_breakpoint(&node, port.as_ref())?)
+        }
+    }
+
+    pub(crate) async fn resume(
+        &self,
+        instance_id: Uuid,
+        node: String,
+        step: bool,
+    ) -> DaemonResult<()> {
+        let instance = self.instance(instance_id).await?;
+        let instance = instance.lock().await;
+
+        Ok(instance.resume(&node.into(), step)?)
+    }
+
     // pub(crate) async fn start_record(&self, instance_id: Uuid, source_id: NodeId) -> DaemonResult<String> {
     //     let mut _state = self.state.lock().await;
     //     let mut rt_status = self
@@ -643,6 +1300,144 @@ impl Runtime {
     //     }
     // }
 
+    pub(crate) async fn compose(
+        &self,
+        from_flow: String,
+        output: OutputDescriptor,
+        to_flow: String,
+        input: InputDescriptor,
+    ) -> DaemonResult<String> {
+        let from = self.get_instance_by_flow(&from_flow).await?;
+        if !from.exposed.contains(&output) {
+            return Err(zferror!(
+                ErrorKind::PortNotFound((output.node.clone(), output.output.clone())),
+                "Output < {} > of the flow < {} > is not exposed",
+                output,
+                from_flow
+            ));
+        }
+
+        let to = self.get_instance_by_flow(&to_flow).await?;
+        log::info!("Composing < {from_flow}:{output} > => < {to_flow}:{input} >");
+        match self.get_node_client(&to, &input.node).await? {
+            Some(client) => Ok(client
+                .connect_import(to.uuid, input, from.uuid, output)
+                .await??),
+            None => self.connect_import(to.uuid, input, from.uuid, output).await,
+        }
+    }
+
+    pub(crate) async fn decompose(
+        &self,
+        to_flow: String,
+        input: InputDescriptor,
+    ) -> DaemonResult<Option<String>> {
+        let to = self.get_instance_by_flow(&to_flow).await?;
+        log::info!("Decomposing < {to_flow}:{input} >");
+        match self.get_node_client(&to, &input.node).await? {
+            Some(client) => Ok(client.disconnect_import(to.uuid, input).await??),
+            None => self.disconnect_import(to.uuid, input).await,
+        }
+    }
+
+    pub(crate) async fn connect_import(
+        &self,
+        instance_id: Uuid,
+        input: InputDescriptor,
+        from_instance: Uuid,
+        output: OutputDescriptor,
+    ) -> DaemonResult<String> {
+        let instance = self.instance(instance_id).await?;
+        let mut instance = instance.lock().await;
+
+        Ok(instance
+            .connect_import(&input, from_instance, &output)
+            .await?)
+    }
+
+    pub(crate) async fn disconnect_import(
+        &self,
+        instance_id: Uuid,
+        input: InputDescriptor,
+    ) -> DaemonResult<Option<String>> {
+        let instance = self.instance(instance_id).await?;
+        let mut instance = instance.lock().await;
+
+        Ok(instance.disconnect_import(&input).await)
+    }
+
+    pub(crate) async fn idle_time(&self, instance_id: Uuid) -> DaemonResult<Duration> {
+        let instance = self.instance(instance_id).await?;
+        let instance = instance.lock().await;
+
+        Ok(instance.idle_time())
+    }
+
+    /// Returns the instance `instance_id`, to be locked once the state is unlocked (see [RTState]).
+    async fn instance(&self, instance_id: Uuid) -> DaemonResult<Arc<Mutex<DataFlowInstance>>> {
+        self.state
+            .lock()
+            .await
+            .graphs
+            .get(&instance_id)
+            .cloned()
+            .ok_or_else(|| zferror!(ErrorKind::InstanceNotFound(instance_id)))
+    }
+
+    /// Returns the record of the only instance of the flow `flow`.
+    async fn get_instance_by_flow(&self, flow: &str) -> DaemonResult<DataFlowRecord> {
+        let mut instances = self
+            .store
+            .get_all_instances()
+            .await?
+            .into_iter()
+            .filter(|record| record.flow == flow)
+            .collect::<Vec<_>>();
+        // Each daemon involved in an instance stores its record.
+        instances.sort_by_key(|record| record.uuid);
+        instances.dedup_by_key(|record| record.uuid);
+
+        match instances.len() {
+            1 => Ok(instances.remove(0)),
+            0 => Err(zferror!(
+                ErrorKind::NotFound,
+                "No instance of the flow < {} >",
+                flow
+            )),
+            count => Err(zferror!(
+                ErrorKind::Duplicate,
+                "{} instances of the flow < {} >, expected one",
+                count,
+                flow
+            )),
+        }
+    }
+
+    /// Returns a client to the daemon running the node `node` of the instance `record`, or `None`
+    /// if it is this daemon.
+    async fn get_node_client(
+        &self,
+        record: &DataFlowRecord,
+        node: &NodeId,
+    ) -> DaemonResult<Option<DaemonInterfaceInternalClient>> {
+        let runtime = record
+            .operators
+            .get(node)
+            .map(|operator| &operator.runtime)
+            .or_else(|| record.sinks.get(node).map(|sink| &sink.runtime))
+            .ok_or_else(|| zferror!(ErrorKind::NodeNotFound(node.clone())))?;
+
+        if *runtime == self.ctx.runtime_name {
+            return Ok(None);
+        }
+
+        let info = self.store.get_runtime_info_by_name(runtime).await?;
+        Ok(Some(DaemonInterfaceInternalClient::new(
+            self.ctx.session.clone(),
+            info.id,
+        )))
+    }
+
     pub(crate) async fn notify_runtime(
         &self,
         _instance_id: Uuid,
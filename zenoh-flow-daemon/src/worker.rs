@@ -18,8 +18,8 @@ use async_trait::async_trait;
 use flume::Receiver;
 use uhlc::HLC;
 
-use zenoh_flow::runtime::JobKind;
 use zenoh_flow::runtime::{worker_pool::WorkerTrait, Job};
+use zenoh_flow::runtime::{Event, JobKind};
 use zenoh_flow::zfresult::ZFError;
 use zenoh_flow::Result as ZFResult;
 
@@ -63,6 +63,21 @@ impl Worker {
             .store
             .add_failed_job(&self.runtime.ctx.runtime_uuid, job)
             .await?;
+        self.store_event(job).await
+    }
+
+    /// Adds the outcome of the `job` to the event log.
+    async fn store_event(&self, job: &Job) -> ZFResult<()> {
+        if let Some(event) = Event::from_job(
+            job,
+            self.runtime.ctx.runtime_name.clone(),
+            self.runtime.ctx.runtime_uuid,
+        ) {
+            self.runtime
+                .store
+                .add_event(&self.runtime.ctx.runtime_uuid, job.get_id(), &event)
+                .await?;
+        }
         Ok(())
     }
 }
@@ -277,6 +292,30 @@ impl WorkerTrait for Worker {
                         }
                     }
                 }
+                JobKind::RestartNode(inst_uuid, node_id) => {
+                    log::info!(
+                        "[Worker: {}] Job: {} Restart Node {} Instance {}",
+                        self.id,
+                        job.get_id(),
+                        node_id,
+                        inst_uuid
+                    );
+
+                    match self.runtime.restart_node(*inst_uuid, node_id.clone()).await {
+                        Ok(_) => {
+                            log::info!(
+                                "[Worker: {}] Restarted Node {}  Instance UUID: {}",
+                                self.id,
+                                node_id,
+                                inst_uuid
+                            );
+                        }
+                        Err(e) => {
+                            self.store_error(&mut job, e).await?;
+                            continue;
+                        }
+                    }
+                }
             }
 
             job.done(self.hlc.new_timestamp());
@@ -284,6 +323,7 @@ impl WorkerTrait for Worker {
                 .store
                 .add_done_job(&self.runtime.ctx.runtime_uuid, &job)
                 .await?;
+            self.store_event(&job).await?;
         }
         Ok(())
     }
@@ -59,13 +59,13 @@ pub fn export_source(_: TokenStream, input: TokenStream) -> TokenStream {
 
         #[doc(hidden)]
         #[no_mangle]
-        pub static _zf_export_source: zenoh_flow::runtime::dataflow::loader::NodeDeclaration<
-        zenoh_flow::runtime::dataflow::node::SourceFn,
-        > = zenoh_flow::runtime::dataflow::loader::NodeDeclaration::<
-        zenoh_flow::runtime::dataflow::node::SourceFn,
+        pub static _zf_export_source: zenoh_flow::__private::NodeDeclaration<
+        zenoh_flow::__private::SourceFn,
+        > = zenoh_flow::__private::NodeDeclaration::<
+        zenoh_flow::__private::SourceFn,
         > {
-            rustc_version: zenoh_flow::runtime::dataflow::loader::RUSTC_VERSION,
-            core_version: zenoh_flow::runtime::dataflow::loader::CORE_VERSION,
+            rustc_version: zenoh_flow::__private::RUSTC_VERSION,
+            core_version: zenoh_flow::__private::CORE_VERSION,
             constructor: |context: zenoh_flow::types::Context,
                           configuration: Option<zenoh_flow::types::Configuration>,
                           outputs: zenoh_flow::io::Outputs| {
@@ -122,13 +122,13 @@ pub fn export_sink(_: TokenStream, input: TokenStream) -> TokenStream {
 
         #[doc(hidden)]
         #[no_mangle]
-        pub static _zf_export_sink: zenoh_flow::runtime::dataflow::loader::NodeDeclaration<
-        zenoh_flow::runtime::dataflow::node::SinkFn,
-        > = zenoh_flow::runtime::dataflow::loader::NodeDeclaration::<
-        zenoh_flow::runtime::dataflow::node::SinkFn,
+        pub static _zf_export_sink: zenoh_flow::__private::NodeDeclaration<
+        zenoh_flow::__private::SinkFn,
+        > = zenoh_flow::__private::NodeDeclaration::<
+        zenoh_flow::__private::SinkFn,
         > {
-            rustc_version: zenoh_flow::runtime::dataflow::loader::RUSTC_VERSION,
-            core_version: zenoh_flow::runtime::dataflow::loader::CORE_VERSION,
+            rustc_version: zenoh_flow::__private::RUSTC_VERSION,
+            core_version: zenoh_flow::__private::CORE_VERSION,
             constructor: |context: zenoh_flow::types::Context,
                           configuration: Option<zenoh_flow::types::Configuration>,
                           mut inputs: zenoh_flow::io::Inputs| {
@@ -194,13 +194,13 @@ pub fn export_operator(_: TokenStream, input: TokenStream) -> TokenStream {
 
         #[doc(hidden)]
         #[no_mangle]
-        pub static _zf_export_operator: zenoh_flow::runtime::dataflow::loader::NodeDeclaration<
-        zenoh_flow::runtime::dataflow::node::OperatorFn,
-        > = zenoh_flow::runtime::dataflow::loader::NodeDeclaration::<
-        zenoh_flow::runtime::dataflow::node::OperatorFn,
+        pub static _zf_export_operator: zenoh_flow::__private::NodeDeclaration<
+        zenoh_flow::__private::OperatorFn,
+        > = zenoh_flow::__private::NodeDeclaration::<
+        zenoh_flow::__private::OperatorFn,
         > {
-            rustc_version: zenoh_flow::runtime::dataflow::loader::RUSTC_VERSION,
-            core_version: zenoh_flow::runtime::dataflow::loader::CORE_VERSION,
+            rustc_version: zenoh_flow::__private::RUSTC_VERSION,
+            core_version: zenoh_flow::__private::CORE_VERSION,
             constructor: |context: zenoh_flow::types::Context,
                           configuration: Option<zenoh_flow::types::Configuration>,
                           mut inputs: zenoh_flow::io::Inputs,
@@ -214,3 +214,131 @@ pub fn export_operator(_: TokenStream, input: TokenStream) -> TokenStream {
     };
     gen.into()
 }
+
+/// The `export_transform` attribute macro is provided to allow the users in exporting the
+/// transform of a redaction rule: a function receiving the serialized payload of a message and
+/// returning the one to publish on a debug tap or to store in a recording.
+///
+/// ## Example
+///
+/// ```no_compile
+/// use zenoh_flow::prelude::*;
+///
+/// #[export_transform]
+/// pub fn strip_gps(payload: &[u8]) -> Result<Vec<u8>> {
+///     todo!()
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn export_transform(_: TokenStream, input: TokenStream) -> TokenStream {
+    let function = parse_macro_input!(input as syn::ItemFn);
+    let ident = &function.sig.ident;
+
+    let gen = quote! {
+
+        #function
+
+        #[doc(hidden)]
+        #[no_mangle]
+        pub static _zf_export_transform: zenoh_flow::__private::TransformDeclaration =
+            zenoh_flow::__private::TransformDeclaration {
+                rustc_version: zenoh_flow::__private::RUSTC_VERSION,
+                core_version: zenoh_flow::__private::CORE_VERSION,
+                transform: #ident,
+            };
+    };
+    gen.into()
+}
+
+/// The `UnionData` derive macro implements `zenoh_flow::types::UnionData` for an enumeration, the
+/// data of a union port: the variants, each holding a single value, follow the data types of the
+/// union in order. The values are serialized with `bincode`.
+///
+/// ## Example
+///
+/// ```no_compile
+/// use zenoh_flow::prelude::*;
+///
+/// // output_types:
+/// //   Reading: [my.company.Temperature@1.0, my.company.Humidity@1.0]
+/// #[derive(UnionData)]
+/// pub enum Reading {
+///     Temperature(f64),
+///     Humidity(u8),
+/// }
+/// ```
+#[proc_macro_derive(UnionData)]
+pub fn derive_union_data(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    let ident = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    let variants = match &ast.data {
+        syn::Data::Enum(data) if !data.variants.is_empty() => &data.variants,
+        _ => {
+            return syn::Error::new_spanned(
+                ident,
+                "`UnionData` can only be derived for an enumeration with at least one variant",
+            )
+            .to_compile_error()
+            .into()
+        }
+    };
+
+    if let Some(variant) = variants.iter().find(|variant| match &variant.fields {
+        syn::Fields::Unnamed(fields) => fields.unnamed.len() != 1,
+        _ => true,
+    }) {
+        return syn::Error::new_spanned(
+            variant,
+            "The variants of a `UnionData` must each hold a single value",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let names = variants
+        .iter()
+        .map(|variant| &variant.ident)
+        .collect::<Vec<_>>();
+    let indexes = (0..names.len() as u32).collect::<Vec<_>>();
+
+    let gen = quote! {
+        impl #impl_generics zenoh_flow::types::UnionData for #ident #ty_generics #where_clause {
+            fn variant(&self) -> u32 {
+                match self {
+                    #(Self::#names(_) => #indexes,)*
+                }
+            }
+
+            fn serialize_variant(
+                &self,
+                buffer: &mut std::vec::Vec<u8>,
+            ) -> zenoh_flow::__private::anyhow::Result<()> {
+                match self {
+                    #(Self::#names(value) => {
+                        zenoh_flow::__private::bincode::serialize_into(buffer, value)?
+                    })*
+                }
+                Ok(())
+            }
+
+            fn deserialize_variant(
+                variant: u32,
+                bytes: &[u8],
+            ) -> zenoh_flow::__private::anyhow::Result<Self> {
+                match variant {
+                    #(#indexes => Ok(Self::#names(
+                        zenoh_flow::__private::bincode::deserialize(bytes)?
+                    )),)*
+                    _ => Err(zenoh_flow::__private::anyhow::anyhow!(
+                        "Unknown variant {} of the union {}",
+                        variant,
+                        stringify!(#ident)
+                    )),
+                }
+            }
+        }
+    };
+    gen.into()
+}
@@ -0,0 +1,39 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The SDK to implement a Zenoh-Flow node: the only dependency of a node crate.
+//!
+//! Its [prelude] provides the traits, the types and the export macros needed to implement a
+//! Source, an Operator or a Sink. A new node crate can be generated from the `template` directory
+//! of this crate:
+//!
+//! ```text
+//! cargo generate --git https://github.com/eclipse-zenoh/zenoh-flow zenoh-flow-node-sdk/template
+//! ```
+//!
+//! A built node can then be tested, outside of any data flow, by feeding it the inputs of a YAML
+//! fixture (see [testing]):
+//!
+//! ```text
+//! cargo zenoh-flow build && cargo zenoh-flow test fixture.yml
+//! ```
+
+pub mod testing;
+
+pub mod prelude {
+    // The export macros generate paths starting with `zenoh_flow`.
+    pub use async_trait::async_trait;
+    pub use zenoh_flow;
+    pub use zenoh_flow::prelude::*;
+}
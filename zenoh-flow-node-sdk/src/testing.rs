@@ -0,0 +1,263 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Run a built node, alone, on the synthetic inputs of a fixture.
+//!
+//! The node is loaded from its descriptor, as a daemon would, in an embedded runtime (see
+//! [`zenoh_flow::runtime::embedded`]): a Host Source sends the inputs of the fixture, in order, on
+//! the inputs of the node and a Host Sink collects what the node sends on its outputs.
+//!
+//! ```yaml
+//! configuration:
+//!   factor: 2
+//! inputs:
+//!   - port: in
+//!     text: "hello"
+//!   - port: in
+//!     json: { "value": 21 }
+//!   - port: in
+//!     bytes: [0, 1, 2]
+//! # How long to wait for an output before considering the node is done, 1s by default.
+//! timeout: 500ms
+//! # Stop as soon as that many outputs were received.
+//! max_outputs: 3
+//! ```
+
+use serde::Deserialize;
+use std::path::Path;
+use std::time::Duration;
+use zenoh_flow::model::descriptor::DataFlowDescriptor;
+use zenoh_flow::model::registry::NodeKind;
+use zenoh_flow::prelude::{zferror, Configuration, ErrorKind, PortId};
+use zenoh_flow::runtime::Runtime;
+use zenoh_flow::utils::deserialize_duration;
+use zenoh_flow::Result;
+
+/// How long to wait for an output, when the fixture does not say.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The id of the Host Source sending the inputs of the fixture.
+const FIXTURE_INPUTS: &str = "fixture-inputs";
+
+/// The id of the Host Sink collecting the outputs of the node.
+const FIXTURE_OUTPUTS: &str = "fixture-outputs";
+
+/// The synthetic inputs of a node and how long to wait for its outputs.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Fixture {
+    /// The configuration of the node, replacing the one of its descriptor.
+    #[serde(default)]
+    pub configuration: Option<Configuration>,
+    /// The data to send, in order, on the inputs of the node.
+    #[serde(default)]
+    pub inputs: Vec<FixtureInput>,
+    /// How long to wait for an output before considering the node is done.
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub timeout: Option<Duration>,
+    /// Stop as soon as that many outputs were received.
+    #[serde(default)]
+    pub max_outputs: Option<usize>,
+}
+
+impl Fixture {
+    /// Reads the fixture from the YAML file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the file could not be read or is not a valid fixture.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let content = std::fs::read_to_string(path.as_ref()).map_err(|e| {
+            zferror!(
+                ErrorKind::IOError,
+                "Unable to read the fixture < {} >: {}",
+                path.as_ref().display(),
+                e
+            )
+        })?;
+        Self::from_yaml(&content)
+    }
+
+    /// Reads the fixture from its YAML representation.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if `data` is not a valid fixture.
+    pub fn from_yaml(data: &str) -> Result<Self> {
+        serde_yaml::from_str(data).map_err(|e| zferror!(ErrorKind::ParsingError, e).into())
+    }
+}
+
+/// A data sent on the input `port` of the node.
+#[derive(Debug, Deserialize)]
+pub struct FixtureInput {
+    pub port: PortId,
+    #[serde(flatten)]
+    pub data: FixtureData,
+}
+
+/// The content of a data: a text, a JSON value (serialized) or raw bytes.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FixtureData {
+    Text(String),
+    Json(serde_json::Value),
+    Bytes(Vec<u8>),
+}
+
+impl FixtureData {
+    /// Returns the bytes sent to the node.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            FixtureData::Text(text) => text.as_bytes().to_vec(),
+            FixtureData::Json(value) => value.to_string().into_bytes(),
+            FixtureData::Bytes(bytes) => bytes.clone(),
+        }
+    }
+}
+
+/// Runs the node described by `descriptor` (the path of its YAML descriptor) on the inputs of the
+/// `fixture`, returning, in order, the data it sent on its outputs.
+///
+/// The inputs are only sent to an Operator or a Sink, the outputs only collected from a Source or
+/// an Operator: for a Sink, the runner only waits for the `timeout` of the fixture.
+///
+/// # Errors
+///
+/// An error is returned if the node could not be loaded, if an input of the fixture is not an
+/// input of the node or if the node failed.
+pub async fn run_fixture(
+    descriptor: impl AsRef<Path>,
+    kind: NodeKind,
+    inputs: &[PortId],
+    outputs: &[PortId],
+    fixture: &Fixture,
+) -> Result<Vec<(PortId, Vec<u8>)>> {
+    let (inputs, outputs) = match kind {
+        NodeKind::Source => (&[][..], outputs),
+        NodeKind::Operator => (inputs, outputs),
+        NodeKind::Sink => (inputs, &[][..]),
+    };
+    let descriptor = flow_descriptor(descriptor.as_ref(), kind, inputs, outputs, fixture)?;
+
+    let mut runtime = Runtime::builder()
+        .name("zenoh-flow-test")
+        .descriptor(descriptor)
+        .build()
+        .await?;
+    let host_input = runtime.take_input(FIXTURE_INPUTS);
+    let host_output = runtime.take_output(FIXTURE_OUTPUTS);
+    runtime.start().await?;
+
+    if let Some(host_input) = host_input {
+        for input in &fixture.inputs {
+            host_input.send(&input.port, input.data.to_bytes()).await?;
+        }
+    } else if !fixture.inputs.is_empty() {
+        log::warn!("The fixture has inputs but the node has none, they are ignored");
+    }
+
+    let timeout = fixture.timeout.unwrap_or(DEFAULT_TIMEOUT);
+    let mut received = Vec::new();
+    match host_output {
+        Some(host_output) => {
+            while fixture
+                .max_outputs
+                .map_or(true, |max_outputs| received.len() < max_outputs)
+            {
+                match async_std::future::timeout(timeout, host_output.recv()).await {
+                    Ok(output) => received.push(output?),
+                    Err(_) => break,
+                }
+            }
+        }
+        None => async_std::task::sleep(timeout).await,
+    }
+
+    runtime.stop().await?;
+    Ok(received)
+}
+
+/// Builds the data flow connecting the node to the Host nodes of the fixture.
+fn flow_descriptor(
+    descriptor: &Path,
+    kind: NodeKind,
+    inputs: &[PortId],
+    outputs: &[PortId],
+    fixture: &Fixture,
+) -> Result<DataFlowDescriptor> {
+    let descriptor = descriptor.canonicalize().map_err(|e| {
+        zferror!(
+            ErrorKind::IOError,
+            "Unable to find the descriptor < {} >: {}",
+            descriptor.display(),
+            e
+        )
+    })?;
+    let node = serde_json::json!({
+        "id": "node",
+        "descriptor": format!("file://{}", descriptor.display()),
+        "configuration": fixture.configuration,
+    });
+
+    let mut sources = Vec::new();
+    let mut sinks = Vec::new();
+    let mut links = Vec::new();
+    if !inputs.is_empty() {
+        sources.push(serde_json::json!({
+            "id": FIXTURE_INPUTS,
+            "descriptor": "builtin://host",
+            "configuration": { "ports": inputs },
+        }));
+        links.extend(inputs.iter().map(|port| {
+            serde_json::json!({
+                "from": { "node": FIXTURE_INPUTS, "output": port },
+                "to": { "node": "node", "input": port },
+            })
+        }));
+    }
+    if !outputs.is_empty() {
+        sinks.push(serde_json::json!({
+            "id": FIXTURE_OUTPUTS,
+            "descriptor": "builtin://host",
+            "configuration": { "ports": outputs },
+        }));
+        links.extend(outputs.iter().map(|port| {
+            serde_json::json!({
+                "from": { "node": "node", "output": port },
+                "to": { "node": FIXTURE_OUTPUTS, "input": port },
+            })
+        }));
+    }
+
+    let mut operators = Vec::new();
+    match kind {
+        NodeKind::Source => sources.push(node),
+        NodeKind::Operator => operators.push(node),
+        NodeKind::Sink => sinks.push(node),
+    }
+
+    let flow = serde_json::json!({
+        "flow": "fixture",
+        "sources": sources,
+        "operators": operators,
+        "sinks": sinks,
+        "links": links,
+    });
+    DataFlowDescriptor::from_json(&flow.to_string())
+}
+
+#[cfg(test)]
+#[path = "./tests/testing-tests.rs"]
+mod tests;
@@ -0,0 +1,97 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::{flow_descriptor, Fixture, FixtureData};
+use std::path::Path;
+use std::time::Duration;
+use zenoh_flow::model::registry::NodeKind;
+
+static FIXTURE: &str = r#"
+configuration:
+  factor: 2
+inputs:
+  - port: in
+    text: "hello"
+  - port: in
+    json: { "value": 21 }
+  - port: in
+    bytes: [0, 1, 2]
+timeout: 500ms
+max_outputs: 3
+"#;
+
+#[test]
+fn test_fixture_from_yaml() {
+    let fixture = Fixture::from_yaml(FIXTURE).expect("Failed to parse the fixture");
+
+    assert_eq!(fixture.timeout, Some(Duration::from_millis(500)));
+    assert_eq!(fixture.max_outputs, Some(3));
+    assert_eq!(
+        fixture.configuration,
+        Some(serde_json::json!({ "factor": 2 }))
+    );
+
+    let inputs = fixture
+        .inputs
+        .iter()
+        .map(|input| (input.port.as_ref(), input.data.to_bytes()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        inputs,
+        vec![
+            ("in", b"hello".to_vec()),
+            ("in", br#"{"value":21}"#.to_vec()),
+            ("in", vec![0, 1, 2]),
+        ]
+    );
+    assert!(matches!(fixture.inputs[0].data, FixtureData::Text(_)));
+}
+
+#[test]
+fn test_fixture_invalid() {
+    assert!(Fixture::from_yaml("inputs:\n  - port: in\n").is_err());
+    assert!(Fixture::from_yaml("unknown: field\n").is_err());
+}
+
+#[test]
+fn test_flow_descriptor() {
+    let fixture = Fixture::default();
+    let descriptor = Path::new("../zenoh-flow/src/model/descriptor/tests/operator.yml");
+
+    let flow = flow_descriptor(
+        descriptor,
+        NodeKind::Operator,
+        &["operator-in".into()],
+        &["operator-out".into()],
+        &fixture,
+    )
+    .expect("Failed to build the data flow");
+    assert_eq!(flow.sources.len(), 1);
+    assert_eq!(flow.operators.len(), 1);
+    assert_eq!(flow.sinks.len(), 1);
+    assert_eq!(flow.links.len(), 2);
+
+    let flow = flow_descriptor(
+        descriptor,
+        NodeKind::Sink,
+        &["operator-in".into()],
+        &[],
+        &fixture,
+    )
+    .expect("Failed to build the data flow");
+    assert_eq!(flow.sources.len(), 1);
+    assert!(flow.operators.is_empty());
+    assert_eq!(flow.sinks.len(), 1);
+    assert_eq!(flow.links.len(), 1);
+}
@@ -0,0 +1,83 @@
+use zenoh_flow_node_sdk::prelude::*;
+{% if kind == "operator" %}
+#[export_operator]
+pub struct {{crate_name | pascal_case}} {
+    input: InputRaw,
+    output: OutputRaw,
+}
+
+#[async_trait]
+impl Operator for {{crate_name | pascal_case}} {
+    async fn new(
+        _context: Context,
+        _configuration: Option<Configuration>,
+        mut inputs: Inputs,
+        mut outputs: Outputs,
+    ) -> Result<Self> {
+        Ok(Self {
+            input: inputs.take("in").expect("No input `in`").raw(),
+            output: outputs.take("out").expect("No output `out`").raw(),
+        })
+    }
+}
+
+#[async_trait]
+impl Node for {{crate_name | pascal_case}} {
+    async fn iteration(&self) -> Result<()> {
+        let message = self.input.recv().await?;
+        self.output.forward(message).await
+    }
+}
+{% elsif kind == "source" %}
+#[export_source]
+pub struct {{crate_name | pascal_case}} {
+    output: OutputRaw,
+}
+
+#[async_trait]
+impl Source for {{crate_name | pascal_case}} {
+    async fn new(
+        _context: Context,
+        _configuration: Option<Configuration>,
+        mut outputs: Outputs,
+    ) -> Result<Self> {
+        Ok(Self {
+            output: outputs.take("out").expect("No output `out`").raw(),
+        })
+    }
+}
+
+#[async_trait]
+impl Node for {{crate_name | pascal_case}} {
+    async fn iteration(&self) -> Result<()> {
+        async_std::task::sleep(std::time::Duration::from_millis(100)).await;
+        self.output.send(b"hello".to_vec(), None).await
+    }
+}
+{% else %}
+#[export_sink]
+pub struct {{crate_name | pascal_case}} {
+    input: InputRaw,
+}
+
+#[async_trait]
+impl Sink for {{crate_name | pascal_case}} {
+    async fn new(
+        _context: Context,
+        _configuration: Option<Configuration>,
+        mut inputs: Inputs,
+    ) -> Result<Self> {
+        Ok(Self {
+            input: inputs.take("in").expect("No input `in`").raw(),
+        })
+    }
+}
+
+#[async_trait]
+impl Node for {{crate_name | pascal_case}} {
+    async fn iteration(&self) -> Result<()> {
+        println!("{:?}", self.input.recv().await?);
+        Ok(())
+    }
+}
+{% endif -%}
@@ -0,0 +1,515 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Benchmarks of the data path: the links between nodes, the serialization of the messages, the
+//! connectors and the activation of the operators.
+//!
+//! Run them with `cargo bench -p zenoh-flow --bench data_path` from the root of the workspace.
+//! The end-to-end benchmarks measure the time the `counter` Sink takes to receive `iters` messages
+//! sent, as fast as its credits allow, by the `generator` Source: with its credits, at most 64
+//! messages are waiting in a link when the measurement starts.
+
+use async_std::task::block_on;
+use async_trait::async_trait;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::convert::TryFrom;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+use zenoh::prelude::r#async::AsyncResolve;
+use zenoh::Session;
+
+use zenoh_flow::io::{Inputs, Outputs};
+use zenoh_flow::model::descriptor::DataFlowDescriptor;
+use zenoh_flow::model::record::DataFlowRecord;
+use zenoh_flow::prelude::*;
+use zenoh_flow::runtime::dataflow::instance::record_sink::RecordingBackend;
+use zenoh_flow::runtime::dataflow::instance::DataFlowInstance;
+use zenoh_flow::runtime::dataflow::loader::{Loader, LoaderConfig};
+use zenoh_flow::runtime::dataflow::node::{OperatorFn, SinkFn, SourceFn};
+use zenoh_flow::runtime::dataflow::DataFlow;
+use zenoh_flow::runtime::{Runtime, RuntimeContext};
+use zenoh_flow::types::{LinkMessage, Payload};
+use zenoh_flow::{
+    DEFAULT_SHM_ALLOCATION_BACKOFF_NS, DEFAULT_SHM_ELEMENT_SIZE, DEFAULT_SHM_TOTAL_ELEMENTS,
+};
+
+static GENERATOR: &str = "generator";
+static COUNTER: &str = "counter";
+static IN: &str = "in";
+static OUT: &str = "out";
+
+static SENDER_RUNTIME: &str = "bench-sender";
+static RECEIVER_RUNTIME: &str = "bench-receiver";
+
+/// The key, in the configuration of the `generator`, of the size in bytes of its payloads.
+static KEY_PAYLOAD_SIZE: &str = "payload_size";
+static DEFAULT_PAYLOAD_SIZE: usize = 8;
+
+static PAYLOAD_SIZES: [usize; 4] = [8, 1_024, 65_536, 1_048_576];
+static OPERATOR_CHAINS: [usize; 3] = [1, 4, 16];
+
+/// The number of messages received by all the `counter` Sinks.
+static RECEIVED: AtomicU64 = AtomicU64::new(0);
+
+// -------------------------------------------------------------------------------------------------
+// -------------------------------------------------------------------------------------------------
+// LOAD GENERATOR
+// -------------------------------------------------------------------------------------------------
+// -------------------------------------------------------------------------------------------------
+
+/// A Source sending, at each iteration, the same payload of `payload_size` bytes.
+///
+/// It is meant to be given `credits`: it then sends as fast as the downstream nodes receive.
+struct LoadGenerator {
+    output: OutputRaw,
+    payload: Arc<Vec<u8>>,
+}
+
+#[async_trait]
+impl Source for LoadGenerator {
+    async fn new(
+        _context: Context,
+        configuration: Option<Configuration>,
+        mut outputs: Outputs,
+    ) -> Result<Self> {
+        let payload_size = configuration
+            .as_ref()
+            .and_then(|configuration| configuration.get(KEY_PAYLOAD_SIZE))
+            .and_then(|payload_size| payload_size.as_u64())
+            .map_or(DEFAULT_PAYLOAD_SIZE, |payload_size| payload_size as usize);
+
+        Ok(LoadGenerator {
+            output: outputs
+                .take(OUT)
+                .expect("No output `out` for LoadGenerator")
+                .raw(),
+            payload: Arc::new(vec![0u8; payload_size]),
+        })
+    }
+}
+
+#[async_trait]
+impl Node for LoadGenerator {
+    async fn iteration(&self) -> Result<()> {
+        self.output
+            .send(Payload::Bytes(self.payload.clone()), None)
+            .await
+    }
+}
+
+static LOAD_GENERATOR: SourceFn = |context, configuration, outputs| {
+    Box::pin(async {
+        let node = LoadGenerator::new(context, configuration, outputs).await?;
+        Ok(Arc::new(node) as Arc<dyn Node>)
+    })
+};
+
+// -------------------------------------------------------------------------------------------------
+// -------------------------------------------------------------------------------------------------
+// RELAY
+// -------------------------------------------------------------------------------------------------
+// -------------------------------------------------------------------------------------------------
+
+/// An Operator forwarding the data it receives, as is.
+struct Relay {
+    input: InputRaw,
+    output: OutputRaw,
+}
+
+#[async_trait]
+impl Operator for Relay {
+    async fn new(
+        _context: Context,
+        _configuration: Option<Configuration>,
+        mut inputs: Inputs,
+        mut outputs: Outputs,
+    ) -> Result<Self> {
+        Ok(Relay {
+            input: inputs.take(IN).expect("No input `in` for Relay").raw(),
+            output: outputs.take(OUT).expect("No output `out` for Relay").raw(),
+        })
+    }
+}
+
+#[async_trait]
+impl Node for Relay {
+    async fn iteration(&self) -> Result<()> {
+        if let LinkMessage::Data(data_message) = self.input.recv().await? {
+            self.output.send(data_message, None).await?;
+        }
+        Ok(())
+    }
+}
+
+static RELAY: OperatorFn = |context, configuration, inputs, outputs| {
+    Box::pin(async {
+        let node = Relay::new(context, configuration, inputs, outputs).await?;
+        Ok(Arc::new(node) as Arc<dyn Node>)
+    })
+};
+
+// -------------------------------------------------------------------------------------------------
+// -------------------------------------------------------------------------------------------------
+// COUNTER
+// -------------------------------------------------------------------------------------------------
+// -------------------------------------------------------------------------------------------------
+
+/// A Sink counting, in [RECEIVED], the data it receives.
+struct Counter {
+    input: InputRaw,
+}
+
+#[async_trait]
+impl Sink for Counter {
+    async fn new(
+        _context: Context,
+        _configuration: Option<Configuration>,
+        mut inputs: Inputs,
+    ) -> Result<Self> {
+        Ok(Counter {
+            input: inputs.take(IN).expect("No input `in` for Counter").raw(),
+        })
+    }
+}
+
+#[async_trait]
+impl Node for Counter {
+    async fn iteration(&self) -> Result<()> {
+        if let LinkMessage::Data(_) = self.input.recv().await? {
+            RECEIVED.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+}
+
+static COUNTER_SINK: SinkFn = |context, configuration, inputs| {
+    Box::pin(async {
+        let node = Counter::new(context, configuration, inputs).await?;
+        Ok(Arc::new(node) as Arc<dyn Node>)
+    })
+};
+
+/// Waits until the `counter` Sinks received `count` more messages and returns the time it took.
+async fn receive(count: u64) -> Duration {
+    let target = RECEIVED.load(Ordering::Relaxed) + count;
+    let start = Instant::now();
+    while RECEIVED.load(Ordering::Relaxed) < target {
+        async_std::task::yield_now().await;
+    }
+    start.elapsed()
+}
+
+// -------------------------------------------------------------------------------------------------
+// -------------------------------------------------------------------------------------------------
+// DATA FLOWS
+// -------------------------------------------------------------------------------------------------
+// -------------------------------------------------------------------------------------------------
+
+fn node(id: &str, descriptor: &str) -> String {
+    format!(
+        r#"
+  - id: {id}
+    descriptor: file://./benches/descriptors/{descriptor}.yml"#
+    )
+}
+
+fn link(from: &str, to: &str) -> String {
+    format!(
+        r#"
+  - from:
+      node: {from}
+      output: {OUT}
+    to:
+      node: {to}
+      input: {IN}"#
+    )
+}
+
+fn generator(payload_size: usize) -> String {
+    format!(
+        r#"{}
+    credits: 64
+    configuration:
+      {KEY_PAYLOAD_SIZE}: {payload_size}"#,
+        node(GENERATOR, GENERATOR)
+    )
+}
+
+/// The data flow `generator -> relay-0 -> ... -> relay-<relays - 1> -> counter`.
+fn chain(relays: usize, payload_size: usize) -> String {
+    let ids = std::iter::once(GENERATOR.to_string())
+        .chain((0..relays).map(|relay| format!("relay-{relay}")))
+        .chain(std::iter::once(COUNTER.to_string()))
+        .collect::<Vec<_>>();
+
+    let operators = ids[1..ids.len() - 1]
+        .iter()
+        .map(|id| node(id, "relay"))
+        .collect::<String>();
+    let links = ids
+        .windows(2)
+        .map(|pair| link(&pair[0], &pair[1]))
+        .collect::<String>();
+
+    format!(
+        r#"
+flow: bench-chain
+
+sources:{}
+
+operators:{}
+
+sinks:{}
+
+links:{links}
+"#,
+        generator(payload_size),
+        if relays == 0 { " []".into() } else { operators },
+        node(COUNTER, COUNTER),
+    )
+}
+
+/// The data flow `generator -> counter` where the `generator` and the `counter` run on different
+/// runtimes: they are connected through Zenoh.
+fn remote(payload_size: usize) -> String {
+    format!(
+        r#"
+flow: bench-connector
+
+sources:{}
+
+operators: []
+
+sinks:{}
+
+links:{}
+
+mapping:
+  {GENERATOR}: {SENDER_RUNTIME}
+  {COUNTER}: {RECEIVER_RUNTIME}
+"#,
+        generator(payload_size),
+        node(COUNTER, COUNTER),
+        link(GENERATOR, COUNTER),
+    )
+}
+
+/// Creates and starts an embedded runtime running the data flow `chain(relays, payload_size)`.
+async fn embedded(relays: usize, payload_size: usize) -> Runtime {
+    let descriptor = DataFlowDescriptor::from_yaml(&chain(relays, payload_size)).unwrap();
+
+    let mut builder = Runtime::builder()
+        .descriptor(descriptor)
+        .source(GENERATOR, LOAD_GENERATOR)
+        .sink(COUNTER, COUNTER_SINK);
+    for relay in 0..relays {
+        builder = builder.operator(format!("relay-{relay}"), RELAY);
+    }
+
+    let mut runtime = builder.build().await.unwrap();
+    runtime.start().await.unwrap();
+    runtime
+}
+
+fn runtime_context(session: Arc<Session>, hlc: Arc<uhlc::HLC>, name: &str) -> RuntimeContext {
+    RuntimeContext {
+        session,
+        hlc,
+        loader: Arc::new(Loader::new(LoaderConfig::new())),
+        runtime_name: name.into(),
+        runtime_uuid: Uuid::new_v4(),
+        shared_memory_element_size: DEFAULT_SHM_ELEMENT_SIZE as usize,
+        shared_memory_elements: DEFAULT_SHM_TOTAL_ELEMENTS as usize,
+        shared_memory_backoff: DEFAULT_SHM_ALLOCATION_BACKOFF_NS,
+        use_shm: false,
+        recording_backend: RecordingBackend::default(),
+        zenoh_configs: Arc::default(),
+        gpus: Arc::default(),
+    }
+}
+
+/// Creates and starts the instances of the data flow `remote(payload_size)` on its two runtimes,
+/// the receiving one first.
+///
+/// Both runtimes share the Zenoh `session`: the messages are serialized, published and
+/// deserialized by the connectors but do not go through the network.
+async fn connected(session: Arc<Session>, payload_size: usize) -> Vec<DataFlowInstance> {
+    let descriptor = DataFlowDescriptor::from_yaml(&remote(payload_size)).unwrap();
+    let flattened = descriptor.flatten().await.unwrap();
+    let record = DataFlowRecord::try_from((flattened, Uuid::new_v4())).unwrap();
+    let hlc = Arc::new(uhlc::HLC::default());
+
+    let mut instances = Vec::with_capacity(2);
+    for name in [RECEIVER_RUNTIME, SENDER_RUNTIME] {
+        let context = runtime_context(session.clone(), hlc.clone(), name);
+        let runtime = context.runtime_name.clone();
+
+        // The nodes are registered through their constructor, only the connectors are created from
+        // the record.
+        let mut record = record.clone();
+        let sources = std::mem::take(&mut record.sources);
+        let sinks = std::mem::take(&mut record.sinks);
+
+        let mut dataflow = DataFlow::try_new(record, context).unwrap();
+        for source in sources.into_values().filter(|s| s.runtime == runtime) {
+            dataflow.add_source(source, LOAD_GENERATOR);
+        }
+        for sink in sinks.into_values().filter(|s| s.runtime == runtime) {
+            dataflow.add_sink(sink, COUNTER_SINK);
+        }
+
+        let mut instance = DataFlowInstance::try_instantiate(dataflow, hlc.clone())
+            .await
+            .unwrap();
+        let nodes = instance
+            .get_sinks()
+            .into_iter()
+            .chain(instance.get_connectors())
+            .chain(instance.get_sources())
+            .collect::<Vec<_>>();
+        for id in nodes {
+            instance.start_node(&id).unwrap();
+        }
+        instances.push(instance);
+    }
+
+    instances
+}
+
+// -------------------------------------------------------------------------------------------------
+// -------------------------------------------------------------------------------------------------
+// BENCHMARKS
+// -------------------------------------------------------------------------------------------------
+// -------------------------------------------------------------------------------------------------
+
+/// Sending messages of different sizes over a link between a Source and a Sink.
+fn bench_link(c: &mut Criterion) {
+    let mut group = c.benchmark_group("link");
+    for payload_size in PAYLOAD_SIZES {
+        let runtime = block_on(embedded(0, payload_size));
+
+        group.throughput(Throughput::Bytes(payload_size as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(payload_size),
+            &payload_size,
+            |b, _| b.iter_custom(|iters| block_on(receive(iters))),
+        );
+
+        block_on(runtime.stop()).unwrap();
+    }
+    group.finish();
+}
+
+/// Serializing, as a connector does, and deserializing messages of different sizes.
+fn bench_serialization(c: &mut Criterion) {
+    let hlc = uhlc::HLC::default();
+    let mut message_buffer = Vec::new();
+    let mut payload_buffer = Vec::new();
+
+    let mut group = c.benchmark_group("serialization");
+    for payload_size in PAYLOAD_SIZES {
+        group.throughput(Throughput::Bytes(payload_size as u64));
+
+        let bytes =
+            LinkMessage::from_payload(Payload::from(vec![0u8; payload_size]), hlc.new_timestamp());
+        group.bench_with_input(BenchmarkId::new("bytes", payload_size), &bytes, |b, m| {
+            b.iter(|| {
+                m.serialize_bincode_into(&mut message_buffer, &mut payload_buffer)
+                    .unwrap()
+            })
+        });
+
+        let typed = LinkMessage::from_payload(
+            Payload::from_data(
+                Data::from(vec![0u8; payload_size]),
+                Arc::new(
+                    |buffer: &mut Vec<u8>, data: Arc<dyn SendSyncAny>| -> Result<()> {
+                        let data = (*data)
+                            .as_any()
+                            .downcast_ref::<Vec<u8>>()
+                            .ok_or_else(|| zferror!(ErrorKind::InvalidData, "Not a Vec<u8>"))?;
+                        buffer.extend_from_slice(data);
+                        Ok(())
+                    },
+                ),
+            ),
+            hlc.new_timestamp(),
+        );
+        group.bench_with_input(BenchmarkId::new("typed", payload_size), &typed, |b, m| {
+            b.iter(|| {
+                m.serialize_bincode_into(&mut message_buffer, &mut payload_buffer)
+                    .unwrap()
+            })
+        });
+
+        bytes
+            .serialize_bincode_into(&mut message_buffer, &mut payload_buffer)
+            .unwrap();
+        group.bench_with_input(
+            BenchmarkId::new("deserialize", payload_size),
+            &message_buffer,
+            |b, buffer| b.iter(|| bincode::deserialize::<LinkMessage>(buffer).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+/// Sending messages of different sizes from a Source to a Sink running on another runtime.
+fn bench_connector_round_trip(c: &mut Criterion) {
+    let session = Arc::new(block_on(zenoh::open(zenoh::config::Config::default()).res()).unwrap());
+
+    let mut group = c.benchmark_group("connector_round_trip");
+    for payload_size in PAYLOAD_SIZES {
+        let instances = block_on(connected(session.clone(), payload_size));
+
+        group.throughput(Throughput::Bytes(payload_size as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(payload_size),
+            &payload_size,
+            |b, _| b.iter_custom(|iters| block_on(receive(iters))),
+        );
+
+        for instance in instances.into_iter().rev() {
+            block_on(instance.stop()).unwrap();
+        }
+    }
+    group.finish();
+}
+
+/// Forwarding small messages through chains of Operators of different lengths.
+fn bench_operator_activation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("operator_activation");
+    for relays in OPERATOR_CHAINS {
+        let runtime = block_on(embedded(relays, DEFAULT_PAYLOAD_SIZE));
+
+        group.throughput(Throughput::Elements(relays as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(relays), &relays, |b, _| {
+            b.iter_custom(|iters| block_on(receive(iters)))
+        });
+
+        block_on(runtime.stop()).unwrap();
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_link,
+    bench_serialization,
+    bench_connector_round_trip,
+    bench_operator_activation
+);
+criterion_main!(benches);
@@ -15,4 +15,10 @@
 fn main() {
     let version = rustc_version::version().unwrap();
     println!("cargo:rustc-env=RUSTC_VERSION={version}");
+
+    if std::env::var("CARGO_CFG_PANIC").as_deref() == Ok("abort") {
+        println!(
+            "cargo:warning=Built with `panic = \"abort\"`: a panicking node will abort the whole runtime instead of being restarted"
+        );
+    }
 }
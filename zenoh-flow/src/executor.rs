@@ -0,0 +1,172 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The executor on which Zenoh-Flow spawns its tasks and sets its timers: async-std (feature
+//! `async-std`, the default) or Tokio (feature `tokio`).
+//!
+//! Tokio takes precedence when both features are enabled, e.g. when the default features are kept
+//! or when another crate of the dependency graph enables `async-std`: the instances must then be
+//! created and run from within a Tokio runtime.
+//!
+//! The channels (`flume`) and the locks (`async-lock`) do not depend on the executor.
+
+#[cfg(not(any(feature = "async-std", feature = "tokio")))]
+compile_error!("One of the features `async-std` or `tokio` must be enabled.");
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+/// A handle to a spawned task: awaiting it returns the output of the task.
+///
+/// Contrary to Tokio's, the panics of the task are propagated to the awaiting task.
+#[derive(Debug)]
+pub(crate) struct JoinHandle<T> {
+    #[cfg(all(feature = "async-std", not(feature = "tokio")))]
+    inner: async_std::task::JoinHandle<T>,
+    #[cfg(feature = "tokio")]
+    inner: tokio::task::JoinHandle<T>,
+}
+
+impl<T> JoinHandle<T> {
+    /// Cancels the task, returning its output if it completed before being cancelled.
+    #[cfg(all(feature = "async-std", not(feature = "tokio")))]
+    pub(crate) async fn cancel(self) -> Option<T> {
+        self.inner.cancel().await
+    }
+
+    /// Cancels the task, returning its output if it completed before being cancelled.
+    #[cfg(feature = "tokio")]
+    pub(crate) async fn cancel(self) -> Option<T> {
+        self.inner.abort();
+        match self.inner.await {
+            Ok(output) => Some(output),
+            Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+            Err(_) => None,
+        }
+    }
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = T;
+
+    #[cfg(all(feature = "async-std", not(feature = "tokio")))]
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        Pin::new(&mut self.inner).poll(cx)
+    }
+
+    #[cfg(feature = "tokio")]
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        match Pin::new(&mut self.inner).poll(cx) {
+            Poll::Ready(Ok(output)) => Poll::Ready(output),
+            Poll::Ready(Err(e)) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+            Poll::Ready(Err(e)) => panic!("Task was cancelled: {e}"),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Spawns the `future` as a new task.
+pub(crate) fn spawn<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    JoinHandle {
+        #[cfg(all(feature = "async-std", not(feature = "tokio")))]
+        inner: async_std::task::spawn(future),
+        #[cfg(feature = "tokio")]
+        inner: tokio::task::spawn(future),
+    }
+}
+
+/// Waits for `duration` to elapse.
+pub(crate) async fn sleep(duration: Duration) {
+    #[cfg(all(feature = "async-std", not(feature = "tokio")))]
+    async_std::task::sleep(duration).await;
+    #[cfg(feature = "tokio")]
+    tokio::time::sleep(duration).await;
+}
+
+/// Yields to the other tasks.
+pub(crate) async fn yield_now() {
+    #[cfg(all(feature = "async-std", not(feature = "tokio")))]
+    async_std::task::yield_now().await;
+    #[cfg(feature = "tokio")]
+    tokio::task::yield_now().await;
+}
+
+/// The error returned by [timeout] when the future did not complete in time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TimedOut;
+
+impl fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "future has timed out")
+    }
+}
+
+impl std::error::Error for TimedOut {}
+
+/// Awaits the `future` for at most `duration`.
+#[cfg(all(feature = "async-std", not(feature = "tokio")))]
+pub(crate) async fn timeout<F: Future>(
+    duration: Duration,
+    future: F,
+) -> Result<F::Output, TimedOut> {
+    async_std::future::timeout(duration, future)
+        .await
+        .map_err(|_| TimedOut)
+}
+
+/// Awaits the `future` for at most `duration`.
+#[cfg(feature = "tokio")]
+pub(crate) async fn timeout<F: Future>(
+    duration: Duration,
+    future: F,
+) -> Result<F::Output, TimedOut> {
+    tokio::time::timeout(duration, future)
+        .await
+        .map_err(|_| TimedOut)
+}
+
+/// The asynchronous file system operations.
+pub(crate) mod fs {
+    #[cfg(all(feature = "async-std", not(feature = "tokio")))]
+    pub(crate) use async_std::fs::{create_dir_all, read_to_string, remove_file, write};
+    #[cfg(feature = "tokio")]
+    pub(crate) use tokio::fs::{create_dir_all, read_to_string, remove_file, write};
+
+    /// Returns whether the `path` points at an existing entity.
+    #[cfg(all(feature = "async-std", not(feature = "tokio")))]
+    pub(crate) async fn exists(path: impl AsRef<std::path::Path>) -> bool {
+        async_std::path::Path::new(path.as_ref()).exists().await
+    }
+
+    /// Returns whether the `path` points at an existing entity.
+    #[cfg(feature = "tokio")]
+    pub(crate) async fn exists(path: impl AsRef<std::path::Path>) -> bool {
+        tokio::fs::metadata(path).await.is_ok()
+    }
+}
+
+/// The asynchronous networking primitives.
+pub(crate) mod net {
+    #[cfg(all(feature = "async-std", not(feature = "tokio")))]
+    pub(crate) use async_std::net::TcpStream;
+    #[cfg(feature = "tokio")]
+    pub(crate) use tokio::net::TcpStream;
+}
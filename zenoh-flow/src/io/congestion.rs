@@ -0,0 +1,124 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::io::link::LinkSender;
+use crate::io::output::LinkQueue;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// The congestion of the links of an output, as observed by a [CongestionMonitor].
+///
+/// The channel of a link fills up when the downstream node --- or, for a node running on another
+/// daemon, the network --- cannot keep up.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Congestion {
+    /// The number of messages waiting in the most loaded channel of the output.
+    pub queued: usize,
+    /// The fill ratio, between 0 and 1, of the most loaded bounded channel of the output: its link
+    /// declares a queue (see [`QueueDescriptor`](crate::model::descriptor::QueueDescriptor)) or a
+    /// capacity (see [`LinkDescriptor`](crate::model::descriptor::LinkDescriptor)), or the node is
+    /// a Source with credits. `None` if all the channels of the output are unbounded.
+    pub level: Option<f64>,
+    /// The number of messages dropped, on all the links of the output, since the previous
+    /// observation.
+    pub dropped: u64,
+    /// The number of messages dropped per second since the previous observation.
+    pub drop_rate: f64,
+}
+
+impl Congestion {
+    /// Returns `true` if messages were dropped since the previous observation or if the fill
+    /// ratio of a queue reached `threshold`.
+    pub fn is_congested(&self, threshold: f64) -> bool {
+        self.dropped > 0 || self.level.map_or(false, |level| level >= threshold)
+    }
+}
+
+/// A `CongestionMonitor` observes the links of an output, for the node sending on it to adapt its
+/// throughput (e.g. lower the frame rate of a camera or the quality of a JPEG encoding) when the
+/// downstream nodes cannot keep up.
+///
+/// It is obtained with [`OutputRaw::congestion_monitor`](crate::io::OutputRaw::congestion_monitor)
+/// and polled, typically once per iteration of the node:
+///
+/// ```ignore
+/// let congestion = self.monitor.lock().await.observe();
+/// if congestion.is_congested(0.8) {
+///     self.fps.store(self.fps.load(Ordering::Relaxed) / 2, Ordering::Relaxed);
+/// }
+/// ```
+///
+/// See the builtin operator `builtin://throttle` for a stock implementation of this pattern.
+pub struct CongestionMonitor {
+    senders: Vec<LinkSender>,
+    queues: Vec<Arc<LinkQueue>>,
+    dropped: u64,
+    observed: Instant,
+}
+
+impl CongestionMonitor {
+    pub(crate) fn new(senders: Vec<LinkSender>, queues: Vec<Arc<LinkQueue>>) -> Self {
+        let dropped = queues.iter().map(|queue| queue.dropped()).sum();
+        Self {
+            senders,
+            queues,
+            dropped,
+            observed: Instant::now(),
+        }
+    }
+
+    /// Returns the current [Congestion] of the links of the output. The messages dropped are
+    /// counted since the previous observation (or since the monitor was created).
+    pub fn observe(&mut self) -> Congestion {
+        let queued = self
+            .senders
+            .iter()
+            .map(|sender| sender.len())
+            .max()
+            .unwrap_or(0);
+        let level = self
+            .senders
+            .iter()
+            .filter_map(|sender| {
+                sender
+                    .capacity()
+                    .filter(|capacity| *capacity > 0)
+                    .map(|capacity| sender.len() as f64 / capacity as f64)
+            })
+            .fold(None, |max: Option<f64>, level| {
+                Some(max.map_or(level, |max| max.max(level)))
+            });
+
+        let total = self.queues.iter().map(|queue| queue.dropped()).sum::<u64>();
+        let dropped = total.saturating_sub(self.dropped);
+        let elapsed = self.observed.elapsed().as_secs_f64();
+        self.dropped = total;
+        self.observed = Instant::now();
+
+        Congestion {
+            queued,
+            level,
+            dropped,
+            drop_rate: if elapsed > 0.0 {
+                dropped as f64 / elapsed
+            } else {
+                0.0
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/congestion-tests.rs"]
+mod tests;
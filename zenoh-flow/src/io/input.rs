@@ -12,15 +12,33 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+use crate::io::link::{LinkReceiver, LinkSender};
+use crate::io::output::LastValueCache;
+use crate::io::rule::InputSet;
+use crate::model::descriptor::InputPolicyDescriptor;
 use crate::prelude::{ErrorKind, Message, PortId};
-use crate::types::{Data, DataMessage, DeserializerFn, LinkMessage};
+use crate::runtime::dataflow::instance::{
+    control::{ChannelAlignment, NodeControl},
+    debugger::NodeDebugger,
+    flow_control::FlowControl,
+    EndOfStreamTracker,
+};
+use crate::types::{
+    ControlMarker, Data, DataMessage, DeserializerFn, LinkMessage, Payload, UnionData,
+};
+use crate::zfresult::{ErrorContext, WithContext};
 use crate::{bail, Result};
 
 use flume::TryRecvError;
+use futures::future::{self, Either};
+use futures::FutureExt;
 use std::collections::HashMap;
 use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
-use uhlc::Timestamp;
+use std::time::{Duration, Instant};
+use uhlc::{Timestamp, HLC};
+use zenoh::Session;
 
 /// The `Inputs` structure contains all the inputs created for a [Sink](crate::prelude::Sink) or an
 /// [Operator](crate::prelude::Operator).
@@ -47,14 +65,28 @@ use uhlc::Timestamp;
 ///                                 .map_err(|e| anyhow::anyhow!(e))
 ///                         )?;
 /// ```
+#[derive(Clone)]
 pub struct Inputs {
-    pub(crate) hmap: HashMap<PortId, Vec<flume::Receiver<LinkMessage>>>,
+    pub(crate) hmap: HashMap<PortId, Vec<LinkReceiver>>,
+    pub(crate) end_of_stream_tracker: Option<Arc<EndOfStreamTracker>>,
+    pub(crate) session: Option<Arc<Session>>,
+    pub(crate) hlc: Option<Arc<HLC>>,
+    pub(crate) debugger: Option<Arc<NodeDebugger>>,
+    // The caches of the upstream outputs and the channels they feed, to send the last values again
+    // when the node is restarted.
+    pub(crate) last_values: Vec<(Arc<LastValueCache>, LinkSender)>,
+    // The flow controls of the upstream Sources, per input, to grant them credits.
+    pub(crate) flow_controls: HashMap<PortId, Vec<Arc<FlowControl>>>,
+    // The policies of the inputs, set in the descriptor of the node, followed by an `InputSet`.
+    pub(crate) policies: HashMap<PortId, InputPolicyDescriptor>,
+    // The control of the node, aligning the barriers received on its inputs.
+    pub(crate) control: Option<Arc<NodeControl>>,
 }
 
 // Dereferencing on the internal `Hashmap` allows users to call all the methods implemented on it:
 // `keys()` for one.
 impl Deref for Inputs {
-    type Target = HashMap<PortId, Vec<flume::Receiver<LinkMessage>>>;
+    type Target = HashMap<PortId, Vec<LinkReceiver>>;
 
     fn deref(&self) -> &Self::Target {
         &self.hmap
@@ -65,18 +97,39 @@ impl Inputs {
     pub(crate) fn new() -> Self {
         Self {
             hmap: HashMap::default(),
+            end_of_stream_tracker: None,
+            session: None,
+            hlc: None,
+            debugger: None,
+            last_values: Vec::default(),
+            flow_controls: HashMap::default(),
+            policies: HashMap::default(),
+            control: None,
         }
     }
 
-    /// Insert the `flume::Receiver` in the [Inputs], creating the entry if needed in the internal
+    /// Insert the [LinkReceiver] in the [Inputs], creating the entry if needed in the internal
     /// `HashMap`.
-    pub(crate) fn insert(&mut self, port_id: PortId, rx: flume::Receiver<LinkMessage>) {
+    pub(crate) fn insert(&mut self, port_id: PortId, rx: LinkReceiver) {
         self.hmap
             .entry(port_id)
             .or_insert_with(Vec::default)
             .push(rx)
     }
 
+    /// Removes the link leading to the input `port_id` whose channel is made of `tx` and `rx`. The
+    /// input is removed along with its last link.
+    pub(crate) fn remove(&mut self, port_id: &PortId, tx: &LinkSender, rx: &LinkReceiver) {
+        self.last_values
+            .retain(|(_, sender)| !sender.same_channel(tx));
+        if let Some(receivers) = self.hmap.get_mut(port_id) {
+            receivers.retain(|receiver| !receiver.same_channel(rx));
+            if receivers.is_empty() {
+                self.hmap.remove(port_id);
+            }
+        }
+    }
+
     /// Returns an [InputBuilder] for the provided `port_id`, if an input was declared with this
     /// exact name in the descriptor of the node, otherwise returns `None`.
     ///
@@ -119,8 +172,40 @@ impl Inputs {
             .map(|receivers| InputBuilder {
                 port_id: port_id.as_ref().into(),
                 receivers,
+                end_of_stream_tracker: self.end_of_stream_tracker.clone(),
+                session: self.session.clone(),
+                hlc: self.hlc.clone(),
+                debugger: self.debugger.clone(),
+                flow_controls: self
+                    .flow_controls
+                    .remove(port_id.as_ref())
+                    .unwrap_or_default(),
+                control: self.control.clone(),
             })
     }
+
+    /// Returns an [InputSet] grouping all the inputs that were not taken yet.
+    ///
+    /// The [InputSet] gives their data to the node together, following the
+    /// [default_input_rule](crate::io::default_input_rule): once all the inputs that are required
+    /// received data. The inputs declared with `required: false` in the `input_policies` of the
+    /// descriptor of the node do not block it.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let input_set = inputs.take_set();
+    /// let data = input_set.recv().await?;
+    /// ```
+    pub fn take_set(&mut self) -> InputSet {
+        let port_ids = self.hmap.keys().cloned().collect::<Vec<_>>();
+        let inputs = port_ids
+            .into_iter()
+            .filter_map(|port_id| self.take(&port_id).map(|builder| (port_id, builder.raw())))
+            .collect();
+
+        InputSet::new(inputs, &self.policies)
+    }
 }
 
 /// An `InputBuilder` is the intermediate structure to obtain either an [`Input<T>`] or an
@@ -137,7 +222,13 @@ impl Inputs {
 /// issues.
 pub struct InputBuilder {
     pub(crate) port_id: PortId,
-    pub(crate) receivers: Vec<flume::Receiver<LinkMessage>>,
+    pub(crate) receivers: Vec<LinkReceiver>,
+    pub(crate) end_of_stream_tracker: Option<Arc<EndOfStreamTracker>>,
+    pub(crate) session: Option<Arc<Session>>,
+    pub(crate) hlc: Option<Arc<HLC>>,
+    pub(crate) debugger: Option<Arc<NodeDebugger>>,
+    pub(crate) flow_controls: Vec<Arc<FlowControl>>,
+    pub(crate) control: Option<Arc<NodeControl>>,
 }
 
 impl InputBuilder {
@@ -163,17 +254,23 @@ impl InputBuilder {
     ///     .raw();
     /// ```
     pub fn raw(self) -> InputRaw {
-        InputRaw {
-            port_id: self.port_id,
-            receivers: self.receivers,
+        let mut input_raw = InputRaw::new(self.port_id, self.receivers, self.end_of_stream_tracker);
+        input_raw.debugger = self.debugger;
+        input_raw.flow_controls = self.flow_controls;
+        if let Some(control) = &self.control {
+            control.register(input_raw.channels_count());
         }
+        input_raw.control = self.control;
+        input_raw
     }
 
     /// Consume the `InputBuilder` to produce an [`Input<T>`].
     ///
     /// An [`Input<T>`] tries to automatically convert the data contained in the [LinkMessage] in
     /// order to expose `&T`. Depending on if the data is received serialized or not, to perform
-    /// this conversion either the `deserializer` is called or a downcast is attempted.
+    /// this conversion either the `deserializer` is called or a downcast is attempted. Data received
+    /// by reference (see [PayloadReference](crate::types::PayloadReference)) is first retrieved
+    /// from Zenoh.
     ///
     /// # `Input<T>` vs `InputRaw`
     ///
@@ -195,27 +292,154 @@ impl InputBuilder {
         deserializer: impl Fn(&[u8]) -> anyhow::Result<T> + Send + Sync + 'static,
     ) -> Input<T> {
         Input {
+            session: self.session.clone(),
+            hlc: self.hlc.clone(),
+            default: None,
+            received: AtomicBool::new(false),
             input_raw: self.raw(),
             deserializer: Arc::new(deserializer),
+            union: None,
         }
     }
+
+    /// Consume the `InputBuilder` to produce an [`Input<U>`] receiving the data of a union port
+    /// (see [UnionData]).
+    ///
+    /// The data received serialized is deserialized according to the index of its variant, carried
+    /// in the header of its message: receiving data without variant is an error.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let input_reading: Input<Reading> = inputs
+    ///     .take("Reading")
+    ///     .expect("No input named 'Reading' found")
+    ///     .union();
+    /// ```
+    pub fn union<U: UnionData>(self) -> Input<U> {
+        let port_id = self.port_id.clone();
+        let mut input = self.typed(move |_| {
+            Err(anyhow::anyhow!(
+                "The data received on < {port_id} > does not carry the variant of its union"
+            ))
+        });
+        input.union = Some(U::deserialize_variant);
+        input
+    }
 }
 
 /// An [`InputRaw`](`InputRaw`) exposes the [`LinkMessage`](`LinkMessage`) it receives.
 ///
 /// It's primary purpose is to ensure "optimal" performance. This can be useful to implement
 /// behaviour where actual access to the underlying data is irrelevant.
+///
+/// # End of stream
+///
+/// When an Input is connected to several upstream nodes, an
+/// [EndOfStream](LinkMessage::EndOfStream) is only exposed once _all_ of them have signaled the end
+/// of their stream. Each upstream node is expected to signal it once.
+///
+/// # Breakpoints
+///
+/// If a breakpoint is set on the Input (see
+/// [`DataFlowInstance::set_breakpoint`](crate::runtime::dataflow::instance::DataFlowInstance::set_breakpoint)),
+/// the node is paused when a data message is received, before it is returned, until it is resumed.
 #[derive(Clone, Debug)]
 pub struct InputRaw {
     pub(crate) port_id: PortId,
-    pub(crate) receivers: Vec<flume::Receiver<LinkMessage>>,
+    pub(crate) receivers: Vec<LinkReceiver>,
+    pub(crate) pending_end_of_stream: Arc<AtomicUsize>,
+    pub(crate) end_of_stream_tracker: Option<Arc<EndOfStreamTracker>>,
+    pub(crate) debugger: Option<Arc<NodeDebugger>>,
+    pub(crate) flow_controls: Vec<Arc<FlowControl>>,
+    pub(crate) control: Option<Arc<NodeControl>>,
+    // The state of each channel relative to the barriers, in the same order as the receivers.
+    pub(crate) alignment: Arc<Vec<ChannelAlignment>>,
 }
 
 impl InputRaw {
+    pub(crate) fn new(
+        port_id: PortId,
+        receivers: Vec<LinkReceiver>,
+        end_of_stream_tracker: Option<Arc<EndOfStreamTracker>>,
+    ) -> Self {
+        Self {
+            port_id,
+            pending_end_of_stream: Arc::new(AtomicUsize::new(receivers.len())),
+            alignment: Arc::new(
+                receivers
+                    .iter()
+                    .map(|_| ChannelAlignment::default())
+                    .collect(),
+            ),
+            receivers,
+            end_of_stream_tracker,
+            debugger: None,
+            flow_controls: Vec::new(),
+            control: None,
+        }
+    }
+
     pub fn port_id(&self) -> &PortId {
         &self.port_id
     }
 
+    /// Grants a credit to the flow controlled upstream Sources: a message was taken out of one of
+    /// the channels.
+    fn grant_credits(&self) {
+        self.flow_controls.iter().for_each(|flow| flow.grant());
+    }
+
+    /// Accounts for an [EndOfStream](LinkMessage::EndOfStream) received on one of the channels and
+    /// returns `true` if all the channels have now signaled the end of their stream.
+    fn end_of_stream_reached(&self) -> bool {
+        let previous = self
+            .pending_end_of_stream
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |pending| {
+                pending.checked_sub(1)
+            })
+            .unwrap_or(0);
+
+        if previous == 1 {
+            log::trace!("[Input: {}] End of stream reached", self.port_id);
+            if let Some(tracker) = &self.end_of_stream_tracker {
+                tracker.input_completed();
+            }
+        }
+
+        previous <= 1
+    }
+
+    /// Returns `true` if the channel at `index` delivered the marker of a barrier the node did not
+    /// handle yet: it is not polled until then.
+    fn is_blocked(&self, index: usize) -> bool {
+        self.control
+            .as_ref()
+            .map_or(false, |control| control.is_blocked(&self.alignment[index]))
+    }
+
+    /// Accounts for the `marker` of a barrier delivered by the channel at `index`.
+    fn arrive(&self, index: usize, marker: ControlMarker) {
+        if let Some(control) = &self.control {
+            control.arrive(&self.alignment[index], marker);
+        }
+    }
+
+    /// Records the `message` received on the channel at `index` if it was in flight when the node
+    /// checkpointed its state.
+    fn record(&self, index: usize, message: &LinkMessage) {
+        if let Some(control) = &self.control {
+            control.record(&self.alignment[index], &self.receivers[index], message);
+        }
+    }
+
+    /// Accounts for the channel at `index` reaching its end of stream or being disconnected.
+    fn close(&self, index: usize) {
+        if let Some(control) = &self.control {
+            control.close(&self.alignment[index]);
+        }
+    }
+
     /// Returns the number of channels associated with this Input.
     pub fn channels_count(&self) -> usize {
         self.receivers.len()
@@ -234,13 +458,54 @@ impl InputRaw {
     ///
     /// If no message was received, an `Empty` error is returned. Note that if some channels are
     /// disconnected, for each of such channel an error is logged.
+    ///
+    /// # Breakpoints
+    ///
+    /// If the message received hits a breakpoint, the thread is blocked until the node is resumed.
+    ///
+    /// # Barriers
+    ///
+    /// The markers of the barriers are kept by the runtime: a channel that delivered one is not
+    /// polled until the node handled the barrier. The marker of a checkpoint barrier pauses all the
+    /// channels until the node checkpointed its state.
     pub fn try_recv(&self) -> Result<LinkMessage> {
-        for receiver in &self.receivers {
-            match receiver.try_recv() {
-                Ok(message) => return Ok(message),
+        self.try_recv_inline().map(LinkMessage::into_delivered)
+    }
+
+    /// As `try_recv`, the payloads stored inline being returned as they are: an [`Input<T>`]
+    /// deserializes them without copying them.
+    pub(crate) fn try_recv_inline(&self) -> Result<LinkMessage> {
+        for (index, receiver) in self.receivers.iter().enumerate() {
+            if self.is_blocked(index) {
+                continue;
+            }
+
+            let result = receiver.try_recv();
+            if let Ok(message) = &result {
+                self.grant_credits();
+                self.record(index, message);
+            }
+
+            match result {
+                Ok(LinkMessage::Control(marker)) if self.control.is_some() => {
+                    self.arrive(index, marker)
+                }
+                Ok(LinkMessage::EndOfStream(_)) if !self.end_of_stream_reached() => {
+                    self.close(index)
+                }
+                Ok(message) => {
+                    if matches!(message, LinkMessage::EndOfStream(_)) {
+                        self.close(index);
+                    }
+                    if let Some(debugger) = &self.debugger {
+                        debugger.check_sync(&self.port_id, &message);
+                    }
+                    return Ok(message);
+                }
                 Err(e) => {
                     if matches!(e, TryRecvError::Disconnected) {
                         log::error!("[Input: {}] A channel is disconnected", self.port_id);
+                        self.close(index);
                     }
                 }
             }
@@ -260,27 +525,157 @@ impl InputRaw {
     /// An error is returned if *all* channels are disconnected. For each disconnected channel, an
     /// error is separately logged.
     pub async fn recv(&self) -> Result<LinkMessage> {
-        let mut recv_futures = self
-            .receivers
-            .iter()
-            .map(|link| link.recv_async())
-            .collect::<Vec<_>>();
-
-        loop {
-            let (res, _, remaining) = futures::future::select_all(recv_futures).await;
-            match res {
-                Ok(message) => return Ok(message),
-                Err(_disconnected) => {
-                    log::error!("[Input: {}] A channel is disconnected", self.port_id);
-                    if remaining.is_empty() {
-                        bail!(
-                            ErrorKind::Disconnected,
-                            "[Input: {}] All channels are disconnected",
-                            self.port_id
-                        );
+        self.recv_inline().await.map(LinkMessage::into_delivered)
+    }
+
+    /// As `recv`, the payloads stored inline being returned as they are: an [`Input<T>`]
+    /// deserializes them without copying them.
+    pub(crate) async fn recv_inline(&self) -> Result<LinkMessage> {
+        let message = self.next().await?;
+        if let Some(debugger) = &self.debugger {
+            debugger.check(&self.port_id, &message).await;
+        }
+        Ok(message)
+    }
+
+    /// Returns, *asynchronously*, a batch of at most `max_size` [LinkMessage]: the first one that
+    /// is received and, for at most `max_wait` after it, the ones that follow.
+    ///
+    /// Receiving the messages of a high-frequency stream (e.g. IMU data) in batches, in a single
+    /// iteration of the node, reduces the overhead paid per message. The messages already waiting in
+    /// the channels are taken without waiting.
+    ///
+    /// The batch ends with the [EndOfStream](LinkMessage::EndOfStream), if it is received.
+    ///
+    /// # Error
+    ///
+    /// An error is returned if *all* channels are disconnected before the first message is received.
+    /// Once the batch started, it is returned as is.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let messages = input_raw.recv_batch(64, Duration::from_millis(5)).await?;
+    /// ```
+    pub async fn recv_batch(
+        &self,
+        max_size: usize,
+        max_wait: Duration,
+    ) -> Result<Vec<LinkMessage>> {
+        let batch = self.recv_batch_inline(max_size, max_wait).await?;
+        Ok(batch.into_iter().map(LinkMessage::into_delivered).collect())
+    }
+
+    /// As `recv_batch`, the payloads stored inline being returned as they are: an [`Input<T>`]
+    /// deserializes them without copying them.
+    pub(crate) async fn recv_batch_inline(
+        &self,
+        max_size: usize,
+        max_wait: Duration,
+    ) -> Result<Vec<LinkMessage>> {
+        let first = self.recv_inline().await?;
+        let deadline = Instant::now() + max_wait;
+        let mut end_of_stream = matches!(first, LinkMessage::EndOfStream(_));
+        let mut batch = vec![first];
+
+        while batch.len() < max_size && !end_of_stream {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            // The debugger is checked once the message is received: waiting on a breakpoint does
+            // not count in the time left.
+            let message = match crate::executor::timeout(remaining, self.next()).await {
+                Ok(Ok(message)) => message,
+                Ok(Err(_)) | Err(_) => break,
+            };
+            if let Some(debugger) = &self.debugger {
+                debugger.check(&self.port_id, &message).await;
+            }
+
+            end_of_stream = matches!(message, LinkMessage::EndOfStream(_));
+            batch.push(message);
+        }
+
+        Ok(batch)
+    }
+
+    /// Returns the first [LinkMessage] received on any of the channels, without checking the
+    /// breakpoints.
+    ///
+    /// Once a message was taken out of a channel, nothing is awaited before it is returned:
+    /// dropping the future never loses a message.
+    ///
+    /// The channels blocked by a barrier are polled again once the node handled it.
+    async fn next(&self) -> Result<LinkMessage> {
+        let recv = |index: usize| {
+            self.receivers[index]
+                .recv_async()
+                .map(move |result| (index, result))
+        };
+        let unblocked = || {
+            (0..self.receivers.len())
+                .filter(|index| !self.is_blocked(*index))
+                .map(recv)
+                .collect::<Vec<_>>()
+        };
+
+        'released: loop {
+            // Listening before selecting the channels: a barrier handled in between is not missed.
+            let mut released = match &self.control {
+                Some(control) => Either::Left(control.listen()),
+                None => Either::Right(future::pending::<()>()),
+            };
+            let mut recv_futures = unblocked();
+
+            loop {
+                if recv_futures.is_empty() {
+                    (&mut released).await;
+                    continue 'released;
+                }
+
+                let ((index, res), _, remaining) =
+                    match future::select(future::select_all(recv_futures), &mut released).await {
+                        Either::Left((received, _)) => received,
+                        Either::Right(_) => continue 'released,
+                    };
+                if let Ok(message) = &res {
+                    self.grant_credits();
+                    self.record(index, message);
+                }
+
+                match res {
+                    Ok(LinkMessage::Control(marker)) if self.control.is_some() => {
+                        // The marker of a checkpoint barrier pauses all the channels.
+                        self.arrive(index, marker);
+                        recv_futures = unblocked();
                     }
+                    Ok(LinkMessage::EndOfStream(_)) if !self.end_of_stream_reached() => {
+                        // The other upstream nodes have not all signaled the end of their stream.
+                        self.close(index);
+                        recv_futures = if remaining.is_empty() {
+                            unblocked()
+                        } else {
+                            remaining
+                        };
+                    }
+                    Ok(message) => {
+                        if matches!(message, LinkMessage::EndOfStream(_)) {
+                            self.close(index);
+                        }
+                        return Ok(message);
+                    }
+                    Err(_disconnected) => {
+                        log::error!("[Input: {}] A channel is disconnected", self.port_id);
+                        self.close(index);
+                        let blocked = (0..self.receivers.len()).any(|index| self.is_blocked(index));
+                        if remaining.is_empty() && !blocked {
+                            bail!(
+                                ErrorKind::Disconnected,
+                                "[Input: {}] All channels are disconnected",
+                                self.port_id
+                            );
+                        }
 
-                    recv_futures = remaining;
+                        recv_futures = remaining;
+                    }
                 }
             }
         }
@@ -293,10 +688,15 @@ impl InputRaw {
 /// # Performance
 ///
 /// If the data is received serialized from the upstream node, an allocation is performed to host
-/// the deserialized `T`.
+/// the deserialized `T`. If the data is received by reference, it is first retrieved from Zenoh.
 pub struct Input<T> {
     pub(crate) input_raw: InputRaw,
     pub(crate) deserializer: Arc<DeserializerFn<T>>,
+    pub(crate) union: Option<fn(u32, &[u8]) -> anyhow::Result<T>>,
+    pub(crate) session: Option<Arc<Session>>,
+    pub(crate) hlc: Option<Arc<HLC>>,
+    pub(crate) default: Option<Arc<dyn Fn() -> T + Send + Sync>>,
+    pub(crate) received: AtomicBool,
 }
 
 // Dereferencing to the [InputRaw] allows to directly call methods on it with a typed [Input].
@@ -309,6 +709,40 @@ impl<T: Send + Sync + 'static> Deref for Input<T> {
 }
 
 impl<T: Send + Sync + 'static> Input<T> {
+    /// Sets the value returned by `try_recv` until the first data message is received.
+    ///
+    /// This is useful for nodes that sample an input (e.g. a configuration or a slowly changing
+    /// measurement) and need a value to work with before the upstream node produced one.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let input_threshold: Input<f64> = inputs
+    ///     .take("threshold")
+    ///     .expect("No input named 'threshold' found")
+    ///     .typed(|bytes| serde_json::from_slice(bytes).map_err(|e| anyhow::anyhow!(e)))
+    ///     .with_default(0.5);
+    /// ```
+    pub fn with_default(mut self, default: T) -> Self
+    where
+        T: Clone,
+    {
+        self.default = Some(Arc::new(move || default.clone()));
+        self
+    }
+
+    /// Returns the Zenoh session used to resolve the references, or an error if there is none.
+    fn session(&self) -> Result<&Session> {
+        match &self.session {
+            Some(session) => Ok(session),
+            None => bail!(
+                ErrorKind::Unsupported,
+                "Input < {} > cannot resolve references: no Zenoh session",
+                self.input_raw.port_id
+            ),
+        }
+    }
+
     /// Returns the first [`Message<T>`] that was received, *asynchronously*, on any of the channels
     /// associated with this Input.
     ///
@@ -321,20 +755,93 @@ impl<T: Send + Sync + 'static> Input<T> {
     /// As this method interprets the data received additional operations are performed:
     /// - data received serialized is deserialized (an allocation is performed to store an instance
     ///   of `T`),
-    /// - data received "typed" are checked against the type associated to this [`Input<T>`].
+    /// - data received "typed" are checked against the type associated to this [`Input<T>`],
+    /// - data received by reference are retrieved from Zenoh and then deserialized.
     ///
     /// # Error
     ///
     /// Several errors can occur:
     /// - all the channels are disconnected,
+    /// - the data received by reference could not be retrieved,
     /// - Zenoh-Flow failed at interpreting the received data as an instance of `T`.
     pub async fn recv(&self) -> Result<(Message<T>, Timestamp)> {
-        match self.input_raw.recv().await? {
-            LinkMessage::Data(DataMessage { data, timestamp }) => Ok((
-                Message::Data(Data::try_from_payload(data, self.deserializer.clone())?),
+        let message = self.input_raw.recv_inline().await?;
+        self.interpret(message).await
+    }
+
+    /// Returns, *asynchronously*, a batch of at most `max_size` [`Message<T>`]: the first one that
+    /// is received and, for at most `max_wait` after it, the ones that follow (see
+    /// [`InputRaw::recv_batch`]).
+    ///
+    /// This method interprets the data of each message to the type associated with this
+    /// [`Input<T>`], as `recv` does.
+    ///
+    /// # Error
+    ///
+    /// The errors are those of `recv`: the batch is discarded if the data of one of its messages
+    /// could not be interpreted.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// for (message, timestamp) in input.recv_batch(64, Duration::from_millis(5)).await? {
+    ///     if let Message::Data(sample) = message {
+    ///         filter.update(&*sample, timestamp);
+    ///     }
+    /// }
+    /// ```
+    pub async fn recv_batch(
+        &self,
+        max_size: usize,
+        max_wait: Duration,
+    ) -> Result<Vec<(Message<T>, Timestamp)>> {
+        let messages = self.input_raw.recv_batch_inline(max_size, max_wait).await?;
+        let mut batch = Vec::with_capacity(messages.len());
+        for message in messages {
+            batch.push(self.interpret(message).await?);
+        }
+        Ok(batch)
+    }
+
+    /// Returns the deserializer of the data received: for a union, the one of its `variant`.
+    fn deserializer(&self, variant: Option<u32>) -> Arc<DeserializerFn<T>> {
+        match (self.union, variant) {
+            (Some(union), Some(variant)) => Arc::new(move |bytes: &[u8]| (union)(variant, bytes)),
+            _ => self.deserializer.clone(),
+        }
+    }
+
+    /// Interprets the data of the `message` received to the type associated with this Input.
+    async fn interpret(&self, message: LinkMessage) -> Result<(Message<T>, Timestamp)> {
+        match message {
+            LinkMessage::Data(DataMessage {
+                mut data,
                 timestamp,
-            )),
+                event_time,
+                variant,
+            }) => {
+                self.received.store(true, Ordering::Relaxed);
+                if let Payload::Reference(reference) = &data {
+                    data = Payload::Bytes(reference.resolve(self.session()?).await?);
+                }
+
+                Ok((
+                    Message::Data(
+                        Data::try_from_payload(data, self.deserializer(variant))
+                            .context(ErrorContext::Input(self.input_raw.port_id.clone()))?
+                            .with_event_time(event_time),
+                    ),
+                    timestamp,
+                ))
+            }
             LinkMessage::Watermark(timestamp) => Ok((Message::Watermark, timestamp)),
+            LinkMessage::EndOfStream(timestamp) => Ok((Message::EndOfStream, timestamp)),
+            LinkMessage::Live(timestamp) => Ok((Message::Live, timestamp)),
+            LinkMessage::Control(_) => bail!(
+                ErrorKind::Unsupported,
+                "[Input: {}] Unexpected control marker",
+                self.input_raw.port_id
+            ),
         }
     }
 
@@ -344,22 +851,63 @@ impl<T: Send + Sync + 'static> Input<T> {
     /// # Asynchronous alternative: `recv`
     ///
     /// This method is a synchronous fail-fast alternative to it's asynchronous counterpart: `recv`.
-    /// Although synchronous, this method will not block the thread on which it is executed.
+    /// Although synchronous, this method will not block the thread on which it is executed, unless
+    /// the data was received by reference: the thread is then blocked while it is retrieved.
+    ///
+    /// # Default value
+    ///
+    /// If a default value was set (see `with_default`) and no data message was received yet, the
+    /// default value is returned instead of an error when no message is available.
     ///
     /// # Error
     ///
     /// Several errors can occur:
     /// - no message was received (i.e. Empty error),
+    /// - the data received by reference could not be retrieved,
     /// - Zenoh-Flow failed at interpreting the received data as an instance of `T`.
     ///
     /// Note that if some channels are disconnected, for each of such channel an error is logged.
     pub fn try_recv(&self) -> Result<(Message<T>, Timestamp)> {
-        match self.input_raw.try_recv()? {
-            LinkMessage::Data(DataMessage { data, timestamp }) => Ok((
-                Message::Data(Data::try_from_payload(data, self.deserializer.clone())?),
+        let message = match (self.input_raw.try_recv_inline(), &self.default, &self.hlc) {
+            (Err(e), Some(default), Some(hlc)) if !self.received.load(Ordering::Relaxed) => {
+                log::trace!(
+                    "[Input: {}] using default value: {e:?}",
+                    self.input_raw.port_id
+                );
+                return Ok((Message::Data((default)().into()), hlc.new_timestamp()));
+            }
+            (message, _, _) => message?,
+        };
+
+        match message {
+            LinkMessage::Data(DataMessage {
+                mut data,
                 timestamp,
-            )),
+                event_time,
+                variant,
+            }) => {
+                self.received.store(true, Ordering::Relaxed);
+                if let Payload::Reference(reference) = &data {
+                    data = Payload::Bytes(reference.resolve_sync(self.session()?)?);
+                }
+
+                Ok((
+                    Message::Data(
+                        Data::try_from_payload(data, self.deserializer(variant))
+                            .context(ErrorContext::Input(self.input_raw.port_id.clone()))?
+                            .with_event_time(event_time),
+                    ),
+                    timestamp,
+                ))
+            }
             LinkMessage::Watermark(ts) => Ok((Message::Watermark, ts)),
+            LinkMessage::EndOfStream(ts) => Ok((Message::EndOfStream, ts)),
+            LinkMessage::Live(ts) => Ok((Message::Live, ts)),
+            LinkMessage::Control(_) => bail!(
+                ErrorKind::Unsupported,
+                "[Input: {}] Unexpected control marker",
+                self.input_raw.port_id
+            ),
         }
     }
 }
@@ -0,0 +1,180 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::io::spsc;
+use crate::types::LinkMessage;
+use flume::{RecvError, SendError, TryRecvError, TrySendError};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Creates the channel of a link that can hold any number of messages.
+pub(crate) fn unbounded() -> (LinkSender, LinkReceiver) {
+    let (tx, rx) = flume::unbounded();
+    (tx.into(), rx.into())
+}
+
+/// Creates the channel of a link that holds at most `capacity` messages.
+pub(crate) fn bounded(capacity: usize) -> (LinkSender, LinkReceiver) {
+    let (tx, rx) = flume::bounded(capacity);
+    (tx.into(), rx.into())
+}
+
+/// Creates the channel of a point-to-point link, between two nodes running on the same daemon,
+/// that holds at most `capacity` messages: a lock-free single-producer single-consumer ring
+/// buffer.
+///
+/// # Panics
+///
+/// Panics if `capacity` is lower than 2 (see [spsc::channel]).
+pub(crate) fn spsc(capacity: usize) -> (LinkSender, LinkReceiver) {
+    let (tx, rx) = spsc::channel(capacity);
+    (
+        LinkSender(Sender::Spsc(tx)),
+        LinkReceiver(Receiver::Spsc(rx)),
+    )
+}
+
+/// The sending side of the channel of a link, held by the output it starts from.
+#[derive(Clone, Debug)]
+pub struct LinkSender(Sender);
+
+#[derive(Clone, Debug)]
+enum Sender {
+    Channel(flume::Sender<LinkMessage>),
+    Spsc(spsc::Sender<LinkMessage>),
+}
+
+impl From<flume::Sender<LinkMessage>> for LinkSender {
+    fn from(sender: flume::Sender<LinkMessage>) -> Self {
+        Self(Sender::Channel(sender))
+    }
+}
+
+impl LinkSender {
+    pub(crate) fn try_send(&self, message: LinkMessage) -> Result<(), TrySendError<LinkMessage>> {
+        match &self.0 {
+            Sender::Channel(sender) => sender.try_send(message),
+            Sender::Spsc(sender) => sender.try_send(message),
+        }
+    }
+
+    pub(crate) async fn send_async(
+        &self,
+        message: LinkMessage,
+    ) -> Result<(), SendError<LinkMessage>> {
+        match &self.0 {
+            Sender::Channel(sender) => sender.send_async(message).await,
+            Sender::Spsc(sender) => sender.send_async(message).await,
+        }
+    }
+
+    /// Returns the number of messages waiting in the channel.
+    pub(crate) fn len(&self) -> usize {
+        match &self.0 {
+            Sender::Channel(sender) => sender.len(),
+            Sender::Spsc(sender) => sender.len(),
+        }
+    }
+
+    /// Returns the number of messages the channel can hold, `None` if it is unbounded.
+    pub(crate) fn capacity(&self) -> Option<usize> {
+        match &self.0 {
+            Sender::Channel(sender) => sender.capacity(),
+            Sender::Spsc(sender) => Some(sender.capacity()),
+        }
+    }
+
+    /// Returns `true` if both senders belong to the same channel.
+    pub(crate) fn same_channel(&self, other: &LinkSender) -> bool {
+        match (&self.0, &other.0) {
+            (Sender::Channel(sender), Sender::Channel(other)) => sender.same_channel(other),
+            (Sender::Spsc(sender), Sender::Spsc(other)) => sender.same_channel(other),
+            _ => false,
+        }
+    }
+}
+
+/// The receiving side of the channel of a link, held by the input it leads to.
+#[derive(Clone, Debug)]
+pub struct LinkReceiver(Receiver);
+
+#[derive(Clone, Debug)]
+enum Receiver {
+    Channel(flume::Receiver<LinkMessage>),
+    Spsc(spsc::Receiver<LinkMessage>),
+}
+
+impl From<flume::Receiver<LinkMessage>> for LinkReceiver {
+    fn from(receiver: flume::Receiver<LinkMessage>) -> Self {
+        Self(Receiver::Channel(receiver))
+    }
+}
+
+impl LinkReceiver {
+    pub(crate) fn try_recv(&self) -> Result<LinkMessage, TryRecvError> {
+        match &self.0 {
+            Receiver::Channel(receiver) => receiver.try_recv(),
+            Receiver::Spsc(receiver) => receiver.try_recv(),
+        }
+    }
+
+    pub(crate) fn recv_async(&self) -> RecvFut<'_> {
+        match &self.0 {
+            Receiver::Channel(receiver) => RecvFut::Channel(receiver.recv_async()),
+            Receiver::Spsc(receiver) => RecvFut::Spsc(receiver.recv_async()),
+        }
+    }
+
+    /// Receives all the messages waiting in the channel.
+    pub(crate) fn drain(&self) -> Vec<LinkMessage> {
+        match &self.0 {
+            Receiver::Channel(receiver) => receiver.drain().collect(),
+            Receiver::Spsc(receiver) => receiver.drain(),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        match &self.0 {
+            Receiver::Channel(receiver) => receiver.is_empty(),
+            Receiver::Spsc(receiver) => receiver.is_empty(),
+        }
+    }
+
+    /// Returns `true` if both receivers belong to the same channel.
+    pub(crate) fn same_channel(&self, other: &LinkReceiver) -> bool {
+        match (&self.0, &other.0) {
+            (Receiver::Channel(receiver), Receiver::Channel(other)) => receiver.same_channel(other),
+            (Receiver::Spsc(receiver), Receiver::Spsc(other)) => receiver.same_channel(other),
+            _ => false,
+        }
+    }
+}
+
+/// The future returned by [`LinkReceiver::recv_async`].
+pub(crate) enum RecvFut<'a> {
+    Channel(flume::r#async::RecvFut<'a, LinkMessage>),
+    Spsc(spsc::RecvFut<'a, LinkMessage>),
+}
+
+impl Future for RecvFut<'_> {
+    type Output = Result<LinkMessage, RecvError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.get_mut() {
+            RecvFut::Channel(future) => Pin::new(future).poll(cx),
+            RecvFut::Spsc(future) => Pin::new(future).poll(cx),
+        }
+    }
+}
@@ -12,8 +12,15 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+pub mod congestion;
 pub mod input;
+pub mod link;
 pub mod output;
+pub mod rule;
+pub(crate) mod spsc;
 
+pub use congestion::{Congestion, CongestionMonitor};
 pub use input::{Input, InputBuilder, InputRaw, Inputs};
+pub use link::{LinkReceiver, LinkSender};
 pub use output::{Output, OutputBuilder, OutputRaw, Outputs};
+pub use rule::{default_input_rule, InputRule, InputSet, Token, TokenAction, Tokens};
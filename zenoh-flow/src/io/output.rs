@@ -12,17 +12,22 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+use crate::io::congestion::CongestionMonitor;
+use crate::io::link::{LinkReceiver, LinkSender};
+use crate::model::descriptor::{InputDescriptor, OverflowPolicy, WarmupDescriptor};
 use crate::prelude::{Data, ErrorKind, PortId};
-use crate::types::{LinkMessage, Payload, SerializerFn};
+use crate::types::{LinkMessage, Payload, PayloadReference, SerializerFn, UnionData};
+use crate::zfresult::{ErrorContext, WithContext};
 use crate::{bail, zferror, Result};
-use flume::Sender;
+use flume::{SendError, Sender, TrySendError};
 use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::ops::Deref;
 use std::sync::{
-    atomic::{AtomicU64, Ordering},
-    Arc,
+    atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+    Arc, Mutex,
 };
+use std::time::{Duration, Instant};
 use uhlc::{Timestamp, HLC};
 
 /// The [Outputs] structure contains all the outputs created for a [Source](crate::prelude::Source)
@@ -35,15 +40,24 @@ use uhlc::{Timestamp, HLC};
 /// Zenoh-Flow provides two flavors of output: [OutputRaw] and [`Output<T>`]. An [`Output<T>`]
 /// conveniently accepts instances of `T` while an [OutputRaw] operates at the message level,
 /// potentially disregarding the data it contains.
+#[derive(Clone)]
 pub struct Outputs {
-    pub(crate) hmap: HashMap<PortId, Vec<flume::Sender<LinkMessage>>>,
+    pub(crate) hmap: HashMap<PortId, Vec<LinkSender>>,
+    pub(crate) caches: HashMap<PortId, Arc<LastValueCache>>,
+    pub(crate) taps: HashMap<PortId, Arc<OutputTap>>,
+    // The queues of the links, in the same order as their senders.
+    pub(crate) queues: HashMap<PortId, Vec<Option<Arc<LinkQueue>>>>,
+    // Shared by all the outputs of the node.
+    pub(crate) warmup: Arc<WarmUp>,
+    // Shared by all the outputs of the instance.
+    pub(crate) activity: Arc<LinkActivity>,
     pub(crate) hlc: Arc<HLC>,
 }
 
 // Dereferencing on the internal [`HashMap`](`Hashmap`) allows users to call all the methods
 // implemented on it: `keys()` for one.
 impl Deref for Outputs {
-    type Target = HashMap<PortId, Vec<flume::Sender<LinkMessage>>>;
+    type Target = HashMap<PortId, Vec<LinkSender>>;
 
     fn deref(&self) -> &Self::Target {
         &self.hmap
@@ -54,14 +68,61 @@ impl Outputs {
     pub(crate) fn new(hlc: Arc<HLC>) -> Self {
         Self {
             hmap: HashMap::default(),
+            caches: HashMap::default(),
+            taps: HashMap::default(),
+            queues: HashMap::default(),
+            warmup: Arc::default(),
+            activity: Arc::default(),
             hlc,
         }
     }
 
-    /// Insert the `flume::Sender` in the [Outputs], creating the entry if needed in the internal
-    /// `HashMap`.
-    pub(crate) fn insert(&mut self, port_id: PortId, tx: Sender<LinkMessage>) {
-        self.hmap.entry(port_id).or_insert_with(Vec::new).push(tx)
+    /// Insert the [LinkSender] in the [Outputs], creating the entry if needed in the internal
+    /// `HashMap`, along with the [LinkQueue] of the link, if it has one.
+    ///
+    /// Returns the [LastValueCache] of the output.
+    pub(crate) fn insert(
+        &mut self,
+        port_id: PortId,
+        tx: LinkSender,
+        queue: Option<Arc<LinkQueue>>,
+    ) -> Arc<LastValueCache> {
+        self.hmap
+            .entry(port_id.clone())
+            .or_insert_with(Vec::new)
+            .push(tx);
+        self.queues
+            .entry(port_id.clone())
+            .or_insert_with(Vec::new)
+            .push(queue);
+        self.taps.entry(port_id.clone()).or_default();
+        self.caches.entry(port_id).or_default().clone()
+    }
+
+    /// Removes the link starting from the output `port_id` whose channel is fed by `tx`, along with
+    /// its [LinkQueue]. The output is removed along with its last link.
+    pub(crate) fn remove(&mut self, port_id: &PortId, tx: &LinkSender) {
+        if let (Some(senders), Some(queues)) =
+            (self.hmap.get_mut(port_id), self.queues.get_mut(port_id))
+        {
+            if let Some(index) = senders.iter().position(|sender| sender.same_channel(tx)) {
+                senders.remove(index);
+                queues.remove(index);
+            }
+            if senders.is_empty() {
+                self.hmap.remove(port_id);
+                self.queues.remove(port_id);
+            }
+        }
+    }
+
+    /// Declares the output `port_id` as exposed, returning its [OutputTap]: the output exists even
+    /// if it is not connected to any node.
+    pub(crate) fn expose(&mut self, port_id: PortId) -> Arc<OutputTap> {
+        self.hmap.entry(port_id.clone()).or_default();
+        self.queues.entry(port_id.clone()).or_default();
+        self.caches.entry(port_id.clone()).or_default();
+        self.taps.entry(port_id).or_default().clone()
     }
 
     /// Returns an [OutputBuilder] for the provided `port_id`, if an output was declared with this
@@ -104,12 +165,378 @@ impl Outputs {
             .map(|senders| OutputBuilder {
                 port_id: port_id.as_ref().into(),
                 senders,
+                queues: self.queues.remove(port_id.as_ref()).unwrap_or_default(),
+                cache: self
+                    .caches
+                    .get(port_id.as_ref())
+                    .cloned()
+                    .unwrap_or_default(),
+                tap: self.taps.get(port_id.as_ref()).cloned().unwrap_or_default(),
+                warmup: self.warmup.clone(),
+                activity: self.activity.clone(),
                 hlc: Arc::clone(&self.hlc),
                 last_watermark: Arc::new(AtomicU64::new(
                     self.hlc.new_timestamp().get_time().as_u64(),
                 )),
             })
     }
+
+    /// Returns the [LinkQueue] of each link, with the output it starts from.
+    pub(crate) fn link_queues(&self) -> impl Iterator<Item = (&PortId, &Arc<LinkQueue>)> {
+        self.queues.iter().flat_map(|(port_id, queues)| {
+            queues.iter().flatten().map(move |queue| (port_id, queue))
+        })
+    }
+
+    /// Returns an [OutputBuilder] for the side output `port_id` (see
+    /// [`OperatorDescriptor`](crate::model::descriptor::OperatorDescriptor)).
+    ///
+    /// Contrary to `take`, an [OutputBuilder] is always returned: if the side output is not
+    /// connected, the messages sent on it are silently discarded. Whether it is connected can be
+    /// checked with `channels_count`.
+    ///
+    /// ```ignore
+    /// let rejected = outputs.take_side("rejected").raw();
+    /// ```
+    pub fn take_side(&mut self, port_id: impl AsRef<str>) -> OutputBuilder {
+        match self.take(port_id.as_ref()) {
+            Some(builder) => builder,
+            None => OutputBuilder {
+                port_id: port_id.as_ref().into(),
+                senders: Vec::new(),
+                queues: Vec::new(),
+                cache: Arc::default(),
+                tap: Arc::default(),
+                warmup: self.warmup.clone(),
+                activity: self.activity.clone(),
+                hlc: Arc::clone(&self.hlc),
+                last_watermark: Arc::new(AtomicU64::new(
+                    self.hlc.new_timestamp().get_time().as_u64(),
+                )),
+            },
+        }
+    }
+}
+
+/// The `LinkQueue` bounds the channel of a link declaring a queue (see
+/// [`QueueDescriptor`](crate::model::descriptor::QueueDescriptor)): when it is full, a data message
+/// is dropped according to the overflow policy and counted.
+///
+/// The other messages (control markers, watermarks and end of streams) are never dropped.
+#[derive(Debug)]
+pub(crate) struct LinkQueue {
+    pub(crate) to: InputDescriptor,
+    overflow: OverflowPolicy,
+    // A receiver of the channel, to drop its oldest data message.
+    rx: LinkReceiver,
+    dropped: AtomicU64,
+}
+
+impl LinkQueue {
+    pub(crate) fn new(to: InputDescriptor, overflow: OverflowPolicy, rx: LinkReceiver) -> Self {
+        Self {
+            to,
+            overflow,
+            rx,
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the number of messages dropped so far.
+    pub(crate) fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Sends the `message` on `tx`, the sender of the link, without ever blocking: if the queue is
+    /// full, a data message is dropped instead.
+    ///
+    /// A message that is not a data message is never dropped: the oldest data message waiting in
+    /// the queue is dropped to make room for it, whatever the overflow policy.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the link is disconnected or if the queue is full without any data
+    /// message to drop in favour of a message that is not a data message.
+    pub(crate) fn push(
+        &self,
+        tx: &LinkSender,
+        message: LinkMessage,
+    ) -> std::result::Result<(), TrySendError<LinkMessage>> {
+        let message = match tx.try_send(message) {
+            Ok(()) => return Ok(()),
+            Err(TrySendError::Disconnected(message)) => {
+                return Err(TrySendError::Disconnected(message))
+            }
+            Err(TrySendError::Full(message)) => message,
+        };
+
+        let is_data = matches!(message, LinkMessage::Data(_));
+        if is_data && self.overflow == OverflowPolicy::DropNewest {
+            self.drop_newest();
+            return Ok(());
+        }
+
+        if !self.drop_oldest(tx) {
+            if is_data {
+                self.drop_newest();
+                return Ok(());
+            }
+            return Err(TrySendError::Full(message));
+        }
+
+        match tx.try_send(message) {
+            // Another message took the freed slot: the newest is dropped after all.
+            Err(TrySendError::Full(_)) if is_data => {
+                self.drop_newest();
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    /// Sends the `message` on `tx` as [push](LinkQueue::push) does, waiting for a slot if the
+    /// message cannot be dropped and only such messages are waiting in the queue.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the link is disconnected.
+    pub(crate) async fn push_async(
+        &self,
+        tx: &LinkSender,
+        message: LinkMessage,
+    ) -> std::result::Result<(), SendError<LinkMessage>> {
+        match self.push(tx, message) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(message)) => tx.send_async(message).await,
+            Err(TrySendError::Disconnected(message)) => Err(SendError(message)),
+        }
+    }
+
+    /// Counts the newest data message as dropped.
+    fn drop_newest(&self) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+        log::trace!(
+            "[Link: {}] Queue full, dropping the newest message",
+            self.to
+        );
+    }
+
+    /// Drops the oldest data message waiting in the queue, the other messages are sent back in
+    /// order. Returns `false` if no data message is waiting.
+    fn drop_oldest(&self, tx: &LinkSender) -> bool {
+        let mut waiting = self.rx.drain();
+        let oldest = waiting
+            .iter()
+            .position(|message| matches!(message, LinkMessage::Data(_)));
+        if let Some(index) = oldest {
+            waiting.remove(index);
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            log::trace!(
+                "[Link: {}] Queue full, dropping the oldest message",
+                self.to
+            );
+        }
+
+        // The senders blocked on the full queue are still waiting: the drained messages fit back,
+        // ahead of theirs.
+        for message in waiting {
+            if tx.try_send(message).is_err() {
+                log::error!(
+                    "[Link: {}] A message could not be put back in the queue",
+                    self.to
+                );
+            }
+        }
+
+        oldest.is_some()
+    }
+}
+
+/// The `LastValueCache` keeps the last data message sent on an output, if the output opted into
+/// last value caching (see [`OutputBuilder::cache_last_value`]).
+#[derive(Debug, Default)]
+pub(crate) struct LastValueCache {
+    pub(crate) enabled: AtomicBool,
+    pub(crate) last: Mutex<Option<LinkMessage>>,
+}
+
+impl LastValueCache {
+    /// Keeps the `message` if it is a data message and the caching is enabled.
+    pub(crate) fn store(&self, message: &LinkMessage) {
+        if !self.enabled.load(Ordering::Relaxed) {
+            return;
+        }
+
+        if let LinkMessage::Data(_) = message {
+            if let Ok(mut last) = self.last.lock() {
+                *last = Some(message.clone());
+            }
+        }
+    }
+
+    /// Returns the last data message sent on the output, if any.
+    pub(crate) fn get(&self) -> Option<LinkMessage> {
+        self.last.lock().ok().and_then(|last| last.clone())
+    }
+}
+
+/// The `WarmUp` of a node discards the data sent on its outputs until it is over (see
+/// [`WarmupDescriptor`]). It is shared by all the outputs of the node and started again every time
+/// the node is started.
+///
+/// A node without a warm-up is always warm.
+#[derive(Debug)]
+pub(crate) struct WarmUp {
+    descriptor: WarmupDescriptor,
+    over: AtomicBool,
+    activations: AtomicUsize,
+    started: Mutex<Instant>,
+}
+
+impl Default for WarmUp {
+    fn default() -> Self {
+        Self::new(WarmupDescriptor::default())
+    }
+}
+
+impl WarmUp {
+    pub(crate) fn new(descriptor: WarmupDescriptor) -> Self {
+        Self {
+            descriptor,
+            over: AtomicBool::new(true),
+            activations: AtomicUsize::new(0),
+            started: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Starts the warm-up, if the node has one.
+    pub(crate) fn start(&self) {
+        if self.descriptor.duration.is_none() && self.descriptor.activations.is_none() {
+            return;
+        }
+
+        if let Ok(mut started) = self.started.lock() {
+            *started = Instant::now();
+        }
+        self.activations.store(0, Ordering::Relaxed);
+        self.over.store(false, Ordering::Relaxed);
+    }
+
+    /// Counts an activation (i.e. an iteration) of the node.
+    pub(crate) fn activated(&self) {
+        if !self.over.load(Ordering::Relaxed) {
+            self.activations.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Tells if the warm-up is over: its duration elapsed and the node was activated enough times.
+    pub(crate) fn is_over(&self) -> bool {
+        if self.over.load(Ordering::Relaxed) {
+            return true;
+        }
+
+        let elapsed = self.descriptor.duration.map_or(true, |duration| {
+            self.started
+                .lock()
+                .map_or(true, |started| started.elapsed() >= duration)
+        });
+        let activated = self.descriptor.activations.map_or(true, |activations| {
+            self.activations.load(Ordering::Relaxed) >= activations
+        });
+
+        let over = elapsed && activated;
+        if over {
+            self.over.store(true, Ordering::Relaxed);
+        }
+        over
+    }
+}
+
+/// The `LinkActivity` tracks when a message was last sent on the outputs of the nodes of an
+/// instance, for the instance to detect that it is idle (see
+/// [`DataFlowInstance::idle_time`](crate::runtime::dataflow::instance::DataFlowInstance::idle_time)).
+#[derive(Debug)]
+pub(crate) struct LinkActivity {
+    origin: Instant,
+    // The milliseconds elapsed between the origin and the last message.
+    last: AtomicU64,
+}
+
+impl Default for LinkActivity {
+    fn default() -> Self {
+        Self {
+            origin: Instant::now(),
+            last: AtomicU64::new(0),
+        }
+    }
+}
+
+impl LinkActivity {
+    /// Records that a message was sent now.
+    pub(crate) fn touch(&self) {
+        self.last
+            .store(self.origin.elapsed().as_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// Returns the time elapsed since the last message was sent, or since the activity was last
+    /// touched.
+    pub(crate) fn idle_time(&self) -> Duration {
+        self.origin
+            .elapsed()
+            .saturating_sub(Duration::from_millis(self.last.load(Ordering::Relaxed)))
+    }
+}
+
+/// Maximum number of messages waiting to be processed by a debug tap (1024).
+static TAP_CAPACITY: usize = 1024;
+
+/// The `OutputTap` copies the messages sent on an output to the debug taps attached to it (see
+/// [`DataFlowInstance::tap`](crate::runtime::dataflow::instance::DataFlowInstance::tap)) and counts
+/// them (see
+/// [`DataFlowInstance::sent_messages`](crate::runtime::dataflow::instance::DataFlowInstance::sent_messages)).
+///
+/// A tap never slows down the output: if a tap cannot keep up, the messages it misses are dropped.
+#[derive(Debug, Default)]
+pub(crate) struct OutputTap {
+    pub(crate) attached: AtomicBool,
+    pub(crate) senders: Mutex<Vec<Sender<LinkMessage>>>,
+    sent: AtomicU64,
+}
+
+impl OutputTap {
+    /// Attaches a new tap, returning the channel on which the messages will be copied.
+    pub(crate) fn attach(&self) -> flume::Receiver<LinkMessage> {
+        let (tx, rx) = flume::bounded(TAP_CAPACITY);
+        if let Ok(mut senders) = self.senders.lock() {
+            senders.push(tx);
+            self.attached.store(true, Ordering::Relaxed);
+        }
+        rx
+    }
+
+    /// Returns the number of messages sent on the output.
+    pub(crate) fn sent(&self) -> u64 {
+        self.sent.load(Ordering::Relaxed)
+    }
+
+    /// Counts the `message` and copies it to the attached taps, detaching the ones that were
+    /// dropped.
+    pub(crate) fn copy(&self, message: &LinkMessage) {
+        self.sent.fetch_add(1, Ordering::Relaxed);
+        if !self.attached.load(Ordering::Relaxed) {
+            return;
+        }
+
+        if let Ok(mut senders) = self.senders.lock() {
+            senders.retain(|sender| {
+                !matches!(
+                    sender.try_send(message.clone()),
+                    Err(flume::TrySendError::Disconnected(_))
+                )
+            });
+            self.attached.store(!senders.is_empty(), Ordering::Relaxed);
+        }
+    }
 }
 
 /// An [OutputBuilder] is the intermediate structure to obtain either an [`Output<T>`] or an
@@ -125,12 +552,39 @@ impl Outputs {
 /// channels are _unbounded_ and do not implement a dropping policy, which could lead to issues.
 pub struct OutputBuilder {
     pub(crate) port_id: PortId,
-    pub(crate) senders: Vec<flume::Sender<LinkMessage>>,
+    pub(crate) senders: Vec<LinkSender>,
+    pub(crate) queues: Vec<Option<Arc<LinkQueue>>>,
+    pub(crate) cache: Arc<LastValueCache>,
+    pub(crate) tap: Arc<OutputTap>,
+    pub(crate) warmup: Arc<WarmUp>,
+    pub(crate) activity: Arc<LinkActivity>,
     pub(crate) hlc: Arc<HLC>,
     pub(crate) last_watermark: Arc<AtomicU64>,
 }
 
 impl OutputBuilder {
+    /// Opt into last value caching: the last data message sent on the output is kept and, when a
+    /// downstream node is restarted (see
+    /// [`DataFlowInstance::restart_node`](crate::runtime::dataflow::instance::DataFlowInstance::restart_node)),
+    /// it is sent again to that node if no message is waiting in its channel.
+    ///
+    /// This allows restarted nodes to immediately receive the most recent value of each of their
+    /// inputs instead of waiting for the next one.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let output_raw = outputs
+    ///     .take("test")
+    ///     .expect("No key named 'test' found")
+    ///     .cache_last_value()
+    ///     .raw();
+    /// ```
+    pub fn cache_last_value(self) -> Self {
+        self.cache.enabled.store(true, Ordering::Relaxed);
+        self
+    }
+
     /// Consume this `OutputBuilder` to produce an [OutputRaw].
     ///
     /// An [OutputRaw] sends [LinkMessage]s (through `forward`) or anything that is
@@ -158,6 +612,11 @@ impl OutputBuilder {
         OutputRaw {
             port_id: self.port_id,
             senders: self.senders,
+            queues: self.queues,
+            cache: self.cache,
+            tap: self.tap,
+            warmup: self.warmup,
+            activity: self.activity,
             hlc: self.hlc,
             last_watermark: self.last_watermark,
         }
@@ -195,6 +654,7 @@ impl OutputBuilder {
         Output {
             _phantom: PhantomData,
             output_raw: self.raw(),
+            variant: None,
             serializer: Arc::new(move |buffer, data| {
                 if let Some(typed) = (*data).as_any().downcast_ref::<T>() {
                     match (serializer)(buffer, typed) {
@@ -210,6 +670,26 @@ impl OutputBuilder {
             }),
         }
     }
+
+    /// Consume this `OutputBuilder` to produce an [`Output<U>`] sending the data of a union port
+    /// (see [UnionData]).
+    ///
+    /// The index of the variant of each data sent is carried in the header of its message: the
+    /// value it holds is serialized on its own.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let output_reading: Output<Reading> = outputs
+    ///     .take("Reading")
+    ///     .expect("No key named 'Reading' found")
+    ///     .union();
+    /// ```
+    pub fn union<U: UnionData>(self) -> Output<U> {
+        let mut output = self.typed(|buffer, data: &U| data.serialize_variant(buffer));
+        output.variant = Some(U::variant);
+        output
+    }
 }
 
 /// An [OutputRaw] sends [LinkMessage] or `Into<`[Payload]`>` to downstream Nodes.
@@ -220,7 +700,12 @@ impl OutputBuilder {
 #[derive(Clone)]
 pub struct OutputRaw {
     pub(crate) port_id: PortId,
-    pub(crate) senders: Vec<flume::Sender<LinkMessage>>,
+    pub(crate) senders: Vec<LinkSender>,
+    pub(crate) queues: Vec<Option<Arc<LinkQueue>>>,
+    pub(crate) cache: Arc<LastValueCache>,
+    pub(crate) tap: Arc<OutputTap>,
+    pub(crate) warmup: Arc<WarmUp>,
+    pub(crate) activity: Arc<LinkActivity>,
     pub(crate) hlc: Arc<HLC>,
     pub(crate) last_watermark: Arc<AtomicU64>,
 }
@@ -236,6 +721,33 @@ impl OutputRaw {
         self.senders.len()
     }
 
+    /// Returns a [CongestionMonitor] observing the links of this Output, for the node to adapt its
+    /// throughput when the downstream nodes cannot keep up.
+    pub fn congestion_monitor(&self) -> CongestionMonitor {
+        CongestionMonitor::new(
+            self.senders.clone(),
+            self.queues.iter().flatten().cloned().collect(),
+        )
+    }
+
+    /// Returns the [LinkQueue] of the channel at `index`, if its link declared a queue.
+    fn queue(&self, index: usize) -> Option<&Arc<LinkQueue>> {
+        self.queues.get(index).and_then(|queue| queue.as_ref())
+    }
+
+    /// Tells if the `message` must be discarded because the node is warming up: only data
+    /// messages are.
+    fn is_warming_up(&self, message: &LinkMessage) -> bool {
+        if let LinkMessage::Data(_) = message {
+            if !self.warmup.is_over() {
+                log::trace!("[Output: {}] Warming up, discarding data", self.port_id);
+                return true;
+            }
+        }
+
+        false
+    }
+
     /// If a timestamp is provided, check that it is not inferior to the latest watermark.
     ///
     /// If no timestamp is provided, a new one is generated from the [HLC](uhlc::HLC).
@@ -252,6 +764,16 @@ impl OutputRaw {
         Ok(ts)
     }
 
+    /// Returns the [Timestamp] of the event time `event_time`, attributed to the [HLC](uhlc::HLC)
+    /// used by the Zenoh-Flow daemon running this Node.
+    ///
+    /// Contrary to the timestamp of a message, the event time is not checked against the latest
+    /// watermark: it is set by the component that produced the data and is not used to order the
+    /// messages.
+    pub(crate) fn event_timestamp(&self, event_time: u64) -> Timestamp {
+        Timestamp::new(uhlc::NTP64(event_time), *self.hlc.get_id())
+    }
+
     /// Attempt to forward, *synchronously*, the message to the downstream Nodes.
     ///
     /// # Asynchronous alternative: `forward`
@@ -265,9 +787,22 @@ impl OutputRaw {
     /// If an error occurs while sending the message on a channel, Zenoh-Flow still tries to send it
     /// on the remaining channels. For each failing channel, an error is logged.
     pub(crate) fn try_forward(&self, message: LinkMessage) -> Result<()> {
+        if self.is_warming_up(&message) {
+            return Ok(());
+        }
+
+        self.cache.store(&message);
+        self.tap.copy(&message);
+        self.activity.touch();
+
         let mut err_count = 0;
-        self.senders.iter().for_each(|sender| {
-            if let Err(e) = sender.try_send(message.clone()) {
+        self.senders.iter().enumerate().for_each(|(index, sender)| {
+            let result = match self.queue(index) {
+                Some(queue) => queue.push(sender, message.clone()),
+                None => sender.try_send(message.clone()),
+            };
+
+            if let Err(e) = result {
                 err_count += 1;
                 match e {
                     flume::TrySendError::Full(_) => {
@@ -314,6 +849,38 @@ impl OutputRaw {
         self.try_forward(message)
     }
 
+    /// Attempt to send, *synchronously*, the `data` along with the `event_time` assigned to it on
+    /// all channels to the downstream Nodes.
+    ///
+    /// The `event_time` is the time at which the data was produced, e.g. the capture time of a
+    /// camera frame. The message is still timestamped with the current timestamp (as per the
+    /// [HLC](uhlc::HLC) used by the Zenoh-Flow daemon running this Node): its processing time.
+    ///
+    /// # Asynchronous alternative: `send_with_event_time`
+    ///
+    /// This method is a synchronous fail-fast alternative to its asynchronous counterpart:
+    /// `send_with_event_time`. Hence, although synchronous, this method will not block the thread
+    /// on which it is executed.
+    ///
+    /// # Errors
+    ///
+    /// If an error occurs while sending the message on a channel, Zenoh-Flow still tries to send
+    /// it on the remaining channels. For each failing channel, an error is logged and counted for.
+    pub fn try_send_with_event_time(
+        &self,
+        data: impl Into<Payload>,
+        event_time: u64,
+    ) -> Result<()> {
+        let ts = self.check_timestamp(None)?;
+        let message = LinkMessage::from_payload_with_event_time(
+            data.into(),
+            ts,
+            Some(self.event_timestamp(event_time)),
+        );
+
+        self.try_forward(message)
+    }
+
     /// Attempt to send, *synchronously*, the watermark on all channels to the downstream Nodes.
     ///
     /// If no `timestamp` is provided, the current timestamp (as per the [HLC](uhlc::HLC) used by
@@ -343,12 +910,26 @@ impl OutputRaw {
     /// If an error occurs while sending the message on a channel, Zenoh-Flow still tries to send
     /// it on the remaining channels. For each failing channel, an error is logged and counted for.
     pub async fn forward(&self, message: LinkMessage) -> Result<()> {
+        if self.is_warming_up(&message) {
+            return Ok(());
+        }
+
+        self.cache.store(&message);
+        self.tap.copy(&message);
+        self.activity.touch();
+
         // FIXME Feels like a cheap hack counting the number of errors. To improve.
         let mut err = 0;
-        let fut_senders = self
-            .senders
-            .iter()
-            .map(|sender| sender.send_async(message.clone()));
+        // The links with a queue never block: a message is dropped instead.
+        let fut_senders = self.senders.iter().enumerate().map(|(index, sender)| {
+            let message = message.clone();
+            async move {
+                match self.queue(index) {
+                    Some(queue) => queue.push_async(sender, message).await,
+                    None => sender.send_async(message).await,
+                }
+            }
+        });
         // [`join_all`](`futures::future::join_all`) executes all futures in parallel.
         let res = futures::future::join_all(fut_senders).await;
 
@@ -392,6 +973,53 @@ impl OutputRaw {
         self.forward(message).await
     }
 
+    /// Send, *asynchronously*, the `data` along with the `event_time` assigned to it on all
+    /// channels to the downstream Nodes.
+    ///
+    /// The `event_time` is the time at which the data was produced, e.g. the capture time of a
+    /// camera frame. The message is still timestamped with the current timestamp (as per the
+    /// [HLC](uhlc::HLC) used by the Zenoh-Flow daemon running this Node): its processing time.
+    ///
+    /// # Errors
+    ///
+    /// If an error occurs while sending the message on a channel, Zenoh-Flow still tries to send
+    /// it on the remaining channels. For each failing channel, an error is logged and counted for.
+    pub async fn send_with_event_time(
+        &self,
+        data: impl Into<Payload>,
+        event_time: u64,
+    ) -> Result<()> {
+        let ts = self.check_timestamp(None)?;
+        let message = LinkMessage::from_payload_with_event_time(
+            data.into(),
+            ts,
+            Some(self.event_timestamp(event_time)),
+        );
+
+        self.forward(message).await
+    }
+
+    /// Send, *asynchronously*, a reference to data stored in Zenoh on all channels to the
+    /// downstream Nodes.
+    ///
+    /// The data is only retrieved by the downstream Nodes that read it (see [PayloadReference]).
+    /// It must be stored under the key expression of the `reference` before it is sent.
+    ///
+    /// If no `timestamp` is provided, the current timestamp — as per the [HLC](uhlc::HLC) used by
+    /// the Zenoh-Flow daemon running this Node — is taken.
+    ///
+    /// # Errors
+    ///
+    /// If an error occurs while sending the message on a channel, Zenoh-Flow still tries to send
+    /// it on the remaining channels. For each failing channel, an error is logged and counted for.
+    pub async fn send_reference(
+        &self,
+        reference: PayloadReference,
+        timestamp: Option<u64>,
+    ) -> Result<()> {
+        self.send(reference, timestamp).await
+    }
+
     /// Send, *asynchronously*, a [Watermark](LinkMessage::Watermark) on all channels.
     ///
     /// If no `timestamp` is provided, the current timestamp (as per the [HLC](uhlc::HLC) used by
@@ -414,6 +1042,37 @@ impl OutputRaw {
         let message = LinkMessage::Watermark(ts);
         self.forward(message).await
     }
+
+    /// Send, *asynchronously*, an [EndOfStream](LinkMessage::EndOfStream) on all channels.
+    ///
+    /// An [EndOfStream](LinkMessage::EndOfStream) signals to the downstream Nodes that no more
+    /// message will be sent on this output. It is used to process finite data sets: once all the
+    /// Sinks of a data flow instance received it, the instance is complete (see
+    /// [`DataFlowInstance::wait_complete`](crate::runtime::dataflow::instance::DataFlowInstance::wait_complete)).
+    ///
+    /// # Errors
+    ///
+    /// If an error occurs while sending the message on a channel, Zenoh-Flow still tries to send
+    /// it on the remaining channels. For each failing channel, an error is logged and counted for.
+    pub async fn send_end_of_stream(&self) -> Result<()> {
+        let ts = self.check_timestamp(None)?;
+        self.forward(LinkMessage::EndOfStream(ts)).await
+    }
+
+    /// Send, *asynchronously*, a [Live](LinkMessage::Live) on all channels.
+    ///
+    /// A [Live](LinkMessage::Live) is sent by a Source in backfill mode once it replayed all the
+    /// historical data it had access to: it signals to the downstream Nodes that the messages that
+    /// follow are live.
+    ///
+    /// # Errors
+    ///
+    /// If an error occurs while sending the message on a channel, Zenoh-Flow still tries to send
+    /// it on the remaining channels. For each failing channel, an error is logged and counted for.
+    pub async fn send_live(&self) -> Result<()> {
+        let ts = self.check_timestamp(None)?;
+        self.forward(LinkMessage::Live(ts)).await
+    }
 }
 
 /// An [`Output<T>`] sends instances of `T` to downstream Nodes.
@@ -425,6 +1084,7 @@ pub struct Output<T> {
     _phantom: PhantomData<T>,
     pub(crate) output_raw: OutputRaw,
     pub(crate) serializer: Arc<SerializerFn>,
+    pub(crate) variant: Option<fn(&T) -> u32>,
 }
 
 // Dereferencing to the [`OutputRaw`](`OutputRaw`) allows to directly call methods on it with a
@@ -439,14 +1099,26 @@ impl<T> Deref for Output<T> {
 
 impl<T: Send + Sync + 'static> Output<T> {
     // Construct the `LinkMessage` to send.
+    //
+    // If no `event_time` is provided, the one carried by the `data` (if it was received) is kept.
     fn construct_message(
         &self,
         data: impl Into<Data<T>>,
         timestamp: Option<u64>,
+        event_time: Option<u64>,
     ) -> Result<LinkMessage> {
         let ts = self.check_timestamp(timestamp)?;
-        let payload = Payload::from_data(data.into(), Arc::clone(&self.serializer));
-        Ok(LinkMessage::from_payload(payload, ts))
+        let data = data.into();
+        let event_time = match event_time {
+            Some(event_time) => Some(self.event_timestamp(event_time)),
+            None => data.event_time().copied(),
+        };
+        let variant = self.variant.map(|variant| (variant)(&data));
+        let payload = Payload::from_data(data, Arc::clone(&self.serializer));
+        Ok(
+            LinkMessage::from_payload_with_event_time(payload, ts, event_time)
+                .with_variant(variant),
+        )
     }
 
     /// Send, *asynchronously*, the provided `data` to all downstream Nodes.
@@ -466,8 +1138,9 @@ impl<T: Send + Sync + 'static> Output<T> {
     /// total number of encountered errors is returned.
     pub async fn send(&self, data: impl Into<Data<T>>, timestamp: Option<u64>) -> Result<()> {
         self.output_raw
-            .forward(self.construct_message(data, timestamp)?)
+            .forward(self.construct_message(data, timestamp, None)?)
             .await
+            .context(ErrorContext::Output(self.output_raw.port_id.clone()))
     }
 
     /// Tries to send the provided `data` to all downstream Nodes.
@@ -487,7 +1160,51 @@ impl<T: Send + Sync + 'static> Output<T> {
     /// total number of encountered errors is returned.
     pub fn try_send(&self, data: impl Into<Data<T>>, timestamp: Option<u64>) -> Result<()> {
         self.output_raw
-            .try_forward(self.construct_message(data, timestamp)?)
+            .try_forward(self.construct_message(data, timestamp, None)?)
+            .context(ErrorContext::Output(self.output_raw.port_id.clone()))
+    }
+
+    /// Send, *asynchronously*, the provided `data` along with the `event_time` assigned to it to
+    /// all downstream Nodes.
+    ///
+    /// The `event_time` is the time at which the data was produced, e.g. the capture time of a
+    /// camera frame. The message is still timestamped with the current timestamp (as per the
+    /// [HLC](uhlc::HLC) used by the Zenoh-Flow daemon running this Node): its processing time.
+    ///
+    /// # Errors
+    ///
+    /// If an error occurs while sending the message on a channel, Zenoh-Flow still tries to send it
+    /// on the remaining channels. For each failing channel, an error is logged and counted for. The
+    /// total number of encountered errors is returned.
+    pub async fn send_with_event_time(
+        &self,
+        data: impl Into<Data<T>>,
+        event_time: u64,
+    ) -> Result<()> {
+        self.output_raw
+            .forward(self.construct_message(data, None, Some(event_time))?)
+            .await
+            .context(ErrorContext::Output(self.output_raw.port_id.clone()))
+    }
+
+    /// Tries to send the provided `data` along with the `event_time` assigned to it to all
+    /// downstream Nodes.
+    ///
+    /// See [`send_with_event_time`](Output::send_with_event_time).
+    ///
+    /// # Errors
+    ///
+    /// If an error occurs while sending the message on a channel, Zenoh-Flow still tries to send it
+    /// on the remaining channels. For each failing channel, an error is logged and counted for. The
+    /// total number of encountered errors is returned.
+    pub fn try_send_with_event_time(
+        &self,
+        data: impl Into<Data<T>>,
+        event_time: u64,
+    ) -> Result<()> {
+        self.output_raw
+            .try_forward(self.construct_message(data, None, Some(event_time))?)
+            .context(ErrorContext::Output(self.output_raw.port_id.clone()))
     }
 }
 
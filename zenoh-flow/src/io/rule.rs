@@ -0,0 +1,337 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::io::InputRaw;
+use crate::model::descriptor::InputPolicyDescriptor;
+use crate::prelude::{ErrorKind, PortId};
+use crate::runtime::dataflow::instance::builtin::zenoh::{wait_flow_input, ZFInputFut};
+use crate::types::{DataMessage, LinkMessage};
+use crate::{bail, Result};
+
+use async_lock::Mutex;
+use futures::future::select_all;
+use std::collections::HashMap;
+
+pub use crate::model::descriptor::TokenAction;
+
+/// The state of an input of an [InputSet]: either it waits for data or it holds data, received
+/// since the node was last triggered or kept from a previous iteration (see [TokenAction]).
+#[derive(Debug, Clone)]
+pub enum Token {
+    Pending,
+    Ready(DataMessage),
+}
+
+impl Token {
+    /// Returns `true` if the input holds data.
+    pub fn is_ready(&self) -> bool {
+        matches!(self, Token::Ready(_))
+    }
+}
+
+/// The token of an input, whether it holds data received since the node was last triggered and
+/// how it is considered by the input rule.
+#[derive(Debug)]
+struct Slot {
+    token: Token,
+    fresh: bool,
+    required: bool,
+    // The action declared in the descriptor and the one applied when the node is next triggered.
+    policy: TokenAction,
+    action: TokenAction,
+}
+
+impl Slot {
+    fn new(policy: InputPolicyDescriptor) -> Self {
+        Self {
+            token: Token::Pending,
+            fresh: false,
+            required: policy.required,
+            policy: policy.token,
+            action: policy.token,
+        }
+    }
+}
+
+/// The tokens of all the inputs of an [InputSet], given to its [InputRule].
+///
+/// Besides deciding if the node is triggered, a rule can override, for this time only, the
+/// [TokenAction] declared for an input with [`set_action`](Tokens::set_action).
+#[derive(Debug, Default)]
+pub struct Tokens {
+    slots: HashMap<PortId, Slot>,
+}
+
+impl Tokens {
+    /// Returns the token of the input `port_id`, if it is part of the set.
+    pub fn get(&self, port_id: impl AsRef<str>) -> Option<&Token> {
+        self.slots.get(port_id.as_ref()).map(|slot| &slot.token)
+    }
+
+    /// Returns `true` if the input `port_id` is part of the set and is required (see
+    /// [InputPolicyDescriptor]).
+    pub fn is_required(&self, port_id: impl AsRef<str>) -> bool {
+        self.slots
+            .get(port_id.as_ref())
+            .map_or(false, |slot| slot.required)
+    }
+
+    /// Returns `true` if the input `port_id` is part of the set and received data since the node
+    /// was last triggered, as opposed to data kept from a previous iteration.
+    pub fn is_fresh(&self, port_id: impl AsRef<str>) -> bool {
+        self.slots
+            .get(port_id.as_ref())
+            .map_or(false, |slot| slot.fresh)
+    }
+
+    /// Returns the [TokenAction] applied to the input `port_id` if the node is triggered.
+    pub fn action(&self, port_id: impl AsRef<str>) -> Option<TokenAction> {
+        self.slots.get(port_id.as_ref()).map(|slot| slot.action)
+    }
+
+    /// Sets the [TokenAction] applied to the input `port_id` if the node is triggered, in place of
+    /// the one declared in the descriptor. It is reset once the rule was evaluated again.
+    ///
+    /// Returns `false` if the input is not part of the set.
+    pub fn set_action(&mut self, port_id: impl AsRef<str>, action: TokenAction) -> bool {
+        match self.slots.get_mut(port_id.as_ref()) {
+            Some(slot) => {
+                slot.action = action;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns an iterator over the inputs of the set and their token.
+    pub fn iter(&self) -> impl Iterator<Item = (&PortId, &Token)> {
+        self.slots
+            .iter()
+            .map(|(port_id, slot)| (port_id, &slot.token))
+    }
+
+    /// Sets the token of `port_id` to `Ready`, replacing the data it possibly held.
+    fn ready(&mut self, port_id: &PortId, message: DataMessage) {
+        if let Some(slot) = self.slots.get_mut(port_id) {
+            slot.token = Token::Ready(message);
+            slot.fresh = true;
+        }
+    }
+
+    /// Restores the actions declared in the descriptor, before the rule is evaluated.
+    fn reset_actions(&mut self) {
+        self.slots
+            .values_mut()
+            .for_each(|slot| slot.action = slot.policy);
+    }
+
+    /// Applies the action of each `Ready` token, returning the data given to the node and the
+    /// inputs that received data since it was last triggered --- they wait for new data again.
+    fn trigger(&mut self) -> (HashMap<PortId, DataMessage>, Vec<PortId>) {
+        let mut data = HashMap::with_capacity(self.slots.len());
+        let mut fresh = Vec::with_capacity(self.slots.len());
+
+        for (port_id, slot) in self.slots.iter_mut() {
+            if std::mem::take(&mut slot.fresh) {
+                fresh.push(port_id.clone());
+            }
+
+            let message = match slot.action {
+                TokenAction::Keep => match &slot.token {
+                    Token::Ready(message) => Some(message.clone()),
+                    Token::Pending => None,
+                },
+                TokenAction::Consume => match std::mem::replace(&mut slot.token, Token::Pending) {
+                    Token::Ready(message) => Some(message),
+                    Token::Pending => None,
+                },
+                TokenAction::Drop => {
+                    slot.token = Token::Pending;
+                    None
+                }
+            };
+
+            if let Some(message) = message {
+                data.insert(port_id.clone(), message);
+            }
+        }
+
+        (data, fresh)
+    }
+}
+
+/// A function deciding, from the tokens of its inputs, if an [InputSet] triggers the node, i.e.
+/// gives it their data.
+///
+/// A rule can also set, with [`Tokens::set_action`], what becomes of each token once the node is
+/// triggered.
+///
+/// # Example
+///
+/// A rule triggering the node only once both inputs received new data, and keeping the last
+/// `Reference` for the next iterations:
+///
+/// ```ignore
+/// fn rule(tokens: &mut Tokens) -> bool {
+///     tokens.set_action("Reference", TokenAction::Keep);
+///     tokens.is_fresh("Measure") && tokens.is_fresh("Reference")
+/// }
+///
+/// let input_set = inputs.take_set().with_rule(rule);
+/// ```
+pub type InputRule = fn(&mut Tokens) -> bool;
+
+/// The input rule used by default: the node is triggered once all its required inputs hold data,
+/// and at least one of its inputs received new data.
+///
+/// An input that is not required (see [InputPolicyDescriptor]) never blocks the node: its data are
+/// given along with those of the required inputs when there are some. The data kept from a
+/// previous iteration (see [TokenAction]) are given again but do not trigger the node on their
+/// own.
+pub fn default_input_rule(tokens: &mut Tokens) -> bool {
+    tokens
+        .slots
+        .values()
+        .all(|slot| !slot.required || slot.token.is_ready())
+        && tokens.slots.values().any(|slot| slot.fresh)
+}
+
+/// The state of an [InputSet], behind a lock as a node only has a shared reference on itself.
+struct InputSetState {
+    tokens: Tokens,
+    futs: Vec<ZFInputFut>,
+}
+
+/// An `InputSet` groups inputs of a node and gives it their data together, once its [InputRule]
+/// allows it (by default, the [default_input_rule]).
+///
+/// The data of an input are then consumed, kept for the next iterations or dropped following the
+/// `token` declared in its `input_policies` (see [TokenAction]).
+///
+/// Only the data messages are given to the node: the watermarks are discarded and an input that
+/// reached its end of stream no longer receives data.
+///
+/// # Example
+///
+/// ```ignore
+/// let input_set = inputs.take_set();
+///
+/// // In the iteration of the node.
+/// let data = input_set.recv().await?;
+/// if let Some(calibration) = data.get("Calibration") {
+///     // Update the calibration.
+/// }
+/// ```
+pub struct InputSet {
+    inputs: HashMap<PortId, InputRaw>,
+    rule: InputRule,
+    state: Mutex<InputSetState>,
+}
+
+impl InputSet {
+    pub(crate) fn new(
+        inputs: HashMap<PortId, InputRaw>,
+        policies: &HashMap<PortId, InputPolicyDescriptor>,
+    ) -> Self {
+        let slots = inputs
+            .keys()
+            .map(|port_id| {
+                let policy = policies.get(port_id).copied().unwrap_or_default();
+                (port_id.clone(), Slot::new(policy))
+            })
+            .collect();
+        let futs = inputs
+            .iter()
+            .map(|(port_id, input)| wait_flow_input(port_id.clone(), input))
+            .collect();
+
+        Self {
+            inputs,
+            rule: default_input_rule,
+            state: Mutex::new(InputSetState {
+                tokens: Tokens { slots },
+                futs,
+            }),
+        }
+    }
+
+    /// Replaces the [default_input_rule] with `rule`.
+    pub fn with_rule(mut self, rule: InputRule) -> Self {
+        self.rule = rule;
+        self
+    }
+
+    /// Returns the identifiers of the inputs of the set.
+    pub fn port_ids(&self) -> impl Iterator<Item = &PortId> {
+        self.inputs.keys()
+    }
+
+    /// Returns, once the [InputRule] of the set triggers the node, the data of its inputs, indexed
+    /// by their identifier.
+    ///
+    /// The inputs that hold no data, or whose data were dropped, are absent.
+    ///
+    /// # Error
+    ///
+    /// An error is returned if all the channels of an input are disconnected or if the rule can no
+    /// longer be satisfied: all the inputs that wait for data reached their end of stream.
+    pub async fn recv(&self) -> Result<HashMap<PortId, DataMessage>> {
+        let mut state = self.state.lock().await;
+        let InputSetState { tokens, futs } = &mut *state;
+
+        loop {
+            tokens.reset_actions();
+            if (self.rule)(tokens) {
+                let (data, fresh) = tokens.trigger();
+                futs.extend(fresh.iter().filter_map(|port_id| {
+                    self.inputs
+                        .get(port_id)
+                        .map(|input| wait_flow_input(port_id.clone(), input))
+                }));
+                return Ok(data);
+            }
+
+            if futs.is_empty() {
+                bail!(
+                    ErrorKind::Disconnected,
+                    "[InputSet] All the inputs reached their end of stream"
+                );
+            }
+
+            // Polling the futures by reference keeps them in the state if `recv` is cancelled.
+            let ((port_id, result), index, _) = select_all(futs.iter_mut()).await;
+            futs.swap_remove(index);
+
+            match result? {
+                LinkMessage::Data(message) => {
+                    tokens.ready(&port_id, message);
+                    continue;
+                }
+                LinkMessage::EndOfStream(_) => {
+                    log::trace!("[InputSet] Input < {port_id} > reached its end of stream");
+                    continue;
+                }
+                LinkMessage::Watermark(_) | LinkMessage::Live(_) | LinkMessage::Control(_) => {}
+            }
+
+            if let Some(input) = self.inputs.get(&port_id) {
+                futs.push(wait_flow_input(port_id, input));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/rule-tests.rs"]
+mod tests;
@@ -0,0 +1,410 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use event_listener::{Event, EventListener};
+use flume::{RecvError, SendError, TryRecvError, TrySendError};
+use std::cell::UnsafeCell;
+use std::fmt;
+use std::future::Future;
+use std::mem::MaybeUninit;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// Creates a bounded single-producer single-consumer channel holding at most `capacity` messages.
+///
+/// The messages are stored in a ring buffer without any lock: a side reserves a slot by moving
+/// its index with a compare-and-swap, and each slot carries a sequence number telling whether it
+/// holds a message to read or is free to write. Neither side ever waits for the other: a slot
+/// still being written is seen as empty, a slot still being read as full.
+///
+/// The [Sender] and the [Receiver] can be cloned, for the runtime to keep handles on the channel
+/// (e.g. to put back the messages of a snapshot or to send the last values again when a node is
+/// restarted), but they are meant to be used by a single task each: the compare-and-swap then
+/// always succeeds at the first attempt. Concurrent uses of the same side remain correct, they
+/// only retry.
+///
+/// # Panics
+///
+/// Panics if `capacity` is lower than 2: with a single slot, the sequence number of a written
+/// message is the one of the slot free for the next lap, the two states cannot be told apart.
+pub(crate) fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(
+        capacity >= 2,
+        "The capacity of a SPSC channel must be at least 2"
+    );
+
+    let ring = Arc::new(Ring {
+        slots: (0..capacity)
+            .map(|index| Slot {
+                sequence: AtomicUsize::new(index),
+                message: UnsafeCell::new(MaybeUninit::uninit()),
+            })
+            .collect(),
+        head: AtomicUsize::new(0),
+        tail: AtomicUsize::new(0),
+        senders: AtomicUsize::new(1),
+        receivers: AtomicUsize::new(1),
+        not_empty: Event::new(),
+        not_full: Event::new(),
+    });
+
+    (Sender { ring: ring.clone() }, Receiver { ring })
+}
+
+struct Slot<T> {
+    // Equal to the position of the next message to write in this slot when it is free, to that
+    // position plus one once the message is written.
+    sequence: AtomicUsize,
+    message: UnsafeCell<MaybeUninit<T>>,
+}
+
+struct Ring<T> {
+    slots: Box<[Slot<T>]>,
+    // The number of messages received so far: the position, modulo the capacity, of the next slot
+    // to read.
+    head: AtomicUsize,
+    // The number of messages sent so far: the position, modulo the capacity, of the next slot to
+    // write.
+    tail: AtomicUsize,
+    senders: AtomicUsize,
+    receivers: AtomicUsize,
+    not_empty: Event,
+    not_full: Event,
+}
+
+// SAFETY: a slot is only accessed by the handle that reserved it by moving the head or the tail,
+// and its sequence number publishes the access to the other side.
+unsafe impl<T: Send> Send for Ring<T> {}
+unsafe impl<T: Send> Sync for Ring<T> {}
+
+impl<T> Ring<T> {
+    fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    fn len(&self) -> usize {
+        // The head is loaded first: the tail, loaded after, is never behind it.
+        let head = self.head.load(Ordering::Acquire);
+        let tail = self.tail.load(Ordering::Acquire);
+        tail.wrapping_sub(head).min(self.capacity())
+    }
+
+    fn slot(&self, position: usize) -> &Slot<T> {
+        &self.slots[position % self.capacity()]
+    }
+
+    /// Writes the `message` in the next free slot, giving it back if the ring is full.
+    fn push(&self, message: T) -> Result<(), T> {
+        let mut tail = self.tail.load(Ordering::Relaxed);
+        loop {
+            let slot = self.slot(tail);
+            let sequence = slot.sequence.load(Ordering::Acquire);
+            if sequence == tail {
+                match self.tail.compare_exchange_weak(
+                    tail,
+                    tail.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        // SAFETY: moving the tail reserved the slot, the consumer does not read it
+                        // before its sequence number is updated.
+                        unsafe { (*slot.message.get()).write(message) };
+                        slot.sequence.store(tail.wrapping_add(1), Ordering::Release);
+                        return Ok(());
+                    }
+                    Err(current) => tail = current,
+                }
+            } else if (sequence.wrapping_sub(tail) as isize) < 0 {
+                // The slot still holds the message written one lap before.
+                return Err(message);
+            } else {
+                tail = self.tail.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Reads the message of the oldest written slot, if any.
+    fn pop(&self) -> Option<T> {
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            let slot = self.slot(head);
+            let sequence = slot.sequence.load(Ordering::Acquire);
+            let written = head.wrapping_add(1);
+            if sequence == written {
+                match self.head.compare_exchange_weak(
+                    head,
+                    written,
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        // SAFETY: the sequence number tells the producer wrote the slot, moving
+                        // the head reserved it.
+                        let message = unsafe { (*slot.message.get()).assume_init_read() };
+                        slot.sequence
+                            .store(head.wrapping_add(self.capacity()), Ordering::Release);
+                        return Some(message);
+                    }
+                    Err(current) => head = current,
+                }
+            } else if (sequence.wrapping_sub(written) as isize) < 0 {
+                // The slot was not written yet.
+                return None;
+            } else {
+                head = self.head.load(Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+impl<T> Drop for Ring<T> {
+    fn drop(&mut self) {
+        let head = *self.head.get_mut();
+        let tail = *self.tail.get_mut();
+        let mut position = head;
+        while position != tail {
+            // SAFETY: the slots between the head and the tail hold messages that were not received.
+            unsafe { (*self.slot(position).message.get()).assume_init_drop() };
+            position = position.wrapping_add(1);
+        }
+    }
+}
+
+/// The sending side of a SPSC [channel].
+pub(crate) struct Sender<T> {
+    ring: Arc<Ring<T>>,
+}
+
+impl<T> Sender<T> {
+    /// Sends the `message` if the channel is not full, without waiting.
+    pub(crate) fn try_send(&self, message: T) -> Result<(), TrySendError<T>> {
+        if self.ring.receivers.load(Ordering::Acquire) == 0 {
+            return Err(TrySendError::Disconnected(message));
+        }
+
+        if let Err(message) = self.ring.push(message) {
+            return Err(TrySendError::Full(message));
+        }
+
+        self.ring.not_empty.notify(usize::MAX);
+        Ok(())
+    }
+
+    /// Sends the `message`, waiting for a slot to be freed if the channel is full.
+    pub(crate) fn send_async(&self, message: T) -> SendFut<'_, T> {
+        SendFut {
+            sender: self,
+            message: Some(message),
+            listener: None,
+        }
+    }
+
+    /// Returns the number of messages waiting in the channel.
+    pub(crate) fn len(&self) -> usize {
+        self.ring.len()
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.ring.capacity()
+    }
+
+    /// Returns `true` if both senders belong to the same channel.
+    pub(crate) fn same_channel(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.ring, &other.ring)
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.ring.senders.fetch_add(1, Ordering::Relaxed);
+        Self {
+            ring: self.ring.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        if self.ring.senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.ring.not_empty.notify(usize::MAX);
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("spsc::Sender")
+            .field("len", &self.len())
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
+/// The receiving side of a SPSC [channel].
+pub(crate) struct Receiver<T> {
+    ring: Arc<Ring<T>>,
+}
+
+impl<T> Receiver<T> {
+    /// Receives the oldest message of the channel, without waiting.
+    pub(crate) fn try_recv(&self) -> Result<T, TryRecvError> {
+        let message = match self.ring.pop() {
+            Some(message) => message,
+            // The messages sent before the last sender was dropped are still received.
+            None if self.ring.senders.load(Ordering::Acquire) == 0 => {
+                self.ring.pop().ok_or(TryRecvError::Disconnected)?
+            }
+            None => return Err(TryRecvError::Empty),
+        };
+
+        self.ring.not_full.notify(usize::MAX);
+        Ok(message)
+    }
+
+    /// Receives the oldest message of the channel, waiting for one if it is empty.
+    pub(crate) fn recv_async(&self) -> RecvFut<'_, T> {
+        RecvFut {
+            receiver: self,
+            listener: None,
+        }
+    }
+
+    /// Receives all the messages waiting in the channel.
+    pub(crate) fn drain(&self) -> Vec<T> {
+        let mut messages = Vec::with_capacity(self.len());
+        while let Ok(message) = self.try_recv() {
+            messages.push(message);
+        }
+        messages
+    }
+
+    /// Returns the number of messages waiting in the channel.
+    pub(crate) fn len(&self) -> usize {
+        self.ring.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.ring.capacity()
+    }
+
+    /// Returns `true` if both receivers belong to the same channel.
+    pub(crate) fn same_channel(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.ring, &other.ring)
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        self.ring.receivers.fetch_add(1, Ordering::Relaxed);
+        Self {
+            ring: self.ring.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        if self.ring.receivers.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.ring.not_full.notify(usize::MAX);
+        }
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("spsc::Receiver")
+            .field("len", &self.len())
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
+/// The future returned by [`Sender::send_async`].
+pub(crate) struct SendFut<'a, T> {
+    sender: &'a Sender<T>,
+    message: Option<T>,
+    listener: Option<EventListener>,
+}
+
+impl<T: Unpin> Future for SendFut<'_, T> {
+    type Output = Result<(), SendError<T>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        loop {
+            let message = match self.message.take() {
+                Some(message) => message,
+                None => panic!("SendFut polled after completion"),
+            };
+            match self.sender.try_send(message) {
+                Ok(()) => return Poll::Ready(Ok(())),
+                Err(TrySendError::Disconnected(message)) => {
+                    return Poll::Ready(Err(SendError(message)))
+                }
+                Err(TrySendError::Full(message)) => self.message = Some(message),
+            }
+
+            // The listener is registered before trying again: a slot freed in between wakes it up.
+            match self.listener.as_mut() {
+                None => self.listener = Some(self.sender.ring.not_full.listen()),
+                Some(listener) => match Pin::new(listener).poll(cx) {
+                    Poll::Ready(()) => self.listener = None,
+                    Poll::Pending => return Poll::Pending,
+                },
+            }
+        }
+    }
+}
+
+/// The future returned by [`Receiver::recv_async`].
+pub(crate) struct RecvFut<'a, T> {
+    receiver: &'a Receiver<T>,
+    listener: Option<EventListener>,
+}
+
+impl<T> Future for RecvFut<'_, T> {
+    type Output = Result<T, RecvError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(message) => return Poll::Ready(Ok(message)),
+                Err(TryRecvError::Disconnected) => {
+                    return Poll::Ready(Err(RecvError::Disconnected))
+                }
+                Err(TryRecvError::Empty) => {}
+            }
+
+            // The listener is registered before trying again: a message sent in between wakes it
+            // up.
+            match self.listener.as_mut() {
+                None => self.listener = Some(self.receiver.ring.not_empty.listen()),
+                Some(listener) => match Pin::new(listener).poll(cx) {
+                    Poll::Ready(()) => self.listener = None,
+                    Poll::Pending => return Poll::Pending,
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/spsc-tests.rs"]
+mod tests;
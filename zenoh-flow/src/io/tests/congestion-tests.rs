@@ -0,0 +1,69 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::Congestion;
+use crate::io::output::LinkQueue;
+use crate::io::Outputs;
+use crate::model::descriptor::{InputDescriptor, OverflowPolicy};
+use crate::types::LinkMessage;
+use std::sync::Arc;
+
+#[test]
+fn test_congestion_monitor() {
+    let hlc = Arc::new(uhlc::HLC::default());
+    let (tx_queued, rx_queued) = flume::bounded::<LinkMessage>(4);
+    let (tx_unbounded, _rx_unbounded) = flume::unbounded::<LinkMessage>();
+    let queue = Arc::new(LinkQueue::new(
+        InputDescriptor {
+            node: "sink".into(),
+            input: "in".into(),
+        },
+        OverflowPolicy::DropOldest,
+        rx_queued.clone().into(),
+    ));
+
+    let mut outputs = Outputs::new(hlc);
+    outputs.insert("out".into(), tx_queued.into(), Some(queue));
+    outputs.insert("out".into(), tx_unbounded.into(), None);
+    let output = outputs.take("out").expect("Wrong key provided").raw();
+    let mut monitor = output.congestion_monitor();
+
+    assert_eq!(
+        monitor.observe(),
+        Congestion {
+            queued: 0,
+            level: Some(0.0),
+            dropped: 0,
+            drop_rate: 0.0,
+        }
+    );
+
+    for byte in 0..6u8 {
+        output.try_send(vec![byte], None).expect("Failed to send");
+    }
+
+    // The queue holds 4 messages out of 6, the unbounded channel all of them.
+    let congestion = monitor.observe();
+    assert_eq!(congestion.queued, 6);
+    assert_eq!(congestion.level, Some(1.0));
+    assert_eq!(congestion.dropped, 2);
+    assert!(congestion.is_congested(0.8));
+
+    // The drops are counted since the previous observation.
+    while rx_queued.try_recv().is_ok() {}
+    let congestion = monitor.observe();
+    assert_eq!(congestion.level, Some(0.0));
+    assert_eq!(congestion.dropped, 0);
+    assert!(!congestion.is_congested(0.8));
+}
@@ -14,13 +14,16 @@
 
 use prost::Message as pMessage;
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
+use std::time::Duration;
 use types::Message;
 
 use super::{Input, InputRaw};
 use crate::{
+    runtime::dataflow::instance::EndOfStreamTracker,
     traits::SendSyncAny,
-    types::{self, LinkMessage, Payload},
+    types::{self, InlineBytes, LinkMessage, Payload, PayloadReference},
 };
 
 /// Test that the Input behaves as expected for the provided data and deserializer:
@@ -47,14 +50,16 @@ fn test_typed_input<T: Send + Sync + Clone + std::fmt::Debug + PartialEq + 'stat
     let hlc = uhlc::HLC::default();
     let (tx, rx) = flume::unbounded::<LinkMessage>();
 
-    let input_raw = InputRaw {
-        port_id: "test-id".into(),
-        receivers: vec![rx],
-    };
+    let input_raw = InputRaw::new("test-id".into(), vec![rx.into()], None);
 
     let input = Input {
         input_raw,
         deserializer: Arc::new(deserializer),
+        union: None,
+        session: None,
+        hlc: None,
+        default: None,
+        received: AtomicBool::new(false),
     };
 
     let message = LinkMessage::from_payload(
@@ -144,3 +149,286 @@ fn test_protobuf_prost() {
         <TestProto>::decode(bytes).map_err(|e| anyhow::anyhow!(e))
     })
 }
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// END OF STREAM
+
+#[async_std::test]
+async fn test_end_of_stream() {
+    let hlc = uhlc::HLC::default();
+    let (tx1, rx1) = flume::unbounded::<LinkMessage>();
+    let (tx2, rx2) = flume::unbounded::<LinkMessage>();
+
+    let tracker = Arc::new(EndOfStreamTracker::default());
+    tracker.expect(1);
+
+    let input_raw = InputRaw::new(
+        "test-id".into(),
+        vec![rx1.into(), rx2.into()],
+        Some(tracker.clone()),
+    );
+
+    // The first upstream node ends its stream: it should not be exposed as the second one did not.
+    tx1.send(LinkMessage::EndOfStream(hlc.new_timestamp()))
+        .expect("Failed to send message");
+    tx2.send(LinkMessage::from_payload(
+        vec![1u8].into(),
+        hlc.new_timestamp(),
+    ))
+    .expect("Failed to send message");
+
+    assert!(matches!(input_raw.recv().await, Ok(LinkMessage::Data(_))));
+
+    tx2.send(LinkMessage::EndOfStream(hlc.new_timestamp()))
+        .expect("Failed to send message");
+    assert!(matches!(
+        input_raw.recv().await,
+        Ok(LinkMessage::EndOfStream(_))
+    ));
+
+    async_std::future::timeout(std::time::Duration::from_secs(1), tracker.wait())
+        .await
+        .expect("The tracker should be complete");
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// BATCHES
+
+#[async_std::test]
+async fn test_recv_batch() {
+    let hlc = uhlc::HLC::default();
+    let (tx, rx) = flume::unbounded::<LinkMessage>();
+    let input_raw = InputRaw::new("test-id".into(), vec![rx.into()], None);
+    let data = |value: u8| LinkMessage::from_payload(vec![value].into(), hlc.new_timestamp());
+
+    // The pending messages are batched, up to the maximum size.
+    for value in 0..5 {
+        tx.send(data(value)).expect("Failed to send message");
+    }
+    let batch = input_raw
+        .recv_batch(3, Duration::from_secs(5))
+        .await
+        .expect("Failed to receive batch");
+    assert_eq!(batch.len(), 3);
+
+    // The batch is returned once the maximum wait elapsed, even if it is not full.
+    let batch = input_raw
+        .recv_batch(10, Duration::from_millis(10))
+        .await
+        .expect("Failed to receive batch");
+    assert_eq!(batch.len(), 2);
+
+    // The batch ends with the end of stream.
+    tx.send(data(5)).expect("Failed to send message");
+    tx.send(LinkMessage::EndOfStream(hlc.new_timestamp()))
+        .expect("Failed to send message");
+    tx.send(data(6)).expect("Failed to send message");
+    let batch = input_raw
+        .recv_batch(10, Duration::from_secs(5))
+        .await
+        .expect("Failed to receive batch");
+    assert_eq!(batch.len(), 2);
+    assert!(matches!(batch[1], LinkMessage::EndOfStream(_)));
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// INLINE BYTES
+
+// The payloads stored inline by the links are delivered as `Payload::Bytes` to the nodes.
+#[test]
+fn test_inline_delivered_as_bytes() {
+    let hlc = uhlc::HLC::default();
+    let (tx, rx) = flume::unbounded::<LinkMessage>();
+    let inline = |value: u8| {
+        LinkMessage::from_payload(
+            Payload::Inline(InlineBytes::new(&[value]).unwrap()),
+            hlc.new_timestamp(),
+        )
+    };
+
+    let input_raw = InputRaw::new("test-id".into(), vec![rx.into()], None);
+    tx.send(inline(1)).expect("Failed to send message");
+    match input_raw.try_recv().expect("Failed to receive message") {
+        LinkMessage::Data(data_message) => {
+            assert!(matches!(&*data_message, Payload::Bytes(bytes) if **bytes == vec![1u8]))
+        }
+        _ => panic!("Unexpected message"),
+    }
+
+    let input = Input {
+        input_raw,
+        deserializer: Arc::new(|bytes: &[u8]| Ok(bytes[0])),
+        union: None,
+        session: None,
+        hlc: None,
+        default: None,
+        received: AtomicBool::new(false),
+    };
+    tx.send(inline(2)).expect("Failed to send message");
+    match input.try_recv().expect("Failed to receive message") {
+        (Message::Data(data), _) => assert_eq!(*data, 2u8),
+        _ => panic!("Unexpected message"),
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// REFERENCES
+
+#[async_std::test]
+async fn test_reference() {
+    let hlc = uhlc::HLC::default();
+    let (tx, rx) = flume::unbounded::<LinkMessage>();
+
+    let input_raw = InputRaw::new("test-id".into(), vec![rx.into()], None);
+    let reference = PayloadReference::new("test/blob");
+
+    // An InputRaw exposes the reference without retrieving the data.
+    tx.send(LinkMessage::from_payload(
+        reference.clone().into(),
+        hlc.new_timestamp(),
+    ))
+    .expect("Failed to send message");
+    match input_raw.recv().await {
+        Ok(LinkMessage::Data(data_message)) => match &*data_message {
+            Payload::Reference(received) => {
+                assert_eq!(&reference, received);
+                assert!(data_message.try_as_bytes().is_err());
+            }
+            _ => panic!("Unexpected payload"),
+        },
+        _ => panic!("Unexpected message"),
+    }
+
+    // Without a Zenoh session, an Input<T> cannot resolve it.
+    let input = Input::<u64> {
+        input_raw,
+        deserializer: Arc::new(|bytes| {
+            serde_json::from_slice(bytes).map_err(|e| anyhow::anyhow!(e))
+        }),
+        union: None,
+        session: None,
+        hlc: None,
+        default: None,
+        received: AtomicBool::new(false),
+    };
+    tx.send(LinkMessage::from_payload(
+        reference.into(),
+        hlc.new_timestamp(),
+    ))
+    .expect("Failed to send message");
+    assert!(input.recv().await.is_err());
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// DEFAULT VALUE
+
+#[test]
+fn test_default_value() {
+    let hlc = Arc::new(uhlc::HLC::default());
+    let (tx, rx) = flume::unbounded::<LinkMessage>();
+
+    let input = Input::<u64> {
+        input_raw: InputRaw::new("test-id".into(), vec![rx.into()], None),
+        deserializer: Arc::new(|bytes| {
+            serde_json::from_slice(bytes).map_err(|e| anyhow::anyhow!(e))
+        }),
+        union: None,
+        session: None,
+        hlc: Some(hlc.clone()),
+        default: None,
+        received: AtomicBool::new(false),
+    }
+    .with_default(42);
+
+    // No message was received: the default value is used.
+    match input.try_recv() {
+        Ok((Message::Data(data), _)) => assert_eq!(*data, 42),
+        _ => panic!("Expected the default value"),
+    }
+
+    tx.send(LinkMessage::from_payload(
+        serde_json::to_vec(&1u64).unwrap().into(),
+        hlc.new_timestamp(),
+    ))
+    .expect("Failed to send message");
+    match input.try_recv() {
+        Ok((Message::Data(data), _)) => assert_eq!(*data, 1),
+        _ => panic!("Expected the received value"),
+    }
+
+    // Once a message was received, the default value is no longer used.
+    assert!(input.try_recv().is_err());
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// UNION
+
+#[derive(Debug, PartialEq)]
+enum Reading {
+    Temperature(f64),
+    Humidity(u8),
+}
+
+impl types::UnionData for Reading {
+    fn variant(&self) -> u32 {
+        match self {
+            Reading::Temperature(_) => 0,
+            Reading::Humidity(_) => 1,
+        }
+    }
+
+    fn serialize_variant(&self, buffer: &mut Vec<u8>) -> anyhow::Result<()> {
+        match self {
+            Reading::Temperature(value) => bincode::serialize_into(buffer, value)?,
+            Reading::Humidity(value) => bincode::serialize_into(buffer, value)?,
+        }
+        Ok(())
+    }
+
+    fn deserialize_variant(variant: u32, bytes: &[u8]) -> anyhow::Result<Self> {
+        match variant {
+            0 => Ok(Reading::Temperature(bincode::deserialize(bytes)?)),
+            1 => Ok(Reading::Humidity(bincode::deserialize(bytes)?)),
+            _ => Err(anyhow::anyhow!("Unknown variant {variant}")),
+        }
+    }
+}
+
+#[test]
+fn test_union_input() {
+    let hlc = uhlc::HLC::default();
+    let (tx, rx) = flume::unbounded::<LinkMessage>();
+
+    let input = Input::<Reading> {
+        input_raw: InputRaw::new("test-id".into(), vec![rx.into()], None),
+        deserializer: Arc::new(|_| Err(anyhow::anyhow!("No variant"))),
+        union: Some(<Reading as types::UnionData>::deserialize_variant),
+        session: None,
+        hlc: None,
+        default: None,
+        received: AtomicBool::new(false),
+    };
+
+    // The variant carried in the header of the message tells how to deserialize the data.
+    for (reading, variant) in [(Reading::Humidity(42), 1), (Reading::Temperature(21.5), 0)] {
+        let mut bytes = Vec::new();
+        types::UnionData::serialize_variant(&reading, &mut bytes).unwrap();
+        tx.send(
+            LinkMessage::from_payload(bytes.into(), hlc.new_timestamp())
+                .with_variant(Some(variant)),
+        )
+        .expect("Failed to send message");
+        match input.try_recv() {
+            Ok((Message::Data(data), _)) => assert_eq!(*data, reading),
+            _ => panic!("Expected {reading:?}"),
+        }
+    }
+
+    // Data sent without variant cannot be deserialized.
+    tx.send(LinkMessage::from_payload(
+        bincode::serialize(&42u8).unwrap().into(),
+        hlc.new_timestamp(),
+    ))
+    .expect("Failed to send message");
+    assert!(input.try_recv().is_err());
+}
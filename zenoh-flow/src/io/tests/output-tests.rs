@@ -16,8 +16,9 @@ use prost::Message;
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, sync::Arc};
 
-use super::Outputs;
-use crate::types::{LinkMessage, Payload};
+use super::{LinkQueue, OutputRaw, Outputs, WarmUp};
+use crate::model::descriptor::{InputDescriptor, OverflowPolicy, WarmupDescriptor};
+use crate::types::{ControlMarker, LinkMessage, Payload};
 
 /// Test that the Output behaves as expected for the provided data and serializer:
 /// 1. the `serializer` is correctly type-erased yet still produces the correct output,
@@ -54,6 +55,11 @@ fn test_typed_output<T: Send + Sync + Clone + std::fmt::Debug + PartialEq + 'sta
 
     let mut outputs = Outputs {
         hmap: HashMap::from([(key.clone(), vec![tx])]),
+        caches: HashMap::default(),
+        taps: HashMap::default(),
+        queues: HashMap::default(),
+        warmup: Arc::default(),
+        activity: Arc::default(),
         hlc: Arc::new(hlc),
     };
 
@@ -69,7 +75,8 @@ fn test_typed_output<T: Send + Sync + Clone + std::fmt::Debug + PartialEq + 'sta
     let message = rx.recv().expect("Received no message");
     match message {
         LinkMessage::Data(data) => match &*data {
-            Payload::Bytes(_) => panic!("Unexpected bytes payload"),
+            Payload::Bytes(_) | Payload::Inline(_) => panic!("Unexpected bytes payload"),
+            Payload::Reference(_) => panic!("Unexpected reference payload"),
             Payload::Typed((dyn_data, serializer)) => {
                 let mut dyn_serialized = Vec::new();
                 (serializer)(&mut dyn_serialized, dyn_data.clone()).expect("Failed to serialize");
@@ -83,6 +90,9 @@ fn test_typed_output<T: Send + Sync + Clone + std::fmt::Debug + PartialEq + 'sta
             }
         },
         LinkMessage::Watermark(_) => panic!("Unexpected watermark message"),
+        LinkMessage::EndOfStream(_) => panic!("Unexpected end of stream message"),
+        LinkMessage::Live(_) => panic!("Unexpected live message"),
+        LinkMessage::Control(_) => panic!("Unexpected control message"),
     }
 }
 
@@ -147,3 +157,269 @@ fn test_protobuf_prost() {
 
     test_typed_output(expected_data, expected_serialized, serializer)
 }
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// LAST VALUE CACHING
+
+#[test]
+fn test_last_value_cache() {
+    let hlc = Arc::new(uhlc::HLC::default());
+    let (tx, _rx) = flume::unbounded::<LinkMessage>();
+
+    let mut outputs = Outputs::new(hlc);
+    let cache = outputs.insert("test".into(), tx.into(), None);
+
+    let output = outputs.take("test").expect("Wrong key provided").raw();
+    output
+        .try_send(vec![1u8], None)
+        .expect("Failed to send the message");
+    // The output did not opt into last value caching.
+    assert!(cache.get().is_none());
+
+    let (tx, _rx) = flume::unbounded::<LinkMessage>();
+    let cache = outputs.insert("test".into(), tx.into(), None);
+    let output = outputs
+        .take("test")
+        .expect("Wrong key provided")
+        .cache_last_value()
+        .raw();
+    output
+        .try_send(vec![1u8], None)
+        .expect("Failed to send the message");
+    output
+        .try_send_watermark(None)
+        .expect("Failed to send the watermark");
+    // Only data messages are cached.
+    assert!(matches!(cache.get(), Some(LinkMessage::Data(_))));
+}
+
+#[test]
+fn test_sent_messages() {
+    let hlc = Arc::new(uhlc::HLC::default());
+    let (tx, _rx) = flume::unbounded::<LinkMessage>();
+
+    let mut outputs = Outputs::new(hlc);
+    outputs.insert("test".into(), tx.into(), None);
+    let tap = outputs.taps["test"].clone();
+    let output = outputs.take("test").expect("Wrong key provided").raw();
+    output
+        .try_send(vec![1u8], None)
+        .expect("Failed to send the message");
+    output
+        .try_send_watermark(None)
+        .expect("Failed to send the watermark");
+    assert_eq!(tap.sent(), 2);
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// EVENT TIME
+
+#[test]
+fn test_event_time() {
+    let hlc = Arc::new(uhlc::HLC::default());
+    let (tx, rx) = flume::unbounded::<LinkMessage>();
+
+    let mut outputs = Outputs::new(hlc.clone());
+    outputs.insert("test".into(), tx.into(), None);
+    let output = outputs.take("test").expect("Wrong key provided").raw();
+
+    let event_time = hlc.new_timestamp().get_time().0;
+    output
+        .try_send_with_event_time(vec![1u8], event_time)
+        .expect("Failed to send the message");
+    let message = match rx.try_recv() {
+        Ok(LinkMessage::Data(message)) => message,
+        _ => panic!("Expected a data message"),
+    };
+    // The processing time is assigned by the runtime, after the event time.
+    assert_eq!(
+        message.get_event_time().map(|ts| ts.get_time().0),
+        Some(event_time)
+    );
+    assert!(message.get_processing_time().get_time().0 > event_time);
+    assert!(message.get_event_latency().is_some());
+
+    output
+        .try_send(vec![2u8], None)
+        .expect("Failed to send the message");
+    let message = match rx.try_recv() {
+        Ok(LinkMessage::Data(message)) => message,
+        _ => panic!("Expected a data message"),
+    };
+    assert!(message.get_event_time().is_none());
+    assert!(message.get_event_latency().is_none());
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// SIDE OUTPUTS
+
+#[test]
+fn test_unconnected_side_output() {
+    let hlc = Arc::new(uhlc::HLC::default());
+    let (tx, rx) = flume::unbounded::<LinkMessage>();
+
+    let mut outputs = Outputs::new(hlc);
+    outputs.insert("connected".into(), tx.into(), None);
+
+    let connected = outputs.take_side("connected").raw();
+    assert_eq!(connected.channels_count(), 1);
+    connected
+        .try_send(vec![1u8], None)
+        .expect("Failed to send the message");
+    assert!(rx.try_recv().is_ok());
+
+    let unconnected = outputs.take_side("unconnected").raw();
+    assert_eq!(unconnected.channels_count(), 0);
+    unconnected
+        .try_send(vec![1u8], None)
+        .expect("Sending on an unconnected side output should be a no-op");
+    async_std::task::block_on(unconnected.send(vec![1u8], None))
+        .expect("Sending on an unconnected side output should be a no-op");
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// LINK QUEUES
+
+/// Returns an output whose only link has a queue of `capacity` messages, along with the sender of
+/// the link (the markers are sent straight on it, see `control`) and its receiver.
+fn queued_output(
+    overflow: OverflowPolicy,
+    capacity: usize,
+) -> (
+    OutputRaw,
+    Arc<LinkQueue>,
+    flume::Sender<LinkMessage>,
+    flume::Receiver<LinkMessage>,
+) {
+    let hlc = Arc::new(uhlc::HLC::default());
+    let (tx, rx) = flume::bounded::<LinkMessage>(capacity);
+    let queue = Arc::new(LinkQueue::new(
+        InputDescriptor {
+            node: "sink".into(),
+            input: "in".into(),
+        },
+        overflow,
+        rx.clone().into(),
+    ));
+
+    let mut outputs = Outputs::new(hlc);
+    outputs.insert("out".into(), tx.clone().into(), Some(queue.clone()));
+    assert_eq!(outputs.link_queues().count(), 1);
+
+    let output = outputs.take("out").expect("Wrong key provided").raw();
+    (output, queue, tx, rx)
+}
+
+fn data(message: LinkMessage) -> Vec<u8> {
+    match message {
+        LinkMessage::Data(data) => data
+            .try_as_bytes()
+            .expect("Failed to get the bytes")
+            .to_vec(),
+        _ => panic!("Expected a data message"),
+    }
+}
+
+#[test]
+fn test_link_queue_drop_newest() {
+    let (output, queue, _tx, rx) = queued_output(OverflowPolicy::DropNewest, 1);
+
+    output.try_send(vec![1u8], None).expect("Failed to send");
+    // The queue is full: the messages are dropped, sending never fails nor blocks.
+    output.try_send(vec![2u8], None).expect("Failed to send");
+    async_std::task::block_on(output.send(vec![3u8], None)).expect("Failed to send");
+    assert_eq!(queue.dropped(), 2);
+
+    assert_eq!(data(rx.try_recv().expect("Queue is empty")), vec![1u8]);
+    assert!(rx.try_recv().is_err());
+}
+
+#[test]
+fn test_link_queue_drop_oldest() {
+    let (output, queue, _tx, rx) = queued_output(OverflowPolicy::DropOldest, 1);
+
+    output.try_send(vec![1u8], None).expect("Failed to send");
+    output.try_send(vec![2u8], None).expect("Failed to send");
+    async_std::task::block_on(output.send(vec![3u8], None)).expect("Failed to send");
+    assert_eq!(queue.dropped(), 2);
+
+    assert_eq!(data(rx.try_recv().expect("Queue is empty")), vec![3u8]);
+    assert!(rx.try_recv().is_err());
+}
+
+#[test]
+fn test_link_queue_drop_oldest_behind_marker() {
+    let hlc = uhlc::HLC::default();
+    let (output, queue, tx, rx) = queued_output(OverflowPolicy::DropOldest, 2);
+
+    tx.send(LinkMessage::Control(ControlMarker::new(
+        hlc.new_timestamp(),
+        Arc::new(vec![0u8]),
+    )))
+    .expect("Failed to send the marker");
+    output.try_send(vec![1u8], None).expect("Failed to send");
+    // The queue is full: the oldest data message is dropped, not the marker ahead of it.
+    output.try_send(vec![2u8], None).expect("Failed to send");
+    async_std::task::block_on(output.send(vec![3u8], None)).expect("Failed to send");
+    assert_eq!(queue.dropped(), 2);
+
+    // A watermark takes the place of the last data message.
+    output.try_send_watermark(None).expect("Failed to send");
+    assert_eq!(queue.dropped(), 3);
+
+    // Only messages that cannot be dropped are waiting: the newest data message is dropped.
+    output.try_send(vec![4u8], None).expect("Failed to send");
+    assert_eq!(queue.dropped(), 4);
+
+    assert!(matches!(rx.try_recv(), Ok(LinkMessage::Control(_))));
+    assert!(matches!(rx.try_recv(), Ok(LinkMessage::Watermark(_))));
+    assert!(rx.try_recv().is_err());
+}
+
+#[test]
+fn test_link_queue_keeps_end_of_stream() {
+    for overflow in [OverflowPolicy::DropNewest, OverflowPolicy::DropOldest] {
+        let (output, queue, _tx, rx) = queued_output(overflow, 1);
+
+        async_std::task::block_on(output.send_end_of_stream()).expect("Failed to send");
+        output.try_send(vec![1u8], None).expect("Failed to send");
+        async_std::task::block_on(output.send(vec![2u8], None)).expect("Failed to send");
+        assert_eq!(queue.dropped(), 2);
+
+        assert!(matches!(rx.try_recv(), Ok(LinkMessage::EndOfStream(_))));
+        assert!(rx.try_recv().is_err());
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// WARM-UP
+
+#[test]
+fn test_warmup() {
+    let hlc = Arc::new(uhlc::HLC::default());
+    let (tx, rx) = flume::unbounded::<LinkMessage>();
+
+    let mut outputs = Outputs::new(hlc);
+    outputs.warmup = Arc::new(WarmUp::new(WarmupDescriptor {
+        duration: None,
+        activations: Some(2),
+    }));
+    outputs.insert("out".into(), tx.into(), None);
+    let output = outputs.take("out").expect("Wrong key provided").raw();
+
+    // A warm-up is only effective once started.
+    output.try_send(vec![0u8], None).expect("Failed to send");
+    assert!(rx.try_recv().is_ok());
+
+    outputs.warmup.start();
+    output.try_send(vec![1u8], None).expect("Failed to send");
+    outputs.warmup.activated();
+    // Watermarks are not discarded.
+    output.try_send_watermark(None).expect("Failed to send");
+    assert!(matches!(rx.try_recv(), Ok(LinkMessage::Watermark(_))));
+    assert!(rx.try_recv().is_err());
+
+    outputs.warmup.activated();
+    output.try_send(vec![2u8], None).expect("Failed to send");
+    assert_eq!(data(rx.try_recv().expect("Channel is empty")), vec![2u8]);
+}
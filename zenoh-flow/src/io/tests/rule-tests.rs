@@ -0,0 +1,252 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::{default_input_rule, InputSet, Slot, Token, TokenAction, Tokens};
+use crate::io::InputRaw;
+use crate::model::descriptor::InputPolicyDescriptor;
+use crate::prelude::PortId;
+use crate::types::{DataMessage, LinkMessage};
+use std::collections::HashMap;
+use std::time::Duration;
+
+const FRAME: &str = "Frame";
+const CALIBRATION: &str = "Calibration";
+
+/// Returns the tokens of the inputs `(port_id, required, ready)`: the ready tokens are fresh.
+fn tokens(slots: &[(&str, bool, bool)]) -> Tokens {
+    let hlc = uhlc::HLC::default();
+    Tokens {
+        slots: slots
+            .iter()
+            .map(|(port_id, required, ready)| {
+                let mut slot = Slot::new(InputPolicyDescriptor {
+                    required: *required,
+                    token: TokenAction::Consume,
+                });
+                if *ready {
+                    slot.token =
+                        Token::Ready(DataMessage::new_serialized(vec![], hlc.new_timestamp()));
+                    slot.fresh = true;
+                }
+                ((*port_id).into(), slot)
+            })
+            .collect(),
+    }
+}
+
+fn send(tx: &flume::Sender<LinkMessage>, hlc: &uhlc::HLC, byte: u8) {
+    tx.send(LinkMessage::from_payload(
+        vec![byte].into(),
+        hlc.new_timestamp(),
+    ))
+    .unwrap();
+}
+
+fn bytes(data: &HashMap<PortId, DataMessage>, port_id: &str) -> Option<Vec<u8>> {
+    data.get(port_id).map(|message| {
+        let mut bytes = Vec::new();
+        message.try_as_bytes_into(&mut bytes).unwrap();
+        bytes
+    })
+}
+
+#[test]
+fn test_default_input_rule() {
+    assert!(!default_input_rule(&mut tokens(&[
+        (FRAME, true, false),
+        (CALIBRATION, true, true)
+    ])));
+    assert!(default_input_rule(&mut tokens(&[
+        (FRAME, true, true),
+        (CALIBRATION, true, true)
+    ])));
+
+    // An optional input does not block the node...
+    assert!(default_input_rule(&mut tokens(&[
+        (FRAME, true, true),
+        (CALIBRATION, false, false)
+    ])));
+    // ... but is not enough to trigger it.
+    assert!(!default_input_rule(&mut tokens(&[
+        (FRAME, true, false),
+        (CALIBRATION, false, true)
+    ])));
+    // Without required inputs, any data triggers the node.
+    assert!(default_input_rule(&mut tokens(&[
+        (FRAME, false, false),
+        (CALIBRATION, false, true)
+    ])));
+    assert!(!default_input_rule(&mut tokens(&[(FRAME, false, false)])));
+}
+
+#[async_std::test]
+async fn test_optional_input() {
+    let hlc = uhlc::HLC::default();
+    let (tx_frame, rx_frame) = flume::unbounded::<LinkMessage>();
+    let (tx_calibration, rx_calibration) = flume::unbounded::<LinkMessage>();
+
+    let inputs = HashMap::from([
+        (
+            FRAME.into(),
+            InputRaw::new(FRAME.into(), vec![rx_frame.into()], None),
+        ),
+        (
+            CALIBRATION.into(),
+            InputRaw::new(CALIBRATION.into(), vec![rx_calibration.into()], None),
+        ),
+    ]);
+    let policies = HashMap::from([(
+        CALIBRATION.into(),
+        InputPolicyDescriptor {
+            required: false,
+            token: TokenAction::Consume,
+        },
+    )]);
+    let input_set = InputSet::new(inputs, &policies);
+
+    // The node runs on its main input alone.
+    send(&tx_frame, &hlc, 1);
+    let data = input_set.recv().await.unwrap();
+    assert_eq!(data.len(), 1);
+    assert_eq!(bytes(&data, FRAME), Some(vec![1]));
+
+    // An update of the calibration alone does not trigger it: it is given with the next frame.
+    send(&tx_calibration, &hlc, 2);
+    assert!(
+        async_std::future::timeout(Duration::from_millis(100), input_set.recv())
+            .await
+            .is_err()
+    );
+
+    send(&tx_frame, &hlc, 3);
+    let data = input_set.recv().await.unwrap();
+    assert_eq!(data.len(), 2);
+    assert_eq!(bytes(&data, FRAME), Some(vec![3]));
+    assert_eq!(bytes(&data, CALIBRATION), Some(vec![2]));
+}
+
+#[async_std::test]
+async fn test_token_actions() {
+    const REFERENCE: &str = "Reference";
+    const TRIGGER: &str = "Trigger";
+
+    let hlc = uhlc::HLC::default();
+    let (tx_frame, rx_frame) = flume::unbounded::<LinkMessage>();
+    let (tx_reference, rx_reference) = flume::unbounded::<LinkMessage>();
+    let (tx_trigger, rx_trigger) = flume::unbounded::<LinkMessage>();
+
+    let inputs = HashMap::from([
+        (
+            FRAME.into(),
+            InputRaw::new(FRAME.into(), vec![rx_frame.into()], None),
+        ),
+        (
+            REFERENCE.into(),
+            InputRaw::new(REFERENCE.into(), vec![rx_reference.into()], None),
+        ),
+        (
+            TRIGGER.into(),
+            InputRaw::new(TRIGGER.into(), vec![rx_trigger.into()], None),
+        ),
+    ]);
+    let policies = HashMap::from([
+        (
+            REFERENCE.into(),
+            InputPolicyDescriptor {
+                required: true,
+                token: TokenAction::Keep,
+            },
+        ),
+        (
+            TRIGGER.into(),
+            InputPolicyDescriptor {
+                required: false,
+                token: TokenAction::Drop,
+            },
+        ),
+    ]);
+    let input_set = InputSet::new(inputs, &policies);
+
+    send(&tx_reference, &hlc, 1);
+    send(&tx_frame, &hlc, 2);
+    let data = input_set.recv().await.unwrap();
+    assert_eq!(bytes(&data, REFERENCE), Some(vec![1]));
+    assert_eq!(bytes(&data, FRAME), Some(vec![2]));
+
+    // The reference is kept: a new frame is enough to trigger the node.
+    send(&tx_frame, &hlc, 3);
+    let data = input_set.recv().await.unwrap();
+    assert_eq!(bytes(&data, REFERENCE), Some(vec![1]));
+    assert_eq!(bytes(&data, FRAME), Some(vec![3]));
+
+    // The kept reference is replaced by the new one, the frame was consumed.
+    send(&tx_reference, &hlc, 4);
+    assert!(
+        async_std::future::timeout(Duration::from_millis(100), input_set.recv())
+            .await
+            .is_err()
+    );
+    send(&tx_frame, &hlc, 5);
+    let data = input_set.recv().await.unwrap();
+    assert_eq!(bytes(&data, REFERENCE), Some(vec![4]));
+    assert_eq!(bytes(&data, FRAME), Some(vec![5]));
+
+    // The data of the trigger are dropped.
+    send(&tx_trigger, &hlc, 6);
+    send(&tx_frame, &hlc, 7);
+    let data = input_set.recv().await.unwrap();
+    assert_eq!(data.len(), 2);
+    assert_eq!(bytes(&data, TRIGGER), None);
+}
+
+#[async_std::test]
+async fn test_rule_set_action() {
+    let hlc = uhlc::HLC::default();
+    let (tx_frame, rx_frame) = flume::unbounded::<LinkMessage>();
+
+    let inputs = HashMap::from([(
+        FRAME.into(),
+        InputRaw::new(FRAME.into(), vec![rx_frame.into()], None),
+    )]);
+    let input_set = InputSet::new(inputs, &HashMap::default()).with_rule(|tokens| {
+        tokens.set_action(FRAME, TokenAction::Keep);
+        tokens.is_fresh(FRAME)
+    });
+
+    send(&tx_frame, &hlc, 1);
+    let data = input_set.recv().await.unwrap();
+    assert_eq!(bytes(&data, FRAME), Some(vec![1]));
+
+    let mut state = input_set.state.lock().await;
+    assert!(state.tokens.get(FRAME).unwrap().is_ready());
+    assert!(!state.tokens.is_fresh(FRAME));
+    assert!(!(input_set.rule)(&mut state.tokens));
+}
+
+#[async_std::test]
+async fn test_end_of_stream() {
+    let hlc = uhlc::HLC::default();
+    let (tx_frame, rx_frame) = flume::unbounded::<LinkMessage>();
+
+    let inputs = HashMap::from([(
+        FRAME.into(),
+        InputRaw::new(FRAME.into(), vec![rx_frame.into()], None),
+    )]);
+    let input_set = InputSet::new(inputs, &HashMap::default());
+
+    tx_frame
+        .send(LinkMessage::EndOfStream(hlc.new_timestamp()))
+        .unwrap();
+    assert!(input_set.recv().await.is_err());
+}
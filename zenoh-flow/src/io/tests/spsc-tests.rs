@@ -0,0 +1,109 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::channel;
+use flume::{TryRecvError, TrySendError};
+use std::sync::Arc;
+
+#[test]
+fn test_spsc_bounded_fifo() {
+    let (tx, rx) = channel::<u64>(4);
+
+    for value in 0..4 {
+        tx.try_send(value).unwrap();
+    }
+    assert_eq!(tx.len(), 4);
+    assert!(matches!(tx.try_send(4), Err(TrySendError::Full(4))));
+
+    assert_eq!(rx.try_recv().unwrap(), 0);
+    tx.try_send(4).unwrap();
+    assert_eq!(rx.drain(), vec![1, 2, 3, 4]);
+    assert!(rx.is_empty());
+    assert!(matches!(rx.try_recv(), Err(TryRecvError::Empty)));
+}
+
+// A single slot cannot tell a written message from a free slot of the next lap.
+#[test]
+#[should_panic(expected = "at least 2")]
+fn test_spsc_capacity_one() {
+    let _ = channel::<u64>(1);
+}
+
+#[test]
+fn test_spsc_capacity_two() {
+    let (tx, rx) = channel::<u64>(2);
+    for value in 0..10 {
+        tx.try_send(value).unwrap();
+        tx.try_send(value + 100).unwrap();
+        assert!(matches!(tx.try_send(0), Err(TrySendError::Full(0))));
+        assert_eq!(rx.try_recv().unwrap(), value);
+        assert_eq!(rx.try_recv().unwrap(), value + 100);
+        assert!(matches!(rx.try_recv(), Err(TryRecvError::Empty)));
+    }
+}
+
+#[test]
+fn test_spsc_disconnected() {
+    let (tx, rx) = channel::<u64>(2);
+    let rx_clone = rx.clone();
+
+    tx.try_send(1).unwrap();
+    drop(tx);
+    // The messages sent before the sender was dropped are still received.
+    assert_eq!(rx.try_recv().unwrap(), 1);
+    assert!(matches!(rx.try_recv(), Err(TryRecvError::Disconnected)));
+
+    let (tx, rx) = channel::<u64>(2);
+    drop(rx);
+    assert!(matches!(tx.try_send(1), Err(TrySendError::Disconnected(1))));
+    drop(rx_clone);
+}
+
+#[test]
+fn test_spsc_drops_pending_messages() {
+    let value = Arc::new(());
+    let (tx, rx) = channel(3);
+    tx.try_send(value.clone()).unwrap();
+    tx.try_send(value.clone()).unwrap();
+    let _ = rx.try_recv().unwrap();
+    assert_eq!(Arc::strong_count(&value), 2);
+
+    drop(tx);
+    drop(rx);
+    assert_eq!(Arc::strong_count(&value), 1);
+}
+
+#[test]
+fn test_spsc_async() {
+    let (tx, rx) = channel::<u64>(4);
+    let count = 10_000;
+
+    let producer = std::thread::spawn(move || {
+        async_std::task::block_on(async {
+            for value in 0..count {
+                tx.send_async(value).await.unwrap();
+            }
+        })
+    });
+
+    async_std::task::block_on(async {
+        for value in 0..count {
+            assert_eq!(rx.recv_async().await.unwrap(), value);
+        }
+        // All the senders are dropped once the producer is done.
+        assert!(rx.recv_async().await.is_err());
+    });
+
+    producer.join().unwrap();
+}
@@ -34,10 +34,28 @@
 //! Zenoh Flow provides several working examples that illustrate how to
 //! define operators, sources and sinks as well as how to
 //! declaratively define they data flow graph by means of a YAML file.
+//!
+//! A node only needs the [prelude]: the traits to implement, the types of the data it receives
+//! and sends, and the macros exporting it from its library. The rest of the crate is used by the
+//! daemons to describe and run data flows. The internals of the runtime (`loader`, `runners`) are
+//! only exposed with the `runtime` feature, which the daemons enable: they are not part of the API
+//! and a node does not need them.
 use const_format::formatcp;
 
 pub use ::zenoh_flow_derive;
 
+/// Declares a module of the internals of the runtime: public with the `runtime` feature, only
+/// visible inside the crate otherwise.
+macro_rules! runtime_mod {
+    ($name:ident) => {
+        #[cfg(feature = "runtime")]
+        pub mod $name;
+        #[cfg(not(feature = "runtime"))]
+        pub(crate) mod $name;
+    };
+}
+
+pub(crate) mod executor;
 pub mod io;
 pub mod model;
 pub mod runtime;
@@ -50,15 +68,48 @@ pub mod zfresult;
 pub use anyhow::anyhow;
 pub use zfresult::{DaemonResult, ZFResult as Result};
 
+/// Everything needed to implement a node: `use zenoh_flow::prelude::*;`.
+///
+/// - The traits of the nodes: [Source](crate::traits::Source), [Operator](crate::traits::Operator)
+///   and [Sink](crate::traits::Sink), all of them a [Node](crate::traits::Node), implemented with
+///   [async_trait](async_trait::async_trait).
+/// - The inputs and outputs of the nodes, and the data they receive and send.
+/// - The [Context](crate::types::Context) of a node, with its state partitioned by key
+///   ([KeyedState](crate::types::KeyedState)).
+/// - The macros exporting a node from its library (`export_source`, `export_operator`,
+///   `export_sink`) or the transform of a redaction rule (`export_transform`), deriving the data of
+///   a union port ([UnionData](crate::types::UnionData)) and creating errors
+///   ([zferror](crate::zferror), [bail](crate::bail)).
+///
+/// The items of the prelude follow semantic versioning: a node built against a version of the
+/// prelude keeps building against the next compatible versions.
 pub mod prelude {
-    pub use crate::io::{Input, InputRaw, Inputs, Output, OutputRaw, Outputs};
+    pub use crate::io::{
+        CongestionMonitor, Input, InputRaw, InputSet, Inputs, Output, OutputRaw, Outputs,
+    };
     pub use crate::traits::{Node, Operator, SendSyncAny, Sink, Source};
     pub use crate::types::{
-        Configuration, Context, Data, DataMessage, Message, NodeId, PortId, RuntimeId,
+        Configuration, Context, Data, DataMessage, KeyState, KeyedState, Message, NodeId,
+        PayloadReference, PortId, RuntimeId, UnionData,
+    };
+    pub use crate::zenoh_flow_derive::{
+        export_operator, export_sink, export_source, export_transform, UnionData,
     };
-    pub use crate::zenoh_flow_derive::{export_operator, export_sink, export_source};
-    pub use crate::zferror;
     pub use crate::zfresult::{Error, ErrorKind, ZFResult as Result};
+    pub use crate::{bail, zferror};
+    pub use async_trait::async_trait;
+}
+
+/// The items the export and derive macros expand to, whether or not the `runtime` feature is
+/// enabled: they are not part of the API.
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::runtime::dataflow::loader::{
+        NodeDeclaration, TransformDeclaration, CORE_VERSION, RUSTC_VERSION,
+    };
+    pub use crate::runtime::dataflow::node::{OperatorFn, SinkFn, SourceFn};
+    pub use anyhow;
+    pub use bincode;
 }
 
 /// Commit id of latest commit on Zenoh Flow
@@ -0,0 +1,136 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::model::descriptor::dataflow::replica_id;
+use crate::types::NodeId;
+use crate::zfresult::ErrorKind;
+use crate::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// A constraint on the placement of nodes, honoured when the nodes that are not explicitly mapped
+/// are assigned to runtimes (see [map_to_runtimes](crate::runtime::map_to_runtimes)).
+///
+/// - `colocate`: the nodes run on the same runtime, e.g. to never send the frames of a camera over
+///   the network,
+/// - `separate`: the nodes run on different runtimes, e.g. to not lose all the replicas of a node
+///   with a single runtime.
+///
+/// Example:
+///
+/// ```yaml
+/// affinity:
+///   - colocate: [Camera, Detector]
+///   - separate: [Detector]
+/// ```
+///
+/// A rule naming a replicated node applies to all its copies: the copies of the nodes to co-locate
+/// are co-located index by index (`Camera-0` with `Detector-0`, etc.) --- the replicated nodes must
+/// then have the same number of replicas --- and the copies of the nodes to separate are all
+/// separated from each other. A rule naming a composite operator applies to all the operators it
+/// is made of.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AffinityRule {
+    Colocate(Vec<NodeId>),
+    Separate(Vec<NodeId>),
+}
+
+impl AffinityRule {
+    /// Returns the nodes the rule applies to.
+    pub fn nodes(&self) -> &[NodeId] {
+        match self {
+            AffinityRule::Colocate(nodes) | AffinityRule::Separate(nodes) => nodes,
+        }
+    }
+}
+
+impl fmt::Display for AffinityRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AffinityRule::Colocate(nodes) => write!(f, "colocate: [{}]", nodes.join(", ")),
+            AffinityRule::Separate(nodes) => write!(f, "separate: [{}]", nodes.join(", ")),
+        }
+    }
+}
+
+/// Rewrites the `rules` naming replicated nodes, whose number of copies is given by `replicas`, such
+/// that they name their copies instead (see [AffinityRule]).
+///
+/// # Errors
+///
+/// An error variant is returned if a rule co-locates replicated nodes with a different number of
+/// replicas.
+pub(crate) fn expand_replicas(
+    rules: Vec<AffinityRule>,
+    replicas: &HashMap<NodeId, usize>,
+) -> Result<Vec<AffinityRule>> {
+    if replicas.is_empty() {
+        return Ok(rules);
+    }
+
+    let mut expanded = Vec::with_capacity(rules.len());
+    for rule in rules {
+        match rule {
+            AffinityRule::Colocate(nodes) => {
+                let counts = nodes
+                    .iter()
+                    .filter_map(|node| replicas.get(node))
+                    .collect::<Vec<_>>();
+                let count = match counts.first() {
+                    None => {
+                        expanded.push(AffinityRule::Colocate(nodes));
+                        continue;
+                    }
+                    Some(count) => **count,
+                };
+                if counts.iter().any(|other| **other != count) {
+                    bail!(
+                        ErrorKind::ConfigurationError,
+                        "The affinity rule < colocate: [{}] > co-locates nodes with a different number of replicas",
+                        nodes.join(", ")
+                    )
+                }
+
+                for index in 0..count {
+                    expanded.push(AffinityRule::Colocate(
+                        nodes
+                            .iter()
+                            .map(|node| match replicas.get(node) {
+                                Some(_) => replica_id(node, index),
+                                None => node.clone(),
+                            })
+                            .collect(),
+                    ));
+                }
+            }
+            AffinityRule::Separate(nodes) => {
+                expanded.push(AffinityRule::Separate(
+                    nodes
+                        .into_iter()
+                        .flat_map(|node| match replicas.get(&node) {
+                            Some(count) => (0..*count)
+                                .map(|index| replica_id(&node, index))
+                                .collect::<Vec<_>>(),
+                            None => vec![node],
+                        })
+                        .collect(),
+                ));
+            }
+        }
+    }
+
+    Ok(expanded)
+}
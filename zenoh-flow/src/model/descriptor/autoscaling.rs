@@ -0,0 +1,150 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::types::NodeId;
+use crate::utils::{deserialize_duration, serialize_duration};
+use crate::zfresult::ErrorKind;
+use crate::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// The number of messages waiting per copy above which copies are added, when no `queue` is set.
+pub const DEFAULT_AUTOSCALING_QUEUE: usize = 100;
+
+/// The interval at which the load of the copies is checked, when no `interval` is set (1s).
+pub const DEFAULT_AUTOSCALING_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The minimum time between two rescalings of a node, when no `cooldown` is set (30s).
+pub const DEFAULT_AUTOSCALING_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// The autoscaling of a replicated Operator: its number of copies follows its load, between `min`
+/// and `max`.
+///
+/// The load is the number of messages waiting in the links leading to the copies, checked every
+/// `interval` (1s by default). When the messages waiting per copy exceed `queue` (100 by default),
+/// as many copies are added as needed to bring them below. When one copy less would leave them
+/// below half of `queue`, a copy is removed. Two rescalings of the node are separated by at least
+/// `cooldown` (30s by default), such that a burst does not make the number of copies oscillate.
+///
+/// The node must declare `replicas`, its initial number of copies, between `min` and `max`. Its
+/// copies are rescaled as with
+/// [`DataFlowInstance::rescale`](crate::runtime::dataflow::instance::DataFlowInstance::rescale).
+///
+/// Example:
+///
+/// ```yaml
+/// replicas: 2
+/// autoscaling:
+///   min: 1
+///   max: 8
+///   queue: 50
+///   cooldown: 10s
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AutoscalingDescriptor {
+    pub min: usize,
+    pub max: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queue: Option<usize>,
+    #[serde(default)]
+    #[serde(
+        deserialize_with = "deserialize_duration",
+        serialize_with = "serialize_duration",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub interval: Option<Duration>,
+    #[serde(default)]
+    #[serde(
+        deserialize_with = "deserialize_duration",
+        serialize_with = "serialize_duration",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub cooldown: Option<Duration>,
+}
+
+impl AutoscalingDescriptor {
+    /// Returns the number of messages waiting per copy above which copies are added.
+    pub fn queue(&self) -> usize {
+        self.queue.unwrap_or(DEFAULT_AUTOSCALING_QUEUE).max(1)
+    }
+
+    /// Returns the interval at which the load of the copies is checked.
+    pub fn interval(&self) -> Duration {
+        self.interval.unwrap_or(DEFAULT_AUTOSCALING_INTERVAL)
+    }
+
+    /// Returns the minimum time between two rescalings of the node.
+    pub fn cooldown(&self) -> Duration {
+        self.cooldown.unwrap_or(DEFAULT_AUTOSCALING_COOLDOWN)
+    }
+
+    /// Checks that the node `id`, declaring `replicas`, can be autoscaled.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the node does not declare `replicas`, if `min` is 0 or greater than
+    /// `max`, or if `replicas` is not between them.
+    pub fn validate(&self, id: &NodeId, replicas: Option<usize>) -> Result<()> {
+        let replicas = match replicas {
+            Some(replicas) => replicas,
+            None => bail!(
+                ErrorKind::ConfigurationError,
+                "The node < {} > declares an autoscaling but no replicas",
+                id
+            ),
+        };
+
+        if self.min == 0 || self.min > self.max {
+            bail!(
+                ErrorKind::ConfigurationError,
+                "The autoscaling of < {} > needs 0 < min <= max, got min: {} and max: {}",
+                id,
+                self.min,
+                self.max
+            )
+        }
+
+        if replicas < self.min || replicas > self.max {
+            bail!(
+                ErrorKind::ConfigurationError,
+                "The node < {} > declares {} replicas, not between {} and {}",
+                id,
+                replicas,
+                self.min,
+                self.max
+            )
+        }
+
+        Ok(())
+    }
+
+    /// Returns the number of copies the node should have, given the `queued` messages waiting for
+    /// its `replicas` copies.
+    pub fn desired_replicas(&self, replicas: usize, queued: usize) -> usize {
+        let queue = self.queue();
+        let desired = if queued > queue * replicas {
+            (queued + queue - 1) / queue
+        } else if replicas > 1 && queued * 2 < queue * (replicas - 1) {
+            replicas - 1
+        } else {
+            replicas
+        };
+
+        desired.clamp(self.min, self.max)
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/autoscaling.rs"]
+mod tests;
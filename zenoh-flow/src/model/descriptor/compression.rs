@@ -0,0 +1,73 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::model::descriptor::{DataType, TypeRegistry};
+use crate::utils::{deserialize_size, serialize_size};
+use serde::{Deserialize, Serialize};
+
+/// The size, in bytes, below which a message is not compressed when no `min_size` is set.
+pub const DEFAULT_COMPRESSION_MIN_SIZE: usize = 1024;
+
+/// The compression of the messages exchanged by the connectors of a data flow, i.e. on the links
+/// between nodes running on different runtimes.
+///
+/// Whether the messages of a link are compressed depends on the data type of its output (see
+/// [DataType]): the ones declared as `compressed` in the `types` registry of the data flow (see
+/// [TypeDescriptor](crate::model::descriptor::TypeDescriptor)) are sent as is. The other ones, and
+/// the outputs without data type, are compressed with LZ4.
+///
+/// - `min_size`: the messages smaller than that are not compressed (1KiB by default).
+///
+/// Example:
+///
+/// ```yaml
+/// compression:
+///   min_size: 4KiB
+///
+/// types:
+///   camera.jpeg:
+///     compressed: true
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompressionDescriptor {
+    #[serde(default)]
+    #[serde(
+        deserialize_with = "deserialize_size",
+        serialize_with = "serialize_size",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub min_size: Option<usize>,
+}
+
+impl CompressionDescriptor {
+    /// Returns the size, in bytes, below which a message is not compressed.
+    pub fn min_size(&self) -> usize {
+        self.min_size.unwrap_or(DEFAULT_COMPRESSION_MIN_SIZE)
+    }
+
+    /// Returns `true` if the messages carrying data of type `data_type` should be compressed,
+    /// i.e. if the registry `types` does not declare them as already compressed.
+    pub fn applies_to(&self, data_type: Option<&DataType>, types: &TypeRegistry) -> bool {
+        match data_type {
+            Some(data_type) => !types
+                .get(&data_type.name)
+                .map_or(false, |descriptor| descriptor.compressed),
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/compression.rs"]
+mod tests;
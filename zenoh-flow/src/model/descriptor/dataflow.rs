@@ -12,26 +12,50 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
-use crate::model::descriptor::validator::DataFlowValidator;
+use crate::model::descriptor::affinity::{self, AffinityRule};
+use crate::model::descriptor::migration::{migrate, DESCRIPTOR_VERSION};
+use crate::model::descriptor::strict::{check_fields, ParsingMode};
+use crate::model::descriptor::validator::{validate_registered_ports, DataFlowValidator};
 use crate::model::descriptor::Vars;
 use crate::model::descriptor::{
-    LinkDescriptor, NodeDescriptor, OperatorDescriptor, SinkDescriptor, SourceDescriptor,
+    AutoscalingDescriptor, CompressionDescriptor, ExpiryDescriptor, FeasibilityDescriptor,
+    GpuDescriptor, InputDescriptor, LinkDescriptor, MergeOrdering, MissingRuntimePolicy,
+    NodeDescriptor, NodeUri, OperatorDescriptor, OutputDescriptor, PortType, PreemptionPolicy,
+    ReadinessDescriptor, RedactionDescriptor, RetentionDescriptor, SinkDescriptor,
+    SourceDescriptor, TransportDescriptor, TypeRegistry, Unit, WarmupDescriptor,
+    WatchdogDescriptor,
+};
+use crate::model::registry::RegistryNode;
+use crate::runtime::dataflow::instance::builtin::convert::{
+    get_convert_descriptor, CONVERT_INPUT, CONVERT_OUTPUT, KEY_FROM, KEY_TO,
+};
+use crate::runtime::dataflow::instance::builtin::downsample::{
+    get_downsample_descriptor, DOWNSAMPLE_INPUT, DOWNSAMPLE_OUTPUT, KEY_SAMPLE,
+};
+use crate::runtime::dataflow::instance::builtin::faults::{
+    get_faults_descriptor, FAULTS_INPUT, FAULTS_OUTPUT, KEY_LINK,
+};
+use crate::runtime::dataflow::instance::builtin::merge::{
+    get_merge_descriptor, merge_input, KEY_INPUTS, MERGE_OUTPUT,
 };
 use crate::types::configuration::Merge;
 use crate::types::{Configuration, NodeId, RuntimeId};
+use crate::utils::{deserialize_duration, deserialize_links, serialize_duration};
 use crate::zfresult::ErrorKind;
-use crate::{zferror, Result};
+use crate::{bail, zferror, Result};
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::hash::{Hash, Hasher};
+use std::time::Duration;
 
 /// The description of a data flow graph.
 /// It contains all the information needed to instantiate a data flow graph.
 ///
 /// Example:
 /// ```yaml
+/// version: 2
 /// flow: SimplePipeline
 /// operators:
 ///   - id : SumOperator
@@ -66,38 +90,299 @@ use std::hash::{Hash, Hasher};
 ///     runtime: runtime0
 /// ```
 ///
+/// A link can declare a list of `from` and/or `to` endpoints: one link is then created for each
+/// pair of endpoints. When several outputs feed the same input, the order in which their messages
+/// are processed can be set with `merge` (see [MergeOrdering]):
+///
+/// ```yaml
+/// links:
+/// - from:
+///     - node : CameraLeft
+///       output : Frame
+///     - node : CameraRight
+///       output : Frame
+///   to:
+///     node : Detector
+///     input : Frame
+///   merge: timestamp
+/// ```
+///
+/// A link connecting ports that declare different units (see [Unit]), between which a conversion
+/// is known (e.g. `km/h` and `m/s`), is split by a builtin Convert operator, mapped to the same
+/// runtime as the upstream node. Ports whose units cannot be converted cannot be linked.
+///
+/// A node declaring `replicas` is copied that many times, the copies being identified by the id of
+/// the node followed by their index: `Camera-0`, `Camera-1`, etc. A link between two replicated
+/// nodes, which must have the same number of replicas, connects the copies with the same index. A
+/// link between a replicated node and a node that is not is duplicated for each copy: in the port
+/// of the node that is not replicated, `{replica}` is replaced by the index of the copy. Combined
+/// with the `vars`, large symmetric data flows are described once:
+///
+/// ```yaml
+/// vars:
+///   CAMERAS: "16"
+///
+/// sources:
+///   - id : Camera
+///     descriptor: file://./camera.yaml
+///     replicas: {{ CAMERAS }}
+/// operators:
+///   - id : Detector
+///     descriptor: file://./detector.yaml
+///     replicas: {{ CAMERAS }}
+/// sinks:
+///   - id : Display
+///     descriptor: file://./display.yaml
+///
+/// links:
+/// - from:
+///     node : Camera
+///     output : Frame
+///   to:
+///     node : Detector
+///     input : Frame
+/// - from:
+///     node : Detector
+///     output : Objects
+///   to:
+///     node : Display
+///     input : "Objects-{replica}"
+/// ```
+///
+/// The `mapping` of a replicated node applies to all its copies, unless a copy is mapped
+/// explicitly. If a runtime it names is not running when the instance is created, the
+/// `missing_runtime` policy applies (see [MissingRuntimePolicy]): by default, the instance is not
+/// created.
 ///
+/// The nodes that are not mapped are assigned to runtimes honouring the `affinity` rules: the
+/// nodes that must run on the same runtime and those that must not (see [AffinityRule]).
+///
+/// ```yaml
+/// affinity:
+///   - colocate: [Camera, Detector]
+///   - separate: [Detector]
+/// ```
+///
+/// The Sources only start once the `readiness` checks of the external services the data flow
+/// depends on pass (see [ReadinessDescriptor]).
+///
+/// An instance with a higher `priority` (0 by default) can preempt, when it cannot be created for
+/// lack of resources, the instances with a lower priority that hold them and whose `preemption`
+/// policy allows it (see [PreemptionPolicy]).
+///
+/// An instance whose links carry no message for `idle_timeout` is reported idle, even though no
+/// error is raised (e.g. a camera that stopped sending frames): a warning is logged and a
+/// notification is published under the key expression generated by
+/// [`IDLE_PATH`](crate::IDLE_PATH) (see
+/// [`IdleNotification`](crate::runtime::dataflow::instance::idle::IdleNotification)).
+///
+/// ```yaml
+/// idle_timeout: 30s
+/// ```
+///
+/// An instance with an `expiry` is stopped and deleted automatically once it exceeds its
+/// lifetime, or once it stays idle for too long, a warning being issued beforehand (see
+/// [ExpiryDescriptor]).
+///
+/// ```yaml
+/// expiry:
+///   ttl: 8h
+///   idle: 30min
+/// ```
+///
+/// The deadlines and the ordering of the messages rely on the HLC of the runtimes involved in an
+/// instance being close. When the instance is created, the offset between the HLC of the runtime
+/// creating it and the one of each other runtime is measured: a warning is logged above
+/// [CLOCK_SKEW_WARNING](crate::runtime::clock::CLOCK_SKEW_WARNING) and, if the offset exceeds
+/// `max_clock_skew`, the instance is not created.
+///
+/// ```yaml
+/// max_clock_skew: 50ms
+/// ```
+///
+/// The payloads sent on an output can be transformed, masked or truncated, following its `redaction` rule (see
+/// [RedactionDescriptor]), before they are published by a debug tap or stored by a recording. A
+/// rule on an output of a replicated node applies to all its copies.
+///
+/// ```yaml
+/// redaction:
+///   - output:
+///       node: Gateway
+///       output: Request
+///     mask: [/user/email]
+///     max_bytes: 256
+/// ```
+///
+/// The recordings and the events produced by an instance are deleted once they are older than
+/// the `max_age` of its `retention` policy or, for the recordings, when they exceed its `max_size`
+/// (see [RetentionDescriptor]).
+///
+/// ```yaml
+/// retention:
+///   max_age: 7days
+///   max_size: 10GB
+/// ```
+///
+/// The messages exchanged between runtimes are compressed, following the `compression` settings,
+/// unless the data type of their output is declared as already compressed in the `types` registry
+/// (see [CompressionDescriptor] and
+/// [TypeDescriptor](crate::model::descriptor::TypeDescriptor)). Without these settings, no message is
+/// compressed.
+///
+/// ```yaml
+/// compression:
+///   min_size: 4KiB
+///
+/// types:
+///   camera.jpeg:
+///     compressed: true
+/// ```
+///
+/// The Zenoh `sessions` the links can use (see [LinkDescriptor]) are described by their transport
+/// configuration (see [TransportDescriptor]). A daemon that defines a session with the same name
+/// in its own configuration uses it instead.
+///
+/// ```yaml
+/// sessions:
+///   robots:
+///     mode: client
+///     connect:
+///       - tls/[2001:db8::1]:7447
+///     tls:
+///       root_ca_certificate: /etc/zenoh-flow/certs/ca.pem
+/// ```
+///
+/// The `feasibility` section declares the rates of the Sources, the worst-case execution times of
+/// the nodes, the latency budgets of the links and the end-to-end deadlines, from which the
+/// feasibility analysis predicts, before the data flow is deployed, whether its nodes can keep up
+/// and which one is the bottleneck (see [FeasibilityDescriptor]).
+///
+/// ```yaml
+/// feasibility:
+///   rates:
+///     - node: Camera
+///       rate: 30
+///   wcets:
+///     - node: Detector
+///       wcet: 20ms
+///   deadlines:
+///     - from: Camera
+///       to: Display
+///       deadline: 50ms
+/// ```
+///
+/// The `version` indicates the version of the descriptor format (see [DESCRIPTOR_VERSION]).
+/// Descriptors in an older version are upgraded when they are loaded.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct DataFlowDescriptor {
+    #[serde(default = "current_version")]
+    pub version: u64,
     pub flow: String,
     pub operators: Vec<NodeDescriptor>,
     pub sources: Vec<NodeDescriptor>,
     pub sinks: Vec<NodeDescriptor>,
+    #[serde(deserialize_with = "deserialize_links")]
     pub links: Vec<LinkDescriptor>,
     pub mapping: Option<HashMap<NodeId, RuntimeId>>,
+    #[serde(default)]
+    pub missing_runtime: MissingRuntimePolicy,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub affinity: Vec<AffinityRule>,
     #[serde(alias = "configuration")]
     pub global_configuration: Option<Configuration>,
+    #[serde(default)]
+    pub readiness: Option<ReadinessDescriptor>,
+    #[serde(default)]
+    pub priority: u32,
+    #[serde(default)]
+    pub preemption: PreemptionPolicy,
+    #[serde(default)]
+    #[serde(
+        deserialize_with = "deserialize_duration",
+        serialize_with = "serialize_duration",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub idle_timeout: Option<Duration>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiry: Option<ExpiryDescriptor>,
+    #[serde(default)]
+    #[serde(
+        deserialize_with = "deserialize_duration",
+        serialize_with = "serialize_duration",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_clock_skew: Option<Duration>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redaction: Vec<RedactionDescriptor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retention: Option<RetentionDescriptor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression: Option<CompressionDescriptor>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub types: TypeRegistry,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub sessions: HashMap<String, TransportDescriptor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feasibility: Option<FeasibilityDescriptor>,
 }
 
 impl DataFlowDescriptor {
     /// Creates a new `DataFlowDescriptor` from its YAML representation.
     ///
+    /// The fields that are not known are ignored (see [ParsingMode::Permissive]).
+    ///
     ///  # Errors
     /// A variant error is returned if deserialization fails.
     pub fn from_yaml(data: &str) -> Result<Self> {
-        let descriptor = Vars::expand_mustache_yaml(data)?;
-        let dataflow_descriptor = serde_yaml::from_str::<DataFlowDescriptor>(&descriptor)
+        Self::from_yaml_with_mode(data, ParsingMode::Permissive)
+    }
+
+    /// Creates a new `DataFlowDescriptor` from its YAML representation, handling the fields that
+    /// are not known according to the `mode`.
+    ///
+    ///  # Errors
+    /// A variant error is returned if deserialization fails or if, in [ParsingMode::Strict], the
+    /// descriptor contains unknown fields. Their line is reported.
+    pub fn from_yaml_with_mode(data: &str, mode: ParsingMode) -> Result<Self> {
+        let data = Vars::expand_mustache_yaml(data)?;
+        let descriptor = serde_yaml::from_str::<serde_json::Value>(&data)
             .map_err(|e| zferror!(ErrorKind::ParsingError, e))?;
-        Ok(dataflow_descriptor)
+        check_fields(&descriptor, Some(&data), mode)?;
+        Self::from_value(descriptor)
     }
 
     /// Creates a new `DataFlowDescriptor` from its JSON representation.
     ///
+    /// The fields that are not known are ignored (see [ParsingMode::Permissive]).
+    ///
     ///  # Errors
     /// A variant error is returned if deserialization fails.
     pub fn from_json(data: &str) -> Result<Self> {
-        let descriptor = Vars::expand_mustache_json(data)?;
-        let dataflow_descriptor = serde_json::from_str::<DataFlowDescriptor>(&descriptor)
+        Self::from_json_with_mode(data, ParsingMode::Permissive)
+    }
+
+    /// Creates a new `DataFlowDescriptor` from its JSON representation, handling the fields that
+    /// are not known according to the `mode`.
+    ///
+    ///  # Errors
+    /// A variant error is returned if deserialization fails or if, in [ParsingMode::Strict], the
+    /// descriptor contains unknown fields.
+    pub fn from_json_with_mode(data: &str, mode: ParsingMode) -> Result<Self> {
+        let data = Vars::expand_mustache_json(data)?;
+        let descriptor = serde_json::from_str::<serde_json::Value>(&data)
+            .map_err(|e| zferror!(ErrorKind::ParsingError, e))?;
+        check_fields(&descriptor, None, mode)?;
+        Self::from_value(descriptor)
+    }
+
+    /// Creates a new `DataFlowDescriptor` from its representation, upgrading it to the current
+    /// [DESCRIPTOR_VERSION] if needed.
+    ///
+    ///  # Errors
+    /// A variant error is returned if the migration or the deserialization fails.
+    fn from_value(mut descriptor: serde_json::Value) -> Result<Self> {
+        migrate(&mut descriptor)?;
+        let dataflow_descriptor = DataFlowDescriptor::deserialize(descriptor)
             .map_err(|e| zferror!(ErrorKind::ParsingError, e))?;
         Ok(dataflow_descriptor)
     }
@@ -133,17 +418,119 @@ impl DataFlowDescriptor {
     /// A variant error is returned if loading operators fails.
     pub async fn flatten(self) -> Result<FlattenDataFlowDescriptor> {
         let Self {
+            version: _,
             flow,
-            operators,
-            sources,
-            sinks,
+            mut operators,
+            mut sources,
+            mut sinks,
             mut links,
-            mapping,
+            mut mapping,
+            missing_runtime,
+            affinity,
             global_configuration,
+            readiness,
+            priority,
+            preemption,
+            idle_timeout,
+            expiry,
+            max_clock_skew,
+            redaction,
+            retention,
+            compression,
+            types,
+            sessions,
+            feasibility,
         } = self;
 
+        // The redaction rules are turned into links to a placeholder such that they follow the
+        // outputs they apply to when the replicas are expanded and the composite operators are
+        // flattened. The input of the placeholder is the index of the rule.
+        for (index, rule) in redaction.iter().enumerate() {
+            links.push(LinkDescriptor::new(
+                rule.output.clone(),
+                InputDescriptor::new(REDACTION_PLACEHOLDER, index.to_string()),
+            ));
+        }
+
+        // The autoscaling applies to the replicated node, not to each of its copies: it is removed
+        // before they are expanded. Only the copies of an Operator can be rescaled.
+        let mut autoscaling = HashMap::new();
+        for node in sources.iter_mut().chain(sinks.iter_mut()) {
+            if node.autoscaling.take().is_some() {
+                log::warn!(
+                    "[Descriptor] Ignoring the `autoscaling` of < {} >, only Operators can be rescaled",
+                    node.id
+                );
+            }
+        }
+        for node in operators.iter_mut() {
+            if let Some(policy) = node.autoscaling.take() {
+                policy.validate(&node.id, node.replicas)?;
+                autoscaling.insert(node.id.clone(), policy);
+            }
+        }
+
+        let replicas = expand_replicas(
+            [&mut sources, &mut operators, &mut sinks],
+            &mut links,
+            &mut mapping,
+        )?;
+        let affinity = affinity::expand_replicas(affinity, &replicas)?;
+
+        // The exposed outputs and imported inputs are turned into links from or to a placeholder
+        // such that, when the composite operators are flattened, they point to the operators
+        // actually producing or receiving the data.
+        for node in sources.iter_mut().chain(operators.iter_mut()) {
+            for output in node.exposed.drain(..) {
+                links.push(LinkDescriptor::new(
+                    OutputDescriptor::new(&node.id, output),
+                    InputDescriptor::new(CROSS_FLOW_PLACEHOLDER, ""),
+                ));
+            }
+        }
+        for node in operators.iter_mut().chain(sinks.iter_mut()) {
+            for input in node.imported.drain(..) {
+                links.push(LinkDescriptor::new(
+                    OutputDescriptor::new(CROSS_FLOW_PLACEHOLDER, ""),
+                    InputDescriptor::new(&node.id, input),
+                ));
+            }
+        }
+
+        let mut max_run_durations = HashMap::new();
+        let mut credits = HashMap::new();
+        let mut warmups = HashMap::new();
+        let mut cooldowns = HashMap::new();
+        let mut gpus = HashMap::new();
+        let mut watchdogs = HashMap::new();
+
         let mut flattened_sources = Vec::with_capacity(sources.len());
         for source in sources {
+            if let Some(max_run_duration) = source.max_run_duration {
+                max_run_durations.insert(source.id.clone(), max_run_duration);
+            }
+            if let Some(warmup) = source.warmup {
+                warmups.insert(source.id.clone(), warmup);
+            }
+            if let Some(cooldown) = source.cooldown {
+                cooldowns.insert(source.id.clone(), cooldown);
+            }
+            if let Some(gpu) = source.gpu {
+                gpu.validate(&source.id)?;
+                gpus.insert(source.id.clone(), gpu);
+            }
+            if let Some(watchdog) = source.watchdog {
+                watchdogs.insert(source.id.clone(), watchdog);
+            }
+            if !source.imported.is_empty() {
+                log::warn!(
+                    "[Descriptor] Ignoring the `imported` inputs of the Source < {} >, it has no input",
+                    source.id
+                );
+            }
+            if let Some(source_credits) = source.credits {
+                credits.insert(source.id.clone(), source_credits);
+            }
             let config = global_configuration
                 .clone()
                 .merge_overwrite(source.configuration.clone());
@@ -152,6 +539,37 @@ impl DataFlowDescriptor {
 
         let mut flattened_sinks = Vec::with_capacity(sinks.len());
         for sink in sinks {
+            if let Some(max_run_duration) = sink.max_run_duration {
+                max_run_durations.insert(sink.id.clone(), max_run_duration);
+            }
+            if sink.credits.is_some() {
+                log::warn!(
+                    "[Descriptor] Ignoring the `credits` of the Sink < {} >, only Sources are flow controlled",
+                    sink.id
+                );
+            }
+            if sink.warmup.is_some() {
+                log::warn!(
+                    "[Descriptor] Ignoring the `warmup` of the Sink < {} >, it does not send data",
+                    sink.id
+                );
+            }
+            if let Some(cooldown) = sink.cooldown {
+                cooldowns.insert(sink.id.clone(), cooldown);
+            }
+            if let Some(gpu) = sink.gpu {
+                gpu.validate(&sink.id)?;
+                gpus.insert(sink.id.clone(), gpu);
+            }
+            if let Some(watchdog) = sink.watchdog {
+                watchdogs.insert(sink.id.clone(), watchdog);
+            }
+            if !sink.exposed.is_empty() {
+                log::warn!(
+                    "[Descriptor] Ignoring the `exposed` outputs of the Sink < {} >, it has no output",
+                    sink.id
+                );
+            }
             let config = global_configuration
                 .clone()
                 .merge_overwrite(sink.configuration.clone());
@@ -165,12 +583,75 @@ impl DataFlowDescriptor {
                 .merge_overwrite(operator.configuration.clone());
 
             let id = operator.id.clone();
+            let max_run_duration = operator.max_run_duration;
+            let (warmup, cooldown) = (operator.warmup, operator.cooldown);
+            let (gpu, watchdog) = (operator.gpu, operator.watchdog);
+            if let Some(gpu) = gpu {
+                gpu.validate(&id)?;
+            }
+            if operator.credits.is_some() {
+                log::warn!(
+                    "[Descriptor] Ignoring the `credits` of the Operator < {} >, only Sources are flow controlled",
+                    id
+                );
+            }
             let mut flattened = operator
                 .flatten(id, &mut links, config, &mut Vec::new())
                 .await?;
+            for operator in flattened.iter() {
+                if let Some(max_run_duration) = max_run_duration {
+                    max_run_durations.insert(operator.id.clone(), max_run_duration);
+                }
+                if let Some(warmup) = warmup {
+                    warmups.insert(operator.id.clone(), warmup);
+                }
+                if let Some(cooldown) = cooldown {
+                    cooldowns.insert(operator.id.clone(), cooldown);
+                }
+                if let Some(gpu) = gpu {
+                    gpus.insert(operator.id.clone(), gpu);
+                }
+                if let Some(watchdog) = watchdog {
+                    watchdogs.insert(operator.id.clone(), watchdog);
+                }
+            }
             flattened_operators.append(&mut flattened);
         }
 
+        let (redacted, links): (Vec<_>, Vec<_>) = links
+            .into_iter()
+            .partition(|link| link.to.node.as_ref() == REDACTION_PLACEHOLDER);
+        let redaction = redacted
+            .into_iter()
+            .filter_map(|link| {
+                let rule = redaction.get(link.to.input.parse::<usize>().ok()?)?;
+                Some(RedactionDescriptor {
+                    output: link.from,
+                    ..rule.clone()
+                })
+            })
+            .collect();
+
+        let (cross_flow, mut links): (Vec<_>, Vec<_>) = links.into_iter().partition(|link| {
+            link.to.node.as_ref() == CROSS_FLOW_PLACEHOLDER
+                || link.from.node.as_ref() == CROSS_FLOW_PLACEHOLDER
+        });
+        let (exposed, imported): (Vec<_>, Vec<_>) = cross_flow
+            .into_iter()
+            .partition(|link| link.to.node.as_ref() == CROSS_FLOW_PLACEHOLDER);
+        let exposed = exposed.into_iter().map(|link| link.from).collect();
+        let imported = imported.into_iter().map(|link| link.to).collect();
+
+        insert_convert_operators(
+            &mut links,
+            &flattened_sources,
+            &mut flattened_operators,
+            &flattened_sinks,
+            &mut mapping,
+        )?;
+        insert_link_operators(&mut links, &mut flattened_operators, &mut mapping)?;
+        insert_merge_operators(&mut links, &mut flattened_operators, &mut mapping)?;
+
         Ok(FlattenDataFlowDescriptor {
             flow,
             sources: flattened_sources,
@@ -178,9 +659,412 @@ impl DataFlowDescriptor {
             operators: flattened_operators,
             links,
             mapping,
+            missing_runtime,
+            affinity,
             global_configuration,
+            max_run_durations,
+            credits,
+            warmups,
+            cooldowns,
+            gpus,
+            watchdogs,
+            readiness,
+            priority,
+            preemption,
+            idle_timeout,
+            expiry,
+            max_clock_skew,
+            redaction,
+            retention,
+            compression,
+            types,
+            replicas,
+            autoscaling,
+            exposed,
+            imported,
+            sessions,
+            feasibility,
+        })
+    }
+}
+
+/// The placeholder node to which the exposed outputs and the imported inputs are linked while the
+/// data flow is flattened.
+const CROSS_FLOW_PLACEHOLDER: &str = "{cross-flow}";
+
+/// The placeholder node to which the outputs having a redaction rule are linked while the data flow
+/// is flattened.
+const REDACTION_PLACEHOLDER: &str = "{redaction}";
+
+/// The placeholder, in the port of a link connecting a replicated node, replaced by the index of the
+/// replica.
+const REPLICA_PLACEHOLDER: &str = "{replica}";
+
+/// Returns the id of the copy `index` of the replicated node `id`.
+pub(crate) fn replica_id(id: &NodeId, index: usize) -> NodeId {
+    format!("{id}-{index}").into()
+}
+
+/// Returns, if the node `id` is a copy of a replicated node (or an operator of a copy of a
+/// replicated composite operator), the id it would have without replicas, its index and the number
+/// of copies.
+pub(crate) fn replica_of(
+    replicas: &HashMap<NodeId, usize>,
+    id: &NodeId,
+) -> Option<(NodeId, usize, usize)> {
+    replicas.iter().find_map(|(replicated, count)| {
+        (0..*count).find_map(|index| {
+            let copy = replica_id(replicated, index);
+            match id.strip_prefix(&*copy) {
+                Some("") => Some((replicated.clone(), index, *count)),
+                Some(rest) if rest.starts_with('/') => {
+                    Some((format!("{replicated}{rest}").into(), index, *count))
+                }
+                _ => None,
+            }
         })
+    })
+}
+
+/// Replaces every node declaring `replicas` with its copies, and every link connecting it with the
+/// links connecting its copies (see [DataFlowDescriptor]), and returns the number of copies of each
+/// replicated node.
+///
+/// # Errors
+///
+/// An error variant is returned if a node declares 0 replicas or if a link connects two replicated
+/// nodes with a different number of replicas.
+fn expand_replicas(
+    nodes: [&mut Vec<NodeDescriptor>; 3],
+    links: &mut Vec<LinkDescriptor>,
+    mapping: &mut Option<HashMap<NodeId, RuntimeId>>,
+) -> Result<HashMap<NodeId, usize>> {
+    let mut replicas = HashMap::new();
+    for nodes in nodes {
+        let mut expanded = Vec::with_capacity(nodes.len());
+        for node in nodes.drain(..) {
+            let count = match node.replicas {
+                None => {
+                    expanded.push(node);
+                    continue;
+                }
+                Some(0) => bail!(
+                    ErrorKind::ConfigurationError,
+                    "The node < {} > declares 0 replicas",
+                    node.id
+                ),
+                Some(count) => count,
+            };
+
+            let runtime = mapping
+                .as_mut()
+                .and_then(|mapping| mapping.remove(&node.id));
+            for index in 0..count {
+                let id = replica_id(&node.id, index);
+                if let (Some(mapping), Some(runtime)) = (mapping.as_mut(), runtime.as_ref()) {
+                    mapping.entry(id.clone()).or_insert_with(|| runtime.clone());
+                }
+                expanded.push(NodeDescriptor {
+                    id,
+                    replicas: None,
+                    ..node.clone()
+                });
+            }
+            replicas.insert(node.id, count);
+        }
+        *nodes = expanded;
+    }
+
+    if replicas.is_empty() {
+        return Ok(replicas);
+    }
+
+    let mut expanded = Vec::with_capacity(links.len());
+    for link in links.drain(..) {
+        let count = match (replicas.get(&link.from.node), replicas.get(&link.to.node)) {
+            (None, None) => {
+                expanded.push(link);
+                continue;
+            }
+            (Some(from), Some(to)) if from != to => bail!(
+                ErrorKind::ConfigurationError,
+                "The link {} connects nodes with a different number of replicas ({} and {})",
+                link,
+                from,
+                to
+            ),
+            (Some(count), _) | (_, Some(count)) => *count,
+        };
+
+        for index in 0..count {
+            let index_str = index.to_string();
+            let mut replica = link.clone();
+            if replicas.contains_key(&link.from.node) {
+                replica.from.node = replica_id(&link.from.node, index);
+            }
+            replica.from.output = link
+                .from
+                .output
+                .replace(REPLICA_PLACEHOLDER, &index_str)
+                .into();
+            if replicas.contains_key(&link.to.node) {
+                replica.to.node = replica_id(&link.to.node, index);
+            }
+            replica.to.input = link
+                .to
+                .input
+                .replace(REPLICA_PLACEHOLDER, &index_str)
+                .into();
+            expanded.push(replica);
+        }
     }
+    *links = expanded;
+
+    Ok(replicas)
+}
+
+/// Inserts a builtin Convert operator on every link connecting ports that declare different units
+/// between which a conversion is known. The links connecting ports whose units cannot be converted
+/// are left as is: the validation rejects them.
+///
+/// The inserted operator is mapped to the same runtime as the upstream node. The link from the
+/// inserted operator keeps the settings of the original link.
+///
+/// # Errors
+///
+/// An error variant is returned if a link to convert has a port declaring a data type --- the
+/// builtin Convert only converts payloads that are a single JSON number --- or if the descriptor
+/// of an inserted operator could not be generated.
+fn insert_convert_operators(
+    links: &mut Vec<LinkDescriptor>,
+    sources: &[SourceDescriptor],
+    operators: &mut Vec<OperatorDescriptor>,
+    sinks: &[SinkDescriptor],
+    mapping: &mut Option<HashMap<NodeId, RuntimeId>>,
+) -> Result<()> {
+    let mut output_units: HashMap<OutputDescriptor, &Unit> = HashMap::new();
+    let mut input_units: HashMap<InputDescriptor, &Unit> = HashMap::new();
+    let mut output_types: HashMap<OutputDescriptor, &PortType> = HashMap::new();
+    let mut input_types: HashMap<InputDescriptor, &PortType> = HashMap::new();
+    for source in sources {
+        for (output, unit) in &source.output_units {
+            output_units.insert(OutputDescriptor::new(&source.id, output), unit);
+        }
+        for (output, port_type) in &source.output_types {
+            output_types.insert(OutputDescriptor::new(&source.id, output), port_type);
+        }
+    }
+    for operator in operators.iter() {
+        for (output, unit) in &operator.output_units {
+            output_units.insert(OutputDescriptor::new(&operator.id, output), unit);
+        }
+        for (input, unit) in &operator.input_units {
+            input_units.insert(InputDescriptor::new(&operator.id, input), unit);
+        }
+        for (output, port_type) in &operator.output_types {
+            output_types.insert(OutputDescriptor::new(&operator.id, output), port_type);
+        }
+        for (input, port_type) in &operator.input_types {
+            input_types.insert(InputDescriptor::new(&operator.id, input), port_type);
+        }
+    }
+    for sink in sinks {
+        for (input, unit) in &sink.input_units {
+            input_units.insert(InputDescriptor::new(&sink.id, input), unit);
+        }
+        for (input, port_type) in &sink.input_types {
+            input_types.insert(InputDescriptor::new(&sink.id, input), port_type);
+        }
+    }
+
+    let mut converters = Vec::new();
+    for link in links.iter_mut() {
+        let (from, to) = match (output_units.get(&link.from), input_units.get(&link.to)) {
+            (Some(from), Some(to)) if from != to && from.conversion_to(to).is_some() => (from, to),
+            _ => continue,
+        };
+
+        if let Some(port_type) = output_types
+            .get(&link.from)
+            .or_else(|| input_types.get(&link.to))
+        {
+            bail!(
+                ErrorKind::ConfigurationError,
+                "The link {} requires a conversion from < {} > to < {} > but carries data of type < {} >: only the payloads that are a single number can be converted",
+                link,
+                from,
+                to,
+                port_type
+            );
+        }
+
+        let mut configuration = serde_json::Map::new();
+        configuration.insert(KEY_FROM.to_string(), from.to_string().into());
+        configuration.insert(KEY_TO.to_string(), to.to_string().into());
+        let mut convert = get_convert_descriptor(&configuration.into())?;
+        convert.id = format!(
+            "convert-{}-{}-{}-{}",
+            link.from.node, link.from.output, link.to.node, link.to.input
+        )
+        .replace('/', "-")
+        .into();
+
+        if let Some(mapping) = mapping {
+            if let Some(runtime) = mapping.get(&link.from.node).cloned() {
+                mapping.insert(convert.id.clone(), runtime);
+            }
+        }
+
+        let upstream = LinkDescriptor::new(
+            link.from.clone(),
+            InputDescriptor::new(&convert.id, CONVERT_INPUT),
+        );
+        link.from = OutputDescriptor::new(&convert.id, CONVERT_OUTPUT);
+        converters.push((convert, upstream));
+    }
+
+    for (convert, upstream) in converters {
+        operators.push(convert);
+        links.push(upstream);
+    }
+    Ok(())
+}
+
+/// Replaces every link declaring a sampling rate and/or faults with, respectively, a builtin
+/// Downsample and a builtin Faults operator, and the links connecting them.
+///
+/// The inserted operators are mapped to the same runtime as the upstream node so that the discarded
+/// messages never go through the network.
+///
+/// # Errors
+///
+/// An error variant is returned if the descriptor of an inserted operator could not be generated.
+fn insert_link_operators(
+    links: &mut Vec<LinkDescriptor>,
+    operators: &mut Vec<OperatorDescriptor>,
+    mapping: &mut Option<HashMap<NodeId, RuntimeId>>,
+) -> Result<()> {
+    let mut inserted_links = Vec::new();
+
+    for link in links.iter_mut() {
+        let path = format!(
+            "{}/{}/{}/{}",
+            link.from.node, link.from.output, link.to.node, link.to.input
+        );
+        let suffix = path.replace('/', "-");
+
+        let mut inserted = Vec::new();
+
+        if let Some(rate) = link.sample.take() {
+            let mut configuration = serde_json::Map::new();
+            configuration.insert(KEY_SAMPLE.to_string(), rate.to_string().into());
+            let mut downsample = get_downsample_descriptor(&configuration.into())?;
+            downsample.id = format!("downsample-{suffix}").into();
+            inserted.push((downsample, DOWNSAMPLE_INPUT, DOWNSAMPLE_OUTPUT));
+        }
+
+        if let Some(faults) = link.faults.take() {
+            let mut configuration = serde_json::to_value(&faults).map_err(|e| {
+                zferror!(
+                    ErrorKind::SerializationError,
+                    "Unable to serialize the faults of link {link}: {e}"
+                )
+            })?;
+            if let Some(configuration) = configuration.as_object_mut() {
+                configuration.insert(KEY_LINK.to_string(), path.clone().into());
+            }
+            let mut faults = get_faults_descriptor(&configuration)?;
+            faults.id = format!("faults-{suffix}").into();
+            inserted.push((faults, FAULTS_INPUT, FAULTS_OUTPUT));
+        }
+
+        // The queue protects the downstream node: it is kept on the last link.
+        let queue = link.queue.take();
+        for (operator, input, output) in inserted {
+            if let Some(mapping) = mapping {
+                if let Some(runtime) = mapping.get(&link.from.node).cloned() {
+                    mapping.insert(operator.id.clone(), runtime);
+                }
+            }
+
+            let mut downstream = link.clone();
+            downstream.from = OutputDescriptor::new(&operator.id, output);
+            link.to = InputDescriptor::new(&operator.id, input);
+            inserted_links.push(link.clone());
+            *link = downstream;
+
+            operators.push(operator);
+        }
+        link.queue = queue;
+    }
+
+    links.append(&mut inserted_links);
+    Ok(())
+}
+
+/// Inserts a builtin Merge operator in front of every input fed by several links that requested the
+/// messages to be processed in the order of their timestamps.
+///
+/// The inserted operator is mapped to the same runtime as the downstream node.
+///
+/// # Errors
+///
+/// An error variant is returned if the links feeding the same input requested different orderings.
+fn insert_merge_operators(
+    links: &mut Vec<LinkDescriptor>,
+    operators: &mut Vec<OperatorDescriptor>,
+    mapping: &mut Option<HashMap<NodeId, RuntimeId>>,
+) -> Result<()> {
+    let mut orderings: HashMap<InputDescriptor, (Option<MergeOrdering>, Vec<usize>)> =
+        HashMap::new();
+    for (index, link) in links.iter_mut().enumerate() {
+        let (ordering, indexes) = orderings.entry(link.to.clone()).or_default();
+        if let Some(merge) = link.merge.take() {
+            if ordering.map_or(false, |ordering| ordering != merge) {
+                bail!(
+                    ErrorKind::ConfigurationError,
+                    "The links feeding < {} > requested different merge orderings",
+                    link.to
+                )
+            }
+            *ordering = Some(merge);
+        }
+        indexes.push(index);
+    }
+
+    for (to, (ordering, indexes)) in orderings {
+        if ordering != Some(MergeOrdering::Timestamp) || indexes.len() < 2 {
+            continue;
+        }
+
+        let mut configuration = serde_json::Map::new();
+        configuration.insert(KEY_INPUTS.to_string(), indexes.len().into());
+        let mut merge = get_merge_descriptor(&configuration.into())?;
+        merge.id = format!("merge-{}-{}", to.node, to.input).into();
+
+        if let Some(mapping) = mapping {
+            if let Some(runtime) = mapping.get(&to.node).cloned() {
+                mapping.insert(merge.id.clone(), runtime);
+            }
+        }
+
+        for (port, index) in indexes.into_iter().enumerate() {
+            links[index].to = InputDescriptor::new(&merge.id, merge_input(port));
+        }
+        links.push(LinkDescriptor::new(
+            OutputDescriptor::new(&merge.id, MERGE_OUTPUT),
+            to,
+        ));
+
+        operators.push(merge);
+    }
+
+    Ok(())
+}
+
+/// The version of the descriptors deserialized without migration.
+fn current_version() -> u64 {
+    DESCRIPTOR_VERSION
 }
 
 impl Hash for DataFlowDescriptor {
@@ -207,8 +1091,59 @@ pub struct FlattenDataFlowDescriptor {
     pub sinks: Vec<SinkDescriptor>,
     pub links: Vec<LinkDescriptor>,
     pub mapping: Option<HashMap<NodeId, RuntimeId>>,
+    #[serde(default)]
+    pub missing_runtime: MissingRuntimePolicy,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub affinity: Vec<AffinityRule>,
     #[serde(alias = "configuration")]
     pub global_configuration: Option<Configuration>,
+    #[serde(default)]
+    pub max_run_durations: HashMap<NodeId, Duration>,
+    #[serde(default)]
+    pub credits: HashMap<NodeId, usize>,
+    #[serde(default)]
+    pub warmups: HashMap<NodeId, WarmupDescriptor>,
+    #[serde(default)]
+    pub cooldowns: HashMap<NodeId, Duration>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub gpus: HashMap<NodeId, GpuDescriptor>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub watchdogs: HashMap<NodeId, WatchdogDescriptor>,
+    #[serde(default)]
+    pub readiness: Option<ReadinessDescriptor>,
+    #[serde(default)]
+    pub priority: u32,
+    #[serde(default)]
+    pub preemption: PreemptionPolicy,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_timeout: Option<Duration>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiry: Option<ExpiryDescriptor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_clock_skew: Option<Duration>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redaction: Vec<RedactionDescriptor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retention: Option<RetentionDescriptor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression: Option<CompressionDescriptor>,
+    /// The properties of the data types of the data flow, by their name.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub types: TypeRegistry,
+    /// The number of copies of each replicated node, by the id it has in the descriptor.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub replicas: HashMap<NodeId, usize>,
+    /// The autoscaling of each replicated Operator, by the id it has in the descriptor.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub autoscaling: HashMap<NodeId, AutoscalingDescriptor>,
+    #[serde(default)]
+    pub exposed: Vec<OutputDescriptor>,
+    #[serde(default)]
+    pub imported: Vec<InputDescriptor>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub sessions: HashMap<String, TransportDescriptor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feasibility: Option<FeasibilityDescriptor>,
 }
 
 impl FlattenDataFlowDescriptor {
@@ -258,24 +1193,149 @@ impl FlattenDataFlowDescriptor {
         }
     }
 
+    /// Returns the ids of the nodes of this `FlattenDataFlowDescriptor`.
+    pub fn node_ids(&self) -> Vec<NodeId> {
+        self.sources
+            .iter()
+            .map(|source| source.id.clone())
+            .chain(self.operators.iter().map(|operator| operator.id.clone()))
+            .chain(self.sinks.iter().map(|sink| sink.id.clone()))
+            .collect()
+    }
+
+    /// Returns the nodes an affinity rule naming `node` applies to: the node itself or, for a
+    /// composite operator, the operators it is made of.
+    pub(crate) fn affinity_members(&self, node: &NodeId) -> Vec<NodeId> {
+        let prefix = format!("{node}/");
+        self.node_ids()
+            .into_iter()
+            .filter(|id| id.as_ref() == node.as_ref() || id.starts_with(&prefix))
+            .collect()
+    }
+
     /// This method checks that the dataflow graph is correct.
     ///
     /// In particular it verifies that:
     /// - each node has a unique id,
-    /// - each port (input and output) is connected,
+    /// - each port (input and output) is connected, side and exposed outputs as well as imported
+    ///   inputs excepted,
     /// - an input port is connected only once (i.e. it receives data from a single output port),
     /// - connected ports are declared with the same type,
     /// - the dataflow, without the loops, is a DAG,
     /// - the end-to-end deadlines are correct,
-    /// - the loops are valid.
+    /// - the loops are valid,
+    /// - the affinity rules name existing nodes,
+    /// - the queues of the links can hold at least one message.
     ///
     ///  # Errors
     /// A variant error is returned if validation fails.
     pub fn validate(&self) -> Result<()> {
         let validator = DataFlowValidator::try_from(self)?;
         validator.validate_ports()?;
+        for rule in &self.affinity {
+            for node in rule.nodes() {
+                if self.affinity_members(node).is_empty() {
+                    bail!(
+                        ErrorKind::ConfigurationError,
+                        "The affinity rule < {} > names the unknown node < {} >",
+                        rule,
+                        node
+                    )
+                }
+            }
+        }
+        for transport in self.sessions.values() {
+            transport.validate()?;
+        }
+        for link in &self.links {
+            if let Some(queue) = &link.queue {
+                queue.validate().map_err(|e| {
+                    zferror!(ErrorKind::ConfigurationError, "Link < {} >: {}", link, e)
+                })?;
+            }
+        }
+
+        let uris = self
+            .sources
+            .iter()
+            .map(|source| (&source.id, &source.uri))
+            .chain(
+                self.operators
+                    .iter()
+                    .map(|operator| (&operator.id, &operator.uri)),
+            )
+            .chain(self.sinks.iter().map(|sink| (&sink.id, &sink.uri)));
+        for (node_id, uri) in uris {
+            if let Some(uri) = uri {
+                uri.validate().map_err(|e| {
+                    zferror!(ErrorKind::ParsingError, "Node < {} >: {}", node_id, e)
+                })?;
+            }
+        }
+
         Ok(())
     }
+
+    /// Checks that the dataflow graph is correct (see [validate](Self::validate)) and that the
+    /// nodes whose `uri` is one of the components of the `registry` declare exactly the inputs and
+    /// outputs of this component.
+    ///
+    /// The ports are checked against the registry first: a port name mistyped in the descriptor is
+    /// reported along with the closest port declared by the component.
+    ///
+    ///  # Errors
+    /// A variant error is returned if validation fails.
+    pub fn validate_with_registry(&self, registry: &[RegistryNode]) -> Result<()> {
+        let find_component = |uri: &Option<NodeUri>| {
+            uri.as_ref().and_then(|uri| {
+                let uris = uri.uris();
+                registry.iter().find(|component| {
+                    component
+                        .tags
+                        .iter()
+                        .flat_map(|tag| tag.architectures.iter())
+                        .any(|architecture| uris.contains(&architecture.uri.as_str()))
+                })
+            })
+        };
+
+        for source in &self.sources {
+            if let Some(component) = find_component(&source.uri) {
+                validate_registered_ports(
+                    &source.id,
+                    "Output",
+                    &source.outputs,
+                    &component.outputs,
+                )?;
+            }
+        }
+
+        for operator in &self.operators {
+            if let Some(component) = find_component(&operator.uri) {
+                validate_registered_ports(
+                    &operator.id,
+                    "Input",
+                    &operator.inputs,
+                    &component.inputs,
+                )?;
+                let outputs = operator
+                    .outputs
+                    .iter()
+                    .chain(operator.side_outputs.iter())
+                    .cloned()
+                    .collect::<Vec<_>>();
+                validate_registered_ports(&operator.id, "Output", &outputs, &component.outputs)?;
+            }
+        }
+
+        for sink in &self.sinks {
+            if let Some(component) = find_component(&sink.uri) {
+                validate_registered_ports(&sink.id, "Input", &sink.inputs, &component.inputs)?;
+            }
+        }
+
+        self.validate()
+    }
 }
 
 impl Hash for FlattenDataFlowDescriptor {
@@ -0,0 +1,265 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::zfresult::{ErrorKind, ZFResult as Result};
+use crate::{bail, zferror};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+/// The versioned type of the data sent, or expected, on a port: its name followed by its version,
+/// e.g. `my.company.Detection@1.2`.
+///
+/// The version follows the semantic versioning: `major.minor`, optionally followed by a `.patch`.
+/// Two data types are compatible if they have the same name and the same major version --- a new
+/// minor version only adds to the type.
+///
+/// The data types are declared, per port, by the components:
+///
+/// ```yaml
+/// id: Detector
+/// uri: file://./target/release/libdetector.so
+/// inputs: [Frame]
+/// outputs: [Detections]
+/// input_types:
+///   Frame: my.company.Image@2.0
+/// output_types:
+///   Detections: my.company.Detection@1.2
+/// ```
+///
+/// A port without a data type is compatible with all the others.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct DataType {
+    pub name: String,
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl DataType {
+    /// Returns `true` if the data of type `self` can be given to a port expecting `other`: both
+    /// have the same name and the same major version.
+    pub fn is_compatible_with(&self, other: &DataType) -> bool {
+        self.name == other.name && self.major == other.major
+    }
+}
+
+impl std::fmt::Display for DataType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}@{}.{}", self.name, self.major, self.minor)?;
+        if self.patch != 0 {
+            write!(f, ".{}", self.patch)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for DataType {
+    type Err = crate::zfresult::Error;
+
+    /// Parses a data type of the form `name@major.minor[.patch]`.
+    ///
+    /// # Errors
+    /// An error variant is returned if the name is empty or if the version is missing or malformed.
+    fn from_str(data_type: &str) -> Result<Self> {
+        let (name, version) = data_type.rsplit_once('@').ok_or_else(|| {
+            zferror!(
+                ErrorKind::ParsingError,
+                "Data type < {} > is not of the form `name@major.minor`",
+                data_type
+            )
+        })?;
+
+        if name.is_empty() {
+            bail!(
+                ErrorKind::ParsingError,
+                "Data type < {} > has no name",
+                data_type
+            );
+        }
+
+        let numbers = version
+            .split('.')
+            .map(|number| number.parse::<u32>())
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| {
+                zferror!(
+                    ErrorKind::ParsingError,
+                    "Invalid version < {} > of data type < {} >: {}",
+                    version,
+                    name,
+                    e
+                )
+            })?;
+
+        match numbers[..] {
+            [major, minor] => Ok(Self {
+                name: name.to_string(),
+                major,
+                minor,
+                patch: 0,
+            }),
+            [major, minor, patch] => Ok(Self {
+                name: name.to_string(),
+                major,
+                minor,
+                patch,
+            }),
+            _ => bail!(
+                ErrorKind::ParsingError,
+                "Version < {} > of data type < {} > is not of the form `major.minor[.patch]`",
+                version,
+                name
+            ),
+        }
+    }
+}
+
+impl TryFrom<String> for DataType {
+    type Error = crate::zfresult::Error;
+
+    fn try_from(data_type: String) -> Result<Self> {
+        data_type.parse()
+    }
+}
+
+impl From<DataType> for String {
+    fn from(data_type: DataType) -> Self {
+        data_type.to_string()
+    }
+}
+
+/// The type of the data sent, or expected, on a port: either a single [DataType] or a tagged union
+/// of several ones.
+///
+/// A union port carries, at each activation, data of any of its variants: the index of the variant
+/// is sent alongside the data, in the header of the message, such that the receiving side knows
+/// how to deserialize it (see [`UnionData`](crate::prelude::UnionData)). A union is declared as a
+/// list of data types:
+///
+/// ```yaml
+/// output_types:
+///   Reading: [my.company.Temperature@1.0, my.company.Humidity@1.0]
+/// ```
+///
+/// A union is only compatible with a union having as many variants, in the same order, each being
+/// compatible with its counterpart.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PortType {
+    Single(DataType),
+    Union(Vec<DataType>),
+}
+
+impl PortType {
+    /// Returns the data types the port can carry: the variants of a union, in order, or the single
+    /// data type.
+    pub fn variants(&self) -> &[DataType] {
+        match self {
+            PortType::Single(data_type) => std::slice::from_ref(data_type),
+            PortType::Union(variants) => variants,
+        }
+    }
+
+    /// Returns `true` if the data sent on a port of type `self` can be given to a port expecting
+    /// `other` (see [DataType::is_compatible_with]).
+    pub fn is_compatible_with(&self, other: &PortType) -> bool {
+        match (self, other) {
+            (PortType::Single(data_type), PortType::Single(other)) => {
+                data_type.is_compatible_with(other)
+            }
+            (PortType::Union(variants), PortType::Union(others)) => {
+                variants.len() == others.len()
+                    && variants
+                        .iter()
+                        .zip(others)
+                        .all(|(variant, other)| variant.is_compatible_with(other))
+            }
+            _ => false,
+        }
+    }
+}
+
+impl From<DataType> for PortType {
+    fn from(data_type: DataType) -> Self {
+        PortType::Single(data_type)
+    }
+}
+
+impl std::fmt::Display for PortType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PortType::Single(data_type) => write!(f, "{data_type}"),
+            PortType::Union(variants) => {
+                let variants = variants.iter().map(DataType::to_string).collect::<Vec<_>>();
+                write!(f, "[{}]", variants.join("|"))
+            }
+        }
+    }
+}
+
+impl FromStr for PortType {
+    type Err = crate::zfresult::Error;
+
+    /// Parses a port type: a data type, or a union of the form `[name@major.minor|...]`.
+    ///
+    /// # Errors
+    /// An error variant is returned if one of the data types is malformed or if a union is empty.
+    fn from_str(port_type: &str) -> Result<Self> {
+        match port_type
+            .strip_prefix('[')
+            .and_then(|union| union.strip_suffix(']'))
+        {
+            Some("") => bail!(
+                ErrorKind::ParsingError,
+                "Union < {} > has no variant",
+                port_type
+            ),
+            Some(union) => Ok(PortType::Union(
+                union
+                    .split('|')
+                    .map(DataType::from_str)
+                    .collect::<Result<Vec<_>>>()?,
+            )),
+            None => Ok(PortType::Single(port_type.parse()?)),
+        }
+    }
+}
+
+/// The properties of a data type, declared in the `types` registry of a data flow under its name
+/// (without its version).
+///
+/// - `compressed`: the data of that type are already compressed (e.g. JPEG images, H.264 frames),
+///   the connectors send them as they are (see
+///   [`CompressionDescriptor`](crate::model::descriptor::CompressionDescriptor)).
+///
+/// ```yaml
+/// types:
+///   camera.jpeg:
+///     compressed: true
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TypeDescriptor {
+    #[serde(default)]
+    pub compressed: bool,
+}
+
+/// The registry of the data types of a data flow: the properties of each type, by its name.
+pub type TypeRegistry = HashMap<String, TypeDescriptor>;
+
+#[cfg(test)]
+#[path = "./tests/datatype.rs"]
+mod tests;
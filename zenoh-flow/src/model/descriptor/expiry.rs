@@ -0,0 +1,117 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::utils::{
+    deserialize_duration, deserialize_required_duration, serialize_duration,
+    serialize_required_duration,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+/// The delay, before an instance expires, at which a warning is issued when no `warning` is set.
+pub const DEFAULT_EXPIRY_WARNING: Duration = Duration::from_secs(60);
+
+fn default_warning() -> Duration {
+    DEFAULT_EXPIRY_WARNING
+}
+
+/// When an instance of a data flow expires: the runtime that created it stops and deletes it, on
+/// all the runtimes involved.
+///
+/// - `ttl`: the instance expires that long after it was created.
+/// - `idle`: the instance expires once none of its nodes sent a message for that long.
+/// - `warning`: that long before the instance expires, a warning is logged and added to the event
+///   log (1 minute by default). An instance that is active again before expiring is not warned
+///   twice for the same idle period.
+///
+/// Expiring instances prevents forgotten flows, started for a test, from consuming the resources
+/// of the runtimes indefinitely. Without `ttl` nor `idle`, the instance never expires.
+///
+/// Example:
+///
+/// ```yaml
+/// expiry:
+///   ttl: 8h
+///   idle: 30min
+///   warning: 5min
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ExpiryDescriptor {
+    #[serde(default)]
+    #[serde(
+        deserialize_with = "deserialize_duration",
+        serialize_with = "serialize_duration",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub ttl: Option<Duration>,
+    #[serde(default)]
+    #[serde(
+        deserialize_with = "deserialize_duration",
+        serialize_with = "serialize_duration",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub idle: Option<Duration>,
+    #[serde(default = "default_warning")]
+    #[serde(
+        deserialize_with = "deserialize_required_duration",
+        serialize_with = "serialize_required_duration"
+    )]
+    pub warning: Duration,
+}
+
+/// Why an instance expires, see [ExpiryDescriptor].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpiryReason {
+    Ttl,
+    Idle,
+}
+
+impl fmt::Display for ExpiryReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExpiryReason::Ttl => write!(f, "ttl"),
+            ExpiryReason::Idle => write!(f, "idle"),
+        }
+    }
+}
+
+impl ExpiryDescriptor {
+    /// Returns the time left before an instance created `age` ago, whose nodes did not send any
+    /// message for `idle_time`, expires, and why. `None` is returned if it never expires.
+    ///
+    /// A time left of zero means that the instance expired.
+    pub fn remaining(
+        &self,
+        age: Duration,
+        idle_time: Duration,
+    ) -> Option<(Duration, ExpiryReason)> {
+        let ttl = self
+            .ttl
+            .map(|ttl| (ttl.saturating_sub(age), ExpiryReason::Ttl));
+        let idle = self
+            .idle
+            .map(|idle| (idle.saturating_sub(idle_time), ExpiryReason::Idle));
+
+        match (ttl, idle) {
+            (Some(ttl), Some(idle)) => Some(if idle.0 < ttl.0 { idle } else { ttl }),
+            (ttl, idle) => ttl.or(idle),
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/expiry.rs"]
+mod tests;
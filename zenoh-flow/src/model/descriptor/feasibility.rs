@@ -0,0 +1,524 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::model::descriptor::dataflow::replica_of;
+use crate::model::descriptor::{
+    FlattenDataFlowDescriptor, InputDescriptor, NodeUri, OperatorDescriptor, OutputDescriptor,
+    SamplingRate,
+};
+use crate::runtime::dataflow::instance::builtin::downsample::KEY_SAMPLE;
+use crate::types::NodeId;
+use crate::utils::{
+    deserialize_duration, deserialize_required_duration, serialize_duration,
+    serialize_required_duration,
+};
+use crate::zfresult::ErrorKind;
+use crate::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+/// The builtin operators inserted on the links when a data flow is flattened.
+static LINK_OPERATORS: [&str; 4] = [
+    "builtin://convert",
+    "builtin://downsample",
+    "builtin://faults",
+    "builtin://merge",
+];
+
+/// The timing characteristics of a data flow, from which the feasibility analysis predicts,
+/// before it is deployed, whether its nodes can keep up and its deadlines can be met (see
+/// [`FlattenDataFlowDescriptor::analyze_feasibility`]).
+///
+/// - `rates`: the number of messages per second the Sources send on each of their outputs.
+/// - `wcets`: the worst-case execution time of an iteration of the nodes. The measured ones, given
+///   to the analysis, take precedence.
+/// - `links`: the latency budget of links, i.e. the time a message takes to go through them.
+/// - `link_latency`: the latency of the links without a budget (0 by default).
+/// - `deadlines`: the maximum time between a message being sent by the node `from` and the node
+///   `to` being done processing it, or a message that results from it.
+///
+/// A rate or a WCET declared for a replicated node applies to each of its copies. The operators of
+/// a composite operator are named by their flattened id, e.g. `Composite/Operator`.
+///
+/// Example:
+///
+/// ```yaml
+/// feasibility:
+///   rates:
+///     - node: Camera
+///       rate: 30
+///   wcets:
+///     - node: Detector
+///       wcet: 20ms
+///     - node: Display
+///       wcet: 5ms
+///   links:
+///     - from:
+///         node: Camera
+///         output: Frame
+///       to:
+///         node: Detector
+///         input: Frame
+///       latency: 8ms
+///   link_latency: 1ms
+///   deadlines:
+///     - from: Camera
+///       to: Display
+///       deadline: 50ms
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct FeasibilityDescriptor {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rates: Vec<RateDescriptor>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wcets: Vec<WcetDescriptor>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<LinkBudgetDescriptor>,
+    #[serde(default)]
+    #[serde(
+        deserialize_with = "deserialize_duration",
+        serialize_with = "serialize_duration",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub link_latency: Option<Duration>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deadlines: Vec<DeadlineDescriptor>,
+}
+
+/// The number of messages per second a Source sends on each of its outputs.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RateDescriptor {
+    pub node: NodeId,
+    pub rate: f64,
+}
+
+/// The worst-case execution time of an iteration of a node, declared or measured.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct WcetDescriptor {
+    pub node: NodeId,
+    #[serde(
+        deserialize_with = "deserialize_required_duration",
+        serialize_with = "serialize_required_duration"
+    )]
+    pub wcet: Duration,
+}
+
+/// The latency budget of a link.
+///
+/// It applies to the link leading to the input `to`, whichever operators are inserted on it when
+/// the data flow is flattened (e.g. to sample its messages or to merge it with other links).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct LinkBudgetDescriptor {
+    pub from: OutputDescriptor,
+    pub to: InputDescriptor,
+    #[serde(
+        deserialize_with = "deserialize_required_duration",
+        serialize_with = "serialize_required_duration"
+    )]
+    pub latency: Duration,
+}
+
+/// An end-to-end deadline, between the nodes `from` and `to`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DeadlineDescriptor {
+    pub from: NodeId,
+    pub to: NodeId,
+    #[serde(
+        deserialize_with = "deserialize_required_duration",
+        serialize_with = "serialize_required_duration"
+    )]
+    pub deadline: Duration,
+}
+
+/// The load of a node: the number of messages it receives per second, its worst-case execution
+/// time, if known, and the resulting fraction of the time it is busy.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NodeLoad {
+    pub rate: f64,
+    pub wcet: Option<Duration>,
+    pub utilization: f64,
+}
+
+impl NodeLoad {
+    /// Returns `true` if the node receives messages faster than it can process them.
+    pub fn is_overloaded(&self) -> bool {
+        self.utilization > 1.0
+    }
+}
+
+/// The worst-case latency predicted for a deadline and the path of the flattened data flow along
+/// which it occurs, from `from` to `to`.
+///
+/// The latency is `None` if it is unbounded, i.e. if a node on the path is overloaded.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DeadlineReport {
+    pub deadline: DeadlineDescriptor,
+    pub latency: Option<Duration>,
+    pub path: Vec<NodeId>,
+}
+
+impl DeadlineReport {
+    /// Returns `true` if the worst-case latency does not exceed the deadline.
+    pub fn is_satisfiable(&self) -> bool {
+        self.latency
+            .map_or(false, |latency| latency <= self.deadline.deadline)
+    }
+}
+
+/// The outcome of the feasibility analysis of a data flow.
+///
+/// - `nodes`: the load of each node of the flattened data flow.
+/// - `bottleneck`: the node with the highest utilization, if any node has one.
+/// - `deadlines`: the worst-case latency predicted for each deadline.
+/// - `unrated`: the Sources without a rate, assumed to send no message.
+/// - `unprofiled`: the nodes without a WCET, assumed to take no time. The Sources and the
+///   operators inserted on the links are not listed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FeasibilityReport {
+    pub nodes: HashMap<NodeId, NodeLoad>,
+    pub bottleneck: Option<NodeId>,
+    pub deadlines: Vec<DeadlineReport>,
+    pub unrated: Vec<NodeId>,
+    pub unprofiled: Vec<NodeId>,
+}
+
+impl FeasibilityReport {
+    /// Returns `true` if no node is overloaded and all the deadlines are satisfiable.
+    pub fn is_feasible(&self) -> bool {
+        self.overloaded().next().is_none()
+            && self.deadlines.iter().all(DeadlineReport::is_satisfiable)
+    }
+
+    /// Returns the nodes that receive messages faster than they can process them.
+    pub fn overloaded(&self) -> impl Iterator<Item = (&NodeId, &NodeLoad)> {
+        self.nodes.iter().filter(|(_, load)| load.is_overloaded())
+    }
+
+    /// Returns the factor by which the rates of the Sources can be multiplied before the
+    /// bottleneck is overloaded, if there is one.
+    pub fn headroom(&self) -> Option<f64> {
+        let bottleneck = self.nodes.get(self.bottleneck.as_ref()?)?;
+        Some(1.0 / bottleneck.utilization)
+    }
+}
+
+/// Returns the fraction of the messages forwarded by an operator inserted on a link: the sampling
+/// rate of a Downsample, 1 for the others.
+fn forwarded_fraction(operator: &OperatorDescriptor) -> f64 {
+    if operator.uri != Some(NodeUri::from("builtin://downsample")) {
+        return 1.0;
+    }
+
+    operator
+        .configuration
+        .as_ref()
+        .and_then(|configuration| configuration.get(KEY_SAMPLE))
+        .and_then(|sample| sample.as_str())
+        .and_then(|sample| sample.parse::<SamplingRate>().ok())
+        .map_or(1.0, |sample| sample.keep as f64 / sample.every as f64)
+}
+
+/// Returns `true` if the worst-case latency `latency` is worse than `other`, `None` meaning
+/// unbounded.
+fn is_worse(latency: Option<Duration>, other: Option<Duration>) -> bool {
+    match (latency, other) {
+        (None, Some(_)) => true,
+        (Some(latency), Some(other)) => latency > other,
+        _ => false,
+    }
+}
+
+impl FlattenDataFlowDescriptor {
+    /// Analyzes, from the timing characteristics of its `feasibility` section (see
+    /// [FeasibilityDescriptor]), whether the data flow is feasible: whether its nodes can keep up
+    /// with the rates of the Sources and whether its deadlines can be met. The `measured` WCETs,
+    /// e.g. obtained by profiling the nodes, take precedence over the declared ones.
+    ///
+    /// The rates are propagated from the Sources: a node receives the sum of the rates of its
+    /// inputs and sends, on each of its outputs, as many messages as it receives. The sampling of a
+    /// link reduces its rate accordingly. The utilization of a node is its rate multiplied by its
+    /// WCET: above 1, the node is overloaded.
+    ///
+    /// The worst-case latency of a deadline is the longest path from `from` to `to`: the sum of
+    /// the WCETs of the nodes after `from`, `to` included, and of the latencies of the links. It
+    /// is unbounded if a node on the path is overloaded. The time the messages spend waiting for a
+    /// busy node is otherwise not accounted for: a deadline close to its predicted latency can
+    /// still be missed.
+    ///
+    ///  # Errors
+    /// A variant error is returned if the `feasibility` section names unknown nodes or links, if a
+    /// rate is not a positive number, if a rate is declared for a node that is not a Source, if no
+    /// path leads from the `from` to the `to` of a deadline or if the data flow contains loops.
+    pub fn analyze_feasibility(&self, measured: &[WcetDescriptor]) -> Result<FeasibilityReport> {
+        let feasibility = self.feasibility.clone().unwrap_or_default();
+        let ids = self.node_ids();
+
+        // The nodes a declaration applies to: the node itself or the copies of a replicated node.
+        let resolve = |node: &NodeId| -> Result<Vec<NodeId>> {
+            let members = ids
+                .iter()
+                .filter(|id| {
+                    *id == node
+                        || replica_of(&self.replicas, id)
+                            .map_or(false, |(original, _, _)| &original == node)
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            if members.is_empty() {
+                bail!(
+                    ErrorKind::ConfigurationError,
+                    "The feasibility analysis names the unknown node < {node} >"
+                )
+            }
+            Ok(members)
+        };
+
+        let mut rates = HashMap::new();
+        for RateDescriptor { node, rate } in &feasibility.rates {
+            if !rate.is_finite() || *rate < 0.0 {
+                bail!(
+                    ErrorKind::ConfigurationError,
+                    "The rate of < {node} > must be a positive number of messages per second, not < {rate} >"
+                )
+            }
+            for member in resolve(node)? {
+                if !self.sources.iter().any(|source| source.id == member) {
+                    bail!(
+                        ErrorKind::ConfigurationError,
+                        "A rate is declared for < {node} >, which is not a Source"
+                    )
+                }
+                rates.insert(member, *rate);
+            }
+        }
+
+        let mut wcets = HashMap::new();
+        for WcetDescriptor { node, wcet } in feasibility.wcets.iter().chain(measured) {
+            for member in resolve(node)? {
+                wcets.insert(member, *wcet);
+            }
+        }
+
+        let link_operators = self
+            .operators
+            .iter()
+            .filter(|operator| match &operator.uri {
+                Some(NodeUri::Uri(uri)) => LINK_OPERATORS.contains(&uri.as_str()),
+                _ => false,
+            })
+            .map(|operator| (operator.id.clone(), forwarded_fraction(operator)))
+            .collect::<HashMap<_, _>>();
+
+        // The latency of a link is accounted for on its last segment, the one leading to a node
+        // that is not inserted on it.
+        let mut budgets: Vec<Option<Duration>> = vec![None; self.links.len()];
+        for budget in &feasibility.links {
+            resolve(&budget.from.node)?;
+            let targets = resolve(&budget.to.node)?;
+            let mut found = false;
+            for (index, link) in self.links.iter().enumerate() {
+                if targets.contains(&link.to.node) && link.to.input == budget.to.input {
+                    budgets[index] = budgets[index].max(Some(budget.latency));
+                    found = true;
+                }
+            }
+            if !found {
+                bail!(
+                    ErrorKind::ConfigurationError,
+                    "The latency budget of the link {} => {} applies to no link",
+                    budget.from,
+                    budget.to
+                )
+            }
+        }
+        let latencies = self
+            .links
+            .iter()
+            .zip(budgets)
+            .map(|(link, budget)| {
+                if link_operators.contains_key(&link.to.node) {
+                    Duration::ZERO
+                } else {
+                    budget.or(feasibility.link_latency).unwrap_or_default()
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // Topological order of the nodes.
+        let mut incoming: HashMap<&NodeId, Vec<usize>> = HashMap::new();
+        let mut outgoing: HashMap<&NodeId, Vec<usize>> = HashMap::new();
+        for (index, link) in self.links.iter().enumerate() {
+            if ids.contains(&link.from.node) && ids.contains(&link.to.node) {
+                incoming.entry(&link.to.node).or_default().push(index);
+                outgoing.entry(&link.from.node).or_default().push(index);
+            }
+        }
+        let mut in_degrees = ids
+            .iter()
+            .map(|id| (id, incoming.get(id).map_or(0, Vec::len)))
+            .collect::<HashMap<_, _>>();
+        let mut ready = ids
+            .iter()
+            .filter(|id| in_degrees[id] == 0)
+            .collect::<VecDeque<_>>();
+        let mut order = Vec::with_capacity(ids.len());
+        while let Some(id) = ready.pop_front() {
+            order.push(id);
+            for index in outgoing.get(id).into_iter().flatten() {
+                let to = &self.links[*index].to.node;
+                if let Some(in_degree) = in_degrees.get_mut(to) {
+                    *in_degree -= 1;
+                    if *in_degree == 0 {
+                        ready.push_back(to);
+                    }
+                }
+            }
+        }
+        if order.len() != ids.len() {
+            bail!(
+                ErrorKind::ConfigurationError,
+                "The feasibility analysis requires a data flow without loops"
+            )
+        }
+
+        let mut unrated = Vec::new();
+        let mut unprofiled = Vec::new();
+        let mut nodes: HashMap<NodeId, NodeLoad> = HashMap::new();
+        for id in &order {
+            let rate = if self.sources.iter().any(|source| &source.id == *id) {
+                rates.get(*id).copied().unwrap_or_else(|| {
+                    unrated.push((*id).clone());
+                    0.0
+                })
+            } else {
+                if !link_operators.contains_key(*id) && !wcets.contains_key(*id) {
+                    unprofiled.push((*id).clone());
+                }
+                incoming
+                    .get(*id)
+                    .into_iter()
+                    .flatten()
+                    .map(|index| {
+                        let from = &self.links[*index].from.node;
+                        nodes.get(from).map_or(0.0, |load| load.rate)
+                            * link_operators.get(from).copied().unwrap_or(1.0)
+                    })
+                    .sum()
+            };
+            let wcet = wcets.get(*id).copied();
+            let utilization = rate * wcet.unwrap_or_default().as_secs_f64();
+            nodes.insert(
+                (*id).clone(),
+                NodeLoad {
+                    rate,
+                    wcet,
+                    utilization,
+                },
+            );
+        }
+
+        let bottleneck = nodes
+            .iter()
+            .filter(|(_, load)| load.utilization > 0.0)
+            .max_by(|(id, load), (other_id, other)| {
+                load.utilization
+                    .partial_cmp(&other.utilization)
+                    .unwrap_or(Ordering::Equal)
+                    .then_with(|| other_id.cmp(id))
+            })
+            .map(|(id, _)| id.clone());
+
+        let mut deadlines = Vec::with_capacity(feasibility.deadlines.len());
+        for deadline in &feasibility.deadlines {
+            let targets = resolve(&deadline.to)?;
+            let mut worst: Option<(Option<Duration>, Vec<NodeId>)> = None;
+
+            for from in resolve(&deadline.from)? {
+                // The worst-case latency from `from` to each node, and the link it arrives from.
+                let mut paths: HashMap<&NodeId, (Option<Duration>, Option<usize>)> =
+                    HashMap::from([(&from, (Some(Duration::ZERO), None))]);
+                for id in order.iter().skip_while(|id| ***id != from).skip(1) {
+                    let load = &nodes[*id];
+                    for index in incoming.get(*id).into_iter().flatten() {
+                        let previous = match paths.get(&self.links[*index].from.node) {
+                            Some((latency, _)) => *latency,
+                            None => continue,
+                        };
+                        let latency = if load.is_overloaded() {
+                            None
+                        } else {
+                            previous.map(|previous| {
+                                previous + latencies[*index] + load.wcet.unwrap_or_default()
+                            })
+                        };
+                        if paths
+                            .get(*id)
+                            .map_or(true, |(current, _)| is_worse(latency, *current))
+                        {
+                            paths.insert(*id, (latency, Some(*index)));
+                        }
+                    }
+                }
+
+                for to in &targets {
+                    let latency = match paths.get(to) {
+                        Some((latency, _)) if to != &from => *latency,
+                        _ => continue,
+                    };
+                    if worst
+                        .as_ref()
+                        .map_or(false, |(current, _)| !is_worse(latency, *current))
+                    {
+                        continue;
+                    }
+
+                    let mut path = vec![to.clone()];
+                    while let Some((_, Some(index))) = paths.get(path.last().unwrap()) {
+                        path.push(self.links[*index].from.node.clone());
+                    }
+                    path.reverse();
+                    worst = Some((latency, path));
+                }
+            }
+
+            match worst {
+                Some((latency, path)) => deadlines.push(DeadlineReport {
+                    deadline: deadline.clone(),
+                    latency,
+                    path,
+                }),
+                None => bail!(
+                    ErrorKind::ConfigurationError,
+                    "No path leads from < {} > to < {} >, their deadline cannot be analyzed",
+                    deadline.from,
+                    deadline.to
+                ),
+            }
+        }
+
+        Ok(FeasibilityReport {
+            nodes,
+            bottleneck,
+            deadlines,
+            unrated,
+            unprofiled,
+        })
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/feasibility.rs"]
+mod tests;
@@ -0,0 +1,135 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::types::NodeId;
+use crate::zfresult::ErrorKind;
+use crate::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+/// The GPUs a node needs, assigned by the runtime it runs on among the devices it declares (see
+/// [`GpuInventory`](crate::runtime::gpu::GpuInventory)).
+///
+/// The short form requests a number of devices, any of them:
+///
+/// ```yaml
+/// gpu: 1
+/// ```
+///
+/// The long form can request a specific device and share it with other nodes:
+///
+/// ```yaml
+/// gpu:
+///   device: 0
+///   exclusive: false
+/// ```
+///
+/// A node has, by default, an exclusive use of its devices: no other node, of any instance, is
+/// assigned them while it runs. A shared device can be assigned to several nodes that do not
+/// require an exclusive use.
+///
+/// The devices assigned to a node are given in its configuration, under the key `gpus`, as a list
+/// of device indexes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "RawGpuDescriptor")]
+pub struct GpuDescriptor {
+    pub count: usize,
+    pub device: Option<u32>,
+    pub exclusive: bool,
+}
+
+impl Default for GpuDescriptor {
+    fn default() -> Self {
+        Self {
+            count: 1,
+            device: None,
+            exclusive: true,
+        }
+    }
+}
+
+impl GpuDescriptor {
+    /// Checks that the descriptor of the GPUs of the node `node_id` requests at least one device,
+    /// and only one when a specific device is requested.
+    ///
+    /// # Errors
+    ///
+    /// An error variant is returned if the request is invalid.
+    pub fn validate(&self, node_id: &NodeId) -> Result<()> {
+        if self.count == 0 {
+            bail!(
+                ErrorKind::ConfigurationError,
+                "The node < {} > requests 0 GPU, remove its `gpu` instead",
+                node_id
+            )
+        }
+
+        if self.device.is_some() && self.count != 1 {
+            bail!(
+                ErrorKind::ConfigurationError,
+                "The node < {} > requests {} GPUs and a specific device, only one can be requested then",
+                node_id,
+                self.count
+            )
+        }
+
+        Ok(())
+    }
+}
+
+/// The short (a number of devices) and long forms of a [GpuDescriptor].
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawGpuDescriptor {
+    Count(usize),
+    Full {
+        #[serde(default = "default_count")]
+        count: usize,
+        #[serde(default)]
+        device: Option<u32>,
+        #[serde(default = "default_exclusive")]
+        exclusive: bool,
+    },
+}
+
+fn default_count() -> usize {
+    1
+}
+
+fn default_exclusive() -> bool {
+    true
+}
+
+impl From<RawGpuDescriptor> for GpuDescriptor {
+    fn from(raw: RawGpuDescriptor) -> Self {
+        match raw {
+            RawGpuDescriptor::Count(count) => Self {
+                count,
+                ..Default::default()
+            },
+            RawGpuDescriptor::Full {
+                count,
+                device,
+                exclusive,
+            } => Self {
+                count,
+                device,
+                exclusive,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/gpu.rs"]
+mod tests;
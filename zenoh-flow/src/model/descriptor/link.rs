@@ -13,8 +13,16 @@
 //
 
 use crate::types::{NodeId, PortId};
-use crate::utils::{deserialize_size, deserialize_time};
+use crate::utils::{
+    deserialize_duration, deserialize_size, deserialize_time, serialize_duration, serialize_size,
+};
+use crate::zfresult::{ErrorKind, ZFError};
+use crate::{bail, zferror, Result};
 use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
 use std::{fmt, sync::Arc};
 
 /// The description of a link.
@@ -29,8 +37,57 @@ use std::{fmt, sync::Arc};
 /// to:
 ///   node : SumOperator
 ///   input : Number
+/// sample: 1/10 # optional, only forward 1 message out of 10
+/// faults:       # optional, see `FaultsDescriptor`
+///   delay: 10ms
+///   drop: 0.01
+/// queue:        # optional, see `QueueDescriptor`
+///   capacity: 8
+///   overflow: drop-oldest
+/// capacity: 256       # optional, see below
+/// retransmission: 128 # optional, see below
+/// outage_budget: 1MiB  # optional, see below
+/// spool:               # optional, see `SpoolDescriptor`
+///   directory: /var/spool/zenoh-flow
+///   max_size: 1GiB
+///   max_age: 1day
+/// fragment_size: 64KiB # optional, see below
+/// session: robots      # optional, see below
 ///
 /// ```
+///
+/// The messages sent through Zenoh, between nodes running on different daemons, are numbered: the
+/// receiving daemon detects and reports the messages that were lost (see
+/// [`DataFlowInstance::missed_messages`](crate::runtime::dataflow::instance::DataFlowInstance::missed_messages)).
+/// With `retransmission`, the sending daemon keeps that many of the last messages it sent such
+/// that the lost ones can be requested again, and delivered in order.
+///
+/// With `outage_budget`, the sending daemon buffers the messages (up to that many bytes, the oldest
+/// being dropped beyond) while it is not connected to any other Zenoh peer or router, or while the
+/// publications fail, instead of failing. The transmission resumes, in order, once the
+/// connectivity returns.
+///
+/// With `spool`, the sending daemon writes these messages to disk instead of keeping them in
+/// memory, such that a node disconnected for hours (for instance a field robot out of range) does
+/// not lose what it produced: they are forwarded once it is connected again.
+///
+/// With `fragment_size`, the messages are split into fragments of at most that many bytes before
+/// being published, and reassembled by the receiving daemon --- for instance for raw camera frames
+/// larger than what Zenoh, or the network, can carry in a single sample. The messages that are not
+/// complete within a timeout are discarded, and reported as lost. The links from the same output
+/// must all set the same `fragment_size`.
+///
+/// With `capacity`, the link holds at most that many messages: the upstream node waits for the
+/// downstream node to receive one before sending more. When the link is point-to-point --- the only
+/// link of its output and the only link leading to its input --- between two nodes running on the
+/// same daemon, its messages go through a lock-free ring buffer instead of the general channel,
+/// cutting the latency of each message. The links of a flow controlled Source are bounded by its
+/// credits instead, and the links with a `queue` by the capacity of the queue.
+///
+/// With `session`, the messages are sent through the Zenoh session of that name (for instance, to
+/// reach a dedicated router) instead of the session of the daemon. The daemons involved open it
+/// with their own configuration or, if they have none, with the one described in the `sessions` of
+/// the data flow (see [DataFlowDescriptor](crate::model::descriptor::DataFlowDescriptor)).
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct LinkDescriptor {
     pub from: OutputDescriptor,
@@ -42,6 +99,28 @@ pub struct LinkDescriptor {
     #[serde(default)]
     #[serde(deserialize_with = "deserialize_time")]
     pub shared_memory_backoff: Option<u64>,
+    #[serde(default)]
+    pub sample: Option<SamplingRate>,
+    #[serde(default)]
+    pub faults: Option<FaultsDescriptor>,
+    #[serde(default)]
+    pub merge: Option<MergeOrdering>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queue: Option<QueueDescriptor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retransmission: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(deserialize_with = "deserialize_size")]
+    pub outage_budget: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spool: Option<SpoolDescriptor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(deserialize_with = "deserialize_size")]
+    pub fragment_size: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
 }
 
 impl std::fmt::Display for LinkDescriptor {
@@ -58,7 +137,299 @@ impl LinkDescriptor {
             shared_memory_element_size: None,
             shared_memory_elements: None,
             shared_memory_backoff: None,
+            sample: None,
+            faults: None,
+            merge: None,
+            queue: None,
+            capacity: None,
+            retransmission: None,
+            outage_budget: None,
+            spool: None,
+            fragment_size: None,
+            session: None,
+        }
+    }
+}
+
+/// A sampling rate, expressed as `<keep>/<every>`: out of every `<every>` messages only the first
+/// `<keep>` are forwarded.
+///
+/// Watermarks are not affected by the sampling.
+///
+/// Example:
+///
+/// ```yaml
+/// sample: 1/10
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub struct SamplingRate {
+    pub keep: u64,
+    pub every: u64,
+}
+
+impl SamplingRate {
+    /// Returns `true` if the message received at position `index` (starting at 0) should be
+    /// forwarded.
+    pub fn keeps(&self, index: u64) -> bool {
+        index % self.every < self.keep
+    }
+}
+
+impl fmt::Display for SamplingRate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.keep, self.every)
+    }
+}
+
+impl FromStr for SamplingRate {
+    type Err = ZFError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (keep, every) = s.split_once('/').ok_or_else(|| {
+            zferror!(
+                ErrorKind::ParsingError,
+                "Sampling rate < {s} > does not follow the format <keep>/<every>"
+            )
+        })?;
+
+        let parse = |value: &str| {
+            value.trim().parse::<u64>().map_err(|e| {
+                zferror!(
+                    ErrorKind::ParsingError,
+                    "Unable to parse sampling rate < {s} >: {e}"
+                )
+            })
+        };
+        let (keep, every) = (parse(keep)?, parse(every)?);
+
+        if keep == 0 || keep > every {
+            bail!(
+                ErrorKind::ParsingError,
+                "Sampling rate < {s} > must satisfy 0 < <keep> <= <every>"
+            )
+        }
+
+        Ok(Self { keep, every })
+    }
+}
+
+impl TryFrom<String> for SamplingRate {
+    type Error = ZFError;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        Self::from_str(&value)
+    }
+}
+
+impl From<SamplingRate> for String {
+    fn from(rate: SamplingRate) -> Self {
+        rate.to_string()
+    }
+}
+
+/// The bounded queue of a link, to protect a downstream node that cannot keep up.
+///
+/// When the queue holds `capacity` messages (at least 1), sending a new data message drops a data
+/// message according to the `overflow` policy:
+/// - `drop-newest`: the new message is dropped (default),
+/// - `drop-oldest`: the oldest data message in the queue is dropped, the new one is queued.
+///
+/// Watermarks, end of streams and control markers are never dropped: they evict the oldest data
+/// message in the queue instead or, if the queue only holds such messages, wait for a slot. A new
+/// data message is then dropped, whatever the policy.
+///
+/// The dropped messages are counted (see
+/// [`DataFlowInstance::dropped_messages`](crate::runtime::dataflow::instance::DataFlowInstance::dropped_messages))
+/// and reported to the upstream node (see [`Node::on_drop`](crate::traits::Node::on_drop)), for
+/// instance to reduce the rate or the size of what it produces.
+///
+/// The queue only applies between nodes running on the same daemon and is ignored on the links of
+/// a flow controlled Source (see [`NodeDescriptor`](crate::model::descriptor::NodeDescriptor)).
+///
+/// Example:
+///
+/// ```yaml
+/// queue:
+///   capacity: 8
+///   overflow: drop-oldest
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct QueueDescriptor {
+    pub capacity: usize,
+    #[serde(default)]
+    pub overflow: OverflowPolicy,
+}
+
+impl QueueDescriptor {
+    /// Checks that the queue can hold at least one message.
+    ///
+    /// # Errors
+    ///
+    /// A `ConfigurationError` is returned if the `capacity` is 0.
+    pub fn validate(&self) -> Result<()> {
+        if self.capacity == 0 {
+            bail!(
+                ErrorKind::ConfigurationError,
+                "The capacity of a queue must be at least 1"
+            );
         }
+
+        Ok(())
+    }
+}
+
+/// The message dropped when the queue of a link is full, see [QueueDescriptor].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum OverflowPolicy {
+    #[default]
+    DropNewest,
+    DropOldest,
+}
+
+/// The spool of a link, on disk, in which the sending daemon stores the messages it cannot publish
+/// while it is disconnected, and from which it forwards them, in order, on reconnection.
+///
+/// - `directory`: the directory in which each sender creates its own spool (named after it),
+///   removed when the instance is stopped.
+/// - `max_size`: when the spooled messages exceed that size, the oldest ones are dropped.
+/// - `max_age`: the spooled messages older than that are dropped instead of being forwarded.
+///
+/// The dropped messages are detected as lost by the receivers (and retransmitted, if they are still
+/// kept for it). Without limits, the spool grows for as long as the outage lasts.
+///
+/// Example:
+///
+/// ```yaml
+/// spool:
+///   directory: /var/spool/zenoh-flow
+///   max_size: 1GiB
+///   max_age: 1day
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SpoolDescriptor {
+    pub directory: PathBuf,
+    #[serde(default)]
+    #[serde(
+        deserialize_with = "deserialize_size",
+        serialize_with = "serialize_size",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_size: Option<usize>,
+    #[serde(default)]
+    #[serde(
+        deserialize_with = "deserialize_duration",
+        serialize_with = "serialize_duration",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_age: Option<Duration>,
+}
+
+/// The order in which the messages received on an input fed by several outputs are processed.
+///
+/// - `arrival`: the messages are processed as they arrive (default),
+/// - `timestamp`: the messages are processed in the order of their timestamps. A builtin Merge
+///   operator is inserted in front of the input: it only forwards a message once all the upstream
+///   outputs have sent a message (or a watermark) with a greater timestamp.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum MergeOrdering {
+    #[default]
+    Arrival,
+    Timestamp,
+}
+
+/// The faults to inject on a link, to test the behavior of a data flow under degraded network
+/// conditions.
+///
+/// Each data message going through the link is, in order:
+/// 1. dropped with the probability `drop`;
+/// 2. delayed by `delay` plus a random duration between 0 and `jitter`;
+/// 3. held back and forwarded after the next message with the probability `reorder`;
+/// 4. forwarded twice with the probability `duplicate`.
+///
+/// Watermarks are not affected by the faults.
+///
+/// The faults can be updated while the data flow is running by publishing a new `FaultsDescriptor`,
+/// serialized in JSON, on the key expression `zenoh-flow/faults/<instance id>/<link>` where `<link>`
+/// is `<from.node>/<from.output>/<to.node>/<to.input>`.
+///
+/// Example:
+///
+/// ```yaml
+/// faults:
+///   delay: 50ms
+///   jitter: 10ms
+///   drop: 0.05
+///   duplicate: 0.01
+///   reorder: 0.01
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct FaultsDescriptor {
+    #[serde(default)]
+    #[serde(
+        deserialize_with = "deserialize_duration",
+        serialize_with = "serialize_duration",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub delay: Option<Duration>,
+    #[serde(default)]
+    #[serde(
+        deserialize_with = "deserialize_duration",
+        serialize_with = "serialize_duration",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub jitter: Option<Duration>,
+    #[serde(default)]
+    pub drop: Probability,
+    #[serde(default)]
+    pub duplicate: Probability,
+    #[serde(default)]
+    pub reorder: Probability,
+}
+
+/// A probability, between 0 and 1.
+///
+/// It is checked when it is created, or deserialized: NaN, in particular, is rejected.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+#[serde(try_from = "f64", into = "f64")]
+pub struct Probability(f64);
+
+// NaN, the only value not equal to itself, cannot be stored.
+impl Eq for Probability {}
+
+impl Probability {
+    /// Returns the probability, between 0 and 1.
+    pub fn value(&self) -> f64 {
+        self.0
+    }
+}
+
+impl fmt::Display for Probability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl TryFrom<f64> for Probability {
+    type Error = ZFError;
+
+    fn try_from(probability: f64) -> std::result::Result<Self, Self::Error> {
+        if !(0.0..=1.0).contains(&probability) {
+            bail!(
+                ErrorKind::ConfigurationError,
+                "The probability < {probability} > must be between 0 and 1"
+            )
+        }
+
+        Ok(Self(probability))
+    }
+}
+
+impl From<Probability> for f64 {
+    fn from(probability: Probability) -> Self {
+        probability.0
     }
 }
 
@@ -71,7 +442,7 @@ impl LinkDescriptor {
 /// output : Counter
 /// ```
 ///
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct OutputDescriptor {
     pub node: NodeId,
     pub output: PortId,
@@ -127,7 +498,7 @@ impl From<CompositeOutputDescriptor> for OutputDescriptor {
 /// node : SumOperator
 /// input : Number
 /// ```
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
 pub struct InputDescriptor {
     pub node: NodeId,
     pub input: PortId,
@@ -0,0 +1,120 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::zfresult::ErrorKind;
+use crate::{bail, zferror, Result};
+use serde_json::{Map, Value};
+
+/// The version of the data flow descriptor format produced by this version of Zenoh-Flow.
+///
+/// A descriptor without `version` is considered to be in version 1.
+///
+/// - Version 1: the initial format.
+/// - Version 2: the `configuration` of the data flow is renamed `global_configuration`.
+pub const DESCRIPTOR_VERSION: u64 = 2;
+
+/// Key of the version in a data flow descriptor.
+static KEY_VERSION: &str = "version";
+
+/// A migration upgrades a descriptor from one version to the next.
+type Migration = fn(&mut Map<String, Value>) -> Result<()>;
+
+/// The migrations, indexed by the version they upgrade from minus one.
+static MIGRATIONS: [Migration; (DESCRIPTOR_VERSION - 1) as usize] = [migrate_v1_to_v2];
+
+/// The fields still accepted in the current version but that will be removed, and their
+/// replacement.
+static DEPRECATED_FIELDS: [(&str, &str); 1] = [("configuration", "global_configuration")];
+
+/// Returns the version of the `descriptor`.
+fn get_version(descriptor: &Map<String, Value>) -> Result<u64> {
+    match descriptor.get(KEY_VERSION) {
+        Some(value) => match value.as_u64() {
+            Some(version) if version > 0 => Ok(version),
+            _ => bail!(
+                ErrorKind::ParsingError,
+                "The {KEY_VERSION} of a descriptor must be a strictly positive integer, found: {:?}",
+                value
+            ),
+        },
+        None => Ok(1),
+    }
+}
+
+/// Upgrades, in place, the `descriptor` of a data flow to the current [DESCRIPTOR_VERSION],
+/// applying in order the migrations of all the versions in between, and warns about the deprecated
+/// fields it contains.
+///
+/// # Errors
+///
+/// An error is returned if the descriptor is not an object, if its version is invalid or more
+/// recent than the current one, or if a migration failed.
+pub(crate) fn migrate(descriptor: &mut Value) -> Result<()> {
+    let descriptor = descriptor.as_object_mut().ok_or_else(|| {
+        zferror!(
+            ErrorKind::ParsingError,
+            "A data flow descriptor must be a map, found: {:?}",
+            descriptor
+        )
+    })?;
+
+    let version = get_version(descriptor)?;
+    if version > DESCRIPTOR_VERSION {
+        bail!(
+            ErrorKind::VersionMismatch,
+            "The descriptor is in version {version} but this version of Zenoh-Flow only supports up to version {DESCRIPTOR_VERSION}"
+        )
+    }
+
+    for from in version..DESCRIPTOR_VERSION {
+        log::debug!(
+            "Migrating descriptor from version {from} to version {}",
+            from + 1
+        );
+        (MIGRATIONS[(from - 1) as usize])(descriptor)?;
+    }
+
+    for (field, replacement) in DEPRECATED_FIELDS.iter() {
+        if descriptor.contains_key(*field) {
+            log::warn!("The field `{field}` is deprecated, please use `{replacement}` instead");
+        }
+    }
+
+    descriptor.insert(KEY_VERSION.into(), DESCRIPTOR_VERSION.into());
+    Ok(())
+}
+
+/// Renames the field `from` to `to`, failing if both are present.
+fn rename_field(descriptor: &mut Map<String, Value>, from: &str, to: &str) -> Result<()> {
+    if let Some(value) = descriptor.remove(from) {
+        if descriptor.contains_key(to) {
+            bail!(
+                ErrorKind::ParsingError,
+                "Both `{from}` and `{to}` are set, only `{to}` should be"
+            )
+        }
+        descriptor.insert(to.into(), value);
+    }
+
+    Ok(())
+}
+
+/// Version 2 renamed the `configuration` of the data flow `global_configuration`.
+fn migrate_v1_to_v2(descriptor: &mut Map<String, Value>) -> Result<()> {
+    rename_field(descriptor, "configuration", "global_configuration")
+}
+
+#[cfg(test)]
+#[path = "./tests/migration.rs"]
+mod tests;
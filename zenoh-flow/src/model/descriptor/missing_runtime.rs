@@ -0,0 +1,64 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::utils::{deserialize_required_duration, serialize_required_duration};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+/// What a runtime does when it creates an instance whose `mapping` names runtimes that are not
+/// running:
+///
+/// - `fail`: the instance is not created, the error lists the missing runtimes (default),
+/// - `wait`: the runtimes are waited for, at most the given duration, before failing,
+/// - `reschedule`: the nodes mapped to the missing runtimes are placed on the runtimes available,
+///   as if they were not mapped --- honouring the `affinity` rules,
+/// - `attach`: the instance is created without the nodes mapped to the missing runtimes; they are
+///   deployed, and started if the instance is running, once their runtime joins (e.g. a robot
+///   docking). Their connectors only carry data from then on.
+///
+/// Example:
+///
+/// ```yaml
+/// mapping:
+///   Camera: robot
+/// missing_runtime:
+///   wait: 30s
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MissingRuntimePolicy {
+    #[default]
+    Fail,
+    Wait(
+        #[serde(
+            deserialize_with = "deserialize_required_duration",
+            serialize_with = "serialize_required_duration"
+        )]
+        Duration,
+    ),
+    Reschedule,
+    Attach,
+}
+
+impl fmt::Display for MissingRuntimePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MissingRuntimePolicy::Fail => write!(f, "fail"),
+            MissingRuntimePolicy::Wait(timeout) => write!(f, "wait {timeout:?}"),
+            MissingRuntimePolicy::Reschedule => write!(f, "reschedule"),
+            MissingRuntimePolicy::Attach => write!(f, "attach"),
+        }
+    }
+}
@@ -12,18 +12,56 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+pub mod affinity;
+pub use affinity::AffinityRule;
+pub mod autoscaling;
+pub use autoscaling::AutoscalingDescriptor;
+pub mod compression;
+pub use compression::CompressionDescriptor;
 pub mod dataflow;
 pub use dataflow::{DataFlowDescriptor, FlattenDataFlowDescriptor};
+pub mod datatype;
+pub use datatype::{DataType, PortType, TypeDescriptor, TypeRegistry};
+pub mod expiry;
+pub use expiry::{ExpiryDescriptor, ExpiryReason};
+pub mod feasibility;
+pub use feasibility::{
+    DeadlineDescriptor, DeadlineReport, FeasibilityDescriptor, FeasibilityReport,
+    LinkBudgetDescriptor, NodeLoad, RateDescriptor, WcetDescriptor,
+};
+pub mod gpu;
+pub use gpu::GpuDescriptor;
 pub mod link;
+pub mod migration;
 pub use link::{
-    CompositeInputDescriptor, CompositeOutputDescriptor, InputDescriptor, LinkDescriptor,
-    OutputDescriptor,
+    CompositeInputDescriptor, CompositeOutputDescriptor, FaultsDescriptor, InputDescriptor,
+    LinkDescriptor, MergeOrdering, OutputDescriptor, OverflowPolicy, Probability, QueueDescriptor,
+    SamplingRate, SpoolDescriptor,
 };
+pub use migration::DESCRIPTOR_VERSION;
+pub mod missing_runtime;
+pub use missing_runtime::MissingRuntimePolicy;
 pub mod node;
 pub use node::{
-    CompositeOperatorDescriptor, NodeDescriptor, OperatorDescriptor, SinkDescriptor,
-    SourceDescriptor,
+    CompositeOperatorDescriptor, InputPolicyDescriptor, NodeDescriptor, OperatorDescriptor,
+    SinkDescriptor, SourceDescriptor, TokenAction, WarmupDescriptor, WatchdogDescriptor,
 };
+pub mod priority;
+pub use priority::PreemptionPolicy;
+pub mod readiness;
+pub use readiness::{ReadinessCheck, ReadinessDescriptor, ReadinessFailure};
+pub mod redaction;
+pub use redaction::RedactionDescriptor;
+pub mod retention;
+pub use retention::RetentionDescriptor;
+pub mod strict;
+pub use strict::ParsingMode;
+pub mod transport;
+pub use transport::{TlsDescriptor, TransportDescriptor};
+pub mod unit;
+pub use unit::{Unit, UnitConversion};
+pub mod uri;
+pub use uri::NodeUri;
 pub mod validator;
 
 use crate::zfresult::{ErrorKind, ZFResult as Result};
@@ -21,17 +21,28 @@ pub use sink::SinkDescriptor;
 pub mod source;
 pub use source::SourceDescriptor;
 
-use crate::model::descriptor::{LinkDescriptor, Vars};
+use crate::model::descriptor::{AutoscalingDescriptor, GpuDescriptor, LinkDescriptor, Vars};
 use crate::model::{Middleware, ZFUri};
+use crate::runtime::dataflow::instance::builtin::get_builtin_operator_descriptor;
+use crate::runtime::dataflow::instance::builtin::host::{
+    get_host_sink_descriptor, get_host_source_descriptor,
+};
+use crate::runtime::dataflow::instance::builtin::http::{
+    get_http_sink_descriptor, get_http_source_descriptor,
+};
 use crate::runtime::dataflow::instance::builtin::zenoh::{
     get_zenoh_sink_descriptor, get_zenoh_source_descriptor,
 };
 use crate::types::configuration::Merge;
-use crate::types::{Configuration, NodeId};
-use crate::utils::parse_uri;
+use crate::types::{Configuration, NodeId, PortId};
+use crate::utils::{
+    deserialize_duration, deserialize_required_duration, parse_uri, serialize_duration,
+    serialize_required_duration,
+};
 use crate::zfresult::ErrorKind;
 use crate::{bail, zferror, Result};
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 /// Describes an node of the graph
 ///
@@ -40,12 +51,202 @@ use serde::{Deserialize, Serialize};
 /// descriptor: file://./target/release/counter_source.yaml
 /// configuration:
 ///   start: 10
+/// max_run_duration: 500ms # optional, see below
+/// credits: 16             # optional, Sources only, see below
+/// replicas: 4             # optional, see below
+/// autoscaling:            # optional, see below
+///   min: 1
+///   max: 8
+/// warmup:                 # optional, see below
+///   duration: 2s
+///   activations: 10
+/// cooldown: 1s            # optional, see below
+/// watchdog:               # optional, see below
+///   deadline: 10s
+///   restarts: 3
+/// exposed: [Objects]      # optional, see below
+/// imported: [Frame]       # optional, see below
+/// ```
+///
+/// If a `max_run_duration` is set, an iteration of the node that takes longer is interrupted and
+/// treated as an error. For a composite operator, it applies to all the operators it contains.
+///
+/// If `credits` are set on a Source, it is flow controlled: each of its links holds at most that
+/// many messages and the Source is only iterated when all its downstream nodes can accept at least
+/// one more (see [`Node::on_demand`](crate::traits::Node::on_demand)).
+///
+/// If `replicas` are set, the node is copied that many times when the data flow is flattened, see
+/// [`DataFlowDescriptor`](crate::model::descriptor::DataFlowDescriptor). If an `autoscaling` is also
+/// set on an Operator, its number of copies then follows its load (see [AutoscalingDescriptor]).
+///
+/// If a `warmup` is set, the data sent by the node after it is started are discarded until the
+/// warm-up is over (see [WarmupDescriptor]). If a `cooldown` is set, `clean` is only called on the
+/// node once that grace period has elapsed after the node was stopped. For a composite operator,
+/// both apply to all the operators it contains.
 ///
+/// If a `gpu` is set, the node is assigned that many GPUs by the runtime it runs on (see
+/// [GpuDescriptor]). For a composite operator, each operator it contains is assigned its own.
+///
+/// If a `watchdog` is set, an iteration of the node that neither completes nor signals its progress
+/// within the deadline is considered hung and restarted, as is an iteration that panics (see
+/// [WatchdogDescriptor]). For a
+/// composite operator, it applies to all the operators it contains.
+///
+/// The `exposed` outputs are published on Zenoh, under the key expression generated by
+/// [`EXPOSED_PATH`](crate::EXPOSED_PATH), for external applications to subscribe to them. An
+/// exposed output does not need to be connected to another node.
+///
+/// The `imported` inputs can be connected, while the instance runs, to an exposed output of another
+/// instance (see
+/// [`DataFlowInstance::connect_import`](crate::runtime::dataflow::instance::DataFlowInstance::connect_import)).
+/// An imported input does not need to be connected to another node.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct NodeDescriptor {
     pub id: NodeId,
     pub descriptor: String,
     pub configuration: Option<Configuration>,
+    #[serde(default)]
+    #[serde(
+        deserialize_with = "deserialize_duration",
+        serialize_with = "serialize_duration",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_run_duration: Option<Duration>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credits: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replicas: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub autoscaling: Option<AutoscalingDescriptor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warmup: Option<WarmupDescriptor>,
+    #[serde(default)]
+    #[serde(
+        deserialize_with = "deserialize_duration",
+        serialize_with = "serialize_duration",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub cooldown: Option<Duration>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gpu: Option<GpuDescriptor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub watchdog: Option<WatchdogDescriptor>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exposed: Vec<PortId>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub imported: Vec<PortId>,
+}
+
+/// The warm-up of a node: the data it sends are discarded, for instance while a model or a filter
+/// stabilizes, until `duration` has elapsed since it was started and it completed `activations`
+/// iterations. When only one of them is set, the warm-up is over as soon as it is reached.
+///
+/// Watermarks and end of streams are not discarded.
+///
+/// ```yaml
+/// warmup:
+///   duration: 2s
+///   activations: 10
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WarmupDescriptor {
+    #[serde(default)]
+    #[serde(
+        deserialize_with = "deserialize_duration",
+        serialize_with = "serialize_duration",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub duration: Option<Duration>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activations: Option<usize>,
+}
+
+/// The watchdog of a node: an iteration that neither completes nor signals its progress, through
+/// [`Context::heartbeat`](crate::types::Context::heartbeat), for `deadline` is considered hung.
+/// It is then interrupted and the node is iterated again. An iteration exceeding the deadline while
+/// signalling its progress is only reported as slow.
+///
+/// An iteration that panics is restarted the same way. A node that hangs or panics again after
+/// `restarts` consecutive restarts (3 by default) is stopped with a `NodeHung`, respectively
+/// `NodePanic`, error instead, as a circuit breaker: it can then be restarted explicitly. Without a
+/// watchdog, a node is stopped as soon as it panics.
+///
+/// The deadline applies to the whole iteration, including the time spent waiting for inputs: it
+/// should exceed the interval at which the node receives data.
+///
+/// ```yaml
+/// watchdog:
+///   deadline: 10s
+///   restarts: 3
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchdogDescriptor {
+    #[serde(
+        deserialize_with = "deserialize_required_duration",
+        serialize_with = "serialize_required_duration"
+    )]
+    pub deadline: Duration,
+    #[serde(default = "default_restarts")]
+    pub restarts: usize,
+}
+
+fn default_restarts() -> usize {
+    3
+}
+
+/// How an input of an Operator or a Sink is considered by the input rule of an
+/// [`InputSet`](crate::io::InputSet).
+///
+/// An input that is not `required` does not block the
+/// [`default_input_rule`](crate::io::default_input_rule): the node is triggered on its other inputs
+/// alone and receives the data of this input whenever there are some --- for instance calibration
+/// updates.
+///
+/// The `token` tells what becomes of the data of the input once they were given to the node (see
+/// [TokenAction]).
+///
+/// ```yaml
+/// inputs: [Frame, Calibration]
+/// input_policies:
+///   Calibration:
+///     required: false
+///     token: keep
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputPolicyDescriptor {
+    #[serde(default = "default_required")]
+    pub required: bool,
+    #[serde(default)]
+    pub token: TokenAction,
+}
+
+impl Default for InputPolicyDescriptor {
+    fn default() -> Self {
+        Self {
+            required: true,
+            token: TokenAction::default(),
+        }
+    }
+}
+
+/// What becomes of the token of an input, i.e. the data it received, once the input rule of an
+/// [`InputSet`](crate::io::InputSet) triggered the node:
+///
+/// - `consume`: the data are given to the node and the input waits for new data (default),
+/// - `keep`: the data are given to the node and kept for its next iterations, until new data
+///   replace them --- for instance to reuse the last value of a slowly changing input,
+/// - `drop`: the data are discarded without being given to the node, the input only triggers it.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenAction {
+    #[default]
+    Consume,
+    Keep,
+    Drop,
+}
+
+fn default_required() -> bool {
+    true
 }
 
 impl std::fmt::Display for NodeDescriptor {
@@ -111,9 +312,18 @@ impl NodeDescriptor {
         log::trace!("[Descriptor] loading operator {}", self.id);
         let descriptor = match parse_uri(&self.descriptor)? {
             crate::model::ZFUri::File(path) => try_load_descriptor_from_file(path).await,
-            crate::model::ZFUri::Builtin(_) => bail!(
+            crate::model::ZFUri::Builtin(mw) => bail!(
+                ErrorKind::ConfigurationError,
+                "Builtin < {} > cannot be used as an operator",
+                mw.to_string()
+            ),
+            crate::model::ZFUri::BuiltinOperator(operator) => {
+                get_builtin_operator_descriptor(&operator, global_configuration.as_ref())?.to_yaml()
+            }
+            crate::model::ZFUri::Remote(url) => bail!(
                 ErrorKind::ConfigurationError,
-                "Builtin operators are not yet supported!"
+                "Remote descriptor < {} > is not supported, only remote libraries are",
+                url
             ),
         }?;
 
@@ -213,7 +423,47 @@ impl NodeDescriptor {
                         )
                     }
                 },
+                Middleware::Http => match &self.configuration {
+                    Some(configuration) => {
+                        let mut desc = get_http_source_descriptor(configuration)?;
+                        desc.id = self.id;
+                        desc.configuration =
+                            global_configuration.merge_overwrite(desc.configuration);
+                        Ok(desc)
+                    }
+                    None => {
+                        bail!(
+                            ErrorKind::MissingConfiguration,
+                            "Builtin HTTP Source needs a configuration!"
+                        )
+                    }
+                },
+                Middleware::Host => match &self.configuration {
+                    Some(configuration) => {
+                        let mut desc = get_host_source_descriptor(configuration)?;
+                        desc.id = self.id;
+                        desc.configuration =
+                            global_configuration.merge_overwrite(desc.configuration);
+                        Ok(desc)
+                    }
+                    None => {
+                        bail!(
+                            ErrorKind::MissingConfiguration,
+                            "Builtin Host Source needs a configuration!"
+                        )
+                    }
+                },
             },
+            ZFUri::BuiltinOperator(operator) => bail!(
+                ErrorKind::ConfigurationError,
+                "Builtin operator < {} > cannot be used as a Source",
+                operator.to_string()
+            ),
+            ZFUri::Remote(url) => bail!(
+                ErrorKind::ConfigurationError,
+                "Remote descriptor < {} > is not supported, only remote libraries are",
+                url
+            ),
         }
     }
 
@@ -262,7 +512,47 @@ impl NodeDescriptor {
                         )
                     }
                 },
+                Middleware::Http => match &self.configuration {
+                    Some(configuration) => {
+                        let mut desc = get_http_sink_descriptor(configuration)?;
+                        desc.id = self.id;
+                        desc.configuration =
+                            global_configuration.merge_overwrite(desc.configuration);
+                        Ok(desc)
+                    }
+                    None => {
+                        bail!(
+                            ErrorKind::MissingConfiguration,
+                            "Builtin HTTP Sink needs a configuration!"
+                        )
+                    }
+                },
+                Middleware::Host => match &self.configuration {
+                    Some(configuration) => {
+                        let mut desc = get_host_sink_descriptor(configuration)?;
+                        desc.id = self.id;
+                        desc.configuration =
+                            global_configuration.merge_overwrite(desc.configuration);
+                        Ok(desc)
+                    }
+                    None => {
+                        bail!(
+                            ErrorKind::MissingConfiguration,
+                            "Builtin Host Sink needs a configuration!"
+                        )
+                    }
+                },
             },
+            ZFUri::BuiltinOperator(operator) => bail!(
+                ErrorKind::ConfigurationError,
+                "Builtin operator < {} > cannot be used as a Sink",
+                operator.to_string()
+            ),
+            ZFUri::Remote(url) => bail!(
+                ErrorKind::ConfigurationError,
+                "Remote descriptor < {} > is not supported, only remote libraries are",
+                url
+            ),
         }
     }
 }
@@ -277,6 +567,6 @@ impl NodeDescriptor {
 /// - The provided `descriptor_path` is incorrect, i.e. the file does not exists.
 /// - The content of the file could not be read.
 async fn try_load_descriptor_from_file(descriptor_path: PathBuf) -> Result<String> {
-    let data = async_std::fs::read_to_string(&descriptor_path).await?;
+    let data = crate::executor::fs::read_to_string(&descriptor_path).await?;
     Vars::expand_mustache_yaml(&data)
 }
@@ -13,16 +13,18 @@
 //
 
 use crate::model::descriptor::link::{CompositeInputDescriptor, CompositeOutputDescriptor};
-use crate::model::descriptor::node::{try_load_descriptor_from_file, NodeDescriptor};
-use crate::model::descriptor::LinkDescriptor;
+use crate::model::descriptor::node::{
+    try_load_descriptor_from_file, InputPolicyDescriptor, NodeDescriptor,
+};
+use crate::model::descriptor::{LinkDescriptor, NodeUri, PortType, Unit};
 use crate::prelude::PortId;
+use crate::runtime::dataflow::instance::builtin::get_builtin_operator_descriptor;
 use crate::types::configuration::Merge;
 use crate::types::{Configuration, NodeId};
 use crate::utils::parse_uri;
 use crate::zfresult::{ErrorKind, ZFResult as Result};
 use crate::{bail, zferror};
 use async_recursion::async_recursion;
-use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -38,14 +40,46 @@ use std::collections::HashMap;
 ///   by: 10
 /// inputs: [Number]
 /// outputs: [Multiplied]
+/// side_outputs: [Rejected]
 /// ```
 ///
+/// The `side_outputs`, optional, are outputs that may be left unconnected --- for instance to
+/// report rejected records or debug information. The operator obtains them with
+/// [`Outputs::take_side`](crate::io::Outputs::take_side): messages sent on an unconnected side
+/// output are silently discarded.
+///
+/// The `input_policies`, optional, tell how each input is considered by the input rule of an
+/// [`InputSet`](crate::io::InputSet) (see [`InputPolicyDescriptor`]): an input that is not
+/// `required` does not block the operator.
+///
+/// The `input_types` and `output_types`, optional, declare the versioned
+/// [`DataType`](crate::model::descriptor::DataType) of the ports, or the tagged union of data types they carry (see [`PortType`]): the ports they connect
+/// must be compatible.
+///
+/// The `input_units` and `output_units`, optional, declare the physical [`Unit`] of the values
+/// carried by the ports: the ports they connect must have the same unit, or a unit that can be
+/// converted.
+///
+/// The `uri` can also be given per architecture, for a data flow deployed on a mixed fleet (see
+/// [`NodeUri`]).
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct OperatorDescriptor {
     pub id: NodeId,
     pub inputs: Vec<PortId>,
     pub outputs: Vec<PortId>,
-    pub uri: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub side_outputs: Vec<PortId>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub input_policies: HashMap<PortId, InputPolicyDescriptor>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub input_types: HashMap<PortId, PortType>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub input_units: HashMap<PortId, Unit>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub output_types: HashMap<PortId, PortType>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub output_units: HashMap<PortId, Unit>,
+    pub uri: Option<NodeUri>,
     pub configuration: Option<Configuration>,
 }
 
@@ -207,9 +241,23 @@ impl CompositeOperatorDescriptor {
         for o in self.operators {
             let description = match parse_uri(&o.descriptor)? {
                 crate::model::ZFUri::File(path) => try_load_descriptor_from_file(path).await,
-                crate::model::ZFUri::Builtin(_) => bail!(
+                crate::model::ZFUri::Builtin(mw) => bail!(
                     ErrorKind::ConfigurationError,
-                    "Builtin operators are not yet supported!"
+                    "Builtin < {} > cannot be used as an operator",
+                    mw.to_string()
+                ),
+                crate::model::ZFUri::BuiltinOperator(operator) => get_builtin_operator_descriptor(
+                    &operator,
+                    self.configuration
+                        .clone()
+                        .merge_overwrite(o.configuration.clone())
+                        .as_ref(),
+                )?
+                .to_yaml(),
+                crate::model::ZFUri::Remote(url) => bail!(
+                    ErrorKind::ConfigurationError,
+                    "Remote descriptor < {} > is not supported, only remote libraries are",
+                    url
                 ),
             }?;
 
@@ -217,8 +265,60 @@ impl CompositeOperatorDescriptor {
                 id: operator_id,
                 descriptor,
                 configuration,
+                max_run_duration,
+                credits,
+                replicas,
+                autoscaling,
+                warmup,
+                cooldown,
+                gpu,
+                watchdog,
+                exposed,
+                imported,
             } = o;
 
+            if max_run_duration.is_some() {
+                log::warn!(
+                    "[Descriptor] Ignoring the `max_run_duration` of < {operator_id} > in the composite operator < {composite_id} >, set it on the composite operator instead"
+                );
+            }
+
+            if credits.is_some() {
+                log::warn!(
+                    "[Descriptor] Ignoring the `credits` of < {operator_id} > in the composite operator < {composite_id} >, only Sources are flow controlled"
+                );
+            }
+
+            if replicas.is_some() || autoscaling.is_some() {
+                log::warn!(
+                    "[Descriptor] Ignoring the `replicas` and `autoscaling` of < {operator_id} > in the composite operator < {composite_id} >, only the nodes of a data flow can be replicated"
+                );
+            }
+
+            if warmup.is_some() || cooldown.is_some() {
+                log::warn!(
+                    "[Descriptor] Ignoring the `warmup` and `cooldown` of < {operator_id} > in the composite operator < {composite_id} >, set them on the composite operator instead"
+                );
+            }
+
+            if gpu.is_some() {
+                log::warn!(
+                    "[Descriptor] Ignoring the `gpu` of < {operator_id} > in the composite operator < {composite_id} >, set it on the composite operator instead"
+                );
+            }
+
+            if watchdog.is_some() {
+                log::warn!(
+                    "[Descriptor] Ignoring the `watchdog` of < {operator_id} > in the composite operator < {composite_id} >, set it on the composite operator instead"
+                );
+            }
+
+            if !exposed.is_empty() || !imported.is_empty() {
+                log::warn!(
+                    "[Descriptor] Ignoring the `exposed` outputs and `imported` inputs of < {operator_id} > in the composite operator < {composite_id} >, declare them on the composite operator instead"
+                );
+            }
+
             let configuration = self.configuration.clone().merge_overwrite(configuration);
 
             let res_simple = OperatorDescriptor::from_yaml(&description);
@@ -258,23 +358,22 @@ impl CompositeOperatorDescriptor {
 
                 links
                     .iter_mut()
-                    .filter(|link| {
-                        link.from.node == composite_id
-                            && output_ids.keys().contains(&&link.from.output)
-                    })
+                    .filter(|link| link.from.node == composite_id)
                     .for_each(|link| {
-                        link.from.node = new_id.clone();
-                        link.from.output = (*output_ids.get(&&link.from.output).unwrap()).clone();
+                        if let Some(&output) = output_ids.get(&&link.from.output) {
+                            link.from.node = new_id.clone();
+                            link.from.output = output.clone();
+                        }
                     });
 
                 links
                     .iter_mut()
-                    .filter(|link| {
-                        link.to.node == composite_id && input_ids.keys().contains(&&link.to.input)
-                    })
+                    .filter(|link| link.to.node == composite_id)
                     .for_each(|link| {
-                        link.to.node = new_id.clone();
-                        link.to.input = (*input_ids.get(&&link.to.input).unwrap()).clone();
+                        if let Some(&input) = input_ids.get(&&link.to.input) {
+                            link.to.node = new_id.clone();
+                            link.to.input = input.clone();
+                        }
                     });
 
                 // Updating the new id
@@ -12,11 +12,14 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+use crate::model::descriptor::node::InputPolicyDescriptor;
+use crate::model::descriptor::{NodeUri, PortType, Unit};
 use crate::prelude::PortId;
 use crate::types::{Configuration, NodeId};
 use crate::zferror;
 use crate::zfresult::{ErrorKind, ZFResult as Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Describes a sink.
 ///
@@ -31,11 +34,28 @@ use serde::{Deserialize, Serialize};
 /// inputs: [Data]
 /// ```
 ///
+/// The `input_policies`, optional, tell how each input is considered by the input rule of an
+/// [`InputSet`](crate::io::InputSet) (see [`InputPolicyDescriptor`]).
+///
+/// The `input_types`, optional, declare the versioned
+/// [`DataType`](crate::model::descriptor::DataType) expected on the inputs, or the tagged union of
+/// data types they expect (see [`PortType`]).
+///
+/// The `input_units`, optional, declare the physical [`Unit`] of the values expected on the inputs.
+///
+/// The `uri` can also be given per architecture, for a data flow deployed on a mixed fleet (see
+/// [`NodeUri`]).
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct SinkDescriptor {
     pub id: NodeId,
     pub inputs: Vec<PortId>,
-    pub uri: Option<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub input_policies: HashMap<PortId, InputPolicyDescriptor>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub input_types: HashMap<PortId, PortType>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub input_units: HashMap<PortId, Unit>,
+    pub uri: Option<NodeUri>,
     pub configuration: Option<Configuration>,
 }
 
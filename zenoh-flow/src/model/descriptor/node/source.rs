@@ -12,11 +12,13 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+use crate::model::descriptor::{NodeUri, PortType, Unit};
 use crate::prelude::PortId;
 use crate::types::{Configuration, NodeId};
 use crate::zferror;
 use crate::zfresult::{ErrorKind, ZFResult as Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Describes a source.
 ///
@@ -29,13 +31,27 @@ use serde::{Deserialize, Serialize};
 /// configuration:
 ///   start: 10
 /// outputs: [Counter]
+/// output_types:
+///   Counter: my.company.Counter@1.0
 /// ```
 ///
+/// The `output_types`, optional, declare the versioned
+/// [`DataType`](crate::model::descriptor::DataType) of the data sent on the outputs, or the tagged
+/// union of data types they send (see [`PortType`]).
+///
+/// The `output_units`, optional, declare the physical [`Unit`] of the values sent on the outputs.
+///
+/// The `uri` can also be given per architecture, for a data flow deployed on a mixed fleet (see
+/// [`NodeUri`]).
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct SourceDescriptor {
     pub id: NodeId,
     pub outputs: Vec<PortId>,
-    pub uri: Option<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub output_types: HashMap<PortId, PortType>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub output_units: HashMap<PortId, Unit>,
+    pub uri: Option<NodeUri>,
     pub configuration: Option<Configuration>,
 }
 
@@ -0,0 +1,49 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// What a runtime may do to an instance when an instance with a higher `priority` cannot be created
+/// because this one holds the resources it needs (e.g. GPUs):
+///
+/// - `never`: the instance keeps running, the other instance is not created,
+/// - `stop`: the instance is stopped and deleted,
+/// - `migrate`: the instance is stopped and its nodes running on that runtime are moved to another
+///   runtime, where it is started again.
+///
+/// Example:
+///
+/// ```yaml
+/// priority: 1
+/// preemption: migrate
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreemptionPolicy {
+    #[default]
+    Never,
+    Stop,
+    Migrate,
+}
+
+impl fmt::Display for PreemptionPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PreemptionPolicy::Never => write!(f, "never"),
+            PreemptionPolicy::Stop => write!(f, "stop"),
+            PreemptionPolicy::Migrate => write!(f, "migrate"),
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::utils::{deserialize_duration, serialize_duration};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+/// The default time given to the readiness checks to pass.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+/// The default delay between two evaluations of the readiness checks.
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The readiness checks of a data flow: the external services the data flow depends on.
+///
+/// The Sources of the data flow only start once all the checks pass. The checks are evaluated
+/// every `interval` (default: 1s) until they all pass or until `timeout` (default: 30s) expires.
+/// What happens then is set by `on_failure` (see [ReadinessFailure]).
+///
+/// Example:
+///
+/// ```yaml
+/// readiness:
+///   timeout: 1min
+///   interval: 500ms
+///   on_failure: abort
+///   checks:
+///     - zenoh: robot/lidar/calibration
+///     - tcp: localhost:5432
+///     - file: /dev/video0
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReadinessDescriptor {
+    #[serde(default)]
+    #[serde(
+        deserialize_with = "deserialize_duration",
+        serialize_with = "serialize_duration",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub timeout: Option<Duration>,
+    #[serde(default)]
+    #[serde(
+        deserialize_with = "deserialize_duration",
+        serialize_with = "serialize_duration",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub interval: Option<Duration>,
+    #[serde(default)]
+    pub on_failure: ReadinessFailure,
+    pub checks: Vec<ReadinessCheck>,
+}
+
+impl ReadinessDescriptor {
+    /// Returns the time given to the readiness checks to pass.
+    pub fn timeout(&self) -> Duration {
+        self.timeout.unwrap_or(DEFAULT_TIMEOUT)
+    }
+
+    /// Returns the delay between two evaluations of the readiness checks.
+    pub fn interval(&self) -> Duration {
+        self.interval.unwrap_or(DEFAULT_INTERVAL)
+    }
+}
+
+/// A check that must pass before the Sources of a data flow start, see [ReadinessDescriptor].
+///
+/// - `zenoh`: a value can be retrieved, on Zenoh, for the key expression,
+/// - `tcp`: a TCP connection can be established with the address (`host:port`),
+/// - `file`: the file exists on the daemon running the Sources.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReadinessCheck {
+    Zenoh(String),
+    Tcp(String),
+    File(String),
+}
+
+impl fmt::Display for ReadinessCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadinessCheck::Zenoh(key_expr) => write!(f, "zenoh: {key_expr}"),
+            ReadinessCheck::Tcp(address) => write!(f, "tcp: {address}"),
+            ReadinessCheck::File(path) => write!(f, "file: {path}"),
+        }
+    }
+}
+
+/// What happens when the readiness checks did not all pass before the timeout expired.
+///
+/// - `abort`: the Sources are not started and an error is returned (default),
+/// - `proceed`: a warning is logged and the Sources are started anyway.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReadinessFailure {
+    #[default]
+    Abort,
+    Proceed,
+}
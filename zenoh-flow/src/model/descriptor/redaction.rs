@@ -0,0 +1,56 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::model::descriptor::{NodeUri, OutputDescriptor};
+use serde::{Deserialize, Serialize};
+
+/// The redaction rule of an output: how the payloads of the messages sent on it are altered before
+/// they leave the data flow for debugging purposes, i.e. when they are published by a debug tap or
+/// stored by a recording.
+///
+/// - `transform` is the URI of a shared library exporting, with `export_transform`, a function
+///   applied to the payloads first (e.g. to blur the faces in an image or to strip a GPS position).
+///   Like the URI of a node, it can differ per architecture. A payload the transform fails on is
+///   entirely removed. Only shared libraries are supported: WASM modules cannot be loaded.
+/// - `mask` lists the fields of the payloads to hide, as JSON pointers (e.g. `/user/email`): their
+///   value is replaced by `"<redacted>"`. A payload that is not JSON cannot be masked: it is
+///   entirely removed.
+/// - `max_bytes` truncates the payloads, after they were transformed and masked, to that many bytes.
+///
+/// The messages received by the nodes are not altered.
+///
+/// Example:
+///
+/// ```yaml
+/// redaction:
+///   - output:
+///       node: Gateway
+///       output: Request
+///     mask: [/user/email, /payment/card]
+///     max_bytes: 256
+///   - output:
+///       node: Camera
+///       output: Frame
+///     transform: file:///opt/transforms/libblur_faces.so
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RedactionDescriptor {
+    pub output: OutputDescriptor,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transform: Option<NodeUri>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mask: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_bytes: Option<usize>,
+}
@@ -0,0 +1,52 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::utils::{deserialize_duration, deserialize_size, serialize_duration, serialize_size};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// The retention policy of the data produced by an instance of a data flow: its recordings and
+/// its entries in the event log.
+///
+/// - `max_age`: the recording entries and the events older than that are deleted.
+/// - `max_size`: when the recording entries stored by an instance on a daemon exceed that size,
+///   the oldest ones are deleted.
+///
+/// The policy is enforced, by each daemon, for the data produced by the part of the instance it
+/// runs and for as long as the instance exists. Without retention policy, the data are kept.
+///
+/// Example:
+///
+/// ```yaml
+/// retention:
+///   max_age: 7days
+///   max_size: 10GB
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct RetentionDescriptor {
+    #[serde(default)]
+    #[serde(
+        deserialize_with = "deserialize_duration",
+        serialize_with = "serialize_duration",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_age: Option<Duration>,
+    #[serde(default)]
+    #[serde(
+        deserialize_with = "deserialize_size",
+        serialize_with = "serialize_size",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_size: Option<usize>,
+}
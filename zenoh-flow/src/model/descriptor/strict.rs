@@ -0,0 +1,257 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::zfresult::ErrorKind;
+use crate::{bail, Result};
+use serde_json::Value;
+
+/// How the fields of a data flow descriptor that Zenoh-Flow does not know are handled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ParsingMode {
+    /// Unknown fields are ignored, after a warning is logged. This allows loading descriptors
+    /// written for a more recent version of Zenoh-Flow.
+    #[default]
+    Permissive,
+    /// Unknown fields are rejected. This catches typos such as `confguration:`.
+    Strict,
+}
+
+/// The fields of a data flow descriptor.
+static DATA_FLOW_FIELDS: [&str; 24] = [
+    "version",
+    "vars",
+    "flow",
+    "operators",
+    "sources",
+    "sinks",
+    "links",
+    "mapping",
+    "missing_runtime",
+    "affinity",
+    "global_configuration",
+    "configuration",
+    "readiness",
+    "priority",
+    "preemption",
+    "idle_timeout",
+    "expiry",
+    "max_clock_skew",
+    "redaction",
+    "retention",
+    "compression",
+    "types",
+    "sessions",
+    "feasibility",
+];
+
+/// The fields of the description of a node in a data flow descriptor.
+static NODE_FIELDS: [&str; 13] = [
+    "id",
+    "descriptor",
+    "configuration",
+    "max_run_duration",
+    "credits",
+    "replicas",
+    "autoscaling",
+    "warmup",
+    "cooldown",
+    "gpu",
+    "watchdog",
+    "exposed",
+    "imported",
+];
+
+/// The fields of a link.
+static LINK_FIELDS: [&str; 15] = [
+    "from",
+    "to",
+    "shared_memory_element_size",
+    "shared_memory_elements",
+    "shared_memory_backoff",
+    "sample",
+    "faults",
+    "merge",
+    "queue",
+    "capacity",
+    "retransmission",
+    "outage_budget",
+    "spool",
+    "fragment_size",
+    "session",
+];
+
+/// The fields of the output a link starts from.
+static OUTPUT_FIELDS: [&str; 2] = ["node", "output"];
+
+/// The fields of the input a link leads to.
+static INPUT_FIELDS: [&str; 2] = ["node", "input"];
+
+/// The fields of the faults injected on a link.
+static FAULTS_FIELDS: [&str; 5] = ["delay", "jitter", "drop", "duplicate", "reorder"];
+
+/// The fields of the spool of a link.
+static SPOOL_FIELDS: [&str; 3] = ["directory", "max_size", "max_age"];
+
+/// An unknown field: its path in the descriptor (e.g. `sources[0].confguration`) and, if the
+/// descriptor was written in YAML, the line on which it appears.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct UnknownField {
+    pub(crate) path: String,
+    pub(crate) line: Option<usize>,
+}
+
+impl std::fmt::Display for UnknownField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "`{}` (line {})", self.path, line),
+            None => write!(f, "`{}`", self.path),
+        }
+    }
+}
+
+/// Returns the paths of the fields of `value`, an object located at `path`, that are not `known`.
+fn unknown_fields(value: &Value, path: &str, known: &[&str], unknown: &mut Vec<String>) {
+    if let Some(object) = value.as_object() {
+        for key in object.keys() {
+            if !known.contains(&key.as_str()) {
+                unknown.push(format!("{path}{key}"));
+            }
+        }
+    }
+}
+
+/// Returns the paths of the fields of the elements of the list `key` of `descriptor` that are not
+/// `known`.
+fn unknown_fields_in_list(
+    descriptor: &Value,
+    key: &str,
+    known: &[&str],
+    unknown: &mut Vec<String>,
+) -> Vec<(String, Value)> {
+    let mut elements = Vec::new();
+    if let Some(list) = descriptor.get(key).and_then(|list| list.as_array()) {
+        for (index, element) in list.iter().enumerate() {
+            let path = format!("{key}[{index}].");
+            unknown_fields(element, &path, known, unknown);
+            elements.push((path, element.clone()));
+        }
+    }
+
+    elements
+}
+
+/// Returns the line, starting at 1, on which the last key of `path` is declared in the `yaml`.
+///
+/// The line is found by looking for the key followed by a colon, after the line of its parent
+/// key: it is an indication and could be wrong in convoluted descriptors.
+fn locate(yaml: &str, path: &str) -> Option<usize> {
+    let mut start = 0;
+    let keys = path
+        .split('.')
+        .map(|key| key.split('[').next().unwrap_or(key))
+        .collect::<Vec<_>>();
+
+    for key in keys {
+        let pattern = format!("{key}:");
+        let offset = yaml.lines().skip(start).position(|line| {
+            line.trim_start()
+                .trim_start_matches("- ")
+                .trim_start()
+                .starts_with(&pattern)
+        })?;
+        start += offset;
+    }
+
+    Some(start + 1)
+}
+
+/// Returns the fields of the data flow `descriptor` that Zenoh-Flow does not know. If the
+/// descriptor was written in `yaml`, the line on which each field appears is provided.
+///
+/// The configurations are free-form and are not checked.
+pub(crate) fn find_unknown_fields(descriptor: &Value, yaml: Option<&str>) -> Vec<UnknownField> {
+    let mut unknown = Vec::new();
+    unknown_fields(descriptor, "", &DATA_FLOW_FIELDS, &mut unknown);
+
+    for key in ["sources", "operators", "sinks"] {
+        unknown_fields_in_list(descriptor, key, &NODE_FIELDS, &mut unknown);
+    }
+
+    for (path, link) in unknown_fields_in_list(descriptor, "links", &LINK_FIELDS, &mut unknown) {
+        for (key, known) in [("from", &OUTPUT_FIELDS), ("to", &INPUT_FIELDS)] {
+            match link.get(key) {
+                // A link can have a list of endpoints.
+                Some(Value::Array(endpoints)) => {
+                    for (index, endpoint) in endpoints.iter().enumerate() {
+                        let path = format!("{path}{key}[{index}].");
+                        unknown_fields(endpoint, &path, known, &mut unknown);
+                    }
+                }
+                Some(endpoint) => {
+                    unknown_fields(endpoint, &format!("{path}{key}."), known, &mut unknown)
+                }
+                None => (),
+            }
+        }
+
+        for (key, known) in [("faults", &FAULTS_FIELDS[..]), ("spool", &SPOOL_FIELDS[..])] {
+            if let Some(value) = link.get(key) {
+                unknown_fields(value, &format!("{path}{key}."), known, &mut unknown);
+            }
+        }
+    }
+
+    unknown
+        .into_iter()
+        .map(|path| UnknownField {
+            line: yaml.and_then(|yaml| locate(yaml, &path)),
+            path,
+        })
+        .collect()
+}
+
+/// Checks the fields of the data flow `descriptor` according to the `mode`: in strict mode an
+/// error listing all the unknown fields is returned, in permissive mode a warning is logged for
+/// each of them.
+pub(crate) fn check_fields(
+    descriptor: &Value,
+    yaml: Option<&str>,
+    mode: ParsingMode,
+) -> Result<()> {
+    let unknown = find_unknown_fields(descriptor, yaml);
+    if unknown.is_empty() {
+        return Ok(());
+    }
+
+    let fields = unknown
+        .iter()
+        .map(|field| field.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    match mode {
+        ParsingMode::Permissive => {
+            log::warn!("Ignoring unknown field(s) in the descriptor: {fields}");
+            Ok(())
+        }
+        ParsingMode::Strict => bail!(
+            ErrorKind::ParsingError,
+            "Unknown field(s) in the descriptor: {fields}"
+        ),
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/strict.rs"]
+mod tests;
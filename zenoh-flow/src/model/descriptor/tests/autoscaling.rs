@@ -0,0 +1,67 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::{AutoscalingDescriptor, DEFAULT_AUTOSCALING_COOLDOWN};
+use std::time::Duration;
+
+#[test]
+fn test_autoscaling_descriptor() {
+    let autoscaling: AutoscalingDescriptor = serde_yaml::from_str(
+        r#"
+min: 1
+max: 8
+queue: 50
+interval: 500ms
+"#,
+    )
+    .unwrap();
+    assert_eq!(autoscaling.queue(), 50);
+    assert_eq!(autoscaling.interval(), Duration::from_millis(500));
+    assert_eq!(autoscaling.cooldown(), DEFAULT_AUTOSCALING_COOLDOWN);
+
+    let id = "detector".into();
+    assert!(autoscaling.validate(&id, Some(2)).is_ok());
+    assert!(autoscaling.validate(&id, None).is_err());
+    assert!(autoscaling.validate(&id, Some(9)).is_err());
+    let inverted = AutoscalingDescriptor {
+        min: 4,
+        max: 2,
+        ..autoscaling.clone()
+    };
+    assert!(inverted.validate(&id, Some(3)).is_err());
+}
+
+#[test]
+fn test_autoscaling_desired_replicas() {
+    let autoscaling = AutoscalingDescriptor {
+        min: 1,
+        max: 8,
+        queue: Some(50),
+        interval: None,
+        cooldown: None,
+    };
+
+    // Within the bounds: the number of copies does not change.
+    assert_eq!(autoscaling.desired_replicas(2, 100), 2);
+    assert_eq!(autoscaling.desired_replicas(2, 25), 2);
+    // Copies are added to bring the messages waiting per copy below the queue...
+    assert_eq!(autoscaling.desired_replicas(2, 101), 3);
+    assert_eq!(autoscaling.desired_replicas(2, 320), 7);
+    // ... up to the maximum.
+    assert_eq!(autoscaling.desired_replicas(2, 10_000), 8);
+    // A copy is removed at a time when one less would leave them below half of the queue.
+    assert_eq!(autoscaling.desired_replicas(3, 49), 2);
+    assert_eq!(autoscaling.desired_replicas(3, 0), 2);
+    assert_eq!(autoscaling.desired_replicas(1, 0), 1);
+}
@@ -0,0 +1,49 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::{CompressionDescriptor, DEFAULT_COMPRESSION_MIN_SIZE};
+use crate::model::descriptor::{DataType, TypeRegistry};
+
+#[test]
+fn test_compression_descriptor() {
+    let compression: CompressionDescriptor = serde_yaml::from_str("min_size: 4KiB").unwrap();
+    assert_eq!(compression.min_size(), 4 * 1024);
+    assert_eq!(
+        CompressionDescriptor::default().min_size(),
+        DEFAULT_COMPRESSION_MIN_SIZE
+    );
+
+    let types: TypeRegistry = serde_yaml::from_str(
+        r#"
+camera.jpeg:
+  compressed: true
+lidar.draco:
+  compressed: true
+camera.raw: {}
+"#,
+    )
+    .unwrap();
+
+    let data_type = |name: &str| -> DataType { format!("{}@1.0", name).parse().unwrap() };
+
+    // Raw buffers, untyped outputs and types absent from the registry are compressed.
+    assert!(compression.applies_to(None, &types));
+    assert!(compression.applies_to(Some(&data_type("camera.raw")), &types));
+    assert!(compression.applies_to(Some(&data_type("PointCloud")), &types));
+
+    // Data declared as already compressed are not.
+    assert!(!compression.applies_to(Some(&data_type("camera.jpeg")), &types));
+    assert!(!compression.applies_to(Some(&data_type("lidar.draco")), &types));
+    assert!(compression.applies_to(Some(&data_type("lidar.draco")), &TypeRegistry::new()));
+}
@@ -0,0 +1,104 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::{DataType, PortType};
+use std::collections::HashMap;
+
+#[test]
+fn test_parse_data_type() {
+    let data_type = "my.company.Detection@1.2".parse::<DataType>().unwrap();
+    assert_eq!(data_type.name, "my.company.Detection");
+    assert_eq!(
+        (data_type.major, data_type.minor, data_type.patch),
+        (1, 2, 0)
+    );
+    assert_eq!(data_type.to_string(), "my.company.Detection@1.2");
+
+    let data_type = "Detection@0.3.1".parse::<DataType>().unwrap();
+    assert_eq!(
+        (data_type.major, data_type.minor, data_type.patch),
+        (0, 3, 1)
+    );
+    assert_eq!(data_type.to_string(), "Detection@0.3.1");
+
+    assert!("my.company.Detection".parse::<DataType>().is_err());
+    assert!("@1.2".parse::<DataType>().is_err());
+    assert!("Detection@1".parse::<DataType>().is_err());
+    assert!("Detection@1.2.3.4".parse::<DataType>().is_err());
+    assert!("Detection@1.x".parse::<DataType>().is_err());
+}
+
+#[test]
+fn test_compatible_data_types() {
+    let detection = |version: &str| {
+        format!("my.company.Detection@{version}")
+            .parse::<DataType>()
+            .unwrap()
+    };
+
+    assert!(detection("1.2").is_compatible_with(&detection("1.0")));
+    assert!(detection("1.0").is_compatible_with(&detection("1.2.5")));
+    assert!(!detection("2.0").is_compatible_with(&detection("1.2")));
+    assert!(
+        !detection("1.2").is_compatible_with(&"my.company.Image@1.2".parse::<DataType>().unwrap())
+    );
+}
+
+#[test]
+fn test_deserialize_data_types() {
+    let types: HashMap<String, DataType> =
+        serde_yaml::from_str("Detections: my.company.Detection@1.2\n").unwrap();
+    assert_eq!(types["Detections"].major, 1);
+    assert!(serde_yaml::to_string(&types)
+        .unwrap()
+        .contains("Detections: my.company.Detection@1.2"));
+
+    assert!(serde_yaml::from_str::<HashMap<String, DataType>>("Detections: Detection\n").is_err());
+}
+
+#[test]
+fn test_port_types() {
+    let types: HashMap<String, PortType> = serde_yaml::from_str(
+        r#"
+Frame: my.company.Image@2.0
+Reading: [my.company.Temperature@1.0, my.company.Humidity@1.1]
+"#,
+    )
+    .unwrap();
+    assert_eq!(types["Frame"].variants().len(), 1);
+    assert!(matches!(types["Frame"], PortType::Single(_)));
+    let reading = &types["Reading"];
+    assert_eq!(reading.variants()[1].name, "my.company.Humidity");
+    assert_eq!(
+        reading.to_string(),
+        "[my.company.Temperature@1.0|my.company.Humidity@1.1]"
+    );
+    assert_eq!(&reading.to_string().parse::<PortType>().unwrap(), reading);
+    assert_eq!(
+        "my.company.Image@2.0".parse::<PortType>().unwrap(),
+        types["Frame"]
+    );
+    assert!("[]".parse::<PortType>().is_err());
+    assert!("[my.company.Image]".parse::<PortType>().is_err());
+
+    let union = |variants: &str| variants.parse::<PortType>().unwrap();
+    assert!(reading.is_compatible_with(&union(
+        "[my.company.Temperature@1.2|my.company.Humidity@1.0]"
+    )));
+    assert!(!reading.is_compatible_with(&union(
+        "[my.company.Humidity@1.1|my.company.Temperature@1.0]"
+    )));
+    assert!(!reading.is_compatible_with(&union("[my.company.Temperature@1.0]")));
+    assert!(!reading.is_compatible_with(&union("my.company.Temperature@1.0")));
+}
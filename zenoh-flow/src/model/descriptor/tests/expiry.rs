@@ -0,0 +1,79 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::{ExpiryDescriptor, ExpiryReason, DEFAULT_EXPIRY_WARNING};
+use std::time::Duration;
+
+#[test]
+fn test_expiry_descriptor() {
+    let expiry: ExpiryDescriptor = serde_yaml::from_str(
+        r#"
+ttl: 8h
+idle: 30min
+warning: 5min
+"#,
+    )
+    .unwrap();
+    assert_eq!(expiry.ttl, Some(Duration::from_secs(8 * 3600)));
+    assert_eq!(expiry.idle, Some(Duration::from_secs(30 * 60)));
+    assert_eq!(expiry.warning, Duration::from_secs(5 * 60));
+
+    let read: ExpiryDescriptor =
+        serde_yaml::from_str(&serde_yaml::to_string(&expiry).unwrap()).unwrap();
+    assert_eq!(read, expiry);
+
+    let expiry: ExpiryDescriptor = serde_yaml::from_str("ttl: 1h").unwrap();
+    assert_eq!(expiry.idle, None);
+    assert_eq!(expiry.warning, DEFAULT_EXPIRY_WARNING);
+}
+
+#[test]
+fn test_expiry_remaining() {
+    let minutes = |minutes: u64| Duration::from_secs(minutes * 60);
+    let expiry = ExpiryDescriptor {
+        ttl: Some(minutes(60)),
+        idle: Some(minutes(10)),
+        warning: minutes(1),
+    };
+
+    // The earliest deadline applies.
+    assert_eq!(
+        expiry.remaining(minutes(5), minutes(2)),
+        Some((minutes(8), ExpiryReason::Idle))
+    );
+    assert_eq!(
+        expiry.remaining(minutes(55), minutes(0)),
+        Some((minutes(5), ExpiryReason::Ttl))
+    );
+    assert_eq!(
+        expiry.remaining(minutes(70), minutes(0)),
+        Some((Duration::ZERO, ExpiryReason::Ttl))
+    );
+
+    let ttl_only = ExpiryDescriptor {
+        idle: None,
+        ..expiry.clone()
+    };
+    assert_eq!(
+        ttl_only.remaining(minutes(5), minutes(30)),
+        Some((minutes(55), ExpiryReason::Ttl))
+    );
+
+    let never = ExpiryDescriptor {
+        ttl: None,
+        idle: None,
+        warning: minutes(1),
+    };
+    assert_eq!(never.remaining(minutes(5), minutes(30)), None);
+}
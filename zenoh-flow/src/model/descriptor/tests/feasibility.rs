@@ -0,0 +1,155 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::{RateDescriptor, WcetDescriptor};
+use crate::model::descriptor::FlattenDataFlowDescriptor;
+use crate::types::NodeId;
+use std::time::Duration;
+
+static DESCRIPTOR: &str = r#"
+flow: Detection
+sources:
+  - id: Camera
+    uri: file://./target/release/libcamera.so
+    outputs: [Frame]
+operators:
+  - id: downsample-Camera
+    uri: builtin://downsample
+    configuration:
+      sample: 1/2
+    inputs: [in]
+    outputs: [out]
+  - id: Detector
+    uri: file://./target/release/libdetector.so
+    inputs: [Frame]
+    outputs: [Objects]
+sinks:
+  - id: Display
+    uri: file://./target/release/libdisplay.so
+    inputs: [Objects]
+  - id: Logger
+    uri: file://./target/release/liblogger.so
+    inputs: [Frame]
+links:
+  - from: { node: Camera, output: Frame }
+    to: { node: downsample-Camera, input: in }
+  - from: { node: downsample-Camera, output: out }
+    to: { node: Detector, input: Frame }
+  - from: { node: Detector, output: Objects }
+    to: { node: Display, input: Objects }
+  - from: { node: Camera, output: Frame }
+    to: { node: Logger, input: Frame }
+feasibility:
+  rates:
+    - node: Camera
+      rate: 30
+  wcets:
+    - node: Detector
+      wcet: 20ms
+    - node: Display
+      wcet: 5ms
+  links:
+    - from: { node: Camera, output: Frame }
+      to: { node: Detector, input: Frame }
+      latency: 8ms
+  link_latency: 1ms
+  deadlines:
+    - from: Camera
+      to: Display
+      deadline: 50ms
+    - from: Camera
+      to: Detector
+      deadline: 25ms
+"#;
+
+fn ids(ids: &[&str]) -> Vec<NodeId> {
+    ids.iter().map(|id| (*id).into()).collect()
+}
+
+#[test]
+fn test_feasibility_report() {
+    let descriptor = FlattenDataFlowDescriptor::from_yaml(DESCRIPTOR).unwrap();
+    let report = descriptor.analyze_feasibility(&[]).unwrap();
+
+    // The link is sampled: the Detector receives half of the frames.
+    let detector = &report.nodes[&NodeId::from("Detector")];
+    assert_eq!(detector.rate, 15.0);
+    assert!((detector.utilization - 0.3).abs() < 1e-9);
+    assert_eq!(report.nodes[&NodeId::from("Logger")].rate, 30.0);
+
+    assert_eq!(report.bottleneck, Some("Detector".into()));
+    assert!((report.headroom().unwrap() - 1.0 / 0.3).abs() < 1e-9);
+    assert_eq!(report.unprofiled, ids(&["Logger"]));
+    assert!(report.unrated.is_empty());
+
+    // 8ms (budget) + 20ms (Detector) + 1ms (link latency) + 5ms (Display).
+    let display = &report.deadlines[0];
+    assert_eq!(display.latency, Some(Duration::from_millis(34)));
+    assert_eq!(
+        display.path,
+        ids(&["Camera", "downsample-Camera", "Detector", "Display"])
+    );
+    assert!(display.is_satisfiable());
+
+    let detector = &report.deadlines[1];
+    assert_eq!(detector.latency, Some(Duration::from_millis(28)));
+    assert!(!detector.is_satisfiable());
+    assert!(!report.is_feasible());
+}
+
+#[test]
+fn test_feasibility_measured() {
+    let descriptor = FlattenDataFlowDescriptor::from_yaml(DESCRIPTOR).unwrap();
+    let measured = vec![WcetDescriptor {
+        node: "Detector".into(),
+        wcet: Duration::from_millis(80),
+    }];
+    let report = descriptor.analyze_feasibility(&measured).unwrap();
+
+    let overloaded = report
+        .overloaded()
+        .map(|(id, _)| id.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(overloaded, ids(&["Detector"]));
+    assert_eq!(report.bottleneck, Some("Detector".into()));
+    assert_eq!(report.deadlines[0].latency, None);
+    assert!(!report.deadlines[0].is_satisfiable());
+}
+
+#[test]
+fn test_feasibility_errors() {
+    let descriptor = FlattenDataFlowDescriptor::from_yaml(DESCRIPTOR).unwrap();
+
+    let mut unknown = descriptor.clone();
+    unknown.feasibility.as_mut().unwrap().rates[0].node = "Lidar".into();
+    assert!(unknown.analyze_feasibility(&[]).is_err());
+
+    let mut not_a_source = descriptor.clone();
+    not_a_source
+        .feasibility
+        .as_mut()
+        .unwrap()
+        .rates
+        .push(RateDescriptor {
+            node: "Detector".into(),
+            rate: 10.0,
+        });
+    assert!(not_a_source.analyze_feasibility(&[]).is_err());
+
+    let mut no_path = descriptor;
+    let deadline = &mut no_path.feasibility.as_mut().unwrap().deadlines[0];
+    deadline.from = "Display".into();
+    deadline.to = "Camera".into();
+    assert!(no_path.analyze_feasibility(&[]).is_err());
+}
@@ -16,6 +16,7 @@ use crate::model::descriptor::{
     CompositeInputDescriptor, CompositeOperatorDescriptor, CompositeOutputDescriptor,
     InputDescriptor, LinkDescriptor, NodeDescriptor, OperatorDescriptor, OutputDescriptor,
 };
+use std::collections::HashMap;
 
 #[test]
 fn test_flatten_composite_descriptor_non_nested() {
@@ -37,11 +38,31 @@ fn test_flatten_composite_descriptor_non_nested() {
                 id: "my-operator-1".into(),
                 descriptor: "file://./src/model/descriptor/tests/operator-1.yml".into(),
                 configuration: None,
+                max_run_duration: None,
+                credits: None,
+                replicas: None,
+                autoscaling: None,
+                warmup: None,
+                cooldown: None,
+                gpu: None,
+                watchdog: None,
+                exposed: vec![],
+                imported: vec![],
             },
             NodeDescriptor {
                 id: "my-operator-2".into(),
                 descriptor: "file://./src/model/descriptor/tests/operator-2.yml".into(),
                 configuration: None,
+                max_run_duration: None,
+                credits: None,
+                replicas: None,
+                autoscaling: None,
+                warmup: None,
+                cooldown: None,
+                gpu: None,
+                watchdog: None,
+                exposed: vec![],
+                imported: vec![],
             },
         ],
         links: vec![LinkDescriptor::new(
@@ -92,6 +113,12 @@ fn test_flatten_composite_descriptor_non_nested() {
             id: "composite/my-operator-1".into(),
             inputs: vec!["operator-1-in-1".into(), "operator-1-in-2".into()],
             outputs: vec!["operator-1-out".into()],
+            side_outputs: vec![],
+            input_policies: HashMap::new(),
+            input_types: HashMap::new(),
+            input_units: HashMap::new(),
+            output_types: HashMap::new(),
+            output_units: HashMap::new(),
             uri: Some("file://operator-1.so".into()),
             configuration: None,
         },
@@ -99,6 +126,12 @@ fn test_flatten_composite_descriptor_non_nested() {
             id: "composite/my-operator-2".into(),
             inputs: vec!["operator-2-in".into()],
             outputs: vec!["operator-2-out".into()],
+            side_outputs: vec![],
+            input_policies: HashMap::new(),
+            input_types: HashMap::new(),
+            input_units: HashMap::new(),
+            output_types: HashMap::new(),
+            output_units: HashMap::new(),
             uri: Some("file://operator-2.so".into()),
             configuration: None,
         },
@@ -161,16 +194,46 @@ fn test_flatten_composite_descriptor_nested() {
                 id: "composite-outer-o".into(),
                 descriptor: "file://./src/model/descriptor/tests/composite-outer.yml".into(),
                 configuration: None,
+                max_run_duration: None,
+                credits: None,
+                replicas: None,
+                autoscaling: None,
+                warmup: None,
+                cooldown: None,
+                gpu: None,
+                watchdog: None,
+                exposed: vec![],
+                imported: vec![],
             },
             NodeDescriptor {
                 id: "composite-nested".into(),
                 descriptor: "file://./src/model/descriptor/tests/composite-nested.yml".into(),
                 configuration: None,
+                max_run_duration: None,
+                credits: None,
+                replicas: None,
+                autoscaling: None,
+                warmup: None,
+                cooldown: None,
+                gpu: None,
+                watchdog: None,
+                exposed: vec![],
+                imported: vec![],
             },
             NodeDescriptor {
                 id: "composite-outer-i".into(),
                 descriptor: "file://./src/model/descriptor/tests/composite-outer.yml".into(),
                 configuration: None,
+                max_run_duration: None,
+                credits: None,
+                replicas: None,
+                autoscaling: None,
+                warmup: None,
+                cooldown: None,
+                gpu: None,
+                watchdog: None,
+                exposed: vec![],
+                imported: vec![],
             },
         ],
         links: vec![
@@ -212,6 +275,12 @@ fn test_flatten_composite_descriptor_nested() {
             id: "composite/composite-outer-o".into(),
             inputs: vec!["composite-outer-in".into()],
             outputs: vec!["composite-outer-out".into()],
+            side_outputs: vec![],
+            input_policies: HashMap::new(),
+            input_types: HashMap::new(),
+            input_units: HashMap::new(),
+            output_types: HashMap::new(),
+            output_units: HashMap::new(),
             uri: Some("file://composite-outer.so".into()),
             configuration: None,
         },
@@ -219,6 +288,12 @@ fn test_flatten_composite_descriptor_nested() {
             id: "composite/composite-nested/operator-1".into(),
             inputs: vec!["operator-1-in-1".into(), "operator-1-in-2".into()],
             outputs: vec!["operator-1-out".into()],
+            side_outputs: vec![],
+            input_policies: HashMap::new(),
+            input_types: HashMap::new(),
+            input_units: HashMap::new(),
+            output_types: HashMap::new(),
+            output_units: HashMap::new(),
             uri: Some("file://operator-1.so".into()),
             configuration: None,
         },
@@ -226,6 +301,12 @@ fn test_flatten_composite_descriptor_nested() {
             id: "composite/composite-nested/operator-2".into(),
             inputs: vec!["operator-2-in".into()],
             outputs: vec!["operator-2-out".into()],
+            side_outputs: vec![],
+            input_policies: HashMap::new(),
+            input_types: HashMap::new(),
+            input_units: HashMap::new(),
+            output_types: HashMap::new(),
+            output_units: HashMap::new(),
             uri: Some("file://operator-2.so".into()),
             configuration: None,
         },
@@ -233,6 +314,12 @@ fn test_flatten_composite_descriptor_nested() {
             id: "composite/composite-outer-i".into(),
             inputs: vec!["composite-outer-in".into()],
             outputs: vec!["composite-outer-out".into()],
+            side_outputs: vec![],
+            input_policies: HashMap::new(),
+            input_types: HashMap::new(),
+            input_units: HashMap::new(),
+            output_types: HashMap::new(),
+            output_units: HashMap::new(),
             uri: Some("file://composite-outer.so".into()),
             configuration: None,
         },
@@ -14,13 +14,18 @@
 
 use serde_json::json;
 
+use super::{expand_replicas, insert_convert_operators, replica_of};
+use crate::model::descriptor::affinity;
 use crate::model::descriptor::{
-    DataFlowDescriptor, InputDescriptor, LinkDescriptor, OperatorDescriptor, OutputDescriptor,
-    SinkDescriptor, SourceDescriptor,
+    AffinityRule, DataFlowDescriptor, InputDescriptor, LinkDescriptor, MergeOrdering,
+    OperatorDescriptor, OutputDescriptor, OverflowPolicy, QueueDescriptor, ReadinessCheck,
+    ReadinessFailure, RedactionDescriptor, SinkDescriptor, SourceDescriptor,
 };
 use std::{
+    collections::HashMap,
     fs::File,
     io::{BufReader, Read},
+    time::Duration,
 };
 
 const BASE_PATH: &str = "src/model/descriptor/tests/";
@@ -63,12 +68,16 @@ fn test_flatten_descriptor() {
         SourceDescriptor {
             id: "source-1".into(),
             outputs: vec!["source-out".into()],
+            output_types: HashMap::new(),
+            output_units: HashMap::new(),
             uri: Some("file://source.so".into()),
             configuration: Some(json!({ "foo": "global-outer" })),
         },
         SourceDescriptor {
             id: "source-2".into(),
             outputs: vec!["source-out".into()],
+            output_types: HashMap::new(),
+            output_units: HashMap::new(),
             uri: Some("file://source.so".into()),
             configuration: Some(json!({ "foo": "global-outer" })),
         },
@@ -78,6 +87,8 @@ fn test_flatten_descriptor() {
                 "source-composite-out-1".into(),
                 "source-composite-out-2".into(),
             ],
+            output_types: HashMap::new(),
+            output_units: HashMap::new(),
             uri: Some("file://source-composite.so".into()),
             configuration: Some(json!({ "foo": "global-outer" })),
         },
@@ -101,6 +112,12 @@ fn test_flatten_descriptor() {
             id: "operator-1".into(),
             inputs: vec!["operator-in".into()],
             outputs: vec!["operator-out".into()],
+            side_outputs: vec![],
+            input_policies: HashMap::new(),
+            input_types: HashMap::new(),
+            input_units: HashMap::new(),
+            output_types: HashMap::new(),
+            output_units: HashMap::new(),
             uri: Some("file://operator.so".into()),
             configuration: Some(json!({ "foo": "global-outer" })),
         },
@@ -108,6 +125,12 @@ fn test_flatten_descriptor() {
             id: "operator-2".into(),
             inputs: vec!["operator-in".into()],
             outputs: vec!["operator-out".into()],
+            side_outputs: vec![],
+            input_policies: HashMap::new(),
+            input_types: HashMap::new(),
+            input_units: HashMap::new(),
+            output_types: HashMap::new(),
+            output_units: HashMap::new(),
             uri: Some("file://operator.so".into()),
             configuration: Some(json!({ "foo": "global-outer" })),
         },
@@ -124,6 +147,12 @@ fn test_flatten_descriptor() {
             id: "operator-composite/sub-operator-1".into(),
             inputs: vec!["sub-operator-1-in-1".into(), "sub-operator-1-in-2".into()],
             outputs: vec!["sub-operator-1-out".into()],
+            side_outputs: vec![],
+            input_policies: HashMap::new(),
+            input_types: HashMap::new(),
+            input_units: HashMap::new(),
+            output_types: HashMap::new(),
+            output_units: HashMap::new(),
             uri: Some("file://sub-operator-1.so".into()),
             configuration: Some(
                 json!({ "foo": "global-outer", "quux": "global-inner", "bar": "composite-outer" }),
@@ -139,6 +168,12 @@ fn test_flatten_descriptor() {
             id: "operator-composite/sub-operator-composite/sub-sub-operator-1".into(),
             inputs: vec!["sub-sub-operator-1-in".into()],
             outputs: vec!["sub-sub-operator-1-out".into()],
+            side_outputs: vec![],
+            input_policies: HashMap::new(),
+            input_types: HashMap::new(),
+            input_units: HashMap::new(),
+            output_types: HashMap::new(),
+            output_units: HashMap::new(),
             uri: Some("file://sub-sub-operator-1.so".into()),
             configuration: Some(
                 json!({ "foo": "global-outer", "quux": "global-inner", "bar": "composite-outer", "buzz": "composite-inner", "baz": "leaf" }),
@@ -151,6 +186,12 @@ fn test_flatten_descriptor() {
             id: "operator-composite/sub-operator-composite/sub-sub-operator-2".into(),
             inputs: vec!["sub-sub-operator-2-in".into()],
             outputs: vec!["sub-sub-operator-2-out".into()],
+            side_outputs: vec![],
+            input_policies: HashMap::new(),
+            input_types: HashMap::new(),
+            input_units: HashMap::new(),
+            output_types: HashMap::new(),
+            output_units: HashMap::new(),
             uri: Some("file://sub-sub-operator-2.so".into()),
             configuration: Some(
                 json!({ "foo": "global-outer", "quux": "global-inner", "bar": "composite-outer", "buzz": "composite-inner" }),
@@ -163,6 +204,12 @@ fn test_flatten_descriptor() {
             id: "operator-composite/sub-operator-2".into(),
             inputs: vec!["sub-operator-2-in".into()],
             outputs: vec!["sub-operator-2-out-1".into(), "sub-operator-2-out-2".into()],
+            side_outputs: vec![],
+            input_policies: HashMap::new(),
+            input_types: HashMap::new(),
+            input_units: HashMap::new(),
+            output_types: HashMap::new(),
+            output_units: HashMap::new(),
             uri: Some("file://sub-operator-2.so".into()),
             configuration: Some(
                 json!({ "foo": "global-outer", "quux": "global-inner", "bar": "composite-outer" }),
@@ -188,18 +235,27 @@ fn test_flatten_descriptor() {
         SinkDescriptor {
             id: "sink-1".into(),
             inputs: vec!["sink-in".into()],
+            input_policies: HashMap::new(),
+            input_types: HashMap::new(),
+            input_units: HashMap::new(),
             uri: Some("file://sink.so".into()),
             configuration: Some(json!({ "foo": "global-outer" })),
         },
         SinkDescriptor {
             id: "sink-2".into(),
             inputs: vec!["sink-in".into()],
+            input_policies: HashMap::new(),
+            input_types: HashMap::new(),
+            input_units: HashMap::new(),
             uri: Some("file://sink.so".into()),
             configuration: Some(json!({ "foo": "global-outer" })),
         },
         SinkDescriptor {
             id: "sink-composite".into(),
             inputs: vec!["sink-composite-in-1".into(), "sink-composite-in-2".into()],
+            input_policies: HashMap::new(),
+            input_types: HashMap::new(),
+            input_units: HashMap::new(),
             uri: Some("file://sink-composite.so".into()),
             configuration: Some(json!({ "foo": "global-outer" })),
         },
@@ -301,6 +357,34 @@ fn test_flatten_descriptor() {
     });
     assert_eq!(expected_links.len(), flatten.links.len());
 
+    // The redaction rules follow the outputs of the composite operators.
+    let expected_redaction = vec![
+        RedactionDescriptor {
+            output: OutputDescriptor::new(
+                "operator-composite/sub-operator-2",
+                "sub-operator-2-out-1",
+            ),
+            transform: None,
+            mask: vec!["/user/email".into()],
+            max_bytes: None,
+        },
+        RedactionDescriptor {
+            output: OutputDescriptor::new("operator-1", "operator-out"),
+            transform: None,
+            mask: vec![],
+            max_bytes: Some(16),
+        },
+    ];
+    expected_redaction.iter().for_each(|expected_rule| {
+        assert!(
+            flatten.redaction.contains(expected_rule),
+            "Redaction rule missing or incorrect: \n\n (expected) {:?} \n\n {:?}",
+            expected_rule,
+            flatten.redaction
+        )
+    });
+    assert_eq!(expected_redaction.len(), flatten.redaction.len());
+
     // let expected_mappings = HashMap::from([
     //     ("source-composite", "runtime-source-composite"),
     //     ("operator-composite/sub-operator-1", "runtime-operator-composite"),
@@ -353,3 +437,398 @@ fn test_duplicate_composite_at_same_level_not_detected_as_recursion() {
     let descriptor = DataFlowDescriptor::from_yaml(&yaml).expect("Unexpected error");
     assert!(async_std::task::block_on(async { descriptor.flatten().await }).is_ok());
 }
+
+static DATA_FLOW_FAN_IN_FAN_OUT: &str = r#"
+flow: fan-in-fan-out
+sources:
+  - id: source-1
+    descriptor: file://source.yml
+  - id: source-2
+    descriptor: file://source.yml
+operators: []
+sinks:
+  - id: sink-1
+    descriptor: file://sink.yml
+  - id: sink-2
+    descriptor: file://sink.yml
+links:
+  - from:
+      - node: source-1
+        output: out
+      - node: source-2
+        output: out
+    to:
+      node: sink-1
+      input: in
+    merge: timestamp
+  - from:
+      node: source-1
+      output: out
+    to:
+      - node: sink-1
+        input: in
+      - node: sink-2
+        input: in
+"#;
+
+#[test]
+fn test_links_fan_in_fan_out() {
+    let descriptor =
+        DataFlowDescriptor::from_yaml(DATA_FLOW_FAN_IN_FAN_OUT).expect("Unexpected error");
+
+    let expected_links = vec![
+        (
+            ("source-1", "out"),
+            ("sink-1", "in"),
+            Some(MergeOrdering::Timestamp),
+        ),
+        (
+            ("source-2", "out"),
+            ("sink-1", "in"),
+            Some(MergeOrdering::Timestamp),
+        ),
+        (("source-1", "out"), ("sink-1", "in"), None),
+        (("source-1", "out"), ("sink-2", "in"), None),
+    ];
+    assert_eq!(expected_links.len(), descriptor.links.len());
+
+    for (link, ((from_node, from_output), (to_node, to_input), merge)) in
+        descriptor.links.iter().zip(expected_links)
+    {
+        assert_eq!(link.from, OutputDescriptor::new(from_node, from_output));
+        assert_eq!(link.to, InputDescriptor::new(to_node, to_input));
+        assert_eq!(link.merge, merge);
+    }
+
+    let empty = DATA_FLOW_FAN_IN_FAN_OUT.replace(
+        r#"    to:
+      - node: sink-1
+        input: in
+      - node: sink-2
+        input: in"#,
+        "    to: []",
+    );
+    assert!(DataFlowDescriptor::from_yaml(&empty).is_err());
+}
+
+static DATA_FLOW_REPLICAS: &str = r#"
+flow: replicas
+vars:
+  CAMERAS: "3"
+
+sources:
+  - id: camera
+    descriptor: file://camera.yml
+    replicas: {{ CAMERAS }}
+operators:
+  - id: detector
+    descriptor: file://detector.yml
+    replicas: {{ CAMERAS }}
+sinks:
+  - id: display
+    descriptor: file://display.yml
+
+links:
+  - from:
+      node: camera
+      output: frame
+    to:
+      node: detector
+      input: frame
+  - from:
+      node: detector
+      output: objects
+    to:
+      node: display
+      input: "objects-{replica}"
+
+mapping:
+  camera: runtime-0
+  camera-2: runtime-1
+"#;
+
+#[test]
+fn test_expand_replicas() {
+    let DataFlowDescriptor {
+        mut sources,
+        mut operators,
+        mut sinks,
+        mut links,
+        mut mapping,
+        ..
+    } = DataFlowDescriptor::from_yaml(DATA_FLOW_REPLICAS).expect("Unexpected error");
+
+    expand_replicas(
+        [&mut sources, &mut operators, &mut sinks],
+        &mut links,
+        &mut mapping,
+    )
+    .expect("Unexpected error");
+
+    let ids = |nodes: &[crate::model::descriptor::NodeDescriptor]| {
+        nodes
+            .iter()
+            .map(|node| node.id.to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(ids(&sources), vec!["camera-0", "camera-1", "camera-2"]);
+    assert_eq!(
+        ids(&operators),
+        vec!["detector-0", "detector-1", "detector-2"]
+    );
+    assert_eq!(ids(&sinks), vec!["display"]);
+    assert!(sources.iter().all(|source| source.replicas.is_none()));
+
+    let expected_links = vec![
+        (("camera-0", "frame"), ("detector-0", "frame")),
+        (("camera-1", "frame"), ("detector-1", "frame")),
+        (("camera-2", "frame"), ("detector-2", "frame")),
+        (("detector-0", "objects"), ("display", "objects-0")),
+        (("detector-1", "objects"), ("display", "objects-1")),
+        (("detector-2", "objects"), ("display", "objects-2")),
+    ];
+    assert_eq!(expected_links.len(), links.len());
+    for (link, ((from_node, from_output), (to_node, to_input))) in links.iter().zip(expected_links)
+    {
+        assert_eq!(link.from, OutputDescriptor::new(from_node, from_output));
+        assert_eq!(link.to, InputDescriptor::new(to_node, to_input));
+    }
+
+    // The explicit mapping of a copy takes precedence.
+    let mapping = mapping.unwrap();
+    assert!(mapping.get("camera").is_none());
+    assert_eq!(
+        mapping.get("camera-0").map(|r| r.as_ref()),
+        Some("runtime-0")
+    );
+    assert_eq!(
+        mapping.get("camera-1").map(|r| r.as_ref()),
+        Some("runtime-0")
+    );
+    assert_eq!(
+        mapping.get("camera-2").map(|r| r.as_ref()),
+        Some("runtime-1")
+    );
+
+    let mismatch = DATA_FLOW_REPLICAS.replace(
+        "    descriptor: file://detector.yml\n    replicas: {{ CAMERAS }}",
+        "    descriptor: file://detector.yml\n    replicas: 2",
+    );
+    assert_ne!(mismatch, DATA_FLOW_REPLICAS);
+    let mut descriptor = DataFlowDescriptor::from_yaml(&mismatch).expect("Unexpected error");
+    assert!(expand_replicas(
+        [
+            &mut descriptor.sources,
+            &mut descriptor.operators,
+            &mut descriptor.sinks
+        ],
+        &mut descriptor.links,
+        &mut descriptor.mapping,
+    )
+    .is_err());
+}
+
+#[test]
+fn test_expand_affinity_replicas() {
+    let mut descriptor = DataFlowDescriptor::from_yaml(&format!(
+        r#"{DATA_FLOW_REPLICAS}
+affinity:
+  - colocate: [camera, detector, display]
+  - separate: [detector]
+"#
+    ))
+    .expect("Unexpected error");
+    let replicas = expand_replicas(
+        [
+            &mut descriptor.sources,
+            &mut descriptor.operators,
+            &mut descriptor.sinks,
+        ],
+        &mut descriptor.links,
+        &mut descriptor.mapping,
+    )
+    .expect("Unexpected error");
+    assert_eq!(replicas.get("camera"), Some(&3));
+
+    // The copies, and the operators of the copies of a composite operator, are traced back.
+    assert_eq!(
+        replica_of(&replicas, &"detector-2".into()),
+        Some(("detector".into(), 2, 3))
+    );
+    assert_eq!(
+        replica_of(&replicas, &"detector-1/tracker".into()),
+        Some(("detector/tracker".into(), 1, 3))
+    );
+    assert!(replica_of(&replicas, &"detector-20".into()).is_none());
+    assert!(replica_of(&replicas, &"display".into()).is_none());
+
+    let ids = |ids: &[&str]| ids.iter().map(|id| (*id).into()).collect::<Vec<_>>();
+    assert_eq!(
+        affinity::expand_replicas(descriptor.affinity, &replicas).expect("Unexpected error"),
+        vec![
+            AffinityRule::Colocate(ids(&["camera-0", "detector-0", "display"])),
+            AffinityRule::Colocate(ids(&["camera-1", "detector-1", "display"])),
+            AffinityRule::Colocate(ids(&["camera-2", "detector-2", "display"])),
+            AffinityRule::Separate(ids(&["detector-0", "detector-1", "detector-2"])),
+        ]
+    );
+
+    let mismatch = HashMap::from([("camera".into(), 3), ("detector".into(), 2)]);
+    assert!(affinity::expand_replicas(
+        vec![AffinityRule::Colocate(ids(&["camera", "detector"]))],
+        &mismatch
+    )
+    .is_err());
+}
+
+#[test]
+fn test_readiness() {
+    let descriptor = DataFlowDescriptor::from_yaml(&format!(
+        r#"{DATA_FLOW_REPLICAS}
+readiness:
+  timeout: 1min
+  on_failure: proceed
+  checks:
+    - zenoh: robot/lidar/calibration
+    - tcp: localhost:5432
+    - file: /dev/video0
+"#
+    ))
+    .expect("Unexpected error");
+
+    let readiness = descriptor.readiness.expect("Missing readiness");
+    assert_eq!(readiness.timeout(), Duration::from_secs(60));
+    assert_eq!(readiness.interval(), Duration::from_secs(1));
+    assert_eq!(readiness.on_failure, ReadinessFailure::Proceed);
+    assert_eq!(
+        readiness.checks,
+        vec![
+            ReadinessCheck::Zenoh("robot/lidar/calibration".into()),
+            ReadinessCheck::Tcp("localhost:5432".into()),
+            ReadinessCheck::File("/dev/video0".into()),
+        ]
+    );
+
+    assert!(DataFlowDescriptor::from_yaml(DATA_FLOW_REPLICAS)
+        .expect("Unexpected error")
+        .readiness
+        .is_none());
+}
+
+#[test]
+fn test_insert_convert_operators() {
+    let source = SourceDescriptor::from_yaml(
+        r#"
+id: odometry
+outputs: [speed, heading]
+output_units:
+  speed: km/h
+  heading: rad
+uri: file://odometry.so
+configuration: null
+"#,
+    )
+    .expect("Unexpected error");
+    let sink = SinkDescriptor::from_yaml(
+        r#"
+id: controller
+inputs: [speed, heading]
+input_units:
+  speed: m/s
+  heading: rad
+uri: file://controller.so
+configuration: null
+"#,
+    )
+    .expect("Unexpected error");
+
+    let mut links = vec![
+        LinkDescriptor::new(
+            OutputDescriptor::new("odometry", "speed"),
+            InputDescriptor::new("controller", "speed"),
+        ),
+        LinkDescriptor::new(
+            OutputDescriptor::new("odometry", "heading"),
+            InputDescriptor::new("controller", "heading"),
+        ),
+    ];
+    links[0].queue = Some(QueueDescriptor {
+        capacity: 8,
+        overflow: OverflowPolicy::DropOldest,
+    });
+    let mut operators = vec![];
+    let mut mapping = Some(HashMap::from([("odometry".into(), "runtime-0".into())]));
+
+    insert_convert_operators(
+        &mut links,
+        &[source.clone()],
+        &mut operators,
+        &[sink.clone()],
+        &mut mapping,
+    )
+    .expect("Unexpected error");
+
+    // Only the link between different units is converted, on the runtime of the upstream node.
+    assert_eq!(operators.len(), 1);
+    let convert = &operators[0];
+    assert_eq!(
+        convert.id.as_ref(),
+        "convert-odometry-speed-controller-speed"
+    );
+    assert_eq!(convert.uri, Some("builtin://convert".into()));
+    assert_eq!(
+        mapping.unwrap().get(&convert.id).map(|r| r.as_ref()),
+        Some("runtime-0")
+    );
+
+    let expected_links = vec![
+        (
+            ("convert-odometry-speed-controller-speed", "out"),
+            ("controller", "speed"),
+        ),
+        (("odometry", "heading"), ("controller", "heading")),
+        (
+            ("odometry", "speed"),
+            ("convert-odometry-speed-controller-speed", "in"),
+        ),
+    ];
+    assert_eq!(expected_links.len(), links.len());
+    for (link, ((from_node, from_output), (to_node, to_input))) in links.iter().zip(expected_links)
+    {
+        assert_eq!(link.from, OutputDescriptor::new(from_node, from_output));
+        assert_eq!(link.to, InputDescriptor::new(to_node, to_input));
+    }
+    // The link to the downstream node keeps its settings.
+    assert!(links[0].queue.is_some());
+    assert!(links[2].queue.is_none());
+
+    // Units that cannot be converted are left to the validation.
+    let mut links = vec![LinkDescriptor::new(
+        OutputDescriptor::new("odometry", "heading"),
+        InputDescriptor::new("controller", "speed"),
+    )];
+    let mut operators = vec![];
+    insert_convert_operators(
+        &mut links,
+        &[source.clone()],
+        &mut operators,
+        &[sink.clone()],
+        &mut None,
+    )
+    .expect("Unexpected error");
+    assert!(operators.is_empty());
+    assert_eq!(links.len(), 1);
+
+    // Typed data are not a single number: their conversion is rejected.
+    let mut typed = source;
+    typed
+        .output_types
+        .insert("speed".into(), "my.company.Speed@1.0".parse().unwrap());
+    let mut links = vec![LinkDescriptor::new(
+        OutputDescriptor::new("odometry", "speed"),
+        InputDescriptor::new("controller", "speed"),
+    )];
+    assert!(
+        insert_convert_operators(&mut links, &[typed], &mut operators, &[sink], &mut None).is_err()
+    );
+}
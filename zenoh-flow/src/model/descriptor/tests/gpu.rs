@@ -0,0 +1,68 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::GpuDescriptor;
+use crate::model::descriptor::NodeDescriptor;
+
+#[test]
+fn test_gpu_descriptor() {
+    let node: NodeDescriptor = serde_yaml::from_str(
+        r#"
+id: Detector
+descriptor: file://./detector.yml
+configuration: ~
+gpu: 2
+"#,
+    )
+    .unwrap();
+    let gpu = node.gpu.unwrap();
+    assert_eq!(
+        gpu,
+        GpuDescriptor {
+            count: 2,
+            device: None,
+            exclusive: true,
+        }
+    );
+    assert!(gpu.validate(&node.id).is_ok());
+
+    let gpu: GpuDescriptor = serde_yaml::from_str("device: 1\nexclusive: false").unwrap();
+    assert_eq!(
+        gpu,
+        GpuDescriptor {
+            count: 1,
+            device: Some(1),
+            exclusive: false,
+        }
+    );
+    assert_eq!(
+        serde_yaml::from_str::<GpuDescriptor>(&serde_yaml::to_string(&gpu).unwrap()).unwrap(),
+        gpu
+    );
+
+    let id = "Detector".into();
+    assert!(GpuDescriptor {
+        count: 0,
+        ..Default::default()
+    }
+    .validate(&id)
+    .is_err());
+    assert!(GpuDescriptor {
+        count: 2,
+        device: Some(0),
+        exclusive: true,
+    }
+    .validate(&id)
+    .is_err());
+}
@@ -0,0 +1,60 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::model::descriptor::migration::{migrate, DESCRIPTOR_VERSION};
+use crate::model::descriptor::DataFlowDescriptor;
+use serde_json::json;
+
+static DESCRIPTOR_V1: &str = r#"
+flow: migration
+configuration:
+  answer: 42
+sources: []
+operators: []
+sinks: []
+links: []
+"#;
+
+#[test]
+fn test_migrate_v1() {
+    let descriptor = DataFlowDescriptor::from_yaml(DESCRIPTOR_V1).expect("Failed to migrate");
+    assert_eq!(descriptor.version, DESCRIPTOR_VERSION);
+    assert_eq!(
+        descriptor.global_configuration,
+        Some(json!({ "answer": 42 }))
+    );
+
+    // Migrating twice is a no-op.
+    let mut value = json!({ "flow": "migration", "configuration": { "answer": 42 } });
+    migrate(&mut value).expect("Failed to migrate");
+    let migrated = value.clone();
+    migrate(&mut value).expect("Failed to migrate");
+    assert_eq!(migrated, value);
+    assert_eq!(value["version"], json!(DESCRIPTOR_VERSION));
+}
+
+#[test]
+fn test_migrate_ko() {
+    let mut both = json!({ "configuration": {}, "global_configuration": {} });
+    assert!(migrate(&mut both).is_err());
+
+    let mut future = json!({ "version": DESCRIPTOR_VERSION + 1 });
+    assert!(migrate(&mut future).is_err());
+
+    let mut invalid = json!({ "version": "two" });
+    assert!(migrate(&mut invalid).is_err());
+
+    let mut not_a_map = json!(["flow"]);
+    assert!(migrate(&mut not_a_map).is_err());
+}
@@ -0,0 +1,71 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::model::descriptor::strict::{find_unknown_fields, UnknownField};
+use crate::model::descriptor::{DataFlowDescriptor, ParsingMode};
+
+static DESCRIPTOR_TYPO: &str = r#"
+flow: strict
+sources:
+  - id: source
+    descriptor: file://source.yaml
+    confguration:
+      answer: 42
+operators: []
+sinks:
+  - id: sink
+    descriptor: file://sink.yaml
+links:
+  - from:
+      node: source
+      output: out
+    to:
+      node: sink
+      inptu: in
+"#;
+
+#[test]
+fn test_strict_ko() {
+    let unknown = find_unknown_fields(
+        &serde_yaml::from_str(DESCRIPTOR_TYPO).unwrap(),
+        Some(DESCRIPTOR_TYPO),
+    );
+    assert_eq!(
+        unknown,
+        vec![
+            UnknownField {
+                path: "sources[0].confguration".into(),
+                line: Some(6),
+            },
+            UnknownField {
+                path: "links[0].to.inptu".into(),
+                line: Some(18),
+            },
+        ]
+    );
+
+    let error = DataFlowDescriptor::from_yaml_with_mode(DESCRIPTOR_TYPO, ParsingMode::Strict)
+        .expect_err("Unknown fields should be rejected in strict mode");
+    assert!(format!("{error:?}").contains("sources[0].confguration` (line 6)"));
+}
+
+#[test]
+fn test_permissive_ok() {
+    let descriptor = DESCRIPTOR_TYPO.replace("inptu", "input");
+    assert!(DataFlowDescriptor::from_yaml(&descriptor).is_ok());
+    assert!(DataFlowDescriptor::from_yaml_with_mode(&descriptor, ParsingMode::Strict).is_err());
+
+    let descriptor = descriptor.replace("confguration", "configuration");
+    assert!(DataFlowDescriptor::from_yaml_with_mode(&descriptor, ParsingMode::Strict).is_ok());
+}
@@ -0,0 +1,70 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::{check_locator, TransportDescriptor};
+
+static TRANSPORT: &str = r#"
+mode: client
+connect:
+  - quic/[2001:db8::1]:7447
+  - serial//dev/ttyUSB0#baudrate=115200
+listen:
+  - unixsock-stream//tmp/zenoh-flow.sock
+multicast_scouting: false
+tls:
+  root_ca_certificate: /etc/zenoh-flow/certs/ca.pem
+"#;
+
+#[test]
+fn test_check_locator() {
+    assert_eq!(check_locator("tcp/127.0.0.1:7447").unwrap(), "tcp");
+    assert_eq!(check_locator("tls/localhost:7447").unwrap(), "tls");
+    assert_eq!(check_locator("udp/[::1]:7447?iface=eth0").unwrap(), "udp");
+    assert_eq!(
+        check_locator("serial//dev/ttyACM0#baudrate=9600").unwrap(),
+        "serial"
+    );
+
+    assert!(check_locator("127.0.0.1:7447").is_err());
+    assert!(check_locator("bluetooth/00:11:22:33:44:55").is_err());
+    assert!(check_locator("tcp/::1:7447").is_err());
+    assert!(check_locator("tcp/[::1]").is_err());
+    assert!(check_locator("tcp/[not-an-ip]:7447").is_err());
+    assert!(check_locator("tcp/127.0.0.1").is_err());
+    assert!(check_locator("serial/").is_err());
+}
+
+#[test]
+fn test_transport() {
+    let transport: TransportDescriptor = serde_yaml::from_str(TRANSPORT).unwrap();
+    assert!(transport.validate().is_ok());
+
+    let config = transport.to_config().unwrap();
+    assert_eq!(config.connect.endpoints.len(), 2);
+    assert_eq!(config.listen.endpoints.len(), 1);
+
+    let mut missing_ca = transport.clone();
+    missing_ca.tls = None;
+    assert!(missing_ca.validate().is_err());
+
+    let mut missing_key = transport;
+    missing_key.listen = vec!["tls/[::]:7447".into()];
+    assert!(missing_key.validate().is_err());
+
+    let unknown_mode = TransportDescriptor {
+        mode: Some("server".into()),
+        ..Default::default()
+    };
+    assert!(unknown_mode.validate().is_err());
+}
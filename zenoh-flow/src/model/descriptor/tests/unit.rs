@@ -0,0 +1,57 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::Unit;
+
+fn unit(unit: &str) -> Unit {
+    unit.parse().unwrap()
+}
+
+fn convert(value: f64, from: &str, to: &str) -> f64 {
+    unit(from).conversion_to(&unit(to)).unwrap().apply(value)
+}
+
+fn assert_close(value: f64, expected: f64) {
+    assert!(
+        (value - expected).abs() < 1e-9,
+        "expected {}, got {}",
+        expected,
+        value
+    );
+}
+
+#[test]
+fn test_unit_parse() {
+    assert_eq!(unit(" m/s ").to_string(), "m/s");
+    assert!("".parse::<Unit>().is_err());
+    assert!(serde_yaml::from_str::<Unit>("''").is_err());
+
+    assert_eq!(unit("km/h").quantity(), Some("speed"));
+    assert_eq!(unit("furlong").quantity(), None);
+}
+
+#[test]
+fn test_unit_conversion() {
+    assert_close(convert(36.0, "km/h", "m/s"), 10.0);
+    assert_close(convert(10.0, "m/s", "km/h"), 36.0);
+    assert_close(convert(180.0, "deg", "rad"), std::f64::consts::PI);
+    assert_close(convert(1.5, "km", "m"), 1_500.0);
+    assert_close(convert(100.0, "degC", "degF"), 212.0);
+    assert_close(convert(32.0, "degF", "K"), 273.15);
+    assert_close(convert(42.0, "m/s", "m/s"), 42.0);
+
+    // Different quantities, or unknown units, cannot be converted.
+    assert!(unit("m").conversion_to(&unit("s")).is_none());
+    assert!(unit("furlong").conversion_to(&unit("m")).is_none());
+}
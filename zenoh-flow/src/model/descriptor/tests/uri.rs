@@ -0,0 +1,86 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::NodeUri;
+use crate::model::descriptor::OperatorDescriptor;
+use std::collections::BTreeMap;
+
+static SINGLE_URI: &str = r#"
+id: Detector
+uri: file:///opt/nodes/libdetector.so
+inputs: [Frame]
+outputs: [Detections]
+"#;
+
+static URI_PER_ARCHITECTURE: &str = r#"
+id: Detector
+uri:
+  linux/x86_64: file:///opt/nodes/x86_64/libdetector.so
+  linux/aarch64: file:///opt/nodes/aarch64/libdetector.so
+inputs: [Frame]
+outputs: [Detections]
+"#;
+
+#[test]
+fn test_single_uri() {
+    let descriptor = OperatorDescriptor::from_yaml(SINGLE_URI).unwrap();
+    let uri = descriptor.uri.unwrap();
+    assert_eq!(uri, NodeUri::from("file:///opt/nodes/libdetector.so"));
+    assert_eq!(uri.resolve().unwrap(), "file:///opt/nodes/libdetector.so");
+    assert_eq!(
+        uri.for_architecture("linux/riscv64"),
+        Some("file:///opt/nodes/libdetector.so")
+    );
+    assert!(uri.architectures().is_empty());
+    assert!(uri.validate().is_ok());
+}
+
+#[test]
+fn test_uri_per_architecture() {
+    let descriptor = OperatorDescriptor::from_yaml(URI_PER_ARCHITECTURE).unwrap();
+    let uri = descriptor.uri.unwrap();
+    assert!(uri.validate().is_ok());
+    assert_eq!(uri.architectures(), vec!["linux/aarch64", "linux/x86_64"]);
+    assert_eq!(
+        uri.for_architecture("linux/aarch64"),
+        Some("file:///opt/nodes/aarch64/libdetector.so")
+    );
+    assert_eq!(uri.for_architecture("linux/riscv64"), None);
+    assert_eq!(uri.uris().len(), 2);
+
+    let current = NodeUri::current_architecture();
+    match uri.for_architecture(&current) {
+        Some(expected) => assert_eq!(uri.resolve().unwrap(), expected),
+        None => assert!(uri.resolve().is_err()),
+    }
+
+    // The map survives a round trip.
+    let yaml = serde_yaml::to_string(&uri).unwrap();
+    assert_eq!(serde_yaml::from_str::<NodeUri>(&yaml).unwrap(), uri);
+}
+
+#[test]
+fn test_invalid_architectures() {
+    assert!(NodeUri::PerArchitecture(BTreeMap::new())
+        .validate()
+        .is_err());
+
+    for architecture in ["linux", "/x86_64", "linux/", "linux/x86_64/v2"] {
+        let uri = NodeUri::PerArchitecture(BTreeMap::from([(
+            architecture.to_string(),
+            "file:///opt/nodes/libdetector.so".to_string(),
+        )]));
+        assert!(uri.validate().is_err(), "{architecture} should be rejected");
+    }
+}
@@ -0,0 +1,303 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::zfresult::{ErrorKind, ZFResult as Result};
+use crate::{bail, zferror};
+use serde::{Deserialize, Serialize};
+use zenoh::config::{Config, ValidatedMap};
+
+/// The protocols of the locators Zenoh can listen on or connect to.
+const PROTOCOLS: [&str; 7] = [
+    "tcp",
+    "udp",
+    "tls",
+    "quic",
+    "ws",
+    "serial",
+    "unixsock-stream",
+];
+
+/// The protocols that require TLS certificates.
+const TLS_PROTOCOLS: [&str; 2] = ["tls", "quic"];
+
+/// The transport configuration of a Zenoh session: its mode, the locators it listens on and
+/// connects to and the TLS certificates used by the `tls` and `quic` locators.
+///
+/// It is applied on top of a Zenoh configuration (see [TransportDescriptor::apply]): the fields
+/// that are set replace those of the configuration.
+///
+/// A locator is made of a protocol and an address, optionally followed by its metadata:
+/// - `tcp`, `udp`, `tls`, `quic` and `ws` expect a `host:port` address, an IPv6 address being
+///   enclosed in brackets (`tcp/[fe80::1]:7447`),
+/// - `serial` expects the path of the device (`serial//dev/ttyUSB0#baudrate=115200`),
+/// - `unixsock-stream` expects the path of the socket (`unixsock-stream//tmp/zenoh.sock`).
+///
+/// Example:
+///
+/// ```yaml
+/// mode: client
+/// connect:
+///   - quic/[2001:db8::1]:7447
+///   - serial//dev/ttyUSB0#baudrate=115200
+/// multicast_scouting: false
+/// tls:
+///   root_ca_certificate: /etc/zenoh-flow/certs/ca.pem
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransportDescriptor {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub listen: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub connect: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multicast_scouting: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls: Option<TlsDescriptor>,
+}
+
+/// The TLS certificates, and their keys, used by the `tls` and `quic` locators.
+///
+/// Listening on such a locator requires the `server_private_key` and the `server_certificate`,
+/// connecting to one requires the `root_ca_certificate`. With `client_auth`, the clients must
+/// present their own certificate.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct TlsDescriptor {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root_ca_certificate: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_private_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_certificate: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_auth: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_private_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_certificate: Option<String>,
+}
+
+impl TransportDescriptor {
+    /// Checks the mode, the locators and that the certificates the locators require are provided.
+    ///
+    /// # Errors
+    ///
+    /// A `ConfigurationError` is returned if the mode or a locator is not valid, or if a
+    /// certificate is missing.
+    pub fn validate(&self) -> Result<()> {
+        if let Some(mode) = &self.mode {
+            if !["peer", "client", "router"].contains(&mode.as_str()) {
+                bail!(
+                    ErrorKind::ConfigurationError,
+                    "Unknown Zenoh mode < {} >, expected: peer, client or router",
+                    mode
+                );
+            }
+        }
+
+        let tls = self.tls.clone().unwrap_or_default();
+        for locator in &self.listen {
+            if TLS_PROTOCOLS.contains(&check_locator(locator)?)
+                && (tls.server_private_key.is_none() || tls.server_certificate.is_none())
+            {
+                bail!(
+                    ErrorKind::ConfigurationError,
+                    "Listening on < {} > requires the `server_private_key` and the \
+                     `server_certificate`",
+                    locator
+                );
+            }
+        }
+
+        for locator in &self.connect {
+            if TLS_PROTOCOLS.contains(&check_locator(locator)?) && tls.root_ca_certificate.is_none()
+            {
+                bail!(
+                    ErrorKind::ConfigurationError,
+                    "Connecting to < {} > requires the `root_ca_certificate`",
+                    locator
+                );
+            }
+        }
+
+        if tls.client_auth == Some(true)
+            && (tls.client_private_key.is_none() || tls.client_certificate.is_none())
+        {
+            bail!(
+                ErrorKind::ConfigurationError,
+                "`client_auth` requires the `client_private_key` and the `client_certificate`"
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Validates the transport configuration and applies it on top of the Zenoh `config`.
+    ///
+    /// # Errors
+    ///
+    /// A `ConfigurationError` is returned if the transport configuration is not valid (see
+    /// [TransportDescriptor::validate]) or if Zenoh rejects it.
+    pub fn apply(&self, config: &mut Config) -> Result<()> {
+        self.validate()?;
+
+        if let Some(mode) = &self.mode {
+            insert(config, "mode", mode)?;
+        }
+        if !self.listen.is_empty() {
+            insert(config, "listen/endpoints", &self.listen)?;
+        }
+        if !self.connect.is_empty() {
+            insert(config, "connect/endpoints", &self.connect)?;
+        }
+        if let Some(enabled) = self.multicast_scouting {
+            insert(config, "scouting/multicast/enabled", &enabled)?;
+        }
+
+        if let Some(tls) = &self.tls {
+            let certificates = [
+                ("root_ca_certificate", &tls.root_ca_certificate),
+                ("server_private_key", &tls.server_private_key),
+                ("server_certificate", &tls.server_certificate),
+                ("client_private_key", &tls.client_private_key),
+                ("client_certificate", &tls.client_certificate),
+            ];
+            for (key, path) in certificates {
+                if let Some(path) = path {
+                    insert(config, &format!("transport/link/tls/{key}"), path)?;
+                }
+            }
+            if let Some(client_auth) = tls.client_auth {
+                insert(config, "transport/link/tls/client_auth", &client_auth)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the default Zenoh configuration with the transport configuration applied.
+    ///
+    /// # Errors
+    ///
+    /// See [TransportDescriptor::apply].
+    pub fn to_config(&self) -> Result<Config> {
+        let mut config = Config::default();
+        self.apply(&mut config)?;
+        Ok(config)
+    }
+}
+
+/// Checks that the `locator` has a known protocol and a valid address, returning its protocol.
+///
+/// # Errors
+///
+/// A `ConfigurationError` is returned if the protocol is not known, if the address is missing or,
+/// for the protocols over IP, if the port is missing or if an IPv6 address is not enclosed in
+/// brackets.
+pub fn check_locator(locator: &str) -> Result<&str> {
+    let (protocol, address) = locator.split_once('/').ok_or_else(|| {
+        zferror!(
+            ErrorKind::ConfigurationError,
+            "Locator < {} > is not of the form `protocol/address`",
+            locator
+        )
+    })?;
+
+    if !PROTOCOLS.contains(&protocol) {
+        bail!(
+            ErrorKind::ConfigurationError,
+            "Locator < {} > has an unknown protocol, expected one of: {}",
+            locator,
+            PROTOCOLS.join(", ")
+        );
+    }
+
+    // The metadata (`?key=value`) and the configuration (`#key=value`) follow the address.
+    let address = address
+        .split(|c| c == '?' || c == '#')
+        .next()
+        .unwrap_or_default();
+    if address.is_empty() {
+        bail!(
+            ErrorKind::ConfigurationError,
+            "Locator < {} > has no address",
+            locator
+        );
+    }
+
+    if matches!(protocol, "serial" | "unixsock-stream") {
+        return Ok(protocol);
+    }
+
+    let (host, port) = match address.strip_prefix('[') {
+        Some(ipv6) => {
+            let (host, port) = ipv6.split_once(']').ok_or_else(|| {
+                zferror!(
+                    ErrorKind::ConfigurationError,
+                    "Locator < {} > has an unterminated IPv6 address",
+                    locator
+                )
+            })?;
+            if host.parse::<std::net::Ipv6Addr>().is_err() {
+                bail!(
+                    ErrorKind::ConfigurationError,
+                    "Locator < {} > has an invalid IPv6 address",
+                    locator
+                );
+            }
+            (host, port.strip_prefix(':').unwrap_or_default())
+        }
+        None => match address.rsplit_once(':') {
+            Some((host, _)) if host.contains(':') => bail!(
+                ErrorKind::ConfigurationError,
+                "Locator < {} > has an IPv6 address that is not enclosed in brackets",
+                locator
+            ),
+            Some((host, port)) => (host, port),
+            None => (address, ""),
+        },
+    };
+
+    if host.is_empty() || port.parse::<u16>().is_err() {
+        bail!(
+            ErrorKind::ConfigurationError,
+            "Locator < {} > is not of the form `{}/host:port`",
+            locator,
+            protocol
+        );
+    }
+
+    Ok(protocol)
+}
+
+/// Inserts the JSON representation of `value` at `key` in the Zenoh `config`.
+fn insert<T: Serialize + ?Sized>(config: &mut Config, key: &str, value: &T) -> Result<()> {
+    let value =
+        serde_json::to_string(value).map_err(|e| zferror!(ErrorKind::SerializationError, e))?;
+    config.insert_json5(key, &value).map_err(|e| {
+        zferror!(
+            ErrorKind::ConfigurationError,
+            "Zenoh rejected < {} > for < {} >: {:?}",
+            value,
+            key,
+            e
+        )
+        .into()
+    })
+}
+
+#[cfg(test)]
+#[path = "./tests/transport.rs"]
+mod tests;
@@ -0,0 +1,207 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::bail;
+use crate::zfresult::{ErrorKind, ZFResult as Result};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::f64::consts::PI;
+use std::str::FromStr;
+
+/// A unit known to Zenoh-Flow: its symbol, the quantity it measures and how a value expressed in
+/// it is converted to the SI unit of that quantity (`value * scale + offset`).
+struct KnownUnit {
+    symbol: &'static str,
+    quantity: &'static str,
+    scale: f64,
+    offset: f64,
+}
+
+const fn known(symbol: &'static str, quantity: &'static str, scale: f64) -> KnownUnit {
+    KnownUnit {
+        symbol,
+        quantity,
+        scale,
+        offset: 0.0,
+    }
+}
+
+/// The units between which a conversion is known.
+const KNOWN_UNITS: &[KnownUnit] = &[
+    known("m", "length", 1.0),
+    known("km", "length", 1_000.0),
+    known("cm", "length", 0.01),
+    known("mm", "length", 0.001),
+    known("ft", "length", 0.3048),
+    known("in", "length", 0.0254),
+    known("mi", "length", 1_609.344),
+    known("m/s", "speed", 1.0),
+    known("km/h", "speed", 1_000.0 / 3_600.0),
+    known("mph", "speed", 0.44704),
+    known("kn", "speed", 1_852.0 / 3_600.0),
+    known("m/s^2", "acceleration", 1.0),
+    known("rad", "angle", 1.0),
+    known("deg", "angle", PI / 180.0),
+    known("rad/s", "angular velocity", 1.0),
+    known("deg/s", "angular velocity", PI / 180.0),
+    known("rpm", "angular velocity", 2.0 * PI / 60.0),
+    known("s", "time", 1.0),
+    known("min", "time", 60.0),
+    known("h", "time", 3_600.0),
+    known("ms", "time", 1e-3),
+    known("us", "time", 1e-6),
+    known("ns", "time", 1e-9),
+    known("kg", "mass", 1.0),
+    known("g", "mass", 1e-3),
+    known("lb", "mass", 0.453_592_37),
+    known("Pa", "pressure", 1.0),
+    known("kPa", "pressure", 1e3),
+    known("bar", "pressure", 1e5),
+    known("psi", "pressure", 6_894.757_293_168),
+    known("Hz", "frequency", 1.0),
+    known("kHz", "frequency", 1e3),
+    known("K", "temperature", 1.0),
+    KnownUnit {
+        symbol: "degC",
+        quantity: "temperature",
+        scale: 1.0,
+        offset: 273.15,
+    },
+    KnownUnit {
+        symbol: "degF",
+        quantity: "temperature",
+        scale: 5.0 / 9.0,
+        offset: 273.15 - 32.0 * 5.0 / 9.0,
+    },
+];
+
+/// The physical unit of the values sent, or expected, on a port, e.g. `m/s` or `deg`.
+///
+/// The units are declared, per port, by the components:
+///
+/// ```yaml
+/// id: Controller
+/// uri: file://./target/release/libcontroller.so
+/// inputs: [Speed]
+/// outputs: [Steering]
+/// input_units:
+///   Speed: m/s
+/// output_units:
+///   Steering: deg
+/// ```
+///
+/// A link connecting ports of different units is invalid, unless a conversion between them is known
+/// (e.g. from `km/h` to `m/s`): a builtin Convert operator is then inserted on the link when the
+/// data flow is flattened. A port without a unit is compatible with all the others.
+///
+/// A unit applies to the whole value sent on the port: the payloads converted by the builtin
+/// Convert must be a single JSON number, hence a link whose ports declare a data type cannot be
+/// converted.
+///
+/// The known units are, per quantity:
+/// - length: `m`, `km`, `cm`, `mm`, `ft`, `in`, `mi`
+/// - speed: `m/s`, `km/h`, `mph`, `kn`
+/// - acceleration: `m/s^2`
+/// - angle: `rad`, `deg`
+/// - angular velocity: `rad/s`, `deg/s`, `rpm`
+/// - time: `s`, `min`, `h`, `ms`, `us`, `ns`
+/// - mass: `kg`, `g`, `lb`
+/// - pressure: `Pa`, `kPa`, `bar`, `psi`
+/// - frequency: `Hz`, `kHz`
+/// - temperature: `K`, `degC`, `degF`
+///
+/// Other units can be declared, they are then only compatible with themselves.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Unit(String);
+
+impl Unit {
+    fn known(&self) -> Option<&'static KnownUnit> {
+        KNOWN_UNITS.iter().find(|unit| unit.symbol == self.0)
+    }
+
+    /// Returns the quantity measured by the unit (e.g. `speed` for `m/s`), if it is known.
+    pub fn quantity(&self) -> Option<&'static str> {
+        self.known().map(|unit| unit.quantity)
+    }
+
+    /// Returns the conversion of the values expressed in `self` to `other`, if both units are
+    /// known and measure the same quantity.
+    pub fn conversion_to(&self, other: &Unit) -> Option<UnitConversion> {
+        let (from, to) = (self.known()?, other.known()?);
+        if from.quantity != to.quantity {
+            return None;
+        }
+
+        Some(UnitConversion {
+            scale: from.scale / to.scale,
+            offset: (from.offset - to.offset) / to.scale,
+        })
+    }
+}
+
+impl std::fmt::Display for Unit {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for Unit {
+    type Err = crate::zfresult::Error;
+
+    /// Parses a unit, its surrounding spaces are ignored.
+    ///
+    /// # Errors
+    /// An error variant is returned if the unit is empty.
+    fn from_str(unit: &str) -> Result<Self> {
+        let unit = unit.trim();
+        if unit.is_empty() {
+            bail!(ErrorKind::ParsingError, "A unit cannot be empty");
+        }
+
+        Ok(Self(unit.to_string()))
+    }
+}
+
+impl TryFrom<String> for Unit {
+    type Error = crate::zfresult::Error;
+
+    fn try_from(unit: String) -> Result<Self> {
+        unit.parse()
+    }
+}
+
+impl From<Unit> for String {
+    fn from(unit: Unit) -> Self {
+        unit.0
+    }
+}
+
+/// The conversion of a value from a unit to another: `value * scale + offset`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UnitConversion {
+    pub scale: f64,
+    pub offset: f64,
+}
+
+impl UnitConversion {
+    /// Returns the `value` converted.
+    pub fn apply(&self, value: f64) -> f64 {
+        value * self.scale + self.offset
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/unit.rs"]
+mod tests;
@@ -0,0 +1,153 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::zfresult::{ErrorKind, ZFResult as Result};
+use crate::{bail, zferror};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The location of the implementation of a node: either a single URI or, for a data flow deployed
+/// on daemons of different architectures, one URI per `os/arch`.
+///
+/// The architectures follow the naming of the
+/// [`RegistryNodeArchitecture`](crate::model::registry::RegistryNodeArchitecture), i.e. the
+/// [`OS`](std::env::consts::OS) and [`ARCH`](std::env::consts::ARCH) of the daemon:
+///
+/// ```yaml
+/// id: Detector
+/// uri:
+///   linux/x86_64: file:///opt/nodes/x86_64/libdetector.so
+///   linux/aarch64: file:///opt/nodes/aarch64/libdetector.so
+/// inputs: [Frame]
+/// outputs: [Detections]
+/// ```
+///
+/// The daemon loading the node picks the URI of its own architecture.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum NodeUri {
+    Uri(String),
+    PerArchitecture(BTreeMap<String, String>),
+}
+
+impl NodeUri {
+    /// Returns the architecture of the current process, as `os/arch` (e.g. `linux/x86_64`).
+    pub fn current_architecture() -> String {
+        format!("{}/{}", std::env::consts::OS, std::env::consts::ARCH)
+    }
+
+    /// Returns the URI of the node for the architecture `os/arch`, if there is one.
+    pub fn for_architecture(&self, architecture: &str) -> Option<&str> {
+        match self {
+            NodeUri::Uri(uri) => Some(uri.as_str()),
+            NodeUri::PerArchitecture(uris) => uris.get(architecture).map(|uri| uri.as_str()),
+        }
+    }
+
+    /// Returns the URI of the node for the architecture of the current process.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if no URI is declared for the current architecture.
+    pub fn resolve(&self) -> Result<&str> {
+        let architecture = Self::current_architecture();
+        self.for_architecture(&architecture).ok_or_else(|| {
+            zferror!(
+                ErrorKind::LoadingError,
+                "No URI for the architecture < {} >, only for: {}",
+                architecture,
+                self.architectures().join(", ")
+            )
+            .into()
+        })
+    }
+
+    /// Returns all the URIs of the node, whatever their architecture.
+    pub fn uris(&self) -> Vec<&str> {
+        match self {
+            NodeUri::Uri(uri) => vec![uri.as_str()],
+            NodeUri::PerArchitecture(uris) => uris.values().map(|uri| uri.as_str()).collect(),
+        }
+    }
+
+    /// Returns the architectures for which a URI is declared, empty for a single URI.
+    pub fn architectures(&self) -> Vec<&str> {
+        match self {
+            NodeUri::Uri(_) => vec![],
+            NodeUri::PerArchitecture(uris) => uris.keys().map(|key| key.as_str()).collect(),
+        }
+    }
+
+    /// Checks that each architecture is of the form `os/arch` and that at least one is declared.
+    ///
+    /// # Errors
+    ///
+    /// An error variant is returned if an architecture is malformed or if there is none.
+    pub fn validate(&self) -> Result<()> {
+        if let NodeUri::PerArchitecture(uris) = self {
+            if uris.is_empty() {
+                bail!(
+                    ErrorKind::ParsingError,
+                    "A URI per architecture requires at least one architecture"
+                );
+            }
+
+            for architecture in uris.keys() {
+                match architecture.split_once('/') {
+                    Some((os, arch))
+                        if !os.is_empty() && !arch.is_empty() && !arch.contains('/') => {}
+                    _ => bail!(
+                        ErrorKind::ParsingError,
+                        "Architecture < {} > is not of the form `os/arch`",
+                        architecture
+                    ),
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for NodeUri {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            NodeUri::Uri(uri) => write!(f, "{uri}"),
+            NodeUri::PerArchitecture(uris) => write!(
+                f,
+                "{{{}}}",
+                uris.iter()
+                    .map(|(architecture, uri)| format!("{architecture}: {uri}"))
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+impl From<String> for NodeUri {
+    fn from(uri: String) -> Self {
+        NodeUri::Uri(uri)
+    }
+}
+
+impl From<&str> for NodeUri {
+    fn from(uri: &str) -> Self {
+        NodeUri::Uri(uri.to_string())
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/uri.rs"]
+mod tests;
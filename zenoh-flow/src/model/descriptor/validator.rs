@@ -12,7 +12,9 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
-use crate::model::descriptor::{FlattenDataFlowDescriptor, InputDescriptor, OutputDescriptor};
+use crate::model::descriptor::{
+    FlattenDataFlowDescriptor, InputDescriptor, OutputDescriptor, PortType, Unit,
+};
 use crate::types::{NodeId, PortId};
 use crate::zferror;
 use crate::zfresult::ErrorKind;
@@ -29,7 +31,7 @@ use std::sync::Arc;
 /// - each node has a unique id,
 /// - each port (input and output) is connected,
 /// - an input port is connected only once (i.e. it receives data from a single output port),
-/// - connected ports are declared with the same type.
+/// - connected ports are declared with compatible data types (see [PortType]).
 ///
 /// To perform these verifications, two directed `petgraph` graphs are created: `node_checker` and
 /// `graph_checker`.
@@ -50,6 +52,8 @@ use std::sync::Arc;
 /// - `map_id_to_node_checker_idx` maps the `(NodeId, PortId, PortKind)` to the indexes in
 ///   `node_checker`,
 /// - `map_id_to_graph_checker_idx` maps the `NodeId` to the indexes in `graph_checker`,
+/// - `data_types` stores the data types declared for the ports,
+/// - `units` stores the units declared for the ports,
 /// - `loops_node_ids` stores the ids of the nodes involved in loops (ingress and egress).
 ///
 /// Additional verifications are performed calling:
@@ -64,6 +68,8 @@ pub(crate) struct DataFlowValidator {
     output_indexes: HashSet<NodeIndex>,
     map_id_to_node_checker_idx: HashMap<PortUniqueId, NodeIndex>,
     map_id_to_graph_checker_idx: HashMap<NodeId, (NodeKind, NodeIndex)>,
+    data_types: HashMap<PortUniqueId, PortType>,
+    units: HashMap<PortUniqueId, Unit>,
 }
 
 /// Type of a Port, either Input or Output.
@@ -73,6 +79,15 @@ enum PortKind {
     Output,
 }
 
+impl std::fmt::Display for PortKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PortKind::Input => write!(f, "Input"),
+            PortKind::Output => write!(f, "Output"),
+        }
+    }
+}
+
 /// The type of a Node.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 enum NodeKind {
@@ -106,7 +121,12 @@ impl TryFrom<&FlattenDataFlowDescriptor> for DataFlowValidator {
             .try_for_each(|source| validator.try_add_source(source.id.clone(), &source.outputs))?;
 
         descriptor.operators.iter().try_for_each(|operator| {
-            validator.try_add_operator(operator.id.clone(), &operator.inputs, &operator.outputs)
+            validator.try_add_operator(
+                operator.id.clone(),
+                &operator.inputs,
+                &operator.outputs,
+                &operator.side_outputs,
+            )
         })?;
 
         descriptor
@@ -114,11 +134,60 @@ impl TryFrom<&FlattenDataFlowDescriptor> for DataFlowValidator {
             .iter()
             .try_for_each(|sink| validator.try_add_sink(sink.id.clone(), &sink.inputs))?;
 
+        descriptor.operators.iter().try_for_each(|operator| {
+            validator.try_add_input_policies(&operator.id, operator.input_policies.keys())
+        })?;
+
+        descriptor.sinks.iter().try_for_each(|sink| {
+            validator.try_add_input_policies(&sink.id, sink.input_policies.keys())
+        })?;
+
+        descriptor.sources.iter().try_for_each(|source| {
+            validator.try_add_data_types(&source.id, PortKind::Output, &source.output_types)
+        })?;
+
+        descriptor.operators.iter().try_for_each(|operator| {
+            validator.try_add_data_types(&operator.id, PortKind::Input, &operator.input_types)?;
+            validator.try_add_data_types(&operator.id, PortKind::Output, &operator.output_types)
+        })?;
+
+        descriptor.sinks.iter().try_for_each(|sink| {
+            validator.try_add_data_types(&sink.id, PortKind::Input, &sink.input_types)
+        })?;
+
+        descriptor.sources.iter().try_for_each(|source| {
+            validator.try_add_units(&source.id, PortKind::Output, &source.output_units)
+        })?;
+
+        descriptor.operators.iter().try_for_each(|operator| {
+            validator.try_add_units(&operator.id, PortKind::Input, &operator.input_units)?;
+            validator.try_add_units(&operator.id, PortKind::Output, &operator.output_units)
+        })?;
+
+        descriptor.sinks.iter().try_for_each(|sink| {
+            validator.try_add_units(&sink.id, PortKind::Input, &sink.input_units)
+        })?;
+
         descriptor
             .links
             .iter()
             .try_for_each(|link| validator.try_add_link(&link.from, &link.to))?;
 
+        descriptor
+            .exposed
+            .iter()
+            .try_for_each(|output| validator.try_expose(output))?;
+
+        descriptor
+            .imported
+            .iter()
+            .try_for_each(|input| validator.try_import(input))?;
+
+        descriptor
+            .redaction
+            .iter()
+            .try_for_each(|rule| validator.try_redact(&rule.output))?;
+
         Ok(validator)
     }
 }
@@ -133,6 +202,8 @@ impl DataFlowValidator {
             map_id_to_node_checker_idx: HashMap::new(),
             map_id_to_graph_checker_idx: HashMap::new(),
             node_checker: Graph::new(),
+            data_types: HashMap::new(),
+            units: HashMap::new(),
         }
     }
 
@@ -210,6 +281,15 @@ impl DataFlowValidator {
         Ok(())
     }
 
+    /// Adds a side output: contrary to an output, it is not required to be connected.
+    ///
+    /// # Errors
+    /// It can fail when calling `try_add_node`.
+    pub(crate) fn try_add_side_output(&mut self, node_id: NodeId, output: PortId) -> ZFResult<()> {
+        self.try_add_node(node_id, output, PortKind::Output)?;
+        Ok(())
+    }
+
     /// Adds a source
     ///
     /// #Errors
@@ -237,13 +317,14 @@ impl DataFlowValidator {
     /// Adds an operator
     ///
     /// # Errors
-    /// It can fail when calling `try_add_output`, `try_add_id`
+    /// It can fail when calling `try_add_output`, `try_add_side_output`, `try_add_id`
     /// or `try_add_input`.
     pub(crate) fn try_add_operator(
         &mut self,
         node_id: NodeId,
         inputs: &[PortId],
         outputs: &[PortId],
+        side_outputs: &[PortId],
     ) -> ZFResult<()> {
         self.try_add_id(NodeKind::Operator, node_id.clone())?;
 
@@ -253,10 +334,102 @@ impl DataFlowValidator {
 
         outputs
             .iter()
-            .try_for_each(|output| self.try_add_output(node_id.clone(), output.clone()))
+            .try_for_each(|output| self.try_add_output(node_id.clone(), output.clone()))?;
+
+        side_outputs
+            .iter()
+            .try_for_each(|output| self.try_add_side_output(node_id.clone(), output.clone()))
+    }
+
+    /// Checks that the inputs for which a policy is set are declared by the node.
+    ///
+    /// # Errors
+    /// An error variant is returned if an input is not declared.
+    pub(crate) fn try_add_input_policies<'a>(
+        &self,
+        node_id: &NodeId,
+        mut inputs: impl Iterator<Item = &'a PortId>,
+    ) -> ZFResult<()> {
+        inputs.try_for_each(|input| {
+            let id = PortUniqueId {
+                node_id: node_id.clone(),
+                port_id: input.clone(),
+                kind: PortKind::Input,
+            };
+
+            if self.map_id_to_node_checker_idx.contains_key(&id) {
+                Ok(())
+            } else {
+                Err(self.port_not_found(&id))
+            }
+        })
     }
 
-    /// Adds a link, can fail if it does not find the ports.
+    /// Adds the data types declared for the ports of kind `kind` of the node `node_id`.
+    ///
+    /// # Errors
+    /// An error variant is returned if a port is not declared or if it declares a union without
+    /// variant.
+    fn try_add_data_types(
+        &mut self,
+        node_id: &NodeId,
+        kind: PortKind,
+        data_types: &HashMap<PortId, PortType>,
+    ) -> ZFResult<()> {
+        data_types.iter().try_for_each(|(port_id, data_type)| {
+            let id = PortUniqueId {
+                node_id: node_id.clone(),
+                port_id: port_id.clone(),
+                kind: kind.clone(),
+            };
+
+            if !self.map_id_to_node_checker_idx.contains_key(&id) {
+                return Err(self.port_not_found(&id));
+            }
+
+            if data_type.variants().is_empty() {
+                return Err(zferror!(
+                    ErrorKind::ConfigurationError,
+                    "The port < {}.{} > declares a union without variant",
+                    node_id,
+                    port_id
+                )
+                .into());
+            }
+
+            self.data_types.insert(id, data_type.clone());
+            Ok(())
+        })
+    }
+
+    /// Adds the units declared for the ports of kind `kind` of the node `node_id`.
+    ///
+    /// # Errors
+    /// An error variant is returned if a port is not declared.
+    fn try_add_units(
+        &mut self,
+        node_id: &NodeId,
+        kind: PortKind,
+        units: &HashMap<PortId, Unit>,
+    ) -> ZFResult<()> {
+        units.iter().try_for_each(|(port_id, unit)| {
+            let id = PortUniqueId {
+                node_id: node_id.clone(),
+                port_id: port_id.clone(),
+                kind: kind.clone(),
+            };
+
+            if !self.map_id_to_node_checker_idx.contains_key(&id) {
+                return Err(self.port_not_found(&id));
+            }
+
+            self.units.insert(id, unit.clone());
+            Ok(())
+        })
+    }
+
+    /// Adds a link, can fail if it does not find the ports or if they declare incompatible data
+    /// types or units.
     ///
     /// # Errors
     /// An error variant is returned if validation fails.
@@ -306,28 +479,145 @@ impl DataFlowValidator {
             })?;
         edge_weight.2 = edge_idx;
 
-        let from_node_checker_idx =
-            self.map_id_to_node_checker_idx
-                .get(&from_id)
-                .ok_or_else(|| {
-                    zferror!(ErrorKind::PortNotFound((
-                        from.node.clone(),
-                        from.output.clone()
-                    )))
-                })?;
-        let to_node_checker_idx = self.map_id_to_node_checker_idx.get(&to_id).ok_or_else(|| {
-            zferror!(ErrorKind::PortNotFound((to.node.clone(), to.input.clone())))
-        })?;
+        let from_node_checker_idx = self
+            .map_id_to_node_checker_idx
+            .get(&from_id)
+            .ok_or_else(|| self.port_not_found(&from_id))?;
+        let to_node_checker_idx = self
+            .map_id_to_node_checker_idx
+            .get(&to_id)
+            .ok_or_else(|| self.port_not_found(&to_id))?;
+
+        if let (Some(from_type), Some(to_type)) =
+            (self.data_types.get(&from_id), self.data_types.get(&to_id))
+        {
+            if !from_type.is_compatible_with(to_type) {
+                return Err(zferror!(
+                    ErrorKind::IncompatibleDataTypes((
+                        (from.node.clone(), from.output.clone()),
+                        (to.node.clone(), to.input.clone())
+                    )),
+                    "Output < {}.{} > sends < {} > but input < {}.{} > expects < {} >",
+                    from.node,
+                    from.output,
+                    from_type,
+                    to.node,
+                    to.input,
+                    to_type
+                )
+                .into());
+            }
+        }
+
+        if let (Some(from_unit), Some(to_unit)) = (self.units.get(&from_id), self.units.get(&to_id))
+        {
+            if from_unit != to_unit {
+                let hint = if from_unit.conversion_to(to_unit).is_some() {
+                    "they can be converted by a builtin Convert operator, inserted when a data flow descriptor is flattened"
+                } else {
+                    "no conversion is known between them"
+                };
+                return Err(zferror!(
+                    ErrorKind::IncompatibleUnits((
+                        (from.node.clone(), from.output.clone()),
+                        (to.node.clone(), to.input.clone())
+                    )),
+                    "Output < {}.{} > sends values in < {} > but input < {}.{} > expects < {} >: {}",
+                    from.node,
+                    from.output,
+                    from_unit,
+                    to.node,
+                    to.input,
+                    to_unit,
+                    hint
+                )
+                .into());
+            }
+        }
 
         self.node_checker
             .add_edge(*from_node_checker_idx, *to_node_checker_idx, ());
         Ok(())
     }
 
+    /// Exposes an output: like a side output, it is not required to be connected.
+    ///
+    /// # Errors
+    /// An error variant is returned if the output does not exist.
+    pub(crate) fn try_expose(&mut self, output: &OutputDescriptor) -> ZFResult<()> {
+        let id = PortUniqueId {
+            node_id: output.node.clone(),
+            port_id: output.output.clone(),
+            kind: PortKind::Output,
+        };
+        let node_checker_idx = self
+            .map_id_to_node_checker_idx
+            .get(&id)
+            .ok_or_else(|| self.port_not_found(&id))?;
+        self.output_indexes.remove(node_checker_idx);
+        Ok(())
+    }
+
+    /// Checks that an output having a redaction rule exists: a rule that does not apply would let
+    /// the payloads it should redact leave the data flow.
+    ///
+    /// # Errors
+    /// An error variant is returned if the output does not exist.
+    pub(crate) fn try_redact(&self, output: &OutputDescriptor) -> ZFResult<()> {
+        let id = PortUniqueId {
+            node_id: output.node.clone(),
+            port_id: output.output.clone(),
+            kind: PortKind::Output,
+        };
+        if !self.map_id_to_node_checker_idx.contains_key(&id) {
+            return Err(self.port_not_found(&id));
+        }
+        Ok(())
+    }
+
+    /// Imports an input: it can be connected to an output of another instance, hence it is not
+    /// required to be connected.
+    ///
+    /// # Errors
+    /// An error variant is returned if the input does not exist.
+    pub(crate) fn try_import(&mut self, input: &InputDescriptor) -> ZFResult<()> {
+        let id = PortUniqueId {
+            node_id: input.node.clone(),
+            port_id: input.input.clone(),
+            kind: PortKind::Input,
+        };
+        let node_checker_idx = self
+            .map_id_to_node_checker_idx
+            .get(&id)
+            .ok_or_else(|| self.port_not_found(&id))?;
+        self.input_indexes.remove(node_checker_idx);
+        Ok(())
+    }
+
+    /// Returns the error reporting that the port `id` is not declared by its node, suggesting the
+    /// declared port of the same kind with the closest name, if any.
+    fn port_not_found(&self, id: &PortUniqueId) -> crate::zfresult::Error {
+        let declared = self
+            .map_id_to_node_checker_idx
+            .keys()
+            .filter(|port| port.node_id == id.node_id && port.kind == id.kind)
+            .map(|port| &port.port_id);
+
+        zferror!(
+            ErrorKind::PortNotFound((id.node_id.clone(), id.port_id.clone())),
+            "{} < {} > not declared by node < {} >{}",
+            id.kind,
+            id.port_id,
+            id.node_id,
+            did_you_mean(&id.port_id, declared)
+        )
+        .into()
+    }
+
     /// Validate that all ports respect the constraints.
     ///
-    /// - an input port has at least one incoming link,
-    /// - an output port has at least one outgoing link.
+    /// - an input port has at least one incoming link, imported inputs excepted,
+    /// - an output port has at least one outgoing link, side and exposed outputs excepted.
     ///
     /// A link is represented by an "edge" in Petgraph vocabulary.
     ///
@@ -341,7 +631,9 @@ impl DataFlowValidator {
                 .count()
             {
                 0 => {
-                    let port = self.node_checker.node_weight(*idx).unwrap();
+                    let port = self.node_checker.node_weight(*idx).ok_or_else(|| {
+                        zferror!(ErrorKind::NotFound, "Port at index {:?} not found", idx)
+                    })?;
                     Err(zferror!(ErrorKind::PortNotConnected((
                         port.node_id.clone(),
                         port.port_id.clone(),
@@ -358,7 +650,9 @@ impl DataFlowValidator {
                 .count()
             {
                 0 => {
-                    let port = self.node_checker.node_weight(*idx).unwrap();
+                    let port = self.node_checker.node_weight(*idx).ok_or_else(|| {
+                        zferror!(ErrorKind::NotFound, "Port at index {:?} not found", idx)
+                    })?;
                     Err(zferror!(ErrorKind::PortNotConnected((
                         port.node_id.clone(),
                         port.port_id.clone()
@@ -370,3 +664,74 @@ impl DataFlowValidator {
         })
     }
 }
+
+/// Checks the ports declared, in the descriptor, for the node `node_id` against the ports declared
+/// by its component in the registry: they must be the same.
+///
+/// # Errors
+/// An error variant is returned if a port is declared by only one of them. If the port declared by
+/// the descriptor is a likely typo, the port of the component with the closest name is suggested.
+pub(crate) fn validate_registered_ports(
+    node_id: &NodeId,
+    kind: &str,
+    declared: &[PortId],
+    registered: &[PortId],
+) -> ZFResult<()> {
+    if let Some(port) = declared.iter().find(|port| !registered.contains(port)) {
+        return Err(zferror!(
+            ErrorKind::PortNotFound((node_id.clone(), port.clone())),
+            "{} < {} > of node < {} > not declared by its component{}",
+            kind,
+            port,
+            node_id,
+            did_you_mean(port, registered.iter())
+        )
+        .into());
+    }
+
+    if let Some(port) = registered.iter().find(|port| !declared.contains(port)) {
+        return Err(zferror!(
+            ErrorKind::PortNotFound((node_id.clone(), port.clone())),
+            "{} < {} > of the component of node < {} > not declared by the descriptor",
+            kind,
+            port,
+            node_id
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Returns a suggestion, to append to an error message, if one of the `candidates` is close enough
+/// to `port` to make a typo likely.
+fn did_you_mean<'a>(port: &str, candidates: impl Iterator<Item = &'a PortId>) -> String {
+    // Allow roughly one edit every three characters, and at least one.
+    let threshold = std::cmp::max(1, port.chars().count() / 3);
+
+    candidates
+        .map(|candidate| (edit_distance(port, candidate), candidate))
+        .filter(|(distance, _)| *distance <= threshold)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| format!("; did you mean < {candidate} >?"))
+        .unwrap_or_default()
+}
+
+/// Returns the Levenshtein distance between `a` and `b`, ignoring the case.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, char_a) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, char_b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(char_a != char_b);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
@@ -24,6 +24,8 @@ pub mod registry;
 #[derive(Debug)]
 pub(crate) enum Middleware {
     Zenoh,
+    Http,
+    Host,
 }
 
 impl FromStr for Middleware {
@@ -31,19 +33,72 @@ impl FromStr for Middleware {
 
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
         let s = s.to_lowercase();
-        if s == "zenoh" {
-            return Ok(Self::Zenoh);
+        match s.as_str() {
+            "zenoh" => Ok(Self::Zenoh),
+            "http" => Ok(Self::Http),
+            "host" => Ok(Self::Host),
+            _ => bail!(
+                ErrorKind::ParsingError,
+                "Unsupported middleware: '{s}'. Currently supported middlewares: 'zenoh', 'http', 'host'."
+            ),
         }
-        bail!(
-            ErrorKind::ParsingError,
-            "Unsupported middleware: '{s}'. Currently supported middlewares: 'zenoh'."
-        )
     }
 }
 
 impl ToString for Middleware {
     fn to_string(&self) -> String {
-        "zenoh".to_string()
+        match self {
+            Self::Zenoh => "zenoh".to_string(),
+            Self::Http => "http".to_string(),
+            Self::Host => "host".to_string(),
+        }
+    }
+}
+
+/// The operators provided by Zenoh-Flow.
+#[derive(Debug)]
+pub(crate) enum BuiltinOperator {
+    Convert,
+    Downsample,
+    Dedup,
+    Faults,
+    Merge,
+    Fmu,
+    Throttle,
+}
+
+impl FromStr for BuiltinOperator {
+    type Err = ZFError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let s = s.to_lowercase();
+        match s.as_str() {
+            "convert" => Ok(Self::Convert),
+            "downsample" => Ok(Self::Downsample),
+            "dedup" => Ok(Self::Dedup),
+            "faults" => Ok(Self::Faults),
+            "merge" => Ok(Self::Merge),
+            "fmu" => Ok(Self::Fmu),
+            "throttle" => Ok(Self::Throttle),
+            _ => bail!(
+                ErrorKind::ParsingError,
+                "Unsupported builtin operator: '{s}'. Currently supported operators: 'convert', 'downsample', 'dedup', 'faults', 'merge', 'fmu', 'throttle'."
+            ),
+        }
+    }
+}
+
+impl ToString for BuiltinOperator {
+    fn to_string(&self) -> String {
+        match self {
+            Self::Convert => "convert".to_string(),
+            Self::Downsample => "downsample".to_string(),
+            Self::Dedup => "dedup".to_string(),
+            Self::Faults => "faults".to_string(),
+            Self::Merge => "merge".to_string(),
+            Self::Fmu => "fmu".to_string(),
+            Self::Throttle => "throttle".to_string(),
+        }
     }
 }
 
@@ -51,5 +106,7 @@ impl ToString for Middleware {
 /// Zenoh-Flow's custom URI struct used for loading nodes.
 pub(crate) enum ZFUri {
     File(PathBuf),
+    Remote(url::Url),
     Builtin(Middleware),
+    BuiltinOperator(BuiltinOperator),
 }
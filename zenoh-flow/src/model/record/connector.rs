@@ -13,6 +13,7 @@
 //
 
 use super::link::PortRecord;
+use crate::model::descriptor::SpoolDescriptor;
 use crate::types::{NodeId, RuntimeId};
 use serde::{Deserialize, Serialize};
 
@@ -43,6 +44,32 @@ pub struct ZFConnectorRecord {
     pub shared_memory_element_size: Option<usize>,
     pub shared_memory_elements: Option<usize>,
     pub shared_memory_backoff: Option<u64>,
+    /// The number of messages kept by a sender to be retransmitted, requested by a receiver when
+    /// it detects a gap, see [`LinkDescriptor`](crate::model::descriptor::LinkDescriptor).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retransmission: Option<usize>,
+    /// The number of bytes a sender buffers while Zenoh is unavailable, see
+    /// [`LinkDescriptor`](crate::model::descriptor::LinkDescriptor).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outage_budget: Option<usize>,
+    /// The spool on disk in which a sender stores the messages it cannot publish, see
+    /// [`SpoolDescriptor`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spool: Option<SpoolDescriptor>,
+    /// The maximum size of the fragments the messages are split into, see
+    /// [`LinkDescriptor`](crate::model::descriptor::LinkDescriptor).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fragment_size: Option<usize>,
+    /// The size from which a sender compresses the messages it publishes, if they are not already
+    /// compressed, see
+    /// [`CompressionDescriptor`](crate::model::descriptor::CompressionDescriptor). The receivers
+    /// detect the compressed messages on their own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression: Option<usize>,
+    /// The name of the Zenoh session the connector uses instead of the one of the runtime, see
+    /// [`LinkDescriptor`](crate::model::descriptor::LinkDescriptor).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
 }
 
 impl std::fmt::Display for ZFConnectorRecord {
@@ -13,7 +13,10 @@
 //
 
 use crate::model::descriptor::{
-    FlattenDataFlowDescriptor, InputDescriptor, LinkDescriptor, OutputDescriptor,
+    AutoscalingDescriptor, CompressionDescriptor, ExpiryDescriptor, FlattenDataFlowDescriptor,
+    GpuDescriptor, InputDescriptor, LinkDescriptor, OutputDescriptor, PortType, PreemptionPolicy,
+    ReadinessDescriptor, RedactionDescriptor, RetentionDescriptor, TransportDescriptor,
+    TypeRegistry, WarmupDescriptor, WatchdogDescriptor,
 };
 use crate::model::record::connector::{ZFConnectorKind, ZFConnectorRecord};
 use crate::model::record::{LinkRecord, OperatorRecord, PortRecord, SinkRecord, SourceRecord};
@@ -22,9 +25,10 @@ use crate::zferror;
 use crate::zfresult::ErrorKind;
 use crate::Result as ZFResult;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 use std::hash::{Hash, Hasher};
+use std::time::Duration;
 use uuid::Uuid;
 
 /// A `DataFlowRecord` is an instance of a [`FlattenDataFlowDescriptor`](`FlattenDataFlowDescriptor`).
@@ -38,6 +42,48 @@ pub struct DataFlowRecord {
     pub connectors: HashMap<NodeId, ZFConnectorRecord>,
     pub links: Vec<LinkRecord>,
     pub counter: u32,
+    #[serde(default)]
+    pub max_run_durations: HashMap<NodeId, Duration>,
+    #[serde(default)]
+    pub credits: HashMap<NodeId, usize>,
+    #[serde(default)]
+    pub warmups: HashMap<NodeId, WarmupDescriptor>,
+    #[serde(default)]
+    pub cooldowns: HashMap<NodeId, Duration>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub gpus: HashMap<NodeId, GpuDescriptor>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub watchdogs: HashMap<NodeId, WatchdogDescriptor>,
+    #[serde(default)]
+    pub readiness: Option<ReadinessDescriptor>,
+    #[serde(default)]
+    pub priority: u32,
+    #[serde(default)]
+    pub preemption: PreemptionPolicy,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_timeout: Option<Duration>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiry: Option<ExpiryDescriptor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_clock_skew: Option<Duration>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redaction: Vec<RedactionDescriptor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retention: Option<RetentionDescriptor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression: Option<CompressionDescriptor>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub types: TypeRegistry,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub replicas: HashMap<NodeId, usize>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub autoscaling: HashMap<NodeId, AutoscalingDescriptor>,
+    #[serde(default)]
+    pub exposed: Vec<OutputDescriptor>,
+    #[serde(default)]
+    pub imported: Vec<InputDescriptor>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub sessions: HashMap<String, TransportDescriptor>,
 }
 
 impl DataFlowRecord {
@@ -86,6 +132,42 @@ impl DataFlowRecord {
         }
     }
 
+    /// Returns the runtimes the nodes and connectors are mapped to.
+    pub fn runtimes(&self) -> HashSet<RuntimeId> {
+        self.operators
+            .values()
+            .map(|operator| operator.runtime.clone())
+            .chain(self.sources.values().map(|source| source.runtime.clone()))
+            .chain(self.sinks.values().map(|sink| sink.runtime.clone()))
+            .chain(
+                self.connectors
+                    .values()
+                    .map(|connector| connector.runtime.clone()),
+            )
+            .collect()
+    }
+
+    /// Moves the nodes and connectors mapped to the runtime `from` to the runtime `to`.
+    ///
+    /// The links between the moved nodes and the nodes already running on `to` still go through
+    /// their connectors: they remain valid, the data being exchanged over Zenoh.
+    pub fn relocate(&mut self, from: &RuntimeId, to: &RuntimeId) {
+        let runtimes = self
+            .operators
+            .values_mut()
+            .map(|operator| &mut operator.runtime)
+            .chain(self.sources.values_mut().map(|source| &mut source.runtime))
+            .chain(self.sinks.values_mut().map(|sink| &mut sink.runtime))
+            .chain(
+                self.connectors
+                    .values_mut()
+                    .map(|connector| &mut connector.runtime),
+            );
+        for runtime in runtimes.filter(|runtime| **runtime == *from) {
+            *runtime = to.clone();
+        }
+    }
+
     /// Finds the uid of the given node.
     fn find_node_uid_by_id(&self, id: &NodeId) -> Option<u32> {
         if let Some(o) = self.operators.get(id) {
@@ -127,6 +209,32 @@ impl DataFlowRecord {
         None
     }
 
+    /// Finds the data type declared for the output `port_id` of the node `node_id`.
+    fn find_output_data_type(&self, node_id: &NodeId, port_id: &PortId) -> Option<PortType> {
+        let outputs = match self.operators.get(node_id) {
+            Some(operator) => &operator.outputs,
+            None => &self.sources.get(node_id)?.outputs,
+        };
+
+        outputs
+            .iter()
+            .find(|output| output.port_id == *port_id)
+            .and_then(|output| output.data_type.clone())
+    }
+
+    /// Finds the data type declared for the input `port_id` of the node `node_id`.
+    fn find_input_data_type(&self, node_id: &NodeId, port_id: &PortId) -> Option<PortType> {
+        let inputs = match self.operators.get(node_id) {
+            Some(operator) => &operator.inputs,
+            None => &self.sinks.get(node_id)?.inputs,
+        };
+
+        inputs
+            .iter()
+            .find(|input| input.port_id == *port_id)
+            .and_then(|input| input.data_type.clone())
+    }
+
     /// Adds the links.
     ///
     /// If the nodes are mapped to different machines it adds the couple of
@@ -189,16 +297,53 @@ impl DataFlowRecord {
 
                 // We only create a sender if none was created for the same resource. The rationale
                 // is to avoid creating multiple publisher for the same resource in case an operator
-                // acts as a multiplexor.
-                if !self.connectors.iter().any(|(_id, c)| {
+                // acts as a multiplexor. The existing sender then keeps enough messages to serve
+                // the retransmissions of all its receivers.
+                if let Some((_, sender)) = self.connectors.iter_mut().find(|(_id, c)| {
                     c.kind == ZFConnectorKind::Sender && c.resource == z_resource_name
                 }) {
+                    sender.retransmission = sender.retransmission.max(l.retransmission);
+                    sender.outage_budget = sender.outage_budget.max(l.outage_budget);
+                    if sender.spool.is_none() {
+                        sender.spool = l.spool.clone();
+                    }
+                    if sender.session != l.session {
+                        return Err(zferror!(
+                            ErrorKind::ConfigurationError,
+                            "The links from < {}.{} > must all use the same Zenoh session",
+                            l.from.node,
+                            l.from.output
+                        )
+                        .into());
+                    }
+                    if sender.fragment_size != l.fragment_size {
+                        return Err(zferror!(
+                            ErrorKind::ConfigurationError,
+                            "The links from < {}.{} > must all use the same fragment size",
+                            l.from.node,
+                            l.from.output
+                        )
+                        .into());
+                    }
+                } else {
                     // creating sender
                     let sender_id: NodeId = format!(
                         "sender-{}-{}-{}-{}",
                         &self.flow, &self.uuid, &l.from.node, &l.from.output
                     )
                     .into();
+                    let data_type = self.find_output_data_type(&l.from.node, &l.from.output);
+                    let compression =
+                        self.compression
+                            .as_ref()
+                            .filter(|compression| match &data_type {
+                                // A union is compressed if one of its variants is not already.
+                                Some(port_type) => port_type.variants().iter().any(|variant| {
+                                    compression.applies_to(Some(variant), &self.types)
+                                }),
+                                None => compression.applies_to(None, &self.types),
+                            })
+                            .map(CompressionDescriptor::min_size);
                     let sender = ZFConnectorRecord {
                         kind: ZFConnectorKind::Sender,
                         id: sender_id.clone(),
@@ -206,10 +351,17 @@ impl DataFlowRecord {
                         link_id: PortRecord {
                             uid: self.counter,
                             port_id: l.from.output.clone(),
+                            data_type,
                         },
                         shared_memory_element_size: l.shared_memory_element_size,
                         shared_memory_elements: l.shared_memory_elements,
                         shared_memory_backoff: l.shared_memory_backoff,
+                        retransmission: l.retransmission,
+                        outage_budget: l.outage_budget,
+                        spool: l.spool.clone(),
+                        fragment_size: l.fragment_size,
+                        compression,
+                        session: l.session.clone(),
                         runtime: from_runtime,
                     };
                     self.counter += 1;
@@ -224,6 +376,16 @@ impl DataFlowRecord {
                         shared_memory_element_size: l.shared_memory_element_size,
                         shared_memory_elements: l.shared_memory_elements,
                         shared_memory_backoff: l.shared_memory_backoff,
+                        sample: None,
+                        faults: None,
+                        merge: None,
+                        queue: None,
+                        capacity: None,
+                        retransmission: None,
+                        outage_budget: None,
+                        spool: None,
+                        fragment_size: None,
+                        session: None,
                     };
 
                     // storing info in the dataflow record
@@ -245,10 +407,17 @@ impl DataFlowRecord {
                     link_id: PortRecord {
                         uid: self.counter,
                         port_id: l.to.input.clone(),
+                        data_type: self.find_input_data_type(&l.to.node, &l.to.input),
                     },
                     shared_memory_element_size: l.shared_memory_element_size,
                     shared_memory_elements: l.shared_memory_elements,
                     shared_memory_backoff: l.shared_memory_backoff,
+                    retransmission: l.retransmission,
+                    outage_budget: None,
+                    spool: None,
+                    fragment_size: l.fragment_size,
+                    compression: None,
+                    session: l.session.clone(),
                     runtime: to_runtime,
                 };
                 self.counter += 1;
@@ -263,6 +432,16 @@ impl DataFlowRecord {
                     shared_memory_element_size: l.shared_memory_element_size,
                     shared_memory_elements: l.shared_memory_elements,
                     shared_memory_backoff: l.shared_memory_backoff,
+                    sample: None,
+                    faults: None,
+                    merge: None,
+                    queue: l.queue,
+                    capacity: l.capacity,
+                    retransmission: None,
+                    outage_budget: None,
+                    spool: None,
+                    fragment_size: None,
+                    session: None,
                 };
 
                 // storing info in the data flow record
@@ -276,6 +455,15 @@ impl DataFlowRecord {
     }
 }
 
+/// Returns the record of the port `port_id`, along with the data type declared for it in `types`.
+fn port_record(port_id: PortId, uid: u32, types: &HashMap<PortId, PortType>) -> PortRecord {
+    PortRecord {
+        uid,
+        data_type: types.get(&port_id).cloned(),
+        port_id,
+    }
+}
+
 impl TryFrom<(FlattenDataFlowDescriptor, Uuid)> for DataFlowRecord {
     type Error = crate::zfresult::Error;
 
@@ -289,7 +477,31 @@ impl TryFrom<(FlattenDataFlowDescriptor, Uuid)> for DataFlowRecord {
             sinks,
             links,
             mapping,
+            missing_runtime: _,
+            affinity: _,
             global_configuration: _,
+            max_run_durations,
+            credits,
+            warmups,
+            cooldowns,
+            gpus,
+            watchdogs,
+            readiness,
+            priority,
+            preemption,
+            idle_timeout,
+            expiry,
+            max_clock_skew,
+            redaction,
+            retention,
+            compression,
+            types,
+            replicas,
+            autoscaling,
+            exposed,
+            imported,
+            sessions,
+            feasibility: _,
         } = dataflow;
 
         let mapping = mapping.map_or(HashMap::new(), |m| m);
@@ -303,20 +515,41 @@ impl TryFrom<(FlattenDataFlowDescriptor, Uuid)> for DataFlowRecord {
             connectors: HashMap::new(),
             links: Vec::new(),
             counter: 0,
+            max_run_durations,
+            credits,
+            warmups,
+            cooldowns,
+            gpus,
+            watchdogs,
+            readiness,
+            priority,
+            preemption,
+            idle_timeout,
+            expiry,
+            max_clock_skew,
+            redaction,
+            retention,
+            compression,
+            types,
+            replicas,
+            autoscaling,
+            exposed,
+            imported,
+            sessions,
         };
 
         for o in operators.into_iter() {
             // Converting inputs
             let mut inputs: Vec<PortRecord> = vec![];
             for i in o.inputs {
-                inputs.push((i, dfr.counter).into());
+                inputs.push(port_record(i, dfr.counter, &o.input_types));
                 dfr.counter += 1;
             }
 
             // Converting outputs
             let mut outputs: Vec<PortRecord> = vec![];
-            for o in o.outputs {
-                outputs.push((o, dfr.counter).into());
+            for output in o.outputs.into_iter().chain(o.side_outputs) {
+                outputs.push(port_record(output, dfr.counter, &o.output_types));
                 dfr.counter += 1;
             }
 
@@ -325,6 +558,7 @@ impl TryFrom<(FlattenDataFlowDescriptor, Uuid)> for DataFlowRecord {
                 uid: dfr.counter,
                 inputs,
                 outputs,
+                input_policies: o.input_policies,
                 uri: o.uri,
                 configuration: o.configuration,
                 runtime: mapping
@@ -339,7 +573,7 @@ impl TryFrom<(FlattenDataFlowDescriptor, Uuid)> for DataFlowRecord {
         for s in sources.into_iter() {
             let mut outputs: Vec<PortRecord> = vec![];
             for o in s.outputs {
-                outputs.push((o, dfr.counter).into());
+                outputs.push(port_record(o, dfr.counter, &s.output_types));
                 dfr.counter += 1;
             }
 
@@ -361,7 +595,7 @@ impl TryFrom<(FlattenDataFlowDescriptor, Uuid)> for DataFlowRecord {
         for s in sinks.into_iter() {
             let mut inputs: Vec<PortRecord> = Vec::with_capacity(s.inputs.len());
             for i in s.inputs {
-                inputs.push((i, dfr.counter).into());
+                inputs.push(port_record(i, dfr.counter, &s.input_types));
                 dfr.counter += 1;
             }
 
@@ -369,6 +603,7 @@ impl TryFrom<(FlattenDataFlowDescriptor, Uuid)> for DataFlowRecord {
                 id: s.id.clone(),
                 uid: dfr.counter,
                 inputs,
+                input_policies: s.input_policies,
                 uri: s.uri,
                 configuration: s.configuration,
                 runtime: mapping
@@ -12,7 +12,9 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
-use crate::model::descriptor::{InputDescriptor, LinkDescriptor, OutputDescriptor};
+use crate::model::descriptor::{
+    InputDescriptor, LinkDescriptor, OutputDescriptor, PortType, QueueDescriptor,
+};
 use crate::types::PortId;
 use serde::{Deserialize, Serialize};
 
@@ -38,6 +40,10 @@ pub struct LinkRecord {
     pub shared_memory_element_size: Option<usize>,
     pub shared_memory_elements: Option<usize>,
     pub shared_memory_backoff: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queue: Option<QueueDescriptor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<usize>,
 }
 
 impl std::fmt::Display for LinkRecord {
@@ -57,6 +63,8 @@ impl From<(LinkDescriptor, u32)> for LinkRecord {
             shared_memory_element_size: desc.shared_memory_element_size,
             shared_memory_elements: desc.shared_memory_elements,
             shared_memory_backoff: desc.shared_memory_backoff,
+            queue: desc.queue,
+            capacity: desc.capacity,
         }
     }
 }
@@ -68,12 +76,17 @@ impl From<(LinkDescriptor, u32)> for LinkRecord {
 /// ```yaml
 /// id: Counter
 /// uid: 3
+/// data_type: my.company.Counter@1.0
 /// ```
+///
+/// The `data_type` is the one declared for the port by its node, if any (see [PortType]).
 #[derive(Debug, Serialize, Deserialize, Clone, Hash, PartialEq, Eq)]
 pub struct PortRecord {
     pub uid: u32,
     #[serde(alias = "id")]
     pub port_id: PortId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_type: Option<PortType>,
 }
 
 impl std::fmt::Display for PortRecord {
@@ -86,6 +99,10 @@ impl From<(PortId, u32)> for PortRecord {
     fn from(data: (PortId, u32)) -> Self {
         let (port_id, uid) = data;
 
-        Self { uid, port_id }
+        Self {
+            uid,
+            port_id,
+            data_type: None,
+        }
     }
 }
@@ -12,9 +12,11 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+use crate::model::descriptor::{InputPolicyDescriptor, NodeUri};
 use crate::model::record::PortRecord;
-use crate::types::{Configuration, NodeId, RuntimeId};
+use crate::types::{Configuration, NodeId, PortId, RuntimeId};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// A `SinkRecord` is an instance of a [`SinkDescriptor`](`crate::model::descriptor::SinkDescriptor`)
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -22,7 +24,9 @@ pub struct SinkRecord {
     pub id: NodeId,
     pub uid: u32,
     pub inputs: Vec<PortRecord>,
-    pub uri: Option<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub input_policies: HashMap<PortId, InputPolicyDescriptor>,
+    pub uri: Option<NodeUri>,
     pub configuration: Option<Configuration>,
     pub runtime: RuntimeId,
 }
@@ -39,7 +43,7 @@ pub struct SourceRecord {
     pub id: NodeId,
     pub uid: u32,
     pub outputs: Vec<PortRecord>,
-    pub uri: Option<String>,
+    pub uri: Option<NodeUri>,
     pub configuration: Option<Configuration>,
     pub runtime: RuntimeId,
 }
@@ -57,7 +61,9 @@ pub struct OperatorRecord {
     pub uid: u32,
     pub inputs: Vec<PortRecord>,
     pub outputs: Vec<PortRecord>,
-    pub uri: Option<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub input_policies: HashMap<PortId, InputPolicyDescriptor>,
+    pub uri: Option<NodeUri>,
     pub configuration: Option<Configuration>,
     pub runtime: RuntimeId,
 }
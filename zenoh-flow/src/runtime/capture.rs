@@ -0,0 +1,160 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Capture of the standard output and error of the nodes running in their own process.
+//!
+//! A node isolated in a process (e.g. a Python interpreter spawned by an extension) writes its
+//! `print` debugging on its own streams, which are lost if nobody reads them. The node hands the
+//! process to [`Context::capture_output`](crate::types::Context::capture_output): each line its
+//! streams carry is then logged by the runtime, tagged with the identifier of the node:
+//! - under the target `zenoh_flow::node::<node id>`, so that `RUST_LOG=zenoh_flow::node=info`
+//!   shows the output of all the nodes and `RUST_LOG=zenoh_flow::node::<node id>=info` the output
+//!   of one node,
+//! - at the level `Info` for the standard output and `Warn` for the standard error.
+//!
+//! The nodes loaded as shared libraries (Rust or C) run in the process of the runtime: they share
+//! its streams and their output cannot be told apart.
+
+use crate::types::NodeId;
+use std::io::{BufRead, BufReader, Read};
+use std::process::Child;
+use std::thread::JoinHandle;
+
+/// The prefix of the target under which the output of the nodes is logged.
+pub(crate) const NODE_LOG_TARGET: &str = "zenoh_flow::node";
+
+/// A stream of a node that is captured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+impl OutputStream {
+    /// Returns the level at which the lines written on the stream are logged.
+    pub(crate) fn level(&self) -> log::Level {
+        match self {
+            OutputStream::Stdout => log::Level::Info,
+            OutputStream::Stderr => log::Level::Warn,
+        }
+    }
+}
+
+impl std::fmt::Display for OutputStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            OutputStream::Stdout => write!(f, "stdout"),
+            OutputStream::Stderr => write!(f, "stderr"),
+        }
+    }
+}
+
+/// Spawns a thread logging, with `logger`, each line read from `reader`, the `stream` of the node
+/// `node_id`, until it is closed.
+///
+/// The handle of the thread returns the number of lines read. A line that is not valid UTF-8 is
+/// logged lossily.
+///
+/// # Errors
+///
+/// An error is returned if the thread could not be spawned.
+pub(crate) fn forward_output(
+    node_id: NodeId,
+    stream: OutputStream,
+    reader: impl Read + Send + 'static,
+    logger: &'static dyn log::Log,
+) -> std::io::Result<JoinHandle<usize>> {
+    std::thread::Builder::new()
+        .name(format!("{node_id}-{stream}"))
+        .spawn(move || {
+            let target = format!("{NODE_LOG_TARGET}::{node_id}");
+            let mut reader = BufReader::new(reader);
+            let mut line = Vec::new();
+            let mut count = 0;
+
+            loop {
+                line.clear();
+                match reader.read_until(b'\n', &mut line) {
+                    Ok(0) => break,
+                    Ok(_) => {
+                        let text = String::from_utf8_lossy(&line);
+                        let metadata = log::Metadata::builder()
+                            .target(&target)
+                            .level(stream.level())
+                            .build();
+                        if logger.enabled(&metadata) {
+                            logger.log(
+                                &log::Record::builder()
+                                    .metadata(metadata)
+                                    .args(format_args!(
+                                        "[{node_id}] {}",
+                                        text.trim_end_matches(&['\r', '\n'][..])
+                                    ))
+                                    .build(),
+                            );
+                        }
+                        count += 1;
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                    Err(e) => {
+                        log::error!("[Capture] The {stream} of < {node_id} > failed: {e}");
+                        break;
+                    }
+                }
+            }
+
+            log::trace!("[Capture] The {stream} of < {node_id} > was closed");
+            count
+        })
+}
+
+/// Captures the standard output and error of the `child` process running the node `node_id`, and
+/// logs them with the logger of the runtime.
+///
+/// Only the streams created with [`Stdio::piped`](std::process::Stdio::piped) are captured: they
+/// are taken from the `child` and read until the process closes them.
+///
+/// # Errors
+///
+/// An error is returned if a thread reading a stream could not be spawned.
+pub(crate) fn capture_output(
+    node_id: &NodeId,
+    child: &mut Child,
+) -> std::io::Result<Vec<JoinHandle<usize>>> {
+    let mut handles = Vec::with_capacity(2);
+
+    if let Some(stdout) = child.stdout.take() {
+        handles.push(forward_output(
+            node_id.clone(),
+            OutputStream::Stdout,
+            stdout,
+            log::logger(),
+        )?);
+    }
+
+    if let Some(stderr) = child.stderr.take() {
+        handles.push(forward_output(
+            node_id.clone(),
+            OutputStream::Stderr,
+            stderr,
+            log::logger(),
+        )?);
+    }
+
+    Ok(handles)
+}
+
+#[cfg(test)]
+#[path = "./tests/capture-tests.rs"]
+mod tests;
@@ -0,0 +1,77 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::types::RuntimeId;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use uhlc::Timestamp;
+
+/// The offset between the HLC of two runtimes above which a warning is logged when an instance
+/// involving both is created.
+pub const CLOCK_SKEW_WARNING: Duration = Duration::from_millis(100);
+
+/// The offset between the HLC of a `runtime` and the one of the runtime that measured it.
+///
+/// The clock of the `runtime` is read once, between two readings of the local clock: it is
+/// compared to the middle of the `round_trip`, hence the offset is only known within half of
+/// the round trip.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClockSkew {
+    pub runtime: RuntimeId,
+    /// The absolute offset between the two clocks.
+    pub offset: Duration,
+    /// `true` if the clock of the `runtime` is ahead of the local clock.
+    pub ahead: bool,
+    /// The time elapsed between the two readings of the local clock.
+    pub round_trip: Duration,
+}
+
+impl ClockSkew {
+    /// Estimates the offset of the clock of the `runtime`, which read `remote` between the `sent`
+    /// and `received` readings of the local clock.
+    pub fn estimate(
+        runtime: RuntimeId,
+        sent: &Timestamp,
+        remote: &Timestamp,
+        received: &Timestamp,
+    ) -> Self {
+        let sent = sent.get_time().to_duration();
+        let received = received.get_time().to_duration();
+        let remote = remote.get_time().to_duration();
+
+        let round_trip = received.saturating_sub(sent);
+        let local = sent + round_trip / 2;
+        let (offset, ahead) = if remote >= local {
+            (remote - local, true)
+        } else {
+            (local - remote, false)
+        };
+
+        Self {
+            runtime,
+            offset,
+            ahead,
+            round_trip,
+        }
+    }
+
+    /// Returns `true` if the offset exceeds `max_skew`.
+    pub fn exceeds(&self, max_skew: Duration) -> bool {
+        self.offset > max_skew
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/clock-tests.rs"]
+mod tests;
@@ -0,0 +1,474 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::zfresult::{ErrorKind, ZFResult as Result};
+use crate::{bail, zferror};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use url::Url;
+
+/// Name of the directory, inside the cache, mapping the URLs fetched to the checksum of their
+/// content.
+const URLS_DIRECTORY: &str = "urls";
+
+/// Name of the file, next to a library, holding the last time it was used.
+const LAST_USE_FILE: &str = "last-use";
+
+/// Extension of a library being written in the cache.
+const PARTIAL_EXTENSION: &str = "partial";
+
+/// Prefix of the fragment of a URL giving the checksum of the library it points to.
+const CHECKSUM_FRAGMENT: &str = "sha256=";
+
+/// The configuration of the [`LibraryCache`].
+///
+/// Example:
+///
+/// ```yaml
+/// directory: /var/zenoh-flow/libraries
+/// max_size: 1073741824  # optional, in bytes
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LibraryCacheConfig {
+    pub directory: PathBuf,
+    #[serde(default)]
+    pub max_size: Option<u64>,
+}
+
+impl Default for LibraryCacheConfig {
+    fn default() -> Self {
+        Self {
+            directory: std::env::temp_dir().join("zenoh-flow").join("libraries"),
+            max_size: None,
+        }
+    }
+}
+
+/// A library stored in the [`LibraryCache`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedLibrary {
+    /// The SHA-256 of the content of the library, in hexadecimal.
+    pub checksum: String,
+    /// Where the library is stored.
+    pub path: PathBuf,
+    /// The size of the library, in bytes.
+    pub size: u64,
+    /// The last time the library was fetched or loaded.
+    pub last_use: SystemTime,
+}
+
+/// A content-addressable cache of the libraries of the nodes fetched from `http://` and `https://`
+/// URIs.
+///
+/// Each library is stored under the SHA-256 of its content, in `<directory>/<checksum>/`, and is
+/// reused by all the instances loading it --- also across restarts of the runtime --- instead of
+/// being downloaded again. The checksum of a library can be given in the fragment of its URI, in
+/// which case the content downloaded is verified against it:
+///
+/// ```yaml
+/// uri: https://example.com/nodes/libdetector.so#sha256=<checksum>
+/// ```
+///
+/// When a maximum size is configured, the least recently used libraries are evicted every time a
+/// library is added.
+#[derive(Debug, Clone)]
+pub struct LibraryCache {
+    config: LibraryCacheConfig,
+}
+
+impl LibraryCache {
+    /// Creates a `LibraryCache` storing the libraries in the directory of the `config`, created
+    /// when the first library is added.
+    pub fn new(config: LibraryCacheConfig) -> Self {
+        Self { config }
+    }
+
+    /// Returns the directory where the libraries are stored.
+    pub fn directory(&self) -> &Path {
+        &self.config.directory
+    }
+
+    /// Returns the maximum size of the cache, in bytes, if any.
+    pub fn max_size(&self) -> Option<u64> {
+        self.config.max_size
+    }
+
+    /// Returns the path of the library pointed at by `url`, downloading it only if it is not
+    /// already in the cache.
+    ///
+    /// # Errors
+    ///
+    /// An error variant is returned if:
+    /// - the checksum in the fragment of the `url` is malformed or does not match the content,
+    /// - the `url` does not end with a file name,
+    /// - the library could not be downloaded or written in the cache.
+    pub(crate) async fn fetch(&self, url: &Url) -> Result<PathBuf> {
+        let expected = checksum_from_fragment(url)?;
+        let mut location = url.clone();
+        location.set_fragment(None);
+
+        if let Some(checksum) = expected.clone().or_else(|| self.checksum_of_url(&location)) {
+            if let Some(library) = self.get(&checksum)? {
+                log::trace!("[LibraryCache] {} found as {}", location, checksum);
+                return Ok(library.path);
+            }
+        }
+
+        let file_name = location
+            .path_segments()
+            .and_then(|mut segments| segments.next_back())
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| {
+                zferror!(
+                    ErrorKind::LoadingError,
+                    "The URI < {} > does not point to a file",
+                    location
+                )
+            })?
+            .to_string();
+
+        log::debug!("[LibraryCache] Downloading {}", location);
+        let mut response = surf::get(location.as_str()).await.map_err(|e| {
+            zferror!(
+                ErrorKind::LoadingError,
+                "Unable to download < {} >: {}",
+                location,
+                e
+            )
+        })?;
+        if !response.status().is_success() {
+            bail!(
+                ErrorKind::LoadingError,
+                "Unable to download < {} >: status {}",
+                location,
+                response.status()
+            )
+        }
+        let content = response.body_bytes().await.map_err(|e| {
+            zferror!(
+                ErrorKind::LoadingError,
+                "Unable to download < {} >: {}",
+                location,
+                e
+            )
+        })?;
+
+        if let Some(expected) = expected {
+            let checksum = checksum(&content);
+            if checksum != expected {
+                bail!(
+                    ErrorKind::LoadingError,
+                    "The checksum of < {} > is {}, expected {}",
+                    location,
+                    checksum,
+                    expected
+                )
+            }
+        }
+
+        let library = self.store(&file_name, &content)?;
+        let urls = self.config.directory.join(URLS_DIRECTORY);
+        std::fs::create_dir_all(&urls)?;
+        std::fs::write(
+            urls.join(checksum(location.as_str().as_bytes())),
+            &library.checksum,
+        )?;
+
+        Ok(library.path)
+    }
+
+    /// Stores the `content` of the library `file_name` in the cache and returns it, evicting the
+    /// least recently used libraries if the cache exceeds its maximum size.
+    ///
+    /// # Errors
+    ///
+    /// An error variant is returned if the library could not be written in the cache.
+    pub fn store(&self, file_name: &str, content: &[u8]) -> Result<CachedLibrary> {
+        if file_name.is_empty()
+            || file_name == LAST_USE_FILE
+            || file_name.contains(['/', '\\'])
+            || Path::new(file_name).extension() == Some(PARTIAL_EXTENSION.as_ref())
+        {
+            bail!(
+                ErrorKind::InvalidData,
+                "< {} > is not a valid name for a library",
+                file_name
+            )
+        }
+
+        let checksum = checksum(content);
+        let directory = self.config.directory.join(&checksum);
+        std::fs::create_dir_all(&directory)?;
+
+        // The same content stored under another name is reused as is.
+        let path = match read_library(&checksum, &directory)? {
+            Some(library) => library.path,
+            None => {
+                // The library is written aside and then renamed, so that a library in the cache
+                // is always complete.
+                let path = directory.join(file_name);
+                let partial = path.with_extension(PARTIAL_EXTENSION);
+                std::fs::write(&partial, content)?;
+                std::fs::rename(&partial, &path)?;
+                path
+            }
+        };
+
+        let library = CachedLibrary {
+            checksum,
+            path,
+            size: content.len() as u64,
+            last_use: touch(&directory)?,
+        };
+
+        for evicted in self.evict_except(Some(&library.checksum))? {
+            log::debug!(
+                "[LibraryCache] Evicted {} ({} bytes)",
+                evicted.path.display(),
+                evicted.size
+            );
+        }
+
+        Ok(library)
+    }
+
+    /// Returns the library whose content has the given `checksum`, if it is in the cache, and
+    /// marks it as used.
+    ///
+    /// # Errors
+    ///
+    /// An error variant is returned if the `checksum` is not a SHA-256 in hexadecimal or if the
+    /// cache could not be read.
+    pub fn get(&self, checksum: &str) -> Result<Option<CachedLibrary>> {
+        if !is_checksum(checksum) {
+            bail!(
+                ErrorKind::InvalidData,
+                "< {} > is not a SHA-256 in hexadecimal",
+                checksum
+            )
+        }
+
+        let directory = self.config.directory.join(checksum);
+        if !directory.is_dir() {
+            return Ok(None);
+        }
+
+        touch(&directory)?;
+        read_library(checksum, &directory)
+    }
+
+    /// Lists the libraries in the cache, from the least to the most recently used.
+    ///
+    /// # Errors
+    ///
+    /// An error variant is returned if the cache could not be read.
+    pub fn list(&self) -> Result<Vec<CachedLibrary>> {
+        if !self.config.directory.is_dir() {
+            return Ok(vec![]);
+        }
+
+        let mut libraries = vec![];
+        for entry in std::fs::read_dir(&self.config.directory)? {
+            let entry = entry?;
+            let checksum = entry.file_name().to_string_lossy().to_string();
+            if is_checksum(&checksum) && entry.path().is_dir() {
+                if let Some(library) = read_library(&checksum, &entry.path())? {
+                    libraries.push(library);
+                }
+            }
+        }
+
+        libraries.sort_by(|left, right| left.last_use.cmp(&right.last_use));
+        Ok(libraries)
+    }
+
+    /// Returns the total size of the libraries in the cache, in bytes.
+    ///
+    /// # Errors
+    ///
+    /// An error variant is returned if the cache could not be read.
+    pub fn size(&self) -> Result<u64> {
+        Ok(self.list()?.iter().map(|library| library.size).sum())
+    }
+
+    /// Removes the library whose content has the given `checksum` and returns `true` if it was in
+    /// the cache.
+    ///
+    /// # Errors
+    ///
+    /// An error variant is returned if the `checksum` is not a SHA-256 in hexadecimal or if the
+    /// library could not be removed.
+    pub fn remove(&self, checksum: &str) -> Result<bool> {
+        if !is_checksum(checksum) {
+            bail!(
+                ErrorKind::InvalidData,
+                "< {} > is not a SHA-256 in hexadecimal",
+                checksum
+            )
+        }
+
+        let directory = self.config.directory.join(checksum);
+        if !directory.is_dir() {
+            return Ok(false);
+        }
+
+        std::fs::remove_dir_all(directory)?;
+        Ok(true)
+    }
+
+    /// Removes all the libraries from the cache and returns the number removed.
+    ///
+    /// The libraries already loaded by a running instance are not affected.
+    ///
+    /// # Errors
+    ///
+    /// An error variant is returned if a library could not be removed.
+    pub fn purge(&self) -> Result<usize> {
+        let libraries = self.list()?;
+        for library in libraries.iter() {
+            self.remove(&library.checksum)?;
+        }
+
+        let urls = self.config.directory.join(URLS_DIRECTORY);
+        if urls.is_dir() {
+            std::fs::remove_dir_all(urls)?;
+        }
+
+        Ok(libraries.len())
+    }
+
+    /// Removes the least recently used libraries until the cache no longer exceeds its maximum
+    /// size, and returns them.
+    ///
+    /// # Errors
+    ///
+    /// An error variant is returned if a library could not be removed.
+    pub fn evict(&self) -> Result<Vec<CachedLibrary>> {
+        self.evict_except(None)
+    }
+
+    fn evict_except(&self, kept: Option<&str>) -> Result<Vec<CachedLibrary>> {
+        let max_size = match self.config.max_size {
+            Some(max_size) => max_size,
+            None => return Ok(vec![]),
+        };
+
+        let libraries = self.list()?;
+        let mut size: u64 = libraries.iter().map(|library| library.size).sum();
+        let mut evicted = vec![];
+        for library in libraries {
+            if size <= max_size {
+                break;
+            }
+
+            if Some(library.checksum.as_str()) == kept {
+                continue;
+            }
+
+            self.remove(&library.checksum)?;
+            size -= library.size;
+            evicted.push(library);
+        }
+
+        Ok(evicted)
+    }
+
+    /// Returns the checksum of the library last downloaded from `url`, if any.
+    fn checksum_of_url(&self, url: &Url) -> Option<String> {
+        let path = self
+            .config
+            .directory
+            .join(URLS_DIRECTORY)
+            .join(checksum(url.as_str().as_bytes()));
+        std::fs::read_to_string(path)
+            .ok()
+            .map(|checksum| checksum.trim().to_string())
+            .filter(|checksum| is_checksum(checksum))
+    }
+}
+
+/// Returns the SHA-256 of the `content`, in hexadecimal.
+pub(crate) fn checksum(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+fn is_checksum(checksum: &str) -> bool {
+    checksum.len() == 64
+        && checksum
+            .chars()
+            .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+}
+
+/// Returns the checksum given in the fragment of the `url`, i.e. `#sha256=<checksum>`, if any.
+fn checksum_from_fragment(url: &Url) -> Result<Option<String>> {
+    match url.fragment() {
+        None => Ok(None),
+        Some(fragment) => match fragment.strip_prefix(CHECKSUM_FRAGMENT) {
+            Some(checksum) if is_checksum(&checksum.to_lowercase()) => {
+                Ok(Some(checksum.to_lowercase()))
+            }
+            _ => bail!(
+                ErrorKind::ParsingError,
+                "The fragment of < {} > must be of the form `#{}<checksum>`",
+                url,
+                CHECKSUM_FRAGMENT
+            ),
+        },
+    }
+}
+
+/// Records, in the `directory` of a library, that it is used now.
+fn touch(directory: &Path) -> Result<SystemTime> {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    std::fs::write(directory.join(LAST_USE_FILE), millis.to_string())?;
+    Ok(UNIX_EPOCH + Duration::from_millis(millis))
+}
+
+/// Reads the library stored in `directory`, if the directory holds a complete one.
+fn read_library(checksum: &str, directory: &Path) -> Result<Option<CachedLibrary>> {
+    let last_use = std::fs::read_to_string(directory.join(LAST_USE_FILE))
+        .ok()
+        .and_then(|millis| millis.trim().parse::<u64>().ok())
+        .map(|millis| UNIX_EPOCH + Duration::from_millis(millis))
+        .unwrap_or(UNIX_EPOCH);
+
+    for entry in std::fs::read_dir(directory)? {
+        let path = entry?.path();
+        if path.is_file()
+            && path.file_name() != Some(LAST_USE_FILE.as_ref())
+            && path.extension() != Some(PARTIAL_EXTENSION.as_ref())
+        {
+            return Ok(Some(CachedLibrary {
+                checksum: checksum.to_string(),
+                size: std::fs::metadata(&path)?.len(),
+                path,
+                last_use,
+            }));
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+#[path = "./tests/library-cache-tests.rs"]
+mod tests;
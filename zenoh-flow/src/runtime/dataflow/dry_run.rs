@@ -0,0 +1,239 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::instance::create_links;
+use super::instance::runners::catch_panic;
+use super::instance::runners::timers::Timers;
+use super::DataFlow;
+use crate::io::{Inputs, Outputs};
+use crate::model::record::DataFlowRecord;
+use crate::prelude::{Context, Node};
+use crate::runtime::{InstanceContext, RuntimeContext};
+use crate::types::{Blackboard, NodeId};
+use crate::zfresult::Error;
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// The stage of a dry run at which a node failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DryRunStage {
+    /// Its library could not be loaded: it is missing, it does not export a node or it was built
+    /// against another version of Zenoh-Flow.
+    Loading,
+    /// The node could not be created, e.g. because of an invalid configuration.
+    Initialization,
+    /// The node failed to release its resources, in [`Node::clean`].
+    Cleaning,
+}
+
+impl std::fmt::Display for DryRunStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DryRunStage::Loading => write!(f, "loading"),
+            DryRunStage::Initialization => write!(f, "initialization"),
+            DryRunStage::Cleaning => write!(f, "cleaning"),
+        }
+    }
+}
+
+/// The failure of a node during a dry run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DryRunFailure {
+    pub stage: DryRunStage,
+    pub error: String,
+}
+
+impl DryRunFailure {
+    fn new(stage: DryRunStage, error: Error) -> Self {
+        Self {
+            stage,
+            error: error.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for DryRunFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "failed at {}: {}", self.stage, self.error)
+    }
+}
+
+/// The report of a dry run: the outcome of each node of the data flow running on this runtime.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DryRunReport {
+    pub nodes: HashMap<NodeId, std::result::Result<(), DryRunFailure>>,
+}
+
+impl DryRunReport {
+    /// Returns `true` if all the nodes passed the dry run.
+    pub fn is_ok(&self) -> bool {
+        self.nodes.values().all(|outcome| outcome.is_ok())
+    }
+
+    /// Returns the nodes that failed the dry run and their failure.
+    pub fn failures(&self) -> impl Iterator<Item = (&NodeId, &DryRunFailure)> {
+        self.nodes
+            .iter()
+            .filter_map(|(node_id, outcome)| outcome.as_ref().err().map(|e| (node_id, e)))
+    }
+}
+
+impl DataFlow {
+    /// Performs a dry run of the nodes of the `record` running on this runtime: their libraries are
+    /// loaded (checking the versions they were built against), each node is created and then
+    /// cleaned, and everything is unloaded.
+    ///
+    /// Nothing is started: the Sources are never iterated and no data is exchanged. The connectors
+    /// are not created either --- they do not run user code. This surfaces the deployment errors
+    /// (a missing library, an invalid configuration, ...) of all the nodes at once, before the data
+    /// flow is instantiated.
+    ///
+    /// # Error
+    ///
+    /// The failures of the nodes are reported in the [DryRunReport]. An error is only returned if
+    /// the links between the nodes could not be created.
+    pub async fn dry_run(record: DataFlowRecord, context: RuntimeContext) -> Result<DryRunReport> {
+        let mut report = DryRunReport::default();
+        let data_flow = DataFlow::try_load(record, context, &mut |node_id, e| {
+            report.nodes.insert(
+                node_id.clone(),
+                Err(DryRunFailure::new(DryRunStage::Loading, e)),
+            );
+            Ok(())
+        })?;
+
+        data_flow.complete_dry_run(report).await
+    }
+
+    /// Performs a dry run of the nodes of the `DataFlow`, already loaded: each node is created and
+    /// then cleaned, see [DataFlow::dry_run].
+    ///
+    /// The `DataFlow` is consumed: the libraries of the nodes are unloaded once it is dropped.
+    ///
+    /// # Error
+    ///
+    /// An error is only returned if the links between the nodes could not be created.
+    pub async fn dry_run_nodes(self) -> Result<DryRunReport> {
+        self.complete_dry_run(DryRunReport::default()).await
+    }
+
+    /// Creates and then cleans each node of the `DataFlow`, completing the `report` --- which holds
+    /// the nodes that could not be loaded.
+    async fn complete_dry_run(mut self, mut report: DryRunReport) -> Result<DryRunReport> {
+        let hlc = self.context.hlc.clone();
+        let instance_context = Arc::new(InstanceContext {
+            flow_id: self.flow.clone(),
+            instance_id: self.uuid,
+            runtime: self.context.clone(),
+            hlc: hlc.clone(),
+            simulation: None,
+            // The blackboard is not shared on Zenoh: nothing is declared during a dry run.
+            blackboard: Arc::new(Blackboard::new(self.uuid, None)),
+            sessions: HashMap::new(),
+            host: Arc::new(std::mem::take(&mut self.host)),
+        });
+        let context = Context::new(&instance_context);
+
+        // The nodes that could not be loaded are kept in the links: their neighbours must not fail
+        // because one of their ports is missing.
+        let node_ids = self
+            .source_constructors
+            .keys()
+            .chain(self.operator_constructors.keys())
+            .chain(self.sink_constructors.keys())
+            .chain(self.connectors.keys())
+            .chain(report.nodes.keys())
+            .cloned()
+            .collect::<Vec<_>>();
+        let (mut links, _, _) = create_links(&node_ids, &self.links, &self.credits, hlc.clone())?;
+        let mut io = |node_id: &NodeId| {
+            links
+                .remove(node_id)
+                .unwrap_or_else(|| (Inputs::new(), Outputs::new(hlc.clone())))
+        };
+
+        for (source_id, source_constructor) in &self.source_constructors {
+            let (_, outputs) = io(source_id);
+            let (scheduler, _timers) = Timers::new(None);
+            let node = (source_constructor.constructor)(
+                context
+                    .clone()
+                    .with_node_id(source_id.clone())
+                    .with_timers(scheduler),
+                source_constructor.configuration.clone(),
+                outputs,
+            );
+            let outcome = dry_run_node(source_id, &context, node).await;
+            report.nodes.insert(source_id.clone(), outcome);
+        }
+
+        for (operator_id, operator_constructor) in &self.operator_constructors {
+            let (mut inputs, outputs) = io(operator_id);
+            inputs.policies = operator_constructor.input_policies.clone();
+            let (scheduler, _timers) = Timers::new(None);
+            let node = (operator_constructor.constructor)(
+                context
+                    .clone()
+                    .with_node_id(operator_id.clone())
+                    .with_timers(scheduler)
+                    .with_keyed_state(Some(self.keyed_state(operator_id))),
+                operator_constructor.configuration.clone(),
+                inputs,
+                outputs,
+            );
+            let outcome = dry_run_node(operator_id, &context, node).await;
+            report.nodes.insert(operator_id.clone(), outcome);
+        }
+
+        for (sink_id, sink_constructor) in &self.sink_constructors {
+            let (mut inputs, _) = io(sink_id);
+            inputs.policies = sink_constructor.input_policies.clone();
+            let (scheduler, _timers) = Timers::new(None);
+            let node = (sink_constructor.constructor)(
+                context
+                    .clone()
+                    .with_node_id(sink_id.clone())
+                    .with_timers(scheduler),
+                sink_constructor.configuration.clone(),
+                inputs,
+            );
+            let outcome = dry_run_node(sink_id, &context, node).await;
+            report.nodes.insert(sink_id.clone(), outcome);
+        }
+
+        for (node_id, outcome) in report.failures() {
+            log::warn!("[Dry run] Node < {node_id} > {outcome}");
+        }
+
+        Ok(report)
+    }
+}
+
+/// Creates the node `node_id`, awaiting `node`, and cleans it right away. The node is dropped before
+/// returning.
+async fn dry_run_node(
+    node_id: &NodeId,
+    context: &Context,
+    node: impl std::future::Future<Output = Result<Arc<dyn Node>>>,
+) -> std::result::Result<(), DryRunFailure> {
+    let node = catch_panic(node_id, node)
+        .await
+        .map_err(|e| DryRunFailure::new(DryRunStage::Initialization, e))?;
+
+    catch_panic(node_id, node.clean(context))
+        .await
+        .map_err(|e| DryRunFailure::new(DryRunStage::Cleaning, e))
+}
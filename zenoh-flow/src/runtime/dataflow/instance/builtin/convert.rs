@@ -0,0 +1,219 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::{
+    bail,
+    model::descriptor::{OperatorDescriptor, Unit, UnitConversion},
+    prelude::{
+        zferror, Configuration, Context, ErrorKind, InputRaw, Inputs, Node, Operator, OutputRaw,
+        Outputs,
+    },
+    runtime::dataflow::{
+        loader::{NodeDeclaration, CORE_VERSION, RUSTC_VERSION},
+        node::OperatorFn,
+    },
+    types::LinkMessage,
+    Result as ZFResult,
+};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Key for the unit of the values received by the built-in Convert.
+pub(crate) static KEY_FROM: &str = "from";
+
+/// Key for the unit of the values sent by the built-in Convert.
+pub(crate) static KEY_TO: &str = "to";
+
+/// Identifier of the input of the built-in Convert.
+pub(crate) static CONVERT_INPUT: &str = "in";
+
+/// Identifier of the output of the built-in Convert.
+pub(crate) static CONVERT_OUTPUT: &str = "out";
+
+/// Retrieves the unit under `key` from the configuration.
+fn get_unit(configuration: &Configuration, key: &str) -> ZFResult<Unit> {
+    let unit = configuration.get(key).ok_or_else(|| {
+        zferror!(
+            ErrorKind::ConfigurationError,
+            "Missing {key} in builtin Convert configuration"
+        )
+    })?;
+
+    let unit = unit.as_str().ok_or_else(|| {
+        zferror!(
+            ErrorKind::ConfigurationError,
+            "Unable to convert value to string: {:?}",
+            unit
+        )
+    })?;
+
+    Unit::from_str(unit)
+}
+
+/// Retrieves the units to convert from and to from the configuration, and the conversion between
+/// them.
+fn get_conversion(configuration: &Configuration) -> ZFResult<(Unit, Unit, UnitConversion)> {
+    let from = get_unit(configuration, KEY_FROM)?;
+    let to = get_unit(configuration, KEY_TO)?;
+    match from.conversion_to(&to) {
+        Some(conversion) => Ok((from, to, conversion)),
+        None => bail!(
+            ErrorKind::ConfigurationError,
+            "No conversion is known from < {from} > to < {to} >"
+        ),
+    }
+}
+
+/// Converts the `payload`, a JSON number, and returns it serialized.
+///
+/// # Errors
+///
+/// An error variant is returned if the payload is not a single JSON number: the unit of a port
+/// applies to the whole value sent on it.
+pub(crate) fn convert_payload(payload: &[u8], conversion: &UnitConversion) -> ZFResult<Vec<u8>> {
+    let value = serde_json::from_slice::<Value>(payload)
+        .map_err(|e| zferror!(ErrorKind::DeserializationError, e))?;
+    let number = match value.as_f64() {
+        Some(number) => conversion.apply(number),
+        None => bail!(
+            ErrorKind::DeserializationError,
+            "[Convert] the payload < {value} > is not a number"
+        ),
+    };
+
+    serde_json::to_vec(&number).map_err(|e| zferror!(ErrorKind::SerializationError, e).into())
+}
+
+/// The builtin Convert operator
+/// It forwards, from its input `in` to its output `out`, the messages it receives with their
+/// payload converted from a unit to another (see [`Unit`](crate::model::descriptor::Unit)). It is
+/// inserted on the links connecting ports of different units.
+/// The payloads must be a single JSON number: any other payload is an error of the operator.
+/// It expects a configuration in the format
+///
+/// ```yaml
+/// from: <unit>
+/// to: <unit>
+/// ```
+pub(crate) struct Convert {
+    input: InputRaw,
+    output: OutputRaw,
+    conversion: UnitConversion,
+}
+
+/// Private function to retrieve the "Constructor" for the Convert
+pub(crate) fn get_convert_declaration() -> NodeDeclaration<OperatorFn> {
+    NodeDeclaration::<OperatorFn> {
+        rustc_version: RUSTC_VERSION,
+        core_version: CORE_VERSION,
+        constructor: |context: Context,
+                      configuration: Option<Configuration>,
+                      inputs: Inputs,
+                      outputs: Outputs| {
+            Box::pin(async {
+                let node = Convert::new(context, configuration, inputs, outputs).await?;
+                Ok(Arc::new(node) as Arc<dyn Node>)
+            })
+        },
+    }
+}
+
+/// Private function to retrieve the Descriptor for the Convert
+pub(crate) fn get_convert_descriptor(
+    configuration: &Configuration,
+) -> ZFResult<OperatorDescriptor> {
+    let (from, to, _) = get_conversion(configuration)?;
+
+    Ok(OperatorDescriptor {
+        id: "convert".into(),
+        inputs: vec![CONVERT_INPUT.into()],
+        outputs: vec![CONVERT_OUTPUT.into()],
+        side_outputs: vec![],
+        input_policies: HashMap::new(),
+        input_types: HashMap::new(),
+        input_units: HashMap::from([(CONVERT_INPUT.into(), from)]),
+        output_types: HashMap::new(),
+        output_units: HashMap::from([(CONVERT_OUTPUT.into(), to)]),
+        uri: Some("builtin://convert".into()),
+        configuration: Some(configuration.clone()),
+    })
+}
+
+#[async_trait]
+impl Operator for Convert {
+    async fn new(
+        _context: Context,
+        configuration: Option<Configuration>,
+        mut inputs: Inputs,
+        mut outputs: Outputs,
+    ) -> ZFResult<Self> {
+        match configuration {
+            Some(configuration) => Ok(Convert {
+                input: inputs
+                    .take(CONVERT_INPUT)
+                    .ok_or(zferror!(
+                        ErrorKind::MissingInput(CONVERT_INPUT.to_string()),
+                        "Unable to find input: {CONVERT_INPUT}"
+                    ))?
+                    .raw(),
+                output: outputs
+                    .take(CONVERT_OUTPUT)
+                    .ok_or(zferror!(
+                        ErrorKind::MissingOutput(CONVERT_OUTPUT.to_string()),
+                        "Unable to find output: {CONVERT_OUTPUT}"
+                    ))?
+                    .raw(),
+                conversion: get_conversion(&configuration)?.2,
+            }),
+            None => {
+                bail!(
+                    ErrorKind::MissingConfiguration,
+                    "Builtin Convert needs a configuration!"
+                )
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Node for Convert {
+    async fn iteration(&self) -> ZFResult<()> {
+        let message = self.input.recv().await?;
+
+        let data_message = match &message {
+            LinkMessage::Data(data_message) => data_message,
+            _ => return self.output.forward(message).await,
+        };
+
+        let bytes = convert_payload(&data_message.try_as_bytes()?, &self.conversion)?;
+
+        self.output
+            .forward(
+                LinkMessage::from_payload_with_event_time(
+                    bytes.into(),
+                    *data_message.get_timestamp(),
+                    data_message.get_event_time().copied(),
+                )
+                .with_variant(data_message.get_variant()),
+            )
+            .await
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/builtin-convert.rs"]
+mod tests;
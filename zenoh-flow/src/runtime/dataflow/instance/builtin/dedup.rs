@@ -0,0 +1,243 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::{
+    bail,
+    model::descriptor::OperatorDescriptor,
+    prelude::{
+        zferror, Configuration, Context, ErrorKind, InputRaw, Inputs, Node, Operator, OutputRaw,
+        Outputs,
+    },
+    runtime::dataflow::{
+        instance::builtin::get_duration_or_default,
+        loader::{NodeDeclaration, CORE_VERSION, RUSTC_VERSION},
+        node::OperatorFn,
+    },
+    types::LinkMessage,
+    Result as ZFResult,
+};
+use async_lock::Mutex;
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Key for the duration during which a message is remembered by the built-in Dedup.
+static KEY_WINDOW: &str = "window";
+
+/// Key for the JSON pointer used by the built-in Dedup to extract the key of a message.
+static KEY_KEY: &str = "key";
+
+/// Default window of the built-in Dedup (1s).
+static DEFAULT_WINDOW: Duration = Duration::from_secs(1);
+
+/// Identifier of the input of the built-in Dedup.
+pub(crate) static DEDUP_INPUT: &str = "in";
+
+/// Identifier of the output of the built-in Dedup.
+pub(crate) static DEDUP_OUTPUT: &str = "out";
+
+/// Retrieves the optional JSON pointer from the configuration.
+fn get_key(configuration: &Configuration) -> ZFResult<Option<String>> {
+    match configuration.get(KEY_KEY) {
+        Some(value) => {
+            let key = value.as_str().ok_or_else(|| {
+                zferror!(
+                    ErrorKind::ConfigurationError,
+                    "Unable to convert value of {KEY_KEY} to string: {:?}",
+                    value
+                )
+            })?;
+            if !key.is_empty() && !key.starts_with('/') {
+                bail!(
+                    ErrorKind::ConfigurationError,
+                    "The {KEY_KEY} < {key} > is not a valid JSON pointer, it should start with '/'"
+                )
+            }
+            Ok(Some(key.to_string()))
+        }
+        None => Ok(None),
+    }
+}
+
+/// The builtin Dedup operator
+/// It forwards, from its input `in` to its output `out`, the messages it receives unless an
+/// identical message was already forwarded within the `window`. Watermarks are always forwarded.
+///
+/// Two messages are identical if their payloads are the same or, if a `key` is provided, if the
+/// values pointed at by the `key` in their (JSON) payloads are the same. Messages whose payload
+/// cannot be interpreted as JSON, or that do not contain the `key`, are compared on their payload.
+///
+/// The window is based on the timestamps of the messages.
+/// It expects a configuration in the format
+///
+/// ```yaml
+/// window: 10s     # optional, defaults to 1s
+/// key: /frame/id  # optional, a JSON pointer
+/// ```
+pub(crate) struct Dedup {
+    input: InputRaw,
+    output: OutputRaw,
+    window: Duration,
+    key: Option<String>,
+    state: Arc<Mutex<DedupState>>,
+}
+
+/// The DedupState stores in a single structure all the fields protected by a lock.
+///
+/// The fields are:
+/// - `seen` contains the identity (see [identity]) of each message forwarded within the window;
+/// - `history` contains the same identities, along with their timestamp, ordered by timestamp, to
+///   expire old entries;
+/// - `buffer` holds a growable vector of bytes in which the result of the serialization of the data
+///   is stored.
+pub(crate) struct DedupState {
+    pub(crate) seen: HashSet<Arc<[u8]>>,
+    pub(crate) history: VecDeque<(Arc<[u8]>, Duration)>,
+    pub(crate) buffer: Vec<u8>,
+}
+
+impl DedupState {
+    /// Forgets the entries older than `window` relatively to `now`.
+    fn expire(&mut self, now: Duration, window: Duration) {
+        while let Some((_, timestamp)) = self.history.front() {
+            if now.saturating_sub(*timestamp) < window {
+                break;
+            }
+
+            if let Some((identity, _)) = self.history.pop_front() {
+                self.seen.remove(&identity);
+            }
+        }
+    }
+}
+
+/// Returns what identifies the content of the `payload`: the value pointed at by the `key`, if the
+/// payload is JSON and contains it, the payload itself otherwise.
+pub(crate) fn identity(key: Option<&str>, payload: &[u8]) -> Vec<u8> {
+    key.and_then(|key| {
+        serde_json::from_slice::<serde_json::Value>(payload)
+            .ok()
+            .and_then(|json| {
+                json.pointer(key)
+                    .map(|value| value.to_string().into_bytes())
+            })
+    })
+    .unwrap_or_else(|| payload.to_vec())
+}
+
+/// Private function to retrieve the "Constructor" for the Dedup
+pub(crate) fn get_dedup_declaration() -> NodeDeclaration<OperatorFn> {
+    NodeDeclaration::<OperatorFn> {
+        rustc_version: RUSTC_VERSION,
+        core_version: CORE_VERSION,
+        constructor: |context: Context,
+                      configuration: Option<Configuration>,
+                      inputs: Inputs,
+                      outputs: Outputs| {
+            Box::pin(async {
+                let node = Dedup::new(context, configuration, inputs, outputs).await?;
+                Ok(Arc::new(node) as Arc<dyn Node>)
+            })
+        },
+    }
+}
+
+/// Private function to retrieve the Descriptor for the Dedup
+pub(crate) fn get_dedup_descriptor(configuration: &Configuration) -> ZFResult<OperatorDescriptor> {
+    get_duration_or_default(configuration, KEY_WINDOW, DEFAULT_WINDOW)?;
+    get_key(configuration)?;
+
+    Ok(OperatorDescriptor {
+        id: "dedup".into(),
+        inputs: vec![DEDUP_INPUT.into()],
+        outputs: vec![DEDUP_OUTPUT.into()],
+        side_outputs: vec![],
+        input_policies: HashMap::new(),
+        input_types: HashMap::new(),
+        input_units: HashMap::new(),
+        output_types: HashMap::new(),
+        output_units: HashMap::new(),
+        uri: Some("builtin://dedup".into()),
+        configuration: Some(configuration.clone()),
+    })
+}
+
+#[async_trait]
+impl Operator for Dedup {
+    async fn new(
+        _context: Context,
+        configuration: Option<Configuration>,
+        mut inputs: Inputs,
+        mut outputs: Outputs,
+    ) -> ZFResult<Self> {
+        let configuration =
+            configuration.unwrap_or_else(|| Configuration::Object(Default::default()));
+
+        Ok(Dedup {
+            input: inputs
+                .take(DEDUP_INPUT)
+                .ok_or(zferror!(
+                    ErrorKind::MissingInput(DEDUP_INPUT.to_string()),
+                    "Unable to find input: {DEDUP_INPUT}"
+                ))?
+                .raw(),
+            output: outputs
+                .take(DEDUP_OUTPUT)
+                .ok_or(zferror!(
+                    ErrorKind::MissingOutput(DEDUP_OUTPUT.to_string()),
+                    "Unable to find output: {DEDUP_OUTPUT}"
+                ))?
+                .raw(),
+            window: get_duration_or_default(&configuration, KEY_WINDOW, DEFAULT_WINDOW)?,
+            key: get_key(&configuration)?,
+            state: Arc::new(Mutex::new(DedupState {
+                seen: HashSet::new(),
+                history: VecDeque::new(),
+                buffer: Vec::new(),
+            })),
+        })
+    }
+}
+
+#[async_trait]
+impl Node for Dedup {
+    async fn iteration(&self) -> ZFResult<()> {
+        let message = self.input.recv().await?;
+
+        if let LinkMessage::Data(ref data_message) = message {
+            let mut state = self.state.lock().await;
+            let now = data_message.get_timestamp().get_time().to_duration();
+            state.expire(now, self.window);
+
+            data_message.try_as_bytes_into(&mut state.buffer)?;
+            let identity = identity(self.key.as_deref(), &state.buffer);
+
+            if state.seen.contains(identity.as_slice()) {
+                log::trace!("[Dedup] dropping duplicate message");
+                return Ok(());
+            }
+
+            let identity: Arc<[u8]> = identity.into();
+            state.seen.insert(identity.clone());
+            state.history.push_back((identity, now));
+        }
+
+        self.output.forward(message).await
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/builtin-dedup.rs"]
+mod tests;
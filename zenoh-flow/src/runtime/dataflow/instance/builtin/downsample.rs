@@ -0,0 +1,174 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::{
+    bail,
+    model::descriptor::{OperatorDescriptor, SamplingRate},
+    prelude::{
+        zferror, Configuration, Context, ErrorKind, InputRaw, Inputs, Node, Operator, OutputRaw,
+        Outputs,
+    },
+    runtime::dataflow::{
+        loader::{NodeDeclaration, CORE_VERSION, RUSTC_VERSION},
+        node::OperatorFn,
+    },
+    types::LinkMessage,
+    Result as ZFResult,
+};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+/// Key for the sampling rate used by the built-in Downsample.
+pub(crate) static KEY_SAMPLE: &str = "sample";
+
+/// Identifier of the input of the built-in Downsample.
+pub(crate) static DOWNSAMPLE_INPUT: &str = "in";
+
+/// Identifier of the output of the built-in Downsample.
+pub(crate) static DOWNSAMPLE_OUTPUT: &str = "out";
+
+/// Retrieves the sampling rate from the configuration.
+fn get_sampling_rate(configuration: &Configuration) -> ZFResult<SamplingRate> {
+    let sample = configuration.get(KEY_SAMPLE).ok_or_else(|| {
+        zferror!(
+            ErrorKind::ConfigurationError,
+            "Missing {KEY_SAMPLE} in builtin Downsample configuration"
+        )
+    })?;
+
+    let sample = sample.as_str().ok_or_else(|| {
+        zferror!(
+            ErrorKind::ConfigurationError,
+            "Unable to convert value to string: {:?}",
+            sample
+        )
+    })?;
+
+    Ok(SamplingRate::from_str(sample)?)
+}
+
+/// The builtin Downsample operator
+/// It forwards, from its input `in` to its output `out`, only a fraction of the messages it
+/// receives. Watermarks are always forwarded.
+/// It expects a configuration in the format
+///
+/// ```yaml
+/// sample: <keep>/<every>
+/// ```
+pub(crate) struct Downsample {
+    input: InputRaw,
+    output: OutputRaw,
+    rate: SamplingRate,
+    received: AtomicU64,
+}
+
+/// Private function to retrieve the "Constructor" for the Downsample
+pub(crate) fn get_downsample_declaration() -> NodeDeclaration<OperatorFn> {
+    NodeDeclaration::<OperatorFn> {
+        rustc_version: RUSTC_VERSION,
+        core_version: CORE_VERSION,
+        constructor: |context: Context,
+                      configuration: Option<Configuration>,
+                      inputs: Inputs,
+                      outputs: Outputs| {
+            Box::pin(async {
+                let node = Downsample::new(context, configuration, inputs, outputs).await?;
+                Ok(Arc::new(node) as Arc<dyn Node>)
+            })
+        },
+    }
+}
+
+/// Private function to retrieve the Descriptor for the Downsample
+pub(crate) fn get_downsample_descriptor(
+    configuration: &Configuration,
+) -> ZFResult<OperatorDescriptor> {
+    get_sampling_rate(configuration)?;
+
+    Ok(OperatorDescriptor {
+        id: "downsample".into(),
+        inputs: vec![DOWNSAMPLE_INPUT.into()],
+        outputs: vec![DOWNSAMPLE_OUTPUT.into()],
+        side_outputs: vec![],
+        input_policies: HashMap::new(),
+        input_types: HashMap::new(),
+        input_units: HashMap::new(),
+        output_types: HashMap::new(),
+        output_units: HashMap::new(),
+        uri: Some("builtin://downsample".into()),
+        configuration: Some(configuration.clone()),
+    })
+}
+
+#[async_trait]
+impl Operator for Downsample {
+    async fn new(
+        _context: Context,
+        configuration: Option<Configuration>,
+        mut inputs: Inputs,
+        mut outputs: Outputs,
+    ) -> ZFResult<Self> {
+        match configuration {
+            Some(configuration) => Ok(Downsample {
+                input: inputs
+                    .take(DOWNSAMPLE_INPUT)
+                    .ok_or(zferror!(
+                        ErrorKind::MissingInput(DOWNSAMPLE_INPUT.to_string()),
+                        "Unable to find input: {DOWNSAMPLE_INPUT}"
+                    ))?
+                    .raw(),
+                output: outputs
+                    .take(DOWNSAMPLE_OUTPUT)
+                    .ok_or(zferror!(
+                        ErrorKind::MissingOutput(DOWNSAMPLE_OUTPUT.to_string()),
+                        "Unable to find output: {DOWNSAMPLE_OUTPUT}"
+                    ))?
+                    .raw(),
+                rate: get_sampling_rate(&configuration)?,
+                received: AtomicU64::new(0),
+            }),
+            None => {
+                bail!(
+                    ErrorKind::MissingConfiguration,
+                    "Builtin Downsample needs a configuration!"
+                )
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Node for Downsample {
+    async fn iteration(&self) -> ZFResult<()> {
+        let message = self.input.recv().await?;
+
+        if let LinkMessage::Data(_) = message {
+            let index = self.received.fetch_add(1, Ordering::Relaxed);
+            if !self.rate.keeps(index) {
+                return Ok(());
+            }
+        }
+
+        self.output.forward(message).await
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/builtin-downsample.rs"]
+mod tests;
@@ -0,0 +1,281 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::{
+    model::descriptor::{FaultsDescriptor, OperatorDescriptor},
+    prelude::{
+        zferror, Configuration, Context, ErrorKind, InputRaw, Inputs, Node, Operator, OutputRaw,
+        Outputs,
+    },
+    runtime::{
+        dataflow::{
+            loader::{NodeDeclaration, CORE_VERSION, RUSTC_VERSION},
+            node::OperatorFn,
+        },
+        resources::ROOT_STANDALONE,
+    },
+    types::LinkMessage,
+    Result as ZFResult, FAULTS_PATH,
+};
+use async_lock::Mutex;
+use async_trait::async_trait;
+use flume::Receiver;
+use rand::Rng;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use zenoh::{prelude::r#async::*, subscriber::Subscriber};
+
+/// Key for the link on which the built-in Faults injects faults.
+pub(crate) static KEY_LINK: &str = "link";
+
+/// Identifier of the input of the built-in Faults.
+pub(crate) static FAULTS_INPUT: &str = "in";
+
+/// Identifier of the output of the built-in Faults.
+pub(crate) static FAULTS_OUTPUT: &str = "out";
+
+/// Retrieves the faults to inject from the configuration.
+fn get_faults(configuration: &Configuration) -> ZFResult<FaultsDescriptor> {
+    serde_json::from_value(configuration.clone()).map_err(|e| {
+        zferror!(
+            ErrorKind::ConfigurationError,
+            "Unable to parse builtin Faults configuration: {e}"
+        )
+        .into()
+    })
+}
+
+/// Retrieves the optional link from the configuration.
+fn get_link(configuration: &Configuration) -> ZFResult<Option<String>> {
+    match configuration.get(KEY_LINK) {
+        Some(value) => {
+            let link = value.as_str().ok_or_else(|| {
+                zferror!(
+                    ErrorKind::ConfigurationError,
+                    "Unable to convert value of {KEY_LINK} to string: {:?}",
+                    value
+                )
+            })?;
+            Ok(Some(link.to_string()))
+        }
+        None => Ok(None),
+    }
+}
+
+/// The builtin Faults operator
+/// It forwards, from its input `in` to its output `out`, the messages it receives after having
+/// injected the faults described in its configuration (see [FaultsDescriptor]). Watermarks are
+/// always forwarded and a message held back is flushed before the end of the stream.
+///
+/// If a `link` is provided, the faults can be updated at runtime by publishing a
+/// [FaultsDescriptor], serialized in JSON, on `zenoh-flow/faults/<instance id>/<link>`.
+///
+/// The delay is applied before forwarding each message: as messages are processed one at a time, it
+/// also reduces the throughput of the link.
+///
+/// It expects a configuration in the format
+///
+/// ```yaml
+/// delay: 50ms       # optional
+/// jitter: 10ms      # optional
+/// drop: 0.05        # optional, probability between 0 and 1
+/// duplicate: 0.01   # optional, probability between 0 and 1
+/// reorder: 0.01     # optional, probability between 0 and 1
+/// link: Counter/out/Sum/in # optional, set when inserted on a link
+/// ```
+pub(crate) struct Faults<'a> {
+    input: InputRaw,
+    output: OutputRaw,
+    subscriber: Option<Subscriber<'a, Receiver<Sample>>>,
+    state: Arc<Mutex<FaultsState>>,
+}
+
+/// The FaultsState stores in a single structure all the fields protected by a lock.
+///
+/// The fields are:
+/// - `faults` the faults currently injected;
+/// - `held` the message held back to be forwarded after the next one.
+pub(crate) struct FaultsState {
+    pub(crate) faults: FaultsDescriptor,
+    pub(crate) held: Option<LinkMessage>,
+}
+
+/// Private function to retrieve the "Constructor" for the Faults
+pub(crate) fn get_faults_declaration() -> NodeDeclaration<OperatorFn> {
+    NodeDeclaration::<OperatorFn> {
+        rustc_version: RUSTC_VERSION,
+        core_version: CORE_VERSION,
+        constructor: |context: Context,
+                      configuration: Option<Configuration>,
+                      inputs: Inputs,
+                      outputs: Outputs| {
+            Box::pin(async {
+                let node = Faults::new(context, configuration, inputs, outputs).await?;
+                Ok(Arc::new(node) as Arc<dyn Node>)
+            })
+        },
+    }
+}
+
+/// Private function to retrieve the Descriptor for the Faults
+pub(crate) fn get_faults_descriptor(configuration: &Configuration) -> ZFResult<OperatorDescriptor> {
+    get_faults(configuration)?;
+    get_link(configuration)?;
+
+    Ok(OperatorDescriptor {
+        id: "faults".into(),
+        inputs: vec![FAULTS_INPUT.into()],
+        outputs: vec![FAULTS_OUTPUT.into()],
+        side_outputs: vec![],
+        input_policies: HashMap::new(),
+        input_types: HashMap::new(),
+        input_units: HashMap::new(),
+        output_types: HashMap::new(),
+        output_units: HashMap::new(),
+        uri: Some("builtin://faults".into()),
+        configuration: Some(configuration.clone()),
+    })
+}
+
+impl<'a> Faults<'a> {
+    /// Replaces the faults with the last update received, if any.
+    fn update_faults(&self, state: &mut FaultsState) {
+        let subscriber = match &self.subscriber {
+            Some(subscriber) => subscriber,
+            None => return,
+        };
+
+        while let Ok(sample) = subscriber.try_recv() {
+            match serde_json::from_slice::<FaultsDescriptor>(&sample.payload.contiguous())
+                .map_err(|e| zferror!(ErrorKind::DeserializationError, "{e}"))
+            {
+                Ok(faults) => {
+                    log::info!(
+                        "[Faults] updating faults on {}: {faults:?}",
+                        sample.key_expr
+                    );
+                    state.faults = faults;
+                }
+                Err(e) => log::error!("[Faults] ignoring invalid update: {e:?}"),
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<'a> Operator for Faults<'a> {
+    async fn new(
+        context: Context,
+        configuration: Option<Configuration>,
+        mut inputs: Inputs,
+        mut outputs: Outputs,
+    ) -> ZFResult<Self> {
+        let configuration =
+            configuration.unwrap_or_else(|| Configuration::Object(Default::default()));
+
+        let subscriber = match get_link(&configuration)? {
+            Some(link) => {
+                let ke = FAULTS_PATH!(ROOT_STANDALONE, context.get_instance_id(), link);
+                Some(context.session().declare_subscriber(&ke).res().await?)
+            }
+            None => None,
+        };
+
+        Ok(Faults {
+            input: inputs
+                .take(FAULTS_INPUT)
+                .ok_or(zferror!(
+                    ErrorKind::MissingInput(FAULTS_INPUT.to_string()),
+                    "Unable to find input: {FAULTS_INPUT}"
+                ))?
+                .raw(),
+            output: outputs
+                .take(FAULTS_OUTPUT)
+                .ok_or(zferror!(
+                    ErrorKind::MissingOutput(FAULTS_OUTPUT.to_string()),
+                    "Unable to find output: {FAULTS_OUTPUT}"
+                ))?
+                .raw(),
+            subscriber,
+            state: Arc::new(Mutex::new(FaultsState {
+                faults: get_faults(&configuration)?,
+                held: None,
+            })),
+        })
+    }
+}
+
+#[async_trait]
+impl<'a> Node for Faults<'a> {
+    async fn iteration(&self) -> ZFResult<()> {
+        let message = self.input.recv().await?;
+
+        match message {
+            LinkMessage::Data(_) => (),
+            LinkMessage::Watermark(_) | LinkMessage::Live(_) | LinkMessage::Control(_) => {
+                return self.output.forward(message).await
+            }
+            LinkMessage::EndOfStream(_) => {
+                if let Some(held) = self.state.lock().await.held.take() {
+                    self.output.forward(held).await?;
+                }
+                return self.output.forward(message).await;
+            }
+        }
+
+        let faults = {
+            let mut state = self.state.lock().await;
+            self.update_faults(&mut state);
+            state.faults.clone()
+        };
+
+        if rand::random::<f64>() < faults.drop.value() {
+            log::trace!("[Faults] dropping message");
+            return Ok(());
+        }
+
+        let mut delay = faults.delay.unwrap_or_default();
+        if let Some(jitter) = faults.jitter {
+            delay +=
+                Duration::from_nanos(rand::thread_rng().gen_range(0..=jitter.as_nanos() as u64));
+        }
+        if !delay.is_zero() {
+            crate::executor::sleep(delay).await;
+        }
+
+        let mut state = self.state.lock().await;
+        if state.held.is_none() && rand::random::<f64>() < faults.reorder.value() {
+            log::trace!("[Faults] holding back message");
+            state.held = Some(message);
+            return Ok(());
+        }
+
+        self.output.forward(message.clone()).await?;
+        if rand::random::<f64>() < faults.duplicate.value() {
+            log::trace!("[Faults] duplicating message");
+            self.output.forward(message).await?;
+        }
+
+        if let Some(held) = state.held.take() {
+            self.output.forward(held).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/builtin-faults.rs"]
+mod tests;
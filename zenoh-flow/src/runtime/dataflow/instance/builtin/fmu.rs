@@ -0,0 +1,513 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::{
+    bail,
+    model::descriptor::OperatorDescriptor,
+    prelude::{
+        zferror, Configuration, Context, ErrorKind, InputRaw, Inputs, Node, Operator, OutputRaw,
+        Outputs,
+    },
+    runtime::dataflow::{
+        loader::{NodeDeclaration, CORE_VERSION, RUSTC_VERSION},
+        node::OperatorFn,
+    },
+    types::LinkMessage,
+    utils::deserialize_duration,
+    Result as ZFResult,
+};
+use async_lock::Mutex;
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[cfg(target_family = "unix")]
+use libloading::os::unix::Library;
+#[cfg(target_family = "windows")]
+use libloading::Library;
+
+/// The communication step used when none is configured (10ms).
+const DEFAULT_STEP: Duration = Duration::from_millis(10);
+
+/// The configuration of the built-in FMU.
+///
+/// The FMU must be extracted beforehand: `library` is the shared library of its platform
+/// (`binaries/<platform>/<model identifier>.so`), `guid` and the value references of the variables
+/// are found in its `modelDescription.xml`.
+#[derive(Deserialize, Debug, Clone)]
+struct FmuConfiguration {
+    library: String,
+    guid: String,
+    #[serde(default)]
+    resources: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    step: Option<Duration>,
+    #[serde(default)]
+    inputs: BTreeMap<String, u32>,
+    #[serde(default)]
+    outputs: BTreeMap<String, u32>,
+}
+
+/// Retrieves the configuration of the FMU.
+fn get_fmu_configuration(configuration: &Configuration) -> ZFResult<FmuConfiguration> {
+    let fmu: FmuConfiguration = serde_json::from_value(configuration.clone()).map_err(|e| {
+        zferror!(
+            ErrorKind::ConfigurationError,
+            "Unable to parse builtin FMU configuration: {e}"
+        )
+    })?;
+
+    if fmu.step == Some(Duration::ZERO) {
+        bail!(
+            ErrorKind::ConfigurationError,
+            "The step of the builtin FMU must be strictly positive"
+        );
+    }
+
+    Ok(fmu)
+}
+
+// The subset of the FMI 2.0 API used to drive a co-simulation FMU.
+type Fmi2Component = *mut c_void;
+type Fmi2Status = c_int;
+
+const FMI2_OK: Fmi2Status = 0;
+const FMI2_WARNING: Fmi2Status = 1;
+const FMI2_CO_SIMULATION: c_int = 1;
+const FMI2_TRUE: c_int = 1;
+const FMI2_FALSE: c_int = 0;
+
+// The logger of the FMI API is variadic: only the fixed arguments are read.
+type Fmi2CallbackLogger =
+    extern "C" fn(*mut c_void, *const c_char, Fmi2Status, *const c_char, *const c_char);
+
+#[repr(C)]
+struct Fmi2CallbackFunctions {
+    logger: Fmi2CallbackLogger,
+    allocate_memory: unsafe extern "C" fn(usize, usize) -> *mut c_void,
+    free_memory: unsafe extern "C" fn(*mut c_void),
+    step_finished: Option<extern "C" fn(*mut c_void, Fmi2Status)>,
+    component_environment: *mut c_void,
+}
+
+extern "C" {
+    fn calloc(count: usize, size: usize) -> *mut c_void;
+    fn free(pointer: *mut c_void);
+}
+
+extern "C" fn fmi2_logger(
+    _environment: *mut c_void,
+    instance: *const c_char,
+    status: Fmi2Status,
+    category: *const c_char,
+    message: *const c_char,
+) {
+    let to_string = |string: *const c_char| {
+        if string.is_null() {
+            return String::new();
+        }
+        // SAFETY: the FMU provides NUL-terminated strings.
+        unsafe { CStr::from_ptr(string) }
+            .to_string_lossy()
+            .into_owned()
+    };
+
+    let (instance, category, message) =
+        (to_string(instance), to_string(category), to_string(message));
+    match status {
+        FMI2_OK => log::debug!("[FMU: {instance}] {category}: {message}"),
+        FMI2_WARNING => log::warn!("[FMU: {instance}] {category}: {message}"),
+        _ => log::error!("[FMU: {instance}] {category}: {message}"),
+    }
+}
+
+struct Fmi2Functions {
+    instantiate: unsafe extern "C" fn(
+        *const c_char,
+        c_int,
+        *const c_char,
+        *const c_char,
+        *const Fmi2CallbackFunctions,
+        c_int,
+        c_int,
+    ) -> Fmi2Component,
+    setup_experiment:
+        unsafe extern "C" fn(Fmi2Component, c_int, f64, f64, c_int, f64) -> Fmi2Status,
+    enter_initialization_mode: unsafe extern "C" fn(Fmi2Component) -> Fmi2Status,
+    exit_initialization_mode: unsafe extern "C" fn(Fmi2Component) -> Fmi2Status,
+    set_real: unsafe extern "C" fn(Fmi2Component, *const u32, usize, *const f64) -> Fmi2Status,
+    get_real: unsafe extern "C" fn(Fmi2Component, *const u32, usize, *mut f64) -> Fmi2Status,
+    do_step: unsafe extern "C" fn(Fmi2Component, f64, f64, c_int) -> Fmi2Status,
+    terminate: unsafe extern "C" fn(Fmi2Component) -> Fmi2Status,
+    free_instance: unsafe extern "C" fn(Fmi2Component),
+}
+
+impl Fmi2Functions {
+    /// Retrieves the functions of the FMI API from the `library`.
+    ///
+    /// # Safety
+    ///
+    /// The library must implement the FMI 2.0 API.
+    unsafe fn load(library: &Library) -> ZFResult<Self> {
+        macro_rules! symbol {
+            ($name:literal) => {
+                *library
+                    .get($name)
+                    .map_err(|e| zferror!(ErrorKind::LoadingError, "Missing FMI function: {e}"))?
+            };
+        }
+
+        Ok(Self {
+            instantiate: symbol!(b"fmi2Instantiate"),
+            setup_experiment: symbol!(b"fmi2SetupExperiment"),
+            enter_initialization_mode: symbol!(b"fmi2EnterInitializationMode"),
+            exit_initialization_mode: symbol!(b"fmi2ExitInitializationMode"),
+            set_real: symbol!(b"fmi2SetReal"),
+            get_real: symbol!(b"fmi2GetReal"),
+            do_step: symbol!(b"fmi2DoStep"),
+            terminate: symbol!(b"fmi2Terminate"),
+            free_instance: symbol!(b"fmi2FreeInstance"),
+        })
+    }
+}
+
+/// A co-simulation instance of an FMU, terminated and freed when dropped.
+struct FmuInstance {
+    functions: Fmi2Functions,
+    component: Fmi2Component,
+    // The FMU keeps a pointer to its callbacks, they must outlive the component.
+    _callbacks: Box<Fmi2CallbackFunctions>,
+    // The fields are dropped in the order of their declaration: the library must come last.
+    _library: Library,
+}
+
+// SAFETY: the component is only accessed behind the `Mutex` of the `Fmu`, one call at a time.
+unsafe impl Send for FmuInstance {}
+unsafe impl Sync for FmuInstance {}
+
+/// Returns an error if the `status` of the FMI function `function` is neither OK nor a warning.
+fn check(function: &str, status: Fmi2Status) -> ZFResult<()> {
+    if status > FMI2_WARNING {
+        bail!(
+            ErrorKind::GenericError,
+            "{function} failed with the FMI status {status}"
+        );
+    }
+    Ok(())
+}
+
+impl FmuInstance {
+    /// Loads, instantiates and initializes the FMU, its simulated time starting at 0.
+    fn new(name: &str, configuration: &FmuConfiguration) -> ZFResult<Self> {
+        let to_c_string = |string: &str| {
+            CString::new(string).map_err(|e| zferror!(ErrorKind::ConfigurationError, e))
+        };
+        let name = to_c_string(name)?;
+        let guid = to_c_string(&configuration.guid)?;
+        let resources = to_c_string(configuration.resources.as_deref().unwrap_or_default())?;
+
+        // SAFETY: the library is expected to be the one of an FMU, implementing the FMI 2.0 API.
+        unsafe {
+            let library = Library::new(&configuration.library)
+                .map_err(|e| zferror!(ErrorKind::LoadingError, e))?;
+            let functions = Fmi2Functions::load(&library)?;
+            let callbacks = Box::new(Fmi2CallbackFunctions {
+                logger: fmi2_logger,
+                allocate_memory: calloc,
+                free_memory: free,
+                step_finished: None,
+                component_environment: std::ptr::null_mut(),
+            });
+
+            let component = (functions.instantiate)(
+                name.as_ptr(),
+                FMI2_CO_SIMULATION,
+                guid.as_ptr(),
+                resources.as_ptr(),
+                &*callbacks,
+                FMI2_FALSE,
+                FMI2_FALSE,
+            );
+            if component.is_null() {
+                bail!(
+                    ErrorKind::LoadingError,
+                    "Failed to instantiate the FMU < {} >",
+                    configuration.library
+                );
+            }
+
+            let instance = Self {
+                functions,
+                component,
+                _callbacks: callbacks,
+                _library: library,
+            };
+
+            check(
+                "fmi2SetupExperiment",
+                (instance.functions.setup_experiment)(
+                    component, FMI2_FALSE, 0.0, 0.0, FMI2_FALSE, 0.0,
+                ),
+            )?;
+            check(
+                "fmi2EnterInitializationMode",
+                (instance.functions.enter_initialization_mode)(component),
+            )?;
+            check(
+                "fmi2ExitInitializationMode",
+                (instance.functions.exit_initialization_mode)(component),
+            )?;
+
+            Ok(instance)
+        }
+    }
+
+    fn set_reals(&self, references: &[u32], values: &[f64]) -> ZFResult<()> {
+        // SAFETY: both slices have the same length, checked by the caller.
+        let status = unsafe {
+            (self.functions.set_real)(
+                self.component,
+                references.as_ptr(),
+                references.len(),
+                values.as_ptr(),
+            )
+        };
+        check("fmi2SetReal", status)
+    }
+
+    fn get_reals(&self, references: &[u32]) -> ZFResult<Vec<f64>> {
+        let mut values = vec![0.0; references.len()];
+        // SAFETY: both slices have the same length.
+        let status = unsafe {
+            (self.functions.get_real)(
+                self.component,
+                references.as_ptr(),
+                references.len(),
+                values.as_mut_ptr(),
+            )
+        };
+        check("fmi2GetReal", status)?;
+        Ok(values)
+    }
+
+    fn do_step(&self, time: f64, step: f64) -> ZFResult<()> {
+        // SAFETY: the component was successfully instantiated and initialized.
+        let status = unsafe { (self.functions.do_step)(self.component, time, step, FMI2_TRUE) };
+        check("fmi2DoStep", status)
+    }
+}
+
+impl Drop for FmuInstance {
+    fn drop(&mut self) {
+        // SAFETY: the component is not used after being freed.
+        unsafe {
+            (self.functions.terminate)(self.component);
+            (self.functions.free_instance)(self.component);
+        }
+    }
+}
+
+/// The builtin FMU operator
+/// It wraps a Functional Mock-up Unit (FMI 2.0, co-simulation) such that simulated models (e.g. of a
+/// plant) can be mixed with regular nodes.
+///
+/// Each iteration advances the model by one `step`: the last values received on its inputs are
+/// set, the model performs the step and the values of its outputs are sent. It then sleeps for the
+/// duration of the step which, when the instance runs in simulation mode, is measured in simulated
+/// time: the model is thus driven by the virtual-time scheduler.
+///
+/// The values are `f64` serialized with `bincode`. The inputs and outputs of the operator are those
+/// of its configuration, mapped to the value references of real variables of the model:
+///
+/// ```yaml
+/// library: /opt/fmus/plant/binaries/linux64/Plant.so
+/// guid: "{8c4e810f-3df3-4a00-8276-176fa3c9f003}"
+/// resources: file:///opt/fmus/plant/resources # optional
+/// step: 10ms                                   # optional, default: 10ms
+/// inputs:
+///   torque: 0
+/// outputs:
+///   speed: 1
+/// ```
+pub(crate) struct Fmu {
+    context: Context,
+    inputs: Vec<(InputRaw, u32)>,
+    outputs: Vec<(OutputRaw, u32)>,
+    step: Duration,
+    state: Mutex<(FmuInstance, Duration)>,
+}
+
+/// Private function to retrieve the "Constructor" for the FMU
+pub(crate) fn get_fmu_declaration() -> NodeDeclaration<OperatorFn> {
+    NodeDeclaration::<OperatorFn> {
+        rustc_version: RUSTC_VERSION,
+        core_version: CORE_VERSION,
+        constructor: |context: Context,
+                      configuration: Option<Configuration>,
+                      inputs: Inputs,
+                      outputs: Outputs| {
+            Box::pin(async {
+                let node = Fmu::new(context, configuration, inputs, outputs).await?;
+                Ok(Arc::new(node) as Arc<dyn Node>)
+            })
+        },
+    }
+}
+
+/// Private function to retrieve the Descriptor for the FMU
+pub(crate) fn get_fmu_descriptor(configuration: &Configuration) -> ZFResult<OperatorDescriptor> {
+    let fmu = get_fmu_configuration(configuration)?;
+
+    Ok(OperatorDescriptor {
+        id: "fmu".into(),
+        inputs: fmu
+            .inputs
+            .keys()
+            .map(|input| input.as_str().into())
+            .collect(),
+        outputs: fmu
+            .outputs
+            .keys()
+            .map(|output| output.as_str().into())
+            .collect(),
+        side_outputs: vec![],
+        input_policies: HashMap::new(),
+        input_types: HashMap::new(),
+        input_units: HashMap::new(),
+        output_types: HashMap::new(),
+        output_units: HashMap::new(),
+        uri: Some("builtin://fmu".into()),
+        configuration: Some(configuration.clone()),
+    })
+}
+
+#[async_trait]
+impl Operator for Fmu {
+    async fn new(
+        context: Context,
+        configuration: Option<Configuration>,
+        mut inputs: Inputs,
+        mut outputs: Outputs,
+    ) -> ZFResult<Self> {
+        let configuration = match configuration {
+            Some(configuration) => get_fmu_configuration(&configuration)?,
+            None => bail!(
+                ErrorKind::MissingConfiguration,
+                "Builtin FMU needs a configuration!"
+            ),
+        };
+
+        let inputs = configuration
+            .inputs
+            .iter()
+            .map(|(port, reference)| {
+                let input = inputs.take(port).ok_or_else(|| {
+                    zferror!(
+                        ErrorKind::MissingInput(port.clone()),
+                        "Unable to find input: {port}"
+                    )
+                })?;
+                Ok((input.raw(), *reference))
+            })
+            .collect::<ZFResult<Vec<_>>>()?;
+
+        let outputs = configuration
+            .outputs
+            .iter()
+            .map(|(port, reference)| {
+                let output = outputs.take(port).ok_or_else(|| {
+                    zferror!(
+                        ErrorKind::MissingOutput(port.clone()),
+                        "Unable to find output: {port}"
+                    )
+                })?;
+                Ok((output.raw(), *reference))
+            })
+            .collect::<ZFResult<Vec<_>>>()?;
+
+        let instance = FmuInstance::new(context.get_flow_name(), &configuration)?;
+
+        Ok(Fmu {
+            context,
+            inputs,
+            outputs,
+            step: configuration.step.unwrap_or(DEFAULT_STEP),
+            state: Mutex::new((instance, Duration::ZERO)),
+        })
+    }
+}
+
+#[async_trait]
+impl Node for Fmu {
+    async fn iteration(&self) -> ZFResult<()> {
+        let (references, values) = self.latest_inputs()?;
+
+        let outputs = {
+            let mut state = self.state.lock().await;
+            let (instance, time) = &mut *state;
+            if !references.is_empty() {
+                instance.set_reals(&references, &values)?;
+            }
+            instance.do_step(time.as_secs_f64(), self.step.as_secs_f64())?;
+            *time += self.step;
+
+            let references = self
+                .outputs
+                .iter()
+                .map(|(_, reference)| *reference)
+                .collect::<Vec<_>>();
+            instance.get_reals(&references)?
+        };
+
+        for ((output, _), value) in self.outputs.iter().zip(outputs) {
+            let bytes = bincode::serialize(&value)
+                .map_err(|e| zferror!(ErrorKind::SerializationError, e))?;
+            output.send(bytes, None).await?;
+        }
+
+        self.context.sleep(self.step).await;
+        Ok(())
+    }
+}
+
+impl Fmu {
+    /// Returns, for each input that received data since the last step, its value reference and
+    /// the last value it received.
+    fn latest_inputs(&self) -> ZFResult<(Vec<u32>, Vec<f64>)> {
+        let mut references = Vec::with_capacity(self.inputs.len());
+        let mut values = Vec::with_capacity(self.inputs.len());
+
+        for (input, reference) in &self.inputs {
+            let mut latest = None;
+            while let Ok(message) = input.try_recv() {
+                if let LinkMessage::Data(data_message) = message {
+                    latest = Some(data_message);
+                }
+            }
+
+            if let Some(data_message) = latest {
+                let bytes = (*data_message).try_as_bytes()?;
+                let value: f64 = bincode::deserialize(&bytes)
+                    .map_err(|e| zferror!(ErrorKind::DeserializationError, e))?;
+                references.push(*reference);
+                values.push(value);
+            }
+        }
+
+        Ok((references, values))
+    }
+}
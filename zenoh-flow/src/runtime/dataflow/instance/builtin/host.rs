@@ -0,0 +1,377 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::{
+    bail,
+    model::descriptor::{SinkDescriptor, SourceDescriptor},
+    prelude::{
+        zferror, Configuration, Context, ErrorKind, InputRaw, Inputs, Node, NodeId, OutputRaw,
+        Outputs, PortId, Sink, Source,
+    },
+    runtime::dataflow::{
+        instance::builtin::zenoh::{wait_flow_input, ZFInputFut},
+        loader::{NodeDeclaration, CORE_VERSION, RUSTC_VERSION},
+        node::{SinkFn, SourceFn},
+    },
+    types::LinkMessage,
+    Result as ZFResult,
+};
+use async_lock::Mutex;
+use async_trait::async_trait;
+use futures::future::select_all;
+use std::collections::HashMap;
+use std::mem;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Key for the ports used by the built-in Source/Sink.
+static KEY_PORTS: &str = "ports";
+
+/// Key for the id of the node, set by the embedded runtime, used by the built-in Source/Sink.
+pub(crate) static KEY_NODE: &str = "node";
+
+/// The data exchanged between the host application and the built-in Host Source/Sink: the port on
+/// which they are sent, or received, and their bytes.
+pub(crate) type HostMessage = (PortId, Vec<u8>);
+
+/// The closure a built-in Host Sink invokes for each data it receives.
+pub(crate) type HostCallback = Arc<dyn Fn(PortId, Vec<u8>) + Send + Sync>;
+
+/// The channels and callbacks, indexed by the id of the node, between the host application
+/// embedding the instance and its built-in Host Sources and Sinks.
+///
+/// They are only created by the embedded runtime (see
+/// [`Runtime`](crate::runtime::embedded::Runtime)): the instances created by a daemon have none.
+#[derive(Default)]
+pub(crate) struct HostChannels {
+    pub(crate) sources: HashMap<NodeId, flume::Receiver<HostMessage>>,
+    pub(crate) sinks: HashMap<NodeId, HostCallback>,
+}
+
+/// Retrieves the list of ports from the configuration.
+fn get_ports(configuration: &Configuration) -> ZFResult<Vec<PortId>> {
+    let ports = configuration
+        .get(KEY_PORTS)
+        .ok_or_else(|| {
+            zferror!(
+                ErrorKind::ConfigurationError,
+                "Missing ports in builtin Host configuration"
+            )
+        })?
+        .as_array()
+        .ok_or_else(|| {
+            zferror!(
+                ErrorKind::ConfigurationError,
+                "Unable to convert ports to an array: {:?}",
+                configuration
+            )
+        })?;
+
+    let mut res = Vec::with_capacity(ports.len());
+    for value in ports {
+        let port = value.as_str().ok_or_else(|| {
+            zferror!(
+                ErrorKind::ConfigurationError,
+                "Unable to convert value to string: {:?}",
+                value
+            )
+        })?;
+        res.push(port.into());
+    }
+
+    Ok(res)
+}
+
+/// Retrieves the id of the node, set by the embedded runtime, from the configuration.
+fn get_node(configuration: &Configuration) -> ZFResult<NodeId> {
+    configuration
+        .get(KEY_NODE)
+        .and_then(|value| value.as_str())
+        .map(|node| node.into())
+        .ok_or_else(|| {
+            zferror!(
+                ErrorKind::ConfigurationError,
+                "The builtin Host nodes can only run in an embedded runtime"
+            )
+            .into()
+        })
+}
+
+/// The builtin Host Source
+/// It reads the channel owned by the application embedding the instance and sends each data on the
+/// associated output (see [`HostInput`](crate::runtime::embedded::HostInput) and
+/// [`RuntimeBuilder::channel_source`](crate::runtime::embedded::RuntimeBuilder::channel_source)).
+/// Once all the senders of the channel are dropped, an `EndOfStream` is sent on all the outputs.
+/// It expects a configuration in the format
+///
+/// ```yaml
+/// ports: [<output_id>, <output_id>]
+/// ```
+///
+/// It expects the output(s) defined in the configuration to be connected.
+pub(crate) struct ChannelSource {
+    receiver: flume::Receiver<HostMessage>,
+    outputs: HashMap<PortId, OutputRaw>,
+    ended: AtomicBool,
+}
+
+/// Private function to retrieve the "Constructor" for the ChannelSource
+pub(crate) fn get_host_source_declaration() -> NodeDeclaration<SourceFn> {
+    NodeDeclaration::<SourceFn> {
+        rustc_version: RUSTC_VERSION,
+        core_version: CORE_VERSION,
+        constructor: |context: Context, configuration: Option<Configuration>, outputs: Outputs| {
+            Box::pin(async {
+                let node = ChannelSource::new(context, configuration, outputs).await?;
+                Ok(Arc::new(node) as Arc<dyn Node>)
+            })
+        },
+    }
+}
+
+/// Private function to retrieve the Descriptor for the ChannelSource
+pub(crate) fn get_host_source_descriptor(
+    configuration: &Configuration,
+) -> ZFResult<SourceDescriptor> {
+    let mut outputs = get_ports(configuration)?;
+    outputs.sort();
+
+    Ok(SourceDescriptor {
+        id: "host-source".into(),
+        outputs,
+        output_types: HashMap::new(),
+        output_units: HashMap::new(),
+        uri: Some("builtin://host".into()),
+        configuration: Some(configuration.clone()),
+    })
+}
+
+#[async_trait]
+impl Source for ChannelSource {
+    async fn new(
+        context: Context,
+        configuration: Option<Configuration>,
+        mut outputs: Outputs,
+    ) -> ZFResult<Self> {
+        match configuration {
+            Some(configuration) => {
+                let node = get_node(&configuration)?;
+                let receiver = context
+                    .host_channels()
+                    .sources
+                    .get(&node)
+                    .cloned()
+                    .ok_or_else(|| {
+                        zferror!(
+                            ErrorKind::ConfigurationError,
+                            "No channel from the host application for < {node} >"
+                        )
+                    })?;
+
+                let mut source_outputs = HashMap::new();
+                for id in get_ports(&configuration)? {
+                    let output = outputs
+                        .take(&id)
+                        .ok_or(zferror!(
+                            ErrorKind::MissingOutput(id.to_string()),
+                            "Unable to find output: {id}"
+                        ))?
+                        .raw();
+                    source_outputs.insert(id, output);
+                }
+
+                Ok(ChannelSource {
+                    receiver,
+                    outputs: source_outputs,
+                    ended: AtomicBool::new(false),
+                })
+            }
+            None => {
+                bail!(
+                    ErrorKind::MissingConfiguration,
+                    "Builtin ChannelSource needs a configuration!"
+                )
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Node for ChannelSource {
+    async fn iteration(&self) -> ZFResult<()> {
+        if self.ended.load(Ordering::Relaxed) {
+            // The stream ended: there is nothing left to do until the Source is stopped.
+            futures::future::pending::<()>().await;
+        }
+
+        match self.receiver.recv_async().await {
+            Ok((id, data)) => {
+                let output = self.outputs.get(&id).ok_or_else(|| {
+                    zferror!(
+                        ErrorKind::MissingOutput(id.to_string()),
+                        "Unable to find output: {id}"
+                    )
+                })?;
+                output.send(data, None).await?;
+            }
+            Err(_) => {
+                log::debug!("[ChannelSource] all the senders of the channel were dropped");
+                for output in self.outputs.values() {
+                    output.send_end_of_stream().await?;
+                }
+                self.ended.store(true, Ordering::Relaxed);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The builtin Host Sink
+/// It invokes, for each data it receives, the callback of the application embedding the instance
+/// (see [`HostOutput`](crate::runtime::embedded::HostOutput) and
+/// [`RuntimeBuilder::callback_sink`](crate::runtime::embedded::RuntimeBuilder::callback_sink)).
+/// It expects a configuration in the format
+///
+/// ```yaml
+/// ports: [<input_id>, <input_id>]
+/// ```
+///
+/// It expects the input(s) defined in the configuration to be connected.
+pub(crate) struct CallbackSink {
+    callback: HostCallback,
+    inputs: HashMap<PortId, InputRaw>,
+    futs: Arc<Mutex<Vec<ZFInputFut>>>,
+}
+
+/// Private function to retrieve the "Constructor" for the CallbackSink
+pub(crate) fn get_host_sink_declaration() -> NodeDeclaration<SinkFn> {
+    NodeDeclaration::<SinkFn> {
+        rustc_version: RUSTC_VERSION,
+        core_version: CORE_VERSION,
+        constructor: |context: Context, configuration: Option<Configuration>, inputs: Inputs| {
+            Box::pin(async {
+                let node = CallbackSink::new(context, configuration, inputs).await?;
+                Ok(Arc::new(node) as Arc<dyn Node>)
+            })
+        },
+    }
+}
+
+/// Private function to retrieve the Descriptor for the CallbackSink
+pub(crate) fn get_host_sink_descriptor(configuration: &Configuration) -> ZFResult<SinkDescriptor> {
+    let mut inputs = get_ports(configuration)?;
+    inputs.sort();
+
+    Ok(SinkDescriptor {
+        id: "host-sink".into(),
+        inputs,
+        input_policies: HashMap::new(),
+        input_types: HashMap::new(),
+        input_units: HashMap::new(),
+        uri: Some("builtin://host".into()),
+        configuration: Some(configuration.clone()),
+    })
+}
+
+#[async_trait]
+impl Sink for CallbackSink {
+    async fn new(
+        context: Context,
+        configuration: Option<Configuration>,
+        mut inputs: Inputs,
+    ) -> ZFResult<Self> {
+        match configuration {
+            Some(configuration) => {
+                let node = get_node(&configuration)?;
+                let callback = context
+                    .host_channels()
+                    .sinks
+                    .get(&node)
+                    .cloned()
+                    .ok_or_else(|| {
+                        zferror!(
+                            ErrorKind::ConfigurationError,
+                            "No callback of the host application for < {node} >"
+                        )
+                    })?;
+
+                let mut sink_inputs = HashMap::new();
+                for id in get_ports(&configuration)? {
+                    let input = inputs
+                        .take(&id)
+                        .ok_or(zferror!(
+                            ErrorKind::MissingInput(id.to_string()),
+                            "Unable to find input: {id}"
+                        ))?
+                        .raw();
+                    sink_inputs.insert(id, input);
+                }
+
+                let futs = sink_inputs
+                    .iter()
+                    .map(|(id, input)| wait_flow_input(id.clone(), input))
+                    .collect();
+
+                Ok(CallbackSink {
+                    callback,
+                    inputs: sink_inputs,
+                    futs: Arc::new(Mutex::new(futs)),
+                })
+            }
+            None => {
+                bail!(
+                    ErrorKind::MissingConfiguration,
+                    "Builtin CallbackSink needs a configuration!"
+                )
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Node for CallbackSink {
+    async fn iteration(&self) -> ZFResult<()> {
+        // Getting the list of futures to poll in a temporary variable (that `select_all` can take
+        // ownership of)
+        let mut futs = self.futs.lock().await;
+        let tmp = mem::take(&mut *futs);
+
+        let ((id, result), _index, mut remaining) = select_all(tmp).await;
+
+        match result {
+            Ok(LinkMessage::Data(dm)) => {
+                let mut data = Vec::new();
+                dm.try_as_bytes_into(&mut data)?;
+                (self.callback)(id.clone(), data);
+            }
+            Ok(_) => (), // Not the right message, ignore it.
+            Err(e) => log::error!("[CallbackSink] got error on link {id}: {e:?}"),
+        }
+
+        // Add back the input that got polled
+        let input = self.inputs.get(&id).ok_or_else(|| {
+            zferror!(
+                ErrorKind::RecvError,
+                "Unable to find input < {id} > for built-in Host Sink"
+            )
+        })?;
+        remaining.push(wait_flow_input(id, input));
+
+        // Set back the complete list for the next iteration
+        *futs = remaining;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,442 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::{
+    bail,
+    model::descriptor::{SinkDescriptor, SourceDescriptor},
+    prelude::{
+        zferror, Configuration, Context, ErrorKind, InputRaw, Inputs, Node, OutputRaw, Outputs,
+        PortId, Sink, Source,
+    },
+    runtime::dataflow::{
+        instance::builtin::{
+            get_duration_or_default,
+            zenoh::{wait_flow_input, ZFInputFut},
+        },
+        loader::{NodeDeclaration, CORE_VERSION, RUSTC_VERSION},
+        node::{SinkFn, SourceFn},
+    },
+    types::LinkMessage,
+    Result as ZFResult,
+};
+use async_lock::Mutex;
+use async_trait::async_trait;
+use futures::future::select_all;
+use std::collections::HashMap;
+use std::mem;
+use std::sync::Arc;
+use std::time::Duration;
+use url::Url;
+
+/// Key for the endpoints used by the built-in Source/Sink.
+static KEY_ENDPOINTS: &str = "endpoints";
+
+/// Key for the polling period used by the built-in Source.
+static KEY_PERIOD: &str = "period";
+
+/// Key for the number of retries used by the built-in Sink.
+static KEY_RETRIES: &str = "retries";
+
+/// Key for the initial backoff between two retries used by the built-in Sink.
+static KEY_BACKOFF: &str = "backoff";
+
+/// Default polling period of the built-in Source (1s).
+static DEFAULT_PERIOD: Duration = Duration::from_secs(1);
+
+/// Default number of retries of the built-in Sink (3).
+static DEFAULT_RETRIES: u64 = 3;
+
+/// Default initial backoff of the built-in Sink (100ms).
+static DEFAULT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Maximum backoff between two retries of the built-in Sink (30s), unless its initial backoff is
+/// greater.
+static MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Retrieves the `<port_id> : <url>` map from the configuration, checking that every URL can be
+/// parsed.
+fn get_endpoints(configuration: &Configuration) -> ZFResult<HashMap<PortId, Url>> {
+    let endpoints = configuration
+        .get(KEY_ENDPOINTS)
+        .ok_or_else(|| {
+            zferror!(
+                ErrorKind::ConfigurationError,
+                "Missing endpoints in builtin HTTP configuration"
+            )
+        })?
+        .as_object()
+        .ok_or_else(|| {
+            zferror!(
+                ErrorKind::ConfigurationError,
+                "Unable to convert configuration to HashMap: {:?}",
+                configuration
+            )
+        })?;
+
+    let mut res = HashMap::with_capacity(endpoints.len());
+    for (id, value) in endpoints {
+        let url = value.as_str().ok_or_else(|| {
+            zferror!(
+                ErrorKind::ConfigurationError,
+                "Unable to convert value to string: {:?}",
+                value
+            )
+        })?;
+        let url = Url::parse(url).map_err(|e| {
+            zferror!(
+                ErrorKind::ConfigurationError,
+                "Unable to parse URL < {url} > of port < {id} >: {e}"
+            )
+        })?;
+        res.insert(id.clone().into(), url);
+    }
+
+    Ok(res)
+}
+
+/// The builtin HTTP Source
+/// It polls, every `period`, multiple REST endpoints and can have multiple outputs.
+/// The body of each response is sent, as bytes, on the associated output.
+/// It expects a configuration in the format
+///
+/// ```yaml
+/// period: 500ms # optional, defaults to 1s
+/// endpoints:
+///   <output_id> : <url>
+///   <output_id> : <url>
+/// ```
+///
+/// It expects the output(s) defined in the configuration to be connected.
+pub(crate) struct HttpSource {
+    context: Context,
+    client: surf::Client,
+    endpoints: HashMap<PortId, (Url, OutputRaw)>,
+    period: Duration,
+}
+
+/// Private function to retrieve the "Constructor" for the HttpSource
+pub(crate) fn get_http_source_declaration() -> NodeDeclaration<SourceFn> {
+    NodeDeclaration::<SourceFn> {
+        rustc_version: RUSTC_VERSION,
+        core_version: CORE_VERSION,
+        constructor: |context: Context, configuration: Option<Configuration>, outputs: Outputs| {
+            Box::pin(async {
+                let node = HttpSource::new(context, configuration, outputs).await?;
+                Ok(Arc::new(node) as Arc<dyn Node>)
+            })
+        },
+    }
+}
+
+/// Private function to retrieve the Descriptor for the HttpSource
+pub(crate) fn get_http_source_descriptor(
+    configuration: &Configuration,
+) -> ZFResult<SourceDescriptor> {
+    get_duration_or_default(configuration, KEY_PERIOD, DEFAULT_PERIOD)?;
+    let mut outputs: Vec<PortId> = get_endpoints(configuration)?.into_keys().collect();
+    outputs.sort();
+
+    Ok(SourceDescriptor {
+        id: "http-source".into(),
+        outputs,
+        output_types: HashMap::new(),
+        output_units: HashMap::new(),
+        uri: Some("builtin://http".into()),
+        configuration: Some(configuration.clone()),
+    })
+}
+
+#[async_trait]
+impl Source for HttpSource {
+    async fn new(
+        context: Context,
+        configuration: Option<Configuration>,
+        mut outputs: Outputs,
+    ) -> ZFResult<Self> {
+        match configuration {
+            Some(configuration) => {
+                let period = get_duration_or_default(&configuration, KEY_PERIOD, DEFAULT_PERIOD)?;
+
+                let mut endpoints = HashMap::new();
+                for (id, url) in get_endpoints(&configuration)? {
+                    let output = outputs
+                        .take(&id)
+                        .ok_or(zferror!(
+                            ErrorKind::MissingOutput(id.to_string()),
+                            "Unable to find output: {id}"
+                        ))?
+                        .raw();
+                    endpoints.insert(id, (url, output));
+                }
+
+                Ok(HttpSource {
+                    context,
+                    client: surf::Client::new(),
+                    endpoints,
+                    period,
+                })
+            }
+            None => {
+                bail!(
+                    ErrorKind::MissingConfiguration,
+                    "Builtin HttpSource needs a configuration!"
+                )
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Node for HttpSource {
+    async fn iteration(&self) -> ZFResult<()> {
+        self.context.sleep(self.period).await;
+
+        for (id, (url, output)) in self.endpoints.iter() {
+            // A failing endpoint should not stop the Source: we log the error and wait for the
+            // next period.
+            match self.client.get(url.as_str()).recv_bytes().await {
+                Ok(data) => {
+                    log::trace!(
+                        "[HttpSource] Received data from {url} Len: {} for output: {id}",
+                        data.len()
+                    );
+                    output.send(data, None).await?;
+                }
+                Err(e) => log::error!("[HttpSource] unable to poll {url} for output {id}: {e}"),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The builtin HTTP Sink
+/// It POSTs each message it receives to the URL associated with the input.
+/// In case of failure, the request is retried up to `retries` times, the delay between two
+/// attempts starting at `backoff` and doubling after each attempt, up to 30s.
+/// It expects a configuration in the format
+///
+/// ```yaml
+/// retries: 3      # optional, defaults to 3
+/// backoff: 100ms  # optional, defaults to 100ms
+/// endpoints:
+///   <input_id> : <url>
+///   <input_id> : <url>
+/// ```
+///
+/// It expects the input(s) defined in the configuration to be connected.
+pub(crate) struct HttpSink {
+    client: surf::Client,
+    inputs: HashMap<PortId, InputRaw>,
+    endpoints: HashMap<PortId, Url>,
+    retries: u64,
+    backoff: Duration,
+    state: Arc<Mutex<HttpSinkState>>,
+}
+
+/// The HttpSinkState stores in a single structure all the fields protected by a lock.
+///
+/// The fields are:
+/// - `futs` contains the pending futures waiting for inputs on their channel;
+/// - `buffer` holds a growable vector of bytes in which the result of the serialization of the data
+///   is stored.
+pub(crate) struct HttpSinkState {
+    pub(crate) futs: Vec<ZFInputFut>,
+    pub(crate) buffer: Vec<u8>,
+}
+
+/// Private function to retrieve the "Constructor" for the HttpSink
+pub(crate) fn get_http_sink_declaration() -> NodeDeclaration<SinkFn> {
+    NodeDeclaration::<SinkFn> {
+        rustc_version: RUSTC_VERSION,
+        core_version: CORE_VERSION,
+        constructor: |context: Context, configuration: Option<Configuration>, inputs: Inputs| {
+            Box::pin(async {
+                let node = HttpSink::new(context, configuration, inputs).await?;
+                Ok(Arc::new(node) as Arc<dyn Node>)
+            })
+        },
+    }
+}
+
+/// Private function to retrieve the Descriptor for the HttpSink
+pub(crate) fn get_http_sink_descriptor(configuration: &Configuration) -> ZFResult<SinkDescriptor> {
+    get_duration_or_default(configuration, KEY_BACKOFF, DEFAULT_BACKOFF)?;
+    let mut inputs: Vec<PortId> = get_endpoints(configuration)?.into_keys().collect();
+    inputs.sort();
+
+    Ok(SinkDescriptor {
+        id: "http-sink".into(),
+        inputs,
+        input_policies: HashMap::new(),
+        input_types: HashMap::new(),
+        input_units: HashMap::new(),
+        uri: Some("builtin://http".into()),
+        configuration: Some(configuration.clone()),
+    })
+}
+
+impl HttpSink {
+    /// POSTs the `body` to the `url`, retrying with an exponential backoff in case of failure.
+    ///
+    /// # Errors
+    ///
+    /// An error variant is returned if the request did not succeed after `retries` retries.
+    async fn post_with_retries(&self, url: &Url, body: &[u8]) -> ZFResult<()> {
+        let mut backoff = self.backoff;
+        let mut attempt = 0;
+
+        loop {
+            let error = match self
+                .client
+                .post(url.as_str())
+                .body(surf::Body::from_bytes(body.to_vec()))
+                .await
+            {
+                Ok(response) if response.status().is_success() => return Ok(()),
+                Ok(response) => format!("status {}", response.status()),
+                Err(e) => e.to_string(),
+            };
+
+            if attempt >= self.retries {
+                bail!(
+                    ErrorKind::SendError,
+                    "Unable to POST to {url} after {} attempt(s): {error}",
+                    attempt + 1
+                )
+            }
+
+            log::warn!("[HttpSink] POST to {url} failed ({error}), retrying in {backoff:?}");
+            crate::executor::sleep(backoff).await;
+            backoff = next_backoff(backoff, self.backoff);
+            attempt += 1;
+        }
+    }
+}
+
+/// Returns the delay before the next retry of the built-in Sink: the `backoff` doubled, without
+/// exceeding [MAX_BACKOFF] or the `initial` backoff if it is greater.
+fn next_backoff(backoff: Duration, initial: Duration) -> Duration {
+    backoff.saturating_mul(2).min(MAX_BACKOFF.max(initial))
+}
+
+#[async_trait]
+impl Sink for HttpSink {
+    async fn new(
+        _context: Context,
+        configuration: Option<Configuration>,
+        mut inputs: Inputs,
+    ) -> ZFResult<Self> {
+        match configuration {
+            Some(configuration) => {
+                let retries = match configuration.get(KEY_RETRIES) {
+                    Some(value) => value.as_u64().ok_or_else(|| {
+                        zferror!(
+                            ErrorKind::ConfigurationError,
+                            "Unable to convert value of {KEY_RETRIES} to u64: {:?}",
+                            value
+                        )
+                    })?,
+                    None => DEFAULT_RETRIES,
+                };
+                let backoff =
+                    get_duration_or_default(&configuration, KEY_BACKOFF, DEFAULT_BACKOFF)?;
+
+                let endpoints = get_endpoints(&configuration)?;
+                let mut sink_inputs = HashMap::with_capacity(endpoints.len());
+                for id in endpoints.keys() {
+                    let input = inputs
+                        .take(id)
+                        .ok_or(zferror!(
+                            ErrorKind::MissingInput(id.to_string()),
+                            "Unable to find input: {id}"
+                        ))?
+                        .raw();
+                    sink_inputs.insert(id.clone(), input);
+                }
+
+                let futs = sink_inputs
+                    .iter()
+                    .map(|(id, input)| wait_flow_input(id.clone(), input))
+                    .collect();
+
+                Ok(HttpSink {
+                    client: surf::Client::new(),
+                    inputs: sink_inputs,
+                    endpoints,
+                    retries,
+                    backoff,
+                    state: Arc::new(Mutex::new(HttpSinkState {
+                        futs,
+                        buffer: Vec::new(),
+                    })),
+                })
+            }
+            None => {
+                bail!(
+                    ErrorKind::MissingConfiguration,
+                    "Builtin HttpSink needs a configuration!"
+                )
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Node for HttpSink {
+    async fn iteration(&self) -> ZFResult<()> {
+        // Getting the list of futures to poll in a temporary variable (that `select_all` can take
+        // ownership of)
+        let mut state = self.state.lock().await;
+        let tmp = mem::take(&mut state.futs);
+
+        let ((id, result), _index, mut remaining) = select_all(tmp).await;
+
+        match result {
+            Ok(LinkMessage::Data(dm)) => {
+                dm.try_as_bytes_into(&mut state.buffer)?;
+
+                let url = self.endpoints.get(&id).ok_or_else(|| {
+                    zferror!(ErrorKind::SendError, "Unable to find endpoint for {id}")
+                })?;
+
+                // Once all the retries are exhausted the message is dropped: a web service being
+                // unavailable should not stop the Sink.
+                if let Err(e) = self.post_with_retries(url, &state.buffer).await {
+                    log::error!("[HttpSink] dropping message received on {id}: {e:?}");
+                }
+            }
+            Ok(_) => (), // Not the right message, ignore it.
+            Err(e) => log::error!("[HttpSink] got error on link {id}: {e:?}"),
+        }
+
+        // Add back the input that got polled
+        let input = self.inputs.get(&id).ok_or_else(|| {
+            zferror!(
+                ErrorKind::RecvError,
+                "Unable to find input < {id} > for built-in HTTP Sink"
+            )
+        })?;
+        remaining.push(wait_flow_input(id, input));
+
+        // Set back the complete list for the next iteration
+        state.futs = remaining;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/builtin-http.rs"]
+mod tests;
@@ -0,0 +1,255 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::{
+    bail,
+    model::descriptor::OperatorDescriptor,
+    prelude::{
+        zferror, Configuration, Context, ErrorKind, InputRaw, Inputs, Node, Operator, OutputRaw,
+        Outputs, PortId,
+    },
+    runtime::dataflow::{
+        loader::{NodeDeclaration, CORE_VERSION, RUSTC_VERSION},
+        node::OperatorFn,
+    },
+    types::LinkMessage,
+    Result as ZFResult,
+};
+use async_lock::Mutex;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Key for the number of inputs of the built-in Merge.
+pub(crate) static KEY_INPUTS: &str = "inputs";
+
+/// Identifier of the output of the built-in Merge.
+pub(crate) static MERGE_OUTPUT: &str = "out";
+
+/// Returns the identifier of the input at position `index` of the built-in Merge.
+pub(crate) fn merge_input(index: usize) -> PortId {
+    format!("in-{index}").into()
+}
+
+/// Retrieves the number of inputs from the configuration.
+fn get_inputs_count(configuration: &Configuration) -> ZFResult<usize> {
+    let inputs = configuration.get(KEY_INPUTS).ok_or_else(|| {
+        zferror!(
+            ErrorKind::ConfigurationError,
+            "Missing {KEY_INPUTS} in builtin Merge configuration"
+        )
+    })?;
+
+    let inputs = inputs.as_u64().ok_or_else(|| {
+        zferror!(
+            ErrorKind::ConfigurationError,
+            "Unable to convert value of {KEY_INPUTS} to an unsigned integer: {:?}",
+            inputs
+        )
+    })?;
+
+    if inputs == 0 {
+        bail!(
+            ErrorKind::ConfigurationError,
+            "The builtin Merge requires at least one input"
+        )
+    }
+
+    Ok(inputs as usize)
+}
+
+/// The builtin Merge operator
+/// It forwards, from its inputs `in-0`, `in-1`, ... to its output `out`, the messages it receives
+/// in the order of their timestamps.
+///
+/// A message is only forwarded once a message (or a watermark) with a greater timestamp was
+/// received on all the other inputs: an input on which nothing is sent hence blocks the Merge. An
+/// input that received an end of stream no longer blocks it and, once all the inputs did, the end
+/// of stream is forwarded.
+///
+/// It expects a configuration in the format
+///
+/// ```yaml
+/// inputs: 2
+/// ```
+pub(crate) struct Merge {
+    inputs: Vec<InputRaw>,
+    output: OutputRaw,
+    state: Arc<Mutex<MergeState>>,
+}
+
+/// The MergeState stores in a single structure all the fields protected by a lock.
+///
+/// The fields are:
+/// - `pending` the message received on each input and not yet forwarded;
+/// - `ended` whether or not each input received an end of stream;
+/// - `finished` whether or not the end of stream was forwarded.
+pub(crate) struct MergeState {
+    pub(crate) pending: Vec<Option<LinkMessage>>,
+    pub(crate) ended: Vec<bool>,
+    pub(crate) finished: bool,
+}
+
+impl MergeState {
+    /// Returns the index of the input whose pending message has the smallest timestamp, if all the
+    /// inputs that did not end have a pending message.
+    pub(crate) fn next(&self) -> Option<usize> {
+        let mut next: Option<usize> = None;
+
+        for (index, pending) in self.pending.iter().enumerate() {
+            match pending {
+                Some(message) => {
+                    let is_smaller = next.map_or(true, |next| {
+                        // `next` is only set to inputs with a pending message.
+                        self.pending[next].as_ref().map_or(true, |other| {
+                            message.get_timestamp() < other.get_timestamp()
+                        })
+                    });
+                    if is_smaller {
+                        next = Some(index);
+                    }
+                }
+                None if !self.ended[index] => return None,
+                None => (),
+            }
+        }
+
+        next
+    }
+}
+
+/// Private function to retrieve the "Constructor" for the Merge
+pub(crate) fn get_merge_declaration() -> NodeDeclaration<OperatorFn> {
+    NodeDeclaration::<OperatorFn> {
+        rustc_version: RUSTC_VERSION,
+        core_version: CORE_VERSION,
+        constructor: |context: Context,
+                      configuration: Option<Configuration>,
+                      inputs: Inputs,
+                      outputs: Outputs| {
+            Box::pin(async {
+                let node = Merge::new(context, configuration, inputs, outputs).await?;
+                Ok(Arc::new(node) as Arc<dyn Node>)
+            })
+        },
+    }
+}
+
+/// Private function to retrieve the Descriptor for the Merge
+pub(crate) fn get_merge_descriptor(configuration: &Configuration) -> ZFResult<OperatorDescriptor> {
+    let inputs = get_inputs_count(configuration)?;
+
+    Ok(OperatorDescriptor {
+        id: "merge".into(),
+        inputs: (0..inputs).map(merge_input).collect(),
+        outputs: vec![MERGE_OUTPUT.into()],
+        side_outputs: vec![],
+        input_policies: HashMap::new(),
+        input_types: HashMap::new(),
+        input_units: HashMap::new(),
+        output_types: HashMap::new(),
+        output_units: HashMap::new(),
+        uri: Some("builtin://merge".into()),
+        configuration: Some(configuration.clone()),
+    })
+}
+
+#[async_trait]
+impl Operator for Merge {
+    async fn new(
+        _context: Context,
+        configuration: Option<Configuration>,
+        mut inputs: Inputs,
+        mut outputs: Outputs,
+    ) -> ZFResult<Self> {
+        let configuration = configuration.ok_or_else(|| {
+            zferror!(
+                ErrorKind::MissingConfiguration,
+                "Builtin Merge requires a configuration"
+            )
+        })?;
+        let count = get_inputs_count(&configuration)?;
+
+        let mut merge_inputs = Vec::with_capacity(count);
+        for index in 0..count {
+            let port_id = merge_input(index);
+            merge_inputs.push(
+                inputs
+                    .take(&port_id)
+                    .ok_or(zferror!(
+                        ErrorKind::MissingInput(port_id.to_string()),
+                        "Unable to find input: {port_id}"
+                    ))?
+                    .raw(),
+            );
+        }
+
+        Ok(Merge {
+            inputs: merge_inputs,
+            output: outputs
+                .take(MERGE_OUTPUT)
+                .ok_or(zferror!(
+                    ErrorKind::MissingOutput(MERGE_OUTPUT.to_string()),
+                    "Unable to find output: {MERGE_OUTPUT}"
+                ))?
+                .raw(),
+            state: Arc::new(Mutex::new(MergeState {
+                pending: vec![None; count],
+                ended: vec![false; count],
+                finished: false,
+            })),
+        })
+    }
+}
+
+#[async_trait]
+impl Node for Merge {
+    async fn iteration(&self) -> ZFResult<()> {
+        let mut state = self.state.lock().await;
+
+        if state.finished {
+            // All the inputs ended, nothing will be received anymore.
+            drop(state);
+            return futures::future::pending().await;
+        }
+
+        for (index, input) in self.inputs.iter().enumerate() {
+            if state.pending[index].is_none() && !state.ended[index] {
+                match input.recv().await? {
+                    LinkMessage::EndOfStream(_) => state.ended[index] = true,
+                    message => state.pending[index] = Some(message),
+                }
+            }
+        }
+
+        match state.next() {
+            Some(index) => {
+                if let Some(message) = state.pending[index].take() {
+                    self.output.forward(message).await?;
+                }
+            }
+            // All the inputs that did not end have a pending message: they all ended.
+            None => {
+                self.output.send_end_of_stream().await?;
+                state.finished = true;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/builtin-merge.rs"]
+mod tests;
@@ -12,4 +12,96 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+pub mod convert;
+pub mod dedup;
+pub mod downsample;
+pub mod faults;
+pub mod fmu;
+pub mod host;
+pub mod http;
+pub mod merge;
+pub mod throttle;
 pub mod zenoh;
+
+use self::convert::{get_convert_declaration, get_convert_descriptor};
+use self::dedup::{get_dedup_declaration, get_dedup_descriptor};
+use self::downsample::{get_downsample_declaration, get_downsample_descriptor};
+use self::faults::{get_faults_declaration, get_faults_descriptor};
+use self::fmu::{get_fmu_declaration, get_fmu_descriptor};
+use self::merge::{get_merge_declaration, get_merge_descriptor};
+use self::throttle::{get_throttle_declaration, get_throttle_descriptor};
+use crate::model::descriptor::OperatorDescriptor;
+use crate::model::BuiltinOperator;
+use crate::prelude::{Configuration, ErrorKind};
+use crate::runtime::dataflow::loader::NodeDeclaration;
+use crate::runtime::dataflow::node::OperatorFn;
+use crate::{zferror, Result};
+use std::time::Duration;
+
+/// Retrieves a duration, expressed in a human readable format (e.g. "500ms"), from the
+/// configuration or returns the provided default if the key is absent.
+pub(crate) fn get_duration_or_default(
+    configuration: &Configuration,
+    key: &str,
+    default: Duration,
+) -> Result<Duration> {
+    match configuration.get(key) {
+        Some(value) => {
+            let value = value.as_str().ok_or_else(|| {
+                zferror!(
+                    ErrorKind::ConfigurationError,
+                    "Unable to convert value of {key} to string: {:?}",
+                    value
+                )
+            })?;
+            let duration = value.parse::<humantime::Duration>().map_err(|e| {
+                zferror!(
+                    ErrorKind::ConfigurationError,
+                    "Unable to parse {key} as a duration: {e}"
+                )
+            })?;
+            Ok(duration.into())
+        }
+        None => Ok(default),
+    }
+}
+
+/// Private function to retrieve the Descriptor of a builtin operator.
+///
+/// # Errors
+///
+/// An error variant is returned if the configuration is invalid. A missing configuration is
+/// treated as an empty one.
+pub(crate) fn get_builtin_operator_descriptor(
+    operator: &BuiltinOperator,
+    configuration: Option<&Configuration>,
+) -> Result<OperatorDescriptor> {
+    let configuration = configuration
+        .cloned()
+        .unwrap_or_else(|| Configuration::Object(Default::default()));
+
+    match operator {
+        BuiltinOperator::Convert => get_convert_descriptor(&configuration),
+        BuiltinOperator::Downsample => get_downsample_descriptor(&configuration),
+        BuiltinOperator::Dedup => get_dedup_descriptor(&configuration),
+        BuiltinOperator::Faults => get_faults_descriptor(&configuration),
+        BuiltinOperator::Merge => get_merge_descriptor(&configuration),
+        BuiltinOperator::Fmu => get_fmu_descriptor(&configuration),
+        BuiltinOperator::Throttle => get_throttle_descriptor(&configuration),
+    }
+}
+
+/// Private function to retrieve the "Constructor" of a builtin operator.
+pub(crate) fn get_builtin_operator_declaration(
+    operator: &BuiltinOperator,
+) -> NodeDeclaration<OperatorFn> {
+    match operator {
+        BuiltinOperator::Convert => get_convert_declaration(),
+        BuiltinOperator::Downsample => get_downsample_declaration(),
+        BuiltinOperator::Dedup => get_dedup_declaration(),
+        BuiltinOperator::Faults => get_faults_declaration(),
+        BuiltinOperator::Merge => get_merge_declaration(),
+        BuiltinOperator::Fmu => get_fmu_declaration(),
+        BuiltinOperator::Throttle => get_throttle_declaration(),
+    }
+}
@@ -0,0 +1,77 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::model::descriptor::OperatorDescriptor;
+use crate::runtime::dataflow::instance::builtin::convert::{
+    convert_payload, get_convert_descriptor,
+};
+use crate::types::Configuration;
+use serde_json::json;
+use serde_yaml;
+
+static OPERATOR_CONFIGURATION_OK: &str = r#"
+from: km/h
+to: m/s
+"#;
+
+static OPERATOR_DESCRIPTOR_GENERATED: &str = r#"
+id: convert
+configuration:
+  from: km/h
+  to: m/s
+uri: "builtin://convert"
+inputs: [in]
+outputs: [out]
+input_units:
+  in: km/h
+output_units:
+  out: m/s
+"#;
+
+#[test]
+fn test_builtin_convert_ok() {
+    let descr = OperatorDescriptor::from_yaml(OPERATOR_DESCRIPTOR_GENERATED).unwrap();
+
+    let configuration: Configuration = serde_yaml::from_str(OPERATOR_CONFIGURATION_OK).unwrap();
+    let generated = get_convert_descriptor(&configuration).unwrap();
+
+    assert_eq!(descr, generated);
+}
+
+#[test]
+fn test_builtin_convert_ko() {
+    // Different quantities.
+    let configuration: Configuration = serde_yaml::from_str("from: m\nto: s").unwrap();
+    assert!(get_convert_descriptor(&configuration).is_err());
+
+    let configuration: Configuration = serde_yaml::from_str("from: m").unwrap();
+    assert!(get_convert_descriptor(&configuration).is_err());
+}
+
+#[test]
+fn test_convert_payload() {
+    let conversion = "km".parse::<crate::model::descriptor::Unit>().unwrap();
+    let conversion = conversion.conversion_to(&"m".parse().unwrap()).unwrap();
+
+    let converted = convert_payload(b"3", &conversion).unwrap();
+    assert_eq!(
+        serde_json::from_slice::<serde_json::Value>(&converted).unwrap(),
+        json!(3000.0)
+    );
+
+    // Only a single number is converted: the other numbers of a payload may not be in that unit.
+    let payload = serde_json::to_vec(&json!({ "distance": 1.5, "legs": [2, 0.25] })).unwrap();
+    assert!(convert_payload(&payload, &conversion).is_err());
+    assert!(convert_payload(b"\x00\x01", &conversion).is_err());
+}
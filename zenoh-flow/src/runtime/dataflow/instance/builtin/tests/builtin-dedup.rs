@@ -0,0 +1,103 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::model::descriptor::OperatorDescriptor;
+use crate::runtime::dataflow::instance::builtin::dedup::{
+    get_dedup_descriptor, identity, DedupState,
+};
+use crate::types::Configuration;
+use serde_yaml;
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+
+static OPERATOR_CONFIGURATION_OK: &str = r#"
+window: 10s
+key: /frame/id
+"#;
+
+static OPERATOR_DESCRIPTOR_GENERATED: &str = r#"
+id: dedup
+configuration:
+  window: 10s
+  key: /frame/id
+uri: "builtin://dedup"
+inputs: [in]
+outputs: [out]
+"#;
+
+#[test]
+fn test_builtin_dedup_ok() {
+    let descr = OperatorDescriptor::from_yaml(OPERATOR_DESCRIPTOR_GENERATED);
+    assert!(descr.is_ok());
+
+    let descr = descr.unwrap();
+
+    let configuration = serde_yaml::from_str(OPERATOR_CONFIGURATION_OK);
+    assert!(configuration.is_ok());
+    let configuration: Configuration = configuration.unwrap();
+
+    let generated = get_dedup_descriptor(&configuration);
+    assert!(generated.is_ok());
+
+    let generated = generated.unwrap();
+
+    assert_eq!(descr, generated);
+}
+
+static OPERATOR_CONFIGURATION_KO: &str = r#"
+key: frame.id
+"#;
+
+#[test]
+fn test_builtin_dedup_ko() {
+    let configuration = serde_yaml::from_str(OPERATOR_CONFIGURATION_KO);
+    assert!(configuration.is_ok());
+    let configuration: Configuration = configuration.unwrap();
+
+    let generated = get_dedup_descriptor(&configuration);
+    assert!(generated.is_err());
+}
+
+#[test]
+fn test_builtin_dedup_expire() {
+    let (first, second): (Arc<[u8]>, Arc<[u8]>) = (b"1".to_vec().into(), b"2".to_vec().into());
+    let mut state = DedupState {
+        seen: HashSet::from([first.clone(), second.clone()]),
+        history: VecDeque::from([
+            (first.clone(), Duration::from_secs(0)),
+            (second.clone(), Duration::from_secs(5)),
+        ]),
+        buffer: Vec::new(),
+    };
+
+    state.expire(Duration::from_secs(12), Duration::from_secs(10));
+    assert!(!state.seen.contains(&first));
+    assert!(state.seen.contains(&second));
+    assert_eq!(state.history.len(), 1);
+}
+
+#[test]
+fn test_builtin_dedup_identity() {
+    let payload = br#"{"frame": {"id": 42, "pixels": [1, 2]}}"#;
+    assert_eq!(identity(Some("/frame/id"), payload), b"42".to_vec());
+
+    // Without the key, or when it is missing, the whole payload identifies the message.
+    assert_eq!(identity(None, payload), payload.to_vec());
+    assert_eq!(identity(Some("/frame/ts"), payload), payload.to_vec());
+    assert_eq!(
+        identity(Some("/frame/id"), b"not json"),
+        b"not json".to_vec()
+    );
+}
@@ -0,0 +1,79 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::model::descriptor::{OperatorDescriptor, SamplingRate};
+use crate::runtime::dataflow::instance::builtin::downsample::get_downsample_descriptor;
+use crate::types::Configuration;
+use serde_yaml;
+use std::str::FromStr;
+
+static OPERATOR_CONFIGURATION_OK: &str = r#"
+sample: 1/10
+"#;
+
+static OPERATOR_DESCRIPTOR_GENERATED: &str = r#"
+id: downsample
+configuration:
+  sample: 1/10
+uri: "builtin://downsample"
+inputs: [in]
+outputs: [out]
+"#;
+
+#[test]
+fn test_builtin_downsample_ok() {
+    let descr = OperatorDescriptor::from_yaml(OPERATOR_DESCRIPTOR_GENERATED);
+    assert!(descr.is_ok());
+
+    let descr = descr.unwrap();
+
+    let configuration = serde_yaml::from_str(OPERATOR_CONFIGURATION_OK);
+    assert!(configuration.is_ok());
+    let configuration: Configuration = configuration.unwrap();
+
+    let generated = get_downsample_descriptor(&configuration);
+    assert!(generated.is_ok());
+
+    let generated = generated.unwrap();
+
+    assert_eq!(descr, generated);
+}
+
+static OPERATOR_CONFIGURATION_KO: &str = r#"
+sample: 10/1
+"#;
+
+#[test]
+fn test_builtin_downsample_ko() {
+    let configuration = serde_yaml::from_str(OPERATOR_CONFIGURATION_KO);
+    assert!(configuration.is_ok());
+    let configuration: Configuration = configuration.unwrap();
+
+    let generated = get_downsample_descriptor(&configuration);
+    assert!(generated.is_err());
+}
+
+#[test]
+fn test_sampling_rate() {
+    let rate = SamplingRate::from_str("2/5").unwrap();
+    assert_eq!(rate, SamplingRate { keep: 2, every: 5 });
+    assert_eq!(rate.to_string(), "2/5");
+
+    let kept: Vec<u64> = (0..10).filter(|index| rate.keeps(*index)).collect();
+    assert_eq!(kept, vec![0, 1, 5, 6]);
+
+    assert!(SamplingRate::from_str("0/5").is_err());
+    assert!(SamplingRate::from_str("1").is_err());
+    assert!(SamplingRate::from_str("a/b").is_err());
+}
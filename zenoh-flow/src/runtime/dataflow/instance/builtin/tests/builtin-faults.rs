@@ -0,0 +1,93 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::model::descriptor::{FaultsDescriptor, OperatorDescriptor, Probability};
+use crate::runtime::dataflow::instance::builtin::faults::get_faults_descriptor;
+use crate::types::Configuration;
+use serde_yaml;
+use std::convert::TryFrom;
+use std::time::Duration;
+
+static OPERATOR_CONFIGURATION_OK: &str = r#"
+delay: 50ms
+jitter: 10ms
+drop: 0.05
+reorder: 0.01
+link: Counter/out/Sum/in
+"#;
+
+static OPERATOR_DESCRIPTOR_GENERATED: &str = r#"
+id: faults
+configuration:
+  delay: 50ms
+  jitter: 10ms
+  drop: 0.05
+  reorder: 0.01
+  link: Counter/out/Sum/in
+uri: "builtin://faults"
+inputs: [in]
+outputs: [out]
+"#;
+
+#[test]
+fn test_builtin_faults_ok() {
+    let descr = OperatorDescriptor::from_yaml(OPERATOR_DESCRIPTOR_GENERATED);
+    assert!(descr.is_ok());
+
+    let descr = descr.unwrap();
+
+    let configuration = serde_yaml::from_str(OPERATOR_CONFIGURATION_OK);
+    assert!(configuration.is_ok());
+    let configuration: Configuration = configuration.unwrap();
+
+    let generated = get_faults_descriptor(&configuration);
+    assert!(generated.is_ok());
+
+    let generated = generated.unwrap();
+
+    assert_eq!(descr, generated);
+}
+
+static OPERATOR_CONFIGURATION_KO: &str = r#"
+drop: 1.5
+"#;
+
+#[test]
+fn test_builtin_faults_ko() {
+    let configuration = serde_yaml::from_str(OPERATOR_CONFIGURATION_KO);
+    assert!(configuration.is_ok());
+    let configuration: Configuration = configuration.unwrap();
+
+    let generated = get_faults_descriptor(&configuration);
+    assert!(generated.is_err());
+
+    // NaN cannot be stored in a descriptor.
+    assert!(serde_yaml::from_str::<FaultsDescriptor>("drop: .nan").is_err());
+    assert!(Probability::try_from(f64::NAN).is_err());
+}
+
+#[test]
+fn test_faults_descriptor_round_trip() {
+    let faults = FaultsDescriptor {
+        delay: Some(Duration::from_millis(50)),
+        jitter: None,
+        drop: Probability::try_from(0.1).unwrap(),
+        duplicate: Probability::default(),
+        reorder: Probability::default(),
+    };
+
+    let json = serde_json::to_string(&faults).unwrap();
+    let parsed: FaultsDescriptor = serde_json::from_str(&json).unwrap();
+    assert_eq!(faults, parsed);
+}
@@ -0,0 +1,139 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::model::descriptor::{SinkDescriptor, SourceDescriptor};
+use crate::runtime::dataflow::instance::builtin::http::{
+    get_http_sink_descriptor, get_http_source_descriptor, next_backoff, MAX_BACKOFF,
+};
+use crate::types::Configuration;
+use serde_yaml;
+use std::time::Duration;
+
+static SOURCE_CONFIGURATION_OK: &str = r#"
+period: 500ms
+endpoints:
+  weather: http://localhost:8080/weather
+"#;
+
+static SOURCE_DESCRIPTOR_GENERATED: &str = r#"
+id: http-source
+configuration:
+  period: 500ms
+  endpoints:
+    weather: http://localhost:8080/weather
+uri: "builtin://http"
+outputs: [weather]
+"#;
+
+#[test]
+fn test_builtin_http_source_ok() {
+    let descr = SourceDescriptor::from_yaml(SOURCE_DESCRIPTOR_GENERATED);
+    assert!(descr.is_ok());
+
+    let descr = descr.unwrap();
+
+    let configuration = serde_yaml::from_str(SOURCE_CONFIGURATION_OK);
+    assert!(configuration.is_ok());
+    let configuration: Configuration = configuration.unwrap();
+
+    let generated = get_http_source_descriptor(&configuration);
+    assert!(generated.is_ok());
+
+    let generated = generated.unwrap();
+
+    assert_eq!(descr, generated);
+}
+
+static SOURCE_CONFIGURATION_KO: &str = r#"
+period: every second
+endpoints:
+  weather: http://localhost:8080/weather
+"#;
+
+#[test]
+fn test_builtin_http_source_ko() {
+    let configuration = serde_yaml::from_str(SOURCE_CONFIGURATION_KO);
+    assert!(configuration.is_ok());
+    let configuration: Configuration = configuration.unwrap();
+
+    let generated = get_http_source_descriptor(&configuration);
+    assert!(generated.is_err());
+}
+
+static SINK_CONFIGURATION_OK: &str = r#"
+retries: 5
+backoff: 50ms
+endpoints:
+  alert: https://example.com/hooks/alert
+  report: https://example.com/hooks/report
+"#;
+
+static SINK_DESCRIPTOR_GENERATED: &str = r#"
+id: http-sink
+configuration:
+  retries: 5
+  backoff: 50ms
+  endpoints:
+    alert: https://example.com/hooks/alert
+    report: https://example.com/hooks/report
+uri: "builtin://http"
+inputs:
+  - alert
+  - report
+"#;
+
+#[test]
+fn test_builtin_http_sink_ok() {
+    let descr = SinkDescriptor::from_yaml(SINK_DESCRIPTOR_GENERATED);
+    assert!(descr.is_ok());
+
+    let descr = descr.unwrap();
+
+    let configuration = serde_yaml::from_str(SINK_CONFIGURATION_OK);
+    assert!(configuration.is_ok());
+    let configuration: Configuration = configuration.unwrap();
+
+    let generated = get_http_sink_descriptor(&configuration);
+    assert!(generated.is_ok());
+
+    let generated = generated.unwrap();
+
+    assert_eq!(descr, generated);
+}
+
+static SINK_CONFIGURATION_KO: &str = r#"
+endpoints:
+  alert: not a url
+"#;
+
+#[test]
+fn test_builtin_http_sink_ko() {
+    let configuration = serde_yaml::from_str(SINK_CONFIGURATION_KO);
+    assert!(configuration.is_ok());
+    let configuration: Configuration = configuration.unwrap();
+
+    let generated = get_http_sink_descriptor(&configuration);
+    assert!(generated.is_err());
+}
+
+#[test]
+fn test_builtin_http_sink_backoff() {
+    let initial = Duration::from_millis(100);
+    assert_eq!(next_backoff(initial, initial), Duration::from_millis(200));
+    assert_eq!(next_backoff(Duration::from_secs(20), initial), MAX_BACKOFF);
+
+    // Doubling a huge backoff does not overflow.
+    let huge = Duration::from_secs(u64::MAX);
+    assert_eq!(next_backoff(huge, huge), huge);
+}
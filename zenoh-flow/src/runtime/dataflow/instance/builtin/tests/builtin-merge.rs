@@ -0,0 +1,91 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::model::descriptor::OperatorDescriptor;
+use crate::runtime::dataflow::instance::builtin::merge::{get_merge_descriptor, MergeState};
+use crate::types::{Configuration, LinkMessage};
+use serde_yaml;
+
+static OPERATOR_CONFIGURATION_OK: &str = r#"
+inputs: 2
+"#;
+
+static OPERATOR_DESCRIPTOR_GENERATED: &str = r#"
+id: merge
+configuration:
+  inputs: 2
+uri: "builtin://merge"
+inputs: [in-0, in-1]
+outputs: [out]
+"#;
+
+#[test]
+fn test_builtin_merge_ok() {
+    let descr = OperatorDescriptor::from_yaml(OPERATOR_DESCRIPTOR_GENERATED);
+    assert!(descr.is_ok());
+
+    let descr = descr.unwrap();
+
+    let configuration = serde_yaml::from_str(OPERATOR_CONFIGURATION_OK);
+    assert!(configuration.is_ok());
+    let configuration: Configuration = configuration.unwrap();
+
+    let generated = get_merge_descriptor(&configuration);
+    assert!(generated.is_ok());
+
+    let generated = generated.unwrap();
+
+    assert_eq!(descr, generated);
+}
+
+static OPERATOR_CONFIGURATION_KO: &str = r#"
+inputs: 0
+"#;
+
+#[test]
+fn test_builtin_merge_ko() {
+    let configuration = serde_yaml::from_str(OPERATOR_CONFIGURATION_KO);
+    assert!(configuration.is_ok());
+    let configuration: Configuration = configuration.unwrap();
+
+    let generated = get_merge_descriptor(&configuration);
+    assert!(generated.is_err());
+}
+
+#[test]
+fn test_merge_next() {
+    let hlc = uhlc::HLC::default();
+    let first = LinkMessage::Watermark(hlc.new_timestamp());
+    let second = LinkMessage::Watermark(hlc.new_timestamp());
+
+    // An input that did not end has no pending message: nothing can be forwarded.
+    let mut state = MergeState {
+        pending: vec![Some(second.clone()), None],
+        ended: vec![false, false],
+        finished: false,
+    };
+    assert_eq!(state.next(), None);
+
+    state.pending[1] = Some(first);
+    assert_eq!(state.next(), Some(1));
+
+    // An input that ended no longer blocks the others.
+    state.pending[1] = None;
+    state.ended[1] = true;
+    assert_eq!(state.next(), Some(0));
+
+    state.pending[0] = None;
+    state.ended[0] = true;
+    assert_eq!(state.next(), None);
+}
@@ -0,0 +1,88 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::{get_throttle_descriptor, Throttle, ThrottleConfiguration};
+use crate::io::Congestion;
+use crate::model::descriptor::OperatorDescriptor;
+use crate::types::Configuration;
+
+static OPERATOR_DESCRIPTOR_GENERATED: &str = r#"
+id: throttle
+configuration:
+  congested: 0.5
+uri: "builtin://throttle"
+inputs: [in]
+outputs: [out]
+"#;
+
+#[test]
+fn test_builtin_throttle_descriptor() {
+    let descriptor = OperatorDescriptor::from_yaml(OPERATOR_DESCRIPTOR_GENERATED).unwrap();
+    let configuration: Configuration = serde_yaml::from_str("congested: 0.5").unwrap();
+    assert_eq!(get_throttle_descriptor(&configuration).unwrap(), descriptor);
+
+    for invalid in [
+        "congested: 1.5",
+        "congested: 0.2\nrelieved: 0.3",
+        "min_ratio: 0",
+        "backlog: 0",
+        "relieved: fast",
+    ] {
+        let configuration: Configuration = serde_yaml::from_str(invalid).unwrap();
+        assert!(
+            get_throttle_descriptor(&configuration).is_err(),
+            "{invalid} should be rejected"
+        );
+    }
+}
+
+/// Returns the number of messages, out of `count`, the `throttle` forwards under `congestion`.
+fn admitted(throttle: &mut Throttle, congestion: &Congestion, count: usize) -> usize {
+    (0..count).filter(|_| throttle.admit(congestion)).count()
+}
+
+#[test]
+fn test_throttle_adapts_to_congestion() {
+    let configuration = ThrottleConfiguration::try_from_configuration(
+        &serde_yaml::from_str("min_ratio: 0.25\nbacklog: 10").unwrap(),
+    )
+    .unwrap();
+    let mut throttle = Throttle::new(configuration);
+
+    let relieved = Congestion::default();
+    assert_eq!(admitted(&mut throttle, &relieved, 10), 10);
+
+    // The ratio is halved until it reaches its minimum.
+    let congested = Congestion {
+        level: Some(0.9),
+        ..Default::default()
+    };
+    assert!(!throttle.admit(&congested));
+    assert_eq!(throttle.ratio, 0.5);
+    assert_eq!(admitted(&mut throttle, &congested, 8), 2);
+    assert_eq!(throttle.ratio, 0.25);
+
+    // Without a queue, the congestion is measured against the backlog.
+    let mut throttle = Throttle::new(configuration);
+    let backlogged = Congestion {
+        queued: 10,
+        ..Default::default()
+    };
+    throttle.admit(&backlogged);
+    assert_eq!(throttle.ratio, 0.5);
+
+    // The ratio increases once the links are relieved.
+    throttle.admit(&relieved);
+    assert!((throttle.ratio - 0.6).abs() < f64::EPSILON);
+}
@@ -0,0 +1,310 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::{
+    bail,
+    io::{Congestion, CongestionMonitor},
+    model::descriptor::OperatorDescriptor,
+    prelude::{
+        zferror, Configuration, Context, ErrorKind, InputRaw, Inputs, Node, Operator, OutputRaw,
+        Outputs,
+    },
+    runtime::dataflow::{
+        loader::{NodeDeclaration, CORE_VERSION, RUSTC_VERSION},
+        node::OperatorFn,
+    },
+    types::LinkMessage,
+    Result as ZFResult,
+};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Key for the fill ratio of the queues above which the built-in AdaptiveThrottle forwards less.
+static KEY_CONGESTED: &str = "congested";
+
+/// Key for the fill ratio of the queues below which the built-in AdaptiveThrottle forwards more.
+static KEY_RELIEVED: &str = "relieved";
+
+/// Key for the smallest fraction of the messages the built-in AdaptiveThrottle forwards.
+static KEY_MIN_RATIO: &str = "min_ratio";
+
+/// Key for the number of queued messages considered a full queue, on the links without a queue.
+static KEY_BACKLOG: &str = "backlog";
+
+/// Default fill ratio above which the links are congested (0.8).
+const DEFAULT_CONGESTED: f64 = 0.8;
+
+/// Default fill ratio below which the links are relieved (0.2).
+const DEFAULT_RELIEVED: f64 = 0.2;
+
+/// Default smallest fraction of the messages forwarded (0.1).
+const DEFAULT_MIN_RATIO: f64 = 0.1;
+
+/// Default number of queued messages considered a full queue (64).
+const DEFAULT_BACKLOG: u64 = 64;
+
+/// The fraction of the messages added to the ratio every time the links are relieved.
+const RATIO_STEP: f64 = 0.1;
+
+/// Identifier of the input of the built-in AdaptiveThrottle.
+pub(crate) static THROTTLE_INPUT: &str = "in";
+
+/// Identifier of the output of the built-in AdaptiveThrottle.
+pub(crate) static THROTTLE_OUTPUT: &str = "out";
+
+/// Retrieves a ratio, between 0 and 1, from the configuration or returns the provided default if
+/// the key is absent.
+fn get_ratio_or_default(configuration: &Configuration, key: &str, default: f64) -> ZFResult<f64> {
+    let ratio = match configuration.get(key) {
+        Some(value) => value.as_f64().ok_or_else(|| {
+            zferror!(
+                ErrorKind::ConfigurationError,
+                "Unable to convert value of {key} to a number: {:?}",
+                value
+            )
+        })?,
+        None => return Ok(default),
+    };
+
+    if !(0.0..=1.0).contains(&ratio) {
+        bail!(
+            ErrorKind::ConfigurationError,
+            "The {key} of the builtin AdaptiveThrottle must be between 0 and 1, got {ratio}"
+        )
+    }
+
+    Ok(ratio)
+}
+
+/// The thresholds of the built-in AdaptiveThrottle, retrieved from its configuration.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct ThrottleConfiguration {
+    pub(crate) congested: f64,
+    pub(crate) relieved: f64,
+    pub(crate) min_ratio: f64,
+    pub(crate) backlog: usize,
+}
+
+impl ThrottleConfiguration {
+    fn try_from_configuration(configuration: &Configuration) -> ZFResult<Self> {
+        let congested = get_ratio_or_default(configuration, KEY_CONGESTED, DEFAULT_CONGESTED)?;
+        let relieved = get_ratio_or_default(configuration, KEY_RELIEVED, DEFAULT_RELIEVED)?;
+        if relieved >= congested {
+            bail!(
+                ErrorKind::ConfigurationError,
+                "The {KEY_RELIEVED} ratio ({relieved}) of the builtin AdaptiveThrottle must be lower than its {KEY_CONGESTED} ratio ({congested})"
+            )
+        }
+
+        let min_ratio = get_ratio_or_default(configuration, KEY_MIN_RATIO, DEFAULT_MIN_RATIO)?;
+        if min_ratio == 0.0 {
+            bail!(
+                ErrorKind::ConfigurationError,
+                "The {KEY_MIN_RATIO} of the builtin AdaptiveThrottle must be greater than 0"
+            )
+        }
+
+        let backlog = match configuration.get(KEY_BACKLOG) {
+            Some(value) => value
+                .as_u64()
+                .filter(|backlog| *backlog > 0)
+                .ok_or_else(|| {
+                    zferror!(
+                        ErrorKind::ConfigurationError,
+                        "Unable to convert value of {KEY_BACKLOG} to a positive integer: {:?}",
+                        value
+                    )
+                })?,
+            None => DEFAULT_BACKLOG,
+        };
+
+        Ok(Self {
+            congested,
+            relieved,
+            min_ratio,
+            backlog: backlog as usize,
+        })
+    }
+}
+
+/// The `Throttle` decides which messages the built-in AdaptiveThrottle forwards.
+///
+/// It forwards a `ratio` of the messages: the ratio is halved whenever the links of the output are
+/// congested, and increased by [RATIO_STEP] whenever they are relieved (additive increase,
+/// multiplicative decrease). The messages forwarded are evenly spread: a credit accumulates the
+/// ratio and a message is forwarded every time it reaches 1.
+#[derive(Debug)]
+pub(crate) struct Throttle {
+    configuration: ThrottleConfiguration,
+    pub(crate) ratio: f64,
+    credit: f64,
+}
+
+impl Throttle {
+    pub(crate) fn new(configuration: ThrottleConfiguration) -> Self {
+        Self {
+            configuration,
+            ratio: 1.0,
+            credit: 0.0,
+        }
+    }
+
+    /// Adapts the ratio to the `congestion` and returns `true` if the next message is forwarded.
+    pub(crate) fn admit(&mut self, congestion: &Congestion) -> bool {
+        let level = congestion.level.unwrap_or_else(|| {
+            (congestion.queued as f64 / self.configuration.backlog as f64).min(1.0)
+        });
+
+        if congestion.dropped > 0 || level >= self.configuration.congested {
+            self.ratio = (self.ratio / 2.0).max(self.configuration.min_ratio);
+        } else if level <= self.configuration.relieved {
+            self.ratio = (self.ratio + RATIO_STEP).min(1.0);
+        }
+
+        self.credit += self.ratio;
+        if self.credit >= 1.0 {
+            self.credit -= 1.0;
+            return true;
+        }
+
+        false
+    }
+}
+
+/// The builtin AdaptiveThrottle operator
+/// It forwards, from its input `in` to its output `out`, the messages it receives, dropping a
+/// growing fraction of them while the links of its output are congested --- the downstream nodes,
+/// or the network, cannot keep up. Watermarks are always forwarded.
+///
+/// The congestion is the fill ratio of the queues of the links (see
+/// [`QueueDescriptor`](crate::model::descriptor::QueueDescriptor)) or, for the links without a
+/// queue, the number of messages waiting in their channel over the `backlog`. Messages dropped by
+/// a queue always count as a congestion.
+///
+/// It expects a configuration in the format
+///
+/// ```yaml
+/// congested: 0.8  # optional, the fill ratio above which the throughput is halved
+/// relieved: 0.2   # optional, the fill ratio below which the throughput is increased
+/// min_ratio: 0.1  # optional, the smallest fraction of the messages forwarded
+/// backlog: 64     # optional, the number of queued messages considered a full queue
+/// ```
+pub(crate) struct AdaptiveThrottle {
+    input: InputRaw,
+    output: OutputRaw,
+    state: Mutex<(CongestionMonitor, Throttle)>,
+}
+
+/// Private function to retrieve the "Constructor" for the AdaptiveThrottle
+pub(crate) fn get_throttle_declaration() -> NodeDeclaration<OperatorFn> {
+    NodeDeclaration::<OperatorFn> {
+        rustc_version: RUSTC_VERSION,
+        core_version: CORE_VERSION,
+        constructor: |context: Context,
+                      configuration: Option<Configuration>,
+                      inputs: Inputs,
+                      outputs: Outputs| {
+            Box::pin(async {
+                let node = AdaptiveThrottle::new(context, configuration, inputs, outputs).await?;
+                Ok(Arc::new(node) as Arc<dyn Node>)
+            })
+        },
+    }
+}
+
+/// Private function to retrieve the Descriptor for the AdaptiveThrottle
+pub(crate) fn get_throttle_descriptor(
+    configuration: &Configuration,
+) -> ZFResult<OperatorDescriptor> {
+    ThrottleConfiguration::try_from_configuration(configuration)?;
+
+    Ok(OperatorDescriptor {
+        id: "throttle".into(),
+        inputs: vec![THROTTLE_INPUT.into()],
+        outputs: vec![THROTTLE_OUTPUT.into()],
+        side_outputs: vec![],
+        input_policies: HashMap::new(),
+        input_types: HashMap::new(),
+        input_units: HashMap::new(),
+        output_types: HashMap::new(),
+        output_units: HashMap::new(),
+        uri: Some("builtin://throttle".into()),
+        configuration: Some(configuration.clone()),
+    })
+}
+
+#[async_trait]
+impl Operator for AdaptiveThrottle {
+    async fn new(
+        _context: Context,
+        configuration: Option<Configuration>,
+        mut inputs: Inputs,
+        mut outputs: Outputs,
+    ) -> ZFResult<Self> {
+        let configuration =
+            configuration.unwrap_or_else(|| Configuration::Object(Default::default()));
+        let output = outputs
+            .take(THROTTLE_OUTPUT)
+            .ok_or(zferror!(
+                ErrorKind::MissingOutput(THROTTLE_OUTPUT.to_string()),
+                "Unable to find output: {THROTTLE_OUTPUT}"
+            ))?
+            .raw();
+
+        Ok(AdaptiveThrottle {
+            input: inputs
+                .take(THROTTLE_INPUT)
+                .ok_or(zferror!(
+                    ErrorKind::MissingInput(THROTTLE_INPUT.to_string()),
+                    "Unable to find input: {THROTTLE_INPUT}"
+                ))?
+                .raw(),
+            state: Mutex::new((
+                output.congestion_monitor(),
+                Throttle::new(ThrottleConfiguration::try_from_configuration(
+                    &configuration,
+                )?),
+            )),
+            output,
+        })
+    }
+}
+
+#[async_trait]
+impl Node for AdaptiveThrottle {
+    async fn iteration(&self) -> ZFResult<()> {
+        let message = self.input.recv().await?;
+
+        if let LinkMessage::Data(_) = message {
+            let admitted = {
+                let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+                let (monitor, throttle) = &mut *state;
+                let congestion = monitor.observe();
+                throttle.admit(&congestion)
+            };
+
+            if !admitted {
+                log::trace!("[AdaptiveThrottle] Links congested, dropping a message");
+                return Ok(());
+            }
+        }
+
+        self.output.forward(message).await
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/builtin-throttle.rs"]
+mod tests;
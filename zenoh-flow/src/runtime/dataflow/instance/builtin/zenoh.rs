@@ -26,19 +26,23 @@ use crate::{
     types::LinkMessage,
     Result as ZFResult,
 };
-use async_std::sync::Mutex;
+use async_lock::Mutex;
 use async_trait::async_trait;
 use flume::{Receiver, RecvError};
 use futures::{future::select_all, Future};
 use std::mem;
 use std::sync::Arc;
 use std::{collections::HashMap, pin::Pin};
+use uhlc::Timestamp;
 use zenoh::buffers::SharedMemoryManager;
 use zenoh::{prelude::r#async::*, publication::Publisher, subscriber::Subscriber};
 
 /// Key for the key expressions used by the built-in Source/Sink.
 static KEY_KEYEXPRESSIONS: &str = "key-expressions";
 
+/// Key for the backfill mode of the built-in Source.
+static KEY_BACKFILL: &str = "backfill";
+
 /// Key for the shared memory element size used by the built-in Sink.
 static KEY_SHM_ELEM_SIZE: &str = "shared_memory_element_size";
 
@@ -66,11 +70,25 @@ fn wait_zenoh_sub(id: PortId, sub: &Subscriber<Receiver<Sample>>) -> ZSubFut {
 /// <output_id> : <key expression>
 ///
 /// It expects the output(s) defined in the configuration to be connected.
+///
+/// When `backfill: true` is set in its configuration, the Source first replays the historical data
+/// stored for its key expressions (i.e. the replies of the Zenoh storages), ordered by timestamp and
+/// as fast as the downstream Nodes allow. It then sends a [Live](LinkMessage::Live) on each output
+/// and switches to live data, skipping the samples that were already replayed.
 pub(crate) struct ZenohSource<'a> {
-    _session: Arc<Session>,
+    session: Arc<Session>,
     outputs: HashMap<PortId, OutputRaw>,
     subscribers: HashMap<PortId, Subscriber<'a, Receiver<Sample>>>,
     futs: Arc<Mutex<Vec<ZSubFut>>>,
+    backfill: Mutex<Backfill>,
+}
+
+/// The progress of the backfill of the built-in Zenoh Source.
+enum Backfill {
+    /// The historical data of these key expressions remain to be replayed.
+    Pending(HashMap<PortId, String>),
+    /// The historical data was replayed, up to these timestamps.
+    Done(HashMap<PortId, Timestamp>),
 }
 
 /// Private function to retrieve the "Constructor" for the ZenohSource
@@ -122,7 +140,9 @@ pub(crate) fn get_zenoh_source_descriptor(
     Ok(SourceDescriptor {
         id: "zenoh-source".into(),
         outputs,
-        uri: Some("builtin://zenoh".to_string()),
+        output_types: HashMap::new(),
+        output_units: HashMap::new(),
+        uri: Some("builtin://zenoh".into()),
         configuration: Some(configuration.clone()),
     })
 }
@@ -136,9 +156,21 @@ impl<'a> Source for ZenohSource<'a> {
     ) -> ZFResult<Self> {
         let mut source_outputs: HashMap<PortId, OutputRaw> = HashMap::new();
         let mut subscribers: HashMap<PortId, Subscriber<'a, Receiver<Sample>>> = HashMap::new();
+        let mut replays: HashMap<PortId, String> = HashMap::new();
 
         match configuration {
             Some(configuration) => {
+                let backfill = match configuration.get(KEY_BACKFILL) {
+                    Some(value) => value.as_bool().ok_or_else(|| {
+                        zferror!(
+                            ErrorKind::ConfigurationError,
+                            "Unable to convert value of {KEY_BACKFILL} to boolean: {:?}",
+                            value
+                        )
+                    })?,
+                    None => false,
+                };
+
                 let keyexpressions = configuration.get(KEY_KEYEXPRESSIONS).ok_or_else(|| {
                     zferror!(
                         ErrorKind::ConfigurationError,
@@ -173,12 +205,13 @@ impl<'a> Source for ZenohSource<'a> {
                             "Unable to find output: {id}"
                         ))?
                         .raw();
-                    let subscriber = context
-                        .zenoh_session()
-                        .declare_subscriber(&ke)
-                        .res()
-                        .await?;
+                    let subscriber = context.session().declare_subscriber(&ke).res().await?;
 
+                    // The subscriber is declared before the historical data is queried: no sample
+                    // published in between is missed.
+                    if backfill {
+                        replays.insert(id.clone().into(), ke);
+                    }
                     subscribers.insert(id.clone().into(), subscriber);
                     source_outputs.insert(id.clone().into(), output);
                 }
@@ -189,10 +222,11 @@ impl<'a> Source for ZenohSource<'a> {
                     .collect();
 
                 Ok(ZenohSource {
-                    _session: context.zenoh_session(),
+                    session: context.session(),
                     outputs: source_outputs,
                     subscribers,
                     futs: Arc::new(Mutex::new(futs)),
+                    backfill: Mutex::new(Backfill::Pending(replays)),
                 })
             }
             None => {
@@ -208,6 +242,18 @@ impl<'a> Source for ZenohSource<'a> {
 #[async_trait]
 impl<'a> Node for ZenohSource<'a> {
     async fn iteration(&self) -> ZFResult<()> {
+        let mut backfill = self.backfill.lock().await;
+        if let Backfill::Pending(replays) = &*backfill {
+            let mut replayed = HashMap::with_capacity(replays.len());
+            for (id, ke) in replays {
+                if let Some(timestamp) = self.replay(id, ke).await? {
+                    replayed.insert(id.clone(), timestamp);
+                }
+            }
+            *backfill = Backfill::Done(replayed);
+            return Ok(());
+        }
+
         // Getting the list of futures to poll in a temporary variable (that `select_all` can take
         // ownership of)
         let mut futs = self.futs.lock().await;
@@ -216,6 +262,9 @@ impl<'a> Node for ZenohSource<'a> {
         let ((id, result), _index, mut remaining) = select_all(tmp).await;
 
         match result {
+            Ok(sample) if Self::was_replayed(&backfill, &id, &sample) => {
+                log::trace!("[ZenohSource] Skipping a sample already replayed for output: {id}");
+            }
             Ok(sample) => {
                 let data = sample.payload.contiguous().to_vec();
                 let ke = sample.key_expr;
@@ -248,11 +297,60 @@ impl<'a> Node for ZenohSource<'a> {
     }
 }
 
+impl<'a> ZenohSource<'a> {
+    /// Sends, on the output `id`, the historical data stored for `ke` followed by a
+    /// [Live](LinkMessage::Live).
+    ///
+    /// Returns the timestamp of the most recent sample replayed, if any.
+    async fn replay(&self, id: &PortId, ke: &str) -> ZFResult<Option<Timestamp>> {
+        let output = self.outputs.get(id).ok_or(zferror!(
+            ErrorKind::MissingOutput(id.to_string()),
+            "Unable to find output!"
+        ))?;
+
+        let replies = self.session.get(ke).res().await?;
+        let mut samples = Vec::new();
+        while let Ok(reply) = replies.recv_async().await {
+            match reply.sample {
+                Ok(sample) => samples.push(sample),
+                Err(e) => log::warn!("[ZenohSource] Error while replaying {ke}: {e:?}"),
+            }
+        }
+        samples.sort_by_key(|sample| sample.timestamp);
+
+        log::debug!(
+            "[ZenohSource] Replaying {} sample(s) from {ke} on output: {id}",
+            samples.len()
+        );
+        let mut latest = None;
+        for sample in samples {
+            let timestamp = sample.timestamp.map(|timestamp| timestamp.get_time().0);
+            output
+                .send(sample.payload.contiguous().to_vec(), timestamp)
+                .await?;
+            latest = sample.timestamp.or(latest);
+        }
+
+        output.send_live().await?;
+        Ok(latest)
+    }
+
+    /// Returns `true` if the `sample` received on the output `id` was already replayed.
+    fn was_replayed(backfill: &Backfill, id: &PortId, sample: &Sample) -> bool {
+        match (backfill, sample.timestamp) {
+            (Backfill::Done(replayed), Some(timestamp)) => replayed
+                .get(id)
+                .map_or(false, |replayed| timestamp <= *replayed),
+            _ => false,
+        }
+    }
+}
+
 /// Internal type of pending futures for the ZenohSink
 pub(crate) type ZFInputFut =
     Pin<Box<dyn Future<Output = (PortId, ZFResult<LinkMessage>)> + Send + Sync>>;
 
-fn wait_flow_input(id: PortId, input: &InputRaw) -> ZFInputFut {
+pub(crate) fn wait_flow_input(id: PortId, input: &InputRaw) -> ZFInputFut {
     let input = input.clone();
     Box::pin(async move { (id, input.recv().await) })
 }
@@ -334,7 +432,10 @@ pub(crate) fn get_zenoh_sink_descriptor(configuration: &Configuration) -> ZFResu
     Ok(SinkDescriptor {
         id: "zenoh-sink".into(),
         inputs,
-        uri: Some("builtin://zenoh".to_string()),
+        input_policies: HashMap::new(),
+        input_types: HashMap::new(),
+        input_units: HashMap::new(),
+        uri: Some("builtin://zenoh".into()),
         configuration: Some(configuration.clone()),
     })
 }
@@ -428,7 +529,7 @@ impl<'a> Sink for ZenohSink<'a> {
                             "Unable to find input: {id}"
                         ))?
                         .raw();
-                    let subscriber = context.zenoh_session().declare_publisher(ke).res().await?;
+                    let subscriber = context.session().declare_publisher(ke).res().await?;
 
                     publishers.insert(id.clone().into(), subscriber);
                     sink_inputs.insert(id.clone().into(), input);
@@ -440,7 +541,7 @@ impl<'a> Sink for ZenohSink<'a> {
                     .collect();
 
                 Ok(ZenohSink {
-                    _session: context.zenoh_session(),
+                    _session: context.session(),
                     inputs: sink_inputs,
                     publishers,
                     state: Arc::new(Mutex::new(ZenohSinkState {
@@ -488,7 +589,7 @@ impl<'a> Node for ZenohSink<'a> {
                         let mut buff = match shm.alloc(self.shm_element_size) {
                             Ok(buf) => buf,
                             Err(_) => {
-                                async_std::task::sleep(std::time::Duration::from_nanos(
+                                crate::executor::sleep(std::time::Duration::from_nanos(
                                     self.shm_backoff,
                                 ))
                                 .await;
@@ -0,0 +1,457 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::io::link::{LinkReceiver, LinkSender};
+use crate::io::Outputs;
+use crate::types::{ControlMarker, LinkMessage, NodeId};
+use event_listener::{Event, EventListener};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// A control message delivered to a node: its runner calls `on_control` with its payload.
+///
+/// A barrier is forwarded on the outputs of the node once it was handled.
+#[derive(Clone, Debug)]
+pub(crate) enum Control {
+    /// A control message without barrier, handled as soon as it is delivered.
+    Message(ControlMarker),
+    /// A barrier broadcast to a Source, handled once its current `iteration` is over.
+    Barrier(ControlMarker),
+    /// A barrier whose marker was delivered by all the channels leading to the node, handled as
+    /// soon as it is delivered: the node is then waiting on its inputs.
+    Aligned(ControlMarker),
+}
+
+/// The `NodeControl` delivers the control messages broadcast to a node, see
+/// [`DataFlowInstance::broadcast_control`](super::DataFlowInstance::broadcast_control).
+///
+/// A control message without barrier is delivered directly. A barrier is sent in-band, as a
+/// [ControlMarker], by the Sources: a channel leading to the node that delivered the marker is no
+/// longer polled until the marker was delivered by all the other channels (the barrier is then
+/// _aligned_), the node handled it and forwarded it on all its outputs. The node thus handles the
+/// barrier after all the messages sent before it and before all the messages sent after it.
+///
+/// The markers are counted: the n-th marker of each channel belongs to the n-th barrier.
+///
+/// A checkpoint barrier (a marker with an epoch) is not aligned, following Chandy and Lamport: the
+/// first channel delivering its marker pauses the node, which checkpoints its state, forwards the
+/// marker on all its outputs and resumes. Until the other channels deliver the marker, the messages
+/// they deliver are recorded: they were in flight when the node checkpointed its state. The
+/// checkpoint of the node, along with these messages, is then reported to the [Checkpoints] of the
+/// instance.
+#[derive(Debug)]
+pub(crate) struct NodeControl {
+    node_id: NodeId,
+    tx: flume::Sender<Control>,
+    rx: flume::Receiver<Control>,
+    alignment: Mutex<Alignment>,
+    released: AtomicU64,
+    release: Event,
+    outputs: Mutex<Vec<LinkSender>>,
+    checkpoints: Arc<Checkpoints>,
+    // Set from the first marker of a checkpoint barrier until the node checkpointed its state.
+    paused: AtomicBool,
+    // The epoch of the checkpoint whose in-flight messages are recorded, 0 if none is.
+    recording: AtomicU64,
+}
+
+#[derive(Debug, Default)]
+struct Alignment {
+    // The channels leading to the node that can still deliver a marker.
+    channels: usize,
+    // The channels that delivered the marker of the pending barrier.
+    arrived: usize,
+    pending: Option<ControlMarker>,
+    // The epoch of the last checkpoint barrier the node received.
+    epoch: u64,
+    snapshot: Option<Snapshot>,
+}
+
+/// A checkpoint of a node in progress.
+#[derive(Debug)]
+struct Snapshot {
+    epoch: u64,
+    // The channels that did not deliver the marker yet.
+    pending: usize,
+    checkpoint: Option<Result<NodeCheckpoint, String>>,
+    links: Vec<(LinkReceiver, Vec<LinkMessage>)>,
+}
+
+/// The state, relative to the barriers, of a channel leading to a node.
+#[derive(Debug, Default)]
+pub(crate) struct ChannelAlignment {
+    // The number of the last barrier whose marker the channel delivered.
+    barrier: AtomicU64,
+    // The epoch of the last checkpoint barrier whose marker the channel delivered.
+    epoch: AtomicU64,
+    closed: AtomicBool,
+}
+
+/// The checkpoint of a node taken for a checkpoint barrier: its state (see
+/// [`Node::checkpoint`](crate::prelude::Node::checkpoint)), the partitions of its keyed state and
+/// the messages that were in flight on the channels leading to it.
+#[derive(Debug, Default)]
+pub(crate) struct NodeCheckpoint {
+    pub(crate) state: Option<Vec<u8>>,
+    pub(crate) partitions: HashMap<u32, Vec<u8>>,
+    pub(crate) links: Vec<(LinkReceiver, Vec<LinkMessage>)>,
+}
+
+/// The `Checkpoints` collects, by epoch, the checkpoints reported by the nodes of an instance, or
+/// the error that prevented a node from taking its checkpoint.
+#[derive(Debug, Default)]
+pub(crate) struct Checkpoints {
+    reports: Mutex<HashMap<u64, HashMap<NodeId, Result<NodeCheckpoint, String>>>>,
+    reported: Event,
+}
+
+impl Checkpoints {
+    fn report(&self, epoch: u64, node_id: &NodeId, checkpoint: Result<NodeCheckpoint, String>) {
+        if let Ok(mut reports) = self.reports.lock() {
+            reports
+                .entry(epoch)
+                .or_default()
+                .insert(node_id.clone(), checkpoint);
+        }
+        self.reported.notify(usize::MAX);
+    }
+
+    /// Returns a listener notified each time a node reported its checkpoint.
+    pub(crate) fn listen(&self) -> EventListener {
+        self.reported.listen()
+    }
+
+    /// Returns the `nodes` that did not report their checkpoint of `epoch` yet.
+    pub(crate) fn missing<'a>(
+        &self,
+        epoch: u64,
+        nodes: impl Iterator<Item = &'a NodeId>,
+    ) -> Vec<NodeId> {
+        let reports = match self.reports.lock() {
+            Ok(reports) => reports,
+            Err(_) => return nodes.cloned().collect(),
+        };
+        let reported = reports.get(&epoch);
+        nodes
+            .filter(|node_id| !reported.map_or(false, |reported| reported.contains_key(*node_id)))
+            .cloned()
+            .collect()
+    }
+
+    /// Removes, and returns, the checkpoints reported for `epoch`.
+    pub(crate) fn take(&self, epoch: u64) -> HashMap<NodeId, Result<NodeCheckpoint, String>> {
+        self.reports
+            .lock()
+            .ok()
+            .and_then(|mut reports| reports.remove(&epoch))
+            .unwrap_or_default()
+    }
+}
+
+impl NodeControl {
+    pub(crate) fn new(node_id: NodeId, outputs: &Outputs, checkpoints: Arc<Checkpoints>) -> Self {
+        let (tx, rx) = flume::unbounded();
+        Self {
+            node_id,
+            tx,
+            rx,
+            alignment: Mutex::new(Alignment::default()),
+            released: AtomicU64::new(0),
+            release: Event::new(),
+            outputs: Mutex::new(senders(outputs)),
+            checkpoints,
+            paused: AtomicBool::new(false),
+            recording: AtomicU64::new(0),
+        }
+    }
+
+    /// Delivers the `control` message to the node.
+    pub(crate) fn deliver(&self, control: Control) {
+        // The receiver is held by `self`: the channel is never disconnected.
+        let _ = self.tx.send(control);
+    }
+
+    /// Waits for the next control message delivered to the node.
+    pub(crate) async fn next(&self) -> Control {
+        match self.rx.recv_async().await {
+            Ok(control) => control,
+            Err(_) => futures::future::pending().await,
+        }
+    }
+
+    /// Registers `channels` channels leading to the node, as the node takes its inputs.
+    pub(crate) fn register(&self, channels: usize) {
+        if let Ok(mut alignment) = self.alignment.lock() {
+            alignment.channels += channels;
+        }
+    }
+
+    /// Sets the `outputs` of the node and forgets the channels registered, along with the pending
+    /// barrier and the checkpoint in progress: the node is about to be (re)created and takes its
+    /// inputs again.
+    pub(crate) fn rewire(&self, outputs: &Outputs) {
+        if let Ok(mut current) = self.outputs.lock() {
+            *current = senders(outputs);
+        }
+        if let Ok(mut alignment) = self.alignment.lock() {
+            *alignment = Alignment {
+                epoch: alignment.epoch,
+                ..Alignment::default()
+            };
+        }
+        self.paused.store(false, Ordering::Release);
+        self.recording.store(0, Ordering::Release);
+    }
+
+    /// Returns `true` if the `channel` delivered the marker of a barrier the node did not handle
+    /// yet, or if the node is about to checkpoint its state: it should not be polled until then.
+    pub(crate) fn is_blocked(&self, channel: &ChannelAlignment) -> bool {
+        self.paused.load(Ordering::Acquire)
+            || channel.barrier.load(Ordering::Acquire) > self.released.load(Ordering::Acquire)
+    }
+
+    /// Returns a listener notified each time the node handled a barrier.
+    pub(crate) fn listen(&self) -> EventListener {
+        self.release.listen()
+    }
+
+    /// Accounts for the `marker` delivered by the `channel`: it is blocked and, if all the channels
+    /// delivered it, the barrier is delivered to the node.
+    ///
+    /// The marker of a checkpoint barrier is instead delivered to the node by the first channel,
+    /// which is not blocked.
+    pub(crate) fn arrive(&self, channel: &ChannelAlignment, marker: ControlMarker) {
+        if let Some(epoch) = marker.get_epoch() {
+            self.arrive_checkpoint(channel, epoch, marker);
+            return;
+        }
+
+        // The channel is blocked before the barrier can be released.
+        channel
+            .barrier
+            .store(self.released.load(Ordering::Acquire) + 1, Ordering::Release);
+
+        if let Ok(mut alignment) = self.alignment.lock() {
+            alignment.arrived += 1;
+            alignment.pending = Some(marker);
+            self.try_align(&mut alignment);
+        }
+    }
+
+    /// Accounts for the marker of the checkpoint barrier of `epoch` delivered by the `channel`.
+    fn arrive_checkpoint(&self, channel: &ChannelAlignment, epoch: u64, marker: ControlMarker) {
+        channel.epoch.fetch_max(epoch, Ordering::AcqRel);
+
+        if let Ok(mut alignment) = self.alignment.lock() {
+            if epoch > alignment.epoch {
+                if let Some(snapshot) = &alignment.snapshot {
+                    log::warn!(
+                        "[Node: {}] Checkpoint {} abandoned: the marker of checkpoint {} was received",
+                        self.node_id,
+                        snapshot.epoch,
+                        epoch
+                    );
+                }
+                alignment.epoch = epoch;
+                alignment.snapshot = Some(Snapshot {
+                    epoch,
+                    pending: alignment.channels.saturating_sub(1),
+                    checkpoint: None,
+                    links: Vec::new(),
+                });
+                // No other message is received before the node checkpointed its state.
+                self.paused.store(true, Ordering::Release);
+                self.deliver(Control::Aligned(marker));
+            } else if let Some(snapshot) = alignment
+                .snapshot
+                .as_mut()
+                .filter(|snapshot| snapshot.epoch == epoch)
+            {
+                snapshot.pending = snapshot.pending.saturating_sub(1);
+                self.try_complete(&mut alignment);
+            } else {
+                log::debug!(
+                    "[Node: {}] Ignoring the marker of the abandoned checkpoint {}",
+                    self.node_id,
+                    epoch
+                );
+            }
+        }
+    }
+
+    /// Accounts for the `channel` reaching its end of stream or being disconnected: the pending
+    /// barrier, and the checkpoint in progress, no longer wait for it.
+    pub(crate) fn close(&self, channel: &ChannelAlignment) {
+        if channel.closed.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        if let Ok(mut alignment) = self.alignment.lock() {
+            alignment.channels = alignment.channels.saturating_sub(1);
+            let epoch = channel.epoch.load(Ordering::Acquire);
+            if let Some(snapshot) = alignment
+                .snapshot
+                .as_mut()
+                .filter(|snapshot| snapshot.epoch > epoch)
+            {
+                snapshot.pending = snapshot.pending.saturating_sub(1);
+            }
+            self.try_align(&mut alignment);
+            self.try_complete(&mut alignment);
+        }
+    }
+
+    /// Records the `message` received on the `channel`, through its `receiver`, if it was in flight
+    /// when the node checkpointed its state: the channel did not deliver the marker yet.
+    pub(crate) fn record(
+        &self,
+        channel: &ChannelAlignment,
+        receiver: &LinkReceiver,
+        message: &LinkMessage,
+    ) {
+        if self.recording.load(Ordering::Acquire) <= channel.epoch.load(Ordering::Acquire)
+            || matches!(message, LinkMessage::Control(_))
+        {
+            return;
+        }
+
+        if let Ok(mut alignment) = self.alignment.lock() {
+            if let Some(snapshot) = alignment.snapshot.as_mut() {
+                match snapshot
+                    .links
+                    .iter_mut()
+                    .find(|(link, _)| link.same_channel(receiver))
+                {
+                    Some((_, messages)) => messages.push(message.clone()),
+                    None => snapshot
+                        .links
+                        .push((receiver.clone(), vec![message.clone()])),
+                }
+            }
+        }
+    }
+
+    /// Delivers the pending barrier to the node if all the channels delivered its marker.
+    fn try_align(&self, alignment: &mut Alignment) {
+        if alignment.arrived < alignment.channels {
+            return;
+        }
+
+        if let Some(marker) = alignment.pending.take() {
+            alignment.arrived = 0;
+            self.deliver(Control::Aligned(marker));
+        }
+    }
+
+    /// Reports the checkpoint of the node if it was taken and all the channels delivered the marker,
+    /// or were closed.
+    fn try_complete(&self, alignment: &mut Alignment) {
+        let completed = alignment.snapshot.as_ref().map_or(false, |snapshot| {
+            snapshot.pending == 0 && snapshot.checkpoint.is_some()
+        });
+        if !completed {
+            return;
+        }
+
+        if let Some(snapshot) = alignment.snapshot.take() {
+            self.recording.store(0, Ordering::Release);
+            let links = snapshot.links;
+            let checkpoint = snapshot
+                .checkpoint
+                .unwrap_or_else(|| Err("No checkpoint taken".to_string()))
+                .map(|checkpoint| NodeCheckpoint {
+                    links,
+                    ..checkpoint
+                });
+            self.checkpoints
+                .report(snapshot.epoch, &self.node_id, checkpoint);
+        }
+    }
+
+    /// Accounts for the `checkpoint` the node took for the checkpoint barrier of the `marker`, or
+    /// the error that prevented it: the marker is forwarded on all its outputs, then the node
+    /// receives messages again and records the ones in flight.
+    ///
+    /// A Source takes its checkpoint once its current `iteration` is over: it is reported at once.
+    pub(crate) async fn checkpointed(
+        &self,
+        marker: &ControlMarker,
+        checkpoint: Result<NodeCheckpoint, String>,
+    ) {
+        self.forward(marker).await;
+
+        let epoch = marker.get_epoch().unwrap_or_default();
+        if let Ok(mut alignment) = self.alignment.lock() {
+            match alignment
+                .snapshot
+                .as_mut()
+                .filter(|snapshot| snapshot.epoch == epoch)
+            {
+                Some(snapshot) => snapshot.checkpoint = Some(checkpoint),
+                None => {
+                    alignment.epoch = alignment.epoch.max(epoch);
+                    alignment.snapshot = Some(Snapshot {
+                        epoch,
+                        pending: 0,
+                        checkpoint: Some(checkpoint),
+                        links: Vec::new(),
+                    });
+                }
+            }
+            self.recording.store(epoch, Ordering::Release);
+            self.try_complete(&mut alignment);
+        }
+
+        self.paused.store(false, Ordering::Release);
+        self.release.notify(usize::MAX);
+    }
+
+    /// Forwards the `marker` of the barrier the node handled on all its outputs, then polls again
+    /// the channels that were blocked.
+    pub(crate) async fn release(&self, marker: &ControlMarker) {
+        self.forward(marker).await;
+        self.released.fetch_add(1, Ordering::AcqRel);
+        self.release.notify(usize::MAX);
+    }
+
+    /// Forwards the `marker` on all the outputs of the node.
+    async fn forward(&self, marker: &ControlMarker) {
+        let outputs = self
+            .outputs
+            .lock()
+            .map(|outputs| outputs.clone())
+            .unwrap_or_default();
+        for output in outputs {
+            // A marker is never dropped, even on a link declaring a queue: it waits for a slot and
+            // only the data messages are evicted from the queue (see `LinkQueue::push`).
+            if output
+                .send_async(LinkMessage::Control(marker.clone()))
+                .await
+                .is_err()
+            {
+                log::error!("Failed to forward a control marker: a link is disconnected");
+            }
+        }
+    }
+}
+
+/// Returns the senders of all the links starting from the `outputs`.
+fn senders(outputs: &Outputs) -> Vec<LinkSender> {
+    outputs.values().flatten().cloned().collect()
+}
+
+#[cfg(test)]
+#[path = "./tests/control-tests.rs"]
+mod tests;
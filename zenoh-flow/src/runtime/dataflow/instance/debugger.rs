@@ -0,0 +1,260 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::tap::decode_message;
+use crate::types::{LinkMessage, NodeId, PortId};
+use crate::zfresult::ErrorKind;
+use crate::{bail, zferror, Result};
+use flume::{Receiver, Sender};
+use serde_json::json;
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use zenoh::prelude::r#async::AsyncResolve;
+use zenoh::prelude::sync::SyncResolve;
+use zenoh::Session;
+
+/// The command sent to a node paused on a breakpoint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum DebugCommand {
+    /// Process the pending message and pause again only on a breakpoint.
+    Continue,
+    /// Process the pending message and pause again on the next data message, whatever the input.
+    Step,
+}
+
+/// The `Breakpoints` set on the inputs of a node.
+///
+/// The fields are:
+/// - `node` whether or not the node pauses on all its inputs;
+/// - `ports` the inputs on which the node pauses.
+#[derive(Debug, Default)]
+pub(crate) struct Breakpoints {
+    pub(crate) node: bool,
+    pub(crate) ports: HashSet<PortId>,
+}
+
+impl Breakpoints {
+    fn matches(&self, port_id: &PortId) -> bool {
+        self.node || self.ports.contains(port_id)
+    }
+
+    fn is_empty(&self) -> bool {
+        !self.node && self.ports.is_empty()
+    }
+}
+
+/// The `NodeDebugger` pauses a node when a data message matching a breakpoint is received on one of
+/// its inputs, until a [DebugCommand] is received.
+///
+/// While the node is paused, the pending message can be inspected: it is kept in the debugger and
+/// published, as JSON, on `zenoh-flow/debug/<instance id>/<node id>`.
+pub(crate) struct NodeDebugger {
+    pub(crate) node_id: NodeId,
+    pub(crate) key_expr: String,
+    pub(crate) session: Option<Arc<Session>>,
+    // Set if at least one breakpoint is set or if the node is stepping: allows skipping the lock
+    // when the node is not debugged.
+    pub(crate) armed: AtomicBool,
+    pub(crate) stepping: AtomicBool,
+    pub(crate) breakpoints: Mutex<Breakpoints>,
+    pub(crate) paused: Mutex<Option<(PortId, LinkMessage)>>,
+    pub(crate) commands: (Sender<DebugCommand>, Receiver<DebugCommand>),
+}
+
+impl Debug for NodeDebugger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NodeDebugger")
+            .field("node_id", &self.node_id)
+            .field("key_expr", &self.key_expr)
+            .field("breakpoints", &self.breakpoints)
+            .finish()
+    }
+}
+
+impl NodeDebugger {
+    pub(crate) fn new(node_id: NodeId, key_expr: String, session: Option<Arc<Session>>) -> Self {
+        Self {
+            node_id,
+            key_expr,
+            session,
+            armed: AtomicBool::new(false),
+            stepping: AtomicBool::new(false),
+            breakpoints: Mutex::new(Breakpoints::default()),
+            paused: Mutex::new(None),
+            commands: flume::unbounded(),
+        }
+    }
+
+    /// Sets (`enabled` is true) or removes a breakpoint on the input `port_id` or, if no input is
+    /// provided, on all the inputs of the node.
+    pub(crate) fn set_breakpoint(&self, port_id: Option<&PortId>, enabled: bool) -> Result<()> {
+        let mut breakpoints = match self.breakpoints.lock() {
+            Ok(breakpoints) => breakpoints,
+            Err(e) => bail!(ErrorKind::GenericError, "{e}"),
+        };
+
+        match (port_id, enabled) {
+            (None, enabled) => breakpoints.node = enabled,
+            (Some(port_id), true) => {
+                breakpoints.ports.insert(port_id.clone());
+            }
+            (Some(port_id), false) => {
+                breakpoints.ports.remove(port_id);
+            }
+        }
+
+        self.armed.store(
+            !breakpoints.is_empty() || self.stepping.load(Ordering::Acquire),
+            Ordering::Release,
+        );
+        Ok(())
+    }
+
+    /// Returns the message on which the node is paused, if any, and the input it was received on.
+    pub(crate) fn paused(&self) -> Option<(PortId, LinkMessage)> {
+        self.paused.lock().ok().and_then(|paused| paused.clone())
+    }
+
+    /// Resumes the node paused on a breakpoint.
+    ///
+    /// # Error
+    ///
+    /// An error is returned if the node is not paused.
+    pub(crate) fn resume(&self, command: DebugCommand) -> Result<()> {
+        if self.paused().is_none() {
+            bail!(
+                ErrorKind::InvalidState,
+                "Node < {} > is not paused on a breakpoint",
+                self.node_id
+            )
+        }
+
+        self.commands
+            .0
+            .send(command)
+            .map_err(|e| zferror!(ErrorKind::SendError, "{e:?}").into())
+    }
+
+    /// Forgets the message on which the node was paused and the commands not processed, after the
+    /// node was stopped or restarted.
+    pub(crate) fn reset(&self) {
+        if let Ok(mut paused) = self.paused.lock() {
+            *paused = None;
+        }
+        self.commands.1.drain().for_each(drop);
+    }
+
+    /// Returns `true` if the node should pause on the `message` received on the input `port_id`,
+    /// in which case the message is kept for inspection.
+    fn should_pause(&self, port_id: &PortId, message: &LinkMessage) -> bool {
+        if !self.armed.load(Ordering::Acquire) || !matches!(message, LinkMessage::Data(_)) {
+            return false;
+        }
+
+        let matches = self.stepping.load(Ordering::Acquire)
+            || self
+                .breakpoints
+                .lock()
+                .map(|breakpoints| breakpoints.matches(port_id))
+                .unwrap_or(false);
+
+        if matches {
+            log::info!("[Debugger: {}] Paused on input < {port_id} >", self.node_id);
+            if let Ok(mut paused) = self.paused.lock() {
+                *paused = Some((port_id.clone(), message.clone()));
+            }
+        }
+
+        matches
+    }
+
+    /// Returns the JSON published while the node is paused on the `message`.
+    fn describe(&self, port_id: &PortId, message: &LinkMessage) -> Vec<u8> {
+        json!({
+            "node": self.node_id.to_string(),
+            "port": port_id.to_string(),
+            "message": decode_message(message),
+        })
+        .to_string()
+        .into_bytes()
+    }
+
+    /// Applies the `command` received while paused.
+    fn apply(&self, command: Option<DebugCommand>) {
+        let stepping = command == Some(DebugCommand::Step);
+        self.stepping.store(stepping, Ordering::Release);
+
+        if let Ok(mut paused) = self.paused.lock() {
+            *paused = None;
+        }
+
+        let has_breakpoints = self
+            .breakpoints
+            .lock()
+            .map(|breakpoints| !breakpoints.is_empty())
+            .unwrap_or(false);
+        self.armed
+            .store(stepping || has_breakpoints, Ordering::Release);
+        log::info!("[Debugger: {}] Resumed: {command:?}", self.node_id);
+    }
+
+    /// Pauses, *asynchronously*, if the `message` received on the input `port_id` matches a
+    /// breakpoint, until a [DebugCommand] is received.
+    pub(crate) async fn check(&self, port_id: &PortId, message: &LinkMessage) {
+        if !self.should_pause(port_id, message) {
+            return;
+        }
+
+        if let Some(session) = &self.session {
+            if let Err(e) = session
+                .put(&self.key_expr, self.describe(port_id, message))
+                .res_async()
+                .await
+            {
+                log::error!("[Debugger: {}] Failed to publish: {e:?}", self.node_id);
+            }
+        }
+
+        let command = self.commands.1.recv_async().await.ok();
+        self.apply(command);
+    }
+
+    /// Pauses, *synchronously*, if the `message` received on the input `port_id` matches a
+    /// breakpoint, until a [DebugCommand] is received.
+    ///
+    /// The thread is blocked while the node is paused.
+    pub(crate) fn check_sync(&self, port_id: &PortId, message: &LinkMessage) {
+        if !self.should_pause(port_id, message) {
+            return;
+        }
+
+        if let Some(session) = &self.session {
+            if let Err(e) = session
+                .put(&self.key_expr, self.describe(port_id, message))
+                .res_sync()
+            {
+                log::error!("[Debugger: {}] Failed to publish: {e:?}", self.node_id);
+            }
+        }
+
+        let command = self.commands.1.recv().ok();
+        self.apply(command);
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/debugger-tests.rs"]
+mod tests;
@@ -0,0 +1,132 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::runtime::resources::ROOT_STANDALONE;
+use crate::types::{NodeId, PortId, RuntimeId};
+use crate::LIFECYCLE_PATH;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use uhlc::{Timestamp, HLC};
+use uuid::Uuid;
+use zenoh::prelude::sync::SyncResolve;
+use zenoh::Session;
+
+/// A lifecycle event of a node of an instance, see
+/// [`DataFlowInstance::events`](super::DataFlowInstance::events).
+///
+/// It is also published, as JSON, on `zenoh-flow/lifecycle/<instance id>/<node id>` (see
+/// [`LIFECYCLE_PATH`](crate::LIFECYCLE_PATH)).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstanceEvent {
+    /// When the event occurred, taken from the HLC of the instance.
+    pub timestamp: Timestamp,
+    pub instance_id: Uuid,
+    /// The runtime running the node.
+    pub runtime: RuntimeId,
+    pub node: NodeId,
+    #[serde(flatten)]
+    pub kind: InstanceEventKind,
+}
+
+/// What happened to the node of an [InstanceEvent].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum InstanceEventKind {
+    /// The node was started, or restarted.
+    NodeStarted,
+    /// The node was stopped, or is about to be restarted.
+    NodeStopped,
+    /// An iteration of the node failed: it is no longer iterated until it is restarted.
+    NodeErrored { error: String },
+    /// The recording of the output `port` started, under `key_expr`.
+    RecordingStarted { port: PortId, key_expr: String },
+    /// The recording of the output `port`, under `key_expr`, stopped.
+    RecordingStopped { port: PortId, key_expr: String },
+}
+
+/// The `InstanceEvents` dispatches the lifecycle events of the nodes of an instance running on the
+/// current runtime to its subscribers and publishes them on Zenoh.
+pub(crate) struct InstanceEvents {
+    instance_id: Uuid,
+    runtime: RuntimeId,
+    hlc: Arc<HLC>,
+    session: Arc<Session>,
+    subscribers: Mutex<Vec<flume::Sender<InstanceEvent>>>,
+}
+
+impl InstanceEvents {
+    pub(crate) fn new(
+        instance_id: Uuid,
+        runtime: RuntimeId,
+        hlc: Arc<HLC>,
+        session: Arc<Session>,
+    ) -> Self {
+        Self {
+            instance_id,
+            runtime,
+            hlc,
+            session,
+            subscribers: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns the receiving end of a new subscription: it receives all the events emitted from now
+    /// on, in order, until it is dropped.
+    pub(crate) fn subscribe(&self) -> flume::Receiver<InstanceEvent> {
+        let (tx, rx) = flume::unbounded();
+        self.subscribers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(tx);
+        rx
+    }
+
+    /// Emits the event `kind` of the node `node`: it is sent to the subscribers, the ones that
+    /// dropped their subscription being removed, and published on Zenoh.
+    pub(crate) fn emit(&self, node: &NodeId, kind: InstanceEventKind) {
+        let event = InstanceEvent {
+            timestamp: self.hlc.new_timestamp(),
+            instance_id: self.instance_id,
+            runtime: self.runtime.clone(),
+            node: node.clone(),
+            kind,
+        };
+
+        self.subscribers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|subscriber| subscriber.send(event.clone()).is_ok());
+
+        // The events are published synchronously such that they are received in order.
+        let key_expr = LIFECYCLE_PATH!(ROOT_STANDALONE, self.instance_id, node);
+        match serde_json::to_vec(&event) {
+            Ok(payload) => {
+                if let Err(e) = self.session.put(&key_expr, payload).res_sync() {
+                    log::error!(
+                        "[Instance: {}] Failed to publish on < {key_expr} >: {e:?}",
+                        self.instance_id
+                    );
+                }
+            }
+            Err(e) => log::error!(
+                "[Instance: {}] Failed to serialize {event:?}: {e:?}",
+                self.instance_id
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/events-tests.rs"]
+mod tests;
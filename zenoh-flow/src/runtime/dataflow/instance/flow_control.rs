@@ -0,0 +1,102 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::io::link::LinkSender;
+use event_listener::Event;
+use std::sync::Mutex;
+
+/// The `FlowControl` of a Source prevents it from overrunning slow downstream nodes.
+///
+/// Each link of the Source is created with a bounded channel of `window` messages: the credits of
+/// a link are the number of messages it can still accept and the credits of the Source are the
+/// lowest credits of its links. Each time a downstream node receives a message from the Source, it
+/// grants a credit back, waking up the runner of the Source if it was waiting for one.
+#[derive(Debug)]
+pub(crate) struct FlowControl {
+    window: usize,
+    senders: Mutex<Vec<LinkSender>>,
+    granted: Event,
+}
+
+impl FlowControl {
+    pub(crate) fn new(window: usize) -> Self {
+        Self {
+            window,
+            senders: Mutex::new(Vec::new()),
+            granted: Event::new(),
+        }
+    }
+
+    /// Returns the number of messages each link of the Source can hold.
+    pub(crate) fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Adds a link, through its `sender`, to the links of the Source.
+    pub(crate) fn add_link(&self, sender: LinkSender) {
+        if let Ok(mut senders) = self.senders.lock() {
+            senders.push(sender);
+        }
+    }
+
+    /// Removes a link, through its `sender`, from the links of the Source.
+    pub(crate) fn remove_link(&self, sender: &LinkSender) {
+        if let Ok(mut senders) = self.senders.lock() {
+            senders.retain(|link| !link.same_channel(sender));
+        }
+    }
+
+    /// Returns the number of messages that all the links of the Source can still accept.
+    pub(crate) fn credits(&self) -> usize {
+        self.senders
+            .lock()
+            .ok()
+            .and_then(|senders| {
+                senders
+                    .iter()
+                    .map(|sender| self.window.saturating_sub(sender.len()))
+                    .min()
+            })
+            .unwrap_or(self.window)
+    }
+
+    /// Signals that a downstream node received a message from the Source.
+    pub(crate) fn grant(&self) {
+        self.granted.notify(usize::MAX);
+    }
+
+    /// Waits until all the links of the Source can accept at least one message and returns the
+    /// credits of the Source.
+    pub(crate) async fn wait_credits(&self) -> usize {
+        loop {
+            let credits = self.credits();
+            if credits > 0 {
+                return credits;
+            }
+
+            let listener = self.granted.listen();
+            // A credit could have been granted before the listener was registered.
+            let credits = self.credits();
+            if credits > 0 {
+                return credits;
+            }
+
+            listener.await;
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/flow-control-tests.rs"]
+mod tests;
@@ -0,0 +1,129 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::executor::JoinHandle;
+use crate::io::output::LinkActivity;
+use crate::runtime::resources::ROOT_STANDALONE;
+use crate::types::RuntimeId;
+use crate::IDLE_PATH;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+use zenoh::prelude::r#async::AsyncResolve;
+use zenoh::Session;
+
+/// Bounds of the interval at which the activity of an instance is checked: a quarter of its idle
+/// timeout, between 10ms and 1s.
+static MIN_POLLING_INTERVAL: Duration = Duration::from_millis(10);
+static MAX_POLLING_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The notification published, as JSON, on `zenoh-flow/idle/<instance id>/<runtime id>` when the
+/// nodes of an instance running on a runtime did not send any message for its `idle_timeout` (see
+/// [`DataFlowDescriptor`](crate::model::descriptor::DataFlowDescriptor)), and when they send one
+/// again.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdleNotification {
+    pub instance_id: Uuid,
+    pub runtime: RuntimeId,
+    /// `true` when the instance became idle, `false` when it is active again.
+    pub idle: bool,
+    /// The time elapsed since the last message was sent, when the notification was issued.
+    pub idle_time: Duration,
+}
+
+/// The `IdleMonitor` tracks whether an instance is idle: it reports that it is once per idle
+/// period, and that it is active again as soon as a message is sent.
+#[derive(Debug)]
+pub(crate) struct IdleMonitor {
+    timeout: Duration,
+    idle: bool,
+}
+
+impl IdleMonitor {
+    pub(crate) fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            idle: false,
+        }
+    }
+
+    /// Returns the new state of the instance, `true` if it is idle, if no message was sent for
+    /// `idle_time` changes it.
+    pub(crate) fn update(&mut self, idle_time: Duration) -> Option<bool> {
+        let idle = idle_time >= self.timeout;
+        if idle == self.idle {
+            return None;
+        }
+
+        self.idle = idle;
+        Some(idle)
+    }
+}
+
+/// Spawns the task checking the `activity` of the nodes of the instance `instance_id` running on
+/// the runtime `runtime`, which logs and publishes an [IdleNotification] when they become idle or
+/// active again.
+pub(crate) fn watch(
+    activity: Arc<LinkActivity>,
+    timeout: Duration,
+    session: Arc<Session>,
+    instance_id: Uuid,
+    runtime: RuntimeId,
+) -> JoinHandle<()> {
+    let key_expr = IDLE_PATH!(ROOT_STANDALONE, instance_id, runtime);
+    let interval = (timeout / 4).clamp(MIN_POLLING_INTERVAL, MAX_POLLING_INTERVAL);
+
+    crate::executor::spawn(async move {
+        let mut monitor = IdleMonitor::new(timeout);
+        loop {
+            crate::executor::sleep(interval).await;
+
+            let idle_time = activity.idle_time();
+            let idle = match monitor.update(idle_time) {
+                Some(idle) => idle,
+                None => continue,
+            };
+
+            if idle {
+                log::warn!("[Instance: {instance_id}] No message sent for {idle_time:?}, idle");
+            } else {
+                log::info!("[Instance: {instance_id}] Active again");
+            }
+
+            let notification = IdleNotification {
+                instance_id,
+                runtime: runtime.clone(),
+                idle,
+                idle_time,
+            };
+            let payload = match serde_json::to_vec(&notification) {
+                Ok(payload) => payload,
+                Err(e) => {
+                    log::error!(
+                        "[Instance: {instance_id}] Failed to serialize {notification:?}: {e:?}"
+                    );
+                    continue;
+                }
+            };
+            if let Err(e) = session.put(&key_expr, payload).res_async().await {
+                log::error!("[Instance: {instance_id}] Failed to publish on < {key_expr} >: {e:?}");
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+#[path = "./tests/idle-tests.rs"]
+mod tests;
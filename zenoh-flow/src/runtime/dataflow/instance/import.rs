@@ -0,0 +1,99 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::executor::JoinHandle;
+use crate::io::link::LinkSender;
+use crate::types::LinkMessage;
+use crate::Result;
+use std::sync::Arc;
+use zenoh::prelude::r#async::*;
+
+/// An `Import` is an input of a node that can be connected, at runtime, to an exposed output of
+/// another instance (see
+/// [`DataFlowInstance::connect_import`](crate::runtime::dataflow::instance::DataFlowInstance::connect_import)).
+pub(crate) struct Import {
+    /// The channel feeding the input.
+    pub(crate) sender: LinkSender,
+    /// The key expression of the exposed output the input is connected to, if any, and the task
+    /// forwarding its messages.
+    pub(crate) connection: Option<(String, JoinHandle<()>)>,
+}
+
+impl Import {
+    pub(crate) fn new(sender: LinkSender) -> Self {
+        Self {
+            sender,
+            connection: None,
+        }
+    }
+
+    /// Disconnects the input from the exposed output it is connected to, if any.
+    ///
+    /// Returns the key expression of that exposed output.
+    pub(crate) async fn disconnect(&mut self) -> Option<String> {
+        match self.connection.take() {
+            Some((key_expr, handle)) => {
+                handle.cancel().await;
+                Some(key_expr)
+            }
+            None => None,
+        }
+    }
+}
+
+/// Subscribes to the exposed output published on `key_expr` and spawns a task forwarding its
+/// messages to the input through `sender`.
+///
+/// The end of streams are not forwarded: the instances have independent lifecycles. When the
+/// instance exposing the output is stopped, a warning is logged and the task waits for the output
+/// to be published again.
+///
+/// # Errors
+///
+/// An error is returned if the subscriber could not be declared.
+pub(crate) async fn forward_exposed(
+    session: Arc<Session>,
+    key_expr: String,
+    sender: LinkSender,
+) -> Result<JoinHandle<()>> {
+    let subscriber = session.declare_subscriber(&key_expr).res().await?;
+
+    Ok(crate::executor::spawn(async move {
+        while let Ok(sample) = subscriber.recv_async().await {
+            if sample.kind == SampleKind::Delete {
+                log::warn!("[Import: {key_expr}] The exposed output was withdrawn");
+                continue;
+            }
+
+            let message: LinkMessage =
+                match bincode::deserialize(&sample.value.payload.contiguous()) {
+                    Ok(message) => message,
+                    Err(e) => {
+                        log::error!("[Import: {key_expr}] Failed to deserialize a message: {e:?}");
+                        continue;
+                    }
+                };
+
+            if matches!(message, LinkMessage::EndOfStream(_)) {
+                continue;
+            }
+
+            if sender.send_async(message).await.is_err() {
+                break;
+            }
+        }
+
+        log::debug!("[Import: {key_expr}] Input dropped, stopping the import");
+    }))
+}
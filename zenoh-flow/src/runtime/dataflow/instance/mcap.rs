@@ -0,0 +1,309 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Conversion of recordings (see
+//! [`DataFlowInstance::start_recording`](super::DataFlowInstance::start_recording)) from and to
+//! [MCAP](https://mcap.dev) files, allowing to reuse the tooling and visualizers of the robotics
+//! ecosystem. As rosbag2 can store its bags as MCAP files, the exported files can also be read
+//! with `ros2 bag`.
+//!
+//! Only the unindexed subset of the format is written: a header, one channel per recording, the
+//! messages, the metadata of the recordings and a footer. When reading, uncompressed chunks are
+//! supported, compressed chunks are not.
+
+use super::recording;
+use crate::types::{LinkMessage, Payload, RecordingMetadata};
+use crate::zfresult::ErrorKind;
+use crate::{bail, Result};
+use std::collections::BTreeMap;
+use std::convert::TryInto;
+use std::io::{Read, Write};
+use std::sync::Arc;
+use std::time::Duration;
+use uhlc::{Timestamp, ID, NTP64};
+use zenoh::Session;
+
+const MAGIC: &[u8] = b"\x89MCAP0\r\n";
+
+const OP_HEADER: u8 = 0x01;
+const OP_FOOTER: u8 = 0x02;
+const OP_CHANNEL: u8 = 0x04;
+const OP_MESSAGE: u8 = 0x05;
+const OP_CHUNK: u8 = 0x06;
+const OP_METADATA: u8 = 0x0C;
+const OP_DATA_END: u8 = 0x0F;
+
+/// The name of the MCAP metadata records holding the [RecordingMetadata] of a recording.
+pub const METADATA_NAME: &str = "zenoh-flow/recording";
+
+/// A channel of an MCAP file: the data messages of a recording or of a topic of an imported file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct McapChannel {
+    /// The topic of the channel, `<node id>/<port id>` for an exported recording.
+    pub topic: String,
+    /// The encoding of the payloads, e.g. `cdr` for ROS 2 messages.
+    pub message_encoding: String,
+    /// The payloads and their log time, in nanoseconds since the UNIX epoch.
+    pub messages: Vec<(u64, Vec<u8>)>,
+}
+
+impl McapChannel {
+    /// Returns the messages of the channel as [LinkMessage]s, indexed in the order they appear,
+    /// such that they can be replayed (see
+    /// [`DataFlowInstance::replay_mcap`](super::DataFlowInstance::replay_mcap)).
+    ///
+    /// The timestamps are derived from the log time of the messages and the provided `id`.
+    pub(crate) fn link_messages(&self, id: ID) -> Vec<(u64, LinkMessage)> {
+        self.messages
+            .iter()
+            .enumerate()
+            .map(|(index, (log_time, payload))| {
+                (
+                    index as u64,
+                    LinkMessage::from_payload(
+                        Payload::Bytes(Arc::new(payload.clone())),
+                        Timestamp::new(NTP64::from(Duration::from_nanos(*log_time)), id),
+                    ),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Retrieves from Zenoh the recordings stored under `key_exprs` and writes them, as MCAP, to
+/// `writer`: each recording becomes a channel whose payloads are declared as encoded with
+/// `message_encoding`.
+///
+/// Only the data messages are exported, the watermarks and end of streams have no equivalent.
+///
+/// # Errors
+///
+/// An error is returned if a recording could not be retrieved or if writing failed.
+pub async fn export_mcap(
+    session: &Session,
+    key_exprs: &[String],
+    message_encoding: &str,
+    writer: impl Write,
+) -> Result<()> {
+    let mut recordings = Vec::with_capacity(key_exprs.len());
+    for key_expr in key_exprs {
+        let metadata = recording::load_metadata(session, key_expr).await?;
+        let messages = recording::select(
+            vec![recording::load_messages(session, key_expr).await?],
+            &recording::ReplayRange::All,
+        )
+        .into_iter()
+        .map(|(_, message)| message)
+        .collect::<Vec<_>>();
+        recordings.push((metadata, messages));
+    }
+
+    write_mcap(writer, &recordings, message_encoding)
+}
+
+/// Writes the `recordings` to `writer`, see [export_mcap].
+pub(crate) fn write_mcap(
+    mut writer: impl Write,
+    recordings: &[(RecordingMetadata, Vec<LinkMessage>)],
+    message_encoding: &str,
+) -> Result<()> {
+    writer.write_all(MAGIC)?;
+
+    let mut header = Vec::new();
+    put_string(&mut header, "");
+    put_string(&mut header, "zenoh-flow");
+    write_record(&mut writer, OP_HEADER, &header)?;
+
+    for (channel_id, (metadata, messages)) in recordings.iter().enumerate() {
+        let channel_id = (channel_id + 1) as u16;
+
+        let mut channel = Vec::new();
+        channel.extend_from_slice(&channel_id.to_le_bytes());
+        // No schema: the payloads are opaque to Zenoh-Flow.
+        channel.extend_from_slice(&0u16.to_le_bytes());
+        put_string(
+            &mut channel,
+            &format!("{}/{}", metadata.node_id, metadata.port_id),
+        );
+        put_string(&mut channel, message_encoding);
+        put_map(&mut channel, &BTreeMap::new());
+        write_record(&mut writer, OP_CHANNEL, &channel)?;
+
+        let mut sequence = 0u32;
+        for message in messages {
+            let data_message = match message {
+                LinkMessage::Data(data_message) => data_message,
+                _ => continue,
+            };
+
+            let log_time = data_message
+                .get_timestamp()
+                .get_time()
+                .to_duration()
+                .as_nanos() as u64;
+            let mut record = Vec::new();
+            record.extend_from_slice(&channel_id.to_le_bytes());
+            record.extend_from_slice(&sequence.to_le_bytes());
+            record.extend_from_slice(&log_time.to_le_bytes());
+            record.extend_from_slice(&log_time.to_le_bytes());
+            record.extend_from_slice(&data_message.try_as_bytes()?);
+            write_record(&mut writer, OP_MESSAGE, &record)?;
+            sequence = sequence.wrapping_add(1);
+        }
+
+        let mut record = Vec::new();
+        put_string(&mut record, METADATA_NAME);
+        put_map(&mut record, &metadata_map(metadata)?);
+        write_record(&mut writer, OP_METADATA, &record)?;
+    }
+
+    // A CRC of 0 means that it was not computed.
+    write_record(&mut writer, OP_DATA_END, &0u32.to_le_bytes())?;
+    write_record(&mut writer, OP_FOOTER, &[0u8; 20])?;
+    writer.write_all(MAGIC)?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Reads the channels of the MCAP file provided by `reader`.
+///
+/// # Errors
+///
+/// An error is returned if the file is not a valid MCAP file, if it contains compressed chunks or
+/// if reading failed.
+pub fn read_mcap(mut reader: impl Read) -> Result<Vec<McapChannel>> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+
+    if bytes.len() < MAGIC.len() || &bytes[..MAGIC.len()] != MAGIC {
+        bail!(ErrorKind::ParsingError, "Not an MCAP file: invalid magic");
+    }
+
+    let mut channels = BTreeMap::new();
+    read_records(&bytes[MAGIC.len()..], &mut channels)?;
+    Ok(channels.into_values().collect())
+}
+
+fn read_records(mut bytes: &[u8], channels: &mut BTreeMap<u16, McapChannel>) -> Result<()> {
+    // The trailing magic is shorter than the smallest record (opcode and length).
+    while bytes.len() >= 9 {
+        let opcode = bytes[0];
+        let length = u64::from_le_bytes(bytes[1..9].try_into()?) as usize;
+        bytes = &bytes[9..];
+        let mut record = take(&mut bytes, length)?;
+
+        match opcode {
+            OP_CHANNEL => {
+                let id = u16::from_le_bytes(take(&mut record, 2)?.try_into()?);
+                let _schema_id = take(&mut record, 2)?;
+                let topic = get_string(&mut record)?;
+                let message_encoding = get_string(&mut record)?;
+                channels.entry(id).or_insert(McapChannel {
+                    topic,
+                    message_encoding,
+                    messages: Vec::new(),
+                });
+            }
+            OP_MESSAGE => {
+                let id = u16::from_le_bytes(take(&mut record, 2)?.try_into()?);
+                let _sequence = take(&mut record, 4)?;
+                let log_time = u64::from_le_bytes(take(&mut record, 8)?.try_into()?);
+                let _publish_time = take(&mut record, 8)?;
+                match channels.get_mut(&id) {
+                    Some(channel) => channel.messages.push((log_time, record.to_vec())),
+                    None => bail!(
+                        ErrorKind::ParsingError,
+                        "MCAP message on the undeclared channel {}",
+                        id
+                    ),
+                }
+            }
+            OP_CHUNK => {
+                // start time, end time, uncompressed size and CRC.
+                take(&mut record, 28)?;
+                let compression = get_string(&mut record)?;
+                let records_length = u64::from_le_bytes(take(&mut record, 8)?.try_into()?) as usize;
+                if !compression.is_empty() {
+                    bail!(
+                        ErrorKind::Unsupported,
+                        "MCAP chunks compressed with < {} > are not supported",
+                        compression
+                    );
+                }
+                read_records(take(&mut record, records_length)?, channels)?;
+            }
+            OP_FOOTER => break,
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn write_record(writer: &mut impl Write, opcode: u8, content: &[u8]) -> Result<()> {
+    writer.write_all(&[opcode])?;
+    writer.write_all(&(content.len() as u64).to_le_bytes())?;
+    writer.write_all(content)?;
+    Ok(())
+}
+
+fn metadata_map(metadata: &RecordingMetadata) -> Result<BTreeMap<String, String>> {
+    let value = serde_json::to_value(metadata)?;
+    Ok(value
+        .as_object()
+        .map(|object| {
+            object
+                .iter()
+                .map(|(key, value)| match value {
+                    serde_json::Value::String(string) => (key.clone(), string.clone()),
+                    value => (key.clone(), value.to_string()),
+                })
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+fn put_string(buffer: &mut Vec<u8>, string: &str) {
+    buffer.extend_from_slice(&(string.len() as u32).to_le_bytes());
+    buffer.extend_from_slice(string.as_bytes());
+}
+
+fn put_map(buffer: &mut Vec<u8>, map: &BTreeMap<String, String>) {
+    let mut content = Vec::new();
+    for (key, value) in map {
+        put_string(&mut content, key);
+        put_string(&mut content, value);
+    }
+    buffer.extend_from_slice(&(content.len() as u32).to_le_bytes());
+    buffer.extend_from_slice(&content);
+}
+
+fn take<'a>(bytes: &mut &'a [u8], length: usize) -> Result<&'a [u8]> {
+    if bytes.len() < length {
+        bail!(ErrorKind::ParsingError, "Truncated MCAP record");
+    }
+    let (taken, rest) = bytes.split_at(length);
+    *bytes = rest;
+    Ok(taken)
+}
+
+fn get_string(bytes: &mut &[u8]) -> Result<String> {
+    let length = u32::from_le_bytes(take(bytes, 4)?.try_into()?) as usize;
+    Ok(std::str::from_utf8(take(bytes, length)?)?.to_string())
+}
+
+#[cfg(test)]
+#[path = "./tests/mcap-tests.rs"]
+mod tests;
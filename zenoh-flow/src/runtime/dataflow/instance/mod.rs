@@ -13,33 +13,181 @@
 //
 
 pub mod builtin;
-pub mod runners;
+pub(crate) mod control;
+pub(crate) mod debugger;
+pub mod events;
+pub(crate) mod flow_control;
+pub mod idle;
+pub(crate) mod import;
+pub mod mcap;
+pub mod record_sink;
+pub mod recording;
+pub(crate) mod redaction;
+pub(crate) mod rescale;
+pub(crate) mod retention;
+runtime_mod!(runners);
+pub mod snapshot;
+pub(crate) mod tap;
 
-use self::runners::connector::{ZenohReceiver, ZenohSender};
-use self::runners::Runner;
+use self::control::{Checkpoints, Control, NodeControl};
+use self::debugger::{DebugCommand, NodeDebugger};
+use self::events::{InstanceEvent, InstanceEventKind, InstanceEvents};
+use self::flow_control::FlowControl;
+use self::import::Import;
+use self::recording::{Buffering, Recording, RecordingManifest, Replay};
+use self::redaction::Redaction;
+use self::retention::RetentionLedger;
+use self::runners::connector::{LinkSequence, LinkTraffic, Traffic, ZenohReceiver, ZenohSender};
+use self::runners::timers::Timers;
+use self::runners::watchdog::Watchdog;
+use self::runners::{catch_panic, Runner};
+use super::physical::{PhysicalGraph, PhysicalNode};
 use super::DataFlow;
+use crate::executor::JoinHandle;
+use crate::io::link::{self, LinkReceiver, LinkSender};
+use crate::io::output::{LinkActivity, LinkQueue, OutputTap, WarmUp};
 use crate::io::{Inputs, Outputs};
+use crate::model::descriptor::dataflow::replica_id;
+use crate::model::descriptor::{
+    InputDescriptor, OutputDescriptor, PreemptionPolicy, ReadinessDescriptor,
+};
 use crate::model::record::{LinkRecord, ZFConnectorKind};
 use crate::prelude::{Context, Node};
+use crate::runtime::gpu::{GpuLease, KEY_GPUS};
+use crate::runtime::resources::ROOT_STANDALONE;
+use crate::runtime::simulation::SimulationClock;
 use crate::runtime::InstanceContext;
-use crate::types::NodeId;
-use crate::zfresult::ErrorKind;
+use crate::types::{
+    Blackboard, Configuration, ControlMarker, KeyedState, LinkMessage, NodeId, PortId,
+};
+use crate::zfresult::{ErrorKind, WithContext};
 use crate::Result;
-use crate::{bail, zferror};
+use crate::{bail, zferror, DEBUG_PATH, EXPOSED_PATH, TAP_PATH};
+use event_listener::Event;
 use std::collections::HashMap;
 use std::ops::Deref;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use uhlc::HLC;
+use zenoh::prelude::r#async::AsyncResolve;
+
+#[cfg(target_family = "unix")]
+use libloading::os::unix::Library;
+#[cfg(target_family = "windows")]
+use libloading::Library;
+
+/// Maximum duration [`DataFlowInstance::stop`] waits for the messages in flight to be processed
+/// after the Sources were stopped (5s).
+pub static DEFAULT_QUIESCENCE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Interval at which the channels are checked while waiting for quiescence (10ms).
+static QUIESCENCE_POLLING_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The `EndOfStreamTracker` keeps track of the inputs of the Sinks that did not yet receive an
+/// [EndOfStream](crate::types::LinkMessage::EndOfStream).
+#[derive(Debug, Default)]
+pub(crate) struct EndOfStreamTracker {
+    pending: AtomicUsize,
+    completed: Event,
+}
+
+impl EndOfStreamTracker {
+    /// Adds `count` inputs to wait for.
+    pub(crate) fn expect(&self, count: usize) {
+        self.pending.fetch_add(count, Ordering::AcqRel);
+    }
+
+    /// Signals that an input received an `EndOfStream` from all its upstream nodes.
+    pub(crate) fn input_completed(&self) {
+        if self.pending.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.completed.notify(usize::MAX);
+        }
+    }
+
+    /// Waits until all the inputs received an `EndOfStream`.
+    pub(crate) async fn wait(&self) {
+        loop {
+            if self.pending.load(Ordering::Acquire) == 0 {
+                return;
+            }
+
+            let listener = self.completed.listen();
+            // The last input could have completed before the listener was registered.
+            if self.pending.load(Ordering::Acquire) == 0 {
+                return;
+            }
+
+            listener.await;
+        }
+    }
+}
+
+/// A `LinkChannel` is the channel created for a link between two nodes running on the current
+/// daemon.
+#[derive(Clone, Debug)]
+pub(crate) struct LinkChannel {
+    pub(crate) from: OutputDescriptor,
+    pub(crate) to: InputDescriptor,
+    pub(crate) tx: LinkSender,
+    pub(crate) rx: LinkReceiver,
+}
 
 /// A `DataFlowInstance` is an instance of a data flow that is ready to be run.
 ///
 /// All Zenoh-Flow daemons involved in the deployment of an instance of a data flow will create this
 /// structure to manage the nodes they are responsible for. Each daemon will keep in that structure
 /// only their view of the instance.
+///
+/// The shared libraries in which the nodes are defined are unloaded when the instance is dropped,
+/// after the nodes. An instance should be stopped (see [`stop`](DataFlowInstance::stop)) before
+/// being dropped: if nodes are still running, their libraries are never unloaded.
 pub struct DataFlowInstance {
     pub(crate) _instance_context: Arc<InstanceContext>,
     pub(crate) data_flow: DataFlow,
     pub(crate) runners: HashMap<NodeId, Runner>,
+    pub(crate) end_of_stream_tracker: Arc<EndOfStreamTracker>,
+    pub(crate) channels: Vec<LinkChannel>,
+    pub(crate) io: HashMap<NodeId, (Inputs, Outputs)>,
+    pub(crate) taps: HashMap<(NodeId, PortId), JoinHandle<()>>,
+    pub(crate) exposures: HashMap<(NodeId, PortId), JoinHandle<()>>,
+    pub(crate) imports: HashMap<(NodeId, PortId), Import>,
+    pub(crate) recordings: HashMap<(NodeId, PortId), Recording>,
+    pub(crate) recording_session: Option<RecordingManifest>,
+    pub(crate) buffers: HashMap<(NodeId, PortId), Buffering>,
+    pub(crate) replays: Vec<Replay>,
+    pub(crate) debuggers: HashMap<NodeId, Arc<NodeDebugger>>,
+    pub(crate) flow_controls: HashMap<NodeId, Arc<FlowControl>>,
+    /// The control of each node, except the connectors, delivering the control messages broadcast
+    /// to the instance.
+    pub(crate) controls: HashMap<NodeId, Arc<NodeControl>>,
+    /// The checkpoints reported by the nodes for the checkpoint barriers, shared with their
+    /// controls.
+    pub(crate) checkpoints: Arc<Checkpoints>,
+    pub(crate) sequences: HashMap<NodeId, Arc<LinkSequence>>,
+    pub(crate) traffic: HashMap<NodeId, Arc<LinkTraffic>>,
+    /// The state, partitioned by key, of each Operator: it survives the restart of the Operator.
+    pub(crate) keyed_states: HashMap<NodeId, Arc<KeyedState>>,
+    /// The lifecycle events of the nodes, shared with their runners.
+    pub(crate) events: Arc<InstanceEvents>,
+    /// When each replicated Operator was last rescaled, for the cooldown of its autoscaling.
+    pub(crate) rescaled: HashMap<NodeId, Instant>,
+    /// When a message was last sent by the nodes, shared by all their outputs.
+    pub(crate) activity: Arc<LinkActivity>,
+    /// The task reporting that the instance is idle, spawned when the first node is started if
+    /// the data flow has an idle timeout.
+    pub(crate) idle_monitor: Option<JoinHandle<()>>,
+    /// The recording entries stored by the instance, deleted by the `janitor` once they exceed the
+    /// retention policy of the data flow.
+    pub(crate) retention_ledger: Arc<RetentionLedger>,
+    /// The task enforcing the retention policy, spawned when the first node is started if the data
+    /// flow has one.
+    pub(crate) janitor: Option<JoinHandle<()>>,
+    /// The GPUs assigned to the nodes, released when the instance is dropped.
+    pub(crate) gpu_lease: Option<GpuLease>,
+    // The fields are dropped in the order of their declaration: the libraries must come last, once
+    // no node, message or serializer defined in them remains.
+    pub(crate) libraries: Vec<Arc<Library>>,
 }
 
 impl Deref for DataFlowInstance {
@@ -50,7 +198,42 @@ impl Deref for DataFlowInstance {
     }
 }
 
+impl Drop for DataFlowInstance {
+    fn drop(&mut self) {
+        let running = self
+            .runners
+            .values_mut()
+            .filter(|runner| runner.is_running())
+            .map(|runner| {
+                if let Some(abort_handle) = runner.run_loop_abort_handle.take() {
+                    abort_handle.abort();
+                }
+                runner.node_id.clone()
+            })
+            .collect::<Vec<_>>();
+
+        // The task of an aborted runner may still be executing code of the library: unloading it
+        // could crash the daemon, leaking it is the lesser evil.
+        if !running.is_empty() {
+            log::warn!(
+                "[Instance: {}] Dropped while the nodes {:?} were running, their libraries will not be unloaded",
+                self.uuid,
+                running
+            );
+            for library in self.libraries.drain(..) {
+                std::mem::forget(library);
+            }
+        }
+    }
+}
+
 impl DataFlowInstance {
+    /// Returns `true` if at least one node of this data flow instance running on the current daemon
+    /// is started.
+    pub fn is_running(&self) -> bool {
+        self.runners.values().any(|runner| runner.is_running())
+    }
+
     /// Retrieve the `NodeId` of the `Sink`s of this data flow instance running on the current
     /// daemon.
     ///
@@ -69,6 +252,61 @@ impl DataFlowInstance {
         self.source_constructors.keys().cloned().collect()
     }
 
+    /// Retrieve the readiness checks that must pass before the `Source`s of this data flow
+    /// instance start, see
+    /// [`wait_until_ready`](crate::runtime::dataflow::readiness::wait_until_ready).
+    pub fn readiness(&self) -> Option<&ReadinessDescriptor> {
+        self.readiness.as_ref()
+    }
+
+    /// Retrieve the priority of this data flow instance and what may be done to it when an
+    /// instance with a higher priority needs the resources it holds.
+    pub fn priority(&self) -> (u32, PreemptionPolicy) {
+        (self.data_flow.priority, self.data_flow.preemption)
+    }
+
+    /// Returns `true` if GPUs are assigned to nodes of this data flow instance running on the
+    /// current daemon.
+    pub fn holds_gpus(&self) -> bool {
+        self.gpu_lease.is_some()
+    }
+
+    /// Retrieve the key expressions on which the exposed outputs of the nodes of this data flow
+    /// instance running on the current daemon are published.
+    ///
+    /// The messages are serialized with `bincode` and can be deserialized as a [LinkMessage]. As
+    /// with a debug tap, the publication never slows down the data flow: if it cannot keep up,
+    /// messages are skipped.
+    pub fn get_exposed(&self) -> Vec<(OutputDescriptor, String)> {
+        self.exposures
+            .keys()
+            .map(|(node_id, port_id)| {
+                (
+                    OutputDescriptor::new(node_id, port_id),
+                    EXPOSED_PATH!(ROOT_STANDALONE, self.uuid, node_id, port_id),
+                )
+            })
+            .collect()
+    }
+
+    /// Retrieve the imported inputs of the nodes of this data flow instance running on the current
+    /// daemon and, for those that are connected, the key expression of the exposed output they are
+    /// connected to.
+    pub fn get_imports(&self) -> Vec<(InputDescriptor, Option<String>)> {
+        self.imports
+            .iter()
+            .map(|((node_id, port_id), import)| {
+                (
+                    InputDescriptor::new(node_id, port_id),
+                    import
+                        .connection
+                        .as_ref()
+                        .map(|(key_expr, _)| key_expr.clone()),
+                )
+            })
+            .collect()
+    }
+
     /// Retrieve the `NodeId` of the `Operator`s of this data flow instance running on the current
     /// daemon.
     ///
@@ -87,6 +325,387 @@ impl DataFlowInstance {
         self.connectors.keys().cloned().collect()
     }
 
+    /// Returns the [PhysicalGraph] of the instance: all its nodes, including the connectors and
+    /// the nodes running on other daemons, the daemon running each of them and all its links.
+    pub fn physical_graph(&self) -> PhysicalGraph {
+        let mut nodes = self
+            .data_flow
+            .nodes
+            .iter()
+            .map(|node| PhysicalNode {
+                max_run_duration: self.max_run_durations.get(&node.id).copied(),
+                credits: self.credits.get(&node.id).copied(),
+                ..node.clone()
+            })
+            .collect::<Vec<_>>();
+        nodes.sort_by(|left, right| left.id.cmp(&right.id));
+
+        PhysicalGraph {
+            uuid: self.uuid,
+            flow: self.flow.to_string(),
+            nodes,
+            links: self.links.clone(),
+        }
+    }
+
+    /// Waits until all the `Sink`s of this data flow instance running on the current daemon
+    /// received an [EndOfStream](crate::types::LinkMessage::EndOfStream) on all their inputs.
+    ///
+    /// This is used to process finite data sets: the `Source`s signal the end of their stream
+    /// once they have produced all their data (see
+    /// [`OutputRaw::send_end_of_stream`](crate::io::OutputRaw::send_end_of_stream)), the
+    /// `Operator`s propagate it after flushing their state and, once it reached all the `Sink`s,
+    /// the instance is complete.
+    ///
+    /// CAVEAT: If no `Sink` runs on the current daemon, this method returns immediately.
+    pub async fn wait_complete(&self) {
+        self.end_of_stream_tracker.wait().await
+    }
+
+    /// Waits until all the channels between the nodes of this data flow instance running on the
+    /// current daemon are empty, or until the `timeout` expires.
+    ///
+    /// Returns `true` if the instance is quiescent.
+    ///
+    /// CAVEAT: An empty channel does not imply that the downstream node finished processing the
+    /// last message it received.
+    pub async fn wait_quiescence(&self, timeout: Duration) -> bool {
+        self.wait_drained(|_| true, timeout).await
+    }
+
+    /// Waits until the `channels` matching the predicate are empty, or until the `timeout` expires.
+    ///
+    /// Returns `true` if they are empty.
+    async fn wait_drained(
+        &self,
+        channels: impl Fn(&LinkChannel) -> bool,
+        timeout: Duration,
+    ) -> bool {
+        let start = Instant::now();
+        loop {
+            if self
+                .channels
+                .iter()
+                .filter(|channel| channels(channel))
+                .all(|channel| channel.rx.is_empty())
+            {
+                return true;
+            }
+
+            if start.elapsed() >= timeout {
+                return false;
+            }
+
+            crate::executor::sleep(QUIESCENCE_POLLING_INTERVAL).await;
+        }
+    }
+
+    /// Returns the time elapsed since a node of this data flow instance running on the current
+    /// daemon last sent a message, or since the first node was started if none did.
+    pub fn idle_time(&self) -> Duration {
+        self.activity.idle_time()
+    }
+
+    /// Waits until no node of this data flow instance running on the current daemon sent a
+    /// message for `duration`.
+    ///
+    /// Unlike the `idle_timeout` of the data flow, no notification is published.
+    pub async fn wait_idle(&self, duration: Duration) {
+        loop {
+            let idle_time = self.idle_time();
+            if idle_time >= duration {
+                return;
+            }
+
+            crate::executor::sleep(duration - idle_time).await;
+        }
+    }
+
+    /// Returns, for each input of the nodes running on the current daemon fed by links declaring a
+    /// queue (see [`QueueDescriptor`](crate::model::descriptor::QueueDescriptor)), the number of
+    /// messages dropped because the node could not keep up.
+    pub fn dropped_messages(&self) -> HashMap<InputDescriptor, u64> {
+        let mut dropped = HashMap::new();
+        for (_, outputs) in self.io.values() {
+            for (_, queue) in outputs.link_queues() {
+                *dropped.entry(queue.to.clone()).or_insert(0) += queue.dropped();
+            }
+        }
+
+        dropped
+    }
+
+    /// Returns, for each output of the nodes running on the current daemon, the number of messages
+    /// (data and watermarks) sent on it: each link starting from the output carries them all.
+    pub fn sent_messages(&self) -> HashMap<OutputDescriptor, u64> {
+        self.io
+            .iter()
+            .flat_map(|(node_id, (_, outputs))| {
+                outputs.taps.iter().map(move |(port_id, tap)| {
+                    (OutputDescriptor::new(node_id, port_id), tap.sent())
+                })
+            })
+            .collect()
+    }
+
+    /// Returns, for each input of the nodes running on the current daemon fed through Zenoh by a
+    /// node running on another daemon, the number of messages that were lost and the number of
+    /// messages that were lost and then retransmitted (see
+    /// [`LinkDescriptor`](crate::model::descriptor::LinkDescriptor)).
+    pub fn missed_messages(&self) -> HashMap<InputDescriptor, (u64, u64)> {
+        self.sequences
+            .iter()
+            .flat_map(|(receiver_id, sequence)| {
+                self.data_flow
+                    .links
+                    .iter()
+                    .filter(move |link| &link.from.node == receiver_id)
+                    .map(|link| (link.to.clone(), (sequence.missed(), sequence.recovered())))
+            })
+            .collect()
+    }
+
+    /// Returns, for each output of the nodes running on the current daemon that sends data through
+    /// Zenoh to nodes running on other daemons, the [Traffic] it generated.
+    ///
+    /// The traffic of an output feeding several daemons is the sum of the traffic sent to each.
+    pub fn sent_traffic(&self) -> HashMap<OutputDescriptor, Traffic> {
+        let mut sent = HashMap::new();
+        for (connector_id, connector) in &self.data_flow.connectors {
+            if connector.kind != ZFConnectorKind::Sender {
+                continue;
+            }
+
+            let traffic = match self.traffic.get(connector_id) {
+                Some(traffic) => traffic.get(),
+                None => continue,
+            };
+            for link in self
+                .data_flow
+                .links
+                .iter()
+                .filter(|link| &link.to.node == connector_id)
+            {
+                *sent.entry(link.from.clone()).or_default() += traffic;
+            }
+        }
+
+        sent
+    }
+
+    /// Returns, for each input of the nodes running on the current daemon fed through Zenoh by a
+    /// node running on another daemon, the [Traffic] it received.
+    pub fn received_traffic(&self) -> HashMap<InputDescriptor, Traffic> {
+        let mut received = HashMap::new();
+        for (connector_id, connector) in &self.data_flow.connectors {
+            if connector.kind != ZFConnectorKind::Receiver {
+                continue;
+            }
+
+            let traffic = match self.traffic.get(connector_id) {
+                Some(traffic) => traffic.get(),
+                None => continue,
+            };
+            for link in self
+                .data_flow
+                .links
+                .iter()
+                .filter(|link| &link.from.node == connector_id)
+            {
+                *received.entry(link.to.clone()).or_default() += traffic;
+            }
+        }
+
+        received
+    }
+
+    /// Stops, in order, all the nodes of this data flow instance running on the current daemon,
+    /// cleans them and releases all their resources (including the ones declared on Zenoh).
+    ///
+    /// The teardown is performed in the following order:
+    /// 1. the `Source`s are stopped,
+    /// 2. we wait for the messages in flight to be processed (at most
+    ///    [DEFAULT_QUIESCENCE_TIMEOUT]),
+    /// 3. the `Operator`s, the `Sink`s and the `ZFConnector`s are stopped,
+    /// 4. `clean` is called on every node, once its cool-down (if any) has elapsed,
+    /// 5. the nodes are dropped and, once the instance is dropped, the libraries are unloaded.
+    ///
+    /// All the steps are performed even if some of them fail.
+    ///
+    /// # Error
+    ///
+    /// An error aggregating all the failures is returned if at least one node could not be stopped
+    /// or cleaned.
+    pub async fn stop(mut self) -> Result<()> {
+        let mut errors = Vec::new();
+
+        // The nodes stopping must not be reported as idle.
+        if let Some(idle_monitor) = self.idle_monitor.take() {
+            idle_monitor.cancel().await;
+        }
+
+        if let Some(janitor) = self.janitor.take() {
+            janitor.cancel().await;
+        }
+
+        for id in self.get_sources() {
+            self.stop_runner(&id, &mut errors).await;
+        }
+
+        if !self.wait_quiescence(DEFAULT_QUIESCENCE_TIMEOUT).await {
+            log::warn!(
+                "[Instance: {}] Not quiescent after {:?}, messages in flight will be lost",
+                self.uuid,
+                DEFAULT_QUIESCENCE_TIMEOUT
+            );
+        }
+
+        let others = self
+            .get_operators()
+            .into_iter()
+            .chain(self.get_sinks())
+            .chain(self.get_connectors())
+            .collect::<Vec<_>>();
+        for id in others {
+            self.stop_runner(&id, &mut errors).await;
+        }
+
+        // A node is only cleaned once its cool-down, if any, has elapsed.
+        let stopped = Instant::now();
+        let mut runners = self.runners.iter().collect::<Vec<_>>();
+        runners.sort_by_key(|(id, _)| self.cooldowns.get(*id).copied().unwrap_or_default());
+        let context = Context::new(&self._instance_context);
+        for (id, runner) in runners {
+            if let Some(cooldown) = self.cooldowns.get(id) {
+                crate::executor::sleep(cooldown.saturating_sub(stopped.elapsed())).await;
+            }
+            if let Err(e) = catch_panic(id, runner.node.clean(&context)).await {
+                log::error!("[Instance: {}] Failed to clean < {id} >: {e:?}", self.uuid);
+                errors.push(format!("clean < {id} >: {e}"));
+            }
+        }
+
+        for (_, tap) in self.taps.drain() {
+            tap.cancel().await;
+        }
+
+        for import in self.imports.values_mut() {
+            import.disconnect().await;
+        }
+
+        // The instances importing the exposed outputs are notified that they are withdrawn.
+        for ((node_id, port_id), exposure) in std::mem::take(&mut self.exposures) {
+            exposure.cancel().await;
+            let key_expr = EXPOSED_PATH!(ROOT_STANDALONE, self.uuid, node_id, port_id);
+            if let Err(e) = self.context.session.delete(&key_expr).res().await {
+                log::warn!(
+                    "[Instance: {}] Failed to withdraw < {key_expr} >: {e:?}",
+                    self.uuid
+                );
+            }
+        }
+
+        for replay in self.replays.drain(..) {
+            replay.handle.cancel().await;
+        }
+
+        self.recording_session = None;
+        for (_, buffering) in self.buffers.drain() {
+            buffering.stop().await;
+        }
+
+        let recordings = self.recordings.drain().collect::<Vec<_>>();
+        for ((node_id, port_id), recording) in recordings {
+            match recording.stop().await {
+                Ok(metadata) => self.recording_stopped(&metadata),
+                Err(e) => log::error!(
+                    "[Instance: {}] Failed to stop the recording of < {node_id}.{port_id} >: {e:?}",
+                    self.uuid
+                ),
+            }
+        }
+
+        // Dropping the nodes releases their resources, in particular the Zenoh publishers and
+        // subscribers of the connectors. The libraries are unloaded when the instance is dropped.
+        self.runners.clear();
+        self.channels.clear();
+        self.io.clear();
+        self.debuggers.clear();
+        self.flow_controls.clear();
+        self.controls.clear();
+
+        if !errors.is_empty() {
+            bail!(
+                ErrorKind::RunnerStopError,
+                "[Instance: {}] Encountered {} error(s) while stopping: {}",
+                self.uuid,
+                errors.len(),
+                errors.join(", ")
+            )
+        }
+
+        Ok(())
+    }
+
+    /// Stops the runner of the node `id`, if it is running, and records the error, if any.
+    async fn stop_runner(&mut self, id: &NodeId, errors: &mut Vec<String>) {
+        if let Some(runner) = self.runners.get_mut(id) {
+            if runner.is_running() {
+                if let Err(e) = runner.stop().await {
+                    log::error!("Failed to stop < {id} >: {e:?}");
+                    errors.push(format!("stop < {id} >: {e}"));
+                }
+                self.events.emit(id, InstanceEventKind::NodeStopped);
+            }
+        }
+    }
+
+    /// Returns the stream of the lifecycle events of the nodes running on the current daemon: nodes
+    /// started, stopped or ending with an error, recordings started or stopped (see
+    /// [InstanceEvent]).
+    ///
+    /// The stream yields the events emitted after this call, in order, and ends once the instance
+    /// is dropped. The same events are published, as JSON, on
+    /// `zenoh-flow/lifecycle/<instance id>/<node id>` (see [`LIFECYCLE_PATH`](crate::LIFECYCLE_PATH))
+    /// for the supervisors and UIs running elsewhere.
+    pub fn events(&self) -> impl futures::Stream<Item = InstanceEvent> {
+        self.events.subscribe().into_stream()
+    }
+
+    /// Broadcasts a control message, carrying `payload`, to the nodes running on the current
+    /// daemon --- e.g. "flush your caches now" or "rotate your output files": each node calls its
+    /// [`on_control`](Node::on_control) with the `payload`, in between two polls of its
+    /// `iteration`. The connectors do not receive it.
+    ///
+    /// Without `barrier`, the message is delivered to all the nodes at once, regardless of the
+    /// data in flight.
+    ///
+    /// With `barrier`, the message is delivered to the Sources only, once their current `iteration`
+    /// is over. Each node then forwards it, in-band, on all its links: a node handles it once all
+    /// its channels delivered it, no longer receiving from the channels that did until then. Each
+    /// node thus handles the message after all the data its upstream nodes sent before handling it,
+    /// and before all the data they sent after --- across daemons as well, through the connectors.
+    ///
+    /// CAVEATS:
+    /// - an instance spread over several daemons needs the message to be broadcast on each of them
+    ///   without `barrier`, or on the daemons running its Sources with `barrier`,
+    /// - with `barrier`, a cycle in the data flow or an input that is never taken by its node stalls
+    ///   the message.
+    pub fn broadcast_control(&self, payload: Vec<u8>, barrier: bool) {
+        let marker = ControlMarker::new(
+            self._instance_context.hlc.new_timestamp(),
+            Arc::new(payload),
+        );
+
+        for (node_id, control) in &self.controls {
+            if !barrier {
+                control.deliver(Control::Message(marker.clone()));
+            } else if self.source_constructors.contains_key(node_id) {
+                control.deliver(Control::Barrier(marker.clone()));
+            }
+        }
+    }
+
     /// Start the node whose id matches the one provided.
     ///
     /// Start means launching as many tasks as necessary to run continuously the `Node`, input
@@ -99,7 +718,41 @@ impl DataFlowInstance {
     /// This method can return an error if the provided `node_id` is not found.
     pub fn start_node(&mut self, node_id: &NodeId) -> Result<()> {
         if let Some(runner) = self.runners.get_mut(node_id) {
-            runner.start();
+            if self.idle_monitor.is_none() {
+                // The instance is idle from the moment its first node starts.
+                self.activity.touch();
+                if let Some(idle_timeout) = self.data_flow.idle_timeout {
+                    self.idle_monitor = Some(idle::watch(
+                        self.activity.clone(),
+                        idle_timeout,
+                        self.data_flow.context.session.clone(),
+                        self.data_flow.uuid,
+                        self.data_flow.context.runtime_name.clone(),
+                    ));
+                }
+            }
+            if self.janitor.is_none() {
+                if let Some(retention) = &self.data_flow.retention {
+                    // Without recording backend, only the events are subject to the policy.
+                    let sink = record_sink::record_sink(
+                        &self.data_flow.context.recording_backend,
+                        self.data_flow.context.session.clone(),
+                    )
+                    .ok();
+                    self.janitor = Some(retention::spawn_janitor(
+                        retention.clone(),
+                        self.retention_ledger.clone(),
+                        sink,
+                        self.data_flow.context.session.clone(),
+                        self.data_flow.uuid,
+                        self.data_flow.context.runtime_uuid,
+                    ));
+                }
+            }
+            if !runner.is_running() {
+                runner.start();
+                self.events.emit(node_id, InstanceEventKind::NodeStarted);
+            }
             return Ok(());
         }
 
@@ -123,7 +776,14 @@ impl DataFlowInstance {
     /// This method can return an error if the provided `node_id` is not found.
     pub async fn stop_node(&mut self, node_id: &NodeId) -> Result<()> {
         if let Some(runner) = self.runners.get_mut(node_id) {
-            return runner.stop().await;
+            if runner.is_running() {
+                runner.stop().await?;
+                self.events.emit(node_id, InstanceEventKind::NodeStopped);
+            }
+            if let Some(debugger) = self.debuggers.get(node_id) {
+                debugger.reset();
+            }
+            return Ok(());
         }
 
         bail!(
@@ -133,6 +793,529 @@ impl DataFlowInstance {
         )
     }
 
+    /// Restart the node whose id matches the one provided.
+    ///
+    /// Restart means stopping the node (if it is running), calling `clean` on it (after its
+    /// cool-down, if any), creating a new instance of the node --- hence re-initializing its state
+    /// --- connected to the same links and starting it. This is useful when a node is stuck without
+    /// having crashed.
+    ///
+    /// Messages received by the node that were not processed before the restart are lost. Messages
+    /// waiting in the channels are processed by the new instance of the node. If a channel is empty
+    /// and its upstream output opted into last value caching, the last value sent on that output is
+    /// sent again to the new instance.
+    ///
+    /// # Error
+    ///
+    /// This method can return an error if the provided `node_id` is not found or if the node could
+    /// not be created.
+    pub async fn restart_node(&mut self, node_id: &NodeId) -> Result<()> {
+        let context = Context::new(&self._instance_context);
+        let cooldown = self.cooldowns.get(node_id).copied();
+        let runner = self.runners.get_mut(node_id).ok_or_else(|| {
+            zferror!(
+                ErrorKind::NodeNotFound(node_id.clone()),
+                "Node < {} > not found",
+                node_id
+            )
+        })?;
+
+        if runner.is_running() {
+            runner.stop().await?;
+            self.events.emit(node_id, InstanceEventKind::NodeStopped);
+        }
+
+        if let Some(cooldown) = cooldown {
+            crate::executor::sleep(cooldown).await;
+        }
+
+        if let Err(e) = catch_panic(node_id, runner.node.clean(&context)).await {
+            log::warn!("Failed to clean < {node_id} > before restarting it: {e:?}");
+        }
+
+        if let Some(debugger) = self.debuggers.get(node_id) {
+            debugger.reset();
+        }
+
+        let watchdog = self
+            .runners
+            .get(node_id)
+            .and_then(|runner| runner.watchdog.clone());
+        let mut runner = self.create_runner(node_id, watchdog).await?;
+
+        // The upstream outputs that opted into last value caching send their last value again, unless
+        // a more recent message is already waiting to be processed.
+        let inputs_last_values = self
+            .io
+            .get(node_id)
+            .map(|(inputs, _)| inputs.last_values.clone())
+            .unwrap_or_default();
+        for (cache, tx) in inputs_last_values {
+            if let (true, Some(message)) = (tx.is_empty(), cache.get()) {
+                tx.send_async(message).await.map_err(|e| {
+                    zferror!(
+                        ErrorKind::SendError,
+                        "Failed to send the last value to < {} >: {:?}",
+                        node_id,
+                        e
+                    )
+                })?;
+            }
+        }
+
+        runner.start();
+        self.runners.insert(node_id.clone(), runner);
+        self.events.emit(node_id, InstanceEventKind::NodeStarted);
+
+        Ok(())
+    }
+
+    /// Creates a new instance of the node `node_id`, connected to its links, and its runner: the
+    /// runner is not started.
+    async fn create_runner(
+        &self,
+        node_id: &NodeId,
+        watchdog: Option<Arc<Watchdog>>,
+    ) -> Result<Runner> {
+        let (mut inputs, outputs) = self.io.get(node_id).cloned().ok_or_else(|| {
+            zferror!(
+                ErrorKind::IOError,
+                "Links for Node < {} > were not found.",
+                node_id
+            )
+        })?;
+
+        // The node takes its inputs again, possibly rewired.
+        let control = self.controls.get(node_id).cloned();
+        if let Some(control) = &control {
+            control.rewire(&outputs);
+        }
+        inputs.control = control.clone();
+
+        let (scheduler, timers) = Timers::new(self._instance_context.simulation.clone());
+        let context = Context::new(&self._instance_context)
+            .with_node_id(node_id.clone())
+            .with_timers(scheduler)
+            .with_watchdog(watchdog.clone())
+            .with_keyed_state(self.keyed_states.get(node_id).cloned());
+
+        let node = if let Some(source) = self.source_constructors.get(node_id) {
+            catch_panic(
+                node_id,
+                (source.constructor)(context, source.configuration.clone(), outputs),
+            )
+            .await
+            .node_context(node_id)?
+        } else if let Some(operator) = self.operator_constructors.get(node_id) {
+            catch_panic(
+                node_id,
+                (operator.constructor)(context, operator.configuration.clone(), inputs, outputs),
+            )
+            .await
+            .node_context(node_id)?
+        } else if let Some(sink) = self.sink_constructors.get(node_id) {
+            catch_panic(
+                node_id,
+                (sink.constructor)(context, sink.configuration.clone(), inputs),
+            )
+            .await
+            .node_context(node_id)?
+        } else if let Some(connector) = self.connectors.get(node_id) {
+            let instance_context = self._instance_context.clone();
+            let traffic = self.traffic.get(node_id).cloned().unwrap_or_default();
+            match connector.kind {
+                ZFConnectorKind::Sender => {
+                    Arc::new(ZenohSender::new(connector, instance_context, inputs, traffic).await?)
+                        as Arc<dyn Node>
+                }
+                ZFConnectorKind::Receiver => {
+                    // The sender kept on numbering its messages.
+                    let sequence = self.sequences.get(node_id).cloned().unwrap_or_default();
+                    sequence.reset();
+                    Arc::new(
+                        ZenohReceiver::new(connector, instance_context, outputs, sequence, traffic)
+                            .await?,
+                    ) as Arc<dyn Node>
+                }
+            }
+        } else {
+            bail!(
+                ErrorKind::NodeNotFound(node_id.clone()),
+                "Node < {} > not found",
+                node_id
+            )
+        };
+
+        Ok(Runner::new(
+            node_id.clone(),
+            node,
+            self.max_run_durations.get(node_id).copied(),
+        )
+        .with_timers(timers)
+        .with_flow_control(self.flow_controls.get(node_id).cloned())
+        .with_link_queues(&outputs_queues(&self.io, node_id))
+        .with_warmup(outputs_warmup(&self.io, node_id))
+        .with_watchdog(watchdog)
+        .with_events(self.events.clone())
+        .with_control(control)
+        .with_keyed_state(self.keyed_states.get(node_id).cloned()))
+    }
+
+    /// Changes the number of copies of the replicated Operator `node_id` (see
+    /// [`DataFlowDescriptor`](crate::model::descriptor::DataFlowDescriptor)) to `replicas`, while
+    /// the instance is running.
+    ///
+    /// A savepoint of the nodes involved is taken first: the nodes sending messages to the copies
+    /// --- the partitioner among them --- are stopped, the messages in flight are processed (at
+    /// most [DEFAULT_QUIESCENCE_TIMEOUT]), then the copies and the nodes they send messages to are
+    /// stopped. The state of these nodes (see [`Node::checkpoint`]) and the partitions of the keyed
+    /// state of the copies (see [KeyedState]) are saved.
+    ///
+    /// The copies are then added, or removed, and their links rewired: the links of a new copy
+    /// mirror the ones of the first copy, the ports generated with the `{replica}` placeholder being
+    /// indexed with the new copy (e.g. the partitioner gets a new output `out-2` for the third
+    /// copy). The nodes involved are finally created again --- such that the partitioner sees its new
+    /// outputs and spreads the keys over the new number of copies (see
+    /// [`replica_of`](crate::types::replica_of)) ---, restore their state, each copy restoring the
+    /// partitions it now owns, and the ones that were running are started along with the new copies.
+    ///
+    /// CAVEAT: only the Operators whose copies run on the current daemon, and are only connected to
+    /// nodes running on the current daemon, can be rescaled. The state of the removed copies,
+    /// outside of their keyed state, is lost, as well as the messages that were waiting in their
+    /// links after the [DEFAULT_QUIESCENCE_TIMEOUT].
+    ///
+    /// # Error
+    ///
+    /// This method can return an error if the node is not a replicated Operator, if `replicas` is
+    /// 0, if the copies or the nodes they are connected to do not all run on the current daemon, or
+    /// if a node could not be stopped, checkpointed, created or restored. In the last cases the
+    /// nodes involved are left stopped.
+    pub async fn rescale(&mut self, node_id: &NodeId, replicas: usize) -> Result<()> {
+        rescale::rescale(self, node_id, replicas).await
+    }
+
+    /// Returns the shortest interval at which the load of the autoscaled Operators (see
+    /// [AutoscalingDescriptor](crate::model::descriptor::AutoscalingDescriptor)) must be checked,
+    /// `None` if the data flow has none.
+    pub fn autoscaling_interval(&self) -> Option<Duration> {
+        self.data_flow
+            .autoscaling
+            .values()
+            .map(|autoscaling| autoscaling.interval())
+            .min()
+    }
+
+    /// Rescales the autoscaled Operators whose load requires it (see
+    /// [AutoscalingDescriptor](crate::model::descriptor::AutoscalingDescriptor)) and returns their
+    /// new number of copies.
+    ///
+    /// The load of an Operator is the number of messages waiting in the links leading to its
+    /// copies. An Operator is left untouched if its first copy is not running or if it was rescaled
+    /// less than its `cooldown` ago. An Operator that could not be rescaled is reported in the logs
+    /// and skipped: its nodes may be left stopped (see [rescale](Self::rescale)).
+    ///
+    /// This method is meant to be called periodically, every
+    /// [autoscaling_interval](Self::autoscaling_interval).
+    pub async fn autoscale(&mut self) -> Vec<(NodeId, usize)> {
+        let mut rescaled = Vec::new();
+        let policies = self
+            .data_flow
+            .autoscaling
+            .iter()
+            .map(|(node_id, autoscaling)| (node_id.clone(), autoscaling.clone()))
+            .collect::<Vec<_>>();
+
+        for (node_id, autoscaling) in policies {
+            let current = match self.data_flow.replicas.get(&node_id) {
+                Some(current) => *current,
+                None => continue,
+            };
+            if !self
+                .runners
+                .get(&replica_id(&node_id, 0))
+                .map_or(false, |runner| runner.is_running())
+            {
+                continue;
+            }
+            if self
+                .rescaled
+                .get(&node_id)
+                .map_or(false, |last| last.elapsed() < autoscaling.cooldown())
+            {
+                continue;
+            }
+
+            let copies = (0..current)
+                .map(|index| replica_id(&node_id, index))
+                .collect::<Vec<_>>();
+            let queued = self
+                .channels
+                .iter()
+                .filter(|channel| copies.contains(&channel.to.node))
+                .map(|channel| channel.tx.len())
+                .sum();
+            let replicas = autoscaling.desired_replicas(current, queued);
+            if replicas == current {
+                continue;
+            }
+
+            log::debug!(
+                "[Instance: {}] {} messages waiting for the {} copies of < {} >, rescaling to {}",
+                self.uuid,
+                queued,
+                current,
+                node_id,
+                replicas
+            );
+            match self.rescale(&node_id, replicas).await {
+                Ok(()) => rescaled.push((node_id, replicas)),
+                Err(e) => {
+                    // The cooldown also applies to failed attempts, such that a node that cannot
+                    // be rescaled is not stopped at every interval.
+                    self.rescaled.insert(node_id.clone(), Instant::now());
+                    log::warn!(
+                        "[Instance: {}] Could not autoscale < {} > to {} replicas: {:?}",
+                        self.uuid,
+                        node_id,
+                        replicas,
+                        e
+                    )
+                }
+            }
+        }
+
+        rescaled
+    }
+
+    /// Attaches a debug tap to the output `port_id` of the node `node_id`: every message sent on
+    /// that output, hence on all the links starting from it, is published on Zenoh under
+    /// `zenoh-flow/tap/<instance id>/<node id>/<port id>`. The key expression is returned.
+    ///
+    /// If `decode` is false, the messages are published serialized with `bincode`, as they would be
+    /// sent to a node running on another daemon. Otherwise they are published as JSON, the payload
+    /// being interpreted, in order of preference, as JSON, as a UTF-8 string or as raw bytes.
+    ///
+    /// The tap does not modify the data flow and never slows it down: if the publication cannot
+    /// keep up, messages are skipped. Attaching a tap to an output that already has one replaces
+    /// it. The payloads are published altered by the redaction rule of the output, if any.
+    ///
+    /// # Error
+    ///
+    /// This method can return an error if the node or the output are not found on this daemon, or if
+    /// the transform of the redaction rule of the output could not be loaded.
+    pub async fn tap(
+        &mut self,
+        node_id: &NodeId,
+        port_id: &PortId,
+        decode: bool,
+    ) -> Result<String> {
+        let output_tap = self.output_tap(node_id, port_id)?;
+        let redaction = self.redaction(node_id, port_id)?;
+        self.untap(node_id, port_id).await;
+
+        let key_expr = TAP_PATH!(ROOT_STANDALONE, self.uuid, node_id, port_id);
+        let handle = crate::executor::spawn(tap::publish_tap(
+            self.context.session.clone(),
+            key_expr.clone(),
+            output_tap.attach(),
+            decode,
+            redaction,
+        ));
+        self.taps.insert((node_id.clone(), port_id.clone()), handle);
+
+        log::info!("[Instance: {}] Tap attached on < {key_expr} >", self.uuid);
+        Ok(key_expr)
+    }
+
+    /// Detaches the debug tap attached to the output `port_id` of the node `node_id`, if any.
+    ///
+    /// Returns `true` if a tap was detached.
+    pub async fn untap(&mut self, node_id: &NodeId, port_id: &PortId) -> bool {
+        match self.taps.remove(&(node_id.clone(), port_id.clone())) {
+            Some(handle) => {
+                handle.cancel().await;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Connects the imported input `input` to the exposed output `output` of the instance
+    /// `instance_id`: the messages sent on that output are received on the input. The key
+    /// expression of the exposed output is returned.
+    ///
+    /// The instances have independent lifecycles: the instance exposing the output can be started
+    /// after this one and its end of streams are not forwarded. When it is stopped, a warning is
+    /// logged and the input receives nothing until the output is exposed again. Connecting an input
+    /// that already is replaces its connection.
+    ///
+    /// # Error
+    ///
+    /// This method can return an error if the input is not an imported input of a node running on
+    /// this daemon or if the subscription to the exposed output failed.
+    pub async fn connect_import(
+        &mut self,
+        input: &InputDescriptor,
+        instance_id: uuid::Uuid,
+        output: &OutputDescriptor,
+    ) -> Result<String> {
+        let key_expr = EXPOSED_PATH!(ROOT_STANDALONE, instance_id, output.node, output.output);
+        let session = self.context.session.clone();
+        let uuid = self.uuid;
+
+        let import = self
+            .imports
+            .get_mut(&(input.node.clone(), input.input.clone()))
+            .ok_or_else(|| {
+                zferror!(
+                    ErrorKind::PortNotFound((input.node.clone(), input.input.clone())),
+                    "Imported input < {} > not found",
+                    input
+                )
+            })?;
+        import.disconnect().await;
+
+        let handle =
+            import::forward_exposed(session, key_expr.clone(), import.sender.clone()).await?;
+        import.connection = Some((key_expr.clone(), handle));
+
+        log::info!("[Instance: {uuid}] < {input} > connected to < {key_expr} >");
+        Ok(key_expr)
+    }
+
+    /// Disconnects the imported input `input` from the exposed output it is connected to, if any.
+    ///
+    /// Returns the key expression of that exposed output.
+    pub async fn disconnect_import(&mut self, input: &InputDescriptor) -> Option<String> {
+        let import = self
+            .imports
+            .get_mut(&(input.node.clone(), input.input.clone()))?;
+        import.disconnect().await
+    }
+
+    /// Returns the redaction rule of the output `port_id` of the node `node_id`, if any, with its
+    /// transform loaded.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the transform of the rule could not be loaded.
+    fn redaction(&self, node_id: &NodeId, port_id: &PortId) -> Result<Option<Redaction>> {
+        self.data_flow
+            .redaction
+            .iter()
+            .find(|rule| &rule.output.node == node_id && &rule.output.output == port_id)
+            .map(|rule| Redaction::load(rule.clone(), &self.context.loader))
+            .transpose()
+    }
+
+    /// Returns the [OutputTap] of the output `port_id` of the node `node_id`.
+    fn output_tap(&self, node_id: &NodeId, port_id: &PortId) -> Result<Arc<OutputTap>> {
+        let (_, outputs) = self.io.get(node_id).ok_or_else(|| {
+            zferror!(
+                ErrorKind::NodeNotFound(node_id.clone()),
+                "Node < {} > not found",
+                node_id
+            )
+        })?;
+
+        outputs.taps.get(port_id).cloned().ok_or_else(|| {
+            zferror!(
+                ErrorKind::PortNotFound((node_id.clone(), port_id.clone())),
+                "Output < {} > of Node < {} > not found",
+                port_id,
+                node_id
+            )
+            .into()
+        })
+    }
+
+    /// Sets a breakpoint on the input `port_id` of the node `node_id` or, if no input is provided, on
+    /// all its inputs.
+    ///
+    /// When a data message is received on an input with a breakpoint, the node is paused before
+    /// processing it: the message is published, as JSON, on
+    /// `zenoh-flow/debug/<instance id>/<node id>` and can be retrieved with `paused_message`. The
+    /// node then waits for `resume` to be called.
+    ///
+    /// # Error
+    ///
+    /// This method can return an error if the node is not found on this daemon, if it has no input
+    /// or if the input is not found.
+    pub fn set_breakpoint(&self, node_id: &NodeId, port_id: Option<&PortId>) -> Result<()> {
+        self.get_debugger(node_id, port_id)?
+            .set_breakpoint(port_id, true)
+    }
+
+    /// Removes the breakpoint on the input `port_id` of the node `node_id` or, if no input is
+    /// provided, the breakpoint on all its inputs.
+    ///
+    /// CAVEAT: removing the breakpoint does not resume a node paused on it.
+    ///
+    /// # Error
+    ///
+    /// This method can return an error if the node is not found on this daemon, if it has no input
+    /// or if the input is not found.
+    pub fn remove_breakpoint(&self, node_id: &NodeId, port_id: Option<&PortId>) -> Result<()> {
+        self.get_debugger(node_id, port_id)?
+            .set_breakpoint(port_id, false)
+    }
+
+    /// Returns the data message on which the node `node_id` is paused and the input it was received
+    /// on, if the node is paused.
+    pub fn paused_message(&self, node_id: &NodeId) -> Option<(PortId, LinkMessage)> {
+        self.debuggers
+            .get(node_id)
+            .and_then(|debugger| debugger.paused())
+    }
+
+    /// Resumes the node `node_id` paused on a breakpoint.
+    ///
+    /// If `step` is true, the node pauses again on the next data message it receives, whatever the
+    /// input. Otherwise it pauses again only on a breakpoint.
+    ///
+    /// # Error
+    ///
+    /// This method can return an error if the node is not found on this daemon or if it is not
+    /// paused.
+    pub fn resume(&self, node_id: &NodeId, step: bool) -> Result<()> {
+        let command = if step {
+            DebugCommand::Step
+        } else {
+            DebugCommand::Continue
+        };
+
+        self.get_debugger(node_id, None)?.resume(command)
+    }
+
+    /// Returns the debugger of the node `node_id`, checking that it has the input `port_id`.
+    fn get_debugger(&self, node_id: &NodeId, port_id: Option<&PortId>) -> Result<&NodeDebugger> {
+        let debugger = self.debuggers.get(node_id).ok_or_else(|| {
+            zferror!(
+                ErrorKind::NodeNotFound(node_id.clone()),
+                "Node < {} > not found or without input",
+                node_id
+            )
+        })?;
+
+        if let Some(port_id) = port_id {
+            let has_input = self
+                .io
+                .get(node_id)
+                .map(|(inputs, _)| inputs.contains_key(port_id))
+                .unwrap_or(false);
+            if !has_input {
+                bail!(
+                    ErrorKind::PortNotFound((node_id.clone(), port_id.clone())),
+                    "Input < {} > of Node < {} > not found",
+                    port_id,
+                    node_id
+                )
+            }
+        }
+
+        Ok(debugger)
+    }
+
     /// Given a `DataFlow` and an `HLC`, try to instantiate the data flow by generating all the
     /// nodes (via their factories) and all the connections --- _running on the daemon_.
     ///
@@ -142,10 +1325,146 @@ impl DataFlowInstance {
     /// - some links are missing which resulted in some missing connections,
     /// - a factory failed to generate a node.
     pub async fn try_instantiate(data_flow: DataFlow, hlc: Arc<HLC>) -> Result<Self> {
+        Self::try_instantiate_with_clock(data_flow, hlc, None).await
+    }
+
+    /// Given a `DataFlow` and a `SimulationClock`, try to instantiate the data flow in simulation
+    /// mode --- _running on the daemon_.
+    ///
+    /// In simulation mode the timestamps of the messages are derived from the simulated time and
+    /// the nodes relying on [`Context::sleep`](crate::types::Context::sleep) wait for the
+    /// simulated time to elapse. The simulated time only moves forward when the `clock` is
+    /// advanced, which makes the execution of the instance independent of the real time.
+    ///
+    /// # Error
+    ///
+    /// This function can return an error if:
+    /// - some links are missing which resulted in some missing connections,
+    /// - a factory failed to generate a node.
+    pub async fn try_instantiate_simulated(
+        data_flow: DataFlow,
+        clock: SimulationClock,
+    ) -> Result<Self> {
+        let hlc = clock.new_hlc(*data_flow.context.hlc.get_id());
+        Self::try_instantiate_with_clock(data_flow, hlc, Some(clock)).await
+    }
+
+    async fn try_instantiate_with_clock(
+        mut data_flow: DataFlow,
+        hlc: Arc<HLC>,
+        simulation: Option<SimulationClock>,
+    ) -> Result<Self> {
+        let libraries = data_flow
+            .source_constructors
+            .values()
+            .filter_map(|constructor| constructor.library())
+            .chain(
+                data_flow
+                    .operator_constructors
+                    .values()
+                    .filter_map(|constructor| constructor.library()),
+            )
+            .chain(
+                data_flow
+                    .sink_constructors
+                    .values()
+                    .filter_map(|constructor| constructor.library()),
+            )
+            .cloned()
+            .collect::<Vec<_>>();
+
+        // The Zenoh sessions requested by the connectors are opened once per instance.
+        let mut sessions = HashMap::new();
+        for name in data_flow
+            .connectors
+            .values()
+            .filter_map(|record| record.session.as_ref())
+        {
+            if sessions.contains_key(name) {
+                continue;
+            }
+
+            // The configuration of the runtime prevails over the one described in the data flow.
+            let config = match data_flow.context.zenoh_configs.get(name) {
+                Some(config) => config.clone(),
+                None => match data_flow.sessions.get(name) {
+                    Some(transport) => transport.to_config()?,
+                    None => bail!(
+                        ErrorKind::ConfigurationError,
+                        "No Zenoh configuration < {} > on runtime < {} >",
+                        name,
+                        data_flow.context.runtime_name
+                    ),
+                },
+            };
+            let session = zenoh::open(config).res().await.map_err(|e| {
+                zferror!(
+                    ErrorKind::ZenohError,
+                    e => "Failed to open the Zenoh session < {} >: {}",
+                    name,
+                    e
+                )
+            })?;
+            sessions.insert(name.clone(), Arc::new(session));
+        }
+
+        // The GPUs are assigned to the nodes running on this runtime, which find them in their
+        // configuration.
+        let gpu_requests = data_flow
+            .gpus
+            .iter()
+            .filter(|(node_id, _)| {
+                data_flow.source_constructors.contains_key(*node_id)
+                    || data_flow.operator_constructors.contains_key(*node_id)
+                    || data_flow.sink_constructors.contains_key(*node_id)
+            })
+            .map(|(node_id, gpu)| (node_id.clone(), *gpu))
+            .collect::<HashMap<_, _>>();
+        let gpu_lease = if gpu_requests.is_empty() {
+            None
+        } else {
+            Some(
+                data_flow
+                    .context
+                    .gpus
+                    .allocate(data_flow.uuid, &gpu_requests)?,
+            )
+        };
+        if let Some(lease) = &gpu_lease {
+            for (node_id, constructor) in data_flow.source_constructors.iter_mut() {
+                set_gpus(
+                    &mut constructor.record.configuration,
+                    lease.devices(node_id),
+                );
+            }
+            for (node_id, constructor) in data_flow.operator_constructors.iter_mut() {
+                set_gpus(
+                    &mut constructor.record.configuration,
+                    lease.devices(node_id),
+                );
+            }
+            for (node_id, constructor) in data_flow.sink_constructors.iter_mut() {
+                set_gpus(
+                    &mut constructor.record.configuration,
+                    lease.devices(node_id),
+                );
+            }
+        }
+
         let instance_context = Arc::new(InstanceContext {
             flow_id: data_flow.flow.clone(),
             instance_id: data_flow.uuid,
             runtime: data_flow.context.clone(),
+            hlc: hlc.clone(),
+            simulation,
+            blackboard: Arc::new(Blackboard::new(
+                data_flow.uuid,
+                Some(data_flow.context.session.clone()),
+            )),
+            sessions,
+            // The channels belong to the built-in Host nodes: the `DataFlow` kept by the instance
+            // does not need them.
+            host: Arc::new(std::mem::take(&mut data_flow.host)),
         });
 
         let mut node_ids: Vec<NodeId> = Vec::with_capacity(
@@ -178,11 +1497,134 @@ impl DataFlowInstance {
         );
         node_ids.append(&mut data_flow.connectors.keys().cloned().collect::<Vec<_>>());
 
-        let mut links = create_links(&node_ids, &data_flow.links, hlc.clone())?;
+        let (mut links, channels, flow_controls) =
+            create_links(&node_ids, &data_flow.links, &data_flow.credits, hlc.clone())?;
+
+        let end_of_stream_tracker = Arc::new(EndOfStreamTracker::default());
+        for sink_id in data_flow.sink_constructors.keys() {
+            if let Some((inputs, _)) = links.get_mut(sink_id) {
+                end_of_stream_tracker.expect(inputs.len());
+                inputs.end_of_stream_tracker = Some(end_of_stream_tracker.clone());
+            }
+        }
+
+        // The imported inputs are fed by the exposed outputs of other instances, once connected.
+        let mut imports = HashMap::with_capacity(data_flow.imported.len());
+        for input in &data_flow.imported {
+            if !node_ids.contains(&input.node) {
+                continue;
+            }
+
+            let (tx, rx) = link::unbounded();
+            let (inputs, _) = links
+                .entry(input.node.clone())
+                .or_insert_with(|| (Inputs::new(), Outputs::new(hlc.clone())));
+            inputs.insert(input.input.clone(), rx);
+            imports.insert((input.node.clone(), input.input.clone()), Import::new(tx));
+        }
+
+        // The inputs resolve the data received by reference with the session of the runtime and
+        // timestamp their default values with the HLC of the instance.
+        for (inputs, _) in links.values_mut() {
+            inputs.session = Some(data_flow.context.session.clone());
+            inputs.hlc = Some(hlc.clone());
+        }
+
+        // Each node with inputs, except the connectors, can be paused on a breakpoint.
+        let mut debuggers = HashMap::new();
+        for (node_id, (inputs, _)) in links.iter_mut() {
+            if inputs.is_empty() || data_flow.connectors.contains_key(node_id) {
+                continue;
+            }
+
+            let debugger = Arc::new(NodeDebugger::new(
+                node_id.clone(),
+                DEBUG_PATH!(ROOT_STANDALONE, data_flow.uuid, node_id),
+                Some(data_flow.context.session.clone()),
+            ));
+            inputs.debugger = Some(debugger.clone());
+            debuggers.insert(node_id.clone(), debugger);
+        }
+
+        // Each node, except the connectors, receives the control messages broadcast to the
+        // instance.
+        let mut controls = HashMap::new();
+        let checkpoints = Arc::new(Checkpoints::default());
+        for (node_id, (inputs, outputs)) in links.iter_mut() {
+            if data_flow.connectors.contains_key(node_id) {
+                continue;
+            }
+
+            let control = Arc::new(NodeControl::new(
+                node_id.clone(),
+                outputs,
+                checkpoints.clone(),
+            ));
+            inputs.control = Some(control.clone());
+            controls.insert(node_id.clone(), control);
+        }
+
+        // The exposed outputs are published whether they are connected to other nodes or not.
+        let mut exposures = HashMap::with_capacity(data_flow.exposed.len());
+        for output in &data_flow.exposed {
+            if !node_ids.contains(&output.node) {
+                continue;
+            }
+
+            let (_, outputs) = links
+                .entry(output.node.clone())
+                .or_insert_with(|| (Inputs::new(), Outputs::new(hlc.clone())));
+            let key_expr =
+                EXPOSED_PATH!(ROOT_STANDALONE, data_flow.uuid, output.node, output.output);
+            let handle = crate::executor::spawn(tap::publish_tap(
+                data_flow.context.session.clone(),
+                key_expr.clone(),
+                outputs.expose(output.output.clone()).attach(),
+                false,
+                None,
+            ));
+            exposures.insert((output.node.clone(), output.output.clone()), handle);
+            log::debug!("[Instance: {}] Exposing < {key_expr} >", data_flow.uuid);
+        }
+
+        // The outputs of a node share its warm-up.
+        for (node_id, warmup) in &data_flow.warmups {
+            if let Some((_, outputs)) = links.get_mut(node_id) {
+                outputs.warmup = Arc::new(WarmUp::new(*warmup));
+            }
+        }
+
+        // The activity of the instance is tracked on all the outputs.
+        let activity = Arc::new(LinkActivity::default());
+        for (_, outputs) in links.values_mut() {
+            outputs.activity = activity.clone();
+        }
+
+        // The inputs of the Operators and Sinks follow the policies set in their descriptor.
+        for (operator_id, operator_constructor) in &data_flow.operator_constructors {
+            if let Some((inputs, _)) = links.get_mut(operator_id) {
+                inputs.policies = operator_constructor.input_policies.clone();
+            }
+        }
+        for (sink_id, sink_constructor) in &data_flow.sink_constructors {
+            if let Some((inputs, _)) = links.get_mut(sink_id) {
+                inputs.policies = sink_constructor.input_policies.clone();
+            }
+        }
+
+        // Keeping a copy of the channels of each node allows restarting it.
+        let io = links.clone();
 
         let context = Context::new(&instance_context);
+        let events = Arc::new(InstanceEvents::new(
+            data_flow.uuid,
+            data_flow.context.runtime_name.clone(),
+            hlc.clone(),
+            data_flow.context.session.clone(),
+        ));
 
         let mut runners = HashMap::with_capacity(data_flow.source_constructors.len());
+        let mut keyed_states = HashMap::with_capacity(data_flow.operator_constructors.len());
         for (source_id, source_constructor) in &data_flow.source_constructors {
             let (_, outputs) = links.remove(source_id).ok_or_else(|| {
                 zferror!(
@@ -192,14 +1634,35 @@ impl DataFlowInstance {
                 )
             })?;
 
-            let source = (source_constructor.constructor)(
-                context.clone(),
-                source_constructor.configuration.clone(),
-                outputs,
+            let (scheduler, timers) = Timers::new(instance_context.simulation.clone());
+            let watchdog = node_watchdog(&data_flow, source_id);
+            let source = catch_panic(
+                source_id,
+                (source_constructor.constructor)(
+                    context
+                        .clone()
+                        .with_node_id(source_id.clone())
+                        .with_timers(scheduler)
+                        .with_watchdog(watchdog.clone()),
+                    source_constructor.configuration.clone(),
+                    outputs,
+                ),
             )
-            .await?;
+            .await
+            .node_context(source_id)?;
 
-            let runner = Runner::new(source);
+            let runner = Runner::new(
+                source_id.clone(),
+                source,
+                data_flow.max_run_durations.get(source_id).copied(),
+            )
+            .with_timers(timers)
+            .with_flow_control(flow_controls.get(source_id).cloned())
+            .with_link_queues(&outputs_queues(&io, source_id))
+            .with_warmup(outputs_warmup(&io, source_id))
+            .with_watchdog(watchdog)
+            .with_events(events.clone())
+            .with_control(controls.get(source_id).cloned());
             runners.insert(source_id.clone(), runner);
         }
 
@@ -212,15 +1675,39 @@ impl DataFlowInstance {
                 )
             })?;
 
-            let operator = (operator_constructor.constructor)(
-                context.clone(),
-                operator_constructor.configuration.clone(),
-                inputs,
-                outputs,
+            let (scheduler, timers) = Timers::new(instance_context.simulation.clone());
+            let watchdog = node_watchdog(&data_flow, operator_id);
+            let keyed_state = data_flow.keyed_state(operator_id);
+            keyed_states.insert(operator_id.clone(), keyed_state.clone());
+            let operator = catch_panic(
+                operator_id,
+                (operator_constructor.constructor)(
+                    context
+                        .clone()
+                        .with_node_id(operator_id.clone())
+                        .with_timers(scheduler)
+                        .with_watchdog(watchdog.clone())
+                        .with_keyed_state(Some(keyed_state.clone())),
+                    operator_constructor.configuration.clone(),
+                    inputs,
+                    outputs,
+                ),
             )
-            .await?;
+            .await
+            .node_context(operator_id)?;
 
-            let runner = Runner::new(operator);
+            let runner = Runner::new(
+                operator_id.clone(),
+                operator,
+                data_flow.max_run_durations.get(operator_id).copied(),
+            )
+            .with_timers(timers)
+            .with_link_queues(&outputs_queues(&io, operator_id))
+            .with_warmup(outputs_warmup(&io, operator_id))
+            .with_watchdog(watchdog)
+            .with_events(events.clone())
+            .with_control(controls.get(operator_id).cloned())
+            .with_keyed_state(Some(keyed_state));
             runners.insert(operator_id.clone(), runner);
         }
 
@@ -233,18 +1720,40 @@ impl DataFlowInstance {
                 )
             })?;
 
-            let sink = (sink_constructor.constructor)(
-                context.clone(),
-                sink_constructor.configuration.clone(),
-                inputs,
+            let (scheduler, timers) = Timers::new(instance_context.simulation.clone());
+            let watchdog = node_watchdog(&data_flow, sink_id);
+            let sink = catch_panic(
+                sink_id,
+                (sink_constructor.constructor)(
+                    context
+                        .clone()
+                        .with_node_id(sink_id.clone())
+                        .with_timers(scheduler)
+                        .with_watchdog(watchdog.clone()),
+                    sink_constructor.configuration.clone(),
+                    inputs,
+                ),
             )
-            .await?;
+            .await
+            .node_context(sink_id)?;
 
-            let runner = Runner::new(sink);
+            let runner = Runner::new(
+                sink_id.clone(),
+                sink,
+                data_flow.max_run_durations.get(sink_id).copied(),
+            )
+            .with_timers(timers)
+            .with_watchdog(watchdog)
+            .with_events(events.clone())
+            .with_control(controls.get(sink_id).cloned());
             runners.insert(sink_id.clone(), runner);
         }
 
+        let mut sequences = HashMap::new();
+        let mut traffic = HashMap::new();
         for (connector_id, connector_record) in &data_flow.connectors {
+            let link_traffic = Arc::new(LinkTraffic::default());
+            traffic.insert(connector_id.clone(), link_traffic.clone());
             let node = match &connector_record.kind {
                 ZFConnectorKind::Sender => {
                     let (inputs, _) = links.remove(connector_id).ok_or_else(|| {
@@ -255,8 +1764,13 @@ impl DataFlowInstance {
                         )
                     })?;
                     Arc::new(
-                        ZenohSender::new(connector_record, instance_context.clone(), inputs)
-                            .await?,
+                        ZenohSender::new(
+                            connector_record,
+                            instance_context.clone(),
+                            inputs,
+                            link_traffic,
+                        )
+                        .await?,
                     ) as Arc<dyn Node>
                 }
                 ZFConnectorKind::Receiver => {
@@ -267,14 +1781,22 @@ impl DataFlowInstance {
                             &connector_id
                         )
                     })?;
+                    let sequence = Arc::new(LinkSequence::default());
+                    sequences.insert(connector_id.clone(), sequence.clone());
                     Arc::new(
-                        ZenohReceiver::new(connector_record, instance_context.clone(), outputs)
-                            .await?,
+                        ZenohReceiver::new(
+                            connector_record,
+                            instance_context.clone(),
+                            outputs,
+                            sequence,
+                            link_traffic,
+                        )
+                        .await?,
                     ) as Arc<dyn Node>
                 }
             };
 
-            let runner = Runner::new(node);
+            let runner = Runner::new(connector_id.clone(), node, None).with_events(events.clone());
             runners.insert(connector_id.clone(), runner);
         }
 
@@ -282,21 +1804,116 @@ impl DataFlowInstance {
             _instance_context: instance_context,
             data_flow,
             runners,
+            end_of_stream_tracker,
+            channels,
+            io,
+            taps: HashMap::new(),
+            exposures,
+            imports,
+            recordings: HashMap::new(),
+            recording_session: None,
+            buffers: HashMap::new(),
+            replays: Vec::new(),
+            debuggers,
+            flow_controls,
+            controls,
+            checkpoints,
+            sequences,
+            traffic,
+            keyed_states,
+            events,
+            rescaled: HashMap::new(),
+            activity,
+            idle_monitor: None,
+            retention_ledger: Arc::new(RetentionLedger::default()),
+            janitor: None,
+            gpu_lease,
+            libraries,
         })
     }
 }
 
+/// Sets, in the `configuration` of a node, the indexes of the GPUs it is assigned, if any.
+fn set_gpus(configuration: &mut Option<Configuration>, devices: Option<&[u32]>) {
+    let devices = match devices {
+        Some(devices) => devices,
+        None => return,
+    };
+    let configuration =
+        configuration.get_or_insert_with(|| Configuration::Object(Default::default()));
+    if let Some(object) = configuration.as_object_mut() {
+        object.insert(KEY_GPUS.to_string(), devices.iter().copied().collect());
+    }
+}
+
+/// Returns the [WarmUp] shared by the outputs of the node `node_id`.
+fn outputs_warmup(
+    io: &HashMap<NodeId, (Inputs, Outputs)>,
+    node_id: &NodeId,
+) -> Option<Arc<WarmUp>> {
+    io.get(node_id).map(|(_, outputs)| outputs.warmup.clone())
+}
+
+/// Creates the [Watchdog] of the node `node_id`, if it has one.
+fn node_watchdog(data_flow: &DataFlow, node_id: &NodeId) -> Option<Arc<Watchdog>> {
+    data_flow
+        .watchdogs
+        .get(node_id)
+        .map(|watchdog| Arc::new(Watchdog::new(*watchdog)))
+}
+
+/// Returns the [LinkQueue] of each link starting from an output of the node `node_id`.
+fn outputs_queues(
+    io: &HashMap<NodeId, (Inputs, Outputs)>,
+    node_id: &NodeId,
+) -> Vec<(PortId, Arc<LinkQueue>)> {
+    io.get(node_id)
+        .map(|(_, outputs)| {
+            outputs
+                .link_queues()
+                .map(|(port_id, queue)| (port_id.clone(), queue.clone()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 /// Creates the [`Link`](`Link`) between the `nodes` using `links`.
 ///
+/// The channel created for each link is also returned, as well as the [FlowControl] of each Source
+/// having `credits`: the channels of its links are then bounded.
+///
+/// A bounded link that is point-to-point --- the only local link of its output and the only local
+/// link leading to its input --- and has no queue uses a lock-free SPSC ring buffer.
+///
 /// # Errors
 /// An error variant is returned in case of:
 /// -  port id is duplicated.
 pub(crate) fn create_links(
     nodes: &[NodeId],
     links: &[LinkRecord],
+    credits: &HashMap<NodeId, usize>,
     hlc: Arc<HLC>,
-) -> Result<HashMap<NodeId, (Inputs, Outputs)>> {
+) -> Result<(
+    HashMap<NodeId, (Inputs, Outputs)>,
+    Vec<LinkChannel>,
+    HashMap<NodeId, Arc<FlowControl>>,
+)> {
     let mut io: HashMap<NodeId, (Inputs, Outputs)> = HashMap::with_capacity(nodes.len());
+    let mut channels = Vec::with_capacity(links.len());
+    let flow_controls: HashMap<NodeId, Arc<FlowControl>> = credits
+        .iter()
+        .filter(|(node_id, _)| nodes.contains(node_id))
+        .map(|(node_id, window)| (node_id.clone(), Arc::new(FlowControl::new(*window))))
+        .collect();
+
+    let is_local =
+        |link: &LinkRecord| nodes.contains(&link.from.node) && nodes.contains(&link.to.node);
+    let mut senders: HashMap<&OutputDescriptor, usize> = HashMap::new();
+    let mut receivers: HashMap<&InputDescriptor, usize> = HashMap::new();
+    for link_desc in links.iter().filter(|link| is_local(link)) {
+        *senders.entry(&link_desc.from).or_default() += 1;
+        *receivers.entry(&link_desc.to).or_default() += 1;
+    }
 
     for link_desc in links {
         let upstream_node = link_desc.from.node.clone();
@@ -308,35 +1925,84 @@ pub(crate) fn create_links(
             continue;
         }
 
-        // FIXME Introduce a user-configurable maximum capacity on the links. This also requires
-        // implementing a dropping policy.
-        let (tx, rx) = flume::unbounded();
-        let from = link_desc.from.output.clone();
-        let to = link_desc.to.input.clone();
+        let flow_control = flow_controls.get(&upstream_node);
+        let point_to_point = senders[&link_desc.from] == 1 && receivers[&link_desc.to] == 1;
+        channels.push(connect_link(
+            &mut io,
+            link_desc,
+            flow_control,
+            point_to_point,
+            &hlc,
+        ));
+    }
 
-        match io.get_mut(&upstream_node) {
-            Some((_, outputs)) => outputs.insert(from.clone(), tx),
-            None => {
-                let inputs = Inputs::new();
-                let mut outputs = Outputs::new(hlc.clone());
-                outputs.insert(from.clone(), tx);
+    Ok((io, channels, flow_controls))
+}
 
-                io.insert(upstream_node, (inputs, outputs));
-            }
+/// Creates the channel of the `link`, between two nodes running on the current daemon, and adds it
+/// to the [Inputs] and [Outputs] of the nodes in `io`.
+///
+/// The link is bounded by the window of the `flow_control` of its upstream Source, if any, or by
+/// its queue or capacity. A bounded `point_to_point` link without queue, holding at least 2
+/// messages, uses a SPSC ring buffer.
+fn connect_link(
+    io: &mut HashMap<NodeId, (Inputs, Outputs)>,
+    link_desc: &LinkRecord,
+    flow_control: Option<&Arc<FlowControl>>,
+    point_to_point: bool,
+    hlc: &Arc<HLC>,
+) -> LinkChannel {
+    // The links of a flow controlled Source, and the links with a capacity, block the upstream
+    // node when they are full, the links with a queue drop messages. The others are unbounded.
+    let capacity = match (flow_control, &link_desc.queue) {
+        (Some(flow_control), _) => Some(flow_control.window()),
+        (None, Some(queue)) => Some(queue.capacity),
+        (None, None) => link_desc.capacity,
+    };
+    let (tx, rx) = match capacity {
+        Some(capacity) if point_to_point && capacity >= 2 && link_desc.queue.is_none() => {
+            link::spsc(capacity)
         }
+        Some(capacity) => link::bounded(capacity),
+        None => link::unbounded(),
+    };
+    let queue = match (flow_control, &link_desc.queue) {
+        (None, Some(queue)) => Some(Arc::new(LinkQueue::new(
+            link_desc.to.clone(),
+            queue.overflow,
+            rx.clone(),
+        ))),
+        _ => None,
+    };
+    if let Some(flow_control) = flow_control {
+        flow_control.add_link(tx.clone());
+    }
+    let channel = LinkChannel {
+        from: link_desc.from.clone(),
+        to: link_desc.to.clone(),
+        tx: tx.clone(),
+        rx: rx.clone(),
+    };
+    let from = link_desc.from.output.clone();
+    let to = link_desc.to.input.clone();
 
-        match io.get_mut(&downstream_node) {
-            Some((inputs, _)) => inputs.insert(to.clone(), rx),
-            None => {
-                let outputs = Outputs::new(hlc.clone());
-
-                let mut inputs = Inputs::new();
-                inputs.insert(to.clone(), rx);
+    let (_, outputs) = io
+        .entry(link_desc.from.node.clone())
+        .or_insert_with(|| (Inputs::new(), Outputs::new(hlc.clone())));
+    let cache = outputs.insert(from, tx.clone(), queue);
 
-                io.insert(downstream_node, (inputs, outputs));
-            }
-        }
+    let (inputs, _) = io
+        .entry(link_desc.to.node.clone())
+        .or_insert_with(|| (Inputs::new(), Outputs::new(hlc.clone())));
+    inputs.insert(to.clone(), rx);
+    inputs.last_values.push((cache, tx));
+    if let Some(flow_control) = flow_control {
+        inputs
+            .flow_controls
+            .entry(to)
+            .or_default()
+            .push(flow_control.clone());
     }
 
-    Ok(io)
+    channel
 }
@@ -0,0 +1,313 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::zfresult::ErrorKind;
+use crate::{bail, zferror, Result};
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::SystemTime;
+use surf::http::Method;
+use url::Url;
+use zenoh::prelude::r#async::*;
+
+/// Where the recordings are stored, selected in the configuration of the daemon:
+///
+/// ```yaml
+/// recording_backend:
+///   kind: s3
+///   endpoint: http://localhost:9000
+///   bucket: recordings
+///   region: us-east-1
+///   access_key: minio
+///   secret_key: minio123
+/// ```
+///
+/// Recording is opt-in: with the default, `disabled`, backend, no output can be recorded.
+///
+/// Whatever the backend, the entries of a recording are stored under the same keys (see
+/// [`DataFlowInstance::start_recording`](super::DataFlowInstance::start_recording)). The recordings
+/// are replayed from Zenoh: the recordings stored in files or in an object storage can be served by
+/// a Zenoh storage using the corresponding backend.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum RecordingBackend {
+    /// The outputs cannot be recorded.
+    #[default]
+    Disabled,
+    /// The entries are published on Zenoh, a Zenoh storage being expected to store them.
+    Zenoh,
+    /// The entries are written in files, under `path`, the key of an entry being its relative path.
+    File { path: PathBuf },
+    /// The entries are uploaded, as objects, to an S3-compatible object storage. The requests are
+    /// path-style (`<endpoint>/<bucket>/<key>`) and signed with AWS Signature Version 4.
+    S3 {
+        endpoint: String,
+        bucket: String,
+        region: String,
+        access_key: String,
+        secret_key: String,
+    },
+}
+
+/// A `RecordSink` stores the entries of a recording: its messages and its metadata.
+#[async_trait]
+pub trait RecordSink: Send + Sync {
+    /// Stores the `value` of the entry `key`, a Zenoh key expression.
+    async fn put(&self, key: &str, value: Vec<u8>) -> Result<()>;
+
+    /// Deletes the entry `key`, e.g. when it exceeds the retention policy of the data flow.
+    async fn delete(&self, key: &str) -> Result<()>;
+}
+
+/// Returns the [RecordSink] of the `backend`, publishing on the `session` for the Zenoh backend.
+///
+/// # Errors
+///
+/// An error is returned if recording is disabled or if the endpoint of the S3 backend is not a
+/// valid URL.
+pub fn record_sink(
+    backend: &RecordingBackend,
+    session: Arc<Session>,
+) -> Result<Arc<dyn RecordSink>> {
+    Ok(match backend {
+        RecordingBackend::Disabled => bail!(
+            ErrorKind::Unsupported,
+            "Recording is disabled, a `recording_backend` must be set in the configuration of the daemon"
+        ),
+        RecordingBackend::Zenoh => Arc::new(ZenohRecordSink { session }),
+        RecordingBackend::File { path } => Arc::new(FileRecordSink { root: path.clone() }),
+        RecordingBackend::S3 {
+            endpoint,
+            bucket,
+            region,
+            access_key,
+            secret_key,
+        } => Arc::new(S3RecordSink {
+            client: surf::Client::new(),
+            endpoint: Url::parse(endpoint)
+                .map_err(|e| zferror!(ErrorKind::ConfigurationError, "{}: {}", endpoint, e))?,
+            bucket: bucket.clone(),
+            region: region.clone(),
+            access_key: access_key.clone(),
+            secret_key: secret_key.clone(),
+        }),
+    })
+}
+
+/// Publishes the entries on Zenoh.
+pub struct ZenohRecordSink {
+    session: Arc<Session>,
+}
+
+#[async_trait]
+impl RecordSink for ZenohRecordSink {
+    async fn put(&self, key: &str, value: Vec<u8>) -> Result<()> {
+        self.session.put(key, value).res().await?;
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.session.delete(key).res().await?;
+        Ok(())
+    }
+}
+
+/// Writes each entry in the file `<root>/<key>`.
+pub struct FileRecordSink {
+    root: PathBuf,
+}
+
+#[async_trait]
+impl RecordSink for FileRecordSink {
+    async fn put(&self, key: &str, value: Vec<u8>) -> Result<()> {
+        let path = self.root.join(key);
+        if let Some(parent) = path.parent() {
+            crate::executor::fs::create_dir_all(parent).await?;
+        }
+        crate::executor::fs::write(path, value).await?;
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        crate::executor::fs::remove_file(self.root.join(key)).await?;
+        Ok(())
+    }
+}
+
+/// Uploads each entry as the object `key` of the `bucket`.
+pub struct S3RecordSink {
+    client: surf::Client,
+    endpoint: Url,
+    bucket: String,
+    region: String,
+    access_key: String,
+    secret_key: String,
+}
+
+#[async_trait]
+impl RecordSink for S3RecordSink {
+    async fn put(&self, key: &str, value: Vec<u8>) -> Result<()> {
+        self.request(Method::Put, key, value).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.request(Method::Delete, key, Vec::new()).await
+    }
+}
+
+impl S3RecordSink {
+    /// Sends the signed request `method` on the object `key`, with the `body`.
+    async fn request(&self, method: Method, key: &str, body: Vec<u8>) -> Result<()> {
+        let path = format!("/{}/{}", uri_encode(&self.bucket), uri_encode(key));
+        let mut url = self.endpoint.clone();
+        url.set_path(&path);
+
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{host}:{port}"),
+            (Some(host), None) => host.to_string(),
+            (None, _) => bail!(
+                ErrorKind::ConfigurationError,
+                "No host in the S3 endpoint < {} >",
+                self.endpoint
+            ),
+        };
+        let amz_date = amz_date(SystemTime::now());
+        let payload_hash = hex(&Sha256::digest(&body));
+        let authorization = sign_v4(
+            &SigningRequest {
+                method: method.as_ref(),
+                path: &path,
+                host: &host,
+                amz_date: &amz_date,
+                payload_hash: &payload_hash,
+            },
+            &self.region,
+            &self.access_key,
+            &self.secret_key,
+        );
+
+        let response = self
+            .client
+            .request(method, url.as_str())
+            .header("host", host)
+            .header("x-amz-date", amz_date)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("authorization", authorization)
+            .body(surf::Body::from_bytes(body))
+            .await
+            .map_err(|e| zferror!(ErrorKind::IOError, "{} {}: {}", method, url, e))?;
+
+        if !response.status().is_success() {
+            bail!(
+                ErrorKind::IOError,
+                "{} {}: status {}",
+                method,
+                url,
+                response.status()
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// The parts of a request covered by its signature.
+pub(crate) struct SigningRequest<'a> {
+    pub(crate) method: &'a str,
+    /// The URI-encoded path.
+    pub(crate) path: &'a str,
+    pub(crate) host: &'a str,
+    /// The date of the request, formatted as `YYYYMMDD'T'HHMMSS'Z'`.
+    pub(crate) amz_date: &'a str,
+    /// The hexadecimal SHA-256 of the body.
+    pub(crate) payload_hash: &'a str,
+}
+
+/// Returns the `Authorization` header of the `request`, signed with AWS Signature Version 4.
+pub(crate) fn sign_v4(
+    request: &SigningRequest,
+    region: &str,
+    access_key: &str,
+    secret_key: &str,
+) -> String {
+    const SIGNED_HEADERS: &str = "host;x-amz-content-sha256;x-amz-date";
+
+    let canonical_request = format!(
+        "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+        request.method,
+        request.path,
+        request.host,
+        request.payload_hash,
+        request.amz_date,
+        SIGNED_HEADERS,
+        request.payload_hash
+    );
+
+    let date = &request.amz_date[..8];
+    let scope = format!("{date}/{region}/s3/aws4_request");
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        request.amz_date,
+        scope,
+        hex(&Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let key = hmac(format!("AWS4{secret_key}").as_bytes(), date.as_bytes());
+    let key = hmac(&key, region.as_bytes());
+    let key = hmac(&key, b"s3");
+    let key = hmac(&key, b"aws4_request");
+    let signature = hex(&hmac(&key, string_to_sign.as_bytes()));
+
+    format!(
+        "AWS4-HMAC-SHA256 Credential={access_key}/{scope}, SignedHeaders={SIGNED_HEADERS}, Signature={signature}"
+    )
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Formats the `time` as `YYYYMMDD'T'HHMMSS'Z'`.
+fn amz_date(time: SystemTime) -> String {
+    humantime::format_rfc3339_seconds(time)
+        .to_string()
+        .replace(['-', ':'], "")
+}
+
+/// Encodes the `path` as expected by AWS Signature Version 4: all the bytes but the unreserved
+/// characters and `/` are percent-encoded.
+pub(crate) fn uri_encode(path: &str) -> String {
+    path.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                (byte as char).to_string()
+            }
+            byte => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+#[path = "./tests/record-sink-tests.rs"]
+mod tests;
@@ -0,0 +1,1233 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::events::InstanceEventKind;
+use super::mcap;
+use super::record_sink::{self, RecordSink};
+use super::redaction::{redact, Redaction};
+use super::retention::RetainedSink;
+use super::runners::timers::TimerClock;
+use super::DataFlowInstance;
+use crate::executor::JoinHandle;
+use crate::io::link::LinkSender;
+use crate::runtime::resources::ROOT_STANDALONE;
+use crate::runtime::simulation::SimulationClock;
+use crate::types::{LinkMessage, NodeId, PortId, RecordingLabels, RecordingMetadata};
+use crate::zfresult::ErrorKind;
+use crate::{bail, zferror, Result, RECORDING_PATH};
+use flume::{Receiver, Sender};
+use futures::future::{self, Either};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use uhlc::Timestamp;
+use uuid::Uuid;
+use zenoh::prelude::r#async::*;
+
+/// Token, appended to the key expression of a recording, under which its metadata are stored.
+pub(crate) static KEY_METADATA: &str = "metadata";
+
+/// Token, appended to the key expression of a recording, under which its messages are stored.
+pub(crate) static KEY_DATA: &str = "data";
+
+/// The part of a recording to replay.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReplayRange {
+    /// All the messages of the recording.
+    #[default]
+    All,
+    /// The messages whose index, in the order they were recorded, is in the range.
+    Messages(Range<u64>),
+    /// The messages whose timestamp, measured from the timestamp of the first message of the
+    /// recording, is in the range.
+    Time(Range<Duration>),
+}
+
+/// The recordings of all the outputs of an instance started together, see
+/// [`DataFlowInstance::start_recording_all`](super::DataFlowInstance::start_recording_all).
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RecordingManifest {
+    /// The identifier of the recording session, shared by all the recordings.
+    pub session_id: Uuid,
+    /// When the recording session started.
+    pub timestamp: Timestamp,
+    pub recordings: Vec<RecordingMetadata>,
+}
+
+/// A `Recording` in progress, see [record].
+pub(crate) struct Recording {
+    pub(crate) stop: Sender<()>,
+    pub(crate) handle: JoinHandle<Result<RecordingMetadata>>,
+}
+
+impl Recording {
+    /// Stops the recording, returning its metadata once they are stored.
+    pub(crate) async fn stop(self) -> Result<RecordingMetadata> {
+        // The recording also stops if the channel is disconnected.
+        let _ = self.stop.send(());
+        self.handle.await
+    }
+}
+
+/// Stores in Zenoh, until `stop` receives a message or the output is dropped, the messages copied
+/// by a tap, returning the `metadata` of the recording.
+///
+/// Each message is stored, serialized with `bincode`, under `<key_expr>/data/<index>`, `index`
+/// being its position in the recording. The metadata are stored, as JSON, under
+/// `<key_expr>/metadata` when the recording starts and when it stops. The entries are stored by
+/// the `sink`: for the Zenoh backend, a Zenoh storage must be configured for these key expressions
+/// to keep the recording.
+///
+/// The messages are stored altered by the `redaction` rule, if any.
+pub(crate) async fn record(
+    sink: Arc<dyn RecordSink>,
+    receiver: Receiver<LinkMessage>,
+    stop: Receiver<()>,
+    mut metadata: RecordingMetadata,
+    redaction: Option<Redaction>,
+) -> Result<RecordingMetadata> {
+    store_metadata(sink.as_ref(), &metadata).await?;
+
+    let mut buffers = (Vec::default(), Vec::default());
+    while let Either::Left((Ok(message), _)) =
+        future::select(receiver.recv_async(), stop.recv_async()).await
+    {
+        let message = redact(redaction.as_ref(), message);
+        store_message(sink.as_ref(), &mut metadata, &message, &mut buffers).await;
+    }
+
+    store_metadata(sink.as_ref(), &metadata).await?;
+    log::debug!(
+        "[Recording: {}] Stopped after {} messages",
+        metadata.key_expr,
+        metadata.messages
+    );
+    Ok(metadata)
+}
+
+/// Stores the `message` under `<key_expr>/data/<index>`, indexing it in the `metadata` if it was
+/// stored. The `buffers` are reused for the serialization.
+async fn store_message(
+    sink: &dyn RecordSink,
+    metadata: &mut RecordingMetadata,
+    message: &LinkMessage,
+    (message_buffer, payload_buffer): &mut (Vec<u8>, Vec<u8>),
+) {
+    let key_expr = &metadata.key_expr;
+    if let Err(e) = message.serialize_bincode_into(message_buffer, payload_buffer) {
+        log::error!("[Recording: {key_expr}] Failed to serialize a message: {e:?}");
+        return;
+    }
+
+    let message_key_expr = format!("{key_expr}/{KEY_DATA}/{}", metadata.messages);
+    match sink.put(&message_key_expr, message_buffer.clone()).await {
+        Ok(()) => metadata.index(message),
+        Err(e) => log::error!("[Recording: {key_expr}] Failed to store a message: {e:?}"),
+    }
+}
+
+/// A `RingBuffer` keeps the messages of the last `window`, measured with their timestamps: when a
+/// message is pushed, the messages older than `window` with respect to it are dropped.
+pub(crate) struct RingBuffer {
+    window: Duration,
+    messages: VecDeque<LinkMessage>,
+}
+
+impl RingBuffer {
+    pub(crate) fn new(window: Duration) -> Self {
+        Self {
+            window,
+            messages: VecDeque::new(),
+        }
+    }
+
+    pub(crate) fn push(&mut self, message: LinkMessage) {
+        let newest = time(&message);
+        self.messages.push_back(message);
+        while let Some(oldest) = self.messages.front() {
+            if newest.saturating_sub(time(oldest)) <= self.window {
+                break;
+            }
+            self.messages.pop_front();
+        }
+    }
+
+    /// Removes and returns all the messages, oldest first.
+    pub(crate) fn drain(&mut self) -> Vec<LinkMessage> {
+        self.messages.drain(..).collect()
+    }
+}
+
+/// A request to commit the content of a ring buffer, see [buffer].
+pub(crate) struct Commit {
+    /// The metadata of the recording to create.
+    pub(crate) metadata: RecordingMetadata,
+    /// For how long the messages following the commit are added to the recording.
+    pub(crate) post: Duration,
+    pub(crate) reply: Sender<Result<RecordingMetadata>>,
+}
+
+/// A ring buffer recording in progress, see [buffer].
+pub(crate) struct Buffering {
+    pub(crate) commits: Sender<Commit>,
+    pub(crate) handle: JoinHandle<()>,
+}
+
+impl Buffering {
+    /// Stops buffering, discarding the messages that were not committed.
+    pub(crate) async fn stop(self) {
+        // Buffering stops when the channel is disconnected.
+        drop(self.commits);
+        self.handle.await
+    }
+}
+
+/// Keeps, in a [RingBuffer], the messages of the last `window` copied by a tap, until `commits` is
+/// disconnected or the output is dropped.
+///
+/// Upon a [Commit], the content of the ring buffer is stored as a recording (see [record]) to which
+/// the messages received during the `post` duration, measured with the clock of the daemon, are
+/// added. The ring buffer starts empty after a commit.
+///
+/// The messages are buffered, hence stored, altered by the `redaction` rule, if any.
+pub(crate) async fn buffer(
+    sink: Arc<dyn RecordSink>,
+    receiver: Receiver<LinkMessage>,
+    commits: Receiver<Commit>,
+    window: Duration,
+    redaction: Option<Redaction>,
+) {
+    let mut ring_buffer = RingBuffer::new(window);
+    loop {
+        match future::select(receiver.recv_async(), commits.recv_async()).await {
+            Either::Left((Ok(message), _)) => ring_buffer.push(redact(redaction.as_ref(), message)),
+            Either::Right((Ok(commit), _)) => {
+                let result = commit_ring_buffer(
+                    sink.as_ref(),
+                    &receiver,
+                    ring_buffer.drain(),
+                    commit.metadata,
+                    commit.post,
+                    redaction.as_ref(),
+                )
+                .await;
+                let _ = commit.reply.send(result);
+            }
+            _ => break,
+        }
+    }
+}
+
+async fn commit_ring_buffer(
+    sink: &dyn RecordSink,
+    receiver: &Receiver<LinkMessage>,
+    messages: Vec<LinkMessage>,
+    mut metadata: RecordingMetadata,
+    post: Duration,
+    redaction: Option<&Redaction>,
+) -> Result<RecordingMetadata> {
+    store_metadata(sink, &metadata).await?;
+
+    let mut buffers = (Vec::default(), Vec::default());
+    for message in messages {
+        store_message(sink, &mut metadata, &message, &mut buffers).await;
+    }
+
+    let deadline = Instant::now() + post;
+    while let Ok(Ok(message)) = crate::executor::timeout(
+        deadline.saturating_duration_since(Instant::now()),
+        receiver.recv_async(),
+    )
+    .await
+    {
+        let message = redact(redaction, message);
+        store_message(sink, &mut metadata, &message, &mut buffers).await;
+    }
+
+    store_metadata(sink, &metadata).await?;
+    log::debug!(
+        "[Recording: {}] Committed {} messages",
+        metadata.key_expr,
+        metadata.messages
+    );
+    Ok(metadata)
+}
+
+async fn store_metadata(sink: &dyn RecordSink, metadata: &RecordingMetadata) -> Result<()> {
+    let value =
+        serde_json::to_vec(metadata).map_err(|e| zferror!(ErrorKind::SerializationError, e))?;
+    sink.put(&format!("{}/{KEY_METADATA}", metadata.key_expr), value)
+        .await
+}
+
+/// Retrieves from Zenoh the metadata of the recording stored under `key_expr`.
+///
+/// # Errors
+///
+/// An error is returned if the query failed or if no metadata are stored under `key_expr`.
+pub(crate) async fn load_metadata(session: &Session, key_expr: &str) -> Result<RecordingMetadata> {
+    let replies = session
+        .get(&format!("{key_expr}/{KEY_METADATA}"))
+        .res()
+        .await?;
+
+    while let Ok(reply) = replies.recv_async().await {
+        if let Ok(sample) = reply.sample {
+            return serde_json::from_slice(&sample.payload.contiguous())
+                .map_err(|e| zferror!(ErrorKind::DeserializationError, e).into());
+        }
+    }
+
+    bail!(
+        ErrorKind::NotFound,
+        "No recording stored under < {} >",
+        key_expr
+    )
+}
+
+/// The criteria the recordings listed by [list_recordings] match: all the ones that are set.
+///
+/// A recording matches the `tags` if it was given all of them, with the same values (see
+/// [`RecordingLabels`](crate::types::RecordingLabels)); it may have others.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordingQuery {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance_id: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_name: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>,
+}
+
+impl RecordingQuery {
+    /// Returns `true` if the recording described by `metadata` matches the query.
+    pub fn matches(&self, metadata: &RecordingMetadata) -> bool {
+        self.instance_id
+            .map_or(true, |instance_id| instance_id == metadata.instance_id)
+            && self.session_name.as_ref().map_or(true, |name| {
+                metadata.labels.session_name.as_ref() == Some(name)
+            })
+            && self
+                .tags
+                .iter()
+                .all(|(key, value)| metadata.labels.tags.get(key) == Some(value))
+    }
+}
+
+/// Retrieves from Zenoh the metadata of the recordings matching the `query`, oldest first.
+///
+/// The metadata stored when a recording stops replace the ones stored when it started: the
+/// recordings in progress are listed with their initial metadata.
+///
+/// # Errors
+///
+/// An error is returned if the query failed.
+pub async fn list_recordings(
+    session: &Session,
+    query: &RecordingQuery,
+) -> Result<Vec<RecordingMetadata>> {
+    let instance_id = query
+        .instance_id
+        .map_or_else(|| "*".to_string(), |instance_id| instance_id.to_string());
+    let selector = format!(
+        "{}/{KEY_METADATA}",
+        RECORDING_PATH!(ROOT_STANDALONE, instance_id, "*", "*", "*")
+    );
+    let replies = session.get(&selector).res().await?;
+
+    let mut recordings = Vec::new();
+    while let Ok(reply) = replies.recv_async().await {
+        if let Ok(sample) = reply.sample {
+            match serde_json::from_slice::<RecordingMetadata>(&sample.payload.contiguous()) {
+                Ok(metadata) if query.matches(&metadata) => recordings.push(metadata),
+                Ok(_) => (),
+                Err(e) => log::warn!(
+                    "Ignoring the invalid metadata stored under < {} >: {e:?}",
+                    sample.key_expr
+                ),
+            }
+        }
+    }
+
+    recordings.sort_by_key(|metadata| metadata.timestamp);
+    Ok(recordings)
+}
+
+/// Retrieves from Zenoh the messages of the recording stored under `key_expr`, along with their
+/// index.
+///
+/// # Errors
+///
+/// An error is returned if the query failed.
+pub(crate) async fn load_messages(
+    session: &Session,
+    key_expr: &str,
+) -> Result<Vec<(u64, LinkMessage)>> {
+    let replies = session
+        .get(&format!("{key_expr}/{KEY_DATA}/*"))
+        .res()
+        .await?;
+
+    let mut messages = Vec::new();
+    while let Ok(reply) = replies.recv_async().await {
+        let sample = match reply.sample {
+            Ok(sample) => sample,
+            Err(e) => {
+                log::warn!("[Recording: {key_expr}] Error reply: {e:?}");
+                continue;
+            }
+        };
+
+        let index = match sample
+            .key_expr
+            .as_str()
+            .rsplit('/')
+            .next()
+            .and_then(|index| index.parse::<u64>().ok())
+        {
+            Some(index) => index,
+            None => {
+                log::warn!(
+                    "[Recording: {key_expr}] Unexpected key expression < {} >",
+                    sample.key_expr
+                );
+                continue;
+            }
+        };
+
+        let message: LinkMessage = bincode::deserialize(&sample.payload.contiguous())
+            .map_err(|e| zferror!(ErrorKind::DeserializationError, e))?;
+        messages.push((index, message));
+    }
+
+    Ok(messages)
+}
+
+/// Returns the `messages` of the `recordings` that are in the `range`, along with the index of
+/// the recording they belong to.
+///
+/// The messages of the different recordings are interleaved according to their timestamps. A
+/// time range is measured from the oldest first message of the recordings.
+pub(crate) fn select(
+    recordings: Vec<Vec<(u64, LinkMessage)>>,
+    range: &ReplayRange,
+) -> Vec<(usize, LinkMessage)> {
+    let recordings = recordings
+        .into_iter()
+        .map(|mut messages| {
+            messages.sort_by_key(|(index, _)| *index);
+            messages
+        })
+        .collect::<Vec<_>>();
+
+    let origin = recordings
+        .iter()
+        .filter_map(|messages| messages.first())
+        .map(|(_, message)| time(message))
+        .min()
+        .unwrap_or_default();
+
+    let mut selected = recordings
+        .into_iter()
+        .enumerate()
+        .flat_map(|(recording, messages)| {
+            messages
+                .into_iter()
+                .filter(|(index, message)| match range {
+                    ReplayRange::All => true,
+                    ReplayRange::Messages(range) => range.contains(index),
+                    ReplayRange::Time(range) => {
+                        range.contains(&time(message).saturating_sub(origin))
+                    }
+                })
+                .map(move |(_, message)| (recording, message))
+        })
+        .collect::<Vec<_>>();
+
+    // The sort is stable: the order of the messages of a recording sharing the same timestamp is
+    // kept.
+    selected.sort_by_key(|(_, message)| message.get_timestamp());
+    selected
+}
+
+fn time(message: &LinkMessage) -> Duration {
+    message.get_timestamp().get_time().to_duration()
+}
+
+/// A `Replay` in progress, see [replay].
+pub(crate) struct Replay {
+    pub(crate) outputs: Vec<(NodeId, PortId)>,
+    pub(crate) handle: JoinHandle<()>,
+}
+
+/// Sends each message on the `senders` of the recording it belongs to (see [select]), preserving
+/// the time elapsed between them.
+///
+/// The time is measured against the simulated time if a `simulation` clock is provided.
+pub(crate) async fn replay(
+    messages: Vec<(usize, LinkMessage)>,
+    senders: Vec<Vec<LinkSender>>,
+    simulation: Option<SimulationClock>,
+) {
+    let clock = TimerClock::new(simulation);
+    let start = clock.now();
+    let origin = match messages.first() {
+        Some((_, message)) => time(message),
+        None => return,
+    };
+
+    for (recording, message) in messages {
+        clock
+            .sleep_until(start + time(&message).saturating_sub(origin))
+            .await;
+
+        for sender in senders[recording].iter() {
+            if let Err(e) = sender.send_async(message.clone()).await {
+                log::error!("[Replay] Failed to send a message: {e:?}");
+            }
+        }
+    }
+}
+
+/// The result of the comparison of two recordings, see [compare_recordings].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordingComparison {
+    /// The number of data messages of the left recording.
+    pub left: u64,
+    /// The number of data messages of the right recording.
+    pub right: u64,
+    /// The number of data messages, present at the same position in both recordings, whose
+    /// payloads differ.
+    pub differences: u64,
+    /// The position of the first data message that differs or that is missing from one of the
+    /// recordings, if any.
+    pub first_difference: Option<u64>,
+}
+
+impl RecordingComparison {
+    /// Returns `true` if both recordings contain the same payloads, in the same order.
+    pub fn is_identical(&self) -> bool {
+        self.first_difference.is_none()
+    }
+}
+
+/// Compares the payloads of the data messages of the recordings stored under `left` and `right`.
+///
+/// Only the payloads are compared, in the order they were recorded: the timestamps and the
+/// watermarks of two executions of a data flow differ. This allows comparing the outputs of two
+/// variants of a data flow fed with the same recorded data (see
+/// [`DataFlowInstance::record_sinks`](super::DataFlowInstance::record_sinks) and
+/// [`DataFlowInstance::replay_sources`](super::DataFlowInstance::replay_sources)).
+///
+/// # Errors
+///
+/// An error is returned if a recording could not be retrieved.
+pub async fn compare_recordings(
+    session: &Session,
+    left: &str,
+    right: &str,
+) -> Result<RecordingComparison> {
+    compare(
+        load_messages(session, left).await?,
+        load_messages(session, right).await?,
+    )
+}
+
+pub(crate) fn compare(
+    left: Vec<(u64, LinkMessage)>,
+    right: Vec<(u64, LinkMessage)>,
+) -> Result<RecordingComparison> {
+    let payloads = |messages: Vec<(u64, LinkMessage)>| -> Result<Vec<Arc<Vec<u8>>>> {
+        select(vec![messages], &ReplayRange::All)
+            .into_iter()
+            .filter_map(|(_, message)| match message {
+                LinkMessage::Data(data) => Some(data.try_as_bytes()),
+                _ => None,
+            })
+            .collect()
+    };
+    let (left, right) = (payloads(left)?, payloads(right)?);
+
+    let mut comparison = RecordingComparison {
+        left: left.len() as u64,
+        right: right.len() as u64,
+        ..Default::default()
+    };
+    for (position, (left, right)) in left.iter().zip(right.iter()).enumerate() {
+        if left != right {
+            comparison.differences += 1;
+            comparison.first_difference.get_or_insert(position as u64);
+        }
+    }
+    if comparison.first_difference.is_none() && comparison.left != comparison.right {
+        comparison.first_difference = Some(comparison.left.min(comparison.right));
+    }
+
+    Ok(comparison)
+}
+
+impl DataFlowInstance {
+    /// Starts recording the output `port_id` of the node `node_id`: every message sent on that
+    /// output is stored in Zenoh under
+    /// `zenoh-flow/recording/<instance id>/<node id>/<port id>/<recording id>`. The key expression is
+    /// returned.
+    ///
+    /// Along with the messages, the [RecordingMetadata] of the recording are stored under
+    /// `<key expression>/metadata`: they index the messages (count and time range), allowing to
+    /// replay only part of the recording (see `replay`). A Zenoh storage must be configured for
+    /// these key expressions to keep the recording.
+    ///
+    /// The `labels`, the name of the recording session and the tags given to the recording, are
+    /// stored in its metadata: the recordings can then be listed by tags (see
+    /// [`list_recordings`](list_recordings)).
+    ///
+    /// As for a debug tap, the recording never slows down the data flow: if it cannot keep up,
+    /// messages are skipped. The payloads are stored altered by the redaction rule of the output, if
+    /// any.
+    ///
+    /// Recording is opt-in: a [`RecordingBackend`](record_sink::RecordingBackend) must be set in the
+    /// configuration of the daemon. Nothing is allocated for the recording of an output before it
+    /// starts.
+    ///
+    /// # Error
+    ///
+    /// This method can return an error if recording is disabled, if the node or the output are not
+    /// found on this daemon, if the output is already being recorded, if the transform of its
+    /// redaction rule could not be loaded or if the metadata could not be stored.
+    pub async fn start_recording(
+        &mut self,
+        node_id: &NodeId,
+        port_id: &PortId,
+        labels: RecordingLabels,
+    ) -> Result<String> {
+        let timestamp = self._instance_context.hlc.new_timestamp();
+        self.record(
+            node_id,
+            port_id,
+            uuid::Uuid::new_v4(),
+            None,
+            labels,
+            timestamp,
+        )
+        .map(|metadata| metadata.key_expr)
+    }
+
+    /// Starts recording the output `port_id` of the node `node_id` under the `recording_id`, with
+    /// the `labels`, returning the initial [RecordingMetadata] of the recording.
+    fn record(
+        &mut self,
+        node_id: &NodeId,
+        port_id: &PortId,
+        recording_id: uuid::Uuid,
+        session_id: Option<uuid::Uuid>,
+        labels: RecordingLabels,
+        timestamp: uhlc::Timestamp,
+    ) -> Result<RecordingMetadata> {
+        let output_tap = self.output_tap(node_id, port_id)?;
+        if self
+            .recordings
+            .contains_key(&(node_id.clone(), port_id.clone()))
+        {
+            bail!(
+                ErrorKind::AlreadyRecording,
+                "Output < {} > of Node < {} > is already being recorded",
+                port_id,
+                node_id
+            );
+        }
+        let redaction = self.redaction(node_id, port_id)?;
+
+        let metadata = RecordingMetadata {
+            session_id,
+            labels,
+            ..self.recording_metadata(node_id, port_id, recording_id, timestamp)
+        };
+        let key_expr = metadata.key_expr.clone();
+
+        let sink = self.record_sink()?;
+        let (stop, stop_rx) = flume::bounded(1);
+        let handle = crate::executor::spawn(record(
+            sink,
+            output_tap.attach(),
+            stop_rx,
+            metadata.clone(),
+            redaction,
+        ));
+        self.recordings.insert(
+            (node_id.clone(), port_id.clone()),
+            Recording { stop, handle },
+        );
+
+        log::info!("[Instance: {}] Recording on < {key_expr} >", self.uuid);
+        self.events.emit(
+            node_id,
+            InstanceEventKind::RecordingStarted {
+                port: port_id.clone(),
+                key_expr,
+            },
+        );
+        Ok(metadata)
+    }
+
+    /// Returns the initial [RecordingMetadata] of the recording `recording_id` of the output
+    /// `port_id` of the node `node_id`.
+    fn recording_metadata(
+        &self,
+        node_id: &NodeId,
+        port_id: &PortId,
+        recording_id: uuid::Uuid,
+        timestamp: uhlc::Timestamp,
+    ) -> RecordingMetadata {
+        RecordingMetadata {
+            timestamp,
+            port_id: port_id.clone(),
+            node_id: node_id.clone(),
+            flow_id: self.flow.clone(),
+            instance_id: self.uuid,
+            key_expr: RECORDING_PATH!(ROOT_STANDALONE, self.uuid, node_id, port_id, recording_id),
+            session_id: None,
+            labels: RecordingLabels::default(),
+            messages: 0,
+            data_messages: 0,
+            start: None,
+            end: None,
+        }
+    }
+
+    /// Stops recording the output `port_id` of the node `node_id`, returning the
+    /// [RecordingMetadata] of the recording.
+    ///
+    /// # Error
+    ///
+    /// This method can return an error if the output is not being recorded or if the metadata
+    /// could not be stored.
+    pub async fn stop_recording(
+        &mut self,
+        node_id: &NodeId,
+        port_id: &PortId,
+    ) -> Result<RecordingMetadata> {
+        match self.recordings.remove(&(node_id.clone(), port_id.clone())) {
+            Some(recording) => {
+                let metadata = recording.stop().await?;
+                self.recording_stopped(&metadata);
+                Ok(metadata)
+            }
+            None => bail!(
+                ErrorKind::NotRecording,
+                "Output < {} > of Node < {} > is not being recorded",
+                port_id,
+                node_id
+            ),
+        }
+    }
+
+    /// Emits the event of the end of the recording described by `metadata`.
+    pub(super) fn recording_stopped(&self, metadata: &RecordingMetadata) {
+        self.events.emit(
+            &metadata.node_id,
+            InstanceEventKind::RecordingStopped {
+                port: metadata.port_id.clone(),
+                key_expr: metadata.key_expr.clone(),
+            },
+        );
+    }
+
+    /// Starts recording all the outputs of the nodes running on the current daemon, returning the
+    /// [RecordingManifest] of the recording session.
+    ///
+    /// All the recordings share the identifier and the start time, taken from the HLC of the
+    /// instance, of the session: their key expressions end with the identifier of the session,
+    /// `zenoh-flow/recording/<instance id>/*/*/<session id>` hence matching all of them. The outputs
+    /// of the connectors, which are recorded by the daemons running the upstream nodes, and the
+    /// outputs already being recorded are skipped. The `labels` are given to all the recordings.
+    ///
+    /// # Error
+    ///
+    /// This method can return an error if recording is disabled, if a recording session is already
+    /// in progress or if the recording of an output could not be started, in which case the
+    /// recordings of the session already started are stopped.
+    pub async fn start_recording_all(
+        &mut self,
+        labels: RecordingLabels,
+    ) -> Result<RecordingManifest> {
+        if let Some(manifest) = &self.recording_session {
+            bail!(
+                ErrorKind::AlreadyRecording,
+                "[Instance: {}] The recording session {} is in progress",
+                self.uuid,
+                manifest.session_id
+            );
+        }
+
+        let mut outputs = self
+            .io
+            .iter()
+            .filter(|(node_id, _)| !self.connectors.contains_key(*node_id))
+            .flat_map(|(node_id, (_, outputs))| {
+                outputs
+                    .keys()
+                    .map(move |port_id| (node_id.clone(), port_id.clone()))
+            })
+            .filter(|output| !self.recordings.contains_key(output))
+            .collect::<Vec<_>>();
+        outputs.sort();
+
+        let mut manifest = RecordingManifest {
+            session_id: uuid::Uuid::new_v4(),
+            timestamp: self._instance_context.hlc.new_timestamp(),
+            recordings: Vec::with_capacity(outputs.len()),
+        };
+
+        for (node_id, port_id) in outputs {
+            match self.record(
+                &node_id,
+                &port_id,
+                manifest.session_id,
+                Some(manifest.session_id),
+                labels.clone(),
+                manifest.timestamp,
+            ) {
+                Ok(metadata) => manifest.recordings.push(metadata),
+                Err(e) => {
+                    for metadata in manifest.recordings.iter() {
+                        let _ = self
+                            .stop_recording(&metadata.node_id, &metadata.port_id)
+                            .await;
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        self.recording_session = Some(manifest.clone());
+        Ok(manifest)
+    }
+
+    /// Stops the recording session started by `start_recording_all`, returning its
+    /// [RecordingManifest] with the final [RecordingMetadata] of the recordings.
+    ///
+    /// The recordings of the session that were stopped individually keep their initial metadata.
+    ///
+    /// # Error
+    ///
+    /// This method can return an error if no recording session is in progress or if the metadata
+    /// of a recording could not be stored.
+    pub async fn stop_recording_all(&mut self) -> Result<RecordingManifest> {
+        let mut manifest = match self.recording_session.take() {
+            Some(manifest) => manifest,
+            None => bail!(
+                ErrorKind::NotRecording,
+                "[Instance: {}] No recording session in progress",
+                self.uuid
+            ),
+        };
+
+        let mut errors = Vec::new();
+        for metadata in manifest.recordings.iter_mut() {
+            let output = (metadata.node_id.clone(), metadata.port_id.clone());
+            if let Some(recording) = self.recordings.remove(&output) {
+                match recording.stop().await {
+                    Ok(stopped) => {
+                        self.recording_stopped(&stopped);
+                        *metadata = stopped;
+                    }
+                    Err(e) => errors.push(format!("< {}.{} >: {e}", output.0, output.1)),
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            bail!(
+                ErrorKind::GenericError,
+                "[Instance: {}] Failed to stop the recordings {}",
+                self.uuid,
+                errors.join(", ")
+            );
+        }
+
+        Ok(manifest)
+    }
+
+    /// Starts keeping, in memory, the messages sent during the last `window` on the output `port_id`
+    /// of the node `node_id`, the window being measured with the timestamps of the messages.
+    ///
+    /// Nothing is stored until `commit_buffer` is called, typically when an event of interest
+    /// occurs: the buffered messages, sent before the event, are then stored as a recording. The
+    /// messages are buffered altered by the redaction rule of the output, if any.
+    ///
+    /// # Error
+    ///
+    /// This method can return an error if recording is disabled, if the node or the output are not
+    /// found on this daemon, if the output is already being buffered or if the transform of its
+    /// redaction rule could not be loaded.
+    pub async fn start_buffering(
+        &mut self,
+        node_id: &NodeId,
+        port_id: &PortId,
+        window: Duration,
+    ) -> Result<()> {
+        let output_tap = self.output_tap(node_id, port_id)?;
+        if self
+            .buffers
+            .contains_key(&(node_id.clone(), port_id.clone()))
+        {
+            bail!(
+                ErrorKind::AlreadyRecording,
+                "Output < {} > of Node < {} > is already being buffered",
+                port_id,
+                node_id
+            );
+        }
+        let redaction = self.redaction(node_id, port_id)?;
+
+        let sink = self.record_sink()?;
+        let (commits, commits_rx) = flume::unbounded();
+        let handle = crate::executor::spawn(buffer(
+            sink,
+            output_tap.attach(),
+            commits_rx,
+            window,
+            redaction,
+        ));
+        self.buffers.insert(
+            (node_id.clone(), port_id.clone()),
+            Buffering { commits, handle },
+        );
+
+        Ok(())
+    }
+
+    /// Stores, as a recording, the messages buffered on the output `port_id` of the node `node_id`
+    /// (see `start_buffering`) followed by the messages sent during the `post` duration, returning
+    /// the [RecordingMetadata] of the recording once it is complete.
+    ///
+    /// The recording is stored under the same key expressions as the ones of `start_recording` and
+    /// can be replayed likewise. The output keeps being buffered, starting from an empty buffer.
+    ///
+    /// # Error
+    ///
+    /// This method can return an error if the output is not being buffered or if the recording
+    /// could not be stored.
+    pub async fn commit_buffer(
+        &mut self,
+        node_id: &NodeId,
+        port_id: &PortId,
+        post: Duration,
+    ) -> Result<RecordingMetadata> {
+        let commits = match self.buffers.get(&(node_id.clone(), port_id.clone())) {
+            Some(buffering) => buffering.commits.clone(),
+            None => bail!(
+                ErrorKind::NotRecording,
+                "Output < {} > of Node < {} > is not being buffered",
+                port_id,
+                node_id
+            ),
+        };
+
+        let metadata = self.recording_metadata(
+            node_id,
+            port_id,
+            uuid::Uuid::new_v4(),
+            self._instance_context.hlc.new_timestamp(),
+        );
+        let (reply, reply_rx) = flume::bounded(1);
+        commits
+            .send_async(Commit {
+                metadata,
+                post,
+                reply,
+            })
+            .await
+            .map_err(|e| zferror!(ErrorKind::SendError, "{}", e))?;
+
+        reply_rx
+            .recv_async()
+            .await
+            .map_err(|e| zferror!(ErrorKind::RecvError, "{}", e))?
+    }
+
+    /// Stops buffering the output `port_id` of the node `node_id`, discarding the messages that
+    /// were not committed.
+    ///
+    /// # Error
+    ///
+    /// This method can return an error if the output is not being buffered.
+    pub async fn stop_buffering(&mut self, node_id: &NodeId, port_id: &PortId) -> Result<()> {
+        match self.buffers.remove(&(node_id.clone(), port_id.clone())) {
+            Some(buffering) => {
+                buffering.stop().await;
+                Ok(())
+            }
+            None => bail!(
+                ErrorKind::NotRecording,
+                "Output < {} > of Node < {} > is not being buffered",
+                port_id,
+                node_id
+            ),
+        }
+    }
+
+    /// Replays, on the output `port_id` of the node `node_id`, the `range` of the recording stored
+    /// under `key_expr` (see `start_recording`), returning its [RecordingMetadata].
+    ///
+    /// The messages are sent on all the links starting from the output, with their original
+    /// timestamps and preserving the time elapsed between them. The node should be stopped
+    /// beforehand, otherwise the messages it produces are interleaved with the replayed ones.
+    /// Replaying on an output that is already being replayed stops the previous replay.
+    ///
+    /// # Error
+    ///
+    /// This method can return an error if the node or the output are not found on this daemon or
+    /// if the recording could not be retrieved.
+    pub async fn replay(
+        &mut self,
+        node_id: &NodeId,
+        port_id: &PortId,
+        key_expr: &str,
+        range: ReplayRange,
+    ) -> Result<RecordingMetadata> {
+        let mut metadata = self
+            .replay_synchronized(
+                &[(node_id.clone(), port_id.clone(), key_expr.to_string())],
+                range,
+            )
+            .await?;
+        Ok(metadata.remove(0))
+    }
+
+    /// Replays together several recordings, each given as `(node id, port id, key expression)`,
+    /// returning their [RecordingMetadata] in the same order.
+    ///
+    /// Contrary to calling `replay` for each of them, the messages of all the recordings are sent
+    /// by a single task, interleaved according to their original timestamps: the nodes consuming
+    /// several of the replayed outputs receive them in the order they were produced. A time
+    /// `range` is measured from the oldest first message of the recordings.
+    ///
+    /// Stopping the replay of one of the outputs (see `stop_replay`) stops the replay of all of
+    /// them.
+    ///
+    /// # Error
+    ///
+    /// This method can return an error if a node or an output are not found on this daemon or if a
+    /// recording could not be retrieved.
+    pub async fn replay_synchronized(
+        &mut self,
+        recordings: &[(NodeId, PortId, String)],
+        range: ReplayRange,
+    ) -> Result<Vec<RecordingMetadata>> {
+        let session = self.context.session.clone();
+        let mut metadata = Vec::with_capacity(recordings.len());
+        let mut messages = Vec::with_capacity(recordings.len());
+
+        for (node_id, port_id, key_expr) in recordings {
+            self.output_tap(node_id, port_id)?;
+            metadata.push(load_metadata(&session, key_expr).await?);
+            messages.push(load_messages(&session, key_expr).await?);
+        }
+
+        let outputs = recordings
+            .iter()
+            .map(|(node_id, port_id, _)| (node_id.clone(), port_id.clone()))
+            .collect();
+        self.spawn_replay(outputs, messages, &range).await;
+
+        Ok(metadata)
+    }
+
+    /// Replays the channels of an MCAP file (see [mcap]), read from `reader`, on the outputs of
+    /// the nodes: `topics` associates the topic of a channel to an output, given as
+    /// `(topic, node id, port id)`. The channels whose topic is not listed are ignored.
+    ///
+    /// The channels are replayed together, as with `replay_synchronized`, their payloads being
+    /// sent as is. The timestamps of the messages are derived from their log time.
+    ///
+    /// # Error
+    ///
+    /// This method can return an error if a node, an output or a topic are not found or if the
+    /// file could not be read.
+    pub async fn replay_mcap(
+        &mut self,
+        reader: impl std::io::Read,
+        topics: &[(String, NodeId, PortId)],
+        range: ReplayRange,
+    ) -> Result<()> {
+        let channels = mcap::read_mcap(reader)?;
+        let id = *self._instance_context.hlc.get_id();
+
+        let mut outputs = Vec::with_capacity(topics.len());
+        let mut messages = Vec::with_capacity(topics.len());
+        for (topic, node_id, port_id) in topics {
+            self.output_tap(node_id, port_id)?;
+            let channel = channels
+                .iter()
+                .find(|channel| &channel.topic == topic)
+                .ok_or_else(|| {
+                    zferror!(
+                        ErrorKind::NotFound,
+                        "No channel with the topic < {} > in the MCAP file",
+                        topic
+                    )
+                })?;
+            outputs.push((node_id.clone(), port_id.clone()));
+            messages.push(channel.link_messages(id));
+        }
+
+        self.spawn_replay(outputs, messages, &range).await;
+        Ok(())
+    }
+
+    /// Replays the `range` of the `messages` of each recording on the corresponding output of
+    /// `outputs`, stopping the replays these outputs were involved in.
+    async fn spawn_replay(
+        &mut self,
+        outputs: Vec<(NodeId, PortId)>,
+        messages: Vec<Vec<(u64, LinkMessage)>>,
+        range: &ReplayRange,
+    ) {
+        for (node_id, port_id) in outputs.iter() {
+            self.stop_replay(node_id, port_id).await;
+        }
+
+        let senders = outputs
+            .iter()
+            .map(|(node_id, port_id)| {
+                self.io
+                    .get(node_id)
+                    .and_then(|(_, outputs)| outputs.get(port_id).cloned())
+                    .unwrap_or_default()
+            })
+            .collect();
+        let messages = select(messages, range);
+
+        log::info!(
+            "[Instance: {}] Replaying {} messages on {:?}",
+            self.uuid,
+            messages.len(),
+            outputs
+        );
+        let handle = crate::executor::spawn(replay(
+            messages,
+            senders,
+            self._instance_context.simulation.clone(),
+        ));
+        self.replays.push(Replay { outputs, handle });
+    }
+
+    /// Stops the replay on the output `port_id` of the node `node_id`, if any.
+    ///
+    /// Returns `true` if a replay was stopped.
+    pub async fn stop_replay(&mut self, node_id: &NodeId, port_id: &PortId) -> bool {
+        let output = (node_id.clone(), port_id.clone());
+        match self
+            .replays
+            .iter()
+            .position(|replay| replay.outputs.contains(&output))
+        {
+            Some(index) => {
+                self.replays.swap_remove(index).handle.cancel().await;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Starts recording the outputs connected to the Sinks running on the current daemon,
+    /// returning the key expression of each recording (see `start_recording`).
+    ///
+    /// Along with `replay_sources`, this allows capturing what reaches the Sinks of two variants of
+    /// a data flow fed with the same data, e.g. to check that a new version of an operator does
+    /// not change the results. The recordings can then be compared with
+    /// [`compare_recordings`](compare_recordings).
+    ///
+    /// # Error
+    ///
+    /// This method can return an error if the recording of an output could not be started.
+    pub async fn record_sinks(&mut self) -> Result<Vec<String>> {
+        let mut outputs = self
+            .links
+            .iter()
+            .filter(|link| self.sink_constructors.contains_key(&link.to.node))
+            .map(|link| (link.from.node.clone(), link.from.output.clone()))
+            .collect::<Vec<_>>();
+        outputs.sort();
+        outputs.dedup();
+
+        let mut key_exprs = Vec::with_capacity(outputs.len());
+        for (node_id, port_id) in outputs {
+            key_exprs.push(
+                self.start_recording(&node_id, &port_id, RecordingLabels::default())
+                    .await?,
+            );
+        }
+
+        Ok(key_exprs)
+    }
+
+    /// Feeds this instance with `recordings` of the outputs of another instance, typically of a
+    /// variant of this data flow: each recording is replayed on the output having the same node
+    /// and port identifiers, that node being stopped beforehand.
+    ///
+    /// The recordings are replayed together, see `replay_synchronized`.
+    ///
+    /// # Error
+    ///
+    /// This method can return an error if a node or an output are not found on this daemon or if a
+    /// recording could not be retrieved.
+    pub async fn replay_sources(
+        &mut self,
+        recordings: &[RecordingMetadata],
+        range: ReplayRange,
+    ) -> Result<Vec<RecordingMetadata>> {
+        let recordings = recordings
+            .iter()
+            .map(|metadata| {
+                (
+                    metadata.node_id.clone(),
+                    metadata.port_id.clone(),
+                    metadata.key_expr.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        for (node_id, _, _) in recordings.iter() {
+            self.stop_node(node_id).await?;
+        }
+
+        self.replay_synchronized(&recordings, range).await
+    }
+
+    /// Returns the [RecordSink] of the recording backend of the daemon, keeping track of the entries
+    /// it stores if the data flow has a retention policy.
+    fn record_sink(&self) -> Result<Arc<dyn RecordSink>> {
+        let sink = record_sink::record_sink(
+            &self.context.recording_backend,
+            self.context.session.clone(),
+        )?;
+        if self.data_flow.retention.is_none() {
+            return Ok(sink);
+        }
+
+        Ok(Arc::new(RetainedSink {
+            sink,
+            ledger: self.retention_ledger.clone(),
+        }))
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/recording-tests.rs"]
+mod tests;
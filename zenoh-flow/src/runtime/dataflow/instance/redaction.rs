@@ -0,0 +1,117 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::model::descriptor::RedactionDescriptor;
+use crate::runtime::dataflow::loader::{Loader, TransformFn};
+use crate::types::{LinkMessage, Payload};
+use crate::Result;
+use serde_json::Value;
+use std::sync::Arc;
+
+#[cfg(target_family = "unix")]
+use libloading::os::unix::Library;
+#[cfg(target_family = "windows")]
+use libloading::Library;
+
+/// The value replacing the masked fields of a payload.
+pub(crate) const MASK: &str = "<redacted>";
+
+/// A redaction rule, with its transform loaded.
+///
+/// The `library` of the transform is kept loaded as long as the rule is used. It is `None` when
+/// the transform is created programmatically.
+#[derive(Clone)]
+pub(crate) struct Redaction {
+    pub(crate) rule: RedactionDescriptor,
+    transform: Option<(TransformFn, Option<Arc<Library>>)>,
+}
+
+impl Redaction {
+    /// Loads the transform of the `rule`, if any, with the `loader`.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the transform could not be loaded.
+    pub(crate) fn load(rule: RedactionDescriptor, loader: &Loader) -> Result<Self> {
+        let transform = match &rule.transform {
+            Some(uri) => {
+                let (library, transform) = loader.load_transform(uri)?;
+                Some((transform, Some(Arc::new(library))))
+            }
+            None => None,
+        };
+
+        Ok(Self { rule, transform })
+    }
+}
+
+/// Returns the `message` altered by the `redaction`, if any (see [RedactionDescriptor]).
+///
+/// Only the payload of a data message is altered, its timestamps are kept. A payload sent by
+/// reference is kept as is: only its key expression leaves the data flow.
+pub(crate) fn redact(redaction: Option<&Redaction>, message: LinkMessage) -> LinkMessage {
+    let (redaction, data_message) = match (redaction, &message) {
+        (Some(redaction), LinkMessage::Data(data_message)) => (redaction, data_message),
+        _ => return message,
+    };
+    let rule = &redaction.rule;
+
+    let mut bytes = match &**data_message {
+        Payload::Reference(_) => return message,
+        payload => match payload.try_as_bytes() {
+            Ok(bytes) => bytes.to_vec(),
+            Err(e) => {
+                log::warn!("Failed to serialize a payload to redact it, it is removed: {e:?}");
+                Vec::new()
+            }
+        },
+    };
+
+    if let Some((transform, _)) = &redaction.transform {
+        bytes = transform(&bytes).unwrap_or_else(|e| {
+            log::warn!("Failed to transform a payload to redact it, it is removed: {e:?}");
+            Vec::new()
+        });
+    }
+
+    if !rule.mask.is_empty() {
+        bytes = match serde_json::from_slice::<Value>(&bytes) {
+            Ok(mut value) => {
+                for pointer in &rule.mask {
+                    if let Some(field) = value.pointer_mut(pointer) {
+                        *field = Value::String(MASK.into());
+                    }
+                }
+                serde_json::to_vec(&value).unwrap_or_default()
+            }
+            // The fields cannot be located: the payload as a whole is sensitive.
+            Err(_) => Vec::new(),
+        };
+    }
+
+    if let Some(max_bytes) = rule.max_bytes {
+        bytes.truncate(max_bytes);
+    }
+
+    LinkMessage::from_payload_with_event_time(
+        bytes.into(),
+        *data_message.get_timestamp(),
+        data_message.get_event_time().copied(),
+    )
+    .with_variant(data_message.get_variant())
+}
+
+#[cfg(test)]
+#[path = "./tests/redaction-tests.rs"]
+mod tests;
@@ -0,0 +1,570 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::control::NodeControl;
+use super::debugger::NodeDebugger;
+use super::runners::catch_panic;
+use super::{connect_link, node_watchdog, DataFlowInstance, DEFAULT_QUIESCENCE_TIMEOUT};
+use crate::io::output::WarmUp;
+use crate::io::{Inputs, Outputs};
+use crate::model::descriptor::dataflow::{replica_id, replica_of};
+use crate::model::descriptor::{InputDescriptor, OutputDescriptor};
+use crate::model::record::LinkRecord;
+use crate::prelude::Context;
+use crate::runtime::resources::ROOT_STANDALONE;
+use crate::types::{NodeId, PortId};
+use crate::zfresult::ErrorKind;
+use crate::Result;
+use crate::{bail, DEBUG_PATH};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Changes the number of copies of the replicated Operator `node_id` of the `instance` to
+/// `replicas`, see [`DataFlowInstance::rescale`].
+pub(super) async fn rescale(
+    instance: &mut DataFlowInstance,
+    node_id: &NodeId,
+    replicas: usize,
+) -> Result<()> {
+    let current = match instance.data_flow.replicas.get(node_id) {
+        Some(current) => *current,
+        None => bail!(
+            ErrorKind::InvalidData,
+            "[Instance: {}] < {} > is not a replicated Operator",
+            instance.uuid,
+            node_id
+        ),
+    };
+    if replicas == 0 {
+        bail!(
+            ErrorKind::InvalidData,
+            "[Instance: {}] < {} > cannot have 0 replicas",
+            instance.uuid,
+            node_id
+        )
+    }
+    if replicas == current {
+        return Ok(());
+    }
+
+    let copies = (0..current.max(replicas))
+        .map(|index| replica_id(node_id, index))
+        .collect::<Vec<_>>();
+    let first = copies[0].clone();
+    let (removed, added) = if replicas < current {
+        (&copies[replicas..], &copies[..0])
+    } else {
+        (&copies[..0], &copies[current..])
+    };
+    let is_copy = |id: &NodeId| copies.contains(id);
+
+    if let Some(copy) = copies[..current]
+        .iter()
+        .find(|copy| !instance.data_flow.operator_constructors.contains_key(*copy))
+    {
+        bail!(
+            ErrorKind::InvalidState,
+            "[Instance: {}] Cannot rescale < {} >: its copy < {} > is not an Operator running on this daemon",
+            instance.uuid,
+            node_id,
+            copy
+        )
+    }
+    if instance.data_flow.gpus.contains_key(&first)
+        || instance
+            .data_flow
+            .exposed
+            .iter()
+            .any(|output| is_copy(&output.node))
+        || instance
+            .data_flow
+            .imported
+            .iter()
+            .any(|input| is_copy(&input.node))
+    {
+        bail!(
+            ErrorKind::InvalidState,
+            "[Instance: {}] Cannot rescale < {} >: its copies are assigned GPUs, expose outputs or import inputs",
+            instance.uuid,
+            node_id
+        )
+    }
+
+    let mut counter = instance.data_flow.counter;
+    let links = rescale_links(
+        &instance.data_flow.links,
+        node_id,
+        current,
+        replicas,
+        &mut counter,
+    );
+
+    // The nodes sending messages to the copies and the ones receiving their messages, before
+    // and after the rescaling.
+    let mut upstream: Vec<NodeId> = Vec::new();
+    let mut downstream: Vec<NodeId> = Vec::new();
+    for link in instance.data_flow.links.iter().chain(links.iter()) {
+        if is_copy(&link.to.node)
+            && !is_copy(&link.from.node)
+            && !upstream.contains(&link.from.node)
+        {
+            upstream.push(link.from.node.clone());
+        }
+        if is_copy(&link.from.node)
+            && !is_copy(&link.to.node)
+            && !downstream.contains(&link.to.node)
+        {
+            downstream.push(link.to.node.clone());
+        }
+    }
+
+    // The nodes inserted on the links of a copy (e.g. to downsample them) and the copies of
+    // another replicated node are specific to a copy: they cannot be shared by the new ones.
+    if let Some(neighbour) = upstream.iter().chain(downstream.iter()).find(|id| {
+        !instance.runners.contains_key(*id)
+            || instance.data_flow.connectors.contains_key(*id)
+            || id.contains(&*first)
+            || replica_of(&instance.data_flow.replicas, id).is_some()
+    }) {
+        bail!(
+            ErrorKind::InvalidState,
+            "[Instance: {}] Cannot rescale < {} >: its copies are connected to < {} >, which does not run on this daemon or is specific to a copy",
+            instance.uuid,
+            node_id,
+            neighbour
+        )
+    }
+
+    // The Sinks count the inputs that received an `EndOfStream`: their inputs cannot change.
+    let inputs_of = |links: &[LinkRecord], sink_id: &NodeId| {
+        let mut inputs = links
+            .iter()
+            .filter(|link| link.to.node == *sink_id)
+            .map(|link| link.to.input.clone())
+            .collect::<Vec<_>>();
+        inputs.sort();
+        inputs.dedup();
+        inputs
+    };
+    if let Some(sink_id) = downstream.iter().find(|id| {
+        instance.data_flow.sink_constructors.contains_key(*id)
+            && inputs_of(&instance.data_flow.links, id) != inputs_of(&links, id)
+    }) {
+        bail!(
+            ErrorKind::InvalidState,
+            "[Instance: {}] Cannot rescale < {} >: the inputs of the Sink < {} > would change",
+            instance.uuid,
+            node_id,
+            sink_id
+        )
+    }
+
+    let mut involved = upstream.clone();
+    for id in copies[..current].iter().chain(downstream.iter()) {
+        if !involved.contains(id) {
+            involved.push(id.clone());
+        }
+    }
+    let running = involved
+        .iter()
+        .filter(|id| {
+            instance
+                .runners
+                .get(*id)
+                .map_or(false, |runner| runner.is_running())
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+
+    // Savepoint.
+    let mut errors = Vec::new();
+    for id in upstream.iter() {
+        instance.stop_runner(id, &mut errors).await;
+    }
+    if !instance
+        .wait_drained(
+            |channel| is_copy(&channel.to.node),
+            DEFAULT_QUIESCENCE_TIMEOUT,
+        )
+        .await
+    {
+        log::warn!(
+            "[Instance: {}] The copies of < {} > did not process their messages after {:?}",
+            instance.uuid,
+            node_id,
+            DEFAULT_QUIESCENCE_TIMEOUT
+        );
+    }
+    for id in copies[..current].iter() {
+        instance.stop_runner(id, &mut errors).await;
+    }
+    if !instance
+        .wait_drained(
+            |channel| is_copy(&channel.from.node),
+            DEFAULT_QUIESCENCE_TIMEOUT,
+        )
+        .await
+    {
+        log::warn!(
+            "[Instance: {}] The messages of the copies of < {} > were not processed after {:?}",
+            instance.uuid,
+            node_id,
+            DEFAULT_QUIESCENCE_TIMEOUT
+        );
+    }
+    for id in downstream.iter() {
+        instance.stop_runner(id, &mut errors).await;
+    }
+    if !errors.is_empty() {
+        bail!(
+            ErrorKind::RunnerStopError,
+            "[Instance: {}] Encountered {} error(s) while stopping: {}",
+            instance.uuid,
+            errors.len(),
+            errors.join(", ")
+        )
+    }
+
+    let mut states = HashMap::new();
+    for id in involved.iter().filter(|id| !removed.contains(*id)) {
+        if let Some(runner) = instance.runners.get(id) {
+            if let Some(state) = catch_panic(id, runner.node.checkpoint()).await? {
+                states.insert(id.clone(), state);
+            }
+        }
+    }
+    let mut partitions = HashMap::new();
+    for copy in copies[..current].iter() {
+        if let Some(keyed_state) = instance.keyed_states.get(copy) {
+            partitions.extend(keyed_state.checkpoint()?);
+        }
+    }
+
+    let context = Context::new(&instance._instance_context);
+    for id in involved.iter() {
+        if let Some(cooldown) = instance.cooldowns.get(id) {
+            crate::executor::sleep(*cooldown).await;
+        }
+        if let Some(runner) = instance.runners.get(id) {
+            if let Err(e) = catch_panic(id, runner.node.clean(&context)).await {
+                log::warn!("Failed to clean < {id} > before rescaling < {node_id} >: {e:?}");
+            }
+        }
+        if let Some(debugger) = instance.debuggers.get(id) {
+            debugger.reset();
+        }
+    }
+
+    // Rewiring: the channels of the links that no longer exist are removed, the ones of the new
+    // links are created.
+    let (obsolete, channels): (Vec<_>, Vec<_>) = std::mem::take(&mut instance.channels)
+        .into_iter()
+        .partition(|channel| {
+            !links
+                .iter()
+                .any(|link| link.from == channel.from && link.to == channel.to)
+        });
+    instance.channels = channels;
+    for channel in obsolete {
+        let lost = channel.rx.drain().len();
+        if lost > 0 {
+            log::warn!(
+                "[Instance: {}] {} message(s) lost on {} => {}",
+                instance.uuid,
+                lost,
+                channel.from,
+                channel.to
+            );
+        }
+        if let Some((_, outputs)) = instance.io.get_mut(&channel.from.node) {
+            outputs.remove(&channel.from.output, &channel.tx);
+        }
+        if let Some((inputs, _)) = instance.io.get_mut(&channel.to.node) {
+            inputs.remove(&channel.to.input, &channel.tx, &channel.rx);
+        }
+        if let Some(flow_control) = instance.flow_controls.get(&channel.from.node) {
+            flow_control.remove_link(&channel.tx);
+        }
+    }
+
+    for copy in removed {
+        let taps = instance
+            .taps
+            .keys()
+            .filter(|(id, _)| id == copy)
+            .cloned()
+            .collect::<Vec<_>>();
+        for (id, port_id) in taps {
+            instance.untap(&id, &port_id).await;
+        }
+        let recordings = instance
+            .recordings
+            .keys()
+            .filter(|(id, _)| id == copy)
+            .cloned()
+            .collect::<Vec<_>>();
+        for (id, port_id) in recordings {
+            if let Err(e) = instance.stop_recording(&id, &port_id).await {
+                log::warn!("Failed to stop the recording of < {id}.{port_id} >: {e:?}");
+            }
+        }
+
+        instance.runners.remove(copy);
+        instance.io.remove(copy);
+        instance.debuggers.remove(copy);
+        instance.controls.remove(copy);
+        instance.keyed_states.remove(copy);
+        instance.data_flow.operator_constructors.remove(copy);
+        instance.data_flow.max_run_durations.remove(copy);
+        instance.data_flow.warmups.remove(copy);
+        instance.data_flow.cooldowns.remove(copy);
+        instance.data_flow.watchdogs.remove(copy);
+        instance.data_flow.nodes.retain(|node| node.id != *copy);
+    }
+
+    for copy in added {
+        let mut record = instance.data_flow.operator_constructors[&first]
+            .record
+            .clone();
+        record.id = copy.clone();
+        record.uid = counter;
+        counter += 1;
+        let constructor = instance.data_flow.operator_constructors[&first].with_record(record);
+        instance
+            .data_flow
+            .operator_constructors
+            .insert(copy.clone(), constructor);
+        if let Some(max_run_duration) = instance.data_flow.max_run_durations.get(&first).copied() {
+            instance
+                .data_flow
+                .max_run_durations
+                .insert(copy.clone(), max_run_duration);
+        }
+        if let Some(warmup) = instance.data_flow.warmups.get(&first).cloned() {
+            instance.data_flow.warmups.insert(copy.clone(), warmup);
+        }
+        if let Some(cooldown) = instance.data_flow.cooldowns.get(&first).copied() {
+            instance.data_flow.cooldowns.insert(copy.clone(), cooldown);
+        }
+        if let Some(watchdog) = instance.data_flow.watchdogs.get(&first).cloned() {
+            instance.data_flow.watchdogs.insert(copy.clone(), watchdog);
+        }
+        if let Some(mut node) = instance
+            .data_flow
+            .nodes
+            .iter()
+            .find(|node| node.id == first)
+            .cloned()
+        {
+            node.id = copy.clone();
+            instance.data_flow.nodes.push(node);
+        }
+    }
+
+    let hlc = instance._instance_context.hlc.clone();
+    let local = |id: &NodeId| instance.runners.contains_key(id) || added.contains(id);
+    let mut senders: HashMap<&OutputDescriptor, usize> = HashMap::new();
+    let mut receivers: HashMap<&InputDescriptor, usize> = HashMap::new();
+    for link in links
+        .iter()
+        .filter(|link| local(&link.from.node) && local(&link.to.node))
+    {
+        *senders.entry(&link.from).or_default() += 1;
+        *receivers.entry(&link.to).or_default() += 1;
+    }
+    let new_links = links
+        .iter()
+        .filter(|link| {
+            !instance
+                .data_flow
+                .links
+                .iter()
+                .any(|existing| existing.from == link.from && existing.to == link.to)
+        })
+        .collect::<Vec<_>>();
+    for link in new_links {
+        let point_to_point = senders[&link.from] == 1 && receivers[&link.to] == 1;
+        let channel = connect_link(
+            &mut instance.io,
+            link,
+            instance.flow_controls.get(&link.from.node),
+            point_to_point,
+            &hlc,
+        );
+        instance.channels.push(channel);
+    }
+
+    // The new copies are wired as the other nodes when the instance is created.
+    for copy in added {
+        let (inputs, outputs) = instance
+            .io
+            .entry(copy.clone())
+            .or_insert_with(|| (Inputs::new(), Outputs::new(hlc.clone())));
+        inputs.session = Some(instance.data_flow.context.session.clone());
+        inputs.hlc = Some(hlc.clone());
+        inputs.policies = instance.data_flow.operator_constructors[copy]
+            .input_policies
+            .clone();
+        if !inputs.is_empty() {
+            let debugger = Arc::new(NodeDebugger::new(
+                copy.clone(),
+                DEBUG_PATH!(ROOT_STANDALONE, instance.data_flow.uuid, copy),
+                Some(instance.data_flow.context.session.clone()),
+            ));
+            inputs.debugger = Some(debugger.clone());
+            instance.debuggers.insert(copy.clone(), debugger);
+        }
+        outputs.activity = instance.activity.clone();
+        if let Some(warmup) = instance.data_flow.warmups.get(copy) {
+            outputs.warmup = Arc::new(WarmUp::new(*warmup));
+        }
+        let control = NodeControl::new(copy.clone(), outputs, instance.checkpoints.clone());
+        instance.controls.insert(copy.clone(), Arc::new(control));
+    }
+
+    instance.data_flow.links = links;
+    instance.data_flow.counter = counter;
+    instance
+        .data_flow
+        .replicas
+        .insert(node_id.clone(), replicas);
+
+    // Restoration: the copies own new partitions, and all the nodes involved are created again.
+    for copy in copies[..replicas].iter() {
+        let keyed_state = instance.data_flow.keyed_state(copy);
+        keyed_state.restore(&partitions)?;
+        instance.keyed_states.insert(copy.clone(), keyed_state);
+    }
+
+    let mut recreated = upstream;
+    for id in copies[..replicas].iter().chain(downstream.iter()) {
+        if !recreated.contains(id) {
+            recreated.push(id.clone());
+        }
+    }
+    for id in recreated.iter() {
+        let watchdog = match instance.runners.get(id) {
+            Some(runner) => runner.watchdog.clone(),
+            None => node_watchdog(&instance.data_flow, id),
+        };
+        let runner = instance.create_runner(id, watchdog).await?;
+        if let Some(state) = states.get(id) {
+            catch_panic(id, runner.node.restore(state)).await?;
+        }
+        instance.runners.insert(id.clone(), runner);
+    }
+
+    let copies_running = running.contains(&first);
+    for id in recreated.iter() {
+        if running.contains(id) || (copies_running && added.contains(id)) {
+            instance.start_node(id)?;
+        }
+    }
+
+    instance.rescaled.insert(node_id.clone(), Instant::now());
+    log::info!(
+        "[Instance: {}] Rescaled < {} > from {} to {} replicas",
+        instance.uuid,
+        node_id,
+        current,
+        replicas
+    );
+    Ok(())
+}
+
+/// Returns the `links` of a data flow once the replicated node `node_id` has `replicas` copies
+/// instead of `current`.
+///
+/// The links of the copies that remain are kept as is and the links of the removed copies are
+/// dropped. The links of each new copy mirror the links of the first copy: they connect the same
+/// nodes, the ports generated with the `{replica}` placeholder being indexed with the new copy. A
+/// port of a link of the first copy is deemed generated with the placeholder if it ends with `0`
+/// and, when there are other copies, if the links of the second copy have the same port ending with
+/// `1`.
+///
+/// The new links are numbered from `counter`, which is incremented accordingly.
+pub(crate) fn rescale_links(
+    links: &[LinkRecord],
+    node_id: &NodeId,
+    current: usize,
+    replicas: usize,
+    counter: &mut u32,
+) -> Vec<LinkRecord> {
+    let removed = (replicas..current)
+        .map(|index| replica_id(node_id, index))
+        .collect::<Vec<_>>();
+    let mut rescaled = links
+        .iter()
+        .filter(|link| !removed.contains(&link.from.node) && !removed.contains(&link.to.node))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let first = replica_id(node_id, 0);
+    let second = replica_id(node_id, 1);
+    let (second_outputs, second_inputs): (Vec<_>, Vec<_>) = links
+        .iter()
+        .filter(|link| link.from.node == second || link.to.node == second)
+        .map(|link| (link.from.output.as_ref(), link.to.input.as_ref()))
+        .unzip();
+    // The port without its index, if it was generated with the placeholder.
+    let prefix = |port: &PortId, ports_of_second: &[&str]| {
+        port.strip_suffix('0')
+            .filter(|prefix| {
+                current == 1 || ports_of_second.contains(&format!("{prefix}1").as_str())
+            })
+            .map(String::from)
+    };
+    let reindex = |port: &PortId, prefix: &Option<String>, index: usize| -> PortId {
+        match prefix {
+            Some(prefix) => format!("{prefix}{index}").into(),
+            None => port.clone(),
+        }
+    };
+
+    let templates = links
+        .iter()
+        .filter(|link| link.from.node == first || link.to.node == first)
+        .map(|link| {
+            (
+                link,
+                prefix(&link.from.output, &second_outputs),
+                prefix(&link.to.input, &second_inputs),
+            )
+        })
+        .collect::<Vec<_>>();
+    for index in current..replicas {
+        let copy = replica_id(node_id, index);
+        for (template, output_prefix, input_prefix) in templates.iter() {
+            let mut link = (*template).clone();
+            link.uid = *counter;
+            *counter += 1;
+            if link.from.node == first {
+                link.from.node = copy.clone();
+            }
+            if link.to.node == first {
+                link.to.node = copy.clone();
+            }
+            link.from.output = reindex(&template.from.output, output_prefix, index);
+            link.to.input = reindex(&template.to.input, input_prefix, index);
+            rescaled.push(link);
+        }
+    }
+
+    rescaled
+}
+
+#[cfg(test)]
+#[path = "./tests/rescale-tests.rs"]
+mod tests;
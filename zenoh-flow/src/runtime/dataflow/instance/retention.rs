@@ -0,0 +1,180 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::record_sink::RecordSink;
+use crate::executor::JoinHandle;
+use crate::model::descriptor::RetentionDescriptor;
+use crate::runtime::resources::DataStore;
+use crate::Result;
+use async_trait::async_trait;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use uuid::Uuid;
+use zenoh::Session;
+
+/// Bounds of the interval at which the janitor enforces the retention policy: a quarter of its
+/// `max_age`, between 1s and 1min.
+static MIN_INTERVAL: Duration = Duration::from_secs(1);
+static MAX_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The `RetentionLedger` keeps track of the recording entries stored by an instance, oldest first,
+/// such that the ones exceeding its retention policy can be deleted.
+///
+/// An entry stored again (e.g. the metadata of a recording, stored when it starts and when it
+/// stops) counts as stored at that time, with its new size.
+#[derive(Debug, Default)]
+pub(crate) struct RetentionLedger {
+    entries: Mutex<Entries>,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    /// The entries in the order they were stored. An entry stored again appears several times:
+    /// only its occurrence matching `latest` is valid.
+    queue: VecDeque<(String, Instant)>,
+    /// The size and the time of the latest storage of each entry.
+    latest: HashMap<String, (usize, Instant)>,
+    size: usize,
+}
+
+impl RetentionLedger {
+    /// Records that the entry `key`, of `size` bytes, was stored at `now`.
+    pub(crate) fn stored(&self, key: &str, size: usize, now: Instant) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((previous_size, _)) = entries.latest.insert(key.to_string(), (size, now)) {
+            entries.size -= previous_size;
+        }
+        entries.size += size;
+        entries.queue.push_back((key.to_string(), now));
+    }
+
+    /// Returns the total size, in bytes, of the entries.
+    pub(crate) fn size(&self) -> usize {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).size
+    }
+
+    /// Removes and returns the entries exceeding the `policy` at `now`, oldest first: the entries
+    /// older than `max_age` and, while their total size exceeds `max_size`, the oldest ones.
+    pub(crate) fn expire(&self, policy: &RetentionDescriptor, now: Instant) -> Vec<String> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let mut expired = Vec::new();
+
+        while let Some((key, stored)) = entries.queue.front() {
+            let (size, latest) = match entries.latest.get(key) {
+                Some(&(size, latest)) if latest == *stored => (size, latest),
+                // The entry was stored again, or deleted, since.
+                _ => {
+                    entries.queue.pop_front();
+                    continue;
+                }
+            };
+
+            let too_old = policy.max_age.map_or(false, |max_age| {
+                now.saturating_duration_since(latest) >= max_age
+            });
+            let too_large = policy
+                .max_size
+                .map_or(false, |max_size| entries.size > max_size);
+            if !too_old && !too_large {
+                break;
+            }
+
+            if let Some((key, _)) = entries.queue.pop_front() {
+                entries.latest.remove(&key);
+                entries.size -= size;
+                expired.push(key);
+            }
+        }
+
+        expired
+    }
+}
+
+/// A [RecordSink] recording in the [RetentionLedger] of the instance the entries it stores.
+pub(crate) struct RetainedSink {
+    pub(crate) sink: Arc<dyn RecordSink>,
+    pub(crate) ledger: Arc<RetentionLedger>,
+}
+
+#[async_trait]
+impl RecordSink for RetainedSink {
+    async fn put(&self, key: &str, value: Vec<u8>) -> Result<()> {
+        let size = value.len();
+        self.sink.put(key, value).await?;
+        self.ledger.stored(key, size, Instant::now());
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.sink.delete(key).await
+    }
+}
+
+/// Spawns the janitor enforcing the retention `policy` of the instance `instance_id` on the
+/// runtime `runtime_uuid`: it regularly deletes, with the `sink`, the recording entries of the
+/// `ledger` that exceed the policy and, from the event log, the events of the instance older than
+/// its `max_age`.
+pub(crate) fn spawn_janitor(
+    policy: RetentionDescriptor,
+    ledger: Arc<RetentionLedger>,
+    sink: Option<Arc<dyn RecordSink>>,
+    session: Arc<Session>,
+    instance_id: Uuid,
+    runtime_uuid: Uuid,
+) -> JoinHandle<()> {
+    let interval = policy
+        .max_age
+        .map_or(MAX_INTERVAL, |max_age| max_age / 4)
+        .clamp(MIN_INTERVAL, MAX_INTERVAL);
+    let store = DataStore::new(session);
+
+    crate::executor::spawn(async move {
+        loop {
+            crate::executor::sleep(interval).await;
+
+            if let Some(sink) = &sink {
+                for key in ledger.expire(&policy, Instant::now()) {
+                    if let Err(e) = sink.delete(&key).await {
+                        log::warn!("[Instance: {instance_id}] Failed to delete < {key} >: {e:?}");
+                    }
+                }
+            }
+
+            if let Some(before) = policy
+                .max_age
+                .and_then(|max_age| SystemTime::now().checked_sub(max_age))
+            {
+                match store
+                    .remove_events(&runtime_uuid, &instance_id, before)
+                    .await
+                {
+                    Ok(0) => (),
+                    Ok(removed) => {
+                        log::debug!("[Instance: {instance_id}] Removed {removed} expired events")
+                    }
+                    Err(e) => {
+                        log::warn!(
+                            "[Instance: {instance_id}] Failed to remove expired events: {e:?}"
+                        )
+                    }
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+#[path = "./tests/retention-tests.rs"]
+mod tests;
@@ -12,25 +12,536 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+use super::frame::{decompress, Frame};
+use super::spool::Spool;
+use crate::executor::JoinHandle;
 use crate::io::{Inputs, Outputs};
+use crate::model::descriptor::PortType;
 use crate::model::record::ZFConnectorRecord;
 use crate::prelude::{InputRaw, OutputRaw};
 use crate::runtime::InstanceContext;
 use crate::traits::Node;
-use crate::types::{LinkMessage, NodeId};
-use crate::zferror;
+use crate::types::{
+    Context, ControlMarker, InlineBytes, LinkMessage, NodeId, Payload, PayloadReference,
+    PoolStatistics, SerializerPool,
+};
 use crate::zfresult::ErrorKind;
 use crate::Result as ZFResult;
-use async_std::sync::Mutex;
+use crate::{bail, zferror};
+use async_lock::Mutex;
 use async_trait::async_trait;
 use flume::Receiver;
-use std::sync::atomic::AtomicU64;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::convert::Infallible;
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use zenoh::buffers::SharedMemoryManager;
+use std::time::{Duration, Instant};
+use uhlc::Timestamp;
+use zenoh::buffers::{SharedMemoryManager, ZBuf};
 use zenoh::prelude::r#async::*;
 use zenoh::subscriber::Subscriber;
+use zenoh_flow_core::{fragments, unfragment, FRAGMENT_HEADER_SIZE, SEQUENCE_SIZE};
 use zenoh_util::core::AsyncResolve;
 
+/// The delay after which a [ZenohSender] buffering messages during an outage tries to send them
+/// again, when it has no new message to send.
+const OUTAGE_RETRY_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The chunk appended to the resource of a connector on which the retransmissions are queried, as
+/// `<resource>/retransmit/<first>/<end>`.
+const RETRANSMIT: &str = "retransmit";
+
+/// The delay after which a [ZenohReceiver] discards the fragments of a message it did not receive
+/// entirely.
+const REASSEMBLY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Splits a frame published by a [ZenohSender] into its sequence number and its message.
+///
+/// # Errors
+///
+/// An error is returned if the frame is too short or if the message could not be deserialized.
+pub(crate) fn unframe(frame: &[u8], traffic: &LinkTraffic) -> ZFResult<(u64, LinkMessage)> {
+    let (sequence, message) = zenoh_flow_core::unframe(frame).map_err(|e| {
+        zferror!(
+            ErrorKind::DeserializationError,
+            "Received an invalid frame of {} bytes: {}",
+            frame.len(),
+            e
+        )
+    })?;
+
+    Ok((sequence, deserialize(message, traffic)?))
+}
+
+/// Returns the encoding of the messages published by a [ZenohSender] whose output sends data of
+/// type `data_type`: the data type is its suffix, for the receivers to check it.
+pub(crate) fn data_encoding(data_type: Option<&PortType>) -> Encoding {
+    match data_type {
+        Some(data_type) => Encoding::APP_OCTET_STREAM.with_suffix(data_type.to_string()),
+        None => Encoding::APP_OCTET_STREAM,
+    }
+}
+
+/// Checks that the data of a message published with the `encoding` can be received by an input
+/// expecting the data type `expected`. A message, or an input, without data type is accepted.
+///
+/// The components on both sides of a link can be updated separately: the data types declared in
+/// the record of a connector may no longer match what its peer sends.
+///
+/// # Errors
+///
+/// An error is returned if the data types are not compatible (see [PortType::is_compatible_with]).
+pub(crate) fn check_data_type(expected: Option<&PortType>, encoding: &Encoding) -> ZFResult<()> {
+    let expected = match expected {
+        Some(expected) => expected,
+        None => return Ok(()),
+    };
+    if encoding.suffix().is_empty() {
+        return Ok(());
+    }
+
+    let received = encoding.suffix().parse::<PortType>()?;
+    if !received.is_compatible_with(expected) {
+        bail!(
+            ErrorKind::InvalidData,
+            "Received data of type < {} > where < {} > is expected",
+            received,
+            expected
+        );
+    }
+
+    Ok(())
+}
+
+/// A [LinkMessage] published by a [ZenohSender], decoded without copying its payload: its variants
+/// and fields mirror the ones of a `LinkMessage`.
+#[derive(Deserialize)]
+enum WireMessage<'a> {
+    #[serde(borrow)]
+    Data(WireData<'a>),
+    Watermark(Timestamp),
+    EndOfStream(Timestamp),
+    Live(Timestamp),
+    Control(ControlMarker),
+}
+
+#[derive(Deserialize)]
+struct WireData<'a> {
+    #[serde(borrow)]
+    data: WirePayload<'a>,
+    timestamp: Timestamp,
+    #[serde(default)]
+    event_time: Option<Timestamp>,
+    #[serde(default)]
+    variant: Option<u32>,
+}
+
+#[derive(Deserialize)]
+enum WirePayload<'a> {
+    Bytes(&'a [u8]),
+    Reference(PayloadReference),
+}
+
+/// Deserializes a message published by a [ZenohSender].
+///
+/// A payload of at most [INLINE_CAPACITY](crate::types::INLINE_CAPACITY) bytes is stored inline,
+/// a larger one is copied in a buffer of the [SerializerPool] of the `traffic`: once its first
+/// messages were received, a receiver no longer allocates per message.
+///
+/// A message compressed by the sender is first decompressed.
+fn deserialize(message: &[u8], traffic: &LinkTraffic) -> ZFResult<LinkMessage> {
+    let decompressed = decompress(message)?;
+    let message = bincode::deserialize::<WireMessage>(decompressed.as_deref().unwrap_or(message))
+        .map_err(|e| zferror!(ErrorKind::DeserializationError, e))?;
+
+    Ok(match message {
+        WireMessage::Data(WireData {
+            data,
+            timestamp,
+            event_time,
+            variant,
+        }) => {
+            let payload = match data {
+                WirePayload::Bytes(bytes) => traffic.payload(bytes),
+                WirePayload::Reference(reference) => Payload::Reference(reference),
+            };
+            LinkMessage::from_payload_with_event_time(payload, timestamp, event_time)
+                .with_variant(variant)
+        }
+        WireMessage::Watermark(timestamp) => LinkMessage::Watermark(timestamp),
+        WireMessage::EndOfStream(timestamp) => LinkMessage::EndOfStream(timestamp),
+        WireMessage::Live(timestamp) => LinkMessage::Live(timestamp),
+        WireMessage::Control(marker) => LinkMessage::Control(marker),
+    })
+}
+
+/// Splits the `frame` of a message into fragments of at most `fragment_size` bytes.
+fn split(frame: &[u8], fragment_size: usize) -> ZFResult<Vec<Vec<u8>>> {
+    zenoh_flow_core::unframe(frame)
+        .and_then(|(sequence, message)| fragments(sequence, message, fragment_size))
+        .map_err(|e| {
+            zferror!(
+                ErrorKind::SerializationError,
+                "Unable to split a message of {} bytes into fragments of {fragment_size} bytes: {e}",
+                frame.len()
+            )
+            .into()
+        })
+}
+
+/// The fragments received of a message.
+struct PartialMessage {
+    count: u32,
+    chunks: HashMap<u32, Vec<u8>>,
+    started: Instant,
+}
+
+/// The `Reassembly` gathers the fragments received by a [ZenohReceiver], on a link that sets a
+/// `fragment_size`, until the messages they belong to are complete.
+///
+/// The messages that are not complete within the `timeout` are discarded: their sequence number is
+/// then reported as lost.
+pub(crate) struct Reassembly {
+    timeout: Duration,
+    partial: HashMap<u64, PartialMessage>,
+}
+
+impl Reassembly {
+    pub(crate) fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            partial: HashMap::default(),
+        }
+    }
+
+    /// Adds the `fragment`, returning the sequence number and the message it completed, if any.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the fragment is not valid or if the message could not be
+    /// deserialized.
+    pub(crate) fn push(
+        &mut self,
+        fragment: &[u8],
+        traffic: &LinkTraffic,
+    ) -> ZFResult<Option<(u64, LinkMessage)>> {
+        let fragment = unfragment(fragment).map_err(|e| {
+            zferror!(
+                ErrorKind::DeserializationError,
+                "Received an invalid fragment of {} bytes: {}",
+                fragment.len(),
+                e
+            )
+        })?;
+
+        let timeout = self.timeout;
+        self.partial.retain(|sequence, partial| {
+            let expired = partial.started.elapsed() > timeout;
+            if expired {
+                log::warn!(
+                    "Message {sequence} discarded: {} fragment(s) out of {} received in {timeout:?}",
+                    partial.chunks.len(),
+                    partial.count
+                );
+            }
+            !expired
+        });
+
+        if fragment.count == 1 {
+            return Ok(Some((
+                fragment.sequence,
+                deserialize(fragment.chunk, traffic)?,
+            )));
+        }
+
+        let partial = self
+            .partial
+            .entry(fragment.sequence)
+            .or_insert_with(|| PartialMessage {
+                count: fragment.count,
+                chunks: HashMap::default(),
+                started: Instant::now(),
+            });
+        // The sender restarted and numbers its messages from 0 again.
+        if partial.count != fragment.count {
+            *partial = PartialMessage {
+                count: fragment.count,
+                chunks: HashMap::default(),
+                started: Instant::now(),
+            };
+        }
+        partial
+            .chunks
+            .insert(fragment.index, fragment.chunk.to_vec());
+
+        if partial.chunks.len() < partial.count as usize {
+            return Ok(None);
+        }
+
+        let partial = self.partial.remove(&fragment.sequence).ok_or_else(|| {
+            zferror!(
+                ErrorKind::InvalidState,
+                "Message {} not found",
+                fragment.sequence
+            )
+        })?;
+        let mut message = Vec::with_capacity(partial.chunks.values().map(Vec::len).sum());
+        for index in 0..partial.count {
+            if let Some(chunk) = partial.chunks.get(&index) {
+                message.extend_from_slice(chunk);
+            }
+        }
+
+        Ok(Some((fragment.sequence, deserialize(&message, traffic)?)))
+    }
+}
+
+/// The `LinkSequence` tracks the sequence numbers of the messages received by a [ZenohReceiver] to
+/// detect the messages that were lost.
+///
+/// The messages are numbered, per sender, from 0. A sequence number lower than the expected one
+/// means that the sender was restarted: the tracking starts over from it.
+#[derive(Debug)]
+pub(crate) struct LinkSequence {
+    expected: std::sync::Mutex<Option<u64>>,
+    missed: AtomicU64,
+    recovered: AtomicU64,
+}
+
+impl Default for LinkSequence {
+    fn default() -> Self {
+        Self {
+            expected: std::sync::Mutex::new(Some(0)),
+            missed: AtomicU64::new(0),
+            recovered: AtomicU64::new(0),
+        }
+    }
+}
+
+impl LinkSequence {
+    /// Forgets the expected sequence number: the next one received is accepted as is.
+    ///
+    /// This is needed when the receiver is restarted, as the sender kept on numbering its messages.
+    pub(crate) fn reset(&self) {
+        *self.expected.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    /// Registers the reception of the message `sequence`, returning the range of the sequence
+    /// numbers that were skipped, if any.
+    pub(crate) fn check(&self, sequence: u64) -> Option<Range<u64>> {
+        let mut expected = self.expected.lock().unwrap_or_else(|e| e.into_inner());
+        let gap = match *expected {
+            Some(expected) if sequence > expected => Some(expected..sequence),
+            Some(expected) if sequence < expected => {
+                log::debug!("Sequence number {sequence} received, {expected} expected: the sender restarted");
+                None
+            }
+            _ => None,
+        };
+        *expected = Some(sequence + 1);
+
+        gap
+    }
+
+    /// Records that, out of the `missing` messages of a gap, `recovered` were retransmitted.
+    pub(crate) fn record_gap(&self, missing: u64, recovered: u64) {
+        self.missed
+            .fetch_add(missing.saturating_sub(recovered), Ordering::Relaxed);
+        self.recovered.fetch_add(recovered, Ordering::Relaxed);
+    }
+
+    /// Returns the number of messages that were lost and could not be retransmitted.
+    pub(crate) fn missed(&self) -> u64 {
+        self.missed.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of messages that were lost and then retransmitted.
+    pub(crate) fn recovered(&self) -> u64 {
+        self.recovered.load(Ordering::Relaxed)
+    }
+}
+
+/// The number of messages, and of bytes, that went through a connector.
+///
+/// The bytes are the ones of the payloads exchanged on Zenoh: they include the sequence numbers,
+/// the headers of the fragments and the retransmissions.
+///
+/// The allocations of the connector are reported as well:
+/// - `inlined` counts the payloads received small enough to be stored inline (see
+///   [InlineBytes]),
+/// - `pool` tells how often the buffers in which the payloads are serialized (by a sender) or
+///   copied (by a receiver) were reused.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Traffic {
+    pub messages: u64,
+    pub bytes: u64,
+    #[serde(default)]
+    pub inlined: u64,
+    #[serde(default)]
+    pub pool: PoolStatistics,
+}
+
+impl std::ops::AddAssign for Traffic {
+    fn add_assign(&mut self, other: Self) {
+        self.messages += other.messages;
+        self.bytes += other.bytes;
+        self.inlined += other.inlined;
+        self.pool += other.pool;
+    }
+}
+
+/// The `LinkTraffic` counts the messages, and the bytes, published by a [ZenohSender] or received
+/// by a [ZenohReceiver] (see [Traffic]).
+///
+/// It also holds the [SerializerPool] of the connector.
+///
+/// It is kept by the instance: the counters, and the buffers of the pool, survive the restart of
+/// the connector.
+#[derive(Debug, Default)]
+pub(crate) struct LinkTraffic {
+    messages: AtomicU64,
+    bytes: AtomicU64,
+    inlined: AtomicU64,
+    pool: SerializerPool,
+}
+
+impl LinkTraffic {
+    /// Records `bytes` exchanged on Zenoh, a fragment or a retransmission, that are not a message
+    /// on their own.
+    pub(crate) fn record_bytes(&self, bytes: usize) {
+        self.bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Records a message, complete, exchanged on Zenoh.
+    pub(crate) fn record_message(&self) {
+        self.messages.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the pool in which the payloads are serialized, or copied, by the connector.
+    pub(crate) fn pool(&self) -> &SerializerPool {
+        &self.pool
+    }
+
+    /// Returns the received `bytes` as a [Payload]: stored inline if they are small enough,
+    /// copied in a buffer of the pool otherwise.
+    pub(crate) fn payload(&self, bytes: &[u8]) -> Payload {
+        if let Some(inline) = InlineBytes::new(bytes) {
+            self.inlined.fetch_add(1, Ordering::Relaxed);
+            return Payload::Inline(inline);
+        }
+
+        let copied = self.pool.serialize(|buffer| -> Result<(), Infallible> {
+            buffer.extend_from_slice(bytes);
+            Ok(())
+        });
+        match copied {
+            Ok(bytes) => Payload::Bytes(bytes),
+            Err(e) => match e {},
+        }
+    }
+
+    /// Returns the traffic recorded so far.
+    pub(crate) fn get(&self) -> Traffic {
+        Traffic {
+            messages: self.messages.load(Ordering::Relaxed),
+            bytes: self.bytes.load(Ordering::Relaxed),
+            inlined: self.inlined.load(Ordering::Relaxed),
+            pool: self.pool.statistics(),
+        }
+    }
+}
+
+/// The last messages published by a [ZenohSender] (framed) along with their sequence number, kept
+/// to be retransmitted.
+type History = Arc<std::sync::Mutex<VecDeque<(u64, Frame)>>>;
+
+/// The retransmissions served by a [ZenohSender]: the `capacity` last messages it published are
+/// kept in its `history`, the `server` task replies to the requests of the receivers.
+pub(crate) struct Retransmission {
+    capacity: usize,
+    history: History,
+    server: Mutex<Option<JoinHandle<()>>>,
+}
+
+/// Serves the retransmissions requested on `<resource>/retransmit/<first>/<end>`: the messages of
+/// the `history` whose sequence number is in `[first, end)` are sent back, in fragments of at most
+/// `fragment_size` bytes if it is set. The bytes sent back are added to the `traffic`.
+async fn serve_retransmissions(
+    session: Arc<zenoh::Session>,
+    resource: &str,
+    history: History,
+    fragment_size: Option<usize>,
+    traffic: Arc<LinkTraffic>,
+) -> ZFResult<JoinHandle<()>> {
+    let queryable = session
+        .declare_queryable(format!("{resource}/{RETRANSMIT}/**"))
+        .res()
+        .await?;
+
+    Ok(crate::executor::spawn(async move {
+        while let Ok(query) = queryable.recv_async().await {
+            let range = match parse_retransmission(query.key_expr().as_str()) {
+                Some(range) => range,
+                None => {
+                    log::warn!(
+                        "[ZenohSender] Malformed retransmission request: {}",
+                        query.key_expr()
+                    );
+                    continue;
+                }
+            };
+
+            let frames = history
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .iter()
+                .filter(|(sequence, _)| range.contains(sequence))
+                .map(|(_, frame)| frame.clone())
+                .collect::<Vec<_>>();
+            let frames: Vec<(usize, ZBuf)> = match fragment_size {
+                Some(fragment_size) => frames
+                    .iter()
+                    .filter_map(|frame| match split(&frame.to_vec(), fragment_size) {
+                        Ok(fragments) => Some(fragments),
+                        Err(e) => {
+                            log::error!("[ZenohSender] {e:?}");
+                            None
+                        }
+                    })
+                    .flatten()
+                    .map(|fragment| (fragment.len(), fragment.into()))
+                    .collect(),
+                None => frames
+                    .into_iter()
+                    .map(|frame| (frame.len(), frame.into_zbuf()))
+                    .collect(),
+            };
+
+            for (bytes, frame) in frames {
+                match query
+                    .reply(Ok(Sample::new(query.key_expr().clone(), frame)))
+                    .res()
+                    .await
+                {
+                    Ok(()) => traffic.record_bytes(bytes),
+                    Err(e) => log::error!("[ZenohSender] Failed to retransmit a message: {e:?}"),
+                }
+            }
+        }
+    }))
+}
+
+/// Parses the range of sequence numbers `[first, end)` of a retransmission request
+/// `<resource>/retransmit/<first>/<end>`.
+fn parse_retransmission(key_expr: &str) -> Option<Range<u64>> {
+    let mut chunks = key_expr.rsplit('/');
+    let end = chunks.next()?.parse().ok()?;
+    let first = chunks.next()?.parse().ok()?;
+    (chunks.next()? == RETRANSMIT).then_some(first..end)
+}
+
 /// The `ZenohSender` is the connector that sends the data to Zenoh when nodes are running on
 /// different runtimes.
 pub(crate) struct ZenohSender {
@@ -41,20 +552,41 @@ pub(crate) struct ZenohSender {
     pub(crate) state: Arc<Mutex<ZenohSenderState>>,
     pub(crate) shm_element_size: usize,
     pub(crate) shm_backoff: u64,
+    pub(crate) retransmission: Option<Retransmission>,
+    pub(crate) outage_budget: Option<usize>,
+    pub(crate) buffers_outages: bool,
+    pub(crate) fragment_size: Option<usize>,
+    pub(crate) compression: Option<usize>,
+    pub(crate) encoding: Encoding,
+    pub(crate) traffic: Arc<LinkTraffic>,
 }
 
 /// The `ZenohSenderState` stores in a single structure all the fields protected by a lock.
 ///
 /// The fields are:
 /// - `shm` holds the [SharedMemoryManager] used to send data through Zenoh's shared memory;
-/// - `message_buffer` holds a growable vector of bytes in which the result of the serialization of
-///   the [LinkMessage] is stored.
 /// - `payload_buffer` holds a growable vector of bytes in which the result of the serialization of
-///   the [Payload] contained inside the [LinkMessage] is stored.
+///   the [Payload] contained inside the [LinkMessage] is stored, when it is sent through shared
+///   memory.
+/// - `sequence` holds the sequence number of the next message to publish.
+/// - `pending` holds the messages (framed) buffered during an outage and `pending_bytes` their
+///   total size.
+/// - `spool` holds, if the link spools them, the messages (framed) written to disk during an
+///   outage instead of being buffered in `pending`.
 pub(crate) struct ZenohSenderState {
     pub(crate) shm: Option<SharedMemoryManager>,
-    pub(crate) message_buffer: Vec<u8>,
     pub(crate) payload_buffer: Vec<u8>,
+    pub(crate) sequence: u64,
+    pub(crate) pending: VecDeque<Frame>,
+    pub(crate) pending_bytes: usize,
+    pub(crate) spool: Option<Spool>,
+}
+
+impl ZenohSenderState {
+    /// Returns `true` if messages are waiting for the end of an outage, in memory or on disk.
+    fn is_buffering(&self) -> bool {
+        !self.pending.is_empty() || self.spool.as_ref().map_or(false, |spool| !spool.is_empty())
+    }
 }
 
 impl ZenohSender {
@@ -67,11 +599,14 @@ impl ZenohSender {
     ///
     /// An error variant is returned if:
     /// - no link was created for this sender,
-    /// - the declaration of the key expression failed.
+    /// - the Zenoh session of the connector was not opened,
+    /// - the declaration of the key expression failed,
+    /// - the spool of the link could not be created.
     pub(crate) async fn new(
         record: &ZFConnectorRecord,
         ctx: Arc<InstanceContext>,
         mut inputs: Inputs,
+        traffic: Arc<LinkTraffic>,
     ) -> ZFResult<Self> {
         let receivers = inputs.hmap.remove(&record.link_id.port_id).ok_or_else(|| {
             zferror!(
@@ -82,9 +617,20 @@ impl ZenohSender {
             )
         })?;
 
-        let key_expr = ctx
-            .runtime
-            .session
+        if let Some(fragment_size) = record.fragment_size {
+            if fragment_size <= FRAGMENT_HEADER_SIZE {
+                bail!(
+                    ErrorKind::ConfigurationError,
+                    "The fragments of Connector < {} > must be larger than {} bytes, got {}",
+                    record.id,
+                    FRAGMENT_HEADER_SIZE,
+                    fragment_size
+                );
+            }
+        }
+
+        let session = ctx.session(record.session.as_deref())?;
+        let key_expr = session
             .declare_keyexpr(record.resource.clone())
             .res()
             .await?
@@ -94,7 +640,9 @@ impl ZenohSender {
         let mut shm_backoff = 0;
         let mut shm_manager = None;
 
-        if ctx.runtime.use_shm {
+        // A message in shared memory is published as a whole: the links that fragment them do not
+        // use it.
+        if ctx.runtime.use_shm && record.fragment_size.is_none() {
             let shm_size = record
                 .shared_memory_element_size
                 .unwrap_or(ctx.runtime.shared_memory_element_size)
@@ -119,29 +667,282 @@ impl ZenohSender {
                 .unwrap_or(ctx.runtime.shared_memory_element_size);
         }
 
+        let retransmission = match record.retransmission {
+            Some(capacity) if capacity > 0 => {
+                let history = History::default();
+                let server = serve_retransmissions(
+                    session.clone(),
+                    &record.resource,
+                    history.clone(),
+                    record.fragment_size,
+                    traffic.clone(),
+                )
+                .await?;
+                Some(Retransmission {
+                    capacity,
+                    history,
+                    server: Mutex::new(Some(server)),
+                })
+            }
+            _ => None,
+        };
+
+        let spool = record
+            .spool
+            .as_ref()
+            .map(|spool| Spool::open(spool, &record.id))
+            .transpose()?;
+
         Ok(Self {
             id: record.id.clone(),
-            input_raw: InputRaw {
-                port_id: record.link_id.port_id.clone(),
-                receivers,
-            },
-            z_session: ctx.runtime.session.clone(),
+            input_raw: InputRaw::new(record.link_id.port_id.clone(), receivers, None),
+            z_session: session,
             key_expr,
             shm_element_size,
             shm_backoff,
             state: Arc::new(Mutex::new(ZenohSenderState {
                 shm: shm_manager,
-                message_buffer: Vec::default(),
                 payload_buffer: Vec::default(),
+                sequence: 0,
+                pending: VecDeque::default(),
+                pending_bytes: 0,
+                spool,
             })),
+            retransmission,
+            outage_budget: record.outage_budget,
+            buffers_outages: record.outage_budget.is_some() || record.spool.is_some(),
+            fragment_size: record.fragment_size,
+            compression: record.compression,
+            encoding: data_encoding(record.link_id.data_type.as_ref()),
+            traffic,
+        })
+    }
+
+    /// Encodes the `message` numbered `sequence` in a frame, compressed if the link compresses the
+    /// messages of that size.
+    fn frame(&self, sequence: u64, message: &LinkMessage) -> ZFResult<Frame> {
+        let frame = Frame::encode(sequence, message, self.traffic.pool())?;
+        Ok(match self.compression {
+            Some(min_size) => frame.compress(min_size),
+            None => frame,
         })
     }
+
+    /// Waits for the next message to publish.
+    ///
+    /// While messages are buffered because of an outage, their transmission is retried every
+    /// [OUTAGE_RETRY_INTERVAL] until a new message arrives: `None` is then returned on timeout.
+    async fn next_message(&self) -> Option<ZFResult<LinkMessage>> {
+        if !self.state.lock().await.is_buffering() {
+            return Some(self.input_raw.recv().await);
+        }
+
+        match crate::executor::timeout(OUTAGE_RETRY_INTERVAL, self.input_raw.recv()).await {
+            Ok(message) => Some(message),
+            Err(_) => {
+                self.flush(&mut *self.state.lock().await).await;
+                None
+            }
+        }
+    }
+
+    /// Returns `true` if the session is connected to, at least, a Zenoh router or peer.
+    async fn is_connected(&self) -> bool {
+        let info = self.z_session.info();
+        info.routers_zid().res().await.next().is_some()
+            || info.peers_zid().res().await.next().is_some()
+    }
+
+    /// Puts the `frame` on Zenoh, split into fragments if the link sets a fragment size.
+    ///
+    /// A frame that is not split is put as is, its payload is not copied.
+    async fn put(&self, frame: Frame) -> ZFResult<()> {
+        let fragments: Vec<(usize, ZBuf)> = match self.fragment_size {
+            Some(fragment_size) => split(&frame.to_vec(), fragment_size)?
+                .into_iter()
+                .map(|fragment| (fragment.len(), fragment.into()))
+                .collect(),
+            None => vec![(frame.len(), frame.into_zbuf())],
+        };
+
+        for (bytes, fragment) in fragments {
+            self.z_session
+                .put(self.key_expr.clone(), fragment)
+                .encoding(self.encoding.clone())
+                .congestion_control(CongestionControl::Block)
+                .res()
+                .await?;
+            self.traffic.record_bytes(bytes);
+        }
+        self.traffic.record_message();
+
+        Ok(())
+    }
+
+    /// Publishes the `frame` or, if it failed and an outage budget or a spool is set, buffers it.
+    async fn publish(&self, state: &mut ZenohSenderState, frame: Frame) -> ZFResult<()> {
+        // The frame is only cloned if it could have to be buffered: its payload is not copied.
+        let copy = self.buffers_outages.then(|| frame.clone());
+        let published = self.put(frame).await;
+
+        match (published, copy) {
+            (Ok(()), _) => Ok(()),
+            (Err(e), Some(frame)) => {
+                log::warn!(
+                    "[ZenohSender: {}] Failed to publish, buffering: {e:?}",
+                    self.id
+                );
+                self.buffer(state, frame);
+                Ok(())
+            }
+            (Err(e), None) => Err(e),
+        }
+    }
+
+    /// Buffers the `frame` until the outage ends, in the spool if the link has one. The oldest
+    /// messages are dropped to stay within the outage budget, or the limits of the spool: the
+    /// receivers detect them as lost.
+    fn buffer(&self, state: &mut ZenohSenderState, frame: Frame) {
+        if !state.is_buffering() {
+            log::warn!(
+                "[ZenohSender: {}] Zenoh is unavailable, buffering the messages",
+                self.id
+            );
+        }
+
+        if let Some(spool) = &mut state.spool {
+            match spool.push(&frame.to_vec()) {
+                Ok(0) => (),
+                Ok(dropped) => log::warn!(
+                    "[ZenohSender: {}] Limits of the spool exceeded, dropped {dropped} message(s)",
+                    self.id
+                ),
+                Err(e) => log::error!(
+                    "[ZenohSender: {}] Failed to spool a message, dropping it: {e:?}",
+                    self.id
+                ),
+            }
+            return;
+        }
+
+        state.pending_bytes += frame.len();
+        state.pending.push_back(frame);
+
+        let budget = self.outage_budget.unwrap_or_default();
+        while state.pending_bytes > budget {
+            match state.pending.pop_front() {
+                Some(oldest) => {
+                    state.pending_bytes -= oldest.len();
+                    log::warn!(
+                        "[ZenohSender: {}] Outage budget of {budget} bytes exceeded, dropping a message",
+                        self.id
+                    );
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// Sends, in order, the messages buffered during an outage, if the connectivity returned.
+    ///
+    /// The spooled messages that exceeded the age limit of the spool are dropped instead.
+    async fn flush(&self, state: &mut ZenohSenderState) {
+        if !state.is_buffering() || !self.is_connected().await {
+            return;
+        }
+
+        if let Some(spool) = &mut state.spool {
+            let expired = spool.expire();
+            if expired > 0 {
+                log::warn!(
+                    "[ZenohSender: {}] Dropped {expired} spooled message(s) older than the limit",
+                    self.id
+                );
+            }
+
+            log::debug!(
+                "[ZenohSender: {}] Forwarding {} spooled message(s), {} bytes",
+                self.id,
+                spool.len(),
+                spool.bytes()
+            );
+            while let Some(frame) = spool.front() {
+                let frame = match frame {
+                    Ok(bytes) => Frame::from_vec(bytes),
+                    Err(e) => {
+                        log::error!(
+                            "[ZenohSender: {}] Failed to read a spooled message, dropping it: {e:?}",
+                            self.id
+                        );
+                        spool.pop_front();
+                        continue;
+                    }
+                };
+
+                match self.put(frame).await {
+                    Ok(()) => {
+                        spool.pop_front();
+                    }
+                    Err(e) => {
+                        log::debug!("[ZenohSender: {}] Zenoh still unavailable: {e:?}", self.id);
+                        return;
+                    }
+                }
+            }
+        }
+
+        while let Some(frame) = state.pending.front() {
+            match self.put(frame.clone()).await {
+                Ok(()) => {
+                    if let Some(frame) = state.pending.pop_front() {
+                        state.pending_bytes -= frame.len();
+                    }
+                }
+                Err(e) => {
+                    log::debug!("[ZenohSender: {}] Zenoh still unavailable: {e:?}", self.id);
+                    return;
+                }
+            }
+        }
+
+        log::info!(
+            "[ZenohSender: {}] Zenoh is available again, the buffered messages were sent",
+            self.id
+        );
+    }
+
+    /// Keeps the `frame` of the message `sequence` to be retransmitted, if retransmissions are
+    /// enabled, evicting the oldest one if need be.
+    fn keep(&self, sequence: u64, frame: &Frame) {
+        if let Some(retransmission) = &self.retransmission {
+            let mut history = retransmission
+                .history
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            if history.len() == retransmission.capacity {
+                history.pop_front();
+            }
+            history.push_back((sequence, frame.clone()));
+        }
+    }
 }
 
 #[async_trait]
 impl Node for ZenohSender {
     /// An iteration of a ZenohSender: wait for some data to publish, serialize it using `bincode`
-    /// and publish it on Zenoh.
+    /// and publish it on Zenoh, prefixed with its sequence number.
+    ///
+    /// The payload of the message is not copied in the frame published (see [Frame]): the typed
+    /// data are serialized in a buffer of the [SerializerPool] of the sender.
+    ///
+    /// If the link sets a fragment size, the message is published in fragments of at most that
+    /// many bytes.
+    ///
+    /// If an outage budget or a spool is set, the messages are buffered (in memory or on disk)
+    /// while Zenoh is unavailable instead of failing the iteration.
+    ///
+    /// If the link compresses its messages, the frames of at least the size set are compressed,
+    /// except the ones published through shared memory.
     ///
     /// # Errors
     ///
@@ -150,14 +951,29 @@ impl Node for ZenohSender {
     /// - zenoh put fails
     /// - link recv fails
     async fn iteration(&self) -> ZFResult<()> {
-        match self.input_raw.recv().await {
+        let received = match self.next_message().await {
+            Some(received) => received,
+            None => return Ok(()),
+        };
+
+        match received {
             Ok(message) => {
                 let mut state = self.state.lock().await;
+                let sequence = state.sequence;
+                state.sequence += 1;
+
+                // During an outage, the messages are buffered behind the ones already pending.
+                if self.buffers_outages && (state.is_buffering() || !self.is_connected().await) {
+                    let frame = self.frame(sequence, &message)?;
+                    self.keep(sequence, &frame);
+                    self.buffer(&mut state, frame);
+                    self.flush(&mut state).await;
+                    return Ok(());
+                }
 
                 // NOTE: as per the documentation of Vec::default, which is what the
                 // `std::mem::take` will call, no allocation is performed until elements are pushed
                 // in the vector.
-                let mut message_buffer = std::mem::take(&mut state.message_buffer);
                 let mut payload_buffer = std::mem::take(&mut state.payload_buffer);
 
                 match state.shm {
@@ -166,7 +982,7 @@ impl Node for ZenohSender {
                         let mut buff = match shm.alloc(self.shm_element_size) {
                             Ok(buf) => buf,
                             Err(_) => {
-                                async_std::task::sleep(std::time::Duration::from_millis(
+                                crate::executor::sleep(std::time::Duration::from_millis(
                                     self.shm_backoff,
                                 ))
                                 .await;
@@ -192,55 +1008,77 @@ impl Node for ZenohSender {
 
                         // Getting the underlying slice in the shared memory
                         let slice = unsafe { buff.as_mut_slice() };
+                        if slice.len() < SEQUENCE_SIZE {
+                            return Err(zferror!(
+                                ErrorKind::ConfigurationError,
+                                "The shared memory elements ({} bytes) cannot hold a sequence number",
+                                slice.len()
+                            )
+                            .into());
+                        }
+                        let (sequence_slice, message_slice) = slice.split_at_mut(SEQUENCE_SIZE);
+                        sequence_slice.copy_from_slice(&sequence.to_le_bytes());
 
                         // WARNING ACHTUNG ATTENTION
                         // This may fail as the message could be bigger than
                         // the shared memory buffer that was allocated.
-                        match message.serialize_bincode_into_shm(slice, &mut payload_buffer) {
+                        match message.serialize_bincode_into_shm(message_slice, &mut payload_buffer)
+                        {
                             Ok(_) => {
+                                if self.retransmission.is_some() {
+                                    self.keep(sequence, &self.frame(sequence, &message)?);
+                                }
+
                                 // If the serialization succeeded then we send the shared memory
                                 // buffer.
-                                self.z_session
+                                let published = self
+                                    .z_session
                                     .put(self.key_expr.clone(), buff)
+                                    .encoding(self.encoding.clone())
                                     .congestion_control(CongestionControl::Block)
                                     .res()
-                                    .await?;
+                                    .await;
+                                match published {
+                                    Ok(()) => {
+                                        self.traffic.record_bytes(self.shm_element_size);
+                                        self.traffic.record_message();
+                                    }
+                                    Err(e) if self.buffers_outages => {
+                                        log::warn!(
+                                            "[ZenohSender: {}] Failed to publish, buffering: {e:?}",
+                                            self.id
+                                        );
+                                        let frame = self.frame(sequence, &message)?;
+                                        self.buffer(&mut state, frame);
+                                    }
+                                    Err(e) => return Err(e.into()),
+                                }
                             }
                             Err(e) => {
-                                // Otherwise we log a warn and we serialize on a normal
-                                // Vec<u8>
-                                message.serialize_bincode_into(
-                                    &mut message_buffer,
-                                    &mut payload_buffer,
-                                )?;
+                                // Otherwise we log a warn and we publish the frame without
+                                // shared memory.
+                                let frame = self.frame(sequence, &message)?;
                                 log::warn!(
                                     "[ZenohSender: {}] Unable to serialize into shared memory: {}, serialized size {}, shared memory size {}",
                                     self.id,
                                     e,
-                                    state.message_buffer.len(),
+                                    frame.len(),
                                     self.shm_element_size,
                                 );
 
-                                self.z_session
-                                    .put(self.key_expr.clone(), message_buffer.clone())
-                                    .congestion_control(CongestionControl::Block)
-                                    .res()
-                                    .await?;
+                                self.keep(sequence, &frame);
+                                self.publish(&mut state, frame).await?;
                             }
                         }
                     }
                     None => {
-                        message.serialize_bincode_into(&mut message_buffer, &mut payload_buffer)?;
-                        self.z_session
-                            .put(self.key_expr.clone(), message_buffer.clone())
-                            .congestion_control(CongestionControl::Block)
-                            .res()
-                            .await?;
+                        let frame = self.frame(sequence, &message)?;
+                        self.keep(sequence, &frame);
+                        self.publish(&mut state, frame).await?;
                     }
                 }
 
-                // NOTE: set back the buffers such that we don't have to allocate memory again.
-                state.message_buffer = message_buffer;
+                // NOTE: set back the buffer such that we don't have to allocate memory again.
                 state.payload_buffer = payload_buffer;
                 Ok(())
             }
@@ -253,12 +1091,50 @@ impl Node for ZenohSender {
             .into()),
         }
     }
+
+    /// Undeclares the key expression on which the ZenohSender publishes and releases its shared
+    /// memory: contrary to a subscriber, a declared key expression is not undeclared when dropped.
+    /// The retransmissions, if any, are no longer served and the spool, if any, is removed.
+    async fn clean(&self, _context: &Context) -> ZFResult<()> {
+        let mut state = self.state.lock().await;
+        state.shm = None;
+        if let Some(spool) = state.spool.take() {
+            spool.remove();
+        }
+        drop(state);
+        if let Some(retransmission) = &self.retransmission {
+            if let Some(server) = retransmission.server.lock().await.take() {
+                server.cancel().await;
+            }
+            retransmission
+                .history
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .clear();
+        }
+        self.z_session
+            .undeclare(self.key_expr.clone())
+            .res()
+            .await?;
+        Ok(())
+    }
 }
+
 /// A `ZenohReceiver` receives the messages from Zenoh when nodes are running on different runtimes.
+///
+/// It checks the sequence numbers of the messages it receives: the messages that were lost are
+/// reported and, if the link enables retransmissions, requested again to the sender.
 pub(crate) struct ZenohReceiver {
     pub(crate) id: NodeId,
     pub(crate) output_raw: OutputRaw,
     pub(crate) subscriber: Subscriber<'static, Receiver<Sample>>,
+    pub(crate) z_session: Arc<zenoh::Session>,
+    pub(crate) resource: String,
+    pub(crate) retransmission: bool,
+    pub(crate) sequence: Arc<LinkSequence>,
+    pub(crate) reassembly: Option<std::sync::Mutex<Reassembly>>,
+    pub(crate) data_type: Option<PortType>,
+    pub(crate) traffic: Arc<LinkTraffic>,
 }
 
 impl ZenohReceiver {
@@ -271,6 +1147,7 @@ impl ZenohReceiver {
     /// ## Errors
     ///
     /// An error variant is returned if:
+    /// - the Zenoh session of the connector was not opened,
     /// - the declaration of the key expression failed,
     /// - the declaration of the subscriber failed,
     /// - the link for this connector was not created.
@@ -278,20 +1155,16 @@ impl ZenohReceiver {
         record: &ZFConnectorRecord,
         ctx: Arc<InstanceContext>,
         mut outputs: Outputs,
+        sequence: Arc<LinkSequence>,
+        traffic: Arc<LinkTraffic>,
     ) -> ZFResult<Self> {
-        let key_expr = ctx
-            .runtime
-            .session
+        let session = ctx.session(record.session.as_deref())?;
+        let key_expr = session
             .declare_keyexpr(record.resource.clone())
             .res()
             .await?
             .into_owned();
-        let subscriber = ctx
-            .runtime
-            .session
-            .declare_subscriber(key_expr.clone())
-            .res()
-            .await?;
+        let subscriber = session.declare_subscriber(key_expr.clone()).res().await?;
         let senders = outputs
             .hmap
             .remove(&record.link_id.port_id)
@@ -309,14 +1182,88 @@ impl ZenohReceiver {
             output_raw: OutputRaw {
                 port_id: record.link_id.port_id.clone(),
                 senders,
-                hlc: ctx.runtime.hlc.clone(),
+                cache: outputs
+                    .caches
+                    .get(&record.link_id.port_id)
+                    .cloned()
+                    .unwrap_or_default(),
+                queues: outputs
+                    .queues
+                    .remove(&record.link_id.port_id)
+                    .unwrap_or_default(),
+                tap: outputs
+                    .taps
+                    .get(&record.link_id.port_id)
+                    .cloned()
+                    .unwrap_or_default(),
+                warmup: outputs.warmup.clone(),
+                activity: outputs.activity.clone(),
+                hlc: ctx.hlc.clone(),
                 last_watermark: Arc::new(AtomicU64::new(
-                    ctx.runtime.hlc.new_timestamp().get_time().as_u64(),
+                    ctx.hlc.new_timestamp().get_time().as_u64(),
                 )),
             },
             subscriber,
+            z_session: session,
+            resource: record.resource.clone(),
+            retransmission: record.retransmission.map_or(false, |capacity| capacity > 0),
+            sequence,
+            reassembly: record
+                .fragment_size
+                .map(|_| std::sync::Mutex::new(Reassembly::new(REASSEMBLY_TIMEOUT))),
+            data_type: record.link_id.data_type.clone(),
+            traffic,
         })
     }
+
+    /// Requests the sender to retransmit the messages whose sequence number is in the `gap`.
+    ///
+    /// The messages retransmitted are returned in order. The ones the sender no longer has are
+    /// missing.
+    ///
+    /// If the link sets a fragment size, the messages are retransmitted in fragments.
+    async fn retransmit(&self, gap: &Range<u64>) -> Vec<LinkMessage> {
+        let selector = format!("{}/{RETRANSMIT}/{}/{}", self.resource, gap.start, gap.end);
+        let replies = match self.z_session.get(&selector).res().await {
+            Ok(replies) => replies,
+            Err(e) => {
+                log::error!(
+                    "[ZenohReceiver: {}] Failed to request a retransmission: {e:?}",
+                    self.id
+                );
+                return Vec::new();
+            }
+        };
+
+        let mut reassembly = self
+            .reassembly
+            .as_ref()
+            .map(|_| Reassembly::new(REASSEMBLY_TIMEOUT));
+        let mut messages = Vec::new();
+        while let Ok(reply) = replies.recv_async().await {
+            match reply
+                .sample
+                .map_err(|e| zferror!(ErrorKind::RecvError, "{e:?}").into())
+                .and_then(|sample| {
+                    let payload = sample.value.payload.contiguous();
+                    self.traffic.record_bytes(payload.len());
+                    match reassembly.as_mut() {
+                        Some(reassembly) => reassembly.push(&payload, &self.traffic),
+                        None => unframe(&payload, &self.traffic).map(Some),
+                    }
+                }) {
+                Ok(Some((sequence, message))) if gap.contains(&sequence) => {
+                    messages.push((sequence, message))
+                }
+                Ok(_) => (),
+                Err(e) => log::error!("[ZenohReceiver: {}] Invalid retransmission: {e:?}", self.id),
+            }
+        }
+
+        messages.sort_by_key(|(sequence, _)| *sequence);
+        messages.dedup_by_key(|(sequence, _)| *sequence);
+        messages.into_iter().map(|(_, message)| message).collect()
+    }
 }
 
 #[async_trait]
@@ -324,26 +1271,72 @@ impl Node for ZenohReceiver {
     /// An iteration of a `ZenohReceiver`: wait on the subscriber for some message, deserialize it
     /// using `bincode` and send it on the flume channel(s) to the downstream node(s).
     ///
+    /// If messages were lost since the previous one, they are first retransmitted (when enabled)
+    /// and the gap is reported.
+    ///
+    /// If the link sets a fragment size, the fragments are gathered until the message is complete.
+    ///
     /// ## Errors
     ///
     /// An error variant is returned if:
     /// - the subscriber fails
+    /// - the data type of the message is not compatible with the one of the input
     /// - the deserialization fails
     /// - sending on the channels fails
     async fn iteration(&self) -> ZFResult<()> {
         match self.subscriber.recv_async().await {
             Ok(message) => {
-                let de: LinkMessage = bincode::deserialize(&message.value.payload.contiguous())
-                    .map_err(|e| {
-                        zferror!(
-                            ErrorKind::DeserializationError,
-                            "[ZenohReceiver: {}] {:?}",
-                            self.id,
-                            e
-                        )
-                    })?;
+                check_data_type(self.data_type.as_ref(), &message.value.encoding).map_err(|e| {
+                    zferror!(
+                        ErrorKind::InvalidData,
+                        "[ZenohReceiver: {}] {:?}",
+                        self.id,
+                        e
+                    )
+                })?;
+
+                let payload = message.value.payload.contiguous();
+                self.traffic.record_bytes(payload.len());
+                let received = match &self.reassembly {
+                    Some(reassembly) => reassembly
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .push(&payload, &self.traffic),
+                    None => unframe(&payload, &self.traffic).map(Some),
+                };
+                let (sequence, de) = match received.map_err(|e| {
+                    zferror!(
+                        ErrorKind::DeserializationError,
+                        "[ZenohReceiver: {}] {:?}",
+                        self.id,
+                        e
+                    )
+                })? {
+                    Some(received) => received,
+                    // The message is not complete yet.
+                    None => return Ok(()),
+                };
+
+                if let Some(gap) = self.sequence.check(sequence) {
+                    let mut recovered = 0;
+                    if self.retransmission {
+                        for message in self.retransmit(&gap).await {
+                            self.output_raw.forward(message).await?;
+                            self.traffic.record_message();
+                            recovered += 1;
+                        }
+                    }
+
+                    let missing = gap.end - gap.start;
+                    self.sequence.record_gap(missing, recovered);
+                    log::warn!(
+                        "[ZenohReceiver: {}] {missing} message(s) lost before message {sequence}, {recovered} retransmitted",
+                        self.id
+                    );
+                }
 
                 self.output_raw.forward(de).await?;
+                self.traffic.record_message();
 
                 Ok(())
             }
@@ -352,3 +1345,7 @@ impl Node for ZenohReceiver {
         }
     }
 }
+
+#[cfg(test)]
+#[path = "./tests/connector-tests.rs"]
+mod tests;
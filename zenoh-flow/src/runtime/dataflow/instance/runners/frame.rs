@@ -0,0 +1,210 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::types::{DataMessage, LinkMessage, Payload, SerializerPool};
+use crate::zferror;
+use crate::zfresult::ErrorKind;
+use crate::Result;
+use serde::Serialize;
+use std::sync::Arc;
+use zenoh::buffers::ZBuf;
+use zenoh_flow_core::{COMPRESSED_TAG, SEQUENCE_SIZE};
+
+/// The index, in the bincode encoding, of the variant `LinkMessage::Data` and of the variant
+/// `Payload::Bytes`.
+const DATA_VARIANT: u32 = 0;
+const BYTES_VARIANT: u32 = 0;
+
+/// The size of the head of a frame carrying data: the sequence number, the indexes of the variants
+/// and the length of the payload.
+const DATA_HEAD_SIZE: usize = SEQUENCE_SIZE + 2 * std::mem::size_of::<u32>() + 8;
+
+/// The frame of a message published by a [ZenohSender](super::connector::ZenohSender), made of
+/// several slices: its encoding is identical to
+/// [`frame`](zenoh_flow_core::frame)`(sequence, bincode(message))` but the payload of a data message
+/// is not copied into it.
+///
+/// - the `head` holds the sequence number and the encoding of the message up to its payload,
+/// - the `payload` holds the bytes of the payload, shared with the message (or with the
+///   [SerializerPool] in which typed data were serialized),
+/// - the `tail` holds the encoding of the message after its payload: its timestamps and the
+///   variant of its data.
+///
+/// Cloning a frame, to keep it for retransmission or while Zenoh is unavailable, does not copy its
+/// payload.
+#[derive(Clone, Debug)]
+pub(crate) struct Frame {
+    head: Vec<u8>,
+    payload: Option<Arc<Vec<u8>>>,
+    tail: Vec<u8>,
+}
+
+impl Frame {
+    /// Encodes the `message` numbered `sequence`. Typed data are serialized in a buffer of the
+    /// `pool`.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the message could not be serialized.
+    pub(crate) fn encode(
+        sequence: u64,
+        message: &LinkMessage,
+        pool: &SerializerPool,
+    ) -> Result<Self> {
+        let data_message = match message {
+            LinkMessage::Data(data_message) => data_message,
+            _ => return Self::encode_whole(sequence, message),
+        };
+
+        let payload = match &data_message.data {
+            Payload::Bytes(bytes) => bytes.clone(),
+            Payload::Typed((data, serializer)) => {
+                pool.serialize(|buffer| (serializer)(buffer, data.clone()))?
+            }
+            // Inline bytes are small: copying them is cheaper than adding a slice.
+            Payload::Inline(_) | Payload::Reference(_) => {
+                return Self::encode_whole(sequence, message)
+            }
+        };
+
+        let mut head = Vec::with_capacity(DATA_HEAD_SIZE);
+        head.extend_from_slice(&sequence.to_le_bytes());
+        head.extend_from_slice(&DATA_VARIANT.to_le_bytes());
+        head.extend_from_slice(&BYTES_VARIANT.to_le_bytes());
+        head.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+
+        let DataMessage {
+            timestamp,
+            event_time,
+            variant,
+            ..
+        } = data_message;
+        let tail = serialize(&(timestamp, event_time, variant))?;
+
+        Ok(Self {
+            head,
+            payload: Some(payload),
+            tail,
+        })
+    }
+
+    /// Encodes the `message` numbered `sequence` in the head of the frame: it has no payload to
+    /// share.
+    fn encode_whole(sequence: u64, message: &LinkMessage) -> Result<Self> {
+        let mut head = sequence.to_le_bytes().to_vec();
+        bincode::serialize_into(&mut head, message)
+            .map_err(|e| zferror!(ErrorKind::SerializationError, e))?;
+
+        Ok(Self {
+            head,
+            payload: None,
+            tail: Vec::new(),
+        })
+    }
+
+    /// Compresses the frame if it holds at least `min_size` bytes. The frames that do not shrink
+    /// are kept as is.
+    ///
+    /// The message of a compressed frame starts with [COMPRESSED_TAG], which no `LinkMessage` does:
+    /// the receivers, daemons (see [decompress]) or devices using `zenoh-flow-core`, detect it
+    /// without being told.
+    pub(crate) fn compress(self, min_size: usize) -> Self {
+        if self.len() < min_size {
+            return self;
+        }
+
+        let bytes = self.to_vec();
+        let (sequence, message) = bytes.split_at(SEQUENCE_SIZE);
+        let compressed = lz4_flex::compress_prepend_size(message);
+        if compressed.len() + std::mem::size_of::<u32>() >= message.len() {
+            return self;
+        }
+
+        let mut head =
+            Vec::with_capacity(SEQUENCE_SIZE + std::mem::size_of::<u32>() + compressed.len());
+        head.extend_from_slice(sequence);
+        head.extend_from_slice(&COMPRESSED_TAG.to_le_bytes());
+        head.extend_from_slice(&compressed);
+
+        Self {
+            head,
+            payload: None,
+            tail: Vec::new(),
+        }
+    }
+
+    /// Returns the frame held in `bytes`, as returned by [Frame::to_vec].
+    pub(crate) fn from_vec(bytes: Vec<u8>) -> Self {
+        Self {
+            head: bytes,
+            payload: None,
+            tail: Vec::new(),
+        }
+    }
+
+    /// Returns the number of bytes of the frame.
+    pub(crate) fn len(&self) -> usize {
+        self.head.len() + self.payload.as_ref().map_or(0, |payload| payload.len()) + self.tail.len()
+    }
+
+    /// Returns the frame in a single buffer, copying its slices.
+    pub(crate) fn to_vec(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.len());
+        bytes.extend_from_slice(&self.head);
+        if let Some(payload) = &self.payload {
+            bytes.extend_from_slice(payload);
+        }
+        bytes.extend_from_slice(&self.tail);
+        bytes
+    }
+
+    /// Returns the frame as a Zenoh buffer made of its slices: the payload is not copied.
+    pub(crate) fn into_zbuf(self) -> ZBuf {
+        let mut zbuf = ZBuf::from(self.head);
+        if let Some(payload) = self.payload.filter(|payload| !payload.is_empty()) {
+            zbuf.push_zslice(payload.into());
+        }
+        if !self.tail.is_empty() {
+            zbuf.push_zslice(self.tail.into());
+        }
+        zbuf
+    }
+}
+
+/// Returns the decompressed `message`, if it was compressed by [Frame::compress].
+///
+/// The message is decompressed as the devices do (see [zenoh_flow_core::decompress]): the size it
+/// announces is not trusted beyond what it can expand to.
+///
+/// # Errors
+///
+/// An error is returned if the message could not be decompressed.
+pub(crate) fn decompress(message: &[u8]) -> Result<Option<Vec<u8>>> {
+    match message.split_at(message.len().min(std::mem::size_of::<u32>())) {
+        (tag, compressed) if tag == COMPRESSED_TAG.to_le_bytes() => {
+            zenoh_flow_core::decompress(compressed)
+                .map(Some)
+                .map_err(|e| zferror!(ErrorKind::DeserializationError, "{}", e).into())
+        }
+        _ => Ok(None),
+    }
+}
+
+fn serialize(value: &impl Serialize) -> Result<Vec<u8>> {
+    bincode::serialize(value).map_err(|e| zferror!(ErrorKind::SerializationError, e).into())
+}
+
+#[cfg(test)]
+#[path = "./tests/frame-tests.rs"]
+mod tests;
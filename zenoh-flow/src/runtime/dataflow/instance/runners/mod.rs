@@ -13,14 +13,30 @@
 //
 
 pub mod connector;
+pub(crate) mod frame;
+pub(crate) mod spool;
+pub(crate) mod timers;
+pub(crate) mod watchdog;
 
+use self::timers::Timers;
+use self::watchdog::Watchdog;
+use crate::executor::JoinHandle;
+use crate::io::output::{LinkQueue, WarmUp};
+use crate::runtime::dataflow::instance::control::{Control, NodeCheckpoint, NodeControl};
+use crate::runtime::dataflow::instance::events::{InstanceEventKind, InstanceEvents};
+use crate::runtime::dataflow::instance::flow_control::FlowControl;
 use crate::traits::Node;
-use crate::zfresult::Error;
-use crate::Result as ZFResult;
-use async_std::task::JoinHandle;
-use futures::future::{AbortHandle, Abortable, Aborted};
+use crate::types::{ControlMarker, KeyedState, NodeId, PortId};
+use crate::zfresult::{Error, ErrorKind, WithContext, ZFError};
+use crate::{bail, zferror, Result as ZFResult};
+use async_lock::Mutex;
+use futures::future::{self, AbortHandle, Abortable, Aborted, Either};
+use futures::{Future, FutureExt};
+use std::any::Any;
+use std::collections::HashMap;
+use std::panic::AssertUnwindSafe;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 /// Type of the Runner.
 ///
@@ -39,26 +55,290 @@ pub enum RunAction {
     Stop,
 }
 
+/// Returns the message of a panic, if it is a string.
+fn panic_message(panic: Box<dyn Any + Send>) -> String {
+    match panic.downcast::<String>() {
+        Ok(message) => *message,
+        Err(panic) => match panic.downcast::<&'static str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "unknown panic".to_string(),
+        },
+    }
+}
+
+/// Awaits the `future`, a callback of the node `node_id`, converting a panic into a `NodePanic`
+/// error.
+///
+/// A panicking node is then handled as a node returning an error: the rest of the data flow is not
+/// affected and the node can be restarted. This requires panics to unwind: with `panic = "abort"`
+/// the whole process is aborted.
+pub(crate) async fn catch_panic<T>(
+    node_id: &NodeId,
+    future: impl Future<Output = ZFResult<T>>,
+) -> ZFResult<T> {
+    match AssertUnwindSafe(future).catch_unwind().await {
+        Ok(result) => result,
+        Err(panic) => {
+            let message = panic_message(panic);
+            bail!(
+                ErrorKind::NodePanic(node_id.clone(), message.clone()),
+                "Node < {} > panicked: {}",
+                node_id,
+                message
+            )
+        }
+    }
+}
+
+/// Returns `true` if the error `e` is a panic caught by [catch_panic].
+fn is_panic(e: &Error) -> bool {
+    matches!(
+        e.downcast_ref::<ZFError>().map(|e| e.get_kind()),
+        Some(ErrorKind::NodePanic(_, _))
+    )
+}
+
+/// Awaits the `future`, a callback of the node `node_id`, converting a panic into a `NodePanic`
+/// error and interrupting it, with a `RunTimeout` error, if it lasts longer than
+/// `max_run_duration`.
+async fn bounded(
+    node_id: &NodeId,
+    max_run_duration: Option<Duration>,
+    future: impl Future<Output = ZFResult<()>>,
+) -> ZFResult<()> {
+    let future = catch_panic(node_id, future);
+    match max_run_duration {
+        Some(max_run_duration) => match crate::executor::timeout(max_run_duration, future).await {
+            Ok(result) => result,
+            Err(_) => bail!(
+                ErrorKind::RunTimeout(node_id.clone(), max_run_duration),
+                "Callback of < {} > interrupted after {:?}",
+                node_id,
+                max_run_duration
+            ),
+        },
+        None => future.await,
+    }
+}
+
+/// What wakes up the runner of a node in between two polls of its `iteration`.
+enum Wake {
+    Timer(u64),
+    Control(Control),
+}
+
+/// Waits for the next of the `timers` of a node to fire or for the next message delivered to its
+/// `control`, whichever comes first.
+async fn next_wake(timers: Option<&mut Timers>, control: Option<&NodeControl>) -> Wake {
+    let timer = async move {
+        match timers {
+            Some(timers) => Wake::Timer(timers.next().await),
+            None => future::pending().await,
+        }
+    };
+    let control = async move {
+        match control {
+            Some(control) => Wake::Control(control.next().await),
+            None => future::pending().await,
+        }
+    };
+    futures::pin_mut!(timer, control);
+    match future::select(timer, control).await {
+        Either::Left((wake, _)) | Either::Right((wake, _)) => wake,
+    }
+}
+
+/// Calls `on_control` with the payload of the `marker` and, if it is a `barrier`, forwards it on
+/// the outputs of the node --- even if `on_control` failed, such that the barrier reaches the rest
+/// of the data flow.
+///
+/// If the marker is a checkpoint barrier, the node is checkpointed instead: its state and the
+/// partitions of its `keyed_state`. A failed checkpoint is reported, it does not end the task of
+/// the node.
+async fn handle_control(
+    node: &Arc<dyn Node>,
+    node_id: &NodeId,
+    max_run_duration: Option<Duration>,
+    control: Option<&NodeControl>,
+    keyed_state: Option<&KeyedState>,
+    marker: &ControlMarker,
+    barrier: bool,
+) -> ZFResult<()> {
+    if let (Some(epoch), Some(control)) = (marker.get_epoch(), control) {
+        log::trace!("Checkpoint {} of < {} >", epoch, node_id);
+        let checkpoint = checkpoint(node, node_id, keyed_state).await.map_err(|e| {
+            log::error!("Checkpoint {} of < {} > failed: {:?}", epoch, node_id, e);
+            e.to_string()
+        });
+        control.checkpointed(marker, checkpoint).await;
+        return Ok(());
+    }
+
+    log::trace!("Control message delivered to < {} >", node_id);
+    let result = bounded(
+        node_id,
+        max_run_duration,
+        node.on_control(marker.get_payload()),
+    )
+    .await;
+    if let (true, Some(control)) = (barrier, control) {
+        control.release(marker).await;
+    }
+    result
+}
+
+/// Takes the checkpoint of the `node`: its state and the partitions of its `keyed_state`.
+async fn checkpoint(
+    node: &Arc<dyn Node>,
+    node_id: &NodeId,
+    keyed_state: Option<&KeyedState>,
+) -> ZFResult<NodeCheckpoint> {
+    let state = catch_panic(node_id, node.checkpoint()).await?;
+    let partitions = match keyed_state {
+        Some(keyed_state) => keyed_state.checkpoint()?,
+        None => HashMap::new(),
+    };
+    Ok(NodeCheckpoint {
+        state,
+        partitions,
+        links: Vec::new(),
+    })
+}
+
 /// A `Runner` takes care of running a `Node`.
 ///
 /// It spawns an abortable task in which the `iteration` is called in a loop, indefinitely.
 pub(crate) struct Runner {
+    pub(crate) node_id: NodeId,
     pub(crate) node: Arc<dyn Node>,
+    pub(crate) max_run_duration: Option<Duration>,
+    pub(crate) timers: Option<Arc<Mutex<Timers>>>,
+    pub(crate) flow_control: Option<Arc<FlowControl>>,
+    pub(crate) link_queues: HashMap<PortId, Vec<Arc<LinkQueue>>>,
+    pub(crate) warmup: Option<Arc<WarmUp>>,
+    pub(crate) watchdog: Option<Arc<Watchdog>>,
+    pub(crate) events: Option<Arc<InstanceEvents>>,
+    pub(crate) control: Option<Arc<NodeControl>>,
+    pub(crate) keyed_state: Option<Arc<KeyedState>>,
     pub(crate) run_loop_handle: Option<JoinHandle<Result<Error, Aborted>>>,
     pub(crate) run_loop_abort_handle: Option<AbortHandle>,
 }
 
 impl Runner {
-    pub(crate) fn new(node: Arc<dyn Node>) -> Self {
+    pub(crate) fn new(
+        node_id: NodeId,
+        node: Arc<dyn Node>,
+        max_run_duration: Option<Duration>,
+    ) -> Self {
         Self {
+            node_id,
             node,
+            max_run_duration,
+            timers: None,
+            flow_control: None,
+            link_queues: HashMap::new(),
+            warmup: None,
+            watchdog: None,
+            events: None,
+            control: None,
+            keyed_state: None,
             run_loop_handle: None,
             run_loop_abort_handle: None,
         }
     }
 
+    /// Sets the `timers` of the node, scheduled through its `Context`: when one fires, `on_timer`
+    /// is called in between two polls of the current `iteration`.
+    pub(crate) fn with_timers(mut self, timers: Timers) -> Self {
+        self.timers = Some(Arc::new(Mutex::new(timers)));
+        self
+    }
+
+    /// Sets the `flow_control` of the node, a Source: before each `iteration`, the runner waits
+    /// for its downstream nodes to grant it credits and calls `on_demand` with them.
+    pub(crate) fn with_flow_control(mut self, flow_control: Option<Arc<FlowControl>>) -> Self {
+        self.flow_control = flow_control;
+        self
+    }
+
+    /// Sets the `link_queues` of the links starting from the outputs of the node: after each
+    /// `iteration`, `on_drop` is called for each output whose links dropped messages.
+    pub(crate) fn with_link_queues(mut self, link_queues: &[(PortId, Arc<LinkQueue>)]) -> Self {
+        for (port_id, queue) in link_queues {
+            self.link_queues
+                .entry(port_id.clone())
+                .or_default()
+                .push(queue.clone());
+        }
+        self
+    }
+
+    /// Sets the `warmup` shared by the outputs of the node: it is started when the node is started
+    /// and each `iteration` counts as an activation.
+    pub(crate) fn with_warmup(mut self, warmup: Option<Arc<WarmUp>>) -> Self {
+        self.warmup = warmup;
+        self
+    }
+
+    /// Sets the `watchdog` of the node, shared with its `Context`: an `iteration` during which the
+    /// node does not signal its progress for the deadline is interrupted and started again.
+    pub(crate) fn with_watchdog(mut self, watchdog: Option<Arc<Watchdog>>) -> Self {
+        self.watchdog = watchdog;
+        self
+    }
+
+    /// Sets the `events` of the instance: the error ending the task of the node, if any, is
+    /// emitted as a `NodeErrored` event.
+    pub(crate) fn with_events(mut self, events: Arc<InstanceEvents>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Sets the `control` of the node: the control messages delivered to it are handled, through
+    /// `on_control`, in between two polls of the current `iteration`.
+    pub(crate) fn with_control(mut self, control: Option<Arc<NodeControl>>) -> Self {
+        self.control = control;
+        self
+    }
+
+    /// Sets the `keyed_state` of the Operator: its partitions are saved along with the state of the
+    /// Operator for a checkpoint barrier.
+    pub(crate) fn with_keyed_state(mut self, keyed_state: Option<Arc<KeyedState>>) -> Self {
+        self.keyed_state = keyed_state;
+        self
+    }
+
     /// Start the `Runner`, spawning an abortable task.
     ///
+    /// If the node panics during an `iteration`, the panic is caught and the task ends as if the node
+    /// had returned an error. The same goes if an `iteration` lasts longer than the
+    /// `max_run_duration` of the node, if any: the iteration is interrupted.
+    ///
+    /// If the node is a flow controlled Source, each `iteration` is preceded by a call to
+    /// `on_demand`, once its downstream nodes can accept at least one message.
+    ///
+    /// After an `iteration`, if messages sent by the node were dropped by the queues of its links,
+    /// `on_drop` is called for each output concerned.
+    ///
+    /// If the node has a warm-up, it starts again and the data the node sends are discarded until it
+    /// is over.
+    ///
+    /// The timers of the node are served by the same task: `on_timer` is never called concurrently
+    /// with a poll of `iteration`, and it is subject to the same panic and duration checks.
+    ///
+    /// The same goes for the control messages delivered to the node and `on_control`. A barrier is
+    /// then forwarded on the outputs of the node; a barrier broadcast to a Source is only handled
+    /// once its current `iteration` is over. For a checkpoint barrier, the node is checkpointed
+    /// instead of `on_control` being called.
+    ///
+    /// If the node has a watchdog, an `iteration` (including the timers served meanwhile) that is
+    /// hung, or that panicked, is interrupted and started again, until the node hangs or panics more
+    /// times in a row than the watchdog allows: the task then ends with a `NodeHung`, respectively
+    /// `NodePanic`, error.
+    ///
+    /// If the task ends with an error and the runner has the `events` of the instance, a
+    /// `NodeErrored` event is emitted.
+    ///
     /// `start` is idempotent and will do nothing if the node is already running.
     pub(crate) fn start(&mut self) {
         if self.is_running() {
@@ -67,24 +347,191 @@ impl Runner {
         }
 
         let node = self.node.clone();
+        let node_id = self.node_id.clone();
+        let max_run_duration = self.max_run_duration;
+        let timers = self.timers.clone();
+        let flow_control = self.flow_control.clone();
+        let link_queues = self.link_queues.clone();
+        let warmup = self.warmup.clone();
+        let watchdog = self.watchdog.clone();
+        let events = self.events.clone();
+        let control = self.control.clone();
+        let keyed_state = self.keyed_state.clone();
+        let errored = move |node_id: &NodeId, e: Error| -> Error {
+            log::error!("Iteration error: {:?}", e);
+            if let Some(events) = &events {
+                events.emit(
+                    node_id,
+                    InstanceEventKind::NodeErrored {
+                        error: e.to_string(),
+                    },
+                );
+            }
+            e
+        };
+        if let Some(warmup) = &warmup {
+            warmup.start();
+        }
         let run_loop = async move {
+            let count_dropped = |queues: &Vec<Arc<LinkQueue>>| -> u64 {
+                queues.iter().map(|queue| queue.dropped()).sum()
+            };
+            // Only the messages dropped while the node is running are reported.
+            let mut dropped: HashMap<&PortId, u64> = link_queues
+                .iter()
+                .map(|(port_id, queues)| (port_id, count_dropped(queues)))
+                .collect();
+            let mut timers = match &timers {
+                Some(timers) => Some(timers.lock().await),
+                None => None,
+            };
+            // The barriers broadcast to a Source, handled once its iteration is over.
+            let mut barriers: Vec<ControlMarker> = Vec::new();
             let mut instant: Instant;
+            // The iterations restarted in a row, after the node hung or panicked.
+            let mut restarts = 0;
             loop {
                 instant = Instant::now();
                 log::trace!("Iteration start: {:?}", instant);
-                if let Err(e) = node.iteration().await {
-                    log::error!("Iteration error: {:?}", e);
-                    return e;
+                let iteration = async {
+                    if let Some(flow_control) = &flow_control {
+                        let credits = flow_control.wait_credits().await;
+                        bounded(&node_id, max_run_duration, node.on_demand(credits)).await?;
+                    }
+                    bounded(&node_id, max_run_duration, node.iteration()).await
+                };
+                let step = async {
+                    if timers.is_none() && control.is_none() {
+                        return iteration.await;
+                    }
+
+                    futures::pin_mut!(iteration);
+                    loop {
+                        let wake = next_wake(timers.as_deref_mut(), control.as_deref());
+                        let wake = match future::select(iteration.as_mut(), Box::pin(wake)).await {
+                            Either::Left((result, _)) => break result,
+                            Either::Right((wake, _)) => wake,
+                        };
+                        let (marker, barrier) = match wake {
+                            Wake::Timer(token) => {
+                                log::trace!("Timer {} of < {} > fired", token, node_id);
+                                let on_timer = node.on_timer(token);
+                                match bounded(&node_id, max_run_duration, on_timer).await {
+                                    Ok(()) => continue,
+                                    Err(e) => break Err(e),
+                                }
+                            }
+                            Wake::Control(Control::Barrier(marker)) => {
+                                barriers.push(marker);
+                                continue;
+                            }
+                            Wake::Control(Control::Message(marker)) => (marker, false),
+                            Wake::Control(Control::Aligned(marker)) => (marker, true),
+                        };
+                        let result = handle_control(
+                            &node,
+                            &node_id,
+                            max_run_duration,
+                            control.as_deref(),
+                            keyed_state.as_deref(),
+                            &marker,
+                            barrier,
+                        );
+                        if let Err(e) = result.await {
+                            break Err(e);
+                        }
+                    }
+                };
+                let result = match &watchdog {
+                    Some(watchdog) => {
+                        watchdog.heartbeat();
+                        let hung = watchdog.hung(&node_id, instant);
+                        match future::select(Box::pin(step), Box::pin(hung)).await {
+                            Either::Left((Err(e), _)) if is_panic(&e) => {
+                                restarts += 1;
+                                if restarts > watchdog.descriptor.restarts {
+                                    Err(e)
+                                } else {
+                                    log::warn!(
+                                        "[Watchdog: {node_id}] {e}, restarting the iteration ({restarts}/{})",
+                                        watchdog.descriptor.restarts
+                                    );
+                                    continue;
+                                }
+                            }
+                            Either::Left((result, _)) => {
+                                restarts = 0;
+                                result
+                            }
+                            Either::Right(_) => {
+                                restarts += 1;
+                                let deadline = watchdog.descriptor.deadline;
+                                if restarts > watchdog.descriptor.restarts {
+                                    Err(zferror!(
+                                        ErrorKind::NodeHung(node_id.clone(), deadline),
+                                        "< {} > hung {} times in a row, giving up",
+                                        node_id,
+                                        restarts
+                                    )
+                                    .into())
+                                } else {
+                                    log::warn!(
+                                        "[Watchdog: {node_id}] No progress for {deadline:?}, restarting the iteration ({restarts}/{})",
+                                        watchdog.descriptor.restarts
+                                    );
+                                    continue;
+                                }
+                            }
+                        }
+                    }
+                    None => step.await,
+                };
+
+                if let Err(e) = result.node_context(&node_id) {
+                    return errored(&node_id, e);
+                }
+
+                for marker in barriers.drain(..) {
+                    let result = handle_control(
+                        &node,
+                        &node_id,
+                        max_run_duration,
+                        control.as_deref(),
+                        keyed_state.as_deref(),
+                        &marker,
+                        true,
+                    );
+                    if let Err(e) = result.await.node_context(&node_id) {
+                        return errored(&node_id, e);
+                    }
+                }
+
+                if let Some(warmup) = &warmup {
+                    warmup.activated();
+                }
+
+                for (port_id, queues) in link_queues.iter() {
+                    let total = count_dropped(queues);
+                    let previous = dropped.insert(port_id, total).unwrap_or(0);
+                    if total > previous {
+                        let on_drop = node.on_drop(port_id, total - previous);
+                        if let Err(e) = bounded(&node_id, max_run_duration, on_drop)
+                            .await
+                            .node_context(&node_id)
+                        {
+                            return errored(&node_id, e);
+                        }
+                    }
                 }
 
                 log::trace!("iteration took: {}ms", instant.elapsed().as_millis());
 
-                async_std::task::yield_now().await;
+                crate::executor::yield_now().await;
             }
         };
 
         let (abort_handle, abort_registration) = AbortHandle::new_pair();
-        let handle = async_std::task::spawn(Abortable::new(run_loop, abort_registration));
+        let handle = crate::executor::spawn(Abortable::new(run_loop, abort_registration));
 
         self.run_loop_handle = Some(handle);
         self.run_loop_abort_handle = Some(abort_handle);
@@ -121,3 +568,7 @@ impl Runner {
         self.run_loop_handle.is_some()
     }
 }
+
+#[cfg(test)]
+#[path = "./tests/runner-tests.rs"]
+mod tests;
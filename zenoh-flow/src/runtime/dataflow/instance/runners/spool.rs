@@ -0,0 +1,170 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::model::descriptor::SpoolDescriptor;
+use crate::Result;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// A message stored in a [Spool]: the number of its file, its size and when it was spooled.
+struct SpoolEntry {
+    index: u64,
+    bytes: usize,
+    spooled: Instant,
+}
+
+/// The spool, on disk, of a [ZenohSender](super::connector::ZenohSender): the frames it could not
+/// publish during an outage, one file per frame, forwarded in order once the connectivity returns.
+///
+/// The frames beyond the size limit or older than the age limit of the spool are dropped, the
+/// oldest first.
+pub(crate) struct Spool {
+    directory: PathBuf,
+    max_size: Option<usize>,
+    max_age: Option<Duration>,
+    entries: VecDeque<SpoolEntry>,
+    bytes: usize,
+    next: u64,
+}
+
+impl Spool {
+    /// Opens the spool `name` in the directory of the `descriptor`.
+    ///
+    /// The spool is named after a sender, itself named after the instance: a spool left over by a
+    /// previous execution of the same sender holds frames its receivers no longer expect, it is
+    /// emptied.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the directory of the spool could not be created.
+    pub(crate) fn open(descriptor: &SpoolDescriptor, name: &str) -> Result<Self> {
+        let directory = descriptor.directory.join(name);
+        if directory.exists() {
+            std::fs::remove_dir_all(&directory)?;
+        }
+        std::fs::create_dir_all(&directory)?;
+
+        Ok(Self {
+            directory,
+            max_size: descriptor.max_size,
+            max_age: descriptor.max_age,
+            entries: VecDeque::default(),
+            bytes: 0,
+            next: 0,
+        })
+    }
+
+    fn path(&self, index: u64) -> PathBuf {
+        self.directory.join(format!("{index:020}"))
+    }
+
+    /// Returns `true` if the spool holds no frame.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the number of frames in the spool.
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns the total size of the frames in the spool.
+    pub(crate) fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Writes the `frame` at the back of the spool and returns the number of frames dropped to stay
+    /// within its limits.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the frame could not be written, it is then not spooled.
+    pub(crate) fn push(&mut self, frame: &[u8]) -> Result<usize> {
+        std::fs::write(self.path(self.next), frame)?;
+        self.entries.push_back(SpoolEntry {
+            index: self.next,
+            bytes: frame.len(),
+            spooled: Instant::now(),
+        });
+        self.next += 1;
+        self.bytes += frame.len();
+
+        let mut dropped = self.expire();
+        if let Some(max_size) = self.max_size {
+            while self.bytes > max_size && self.pop_front() {
+                dropped += 1;
+            }
+        }
+        Ok(dropped)
+    }
+
+    /// Drops the frames older than the age limit of the spool and returns how many were.
+    pub(crate) fn expire(&mut self) -> usize {
+        let max_age = match self.max_age {
+            Some(max_age) => max_age,
+            None => return 0,
+        };
+
+        let mut dropped = 0;
+        while self
+            .entries
+            .front()
+            .map_or(false, |entry| entry.spooled.elapsed() > max_age)
+        {
+            self.pop_front();
+            dropped += 1;
+        }
+        dropped
+    }
+
+    /// Reads the frame at the front of the spool, if any.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the file of the frame could not be read.
+    pub(crate) fn front(&self) -> Option<Result<Vec<u8>>> {
+        self.entries
+            .front()
+            .map(|entry| Ok(std::fs::read(self.path(entry.index))?))
+    }
+
+    /// Removes the frame at the front of the spool, returning `false` if it was empty.
+    pub(crate) fn pop_front(&mut self) -> bool {
+        match self.entries.pop_front() {
+            Some(entry) => {
+                self.bytes -= entry.bytes;
+                if let Err(e) = std::fs::remove_file(self.path(entry.index)) {
+                    log::warn!("Failed to remove the spooled frame {}: {e:?}", entry.index);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes the spool from the disk, with the frames it still holds.
+    pub(crate) fn remove(self) {
+        if let Err(e) = std::fs::remove_dir_all(&self.directory) {
+            log::warn!(
+                "Failed to remove the spool < {} >: {e:?}",
+                self.directory.display()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/spool-tests.rs"]
+mod tests;
@@ -0,0 +1,268 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::{
+    check_data_type, data_encoding, parse_retransmission, split, unframe, LinkSequence,
+    LinkTraffic, Reassembly, Traffic,
+};
+use crate::model::descriptor::PortType;
+use crate::types::{LinkMessage, Payload, PayloadReference, PoolStatistics, INLINE_CAPACITY};
+use zenoh_flow_core::frame;
+
+#[test]
+fn test_frame_round_trip() {
+    let message = LinkMessage::Watermark(uhlc::HLC::default().new_timestamp());
+    let bytes = bincode::serialize(&message).unwrap();
+
+    let traffic = LinkTraffic::default();
+    let (sequence, unframed) = unframe(&frame(42, &bytes), &traffic).unwrap();
+    assert_eq!(sequence, 42);
+    assert!(matches!(unframed, LinkMessage::Watermark(_)));
+    assert_eq!(unframed, message);
+
+    assert!(unframe(&[0u8; 4], &traffic).is_err());
+}
+
+#[test]
+fn test_link_sequence_gaps() {
+    let sequence = LinkSequence::default();
+    assert_eq!(sequence.check(0), None);
+    assert_eq!(sequence.check(1), None);
+    assert_eq!(sequence.check(4), Some(2..4));
+    sequence.record_gap(2, 1);
+    assert_eq!((sequence.missed(), sequence.recovered()), (1, 1));
+
+    // The sender restarted: the tracking starts over.
+    assert_eq!(sequence.check(0), None);
+    assert_eq!(sequence.check(1), None);
+
+    // The receiver restarted: the next sequence number is accepted as is.
+    sequence.reset();
+    assert_eq!(sequence.check(10), None);
+    assert_eq!(sequence.check(11), None);
+}
+
+#[test]
+fn test_link_traffic() {
+    let traffic = LinkTraffic::default();
+    assert_eq!(traffic.get(), Traffic::default());
+
+    // A message sent in two fragments, then retransmitted.
+    traffic.record_bytes(64);
+    traffic.record_bytes(16);
+    traffic.record_message();
+    traffic.record_bytes(80);
+    assert_eq!(
+        traffic.get(),
+        Traffic {
+            messages: 1,
+            bytes: 160,
+            ..Default::default()
+        }
+    );
+
+    let mut total = traffic.get();
+    total += traffic.get();
+    assert_eq!(
+        total,
+        Traffic {
+            messages: 2,
+            bytes: 320,
+            ..Default::default()
+        }
+    );
+}
+
+// Small payloads are received inline, larger ones in the buffers of the pool once they are
+// released.
+#[test]
+fn test_received_payloads() {
+    let hlc = uhlc::HLC::default();
+    let traffic = LinkTraffic::default();
+    let receive = |payload: Vec<u8>| {
+        let message =
+            LinkMessage::from_payload(Payload::Bytes(payload.into()), hlc.new_timestamp());
+        let (_, received) =
+            unframe(&frame(0, &bincode::serialize(&message).unwrap()), &traffic).unwrap();
+        match received {
+            LinkMessage::Data(data_message) => data_message.data,
+            _ => panic!("Unexpected message"),
+        }
+    };
+
+    let small = receive(vec![1u8; INLINE_CAPACITY]);
+    assert!(matches!(small, Payload::Inline(_)));
+    assert_eq!(*small.try_as_bytes().unwrap(), vec![1u8; INLINE_CAPACITY]);
+
+    let large = receive(vec![2u8; 1024]);
+    assert!(matches!(&large, Payload::Bytes(bytes) if **bytes == vec![2u8; 1024]));
+    drop(large);
+    assert!(matches!(receive(vec![3u8; 512]), Payload::Bytes(_)));
+
+    let received = traffic.get();
+    assert_eq!(received.inlined, 1);
+    assert_eq!(received.pool, PoolStatistics { hits: 1, misses: 1 });
+}
+
+#[test]
+fn test_parse_retransmission() {
+    assert_eq!(
+        parse_retransmission("zf/data/flow/uuid/1/2/retransmit/3/7"),
+        Some(3..7)
+    );
+    assert_eq!(
+        parse_retransmission("zf/data/flow/uuid/1/2/retransmit/3"),
+        None
+    );
+    assert_eq!(
+        parse_retransmission("zf/data/flow/uuid/1/2/other/3/7"),
+        None
+    );
+}
+
+// The messages built with `zenoh-flow-core`, e.g. on a microcontroller, must be received as if they
+// were sent by a daemon, and conversely.
+#[test]
+fn test_core_wire_compatibility() {
+    let hlc = uhlc::HLC::default();
+    let timestamp = hlc.new_timestamp();
+    let event_time = hlc.new_timestamp();
+    let core_timestamp = |timestamp: uhlc::Timestamp| {
+        zenoh_flow_core::Timestamp::new(
+            timestamp.get_time().as_u64(),
+            timestamp.get_id().as_slice(),
+        )
+        .unwrap()
+    };
+
+    let messages = [
+        (
+            LinkMessage::from_payload_with_event_time(
+                Payload::from(vec![1u8, 2, 3]),
+                timestamp,
+                Some(event_time),
+            )
+            .with_variant(Some(2)),
+            zenoh_flow_core::LinkMessage::Data {
+                payload: zenoh_flow_core::Payload::Bytes(vec![1, 2, 3]),
+                timestamp: core_timestamp(timestamp),
+                event_time: Some(core_timestamp(event_time)),
+                variant: Some(2),
+            },
+        ),
+        (
+            LinkMessage::from_payload(
+                Payload::from(PayloadReference::new("sensors/lidar")),
+                timestamp,
+            ),
+            zenoh_flow_core::LinkMessage::Data {
+                payload: zenoh_flow_core::Payload::Reference("sensors/lidar".into()),
+                timestamp: core_timestamp(timestamp),
+                event_time: None,
+                variant: None,
+            },
+        ),
+        (
+            LinkMessage::EndOfStream(timestamp),
+            zenoh_flow_core::LinkMessage::EndOfStream(core_timestamp(timestamp)),
+        ),
+    ];
+
+    let traffic = LinkTraffic::default();
+    for (message, core_message) in messages {
+        let bytes = bincode::serialize(&message).unwrap();
+        assert_eq!(bytes, core_message.encode());
+        assert_eq!(
+            zenoh_flow_core::LinkMessage::decode(&bytes).unwrap(),
+            core_message
+        );
+
+        let (_, received) = unframe(&frame(3, &core_message.encode()), &traffic).unwrap();
+        assert_eq!(received, message);
+    }
+}
+
+#[test]
+fn test_reassembly() {
+    let hlc = uhlc::HLC::default();
+    let message = LinkMessage::from_payload(vec![7u8; 1000].into(), hlc.new_timestamp());
+    let bytes = bincode::serialize(&message).unwrap();
+    let fragments = split(&frame(3, &bytes), 128).unwrap();
+    assert!(fragments.len() > 1);
+    assert!(fragments.iter().all(|fragment| fragment.len() <= 128));
+
+    // The fragments can arrive out of order, and several times.
+    let traffic = LinkTraffic::default();
+    let mut reassembly = Reassembly::new(std::time::Duration::from_secs(10));
+    let (first, others) = fragments.split_first().unwrap();
+    for fragment in others {
+        assert!(reassembly.push(fragment, &traffic).unwrap().is_none());
+    }
+    assert!(reassembly.push(&others[0], &traffic).unwrap().is_none());
+    let (sequence, reassembled) = reassembly.push(first, &traffic).unwrap().unwrap();
+    assert_eq!(sequence, 3);
+    assert_eq!(reassembled, message);
+
+    // A message that fits in a single fragment is returned right away.
+    let watermark = LinkMessage::Watermark(hlc.new_timestamp());
+    let fragments = split(&frame(4, &bincode::serialize(&watermark).unwrap()), 128).unwrap();
+    assert_eq!(fragments.len(), 1);
+    assert_eq!(
+        reassembly.push(&fragments[0], &traffic).unwrap(),
+        Some((4, watermark))
+    );
+
+    assert!(reassembly.push(&[0u8; 4], &traffic).is_err());
+}
+
+#[test]
+fn test_reassembly_timeout() {
+    let message =
+        LinkMessage::from_payload(vec![7u8; 1000].into(), uhlc::HLC::default().new_timestamp());
+    let fragments = split(&frame(0, &bincode::serialize(&message).unwrap()), 128).unwrap();
+
+    let traffic = LinkTraffic::default();
+    let mut reassembly = Reassembly::new(std::time::Duration::from_millis(10));
+    assert!(reassembly.push(&fragments[0], &traffic).unwrap().is_none());
+    std::thread::sleep(std::time::Duration::from_millis(20));
+
+    // The incomplete message was discarded: the remaining fragments do not complete it.
+    for fragment in &fragments[1..] {
+        assert!(reassembly.push(fragment, &traffic).unwrap().is_none());
+    }
+}
+
+#[test]
+fn test_check_data_type() {
+    let detection = |version: &str| {
+        format!("my.company.Detection@{version}")
+            .parse::<PortType>()
+            .unwrap()
+    };
+
+    let encoding = data_encoding(Some(&detection("1.3")));
+    assert!(check_data_type(Some(&detection("1.0")), &encoding).is_ok());
+    assert!(check_data_type(None, &encoding).is_ok());
+    assert!(check_data_type(Some(&detection("2.0")), &encoding).is_err());
+
+    // A sender that does not declare the data type of its output is trusted.
+    assert!(check_data_type(Some(&detection("2.0")), &data_encoding(None)).is_ok());
+
+    // The variants of a union are checked in order.
+    let union = |variants: &str| variants.parse::<PortType>().unwrap();
+    let encoding = data_encoding(Some(&union("[Temperature@1.2|Humidity@1.0]")));
+    assert!(check_data_type(Some(&union("[Temperature@1.0|Humidity@1.1]")), &encoding).is_ok());
+    assert!(check_data_type(Some(&union("[Humidity@1.0|Temperature@1.2]")), &encoding).is_err());
+    assert!(check_data_type(Some(&union("Temperature@1.2")), &encoding).is_err());
+}
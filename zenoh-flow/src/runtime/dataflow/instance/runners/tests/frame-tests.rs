@@ -0,0 +1,165 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::{decompress, Frame};
+use crate::traits::SendSyncAny;
+use crate::types::{LinkMessage, Payload, PayloadReference, SerializerPool};
+use std::sync::Arc;
+use zenoh_flow_core::{frame, unframe, COMPRESSED_TAG, SEQUENCE_SIZE};
+
+// A frame is encoded exactly as the message serialized in a single buffer.
+#[test]
+fn test_frame_encoding() {
+    let hlc = uhlc::HLC::default();
+    let pool = SerializerPool::new(4);
+    let serializer = |buffer: &mut Vec<u8>, data: Arc<dyn SendSyncAny>| -> crate::Result<()> {
+        let value = data.as_any().downcast_ref::<u64>().unwrap();
+        buffer.extend_from_slice(&value.to_le_bytes());
+        Ok(())
+    };
+
+    let messages = [
+        LinkMessage::from_payload_with_event_time(
+            vec![1u8, 2, 3].into(),
+            hlc.new_timestamp(),
+            Some(hlc.new_timestamp()),
+        ),
+        LinkMessage::from_payload(vec![6u8].into(), hlc.new_timestamp()).with_variant(Some(1)),
+        LinkMessage::from_payload(Vec::new().into(), hlc.new_timestamp()),
+        LinkMessage::from_payload(Payload::Bytes(Arc::new(vec![4u8, 5])), hlc.new_timestamp()),
+        LinkMessage::from_payload(
+            Payload::Typed((
+                Arc::new(42u64) as Arc<dyn SendSyncAny>,
+                Arc::new(serializer),
+            )),
+            hlc.new_timestamp(),
+        ),
+        LinkMessage::from_payload(
+            PayloadReference::new("sensors/lidar").into(),
+            hlc.new_timestamp(),
+        ),
+        LinkMessage::Watermark(hlc.new_timestamp()),
+    ];
+
+    let (mut message_buffer, mut payload_buffer) = (Vec::new(), Vec::new());
+    for message in messages {
+        let encoded = Frame::encode(7, &message, &pool).unwrap();
+        message
+            .serialize_bincode_into(&mut message_buffer, &mut payload_buffer)
+            .unwrap();
+
+        assert_eq!(encoded.to_vec(), frame(7, &message_buffer));
+        assert_eq!(encoded.len(), encoded.to_vec().len());
+    }
+}
+
+// The payload of the message is shared with the frame, and with its clones.
+#[test]
+fn test_frame_shares_payload() {
+    let hlc = uhlc::HLC::default();
+    let pool = SerializerPool::new(4);
+    let payload = Arc::new(vec![0u8; 4096]);
+    let message = LinkMessage::from_payload(Payload::Bytes(payload.clone()), hlc.new_timestamp());
+
+    let encoded = Frame::encode(0, &message, &pool).unwrap();
+    let kept = encoded.clone();
+    assert_eq!(Arc::strong_count(&payload), 4);
+
+    drop(message);
+    drop(encoded);
+    drop(kept);
+    assert_eq!(Arc::strong_count(&payload), 1);
+}
+
+// A frame is only compressed from the minimum size, and if it shrinks: the receivers then get the
+// message back.
+#[test]
+fn test_frame_compression() {
+    let hlc = uhlc::HLC::default();
+    let pool = SerializerPool::new(4);
+    let message = LinkMessage::from_payload(vec![7u8; 4096].into(), hlc.new_timestamp());
+    let encoded = Frame::encode(3, &message, &pool).unwrap();
+    let bytes = encoded.to_vec();
+
+    let kept = encoded.clone().compress(bytes.len() + 1);
+    assert_eq!(kept.to_vec(), bytes);
+
+    let compressed = encoded.compress(1024);
+    assert!(compressed.len() < bytes.len());
+    let compressed = compressed.to_vec();
+    let (sequence, compressed_message) = unframe(&compressed).unwrap();
+    assert_eq!(sequence, 3);
+    assert_eq!(
+        decompress(compressed_message).unwrap().unwrap(),
+        bytes[SEQUENCE_SIZE..].to_vec()
+    );
+
+    // A corrupted size is not allocated.
+    let tag = COMPRESSED_TAG.to_le_bytes().len();
+    let mut corrupted = compressed_message.to_vec();
+    corrupted[tag..tag + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(decompress(&corrupted).is_err());
+
+    // A message that does not compress is sent as is.
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let random = (0..4096)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect::<Vec<_>>();
+    let message = LinkMessage::from_payload(random.into(), hlc.new_timestamp());
+    let encoded = Frame::encode(4, &message, &pool).unwrap();
+    let bytes = encoded.to_vec();
+    assert_eq!(encoded.compress(0).to_vec(), bytes);
+    assert!(decompress(&bytes[SEQUENCE_SIZE..]).unwrap().is_none());
+}
+
+// A compressed frame is decoded by `zenoh-flow-core`, e.g. on a microcontroller, as the message it
+// contains.
+#[test]
+fn test_frame_compression_core() {
+    let hlc = uhlc::HLC::default();
+    let pool = SerializerPool::new(4);
+    let timestamp = hlc.new_timestamp();
+    let message = LinkMessage::from_payload(vec![7u8; 4096].into(), timestamp);
+
+    let compressed = Frame::encode(5, &message, &pool)
+        .unwrap()
+        .compress(1024)
+        .to_vec();
+    let (sequence, bytes) = unframe(&compressed).unwrap();
+    assert_eq!(sequence, 5);
+    assert_eq!(
+        bytes[..COMPRESSED_TAG.to_le_bytes().len()],
+        COMPRESSED_TAG.to_le_bytes()
+    );
+
+    let core_timestamp = zenoh_flow_core::Timestamp::new(
+        timestamp.get_time().as_u64(),
+        timestamp.get_id().as_slice(),
+    )
+    .unwrap();
+    assert_eq!(
+        zenoh_flow_core::LinkMessage::decode(bytes).unwrap(),
+        zenoh_flow_core::LinkMessage::Data {
+            payload: zenoh_flow_core::Payload::Bytes(vec![7u8; 4096]),
+            timestamp: core_timestamp,
+            event_time: None,
+            variant: None,
+        }
+    );
+}
@@ -0,0 +1,288 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::model::descriptor::WatchdogDescriptor;
+use crate::runtime::dataflow::instance::runners::timers::{TimerScheduler, Timers};
+use crate::runtime::dataflow::instance::runners::watchdog::Watchdog;
+use crate::runtime::dataflow::instance::runners::{catch_panic, Runner};
+use crate::traits::Node;
+use crate::types::NodeId;
+use crate::zfresult::{ErrorKind, ZFError};
+use crate::{bail, Result};
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+struct PanickingNode {
+    panic: bool,
+}
+
+#[async_trait]
+impl Node for PanickingNode {
+    async fn iteration(&self) -> Result<()> {
+        if self.panic {
+            panic!("boom");
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_catch_panic() {
+    let node_id: NodeId = "panicking".into();
+    let node = PanickingNode { panic: false };
+    assert!(async_std::task::block_on(catch_panic(&node_id, node.iteration())).is_ok());
+
+    let node = PanickingNode { panic: true };
+    let error = async_std::task::block_on(catch_panic(&node_id, node.iteration()))
+        .expect_err("The panic should be converted into an error")
+        .downcast::<ZFError>()
+        .expect("Should be a ZFError");
+    assert_eq!(
+        error.get_kind(),
+        &ErrorKind::NodePanic(node_id, "boom".to_string())
+    );
+}
+
+#[test]
+fn test_runner_survives_panic() {
+    let node_id: NodeId = "panicking".into();
+    let mut runner = Runner::new(
+        node_id.clone(),
+        Arc::new(PanickingNode { panic: true }),
+        None,
+    );
+    runner.start();
+
+    let handle = runner
+        .run_loop_handle
+        .take()
+        .expect("The runner should be running");
+    let error = async_std::task::block_on(handle)
+        .expect("The run loop should not be aborted")
+        .downcast::<ZFError>()
+        .expect("Should be a ZFError");
+    assert_eq!(
+        error.get_kind(),
+        &ErrorKind::NodePanic(node_id, "boom".to_string())
+    );
+}
+
+#[test]
+fn test_release_profile_unwinds() {
+    // The tests are always built with `panic = "unwind"`: the profile used for the release builds of
+    // the runtime is checked instead.
+    let manifest = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/../Cargo.toml"))
+        .expect("The workspace manifest should be readable");
+    let release = manifest
+        .split("[profile.release]")
+        .nth(1)
+        .expect("The workspace should have a release profile");
+    let release = release.split("\n[").next().unwrap_or(release);
+    assert!(
+        !release.replace(' ', "").contains("panic=\"abort\""),
+        "The panics of the nodes can only be caught if they unwind"
+    );
+}
+
+struct CountingPanickingNode {
+    iterations: AtomicUsize,
+}
+
+#[async_trait]
+impl Node for CountingPanickingNode {
+    async fn iteration(&self) -> Result<()> {
+        self.iterations.fetch_add(1, Ordering::Relaxed);
+        panic!("boom");
+    }
+}
+
+#[test]
+fn test_runner_watchdog_panic() {
+    let node_id: NodeId = "panicking".into();
+    let node = Arc::new(CountingPanickingNode {
+        iterations: AtomicUsize::new(0),
+    });
+    let watchdog = Watchdog::new(WatchdogDescriptor {
+        deadline: Duration::from_secs(10),
+        restarts: 2,
+    });
+    let mut runner =
+        Runner::new(node_id.clone(), node.clone(), None).with_watchdog(Some(Arc::new(watchdog)));
+    runner.start();
+
+    let handle = runner
+        .run_loop_handle
+        .take()
+        .expect("The runner should be running");
+    let error = async_std::task::block_on(handle)
+        .expect("The run loop should not be aborted")
+        .downcast::<ZFError>()
+        .expect("Should be a ZFError");
+    assert_eq!(
+        error.get_kind(),
+        &ErrorKind::NodePanic(node_id, "boom".to_string())
+    );
+    // The first iteration and the two restarts.
+    assert_eq!(node.iterations.load(Ordering::Relaxed), 3);
+}
+
+struct SlowNode;
+
+#[async_trait]
+impl Node for SlowNode {
+    async fn iteration(&self) -> Result<()> {
+        async_std::task::sleep(Duration::from_secs(10)).await;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_runner_max_run_duration() {
+    let node_id: NodeId = "slow".into();
+    let max_run_duration = Duration::from_millis(10);
+    let mut runner = Runner::new(node_id.clone(), Arc::new(SlowNode), Some(max_run_duration));
+    runner.start();
+
+    let handle = runner
+        .run_loop_handle
+        .take()
+        .expect("The runner should be running");
+    let error = async_std::task::block_on(handle)
+        .expect("The run loop should not be aborted")
+        .downcast::<ZFError>()
+        .expect("Should be a ZFError");
+    assert_eq!(
+        error.get_kind(),
+        &ErrorKind::RunTimeout(node_id, max_run_duration)
+    );
+}
+
+struct TimerNode {
+    scheduler: TimerScheduler,
+}
+
+#[async_trait]
+impl Node for TimerNode {
+    async fn iteration(&self) -> Result<()> {
+        self.scheduler.schedule_in(Duration::from_millis(10), 42)?;
+        futures::future::pending().await
+    }
+
+    async fn on_timer(&self, token: u64) -> Result<()> {
+        bail!(ErrorKind::GenericError, "Timer {}", token)
+    }
+}
+
+#[test]
+fn test_runner_on_timer() {
+    let node_id: NodeId = "timer".into();
+    let (scheduler, timers) = Timers::new(None);
+    let mut runner =
+        Runner::new(node_id, Arc::new(TimerNode { scheduler }), None).with_timers(timers);
+    runner.start();
+
+    let handle = runner
+        .run_loop_handle
+        .take()
+        .expect("The runner should be running");
+    let error = async_std::task::block_on(handle)
+        .expect("The run loop should not be aborted")
+        .downcast::<ZFError>()
+        .expect("Should be a ZFError");
+    assert_eq!(error.get_kind(), &ErrorKind::GenericError);
+    assert!(format!("{error:?}").contains("Timer 42"));
+}
+
+struct HungNode {
+    iterations: AtomicUsize,
+}
+
+#[async_trait]
+impl Node for HungNode {
+    async fn iteration(&self) -> Result<()> {
+        self.iterations.fetch_add(1, Ordering::Relaxed);
+        futures::future::pending().await
+    }
+}
+
+#[test]
+fn test_runner_watchdog_hung() {
+    let node_id: NodeId = "hung".into();
+    let deadline = Duration::from_millis(10);
+    let node = Arc::new(HungNode {
+        iterations: AtomicUsize::new(0),
+    });
+    let watchdog = Watchdog::new(WatchdogDescriptor {
+        deadline,
+        restarts: 2,
+    });
+    let mut runner =
+        Runner::new(node_id.clone(), node.clone(), None).with_watchdog(Some(Arc::new(watchdog)));
+    runner.start();
+
+    let handle = runner
+        .run_loop_handle
+        .take()
+        .expect("The runner should be running");
+    let error = async_std::task::block_on(handle)
+        .expect("The run loop should not be aborted")
+        .downcast::<ZFError>()
+        .expect("Should be a ZFError");
+    assert_eq!(error.get_kind(), &ErrorKind::NodeHung(node_id, deadline));
+    // The first iteration and the two restarts.
+    assert_eq!(node.iterations.load(Ordering::Relaxed), 3);
+}
+
+struct BusyNode {
+    watchdog: Arc<Watchdog>,
+}
+
+#[async_trait]
+impl Node for BusyNode {
+    async fn iteration(&self) -> Result<()> {
+        for _ in 0..10 {
+            async_std::task::sleep(Duration::from_millis(5)).await;
+            self.watchdog.heartbeat();
+        }
+        bail!(ErrorKind::GenericError, "Done")
+    }
+}
+
+#[test]
+fn test_runner_watchdog_slow() {
+    let node_id: NodeId = "busy".into();
+    let watchdog = Arc::new(Watchdog::new(WatchdogDescriptor {
+        deadline: Duration::from_millis(20),
+        restarts: 0,
+    }));
+    let node = BusyNode {
+        watchdog: watchdog.clone(),
+    };
+    let mut runner = Runner::new(node_id, Arc::new(node), None).with_watchdog(Some(watchdog));
+    runner.start();
+
+    // The iteration lasts longer than the deadline but the node signals its progress: it is not
+    // interrupted.
+    let handle = runner
+        .run_loop_handle
+        .take()
+        .expect("The runner should be running");
+    let error = async_std::task::block_on(handle)
+        .expect("The run loop should not be aborted")
+        .downcast::<ZFError>()
+        .expect("Should be a ZFError");
+    assert_eq!(error.get_kind(), &ErrorKind::GenericError);
+}
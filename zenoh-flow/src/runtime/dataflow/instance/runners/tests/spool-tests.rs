@@ -0,0 +1,80 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::Spool;
+use crate::model::descriptor::SpoolDescriptor;
+use std::time::Duration;
+use uuid::Uuid;
+
+fn descriptor(max_size: Option<usize>, max_age: Option<Duration>) -> SpoolDescriptor {
+    SpoolDescriptor {
+        directory: std::env::temp_dir().join(format!("zenoh-flow-spool-{}", Uuid::new_v4())),
+        max_size,
+        max_age,
+    }
+}
+
+#[test]
+fn test_spool_order_and_size_limit() {
+    let descriptor = descriptor(Some(6), None);
+    let mut spool = Spool::open(&descriptor, "sender").expect("Failed to open the spool");
+    assert!(spool.is_empty());
+
+    assert_eq!(spool.push(&[1, 1]).unwrap(), 0);
+    assert_eq!(spool.push(&[2, 2]).unwrap(), 0);
+    assert_eq!(spool.push(&[3, 3]).unwrap(), 0);
+    // The oldest frame is dropped to stay within 6 bytes.
+    assert_eq!(spool.push(&[4, 4]).unwrap(), 1);
+    assert_eq!((spool.len(), spool.bytes()), (3, 6));
+
+    let mut forwarded = Vec::new();
+    while let Some(frame) = spool.front() {
+        forwarded.push(frame.expect("Failed to read the frame"));
+        assert!(spool.pop_front());
+    }
+    assert_eq!(forwarded, vec![vec![2, 2], vec![3, 3], vec![4, 4]]);
+    assert!(!spool.pop_front());
+
+    let directory = descriptor.directory.join("sender");
+    assert_eq!(std::fs::read_dir(&directory).unwrap().count(), 0);
+    spool.remove();
+    assert!(!directory.exists());
+    std::fs::remove_dir_all(&descriptor.directory).unwrap();
+}
+
+#[test]
+fn test_spool_age_limit_and_reopen() {
+    let descriptor = descriptor(None, Some(Duration::from_millis(50)));
+    let mut spool = Spool::open(&descriptor, "sender").expect("Failed to open the spool");
+
+    spool.push(&[1]).unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+    // The expired frame is dropped when a new one is spooled.
+    assert_eq!(spool.push(&[2]).unwrap(), 1);
+    assert_eq!(spool.front().unwrap().unwrap(), vec![2]);
+
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(spool.expire(), 1);
+    assert!(spool.is_empty());
+
+    // A spool left over by a previous execution is emptied.
+    spool.push(&[3]).unwrap();
+    let spool = Spool::open(&descriptor, "sender").expect("Failed to reopen the spool");
+    assert!(spool.is_empty());
+    let directory = descriptor.directory.join("sender");
+    assert_eq!(std::fs::read_dir(&directory).unwrap().count(), 0);
+
+    spool.remove();
+    std::fs::remove_dir_all(&descriptor.directory).unwrap();
+}
@@ -0,0 +1,49 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::runtime::dataflow::instance::runners::timers::Timers;
+use std::time::Duration;
+
+#[test]
+fn test_timers_fire_in_order() {
+    let (scheduler, mut timers) = Timers::new(None);
+    scheduler.schedule_in(Duration::from_millis(30), 3).unwrap();
+    scheduler.schedule_in(Duration::from_millis(10), 1).unwrap();
+    scheduler.schedule_in(Duration::from_millis(20), 2).unwrap();
+
+    async_std::task::block_on(async {
+        assert_eq!(timers.next().await, 1);
+        assert_eq!(timers.next().await, 2);
+        assert_eq!(timers.next().await, 3);
+    });
+}
+
+#[test]
+fn test_timers_scheduled_while_waiting() {
+    let (scheduler, mut timers) = Timers::new(None);
+    scheduler.schedule_in(Duration::from_secs(10), 10).unwrap();
+
+    async_std::task::block_on(async {
+        let schedule = async {
+            async_std::task::sleep(Duration::from_millis(10)).await;
+            scheduler.schedule_in(Duration::ZERO, 0).unwrap();
+            futures::future::pending::<()>().await;
+        };
+        futures::pin_mut!(schedule);
+        match futures::future::select(Box::pin(timers.next()), schedule).await {
+            futures::future::Either::Left((token, _)) => assert_eq!(token, 0),
+            futures::future::Either::Right(_) => unreachable!(),
+        }
+    });
+}
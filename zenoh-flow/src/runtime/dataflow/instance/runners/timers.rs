@@ -0,0 +1,152 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::runtime::simulation::SimulationClock;
+use crate::zfresult::ErrorKind;
+use crate::{zferror, Result};
+use futures::future::{self, Either};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::time::{Duration, Instant};
+
+/// The clock against which the deadlines of the timers are measured: the time elapsed since the
+/// creation of the timers or, in simulation mode, the simulated time.
+#[derive(Clone)]
+pub(crate) enum TimerClock {
+    Real(Instant),
+    Simulated(SimulationClock),
+}
+
+impl TimerClock {
+    pub(crate) fn new(simulation: Option<SimulationClock>) -> Self {
+        match simulation {
+            Some(clock) => TimerClock::Simulated(clock),
+            None => TimerClock::Real(Instant::now()),
+        }
+    }
+
+    pub(crate) fn now(&self) -> Duration {
+        match self {
+            TimerClock::Real(origin) => origin.elapsed(),
+            TimerClock::Simulated(clock) => clock.now(),
+        }
+    }
+
+    pub(crate) async fn sleep_until(&self, deadline: Duration) {
+        match self {
+            TimerClock::Real(origin) => {
+                let elapsed = origin.elapsed();
+                if deadline > elapsed {
+                    crate::executor::sleep(deadline - elapsed).await;
+                }
+            }
+            TimerClock::Simulated(clock) => clock.sleep_until(deadline).await,
+        }
+    }
+}
+
+/// The half of the timers of a node held by its [`Context`](crate::types::Context), to schedule
+/// them.
+#[derive(Clone)]
+pub(crate) struct TimerScheduler {
+    clock: TimerClock,
+    requests: flume::Sender<(Duration, u64)>,
+}
+
+impl TimerScheduler {
+    /// Schedules a timer, identified by `token`, that fires once `duration` has elapsed.
+    pub(crate) fn schedule_in(&self, duration: Duration, token: u64) -> Result<()> {
+        self.requests
+            .send((self.clock.now() + duration, token))
+            .map_err(|e| {
+                zferror!(
+                    ErrorKind::SendError,
+                    "Failed to schedule the timer {}: {:?}",
+                    token,
+                    e
+                )
+                .into()
+            })
+    }
+}
+
+/// The half of the timers of a node held by its [`Runner`](super::Runner), to wait for them.
+///
+/// The pending timers are kept across a stop and a start of the runner: a timer whose deadline
+/// passed while the node was stopped fires as soon as it is started again.
+pub(crate) struct Timers {
+    clock: TimerClock,
+    requests: flume::Receiver<(Duration, u64)>,
+    pending: BinaryHeap<Reverse<(Duration, u64)>>,
+}
+
+impl Timers {
+    /// Creates the timers of a node, measured in simulated time if a `simulation` clock is
+    /// provided.
+    pub(crate) fn new(simulation: Option<SimulationClock>) -> (TimerScheduler, Self) {
+        let clock = TimerClock::new(simulation);
+        let (tx, rx) = flume::unbounded();
+
+        (
+            TimerScheduler {
+                clock: clock.clone(),
+                requests: tx,
+            },
+            Self {
+                clock,
+                requests: rx,
+                pending: BinaryHeap::new(),
+            },
+        )
+    }
+
+    /// Waits for the next timer to fire and returns its token.
+    ///
+    /// This future can be dropped at any time without losing a timer.
+    pub(crate) async fn next(&mut self) -> u64 {
+        loop {
+            self.pending.extend(self.requests.try_iter().map(Reverse));
+
+            let deadline = match self.pending.peek() {
+                Some(Reverse((deadline, token))) => {
+                    if *deadline <= self.clock.now() {
+                        let token = *token;
+                        self.pending.pop();
+                        return token;
+                    }
+                    *deadline
+                }
+                None => match self.requests.recv_async().await {
+                    Ok(request) => {
+                        self.pending.push(Reverse(request));
+                        continue;
+                    }
+                    // No timer can be scheduled anymore.
+                    Err(_) => future::pending().await,
+                },
+            };
+
+            let sleep = Box::pin(self.clock.sleep_until(deadline));
+            match future::select(sleep, self.requests.recv_async()).await {
+                Either::Left(_) => {}
+                Either::Right((Ok(request), _)) => self.pending.push(Reverse(request)),
+                Either::Right((Err(_), sleep)) => sleep.await,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/timers-tests.rs"]
+mod tests;
@@ -0,0 +1,80 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::model::descriptor::WatchdogDescriptor;
+use crate::types::NodeId;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// The `Watchdog` of a node tracks the progress of its iterations: the runner signals when an
+/// iteration starts and the node, through its `Context`, while it computes.
+///
+/// It tells apart a node that is slow --- an iteration exceeding the deadline while the node
+/// signals its progress --- from a node that is hung (see [WatchdogDescriptor]).
+#[derive(Debug)]
+pub(crate) struct Watchdog {
+    pub(crate) descriptor: WatchdogDescriptor,
+    origin: Instant,
+    // The milliseconds elapsed between the origin and the last heartbeat.
+    last: AtomicU64,
+}
+
+impl Watchdog {
+    pub(crate) fn new(descriptor: WatchdogDescriptor) -> Self {
+        Self {
+            descriptor,
+            origin: Instant::now(),
+            last: AtomicU64::new(0),
+        }
+    }
+
+    /// Records that the node made progress.
+    pub(crate) fn heartbeat(&self) {
+        self.last
+            .store(self.origin.elapsed().as_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// Returns the time elapsed since the last heartbeat.
+    fn silence(&self) -> Duration {
+        self.origin
+            .elapsed()
+            .saturating_sub(Duration::from_millis(self.last.load(Ordering::Relaxed)))
+    }
+
+    /// Waits until the iteration of the node `node_id` that started at `started` is hung: no
+    /// heartbeat was received for the deadline.
+    ///
+    /// A warning is logged, once, if the iteration exceeds the deadline while heartbeats are
+    /// received.
+    pub(crate) async fn hung(&self, node_id: &NodeId, started: Instant) {
+        let deadline = self.descriptor.deadline;
+        let mut slow = false;
+        loop {
+            let silence = self.silence();
+            if silence >= deadline {
+                return;
+            }
+
+            if !slow && started.elapsed() >= deadline {
+                slow = true;
+                log::warn!(
+                    "[Watchdog: {node_id}] Iteration running for {:?}, slow but alive",
+                    started.elapsed()
+                );
+            }
+
+            crate::executor::sleep(deadline - silence).await;
+        }
+    }
+}
@@ -0,0 +1,479 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::control::Control;
+use super::runners::catch_panic;
+use super::{DataFlowInstance, DEFAULT_QUIESCENCE_TIMEOUT};
+use crate::model::descriptor::{InputDescriptor, OutputDescriptor};
+use crate::types::{ControlMarker, FlowId, LinkMessage, NodeId};
+use crate::zfresult::ErrorKind;
+use crate::{bail, zferror, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// The messages that were waiting in a link when a snapshot was taken.
+///
+/// The messages are serialized with `bincode`, as they would be sent to a node running on another
+/// daemon.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LinkSnapshot {
+    pub from: OutputDescriptor,
+    pub to: InputDescriptor,
+    pub messages: Vec<Vec<u8>>,
+}
+
+impl LinkSnapshot {
+    /// Serializes the `messages` waiting in the link going `from` an output `to` an input.
+    pub(crate) fn new(
+        from: OutputDescriptor,
+        to: InputDescriptor,
+        messages: &[LinkMessage],
+    ) -> Result<Self> {
+        let mut payload_buffer = Vec::default();
+        let messages = messages
+            .iter()
+            .map(|message| {
+                let mut message_buffer = Vec::default();
+                message.serialize_bincode_into(&mut message_buffer, &mut payload_buffer)?;
+                Ok(message_buffer)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { from, to, messages })
+    }
+
+    /// Deserializes the messages waiting in the link.
+    pub(crate) fn messages(&self) -> Result<Vec<LinkMessage>> {
+        self.messages
+            .iter()
+            .map(|message| {
+                bincode::deserialize::<LinkMessage>(message)
+                    .map_err(|e| zferror!(ErrorKind::DeserializationError, e).into())
+            })
+            .collect()
+    }
+}
+
+/// An `InstanceSnapshot` is a consistent view of a data flow instance: the states of its nodes, the
+/// partitions of the keyed states of its Operators and the messages that were waiting in its links
+/// (see
+/// [`DataFlowInstance::snapshot`](crate::runtime::dataflow::instance::DataFlowInstance::snapshot)
+/// and
+/// [`DataFlowInstance::checkpoint`](crate::runtime::dataflow::instance::DataFlowInstance::checkpoint)).
+///
+/// Each daemon involved in the deployment of an instance only takes a snapshot of the nodes it is
+/// responsible for. The snapshots of all the daemons can be combined with `merge` and the result
+/// restored on any set of daemons (see
+/// [`DataFlowInstance::restore`](crate::runtime::dataflow::instance::DataFlowInstance::restore)).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct InstanceSnapshot {
+    pub flow_id: FlowId,
+    pub instance_id: Uuid,
+    pub states: HashMap<NodeId, Vec<u8>>,
+    /// The partitions of the keyed state of each Operator, serialized, by the id of the Operator in
+    /// the descriptor: the copies of a replicated Operator save theirs under the same id (see
+    /// [`KeyedState`](crate::types::KeyedState)).
+    #[serde(default)]
+    pub partitions: HashMap<NodeId, HashMap<u32, Vec<u8>>>,
+    pub links: Vec<LinkSnapshot>,
+}
+
+impl InstanceSnapshot {
+    /// Adds to this snapshot the states, the partitions and the links of the snapshot taken by
+    /// another daemon.
+    ///
+    /// # Error
+    ///
+    /// An error is returned if the snapshots were not taken on the same flow.
+    pub fn merge(&mut self, other: InstanceSnapshot) -> Result<()> {
+        if self.flow_id != other.flow_id {
+            bail!(
+                ErrorKind::InvalidData,
+                "Cannot merge snapshots of different flows: < {} > and < {} >",
+                self.flow_id,
+                other.flow_id
+            )
+        }
+
+        self.states.extend(other.states);
+        for (id, partitions) in other.partitions {
+            self.partitions.entry(id).or_default().extend(partitions);
+        }
+        self.links.extend(other.links);
+        Ok(())
+    }
+
+    /// Writes the snapshot, serialized with `bincode`, in the file at `path`.
+    ///
+    /// # Error
+    ///
+    /// An error is returned if the snapshot could not be serialized or written.
+    pub fn to_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        let bytes =
+            bincode::serialize(self).map_err(|e| zferror!(ErrorKind::SerializationError, e))?;
+        std::fs::write(path, bytes)?;
+        Ok(())
+    }
+
+    /// Reads a snapshot from the file at `path`.
+    ///
+    /// # Error
+    ///
+    /// An error is returned if the file could not be read or if it does not contain a snapshot.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let bytes = std::fs::read(path)?;
+        bincode::deserialize(&bytes)
+            .map_err(|e| zferror!(ErrorKind::DeserializationError, e).into())
+    }
+}
+
+impl DataFlowInstance {
+    /// Takes a consistent snapshot of the part of this data flow instance running on the current
+    /// daemon: the state of each node (see [`Node::checkpoint`](crate::prelude::Node::checkpoint)), the partitions of the keyed state
+    /// of each Operator (see [KeyedState](crate::types::KeyedState)) and the messages waiting in the links between them.
+    ///
+    /// To obtain a consistent view, the nodes are stopped as in [`stop`](DataFlowInstance::stop):
+    /// the `Source`s first then, once the messages in flight were processed (or after
+    /// [DEFAULT_QUIESCENCE_TIMEOUT]), all the other nodes. The nodes are *not* restarted: the
+    /// instance is suspended and can either be stopped or resumed by starting its nodes again. The
+    /// messages waiting in the links are kept.
+    ///
+    /// CAVEAT: the snapshot only contains the nodes running on the current daemon; the snapshots
+    /// taken by all the daemons involved in the deployment of the instance can be combined with
+    /// [`InstanceSnapshot::merge`].
+    ///
+    /// # Error
+    ///
+    /// This method can return an error if a node could not be stopped or checkpointed, or if a
+    /// message could not be serialized.
+    pub async fn snapshot(&mut self) -> Result<InstanceSnapshot> {
+        let mut errors = Vec::new();
+
+        for id in self.get_sources() {
+            self.stop_runner(&id, &mut errors).await;
+        }
+
+        if !self.wait_quiescence(DEFAULT_QUIESCENCE_TIMEOUT).await {
+            log::warn!(
+                "[Instance: {}] Not quiescent after {:?}, messages in flight are saved",
+                self.uuid,
+                DEFAULT_QUIESCENCE_TIMEOUT
+            );
+        }
+
+        let ids = self.runners.keys().cloned().collect::<Vec<_>>();
+        for id in ids {
+            self.stop_runner(&id, &mut errors).await;
+        }
+
+        if !errors.is_empty() {
+            bail!(
+                ErrorKind::RunnerStopError,
+                "[Instance: {}] Encountered {} error(s) while stopping: {}",
+                self.uuid,
+                errors.len(),
+                errors.join(", ")
+            )
+        }
+
+        let mut states = HashMap::new();
+        for (id, runner) in self.runners.iter() {
+            if let Some(state) = catch_panic(id, runner.node.checkpoint()).await? {
+                states.insert(id.clone(), state);
+            }
+        }
+
+        // The copies of a replicated Operator save their partitions under the same id.
+        let mut partitions: HashMap<NodeId, HashMap<u32, Vec<u8>>> = HashMap::new();
+        for (id, keyed_state) in self.keyed_states.iter() {
+            let checkpoint = keyed_state.checkpoint()?;
+            if !checkpoint.is_empty() {
+                partitions
+                    .entry(self.data_flow.keyed_state_id(id))
+                    .or_default()
+                    .extend(checkpoint);
+            }
+        }
+
+        let mut links = Vec::with_capacity(self.channels.len());
+        for channel in self.channels.iter() {
+            let messages = channel.rx.drain();
+            links.push(LinkSnapshot::new(
+                channel.from.clone(),
+                channel.to.clone(),
+                &messages,
+            )?);
+
+            // The instance can be resumed: the messages are put back in the link.
+            for message in messages {
+                channel.tx.try_send(message).map_err(|e| {
+                    zferror!(
+                        ErrorKind::SendError,
+                        "[Instance: {}] Failed to put back a message on {} => {}: {:?}",
+                        self.uuid,
+                        channel.from,
+                        channel.to,
+                        e
+                    )
+                })?;
+            }
+        }
+
+        Ok(InstanceSnapshot {
+            flow_id: self.flow.clone(),
+            instance_id: self.uuid,
+            states,
+            partitions,
+            links,
+        })
+    }
+
+    /// Restores a `snapshot` (see [`snapshot`](DataFlowInstance::snapshot)) in this data flow
+    /// instance, before its nodes are started: the state of each node running on the current
+    /// daemon is restored (see [`Node::restore`](crate::prelude::Node::restore)), each Operator restores the partitions of its
+    /// keyed state it owns (see [KeyedState](crate::types::KeyedState)) and the messages that were waiting in the links
+    /// between them are sent again.
+    ///
+    /// The snapshot can be restored in another instance of the same flow, possibly deployed on
+    /// different daemons. The links are matched on the nodes and ports they connect: the messages
+    /// of the links that are not between two nodes running on the current daemon are ignored.
+    ///
+    /// CAVEAT: the messages that were waiting in the links leading to, or coming from, a connector
+    /// are only restored if the nodes are mapped to the same daemons.
+    ///
+    /// # Error
+    ///
+    /// This method can return an error if the snapshot was taken on another flow, if a node is
+    /// running, if a node could not restore its state or if a message could not be deserialized.
+    pub async fn restore(&mut self, snapshot: &InstanceSnapshot) -> Result<()> {
+        if snapshot.flow_id != self.flow {
+            bail!(
+                ErrorKind::InvalidData,
+                "[Instance: {}] The snapshot was taken on flow < {} >, not < {} >",
+                self.uuid,
+                snapshot.flow_id,
+                self.flow
+            )
+        }
+
+        if let Some((id, _)) = self.runners.iter().find(|(_, runner)| runner.is_running()) {
+            bail!(
+                ErrorKind::InvalidState,
+                "[Instance: {}] Cannot restore a snapshot while < {} > is running",
+                self.uuid,
+                id
+            )
+        }
+
+        for (id, runner) in self.runners.iter() {
+            if let Some(state) = snapshot.states.get(id) {
+                catch_panic(id, runner.node.restore(state)).await?;
+            }
+        }
+
+        for (id, keyed_state) in self.keyed_states.iter() {
+            if let Some(partitions) = snapshot.partitions.get(&self.data_flow.keyed_state_id(id)) {
+                let restored = keyed_state.restore(partitions)?;
+                log::debug!(
+                    "[Instance: {}] Restored {restored} partition(s) of the keyed state of < {id} >",
+                    self.uuid
+                );
+            }
+        }
+
+        for link in snapshot.links.iter() {
+            let channel = match self
+                .channels
+                .iter()
+                .find(|channel| channel.from == link.from && channel.to == link.to)
+            {
+                Some(channel) => channel,
+                None => {
+                    log::debug!(
+                        "[Instance: {}] Skipping link {} => {}: not on this daemon",
+                        self.uuid,
+                        link.from,
+                        link.to
+                    );
+                    continue;
+                }
+            };
+
+            for message in link.messages()? {
+                channel.tx.try_send(message).map_err(|e| {
+                    zferror!(
+                        ErrorKind::SendError,
+                        "[Instance: {}] Failed to restore a message on {} => {}: {:?}",
+                        self.uuid,
+                        link.from,
+                        link.to,
+                        e
+                    )
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Begins the checkpoint of `epoch` of this data flow instance, without stopping it: the marker
+    /// of a checkpoint barrier is delivered to the Sources running on the current daemon, once
+    /// their current `iteration` is over, and flows through the data flow --- across daemons as
+    /// well, through the connectors.
+    ///
+    /// Following Chandy and Lamport, each node checkpoints its state (see [`Node::checkpoint`](crate::prelude::Node::checkpoint)) and
+    /// the partitions of its keyed state (see [KeyedState](crate::types::KeyedState)) as soon as the first of its channels
+    /// delivered the marker, then forwards it on all its outputs. The messages delivered by its
+    /// other channels until they deliver the marker were in flight: they are recorded. The states
+    /// and the messages recorded form a consistent cut of the instance, collected with
+    /// [`checkpoint`](DataFlowInstance::checkpoint).
+    ///
+    /// The epochs of the successive checkpoints of an instance must increase: the marker of an
+    /// older checkpoint still flowing is ignored, the checkpoint is abandoned.
+    ///
+    /// CAVEATS:
+    /// - an instance spread over several daemons needs the checkpoint to begin, with the same
+    ///   `epoch`, on the daemons running its Sources,
+    /// - a node is only checkpointed once all its Sources, direct or not, are: a cycle in the data
+    ///   flow or an input that is never taken by its node stalls the checkpoint.
+    ///
+    /// # Error
+    ///
+    /// An error is returned if `epoch` is 0.
+    pub fn begin_checkpoint(&self, epoch: u64) -> Result<()> {
+        if epoch == 0 {
+            bail!(
+                ErrorKind::InvalidData,
+                "[Instance: {}] The epoch of a checkpoint cannot be 0",
+                self.uuid
+            )
+        }
+
+        let marker = ControlMarker::checkpoint(self._instance_context.hlc.new_timestamp(), epoch);
+        for (node_id, control) in &self.controls {
+            if self.source_constructors.contains_key(node_id) {
+                control.deliver(Control::Barrier(marker.clone()));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Waits, for at most `timeout`, until all the nodes of this data flow instance running on the
+    /// current daemon took their checkpoint of `epoch` (see
+    /// [`begin_checkpoint`](DataFlowInstance::begin_checkpoint)) and returns it as a snapshot:
+    /// their states, the partitions of the keyed states of the Operators and the messages that were
+    /// in flight in the links leading to them.
+    ///
+    /// The snapshot can be restored as one taken with [`snapshot`](DataFlowInstance::snapshot),
+    /// which stops the instance: the snapshots returned by all the daemons involved in the
+    /// deployment of the instance can be combined with [`InstanceSnapshot::merge`].
+    ///
+    /// # Error
+    ///
+    /// This method can return an error if a node did not take its checkpoint before the `timeout`
+    /// expired, if it failed to or if a message could not be serialized. The checkpoint is then
+    /// abandoned.
+    pub async fn checkpoint(&self, epoch: u64, timeout: Duration) -> Result<InstanceSnapshot> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            // Listening before checking: a checkpoint reported in between is not missed.
+            let reported = self.checkpoints.listen();
+            let missing = self.checkpoints.missing(epoch, self.controls.keys());
+            if missing.is_empty() {
+                break;
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if crate::executor::timeout(remaining, reported).await.is_err() {
+                self.checkpoints.take(epoch);
+                bail!(
+                    ErrorKind::Uncompleted,
+                    "[Instance: {}] Checkpoint {} not taken by {} node(s) after {:?}: {}",
+                    self.uuid,
+                    epoch,
+                    missing.len(),
+                    timeout,
+                    missing
+                        .iter()
+                        .map(|node_id| node_id.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            }
+        }
+
+        let mut states = HashMap::new();
+        let mut partitions: HashMap<NodeId, HashMap<u32, Vec<u8>>> = HashMap::new();
+        let mut links = Vec::new();
+        for (id, checkpoint) in self.checkpoints.take(epoch) {
+            let checkpoint = checkpoint.map_err(|e| {
+                zferror!(
+                    ErrorKind::InvalidState,
+                    "[Instance: {}] Checkpoint {} of < {} > failed: {}",
+                    self.uuid,
+                    epoch,
+                    id,
+                    e
+                )
+            })?;
+
+            if let Some(state) = checkpoint.state {
+                states.insert(id.clone(), state);
+            }
+            // The copies of a replicated Operator save their partitions under the same id.
+            if !checkpoint.partitions.is_empty() {
+                partitions
+                    .entry(self.data_flow.keyed_state_id(&id))
+                    .or_default()
+                    .extend(checkpoint.partitions);
+            }
+            for (receiver, messages) in checkpoint.links {
+                match self
+                    .channels
+                    .iter()
+                    .find(|channel| channel.rx.same_channel(&receiver))
+                {
+                    Some(channel) => links.push(LinkSnapshot::new(
+                        channel.from.clone(),
+                        channel.to.clone(),
+                        &messages,
+                    )?),
+                    None => log::warn!(
+                        "[Instance: {}] Checkpoint {}: {} message(s) in flight to < {} > on a removed link",
+                        self.uuid,
+                        epoch,
+                        messages.len(),
+                        id
+                    ),
+                }
+            }
+        }
+
+        Ok(InstanceSnapshot {
+            flow_id: self.flow.clone(),
+            instance_id: self.uuid,
+            states,
+            partitions,
+            links,
+        })
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/snapshot-tests.rs"]
+mod tests;
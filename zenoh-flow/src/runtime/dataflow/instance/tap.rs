@@ -0,0 +1,131 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::redaction::{redact, Redaction};
+use crate::types::{LinkMessage, Payload};
+use crate::zferror;
+use crate::zfresult::ErrorKind;
+use crate::Result;
+use flume::Receiver;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use zenoh::prelude::r#async::*;
+
+/// Publishes on `key_expr`, until the output is dropped, the messages copied by a debug tap.
+///
+/// If `decode` is false, the messages are serialized with `bincode`, exactly as they are sent to a
+/// node running on another daemon, and can be deserialized as a [LinkMessage]. Otherwise they are
+/// published as JSON (see [decode_message]).
+///
+/// The messages are first altered by the `redaction` rule, if any.
+pub(crate) async fn publish_tap(
+    session: Arc<Session>,
+    key_expr: String,
+    receiver: Receiver<LinkMessage>,
+    decode: bool,
+    redaction: Option<Redaction>,
+) {
+    let mut message_buffer = Vec::default();
+    let mut payload_buffer = Vec::default();
+
+    while let Ok(message) = receiver.recv_async().await {
+        let message = redact(redaction.as_ref(), message);
+        let res = if decode {
+            serde_json::to_vec(&decode_message(&message))
+                .map_err(|e| zferror!(ErrorKind::SerializationError, e).into())
+        } else {
+            message
+                .serialize_bincode_into(&mut message_buffer, &mut payload_buffer)
+                .map(|_| message_buffer.clone())
+        };
+
+        let value = match res {
+            Ok(value) => value,
+            Err(e) => {
+                log::error!("[Tap: {key_expr}] Failed to serialize a message: {e:?}");
+                continue;
+            }
+        };
+
+        if let Err(e) = session.put(&key_expr, value).res().await {
+            log::error!("[Tap: {key_expr}] Failed to publish a message: {e:?}");
+        }
+    }
+
+    log::debug!("[Tap: {key_expr}] Output dropped, stopping the tap");
+}
+
+/// Returns a human readable, JSON, version of the `message`.
+///
+/// The payload of a data message is, in order of preference, the JSON value it contains, the UTF-8
+/// string it contains or its raw bytes. A payload sent by reference is replaced by its key
+/// expression. The event time of a data message, if one was assigned, is added as `event_time`.
+///
+/// ```json
+/// { "kind": "data", "timestamp": "<timestamp>", "payload": { "frame": 1 } }
+/// ```
+pub(crate) fn decode_message(message: &LinkMessage) -> Value {
+    match message {
+        LinkMessage::Data(data_message) => {
+            let payload = match &**data_message {
+                Payload::Reference(reference) => json!({ "reference": reference.key_expr() }),
+                payload => match payload.try_as_bytes() {
+                    Ok(bytes) => decode_bytes(&bytes),
+                    Err(e) => json!({ "error": e.to_string() }),
+                },
+            };
+
+            let mut decoded = json!({
+                "kind": "data",
+                "timestamp": data_message.get_timestamp().to_string(),
+                "payload": payload,
+            });
+            if let Some(event_time) = data_message.get_event_time() {
+                decoded["event_time"] = json!(event_time.to_string());
+            }
+            decoded
+        }
+        LinkMessage::Watermark(timestamp) => {
+            json!({ "kind": "watermark", "timestamp": timestamp.to_string() })
+        }
+        LinkMessage::EndOfStream(timestamp) => {
+            json!({ "kind": "end-of-stream", "timestamp": timestamp.to_string() })
+        }
+        LinkMessage::Live(timestamp) => {
+            json!({ "kind": "live", "timestamp": timestamp.to_string() })
+        }
+        LinkMessage::Control(marker) => json!({
+            "kind": "control",
+            "timestamp": marker.get_timestamp().to_string(),
+            "payload": decode_bytes(marker.get_payload()),
+            "epoch": marker.get_epoch(),
+        }),
+    }
+}
+
+/// Interprets the `bytes` as JSON, then as a UTF-8 string and finally as raw bytes.
+fn decode_bytes(bytes: &[u8]) -> Value {
+    if let Ok(value) = serde_json::from_slice::<Value>(bytes) {
+        return value;
+    }
+
+    match std::str::from_utf8(bytes) {
+        Ok(string) => Value::String(string.to_string()),
+        Err(_) => json!(bytes),
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/tap-tests.rs"]
+mod tests;
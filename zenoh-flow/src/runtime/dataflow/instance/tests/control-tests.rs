@@ -0,0 +1,180 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::{Checkpoints, Control, NodeCheckpoint, NodeControl};
+use crate::io::{InputRaw, Outputs};
+use crate::types::{ControlMarker, LinkMessage, NodeId};
+use std::sync::Arc;
+use std::time::Duration;
+use uhlc::HLC;
+
+/// Returns the control of a node with a single output, whose receiver is returned, and an input
+/// with `channels` channels, whose senders are returned.
+fn node(
+    channels: usize,
+) -> (
+    Arc<NodeControl>,
+    InputRaw,
+    Vec<flume::Sender<LinkMessage>>,
+    flume::Receiver<LinkMessage>,
+) {
+    let (tx_output, rx_output) = flume::unbounded::<LinkMessage>();
+    let mut outputs = Outputs::new(Arc::new(HLC::default()));
+    outputs.insert("out".into(), tx_output.into(), None);
+    let control = Arc::new(NodeControl::new(
+        "node".into(),
+        &outputs,
+        Arc::new(Checkpoints::default()),
+    ));
+
+    let (senders, receivers): (Vec<_>, Vec<_>) = (0..channels)
+        .map(|_| {
+            let (tx, rx) = flume::unbounded::<LinkMessage>();
+            (tx, rx.into())
+        })
+        .unzip();
+    let mut input = InputRaw::new("in".into(), receivers, None);
+    control.register(input.channels_count());
+    input.control = Some(control.clone());
+
+    (control, input, senders, rx_output)
+}
+
+#[test]
+fn test_barrier_alignment() {
+    let hlc = HLC::default();
+    let (control, input, senders, rx_output) = node(2);
+    let marker = ControlMarker::new(hlc.new_timestamp(), Arc::new(vec![42]));
+    let before = hlc.new_timestamp();
+    let after = hlc.new_timestamp();
+
+    senders[0]
+        .send(LinkMessage::Control(marker.clone()))
+        .unwrap();
+    senders[0].send(LinkMessage::Watermark(after)).unwrap();
+    senders[1].send(LinkMessage::Watermark(before)).unwrap();
+    senders[1]
+        .send(LinkMessage::Control(marker.clone()))
+        .unwrap();
+    senders[1].send(LinkMessage::Watermark(after)).unwrap();
+
+    // The first channel is blocked by the marker: the message sent before it on the second channel
+    // is received first.
+    assert_eq!(input.try_recv().unwrap().get_timestamp(), before);
+    assert!(control.rx.try_recv().is_err());
+
+    // Both channels delivered the marker: the barrier is delivered to the node, never to its code.
+    assert!(input.try_recv().is_err());
+    match control.rx.try_recv() {
+        Ok(Control::Aligned(aligned)) => assert_eq!(aligned.get_payload(), &[42]),
+        other => panic!("Expected an aligned barrier, got: {other:?}"),
+    }
+    assert!(input.try_recv().is_err());
+
+    // Once handled, the barrier is forwarded and the channels are polled again.
+    async_std::task::block_on(control.release(&marker));
+    assert!(matches!(rx_output.try_recv(), Ok(LinkMessage::Control(_))));
+    assert_eq!(input.try_recv().unwrap().get_timestamp(), after);
+    assert_eq!(input.try_recv().unwrap().get_timestamp(), after);
+}
+
+#[test]
+fn test_barrier_release() {
+    let hlc = HLC::default();
+    let (control, input, senders, _rx_output) = node(2);
+    let marker = ControlMarker::new(hlc.new_timestamp(), Arc::new(Vec::new()));
+    let after = hlc.new_timestamp();
+
+    senders[0]
+        .send(LinkMessage::Control(marker.clone()))
+        .unwrap();
+    senders[0].send(LinkMessage::Watermark(after)).unwrap();
+    // The end of the stream of the second channel aligns the barrier.
+    senders[1]
+        .send(LinkMessage::EndOfStream(hlc.new_timestamp()))
+        .unwrap();
+
+    let node_control = control.clone();
+    async_std::task::spawn(async move {
+        match node_control.next().await {
+            Control::Aligned(marker) => node_control.release(&marker).await,
+            other => panic!("Expected an aligned barrier, got: {other:?}"),
+        }
+    });
+
+    let message = async_std::task::block_on(async_std::future::timeout(
+        Duration::from_secs(5),
+        input.recv(),
+    ))
+    .expect("The barrier should have been released")
+    .unwrap();
+    assert_eq!(message.get_timestamp(), after);
+}
+
+#[test]
+fn test_checkpoint_in_flight() {
+    let hlc = HLC::default();
+    let (control, input, senders, rx_output) = node(2);
+    let node_id: NodeId = "node".into();
+    let marker = ControlMarker::checkpoint(hlc.new_timestamp(), 1);
+    let in_flight = hlc.new_timestamp();
+    let after = hlc.new_timestamp();
+
+    senders[0]
+        .send(LinkMessage::Control(marker.clone()))
+        .unwrap();
+    senders[0].send(LinkMessage::Watermark(after)).unwrap();
+    senders[1].send(LinkMessage::Watermark(in_flight)).unwrap();
+    senders[1]
+        .send(LinkMessage::Control(marker.clone()))
+        .unwrap();
+
+    // The first marker pauses the node until it checkpointed its state.
+    assert!(input.try_recv().is_err());
+    match control.rx.try_recv() {
+        Ok(Control::Aligned(aligned)) => assert_eq!(aligned.get_epoch(), Some(1)),
+        other => panic!("Expected a checkpoint barrier, got: {other:?}"),
+    }
+    assert!(input.try_recv().is_err());
+
+    let checkpoint = NodeCheckpoint {
+        state: Some(vec![42]),
+        ..NodeCheckpoint::default()
+    };
+    async_std::task::block_on(control.checkpointed(&marker, Ok(checkpoint)));
+    assert!(matches!(rx_output.try_recv(), Ok(LinkMessage::Control(_))));
+
+    // The channel that delivered the marker is not blocked; the message in flight on the other
+    // one is received, and recorded.
+    assert_eq!(input.try_recv().unwrap().get_timestamp(), after);
+    assert_eq!(input.try_recv().unwrap().get_timestamp(), in_flight);
+    assert_eq!(
+        control.checkpoints.missing(1, std::iter::once(&node_id)),
+        vec![node_id.clone()]
+    );
+
+    // The last marker completes the checkpoint.
+    assert!(input.try_recv().is_err());
+    let mut checkpoints = control.checkpoints.take(1);
+    let checkpoint = checkpoints
+        .remove(&node_id)
+        .expect("The checkpoint should have been reported")
+        .unwrap();
+    assert_eq!(checkpoint.state, Some(vec![42]));
+    assert_eq!(checkpoint.links.len(), 1);
+    let (receiver, messages) = &checkpoint.links[0];
+    assert!(receiver.same_channel(&input.receivers[1]));
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].get_timestamp(), in_flight);
+}
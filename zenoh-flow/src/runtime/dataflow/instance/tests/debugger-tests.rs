@@ -0,0 +1,89 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::runtime::dataflow::instance::debugger::{DebugCommand, NodeDebugger};
+use crate::types::{LinkMessage, Payload, PortId};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn data(hlc: &uhlc::HLC) -> LinkMessage {
+    LinkMessage::from_payload(Payload::Bytes(Arc::new(vec![1u8])), hlc.new_timestamp())
+}
+
+/// Waits until the debugger reports that the node is paused.
+async fn wait_paused(debugger: &NodeDebugger) -> PortId {
+    loop {
+        if let Some((port_id, _)) = debugger.paused() {
+            return port_id;
+        }
+        async_std::task::sleep(Duration::from_millis(1)).await;
+    }
+}
+
+#[test]
+fn test_breakpoint() {
+    let hlc = uhlc::HLC::default();
+    let debugger = Arc::new(NodeDebugger::new(
+        "node".into(),
+        "zenoh-flow/debug/test/node".into(),
+        None,
+    ));
+    let input: PortId = "in".into();
+    let other: PortId = "other".into();
+
+    debugger
+        .set_breakpoint(Some(&input), true)
+        .expect("Failed to set the breakpoint");
+    // Not paused: resuming is an error.
+    assert!(debugger.resume(DebugCommand::Continue).is_err());
+
+    async_std::task::block_on(async {
+        // No breakpoint on `other` and watermarks never pause the node.
+        debugger.check(&other, &data(&hlc)).await;
+        debugger
+            .check(&input, &LinkMessage::Watermark(hlc.new_timestamp()))
+            .await;
+
+        // Breakpoint hit, stepping: the next data message pauses the node whatever the input.
+        let task = {
+            let debugger = debugger.clone();
+            let message = data(&hlc);
+            async_std::task::spawn(async move { debugger.check(&"in".into(), &message).await })
+        };
+        assert_eq!(wait_paused(&debugger).await, input);
+        debugger
+            .resume(DebugCommand::Step)
+            .expect("Failed to resume");
+        task.await;
+        assert!(debugger.paused().is_none());
+
+        let task = {
+            let debugger = debugger.clone();
+            let message = data(&hlc);
+            async_std::task::spawn(async move { debugger.check(&"other".into(), &message).await })
+        };
+        assert_eq!(wait_paused(&debugger).await, other);
+        debugger
+            .resume(DebugCommand::Continue)
+            .expect("Failed to resume");
+        task.await;
+
+        // Breakpoint removed: the node is not paused anymore.
+        debugger
+            .set_breakpoint(Some(&input), false)
+            .expect("Failed to remove the breakpoint");
+        debugger.check(&input, &data(&hlc)).await;
+        assert!(debugger.paused().is_none());
+    });
+}
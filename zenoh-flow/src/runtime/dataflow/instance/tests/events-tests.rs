@@ -0,0 +1,50 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::{InstanceEvent, InstanceEventKind};
+use uhlc::HLC;
+use uuid::Uuid;
+
+#[test]
+fn test_instance_event_json() {
+    let event = InstanceEvent {
+        timestamp: HLC::default().new_timestamp(),
+        instance_id: Uuid::new_v4(),
+        runtime: "runtime".into(),
+        node: "camera".into(),
+        kind: InstanceEventKind::NodeErrored {
+            error: "no frame".to_string(),
+        },
+    };
+
+    let json = serde_json::to_value(&event).unwrap();
+    assert_eq!(json["event"], "node-errored");
+    assert_eq!(json["node"], "camera");
+    assert_eq!(json["error"], "no frame");
+    assert_eq!(
+        serde_json::from_value::<InstanceEvent>(json).unwrap(),
+        event
+    );
+
+    let started = InstanceEvent {
+        kind: InstanceEventKind::RecordingStarted {
+            port: "frame".into(),
+            key_expr: "zenoh-flow/recording/0/camera/frame/1".to_string(),
+        },
+        ..event
+    };
+    let json = serde_json::to_value(&started).unwrap();
+    assert_eq!(json["event"], "recording-started");
+    assert_eq!(json["port"], "frame");
+}
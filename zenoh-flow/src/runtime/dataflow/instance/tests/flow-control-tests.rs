@@ -0,0 +1,68 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::runtime::dataflow::instance::flow_control::FlowControl;
+use crate::types::LinkMessage;
+use std::sync::Arc;
+use std::time::Duration;
+use uhlc::HLC;
+
+fn message() -> LinkMessage {
+    LinkMessage::Watermark(HLC::default().new_timestamp())
+}
+
+#[test]
+fn test_credits() {
+    let flow_control = FlowControl::new(2);
+    // A Source without links is never blocked.
+    assert_eq!(flow_control.credits(), 2);
+
+    let (tx_fast, rx_fast) = flume::bounded::<LinkMessage>(flow_control.window());
+    let (tx_slow, _rx_slow) = flume::bounded::<LinkMessage>(flow_control.window());
+    flow_control.add_link(tx_fast.clone().into());
+    flow_control.add_link(tx_slow.clone().into());
+
+    tx_fast.try_send(message()).unwrap();
+    assert_eq!(flow_control.credits(), 1);
+
+    // The credits are the lowest of the links.
+    tx_slow.try_send(message()).unwrap();
+    tx_slow.try_send(message()).unwrap();
+    assert_eq!(flow_control.credits(), 0);
+
+    rx_fast.try_recv().unwrap();
+    assert_eq!(flow_control.credits(), 0);
+}
+
+#[test]
+fn test_wait_credits() {
+    let flow_control = Arc::new(FlowControl::new(1));
+    let (tx, rx) = flume::bounded::<LinkMessage>(flow_control.window());
+    flow_control.add_link(tx.clone().into());
+    tx.try_send(message()).unwrap();
+
+    let downstream = flow_control.clone();
+    async_std::task::spawn(async move {
+        async_std::task::sleep(Duration::from_millis(10)).await;
+        rx.recv_async().await.unwrap();
+        downstream.grant();
+    });
+
+    let credits = async_std::task::block_on(async_std::future::timeout(
+        Duration::from_secs(5),
+        flow_control.wait_credits(),
+    ))
+    .expect("The downstream node should have granted a credit");
+    assert_eq!(credits, 1);
+}
@@ -0,0 +1,57 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::{IdleMonitor, IdleNotification};
+use crate::io::output::LinkActivity;
+use std::time::Duration;
+use uuid::Uuid;
+
+#[test]
+fn test_idle_monitor() {
+    let mut monitor = IdleMonitor::new(Duration::from_secs(30));
+
+    assert_eq!(monitor.update(Duration::from_secs(10)), None);
+    assert_eq!(monitor.update(Duration::from_secs(30)), Some(true));
+    // Reported once per idle period.
+    assert_eq!(monitor.update(Duration::from_secs(45)), None);
+    assert_eq!(monitor.update(Duration::from_millis(2)), Some(false));
+    assert_eq!(monitor.update(Duration::from_secs(1)), None);
+    assert_eq!(monitor.update(Duration::from_secs(31)), Some(true));
+}
+
+#[test]
+fn test_link_activity() {
+    let activity = LinkActivity::default();
+    std::thread::sleep(Duration::from_millis(20));
+    assert!(activity.idle_time() >= Duration::from_millis(20));
+
+    activity.touch();
+    assert!(activity.idle_time() < Duration::from_millis(20));
+}
+
+#[test]
+fn test_idle_notification_json() {
+    let notification = IdleNotification {
+        instance_id: Uuid::new_v4(),
+        runtime: "edge-0".into(),
+        idle: true,
+        idle_time: Duration::from_millis(30_250),
+    };
+
+    let json = serde_json::to_vec(&notification).unwrap();
+    assert_eq!(
+        serde_json::from_slice::<IdleNotification>(&json).unwrap(),
+        notification
+    );
+}
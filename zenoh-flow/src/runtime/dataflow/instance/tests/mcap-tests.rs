@@ -0,0 +1,69 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::{read_mcap, write_mcap, MAGIC};
+use crate::types::{LinkMessage, Payload, RecordingLabels, RecordingMetadata};
+use std::sync::Arc;
+use std::time::Duration;
+use uhlc::{Timestamp, NTP64};
+use uuid::Uuid;
+
+#[test]
+fn test_mcap_round_trip() {
+    let hlc = uhlc::HLC::default();
+    let id = *hlc.get_id();
+    let timestamp = |nanos: u64| Timestamp::new(NTP64::from(Duration::from_nanos(nanos)), id);
+
+    let metadata = RecordingMetadata {
+        timestamp: hlc.new_timestamp(),
+        port_id: "frame".into(),
+        node_id: "camera".into(),
+        flow_id: "flow".into(),
+        instance_id: Uuid::new_v4(),
+        key_expr: "zenoh-flow/recording/test".into(),
+        session_id: None,
+        labels: RecordingLabels::default(),
+        messages: 3,
+        data_messages: 2,
+        start: None,
+        end: None,
+    };
+    let messages = vec![
+        LinkMessage::from_payload(Payload::Bytes(Arc::new(vec![1, 2])), timestamp(1_000)),
+        LinkMessage::Watermark(timestamp(1_500)),
+        LinkMessage::from_payload(Payload::Bytes(Arc::new(vec![3])), timestamp(2_000)),
+    ];
+
+    let mut file = Vec::new();
+    write_mcap(&mut file, &[(metadata, messages)], "cdr").expect("Failed to write");
+    assert!(file.starts_with(MAGIC));
+    assert!(file.ends_with(MAGIC));
+
+    let channels = read_mcap(file.as_slice()).expect("Failed to read");
+    assert_eq!(channels.len(), 1);
+    assert_eq!(channels[0].topic, "camera/frame");
+    assert_eq!(channels[0].message_encoding, "cdr");
+    // The watermark has no equivalent in MCAP.
+    assert_eq!(
+        channels[0].messages,
+        vec![(1_000, vec![1, 2]), (2_000, vec![3])]
+    );
+
+    let link_messages = channels[0].link_messages(id);
+    assert_eq!(link_messages.len(), 2);
+    assert_eq!(link_messages[1].0, 1);
+    assert_eq!(link_messages[1].1.get_timestamp(), timestamp(2_000));
+
+    assert!(read_mcap(&b"not an mcap file"[..]).is_err());
+}
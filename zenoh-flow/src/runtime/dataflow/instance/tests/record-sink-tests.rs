@@ -0,0 +1,98 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::{
+    amz_date, hex, sign_v4, uri_encode, FileRecordSink, RecordSink, RecordingBackend,
+    SigningRequest,
+};
+use sha2::{Digest, Sha256};
+use std::time::{Duration, SystemTime};
+
+#[test]
+fn test_sign_v4() {
+    assert_eq!(
+        amz_date(SystemTime::UNIX_EPOCH + Duration::from_secs(1_672_628_645)),
+        "20230102T030405Z"
+    );
+
+    let path = format!(
+        "/recordings/{}",
+        uri_encode("zenoh-flow/recording/a b/data/0")
+    );
+    assert_eq!(path, "/recordings/zenoh-flow/recording/a%20b/data/0");
+
+    let payload_hash = hex(&Sha256::digest(b"hello"));
+    assert_eq!(
+        payload_hash,
+        "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+    );
+
+    let authorization = sign_v4(
+        &SigningRequest {
+            method: "PUT",
+            path: &path,
+            host: "localhost:9000",
+            amz_date: "20230102T030405Z",
+            payload_hash: &payload_hash,
+        },
+        "us-east-1",
+        "access",
+        "secret",
+    );
+    assert_eq!(
+        authorization,
+        "AWS4-HMAC-SHA256 Credential=access/20230102/us-east-1/s3/aws4_request, \
+         SignedHeaders=host;x-amz-content-sha256;x-amz-date, \
+         Signature=e82a08bb316c094dc0d74616c91fe9109ef0fbf73e0607cf65e326c1687549e1"
+    );
+}
+
+#[test]
+fn test_file_record_sink() {
+    let root = std::env::temp_dir().join(format!("zenoh-flow-{}", uuid::Uuid::new_v4()));
+    let sink = FileRecordSink { root: root.clone() };
+
+    async_std::task::block_on(sink.put("zenoh-flow/recording/data/0", vec![1, 2, 3]))
+        .expect("Failed to store the entry");
+    assert_eq!(
+        std::fs::read(root.join("zenoh-flow/recording/data/0")).unwrap(),
+        vec![1, 2, 3]
+    );
+
+    async_std::task::block_on(sink.delete("zenoh-flow/recording/data/0"))
+        .expect("Failed to delete the entry");
+    assert!(!root.join("zenoh-flow/recording/data/0").exists());
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_recording_backend_configuration() {
+    let backend: RecordingBackend = serde_yaml::from_str(
+        r#"
+kind: file
+path: /var/zenoh-flow/recordings
+"#,
+    )
+    .unwrap();
+    assert_eq!(
+        backend,
+        RecordingBackend::File {
+            path: "/var/zenoh-flow/recordings".into()
+        }
+    );
+
+    // Recording is opt-in.
+    assert_eq!(RecordingBackend::default(), RecordingBackend::Disabled);
+}
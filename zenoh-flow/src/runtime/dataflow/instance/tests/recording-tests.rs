@@ -0,0 +1,256 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::{compare, select, RecordingQuery, ReplayRange, RingBuffer};
+use crate::types::{LinkMessage, Payload, RecordingLabels, RecordingMetadata};
+use std::sync::Arc;
+use std::time::Duration;
+use uhlc::{Timestamp, ID, NTP64};
+use uuid::Uuid;
+
+fn message(id: ID, seconds: u64) -> LinkMessage {
+    LinkMessage::from_payload(
+        Payload::Bytes(Arc::new(seconds.to_le_bytes().to_vec())),
+        Timestamp::new(NTP64::from(Duration::from_secs(seconds)), id),
+    )
+}
+
+fn seconds(messages: &[(usize, LinkMessage)]) -> Vec<u64> {
+    messages
+        .iter()
+        .map(|(_, message)| message.get_timestamp().get_time().to_duration().as_secs())
+        .collect()
+}
+
+/// One message per second, from 100s to 109s, retrieved out of order.
+fn recording(id: ID) -> Vec<(u64, LinkMessage)> {
+    (0..10)
+        .rev()
+        .map(|index| (index, message(id, 100 + index)))
+        .collect()
+}
+
+#[test]
+fn test_select() {
+    let id = *uhlc::HLC::default().get_id();
+
+    assert_eq!(
+        seconds(&select(vec![recording(id)], &ReplayRange::All)),
+        (100..110).collect::<Vec<_>>()
+    );
+
+    assert_eq!(
+        seconds(&select(vec![recording(id)], &ReplayRange::Messages(2..5))),
+        vec![102, 103, 104]
+    );
+
+    // The time range is measured from the first message of the recording.
+    assert_eq!(
+        seconds(&select(
+            vec![recording(id)],
+            &ReplayRange::Time(Duration::from_secs(7)..Duration::from_secs(30))
+        )),
+        vec![107, 108, 109]
+    );
+
+    assert!(select(vec![Vec::new()], &ReplayRange::Messages(0..10)).is_empty());
+}
+
+#[test]
+fn test_select_interleaves_recordings() {
+    let id = *uhlc::HLC::default().get_id();
+
+    let camera = vec![
+        (0, message(id, 10)),
+        (1, message(id, 12)),
+        (2, message(id, 14)),
+    ];
+    let lidar = vec![(0, message(id, 11)), (1, message(id, 13))];
+
+    let selected = select(vec![camera.clone(), lidar.clone()], &ReplayRange::All);
+    assert_eq!(seconds(&selected), vec![10, 11, 12, 13, 14]);
+    assert_eq!(
+        selected
+            .iter()
+            .map(|(recording, _)| *recording)
+            .collect::<Vec<_>>(),
+        vec![0, 1, 0, 1, 0]
+    );
+
+    // The time range is measured from the oldest first message of the recordings.
+    assert_eq!(
+        seconds(&select(
+            vec![camera, lidar],
+            &ReplayRange::Time(Duration::from_secs(1)..Duration::from_secs(3))
+        )),
+        vec![11, 12]
+    );
+}
+
+#[test]
+fn test_index() {
+    let hlc = uhlc::HLC::default();
+    let id = *hlc.get_id();
+
+    let mut metadata = RecordingMetadata {
+        timestamp: hlc.new_timestamp(),
+        port_id: "out".into(),
+        node_id: "camera".into(),
+        flow_id: "flow".into(),
+        instance_id: Uuid::new_v4(),
+        key_expr: "zenoh-flow/recording/test".into(),
+        session_id: None,
+        labels: RecordingLabels::default(),
+        messages: 0,
+        data_messages: 0,
+        start: None,
+        end: None,
+    };
+
+    metadata.index(&message(id, 5));
+    metadata.index(&LinkMessage::Watermark(Timestamp::new(
+        NTP64::from(Duration::from_secs(7)),
+        id,
+    )));
+    metadata.index(&message(id, 6));
+
+    assert_eq!(metadata.messages, 3);
+    assert_eq!(metadata.data_messages, 2);
+    assert_eq!(
+        metadata.start.map(|start| start.get_time().to_duration()),
+        Some(Duration::from_secs(5))
+    );
+    assert_eq!(
+        metadata.end.map(|end| end.get_time().to_duration()),
+        Some(Duration::from_secs(7))
+    );
+}
+
+#[test]
+fn test_compare() {
+    let hlc = uhlc::HLC::default();
+    let id = *hlc.get_id();
+
+    let baseline = vec![
+        (0, message(id, 1)),
+        (1, message(id, 2)),
+        (2, message(id, 3)),
+    ];
+
+    // Timestamps and watermarks are not compared.
+    let same = vec![
+        (0, message(id, 1)),
+        (1, LinkMessage::Watermark(hlc.new_timestamp())),
+        (2, message(id, 2)),
+        (3, message(id, 3)),
+    ];
+    let comparison = compare(baseline.clone(), same).unwrap();
+    assert!(comparison.is_identical());
+    assert_eq!(comparison.left, 3);
+    assert_eq!(comparison.right, 3);
+
+    let different = vec![
+        (0, message(id, 1)),
+        (1, message(id, 4)),
+        (2, message(id, 3)),
+    ];
+    let comparison = compare(baseline.clone(), different).unwrap();
+    assert!(!comparison.is_identical());
+    assert_eq!(comparison.differences, 1);
+    assert_eq!(comparison.first_difference, Some(1));
+
+    let shorter = vec![(0, message(id, 1)), (1, message(id, 2))];
+    let comparison = compare(baseline, shorter).unwrap();
+    assert_eq!(comparison.differences, 0);
+    assert_eq!(comparison.first_difference, Some(2));
+}
+
+#[test]
+fn test_ring_buffer() {
+    let id = *uhlc::HLC::default().get_id();
+
+    let mut ring_buffer = RingBuffer::new(Duration::from_secs(3));
+    for (_, message) in recording(id).into_iter().rev() {
+        ring_buffer.push(message);
+    }
+
+    // Only the messages of the last 3 seconds, before the newest one (109s), are kept.
+    let messages = ring_buffer
+        .drain()
+        .into_iter()
+        .map(|message| (0, message))
+        .collect::<Vec<_>>();
+    assert_eq!(seconds(&messages), vec![106, 107, 108, 109]);
+    assert!(ring_buffer.drain().is_empty());
+}
+
+#[test]
+fn test_recording_query() {
+    let hlc = uhlc::HLC::default();
+    let instance_id = Uuid::new_v4();
+    let metadata = |labels: RecordingLabels| RecordingMetadata {
+        timestamp: hlc.new_timestamp(),
+        port_id: "out".into(),
+        node_id: "camera".into(),
+        flow_id: "flow".into(),
+        instance_id,
+        key_expr: "zenoh-flow/recording/test".into(),
+        session_id: None,
+        labels,
+        messages: 0,
+        data_messages: 0,
+        start: None,
+        end: None,
+    };
+
+    let tagged = metadata(
+        RecordingLabels::new("field-test")
+            .with_tag("operator", "alice")
+            .with_tag("location", "quarry"),
+    );
+    let untagged = metadata(RecordingLabels::default());
+
+    // The labels are stored along with the other metadata, and optional.
+    let json = serde_json::to_value(&tagged).unwrap();
+    assert_eq!(json["session_name"], "field-test");
+    assert_eq!(json["tags"]["operator"], "alice");
+    let mut legacy = serde_json::to_value(&untagged).unwrap();
+    assert!(legacy.get("tags").is_none());
+    legacy.as_object_mut().unwrap().remove("session_id");
+    let read: RecordingMetadata = serde_json::from_value(legacy).unwrap();
+    assert_eq!(read, untagged);
+
+    assert!(RecordingQuery::default().matches(&tagged));
+    assert!(RecordingQuery::default().matches(&untagged));
+
+    let query = RecordingQuery {
+        tags: [("operator".to_string(), "alice".to_string())].into(),
+        ..Default::default()
+    };
+    assert!(query.matches(&tagged));
+    assert!(!query.matches(&untagged));
+
+    let query = RecordingQuery {
+        instance_id: Some(instance_id),
+        session_name: Some("field-test".into()),
+        tags: [("location".to_string(), "lab".to_string())].into(),
+    };
+    assert!(!query.matches(&tagged));
+
+    let query = RecordingQuery {
+        instance_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    assert!(!query.matches(&tagged));
+}
@@ -0,0 +1,124 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::{redact, Redaction, MASK};
+use crate::model::descriptor::{OutputDescriptor, RedactionDescriptor};
+use crate::types::{LinkMessage, Payload, PayloadReference};
+use crate::zfresult::ErrorKind;
+use crate::{bail, Result};
+use serde_json::{json, Value};
+
+fn rule(mask: &[&str], max_bytes: Option<usize>) -> Redaction {
+    Redaction {
+        rule: RedactionDescriptor {
+            output: OutputDescriptor::new("Gateway", "Request"),
+            transform: None,
+            mask: mask.iter().map(|pointer| pointer.to_string()).collect(),
+            max_bytes,
+        },
+        transform: None,
+    }
+}
+
+/// Strips the `gps` field of a JSON payload, refusing the others.
+fn strip_gps(payload: &[u8]) -> Result<Vec<u8>> {
+    let mut value = serde_json::from_slice::<Value>(payload)?;
+    match value.as_object_mut() {
+        Some(object) => {
+            object.remove("gps");
+            Ok(serde_json::to_vec(&value)?)
+        }
+        None => bail!(ErrorKind::DeserializationError, "Not a JSON object"),
+    }
+}
+
+fn payload(message: &LinkMessage) -> Vec<u8> {
+    match message {
+        LinkMessage::Data(data_message) => data_message.try_as_bytes().unwrap().to_vec(),
+        _ => panic!("Unexpected message"),
+    }
+}
+
+#[test]
+fn test_redact() {
+    let hlc = uhlc::HLC::default();
+    let request = json!({ "user": { "name": "Ada", "email": "ada@example.com" }, "amount": 42 });
+    let message = LinkMessage::from_payload_with_event_time(
+        serde_json::to_vec(&request).unwrap().into(),
+        hlc.new_timestamp(),
+        Some(hlc.new_timestamp()),
+    );
+
+    // Without rule, the message is left untouched.
+    assert_eq!(payload(&redact(None, message.clone())), payload(&message));
+
+    let masked = redact(
+        Some(&rule(&["/user/email", "/missing"], None)),
+        message.clone(),
+    );
+    assert_eq!(masked, message);
+    assert_eq!(
+        serde_json::from_slice::<Value>(&payload(&masked)).unwrap(),
+        json!({ "user": { "name": "Ada", "email": MASK }, "amount": 42 })
+    );
+
+    let truncated = redact(Some(&rule(&[], Some(4))), message.clone());
+    assert_eq!(payload(&truncated), payload(&message)[..4].to_vec());
+
+    // A payload that is not JSON cannot be masked: it is removed.
+    let binary = LinkMessage::from_payload(vec![0xffu8; 64].into(), hlc.new_timestamp());
+    assert!(payload(&redact(Some(&rule(&["/user"], None)), binary)).is_empty());
+
+    // Only the key expression of a reference leaves the data flow.
+    let reference = LinkMessage::from_payload(
+        PayloadReference::new("gateway/requests/1").into(),
+        hlc.new_timestamp(),
+    );
+    match redact(Some(&rule(&["/user"], Some(0))), reference) {
+        LinkMessage::Data(data_message) => {
+            assert!(matches!(&*data_message, Payload::Reference(_)))
+        }
+        _ => panic!("Unexpected message"),
+    }
+
+    let watermark = LinkMessage::Watermark(hlc.new_timestamp());
+    assert!(matches!(
+        redact(Some(&rule(&["/user"], Some(0))), watermark),
+        LinkMessage::Watermark(_)
+    ));
+}
+
+#[test]
+fn test_redact_transform() {
+    let hlc = uhlc::HLC::default();
+    let position = json!({ "user": "ada", "gps": [48.85, 2.35], "speed": 12 });
+    let message = LinkMessage::from_payload(
+        serde_json::to_vec(&position).unwrap().into(),
+        hlc.new_timestamp(),
+    );
+
+    // The payload is transformed before it is masked.
+    let redaction = Redaction {
+        transform: Some((strip_gps, None)),
+        ..rule(&["/user"], None)
+    };
+    assert_eq!(
+        serde_json::from_slice::<Value>(&payload(&redact(Some(&redaction), message))).unwrap(),
+        json!({ "user": MASK, "speed": 12 })
+    );
+
+    // A payload the transform fails on is removed.
+    let binary = LinkMessage::from_payload(vec![0xffu8; 64].into(), hlc.new_timestamp());
+    assert!(payload(&redact(Some(&redaction), binary)).is_empty());
+}
@@ -0,0 +1,115 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::rescale_links;
+use crate::model::descriptor::{InputDescriptor, OutputDescriptor};
+use crate::model::record::LinkRecord;
+use crate::types::NodeId;
+
+fn link(uid: u32, from: &str, output: &str, to: &str, input: &str) -> LinkRecord {
+    LinkRecord {
+        uid,
+        from: OutputDescriptor::new(from, output),
+        to: InputDescriptor::new(to, input),
+        shared_memory_element_size: None,
+        shared_memory_elements: None,
+        shared_memory_backoff: None,
+        queue: None,
+        capacity: None,
+    }
+}
+
+fn ports(links: &[LinkRecord]) -> Vec<String> {
+    let mut ports = links
+        .iter()
+        .map(|link| {
+            format!(
+                "{}.{} => {}.{}",
+                link.from.node, link.from.output, link.to.node, link.to.input
+            )
+        })
+        .collect::<Vec<_>>();
+    ports.sort();
+    ports
+}
+
+#[test]
+fn test_rescale_links() {
+    let node: NodeId = "count".into();
+    // The partitioner sends each key on the output of the copy owning it, the copies all send
+    // their results on the same input.
+    let links = vec![
+        link(0, "partitioner", "out-0", "count-0", "in"),
+        link(1, "partitioner", "out-1", "count-1", "in"),
+        link(2, "count-0", "out", "sink", "in"),
+        link(3, "count-1", "out", "sink", "in"),
+        link(4, "source", "out", "partitioner", "in"),
+    ];
+
+    let mut counter = 5;
+    let scaled_up = rescale_links(&links, &node, 2, 3, &mut counter);
+    assert_eq!(counter, 7);
+    assert_eq!(
+        ports(&scaled_up),
+        vec![
+            "count-0.out => sink.in",
+            "count-1.out => sink.in",
+            "count-2.out => sink.in",
+            "partitioner.out-0 => count-0.in",
+            "partitioner.out-1 => count-1.in",
+            "partitioner.out-2 => count-2.in",
+            "source.out => partitioner.in",
+        ]
+    );
+    assert!(scaled_up
+        .iter()
+        .filter(|link| link.uid >= 5)
+        .all(|link| link.from.node.as_ref() == "count-2" || link.to.node.as_ref() == "count-2"));
+
+    let scaled_down = rescale_links(&scaled_up, &node, 3, 1, &mut counter);
+    assert_eq!(counter, 7);
+    assert_eq!(
+        ports(&scaled_down),
+        vec![
+            "count-0.out => sink.in",
+            "partitioner.out-0 => count-0.in",
+            "source.out => partitioner.in",
+        ]
+    );
+
+    // With a single copy, the ports ending with 0 are deemed indexed.
+    let scaled_up = rescale_links(&scaled_down, &node, 1, 2, &mut counter);
+    assert_eq!(ports(&scaled_up), ports(&links));
+}
+
+#[test]
+fn test_rescale_links_not_indexed() {
+    let node: NodeId = "count".into();
+    // The ports ending with 0 that are the same for all the copies are not indexed.
+    let links = vec![
+        link(0, "source", "out0", "count-0", "in"),
+        link(1, "source", "out0", "count-1", "in"),
+    ];
+
+    let mut counter = 2;
+    let rescaled = rescale_links(&links, &node, 2, 3, &mut counter);
+    assert_eq!(
+        ports(&rescaled),
+        vec![
+            "source.out0 => count-0.in",
+            "source.out0 => count-1.in",
+            "source.out0 => count-2.in",
+        ]
+    );
+}
@@ -0,0 +1,75 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::RetentionLedger;
+use crate::model::descriptor::RetentionDescriptor;
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_retention_ledger() {
+    let start = Instant::now();
+    let at = |secs: u64| start + Duration::from_secs(secs);
+    let ledger = RetentionLedger::default();
+
+    ledger.stored("recording/metadata", 10, at(0));
+    ledger.stored("recording/data/0", 100, at(1));
+    ledger.stored("recording/data/1", 100, at(2));
+    // The metadata are stored again when the recording stops.
+    ledger.stored("recording/metadata", 20, at(3));
+    assert_eq!(ledger.size(), 220);
+
+    let max_age = RetentionDescriptor {
+        max_age: Some(Duration::from_secs(10)),
+        max_size: None,
+    };
+    assert!(ledger.expire(&max_age, at(10)).is_empty());
+    assert_eq!(ledger.expire(&max_age, at(11)), vec!["recording/data/0"]);
+    assert_eq!(ledger.size(), 120);
+
+    // The oldest entries are deleted until the size is within the limit.
+    let max_size = RetentionDescriptor {
+        max_age: None,
+        max_size: Some(50),
+    };
+    assert_eq!(ledger.expire(&max_size, at(11)), vec!["recording/data/1"]);
+    assert_eq!(ledger.size(), 20);
+    assert!(ledger.expire(&max_size, at(11)).is_empty());
+
+    assert_eq!(ledger.expire(&max_age, at(13)), vec!["recording/metadata"]);
+    assert_eq!(ledger.size(), 0);
+}
+
+#[test]
+fn test_retention_descriptor() {
+    let retention: RetentionDescriptor = serde_yaml::from_str(
+        r#"
+max_age: 7days
+max_size: 10KiB
+"#,
+    )
+    .unwrap();
+    assert_eq!(
+        retention,
+        RetentionDescriptor {
+            max_age: Some(Duration::from_secs(7 * 24 * 3600)),
+            max_size: Some(10 * 1024),
+        }
+    );
+
+    let json = serde_json::to_value(&retention).unwrap();
+    assert_eq!(
+        serde_json::from_value::<RetentionDescriptor>(json).unwrap(),
+        retention
+    );
+}
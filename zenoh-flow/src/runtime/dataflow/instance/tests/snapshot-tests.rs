@@ -0,0 +1,104 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::model::descriptor::{InputDescriptor, OutputDescriptor};
+use crate::runtime::dataflow::instance::snapshot::{InstanceSnapshot, LinkSnapshot};
+use crate::types::{LinkMessage, NodeId, Payload};
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+fn link_snapshot() -> LinkSnapshot {
+    let hlc = uhlc::HLC::default();
+    let messages = vec![
+        LinkMessage::from_payload(
+            Payload::Bytes(Arc::new(vec![1u8, 2, 3])),
+            hlc.new_timestamp(),
+        ),
+        LinkMessage::Watermark(hlc.new_timestamp()),
+    ];
+
+    LinkSnapshot::new(
+        OutputDescriptor {
+            node: "counter".into(),
+            output: "out".into(),
+        },
+        InputDescriptor {
+            node: "sum".into(),
+            input: "in".into(),
+        },
+        &messages,
+    )
+    .expect("Failed to serialize the messages")
+}
+
+#[test]
+fn test_link_snapshot() {
+    let messages = link_snapshot()
+        .messages()
+        .expect("Failed to deserialize the messages");
+
+    assert_eq!(messages.len(), 2);
+    match &messages[0] {
+        LinkMessage::Data(data) => assert_eq!(
+            *data.try_as_bytes().expect("Unexpected payload"),
+            vec![1u8, 2, 3]
+        ),
+        _ => panic!("Unexpected message: {:?}", messages[0]),
+    }
+    assert!(matches!(messages[1], LinkMessage::Watermark(_)));
+}
+
+#[test]
+fn test_snapshot_file_and_merge() {
+    let mut snapshot = InstanceSnapshot {
+        flow_id: "flow".into(),
+        instance_id: Uuid::new_v4(),
+        states: HashMap::from([("counter".into(), vec![42u8])]),
+        partitions: HashMap::from([("window".into(), HashMap::from([(0, vec![1u8])]))]),
+        links: vec![link_snapshot()],
+    };
+
+    let path = std::env::temp_dir().join(format!("zenoh-flow-snapshot-{}", Uuid::new_v4()));
+    snapshot
+        .to_file(&path)
+        .expect("Failed to write the snapshot");
+    let read = InstanceSnapshot::from_file(&path).expect("Failed to read the snapshot");
+    std::fs::remove_file(&path).expect("Failed to remove the snapshot");
+    assert_eq!(snapshot, read);
+
+    let other = InstanceSnapshot {
+        flow_id: "flow".into(),
+        instance_id: snapshot.instance_id,
+        states: HashMap::from([("sum".into(), vec![7u8])]),
+        partitions: HashMap::from([("window".into(), HashMap::from([(1, vec![2u8])]))]),
+        links: vec![],
+    };
+    snapshot
+        .merge(other)
+        .expect("Failed to merge the snapshots");
+    assert_eq!(snapshot.states.len(), 2);
+    // The partitions saved by the copies of a replicated Operator are gathered.
+    assert_eq!(snapshot.partitions[&NodeId::from("window")].len(), 2);
+    assert_eq!(snapshot.links.len(), 1);
+
+    let different_flow = InstanceSnapshot {
+        flow_id: "other-flow".into(),
+        instance_id: Uuid::new_v4(),
+        states: HashMap::new(),
+        partitions: HashMap::new(),
+        links: vec![],
+    };
+    assert!(snapshot.merge(different_flow).is_err());
+}
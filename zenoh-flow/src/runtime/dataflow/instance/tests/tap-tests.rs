@@ -0,0 +1,62 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::runtime::dataflow::instance::tap::decode_message;
+use crate::types::{LinkMessage, Payload, PayloadReference};
+use serde_json::json;
+use std::sync::Arc;
+
+#[test]
+fn test_decode_message() {
+    let hlc = uhlc::HLC::default();
+
+    let json = LinkMessage::from_payload(
+        Payload::Bytes(Arc::new(br#"{"frame": 1}"#.to_vec())),
+        hlc.new_timestamp(),
+    );
+    assert_eq!(decode_message(&json)["payload"], json!({ "frame": 1 }));
+
+    let string = LinkMessage::from_payload(
+        Payload::Bytes(Arc::new(b"hello".to_vec())),
+        hlc.new_timestamp(),
+    );
+    assert_eq!(decode_message(&string)["payload"], json!("hello"));
+
+    let bytes = LinkMessage::from_payload(
+        Payload::Bytes(Arc::new(vec![0xff, 0x00])),
+        hlc.new_timestamp(),
+    );
+    assert_eq!(decode_message(&bytes)["payload"], json!([255, 0]));
+
+    let reference = LinkMessage::from_payload(
+        PayloadReference::new("demo/frame").into(),
+        hlc.new_timestamp(),
+    );
+    assert_eq!(
+        decode_message(&reference)["payload"],
+        json!({ "reference": "demo/frame" })
+    );
+
+    let watermark = LinkMessage::Watermark(hlc.new_timestamp());
+    assert_eq!(decode_message(&watermark)["kind"], json!("watermark"));
+
+    let end_of_stream = LinkMessage::EndOfStream(hlc.new_timestamp());
+    assert_eq!(
+        decode_message(&end_of_stream)["kind"],
+        json!("end-of-stream")
+    );
+
+    let live = LinkMessage::Live(hlc.new_timestamp());
+    assert_eq!(decode_message(&live)["kind"], json!("live"));
+}
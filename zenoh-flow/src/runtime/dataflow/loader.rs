@@ -12,13 +12,18 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+use super::cache::{LibraryCache, LibraryCacheConfig};
+use super::instance::builtin::get_builtin_operator_declaration;
+use super::instance::builtin::host::{get_host_sink_declaration, get_host_source_declaration};
+use super::instance::builtin::http::{get_http_sink_declaration, get_http_source_declaration};
 use super::instance::builtin::zenoh::{get_zenoh_sink_declaration, get_zenoh_source_declaration};
 use super::node::{
     ConstructorFn, OperatorConstructor, OperatorFn, SinkConstructor, SinkFn, SourceConstructor,
     SourceFn,
 };
+use crate::model::descriptor::NodeUri;
 use crate::model::record::{OperatorRecord, SinkRecord, SourceRecord};
-use crate::model::{Middleware, ZFUri};
+use crate::model::{BuiltinOperator, Middleware, ZFUri};
 use crate::types::Configuration;
 use crate::utils::parse_uri;
 use crate::zfresult::ErrorKind;
@@ -83,6 +88,21 @@ pub type SourceDeclaration = NodeDeclaration<SourceFn>;
 pub type OperatorDeclaration = NodeDeclaration<OperatorFn>;
 pub type SinkDeclaration = NodeDeclaration<SinkFn>;
 
+/// The symbol of a transform (see [TransformDeclaration]) in the shared library we load.
+pub(crate) static TRANSFORM_SYMBOL: &[u8] = b"_zf_export_transform\0";
+
+/// `TransformFn` is the only signature we accept for the transform of a redaction rule (see
+/// [RedactionDescriptor](crate::model::descriptor::RedactionDescriptor)): it receives the
+/// serialized payload of a message and returns the one to publish or record in its place.
+pub type TransformFn = fn(&[u8]) -> Result<Vec<u8>>;
+
+/// Declaration of a transform expected in the library that will be loaded.
+pub struct TransformDeclaration {
+    pub rustc_version: &'static str,
+    pub core_version: &'static str,
+    pub transform: TransformFn,
+}
+
 /// Extensible support for different implementations
 /// This represents the configuration for an extension.
 ///
@@ -119,17 +139,31 @@ pub struct ExtensibleImplementation {
 ///     sink_lib: ./target/release/libpy_sink.so
 ///     operator_lib: ./target/release/libpy_op.so
 ///     config_lib_key: python-script
+/// cache:            # optional, where the libraries fetched over HTTP are stored
+///   directory: /var/zenoh-flow/libraries
+///   max_size: 1073741824
 /// ```
 ///
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LoaderConfig {
     extensions: Vec<ExtensibleImplementation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cache: Option<LibraryCacheConfig>,
 }
 
 impl LoaderConfig {
     /// Creates an empty `LoaderConfig`.
     pub fn new() -> Self {
-        Self { extensions: vec![] }
+        Self {
+            extensions: vec![],
+            cache: None,
+        }
+    }
+
+    /// Sets the configuration of the [`LibraryCache`] where the libraries fetched over HTTP are
+    /// stored, a directory in the temporary directory of the system being used by default.
+    pub fn set_library_cache(&mut self, config: LibraryCacheConfig) {
+        self.cache = Some(config);
     }
 
     /// Adds the given extension.
@@ -195,12 +229,37 @@ impl Default for LoaderConfig {
 /// - `RTLD_LOCAL` keep all the symbols local.
 pub struct Loader {
     pub(crate) config: LoaderConfig,
+    cache: LibraryCache,
 }
 
 impl Loader {
     /// Creates a new `Loader` with the given `config`.
     pub fn new(config: LoaderConfig) -> Self {
-        Self { config }
+        Self {
+            cache: LibraryCache::new(config.cache.clone().unwrap_or_default()),
+            config,
+        }
+    }
+
+    /// Returns the cache of the libraries fetched over HTTP, to list or purge them.
+    pub fn library_cache(&self) -> &LibraryCache {
+        &self.cache
+    }
+
+    /// Parses the URI of a node for the architecture of this runtime, fetching the library through
+    /// the [`LibraryCache`] if it is remote.
+    ///
+    /// # Errors
+    ///
+    /// An error variant is returned if the URI is missing for the architecture of this runtime, is
+    /// malformed or if the remote library could not be fetched.
+    fn parse_uri(&self, uri: &NodeUri) -> Result<ZFUri> {
+        match parse_uri(uri.resolve()?)? {
+            ZFUri::Remote(url) => Ok(ZFUri::File(futures::executor::block_on(
+                self.cache.fetch(&url),
+            )?)),
+            uri => Ok(uri),
+        }
     }
 
     /// Loads a node library from a file, using one of the extension configured within the loader.
@@ -273,18 +332,89 @@ impl Loader {
         Ok((library, decl.constructor))
     }
 
+    /// Loads the transform of a redaction rule from the shared library at `uri`.
+    ///
+    /// # Errors
+    ///
+    /// It can fail because of:
+    /// - the URI is missing for the architecture of this runtime or the remote library could not be
+    ///   fetched
+    /// - the URI does not point to a shared library (WASM modules and extensions are not supported)
+    /// - different version of Zenoh-Flow or of the rust compiler used to build the transform
+    /// - the library does not contain the symbol
+    pub(crate) fn load_transform(&self, uri: &NodeUri) -> Result<(Library, TransformFn)> {
+        let library_path = match self.parse_uri(uri)? {
+            ZFUri::File(file_path) => file_path,
+            _ => bail!(
+                ErrorKind::LoadingError,
+                "The transform < {} > is not a shared library",
+                uri.resolve()?
+            ),
+        };
+
+        let is_library = crate::utils::get_file_extension(&library_path)
+            .map_or(false, |extension| {
+                crate::utils::is_dynamic_library(&extension)
+            });
+        if !is_library {
+            bail!(
+                ErrorKind::Unimplemented,
+                "The transform < {} > is not a shared library, only those are supported",
+                library_path.display()
+            );
+        }
+
+        log::trace!("[Loader] loading transform {:?}", library_path);
+
+        unsafe {
+            #[cfg(target_family = "unix")]
+            let library = Library::open(Some(library_path.clone()), LOAD_FLAGS)?;
+
+            #[cfg(target_family = "windows")]
+            let library = Library::new(library_path.clone())?;
+
+            let decl = library
+                .get::<*mut TransformDeclaration>(TRANSFORM_SYMBOL)?
+                .read();
+
+            // version checks to prevent accidental ABI incompatibilities
+            if decl.rustc_version != RUSTC_VERSION || decl.core_version != CORE_VERSION {
+                return Err(zferror!(
+                    ErrorKind::VersionMismatch,
+                    "Library {} rustc expected {} rustc found {} - Zenoh-Flow expected {} Zenoh-Flow found {}",
+                    library_path.display(),
+                    RUSTC_VERSION,
+                    decl.rustc_version,
+                    CORE_VERSION,
+                    decl.core_version
+                )
+                .into());
+            }
+
+            Ok((library, decl.transform))
+        }
+    }
+
     /// Loads a source from the builtin ones.
     ///
     /// # Errors
     ///
     /// It can fail because of:
-    /// - the buitin middleware is not supported (so far only Zenoh is supported)
+    /// - the buitin middleware is not supported (so far only Zenoh, HTTP and Host are supported)
     fn load_source_from_builtin(&self, middleware: Middleware) -> Result<SourceFn> {
         match middleware {
             Middleware::Zenoh => {
                 let declaration = get_zenoh_source_declaration();
                 Ok(declaration.constructor)
             }
+            Middleware::Http => {
+                let declaration = get_http_source_declaration();
+                Ok(declaration.constructor)
+            }
+            Middleware::Host => {
+                let declaration = get_host_source_declaration();
+                Ok(declaration.constructor)
+            }
         }
     }
 
@@ -293,16 +423,29 @@ impl Loader {
     /// # Errors
     ///
     /// It can fail because of:
-    /// - the buitin middleware is not supported (so far only Zenoh is supported)
+    /// - the buitin middleware is not supported (so far only Zenoh, HTTP and Host are supported)
     fn load_sink_from_builtin(&self, middleware: Middleware) -> Result<SinkFn> {
         match middleware {
             Middleware::Zenoh => {
                 let declaration = get_zenoh_sink_declaration();
                 Ok(declaration.constructor)
             }
+            Middleware::Http => {
+                let declaration = get_http_sink_declaration();
+                Ok(declaration.constructor)
+            }
+            Middleware::Host => {
+                let declaration = get_host_sink_declaration();
+                Ok(declaration.constructor)
+            }
         }
     }
 
+    /// Loads an operator from the builtin ones.
+    fn load_operator_from_builtin(&self, operator: BuiltinOperator) -> OperatorFn {
+        get_builtin_operator_declaration(&operator).constructor
+    }
+
     /// Tries to load a Source from the information passed within the
     /// [`SourceRecord`](`SourceRecord`).
     ///
@@ -312,14 +455,16 @@ impl Loader {
     /// - different version of Zenoh-Flow used to build the source
     /// - different version of the rust compiler used to build the source
     /// - the library does not contain the symbols
-    /// - the URI is missing
-    /// - the URI scheme is not known (so far only `file://` is supported).
+    /// - the URI is missing, or missing for the architecture of this runtime
+    /// - the remote library could not be fetched
+    /// - the URI scheme is not known (so far `file://`, `builtin://`, `http://` and
+    ///   `https://` are supported).
     pub(crate) fn load_source_constructor(
         &self,
         mut record: SourceRecord,
     ) -> Result<SourceConstructor> {
         if let Some(uri) = &record.uri {
-            match parse_uri(uri)? {
+            match self.parse_uri(uri)? {
                 ZFUri::File(file_path) => {
                     let (library, constructor) = unsafe {
                         self.load_node_from_file::<SourceFn>(
@@ -339,6 +484,15 @@ impl Loader {
                     let constructor = self.load_source_from_builtin(mw)?;
                     Ok(SourceConstructor::new_static(record, constructor))
                 }
+                ZFUri::Remote(_) => unreachable!("remote libraries are fetched when parsing"),
+                ZFUri::BuiltinOperator(operator) => {
+                    bail!(
+                        ErrorKind::LoadingError,
+                        "Builtin operator < {} > cannot be loaded as the Source < {} >.",
+                        operator.to_string(),
+                        record.id.clone()
+                    )
+                }
             }
         } else {
             bail!(
@@ -359,14 +513,16 @@ impl Loader {
     /// - different versions of Zenoh-Flow used to build the operator
     /// - different versions of the rust compiler used to build the operator
     /// - the library does not contain the symbols
-    /// - the URI is missing
-    /// - the URI scheme is not known (so far only `file://` is known).
+    /// - the URI is missing, or missing for the architecture of this runtime
+    /// - the remote library could not be fetched
+    /// - the URI scheme is not known (so far `file://`, `builtin://`, `http://` and
+    ///   `https://` are supported).
     pub(crate) fn load_operator_constructor(
         &self,
         mut record: OperatorRecord,
     ) -> Result<OperatorConstructor> {
         if let Some(uri) = &record.uri {
-            match parse_uri(uri)? {
+            match self.parse_uri(uri)? {
                 ZFUri::File(file_path) => {
                     let (library, constructor) = unsafe {
                         self.load_node_from_file::<OperatorFn>(
@@ -382,13 +538,19 @@ impl Loader {
                         Arc::new(library),
                     ))
                 }
-                ZFUri::Builtin(_mw) => {
+                ZFUri::Builtin(mw) => {
                     bail!(
-                        ErrorKind::Unimplemented,
-                        "Loading builtin operators is not supported < {} >.",
+                        ErrorKind::LoadingError,
+                        "Builtin < {} > cannot be loaded as the Operator < {} >.",
+                        mw.to_string(),
                         record.id.clone()
                     )
                 }
+                ZFUri::Remote(_) => unreachable!("remote libraries are fetched when parsing"),
+                ZFUri::BuiltinOperator(operator) => {
+                    let constructor = self.load_operator_from_builtin(operator);
+                    Ok(OperatorConstructor::new_static(record, constructor))
+                }
             }
         } else {
             bail!(
@@ -408,11 +570,13 @@ impl Loader {
     /// - different versions of Zenoh-Flow used to build the sink
     /// - different versions of the rust compiler used to build the sink
     /// - the library does not contain the symbols
-    /// - the URI is missing
-    /// - the URI scheme is not known (so far only `file://` is known).
+    /// - the URI is missing, or missing for the architecture of this runtime
+    /// - the remote library could not be fetched
+    /// - the URI scheme is not known (so far `file://`, `builtin://`, `http://` and
+    ///   `https://` are supported).
     pub(crate) fn load_sink_constructor(&self, mut record: SinkRecord) -> Result<SinkConstructor> {
         if let Some(uri) = &record.uri {
-            match parse_uri(uri)? {
+            match self.parse_uri(uri)? {
                 ZFUri::File(file_path) => {
                     let (library, constructor) = unsafe {
                         self.load_node_from_file::<SinkFn>(
@@ -432,6 +596,15 @@ impl Loader {
                     let constructor = self.load_sink_from_builtin(mw)?;
                     Ok(SinkConstructor::new_static(record, constructor))
                 }
+                ZFUri::Remote(_) => unreachable!("remote libraries are fetched when parsing"),
+                ZFUri::BuiltinOperator(operator) => {
+                    bail!(
+                        ErrorKind::LoadingError,
+                        "Builtin operator < {} > cannot be loaded as the Sink < {} >.",
+                        operator.to_string(),
+                        record.id.clone()
+                    )
+                }
             }
         } else {
             bail!(
@@ -12,21 +12,34 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+pub mod cache;
+pub mod dry_run;
 pub mod instance;
-pub mod loader;
+runtime_mod!(loader);
 pub mod node;
+pub mod physical;
+pub mod readiness;
 
+use self::instance::builtin::host::HostChannels;
 use self::node::{OperatorConstructor, SinkConstructor, SourceConstructor};
 use self::node::{OperatorFn, SinkFn, SourceFn};
-use crate::model::descriptor::{InputDescriptor, OutputDescriptor};
+use self::physical::PhysicalNode;
+use crate::model::descriptor::dataflow::replica_of;
+use crate::model::descriptor::{
+    AutoscalingDescriptor, GpuDescriptor, InputDescriptor, OutputDescriptor, PreemptionPolicy,
+    ReadinessDescriptor, RedactionDescriptor, RetentionDescriptor, TransportDescriptor,
+    WarmupDescriptor, WatchdogDescriptor,
+};
 use crate::model::record::{
     DataFlowRecord, LinkRecord, OperatorRecord, SinkRecord, SourceRecord, ZFConnectorRecord,
 };
 use crate::runtime::RuntimeContext;
-use crate::types::NodeId;
+use crate::types::{KeyedState, NodeId};
+use crate::zfresult::Error;
 use crate::Result as ZFResult;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use uuid::Uuid;
 
 /// `DataFlow` is an intermediate structure which primary purpose is to store the loaded libraries.
@@ -46,6 +59,32 @@ pub struct DataFlow {
     pub(crate) connectors: HashMap<NodeId, ZFConnectorRecord>,
     pub(crate) links: Vec<LinkRecord>,
     pub(crate) counter: u32,
+    pub(crate) max_run_durations: HashMap<NodeId, Duration>,
+    pub(crate) credits: HashMap<NodeId, usize>,
+    pub(crate) warmups: HashMap<NodeId, WarmupDescriptor>,
+    pub(crate) cooldowns: HashMap<NodeId, Duration>,
+    pub(crate) gpus: HashMap<NodeId, GpuDescriptor>,
+    pub(crate) watchdogs: HashMap<NodeId, WatchdogDescriptor>,
+    /// All the nodes of the data flow, including those running on other daemons.
+    pub(crate) nodes: Vec<PhysicalNode>,
+    pub(crate) readiness: Option<ReadinessDescriptor>,
+    pub(crate) priority: u32,
+    pub(crate) preemption: PreemptionPolicy,
+    pub(crate) idle_timeout: Option<Duration>,
+    pub(crate) redaction: Vec<RedactionDescriptor>,
+    pub(crate) retention: Option<RetentionDescriptor>,
+    /// The number of copies of each replicated node, see
+    /// [`DataFlowDescriptor`](crate::model::descriptor::DataFlowDescriptor).
+    pub(crate) replicas: HashMap<NodeId, usize>,
+    /// The autoscaling of each replicated Operator, see [AutoscalingDescriptor].
+    pub(crate) autoscaling: HashMap<NodeId, AutoscalingDescriptor>,
+    pub(crate) exposed: Vec<OutputDescriptor>,
+    pub(crate) imported: Vec<InputDescriptor>,
+    /// The Zenoh sessions described in the data flow, see
+    /// [`DataFlowDescriptor`](crate::model::descriptor::DataFlowDescriptor).
+    pub(crate) sessions: HashMap<String, TransportDescriptor>,
+    /// The channels between the built-in Host nodes and the application embedding the instance.
+    pub(crate) host: HostChannels,
 }
 
 impl DataFlow {
@@ -66,6 +105,25 @@ impl DataFlow {
             connectors: HashMap::new(),
             links: Vec::new(),
             counter: 0,
+            max_run_durations: HashMap::new(),
+            credits: HashMap::new(),
+            warmups: HashMap::new(),
+            cooldowns: HashMap::new(),
+            gpus: HashMap::new(),
+            watchdogs: HashMap::new(),
+            nodes: Vec::new(),
+            readiness: None,
+            priority: 0,
+            preemption: PreemptionPolicy::default(),
+            idle_timeout: None,
+            redaction: Vec::new(),
+            retention: None,
+            replicas: HashMap::new(),
+            autoscaling: HashMap::new(),
+            exposed: Vec::new(),
+            imported: Vec::new(),
+            sessions: HashMap::new(),
+            host: HostChannels::default(),
         }
     }
 
@@ -76,6 +134,7 @@ impl DataFlow {
     /// If the Source is not correctly connected to downstream nodes, its data will never be
     /// received.
     pub fn add_source(&mut self, record: SourceRecord, constructor: SourceFn) {
+        self.nodes.push(PhysicalNode::from(&record));
         self.source_constructors.insert(
             record.id.clone(),
             SourceConstructor::new_static(record, constructor),
@@ -89,6 +148,7 @@ impl DataFlow {
     /// If the Operator is not correctly connected to upstream and downstream nodes, it will never
     /// receive, process and emit data.
     pub fn add_operator(&mut self, record: OperatorRecord, constructor: OperatorFn) {
+        self.nodes.push(PhysicalNode::from(&record));
         self.operator_constructors.insert(
             record.id.clone(),
             OperatorConstructor::new_static(record, constructor),
@@ -101,6 +161,7 @@ impl DataFlow {
     ///
     /// If the Sink is not correctly connected to upstream nodes, it will never receive data.
     pub fn add_sink(&mut self, record: SinkRecord, constructor: SinkFn) {
+        self.nodes.push(PhysicalNode::from(&record));
         self.sink_constructors.insert(
             record.id.clone(),
             SinkConstructor::new_static(record, constructor),
@@ -121,10 +182,119 @@ impl DataFlow {
             shared_memory_element_size: None,
             shared_memory_elements: None,
             shared_memory_backoff: None,
+            queue: None,
+            capacity: None,
         });
         self.counter += 1;
     }
 
+    /// Set the maximum duration of an iteration of the node `node_id`: an iteration that takes
+    /// longer is interrupted and treated as an error.
+    ///
+    /// **Unless you know very well what you are doing, you should not use this method**.
+    pub fn set_max_run_duration(&mut self, node_id: NodeId, max_run_duration: Duration) {
+        self.max_run_durations.insert(node_id, max_run_duration);
+    }
+
+    /// Set the `credits` of the Source `source_id`: each of its links holds at most that many
+    /// messages and the Source is only iterated when all its downstream nodes can accept more.
+    ///
+    /// **Unless you know very well what you are doing, you should not use this method**.
+    pub fn set_credits(&mut self, source_id: NodeId, credits: usize) {
+        self.credits.insert(source_id, credits);
+    }
+
+    /// Set the warm-up of the node `node_id`: the data it sends after it is started are discarded
+    /// until the warm-up is over.
+    ///
+    /// **Unless you know very well what you are doing, you should not use this method**.
+    pub fn set_warmup(&mut self, node_id: NodeId, warmup: WarmupDescriptor) {
+        self.warmups.insert(node_id, warmup);
+    }
+
+    /// Set the grace period, after the node `node_id` is stopped, before `clean` is called on it.
+    ///
+    /// **Unless you know very well what you are doing, you should not use this method**.
+    pub fn set_cooldown(&mut self, node_id: NodeId, cooldown: Duration) {
+        self.cooldowns.insert(node_id, cooldown);
+    }
+
+    /// Set the GPUs the node `node_id` needs: they are assigned when the instance is created.
+    ///
+    /// **Unless you know very well what you are doing, you should not use this method**.
+    pub fn set_gpu(&mut self, node_id: NodeId, gpu: GpuDescriptor) {
+        self.gpus.insert(node_id, gpu);
+    }
+
+    /// Set the watchdog of the node `node_id`: its hung iterations are interrupted and restarted.
+    ///
+    /// **Unless you know very well what you are doing, you should not use this method**.
+    pub fn set_watchdog(&mut self, node_id: NodeId, watchdog: WatchdogDescriptor) {
+        self.watchdogs.insert(node_id, watchdog);
+    }
+
+    /// Set the readiness checks that must pass before the Sources start.
+    ///
+    /// **Unless you know very well what you are doing, you should not use this method**.
+    pub fn set_readiness(&mut self, readiness: ReadinessDescriptor) {
+        self.readiness = Some(readiness);
+    }
+
+    /// Set the priority of the data flow and what may be done to it when an instance with a higher
+    /// priority needs the resources it holds.
+    ///
+    /// **Unless you know very well what you are doing, you should not use this method**.
+    pub fn set_priority(&mut self, priority: u32, preemption: PreemptionPolicy) {
+        self.priority = priority;
+        self.preemption = preemption;
+    }
+
+    /// Set for how long the links of the instance can carry no message before it is reported idle.
+    ///
+    /// **Unless you know very well what you are doing, you should not use this method**.
+    pub fn set_idle_timeout(&mut self, idle_timeout: Duration) {
+        self.idle_timeout = Some(idle_timeout);
+    }
+
+    /// Add a redaction rule: how the payloads sent on an output are altered before they are published
+    /// by a debug tap or stored by a recording.
+    ///
+    /// **Unless you know very well what you are doing, you should not use this method**.
+    pub fn add_redaction(&mut self, rule: RedactionDescriptor) {
+        self.redaction.push(rule);
+    }
+
+    /// Set the retention policy of the recordings and of the events produced by the instance.
+    ///
+    /// **Unless you know very well what you are doing, you should not use this method**.
+    pub fn set_retention(&mut self, retention: RetentionDescriptor) {
+        self.retention = Some(retention);
+    }
+
+    /// Describe the Zenoh session `name` the connectors can use, when the runtime does not define
+    /// it.
+    ///
+    /// **Unless you know very well what you are doing, you should not use this method**.
+    pub fn add_session(&mut self, name: impl AsRef<str>, transport: TransportDescriptor) {
+        self.sessions.insert(name.as_ref().into(), transport);
+    }
+
+    /// Expose the `output`: its data are published on Zenoh, for external applications to subscribe
+    /// to them under the key expression generated by [`EXPOSED_PATH`](crate::EXPOSED_PATH).
+    ///
+    /// **Unless you know very well what you are doing, you should not use this method**.
+    pub fn expose(&mut self, output: OutputDescriptor) {
+        self.exposed.push(output);
+    }
+
+    /// Import the `input`: it can be connected, while the instance runs, to an exposed output of
+    /// another instance.
+    ///
+    /// **Unless you know very well what you are doing, you should not use this method**.
+    pub fn import(&mut self, input: InputDescriptor) {
+        self.imported.push(input);
+    }
+
     /// Given a `DataFlowRecord`, create the corresponding `DataFlow` by dynamically loading the
     /// shared libraries.
     ///
@@ -132,6 +302,18 @@ impl DataFlow {
     ///
     /// Failures can happen when trying to load node factories.
     pub fn try_new(record: DataFlowRecord, context: RuntimeContext) -> ZFResult<Self> {
+        Self::try_load(record, context, &mut |_, e| Err(e))
+    }
+
+    /// Creates the `DataFlow` of the `record`, loading the nodes running on this runtime.
+    ///
+    /// The nodes that could not be loaded are given to `on_error`: they are left out of the
+    /// `DataFlow` unless it returns an error, which is then returned.
+    pub(crate) fn try_load(
+        record: DataFlowRecord,
+        context: RuntimeContext,
+        on_error: &mut dyn FnMut(&NodeId, Error) -> ZFResult<()>,
+    ) -> ZFResult<Self> {
         let DataFlowRecord {
             uuid,
             flow,
@@ -141,42 +323,79 @@ impl DataFlow {
             connectors,
             links,
             counter,
+            max_run_durations,
+            credits,
+            warmups,
+            cooldowns,
+            gpus,
+            watchdogs,
+            readiness,
+            priority,
+            preemption,
+            idle_timeout,
+            // The expiry is enforced by the runtime that created the instance.
+            expiry: _,
+            // The clocks are only checked when the instance is created.
+            max_clock_skew: _,
+            redaction,
+            retention,
+            // The compression, which depends on the data types, is set in the records of the
+            // connectors.
+            compression: _,
+            types: _,
+            replicas,
+            autoscaling,
+            exposed,
+            imported,
+            sessions,
         } = record;
 
-        let source_constructors = sources
+        let nodes = sources
+            .values()
+            .map(PhysicalNode::from)
+            .chain(operators.values().map(PhysicalNode::from))
+            .chain(sinks.values().map(PhysicalNode::from))
+            .chain(connectors.values().map(PhysicalNode::from))
+            .collect();
+
+        let mut source_constructors: HashMap<NodeId, SourceConstructor> = HashMap::new();
+        for (source_id, source_record) in sources
             .into_iter()
             .filter(|(_, record)| record.runtime == context.runtime_name)
-            .map(|(source_id, source_record)| {
-                context
-                    .loader
-                    .load_source_constructor(source_record)
-                    // `map` leaves the Error untouched and allows us to transform the Ok into a
-                    // tuple so we can finally build an HashMap
-                    .map(|source_constructor| (source_id, source_constructor))
-            })
-            .collect::<ZFResult<HashMap<NodeId, SourceConstructor>>>()?;
-
-        let operator_constructors = operators
+        {
+            match context.loader.load_source_constructor(source_record) {
+                Ok(source_constructor) => {
+                    source_constructors.insert(source_id, source_constructor);
+                }
+                Err(e) => on_error(&source_id, e)?,
+            }
+        }
+
+        let mut operator_constructors: HashMap<NodeId, OperatorConstructor> = HashMap::new();
+        for (operator_id, operator_record) in operators
             .into_iter()
             .filter(|(_, record)| record.runtime == context.runtime_name)
-            .map(|(operator_id, operator_record)| {
-                context
-                    .loader
-                    .load_operator_constructor(operator_record)
-                    .map(|operator_constructor| (operator_id, operator_constructor))
-            })
-            .collect::<ZFResult<HashMap<NodeId, OperatorConstructor>>>()?;
-
-        let sink_constructors = sinks
+        {
+            match context.loader.load_operator_constructor(operator_record) {
+                Ok(operator_constructor) => {
+                    operator_constructors.insert(operator_id, operator_constructor);
+                }
+                Err(e) => on_error(&operator_id, e)?,
+            }
+        }
+
+        let mut sink_constructors: HashMap<NodeId, SinkConstructor> = HashMap::new();
+        for (sink_id, sink_record) in sinks
             .into_iter()
             .filter(|(_, record)| record.runtime == context.runtime_name)
-            .map(|(sink_id, sink_record)| {
-                context
-                    .loader
-                    .load_sink_constructor(sink_record)
-                    .map(|sink_constructor| (sink_id, sink_constructor))
-            })
-            .collect::<ZFResult<HashMap<NodeId, SinkConstructor>>>()?;
+        {
+            match context.loader.load_sink_constructor(sink_record) {
+                Ok(sink_constructor) => {
+                    sink_constructors.insert(sink_id, sink_constructor);
+                }
+                Err(e) => on_error(&sink_id, e)?,
+            }
+        }
 
         let connectors = connectors
             .into_iter()
@@ -193,6 +412,41 @@ impl DataFlow {
             connectors,
             links,
             counter,
+            max_run_durations,
+            credits,
+            warmups,
+            cooldowns,
+            gpus,
+            watchdogs,
+            nodes,
+            readiness,
+            priority,
+            preemption,
+            idle_timeout,
+            redaction,
+            retention,
+            replicas,
+            autoscaling,
+            exposed,
+            imported,
+            sessions,
+            host: HostChannels::default(),
         })
     }
+
+    /// Creates the [KeyedState] of the Operator `operator_id`, owning the partitions of its index
+    /// if it is a copy of a replicated Operator.
+    pub(crate) fn keyed_state(&self, operator_id: &NodeId) -> Arc<KeyedState> {
+        match replica_of(&self.replicas, operator_id) {
+            Some((_, index, count)) => Arc::new(KeyedState::new(index, count)),
+            None => Arc::new(KeyedState::default()),
+        }
+    }
+
+    /// Returns the id under which the keyed state of the Operator `operator_id` is saved in a
+    /// snapshot: the copies of a replicated Operator save their partitions under the id it has in
+    /// the descriptor, such that they can be restored with a different number of copies.
+    pub(crate) fn keyed_state_id(&self, operator_id: &NodeId) -> NodeId {
+        replica_of(&self.replicas, operator_id).map_or_else(|| operator_id.clone(), |(id, _, _)| id)
+    }
 }
@@ -30,12 +30,12 @@ use libloading::Library;
 /// defined by the user to create it (through the implementation of [`Source`](`Source`),
 /// [`Operator`](`Operator`), or [`Sink`](`Sink`)).
 ///
-/// The `_library` is a reference over the dynamically loaded shared library. It can be `None` when
+/// The `library` is a reference over the dynamically loaded shared library. It can be `None` when
 /// the factory is created programmatically.
 pub(crate) struct NodeConstructor<Record, C: ConstructorFn> {
     pub(crate) record: Record,
     pub(crate) constructor: C,
-    _library: Option<Arc<Library>>,
+    library: Option<Arc<Library>>,
 }
 /// `ConstructorFn` is a private trait that prevents us from associating any function to the
 /// `Constructor` of [`NodeConstructor`](`NodeConstructor`) struct.
@@ -95,7 +95,7 @@ impl<Record, C: ConstructorFn> NodeConstructor<Record, C> {
         Self {
             record,
             constructor,
-            _library: None,
+            library: None,
         }
     }
 
@@ -103,7 +103,25 @@ impl<Record, C: ConstructorFn> NodeConstructor<Record, C> {
         Self {
             record,
             constructor,
-            _library: Some(library),
+            library: Some(library),
+        }
+    }
+
+    /// Returns the shared library in which the node is defined, if it was loaded dynamically.
+    pub(crate) fn library(&self) -> Option<&Arc<Library>> {
+        self.library.as_ref()
+    }
+
+    /// Returns a constructor of the same node, from the same library, with another `record`: the
+    /// one of a new copy of a replicated node for instance.
+    pub(crate) fn with_record(&self, record: Record) -> Self
+    where
+        C: Copy,
+    {
+        Self {
+            record,
+            constructor: self.constructor,
+            library: self.library.clone(),
         }
     }
 }
@@ -0,0 +1,174 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::model::record::{
+    DataFlowRecord, LinkRecord, OperatorRecord, SinkRecord, SourceRecord, ZFConnectorKind,
+    ZFConnectorRecord,
+};
+use crate::types::{NodeId, PortId, RuntimeId};
+use crate::utils::{deserialize_duration, serialize_duration};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// The physical graph of an instance: the nodes and links as they are deployed, after the composite
+/// operators were flattened, the builtin operators inserted (sampling, faults, merge) and the
+/// connectors generated for the links crossing daemons.
+///
+/// It can be serialized, e.g. to JSON, to be consumed by tools.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PhysicalGraph {
+    pub uuid: Uuid,
+    pub flow: String,
+    /// The nodes of the instance, on all daemons, sorted by id.
+    pub nodes: Vec<PhysicalNode>,
+    pub links: Vec<LinkRecord>,
+}
+
+/// A node of a [PhysicalGraph].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PhysicalNode {
+    pub id: NodeId,
+    pub kind: PhysicalNodeKind,
+    /// The daemon running the node.
+    pub runtime: RuntimeId,
+    pub inputs: Vec<PortId>,
+    pub outputs: Vec<PortId>,
+    #[serde(default)]
+    #[serde(
+        deserialize_with = "deserialize_duration",
+        serialize_with = "serialize_duration",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_run_duration: Option<Duration>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credits: Option<usize>,
+}
+
+/// The kind of a [PhysicalNode].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum PhysicalNodeKind {
+    Source,
+    Operator,
+    Sink,
+    /// A connector publishing, on Zenoh, the messages of a link crossing daemons.
+    Sender,
+    /// A connector receiving, from Zenoh, the messages of a link crossing daemons.
+    Receiver,
+}
+
+impl From<&DataFlowRecord> for PhysicalGraph {
+    fn from(record: &DataFlowRecord) -> Self {
+        let mut nodes = record
+            .sources
+            .values()
+            .map(PhysicalNode::from)
+            .chain(record.operators.values().map(PhysicalNode::from))
+            .chain(record.sinks.values().map(PhysicalNode::from))
+            .chain(record.connectors.values().map(PhysicalNode::from))
+            .map(|node| PhysicalNode {
+                max_run_duration: record.max_run_durations.get(&node.id).copied(),
+                credits: record.credits.get(&node.id).copied(),
+                ..node
+            })
+            .collect::<Vec<_>>();
+        nodes.sort_by(|left, right| left.id.cmp(&right.id));
+
+        Self {
+            uuid: record.uuid,
+            flow: record.flow.clone(),
+            nodes,
+            links: record.links.clone(),
+        }
+    }
+}
+
+impl PhysicalNode {
+    fn new(
+        id: &NodeId,
+        kind: PhysicalNodeKind,
+        runtime: &RuntimeId,
+        inputs: Vec<PortId>,
+        outputs: Vec<PortId>,
+    ) -> Self {
+        Self {
+            id: id.clone(),
+            kind,
+            runtime: runtime.clone(),
+            inputs,
+            outputs,
+            max_run_duration: None,
+            credits: None,
+        }
+    }
+}
+
+impl From<&SourceRecord> for PhysicalNode {
+    fn from(record: &SourceRecord) -> Self {
+        Self::new(
+            &record.id,
+            PhysicalNodeKind::Source,
+            &record.runtime,
+            Vec::new(),
+            record.outputs.iter().map(|o| o.port_id.clone()).collect(),
+        )
+    }
+}
+
+impl From<&OperatorRecord> for PhysicalNode {
+    fn from(record: &OperatorRecord) -> Self {
+        Self::new(
+            &record.id,
+            PhysicalNodeKind::Operator,
+            &record.runtime,
+            record.inputs.iter().map(|i| i.port_id.clone()).collect(),
+            record.outputs.iter().map(|o| o.port_id.clone()).collect(),
+        )
+    }
+}
+
+impl From<&SinkRecord> for PhysicalNode {
+    fn from(record: &SinkRecord) -> Self {
+        Self::new(
+            &record.id,
+            PhysicalNodeKind::Sink,
+            &record.runtime,
+            record.inputs.iter().map(|i| i.port_id.clone()).collect(),
+            Vec::new(),
+        )
+    }
+}
+
+impl From<&ZFConnectorRecord> for PhysicalNode {
+    fn from(record: &ZFConnectorRecord) -> Self {
+        let port = vec![record.link_id.port_id.clone()];
+        match record.kind {
+            ZFConnectorKind::Sender => Self::new(
+                &record.id,
+                PhysicalNodeKind::Sender,
+                &record.runtime,
+                port,
+                Vec::new(),
+            ),
+            ZFConnectorKind::Receiver => Self::new(
+                &record.id,
+                PhysicalNodeKind::Receiver,
+                &record.runtime,
+                Vec::new(),
+                port,
+            ),
+        }
+    }
+}
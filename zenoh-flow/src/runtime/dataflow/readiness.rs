@@ -0,0 +1,98 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::executor::{self, fs, net::TcpStream};
+use crate::model::descriptor::{ReadinessCheck, ReadinessDescriptor, ReadinessFailure};
+use crate::zfresult::ErrorKind;
+use crate::{bail, Result};
+use itertools::Itertools;
+use std::time::{Duration, Instant};
+use zenoh::prelude::r#async::*;
+
+/// Waits until all the readiness checks pass or until their timeout expires.
+///
+/// The checks that already passed are not evaluated again.
+///
+/// # Errors
+///
+/// An error is returned if the timeout expired and the failure behavior is
+/// [`ReadinessFailure::Abort`].
+pub async fn wait_until_ready(session: &Session, readiness: &ReadinessDescriptor) -> Result<()> {
+    let deadline = Instant::now() + readiness.timeout();
+    let mut pending = readiness.checks.iter().collect::<Vec<_>>();
+
+    loop {
+        let mut failed = Vec::with_capacity(pending.len());
+        for check in pending {
+            if !is_ready(session, check, readiness.interval()).await {
+                failed.push(check);
+            }
+        }
+        pending = failed;
+
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            break;
+        }
+
+        log::debug!(
+            "[Readiness] Waiting for: {}",
+            pending.iter().map(|check| check.to_string()).join(", ")
+        );
+        executor::sleep(readiness.interval().min(deadline - now)).await;
+    }
+
+    let pending = pending.iter().map(|check| check.to_string()).join(", ");
+    match readiness.on_failure {
+        ReadinessFailure::Abort => bail!(
+            ErrorKind::NotReady,
+            "Readiness checks did not pass after {:?}: {}",
+            readiness.timeout(),
+            pending
+        ),
+        ReadinessFailure::Proceed => {
+            log::warn!(
+                "[Readiness] Readiness checks did not pass after {:?}, proceeding anyway: {}",
+                readiness.timeout(),
+                pending
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Evaluates a readiness check, giving it at most `limit` to complete.
+async fn is_ready(session: &Session, check: &ReadinessCheck, limit: Duration) -> bool {
+    let check = async {
+        match check {
+            ReadinessCheck::Zenoh(key_expr) => match session.get(key_expr.as_str()).res().await {
+                Ok(replies) => {
+                    matches!(replies.recv_async().await, Ok(reply) if reply.sample.is_ok())
+                }
+                Err(e) => {
+                    log::debug!("[Readiness] Failed to query < {key_expr} >: {e:?}");
+                    false
+                }
+            },
+            ReadinessCheck::Tcp(address) => TcpStream::connect(address.as_str()).await.is_ok(),
+            ReadinessCheck::File(path) => fs::exists(path).await,
+        }
+    };
+
+    executor::timeout(limit, check).await.unwrap_or(false)
+}
@@ -0,0 +1,110 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::{checksum, checksum_from_fragment, LibraryCache, LibraryCacheConfig};
+use url::Url;
+use uuid::Uuid;
+
+fn library_cache(max_size: Option<u64>) -> LibraryCache {
+    LibraryCache::new(LibraryCacheConfig {
+        directory: std::env::temp_dir().join(format!("zenoh-flow-cache-{}", Uuid::new_v4())),
+        max_size,
+    })
+}
+
+#[test]
+fn test_library_cache_content_addressable() {
+    let cache = library_cache(None);
+    assert!(cache.list().unwrap().is_empty());
+
+    let library = cache.store("libsource.so", b"source").unwrap();
+    assert_eq!(library.checksum, checksum(b"source"));
+    assert_eq!(library.size, 6);
+    assert_eq!(std::fs::read(&library.path).unwrap(), b"source");
+    assert!(library
+        .path
+        .starts_with(cache.directory().join(&library.checksum)));
+
+    // The same content is stored once, whatever its name.
+    let duplicate = cache.store("libsource-copy.so", b"source").unwrap();
+    assert_eq!(duplicate.path, library.path);
+    assert_eq!(cache.list().unwrap().len(), 1);
+
+    assert_eq!(
+        cache.get(&library.checksum).unwrap().unwrap().path,
+        library.path
+    );
+    assert!(cache.get(&checksum(b"sink")).unwrap().is_none());
+    assert!(cache.get("../libsource.so").is_err());
+    assert!(cache.store("../libsource.so", b"source").is_err());
+
+    cache.store("libsink.so", b"sink").unwrap();
+    assert_eq!(cache.size().unwrap(), 10);
+
+    assert!(cache.remove(&library.checksum).unwrap());
+    assert!(!cache.remove(&library.checksum).unwrap());
+    assert_eq!(cache.purge().unwrap(), 1);
+    assert!(cache.list().unwrap().is_empty());
+
+    std::fs::remove_dir_all(cache.directory()).unwrap();
+}
+
+#[test]
+fn test_library_cache_eviction() {
+    let cache = library_cache(Some(12));
+
+    let first = cache.store("libfirst.so", b"first").unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(5));
+    let second = cache.store("libsecond.so", b"second").unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(5));
+
+    // Using the first library makes the second one the least recently used.
+    cache.get(&first.checksum).unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(5));
+    let third = cache.store("libthird.so", b"third").unwrap();
+
+    let checksums = cache
+        .list()
+        .unwrap()
+        .into_iter()
+        .map(|library| library.checksum)
+        .collect::<Vec<_>>();
+    assert_eq!(checksums, vec![first.checksum, third.checksum]);
+    assert!(!second.path.exists());
+
+    // A library larger than the cache is kept until the next one is added.
+    let large = cache
+        .store("liblarge.so", b"larger than the cache")
+        .unwrap();
+    assert_eq!(cache.list().unwrap(), vec![large]);
+
+    std::fs::remove_dir_all(cache.directory()).unwrap();
+}
+
+#[test]
+fn test_checksum_from_fragment() {
+    let expected = checksum(b"source");
+    let url = Url::parse(&format!(
+        "https://example.com/libsource.so#sha256={}",
+        expected.to_uppercase()
+    ))
+    .unwrap();
+    assert_eq!(checksum_from_fragment(&url).unwrap(), Some(expected));
+
+    let url = Url::parse("https://example.com/libsource.so").unwrap();
+    assert_eq!(checksum_from_fragment(&url).unwrap(), None);
+
+    let url = Url::parse("https://example.com/libsource.so#md5=1234").unwrap();
+    assert!(checksum_from_fragment(&url).is_err());
+}
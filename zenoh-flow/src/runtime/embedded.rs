@@ -0,0 +1,591 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Run a data flow inside an existing application, without a daemon.
+//!
+//! The [Runtime] loads a data flow descriptor and runs all its nodes in the process of the
+//! application. The nodes can be loaded from their libraries, as a daemon would, or be registered
+//! directly by the application through their constructor.
+//!
+//! The application exchanges data with the data flow through the built-in Host nodes
+//! (`builtin://host`): the data it pushes on a [HostInput] are sent by the corresponding Host
+//! Source and the data received by a Host Sink are made available on a [HostOutput]. Instead, a
+//! Host Source can read a channel owned by the application (see [RuntimeBuilder::channel_source])
+//! and a Host Sink can invoke a closure for each data it receives (see
+//! [RuntimeBuilder::callback_sink]).
+//!
+//! ```yaml
+//! flow: embedded
+//!
+//! sources:
+//!   - id: camera
+//!     descriptor: builtin://host
+//!     configuration:
+//!       ports: [frame]
+//!
+//! operators:
+//!   - id: detector
+//!     descriptor: file:///etc/my-app/detector.yaml
+//!
+//! sinks:
+//!   - id: detections
+//!     descriptor: builtin://host
+//!     configuration:
+//!       ports: [boxes]
+//!
+//! links:
+//!   # camera.frame -> detector.frame -> detections.boxes
+//! ```
+//!
+//! ```ignore
+//! let mut runtime = Runtime::builder()
+//!     .descriptor(DataFlowDescriptor::from_yaml(&yaml)?)
+//!     .operator("detector", |context, configuration, inputs, outputs| {
+//!         Box::pin(async {
+//!             let node = Detector::new(context, configuration, inputs, outputs).await?;
+//!             Ok(Arc::new(node) as Arc<dyn Node>)
+//!         })
+//!     })
+//!     .build()
+//!     .await?;
+//!
+//! let camera = runtime.take_input("camera").unwrap();
+//! let detections = runtime.take_output("detections").unwrap();
+//! runtime.start().await?;
+//!
+//! camera.send("frame", frame_bytes).await?;
+//! let (_port, boxes) = detections.recv().await?;
+//!
+//! runtime.stop().await?;
+//! ```
+
+use crate::model::descriptor::{DataFlowDescriptor, NodeUri};
+use crate::model::record::DataFlowRecord;
+use crate::model::{Middleware, ZFUri};
+use crate::prelude::{Configuration, ErrorKind, NodeId, PortId};
+use crate::runtime::dataflow::instance::builtin::host::{
+    HostCallback, HostChannels, HostMessage, KEY_NODE,
+};
+use crate::runtime::dataflow::instance::record_sink::RecordingBackend;
+use crate::runtime::dataflow::instance::DataFlowInstance;
+use crate::runtime::dataflow::loader::{Loader, LoaderConfig};
+use crate::runtime::dataflow::node::{OperatorFn, SinkFn, SourceFn};
+use crate::runtime::dataflow::readiness::wait_until_ready;
+use crate::runtime::dataflow::DataFlow;
+use crate::runtime::gpu::GpuInventory;
+use crate::runtime::{map_to_infrastructure, RuntimeContext};
+use crate::types::RuntimeId;
+use crate::utils::parse_uri;
+use crate::{bail, zferror, Result as ZFResult};
+use crate::{
+    DEFAULT_SHM_ALLOCATION_BACKOFF_NS, DEFAULT_SHM_ELEMENT_SIZE, DEFAULT_SHM_TOTAL_ELEMENTS,
+};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::sync::Arc;
+use uhlc::HLC;
+use uuid::Uuid;
+use zenoh::prelude::r#async::*;
+use zenoh::Session;
+
+/// The name of the runtime, when none is provided to the [RuntimeBuilder].
+const DEFAULT_RUNTIME_NAME: &str = "embedded";
+
+/// A data flow instance running inside the application, see the [module documentation](self).
+pub struct Runtime {
+    instance: DataFlowInstance,
+    session: Arc<Session>,
+    inputs: HashMap<NodeId, HostInput>,
+    outputs: HashMap<NodeId, HostOutput>,
+}
+
+impl Runtime {
+    /// Returns a [RuntimeBuilder] to load a data flow descriptor and create its instance.
+    pub fn builder() -> RuntimeBuilder {
+        RuntimeBuilder::default()
+    }
+
+    /// Takes the [HostInput] of the Host Source `node`.
+    ///
+    /// Returns `None` if `node` is not a Host Source or if its input was already taken.
+    pub fn take_input(&mut self, node: impl AsRef<str>) -> Option<HostInput> {
+        self.inputs.remove(node.as_ref())
+    }
+
+    /// Takes the [HostOutput] of the Host Sink `node`.
+    ///
+    /// Returns `None` if `node` is not a Host Sink or if its output was already taken.
+    pub fn take_output(&mut self, node: impl AsRef<str>) -> Option<HostOutput> {
+        self.outputs.remove(node.as_ref())
+    }
+
+    /// Returns the underlying instance, e.g. to tap a link or to record an output.
+    pub fn instance(&self) -> &DataFlowInstance {
+        &self.instance
+    }
+
+    /// Returns a mutable reference over the underlying instance.
+    pub fn instance_mut(&mut self) -> &mut DataFlowInstance {
+        &mut self.instance
+    }
+
+    /// Starts the nodes of the instance: first the Sinks, the Operators and the connectors then,
+    /// once the readiness checks (if any) passed, the Sources.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if a node could not be started or if the readiness checks failed.
+    pub async fn start(&mut self) -> ZFResult<()> {
+        let nodes = self
+            .instance
+            .get_sinks()
+            .into_iter()
+            .chain(self.instance.get_operators())
+            .chain(self.instance.get_connectors())
+            .collect::<Vec<_>>();
+        for id in nodes {
+            self.instance.start_node(&id)?;
+        }
+
+        if let Some(readiness) = self.instance.readiness() {
+            wait_until_ready(&self.session, readiness).await?;
+        }
+
+        for id in self.instance.get_sources() {
+            self.instance.start_node(&id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Waits until all the Sinks received an `EndOfStream` on all their inputs, see
+    /// [`DataFlowInstance::wait_complete`].
+    ///
+    /// The Host Sources send theirs once the application dropped their [HostInput].
+    pub async fn wait_complete(&self) {
+        self.instance.wait_complete().await
+    }
+
+    /// Stops and cleans all the nodes of the instance, see [`DataFlowInstance::stop`].
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if at least one node could not be stopped or cleaned.
+    pub async fn stop(self) -> ZFResult<()> {
+        self.instance.stop().await
+    }
+}
+
+/// Builds a [Runtime]: the data flow descriptor is mandatory, everything else is optional.
+///
+/// The nodes registered with [source](RuntimeBuilder::source), [operator](RuntimeBuilder::operator)
+/// or [sink](RuntimeBuilder::sink) must be declared in the descriptor (which provides their ports
+/// and configuration): their constructor is used instead of their library, which can be omitted.
+///
+/// The nodes registered with [channel_source](RuntimeBuilder::channel_source) or
+/// [callback_sink](RuntimeBuilder::callback_sink) must be declared as Host nodes.
+#[derive(Default)]
+pub struct RuntimeBuilder {
+    name: Option<RuntimeId>,
+    session: Option<Arc<Session>>,
+    zenoh_config: Option<zenoh::config::Config>,
+    loader_config: LoaderConfig,
+    gpus: Vec<u32>,
+    descriptor: Option<DataFlowDescriptor>,
+    sources: HashMap<NodeId, SourceFn>,
+    operators: HashMap<NodeId, OperatorFn>,
+    sinks: HashMap<NodeId, SinkFn>,
+    channel_sources: HashMap<NodeId, flume::Receiver<HostMessage>>,
+    callback_sinks: HashMap<NodeId, HostCallback>,
+}
+
+impl RuntimeBuilder {
+    /// Sets the name of the runtime, on which all the nodes are mapped. Defaults to `embedded`.
+    pub fn name(mut self, name: impl AsRef<str>) -> Self {
+        self.name = Some(name.as_ref().into());
+        self
+    }
+
+    /// Sets the Zenoh session of the application, used by the connectors and the built-in nodes.
+    ///
+    /// If no session is provided, one is opened with the [zenoh_config](Self::zenoh_config).
+    pub fn session(mut self, session: Arc<Session>) -> Self {
+        self.session = Some(session);
+        self
+    }
+
+    /// Sets the configuration of the Zenoh session opened when none is provided. Defaults to the
+    /// default Zenoh configuration.
+    pub fn zenoh_config(mut self, config: zenoh::config::Config) -> Self {
+        self.zenoh_config = Some(config);
+        self
+    }
+
+    /// Sets the configuration of the loader of the node libraries.
+    pub fn loader_config(mut self, config: LoaderConfig) -> Self {
+        self.loader_config = config;
+        self
+    }
+
+    /// Sets the indexes of the GPUs the nodes can request (see
+    /// [GpuDescriptor](crate::model::descriptor::GpuDescriptor)). Defaults to none.
+    pub fn gpus(mut self, devices: Vec<u32>) -> Self {
+        self.gpus = devices;
+        self
+    }
+
+    /// Sets the descriptor of the data flow to run.
+    pub fn descriptor(mut self, descriptor: DataFlowDescriptor) -> Self {
+        self.descriptor = Some(descriptor);
+        self
+    }
+
+    /// Registers the `constructor` of the Source `node`.
+    pub fn source(mut self, node: impl AsRef<str>, constructor: SourceFn) -> Self {
+        self.sources.insert(node.as_ref().into(), constructor);
+        self
+    }
+
+    /// Registers the `constructor` of the Operator `node`.
+    pub fn operator(mut self, node: impl AsRef<str>, constructor: OperatorFn) -> Self {
+        self.operators.insert(node.as_ref().into(), constructor);
+        self
+    }
+
+    /// Registers the `constructor` of the Sink `node`.
+    pub fn sink(mut self, node: impl AsRef<str>, constructor: SinkFn) -> Self {
+        self.sinks.insert(node.as_ref().into(), constructor);
+        self
+    }
+
+    /// Makes the Host Source `node` send the data received on the `receiver`, on the output given
+    /// along each data. Its [HostInput] is then not available.
+    ///
+    /// Once all the senders of the channel are dropped, the Host Source sends an `EndOfStream` on
+    /// all its outputs.
+    pub fn channel_source(
+        mut self,
+        node: impl AsRef<str>,
+        receiver: flume::Receiver<(PortId, Vec<u8>)>,
+    ) -> Self {
+        self.channel_sources.insert(node.as_ref().into(), receiver);
+        self
+    }
+
+    /// Makes the Host Sink `node` invoke the `callback` with each data it receives, along with the
+    /// input on which it was received. Its [HostOutput] is then not available.
+    ///
+    /// The `callback` is invoked by the task running the Host Sink: it should not block.
+    pub fn callback_sink<F>(mut self, node: impl AsRef<str>, callback: F) -> Self
+    where
+        F: Fn(PortId, Vec<u8>) + Send + Sync + 'static,
+    {
+        self.callback_sinks
+            .insert(node.as_ref().into(), Arc::new(callback));
+        self
+    }
+
+    /// Flattens and validates the descriptor, then creates the instance: all its nodes are created
+    /// but none is started, see [`Runtime::start`].
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if:
+    /// - no descriptor was provided or it is not valid,
+    /// - the descriptor maps a node on another runtime,
+    /// - a registered node is not declared in the descriptor, or not as a Host node for the
+    ///   channel Sources and the callback Sinks,
+    /// - the Zenoh session could not be opened,
+    /// - a node could not be loaded or created.
+    pub async fn build(self) -> ZFResult<Runtime> {
+        let RuntimeBuilder {
+            name,
+            session,
+            zenoh_config,
+            loader_config,
+            gpus,
+            descriptor,
+            sources,
+            operators,
+            sinks,
+            mut channel_sources,
+            mut callback_sinks,
+        } = self;
+
+        let descriptor = descriptor.ok_or_else(|| {
+            zferror!(
+                ErrorKind::MissingConfiguration,
+                "No data flow descriptor was provided to the embedded runtime"
+            )
+        })?;
+        let name = name.unwrap_or_else(|| DEFAULT_RUNTIME_NAME.into());
+
+        let flattened = descriptor.flatten().await?;
+        flattened.validate()?;
+        let mapped = map_to_infrastructure(flattened, &name).await?;
+        if let Some(runtime) = mapped.get_runtimes().into_iter().find(|rt| *rt != name) {
+            bail!(
+                ErrorKind::ConfigurationError,
+                "Data flow < {} > maps nodes on runtime < {} >: an embedded runtime runs all the \
+                 nodes",
+                mapped.flow,
+                runtime
+            );
+        }
+
+        let mut record = DataFlowRecord::try_from((mapped, Uuid::new_v4()))?;
+
+        let mut host = HostChannels::default();
+        let mut inputs = HashMap::new();
+        let mut outputs = HashMap::new();
+        for source in record.sources.values_mut() {
+            if !is_host(&source.uri) {
+                continue;
+            }
+
+            set_node(&mut source.configuration, &source.id);
+            let receiver = match channel_sources.remove(&source.id) {
+                Some(receiver) => receiver,
+                None => {
+                    let (tx, rx) = flume::unbounded();
+                    let ports = source.outputs.iter().map(|port| port.port_id.clone());
+                    inputs.insert(
+                        source.id.clone(),
+                        HostInput {
+                            node: source.id.clone(),
+                            ports: ports.collect(),
+                            sender: tx,
+                        },
+                    );
+                    rx
+                }
+            };
+            host.sources.insert(source.id.clone(), receiver);
+        }
+        for sink in record.sinks.values_mut() {
+            if !is_host(&sink.uri) {
+                continue;
+            }
+
+            set_node(&mut sink.configuration, &sink.id);
+            let callback = match callback_sinks.remove(&sink.id) {
+                Some(callback) => callback,
+                None => {
+                    let (tx, rx) = flume::unbounded();
+                    outputs.insert(sink.id.clone(), HostOutput { receiver: rx });
+                    // The application not listening anymore should not stop the Sink.
+                    Arc::new(move |port: PortId, data: Vec<u8>| {
+                        if tx.send((port, data)).is_err() {
+                            log::trace!("[CallbackSink] the host application dropped its output");
+                        }
+                    }) as HostCallback
+                }
+            };
+            host.sinks.insert(sink.id.clone(), callback);
+        }
+
+        if let Some(node) = channel_sources
+            .into_keys()
+            .chain(callback_sinks.into_keys())
+            .next()
+        {
+            bail!(
+                ErrorKind::NodeNotFound(node.clone()),
+                "Node < {} > is registered as a Host node but not declared as one in the data flow",
+                node
+            );
+        }
+
+        // The registered nodes are not loaded: they are added once the others are.
+        let registered_sources = take_registered(&mut record.sources, sources)?;
+        let registered_operators = take_registered(&mut record.operators, operators)?;
+        let registered_sinks = take_registered(&mut record.sinks, sinks)?;
+
+        let session = match session {
+            Some(session) => session,
+            None => {
+                let session = zenoh::open(zenoh_config.unwrap_or_default())
+                    .res()
+                    .await
+                    .map_err(|e| {
+                        zferror!(
+                            ErrorKind::ZenohError,
+                            e => "Failed to open the Zenoh session of the embedded runtime: {}",
+                            e
+                        )
+                    })?;
+                Arc::new(session)
+            }
+        };
+
+        let hlc = Arc::new(HLC::default());
+        let context = RuntimeContext {
+            session: session.clone(),
+            loader: Arc::new(Loader::new(loader_config)),
+            hlc: hlc.clone(),
+            runtime_name: name,
+            runtime_uuid: Uuid::new_v4(),
+            shared_memory_element_size: DEFAULT_SHM_ELEMENT_SIZE as usize,
+            shared_memory_elements: DEFAULT_SHM_TOTAL_ELEMENTS as usize,
+            shared_memory_backoff: DEFAULT_SHM_ALLOCATION_BACKOFF_NS,
+            use_shm: false,
+            recording_backend: RecordingBackend::default(),
+            zenoh_configs: Arc::default(),
+            gpus: Arc::new(GpuInventory::new(gpus)),
+        };
+
+        let mut data_flow = DataFlow::try_new(record, context)?;
+        for (record, constructor) in registered_sources {
+            data_flow.add_source(record, constructor);
+        }
+        for (record, constructor) in registered_operators {
+            data_flow.add_operator(record, constructor);
+        }
+        for (record, constructor) in registered_sinks {
+            data_flow.add_sink(record, constructor);
+        }
+        data_flow.host = host;
+
+        let instance = DataFlowInstance::try_instantiate(data_flow, hlc).await?;
+
+        Ok(Runtime {
+            instance,
+            session,
+            inputs,
+            outputs,
+        })
+    }
+}
+
+/// Returns `true` if the `uri` designates the built-in Host nodes.
+fn is_host(uri: &Option<NodeUri>) -> bool {
+    matches!(
+        uri.as_ref()
+            .and_then(|uri| uri.resolve().ok())
+            .map(parse_uri),
+        Some(Ok(ZFUri::Builtin(Middleware::Host)))
+    )
+}
+
+/// Sets, in the `configuration` of a Host node, its id: it retrieves its channel with it.
+fn set_node(configuration: &mut Option<Configuration>, node: &NodeId) {
+    let configuration =
+        configuration.get_or_insert_with(|| Configuration::Object(Default::default()));
+    if let Some(object) = configuration.as_object_mut() {
+        object.insert(KEY_NODE.to_string(), node.to_string().into());
+    }
+}
+
+/// Removes, from the `records`, the ones of the `registered` nodes and pairs them with their
+/// constructor.
+///
+/// # Errors
+///
+/// An error is returned if a registered node has no record.
+fn take_registered<R, F>(
+    records: &mut HashMap<NodeId, R>,
+    registered: HashMap<NodeId, F>,
+) -> ZFResult<Vec<(R, F)>> {
+    registered
+        .into_iter()
+        .map(|(node, constructor)| match records.remove(&node) {
+            Some(record) => Ok((record, constructor)),
+            None => bail!(
+                ErrorKind::NodeNotFound(node.clone()),
+                "Node < {} > is registered but not declared in the data flow",
+                node
+            ),
+        })
+        .collect()
+}
+
+/// Pushes data into the data flow through the Host Source `node`.
+///
+/// It can be cloned to push data from several tasks. Once all its clones are dropped, the Host
+/// Source sends an `EndOfStream` on all its outputs.
+#[derive(Clone)]
+pub struct HostInput {
+    node: NodeId,
+    ports: Vec<PortId>,
+    sender: flume::Sender<HostMessage>,
+}
+
+impl HostInput {
+    /// Returns the id of the Host Source.
+    pub fn node(&self) -> &NodeId {
+        &self.node
+    }
+
+    /// Sends the `data` on the output `port` of the Host Source.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the Host Source has no output `port` or if the instance was
+    /// stopped.
+    pub async fn send(&self, port: impl AsRef<str>, data: impl Into<Vec<u8>>) -> ZFResult<()> {
+        let port = self
+            .ports
+            .iter()
+            .find(|id| id.as_ref() == port.as_ref())
+            .ok_or_else(|| {
+                zferror!(
+                    ErrorKind::MissingOutput(port.as_ref().to_string()),
+                    "Host Source < {} > has no output < {} >",
+                    self.node,
+                    port.as_ref()
+                )
+            })?;
+
+        self.sender
+            .send_async((port.clone(), data.into()))
+            .await
+            .map_err(|e| {
+                zferror!(
+                    ErrorKind::SendError,
+                    "Host Source < {} > is not running: {}",
+                    self.node,
+                    e
+                )
+                .into()
+            })
+    }
+}
+
+/// Receives the data that reached the Host Sink `node`, along with the input on which they were
+/// received.
+///
+/// The data are buffered until they are received: an application not interested in the outputs of
+/// a Host Sink should drop its `HostOutput`.
+pub struct HostOutput {
+    receiver: flume::Receiver<HostMessage>,
+}
+
+impl HostOutput {
+    /// Waits for the next data received by the Host Sink.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the instance was stopped.
+    pub async fn recv(&self) -> ZFResult<(PortId, Vec<u8>)> {
+        self.receiver
+            .recv_async()
+            .await
+            .map_err(|e| zferror!(ErrorKind::RecvError, "Host Sink is not running: {}", e).into())
+    }
+
+    /// Returns the next data received by the Host Sink, if any.
+    pub fn try_recv(&self) -> Option<(PortId, Vec<u8>)> {
+        self.receiver.try_recv().ok()
+    }
+}
@@ -0,0 +1,200 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::model::descriptor::GpuDescriptor;
+use crate::types::NodeId;
+use crate::zfresult::ErrorKind;
+use crate::{bail, Result as ZFResult};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// The key, in the configuration of a node, of the list of the GPUs it is assigned.
+pub static KEY_GPUS: &str = "gpus";
+
+/// A node using a GPU.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GpuAssignment {
+    pub instance: Uuid,
+    pub node: NodeId,
+    pub exclusive: bool,
+}
+
+/// The GPUs of the machine a runtime runs on, and the nodes they are assigned to.
+///
+/// The devices are declared in the configuration of the runtime, by their index. They are assigned
+/// to the nodes requesting them (see [GpuDescriptor]) when an instance is created, and released
+/// when it is dropped: a device used exclusively by a node is never assigned to another one, of
+/// any instance, meanwhile.
+#[derive(Debug, Default)]
+pub struct GpuInventory {
+    devices: Vec<u32>,
+    assignments: Mutex<HashMap<u32, Vec<GpuAssignment>>>,
+}
+
+impl GpuInventory {
+    /// Creates an inventory of the `devices`.
+    pub fn new(devices: Vec<u32>) -> Self {
+        Self {
+            devices,
+            assignments: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the devices of the inventory.
+    pub fn devices(&self) -> &[u32] {
+        &self.devices
+    }
+
+    /// Returns the nodes each device is currently assigned to.
+    pub fn assignments(&self) -> HashMap<u32, Vec<GpuAssignment>> {
+        self.assignments
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Assigns to the nodes of the instance `instance` the devices they request, the assignments
+    /// lasting as long as the returned [GpuLease].
+    ///
+    /// Either all the nodes are assigned their devices or none is. The requests for a specific
+    /// device are satisfied first, then the exclusive ones and finally the shared ones, which are
+    /// given the devices with the fewest users.
+    ///
+    /// # Errors
+    ///
+    /// An error variant is returned if a node requests a device that is not in the inventory, or
+    /// if there are not enough devices available.
+    pub(crate) fn allocate(
+        self: &Arc<Self>,
+        instance: Uuid,
+        requests: &HashMap<NodeId, GpuDescriptor>,
+    ) -> ZFResult<GpuLease> {
+        let mut guard = self.assignments.lock().unwrap_or_else(|e| e.into_inner());
+        let mut assignments = guard.clone();
+        let mut leased: HashMap<NodeId, Vec<u32>> = HashMap::with_capacity(requests.len());
+
+        let mut requests = requests.iter().collect::<Vec<_>>();
+        requests.sort_by_key(|(node, gpu)| (gpu.device.is_none(), !gpu.exclusive, (*node).clone()));
+
+        for (node, gpu) in requests {
+            let assignment = GpuAssignment {
+                instance,
+                node: node.clone(),
+                exclusive: gpu.exclusive,
+            };
+            let is_available = |device: &u32, assignments: &HashMap<u32, Vec<GpuAssignment>>| {
+                let users = assignments
+                    .get(device)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                if gpu.exclusive {
+                    users.is_empty()
+                } else {
+                    users.iter().all(|user| !user.exclusive)
+                }
+            };
+
+            let mut devices = Vec::with_capacity(gpu.count);
+            if let Some(device) = gpu.device {
+                if !self.devices.contains(&device) {
+                    bail!(
+                        ErrorKind::ConfigurationError,
+                        "The node < {} > requests the GPU < {} > that is not declared on this runtime",
+                        node,
+                        device
+                    )
+                }
+                if !is_available(&device, &assignments) {
+                    bail!(
+                        ErrorKind::ResourceUnavailable,
+                        "The node < {} > requests the GPU < {} > that is already used exclusively",
+                        node,
+                        device
+                    )
+                }
+                devices.push(device);
+            } else {
+                let mut available = self
+                    .devices
+                    .iter()
+                    .filter(|device| is_available(device, &assignments))
+                    .copied()
+                    .collect::<Vec<_>>();
+                available.sort_by_key(|device| assignments.get(device).map_or(0, Vec::len));
+                if available.len() < gpu.count {
+                    bail!(
+                        ErrorKind::ResourceUnavailable,
+                        "The node < {} > requests {} GPU(s) but only {} of the {} declared on this runtime are available",
+                        node,
+                        gpu.count,
+                        available.len(),
+                        self.devices.len()
+                    )
+                }
+                devices.extend(available.into_iter().take(gpu.count));
+            }
+
+            for device in devices.iter() {
+                assignments
+                    .entry(*device)
+                    .or_default()
+                    .push(assignment.clone());
+            }
+            devices.sort_unstable();
+            leased.insert(node.clone(), devices);
+        }
+
+        *guard = assignments;
+        Ok(GpuLease {
+            inventory: self.clone(),
+            instance,
+            devices: leased,
+        })
+    }
+
+    /// Releases all the devices assigned to the nodes of the instance `instance`.
+    fn release(&self, instance: &Uuid) {
+        let mut assignments = self.assignments.lock().unwrap_or_else(|e| e.into_inner());
+        assignments.retain(|_, users| {
+            users.retain(|user| user.instance != *instance);
+            !users.is_empty()
+        });
+    }
+}
+
+/// The devices assigned to the nodes of an instance, released when it is dropped.
+#[derive(Debug)]
+pub struct GpuLease {
+    inventory: Arc<GpuInventory>,
+    instance: Uuid,
+    devices: HashMap<NodeId, Vec<u32>>,
+}
+
+impl GpuLease {
+    /// Returns the devices assigned to the node `node_id`, if it requested any.
+    pub fn devices(&self, node_id: &NodeId) -> Option<&[u32]> {
+        self.devices.get(node_id).map(Vec::as_slice)
+    }
+}
+
+impl Drop for GpuLease {
+    fn drop(&mut self) {
+        self.inventory.release(&self.instance);
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/gpu-tests.rs"]
+mod tests;
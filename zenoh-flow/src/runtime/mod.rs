@@ -17,16 +17,22 @@ use std::collections::HashMap;
 use std::convert::TryFrom;
 
 use crate::model::descriptor::{
-    FlattenDataFlowDescriptor, OperatorDescriptor, SinkDescriptor, SourceDescriptor,
+    FlattenDataFlowDescriptor, InputDescriptor, OperatorDescriptor, OutputDescriptor,
+    SinkDescriptor, SourceDescriptor,
 };
 use crate::model::record::DataFlowRecord;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::Duration;
 use uuid::Uuid;
 
+use self::dataflow::instance::builtin::host::HostChannels;
+use self::dataflow::instance::record_sink::RecordingBackend;
+use self::dataflow::instance::recording::RecordingManifest;
 use self::dataflow::loader::LoaderConfig;
+use self::simulation::SimulationClock;
 use crate::runtime::dataflow::loader::Loader;
-use crate::types::{ControlMessage, FlowId, RuntimeId};
+use crate::types::{Blackboard, ControlMessage, FlowId, RuntimeId};
 use crate::zferror;
 use crate::zfresult::ErrorKind;
 use crate::{DaemonResult, Result as ZFResult};
@@ -35,8 +41,16 @@ use zenoh::Session;
 use zrpc::zrpcresult::{ZRPCError, ZRPCResult};
 use zrpc_macros::zservice;
 
+pub(crate) mod capture;
+pub mod clock;
 pub mod dataflow;
+pub mod embedded;
+pub mod gpu;
+pub(crate) mod placement;
+pub use embedded::{HostInput, HostOutput, Runtime, RuntimeBuilder};
+pub use placement::{missing_runtimes, unmap_runtimes};
 pub mod resources;
+pub mod simulation;
 pub mod worker_pool;
 
 /// The context of a Zenoh Flow runtime.
@@ -54,14 +68,58 @@ pub struct RuntimeContext {
     pub shared_memory_elements: usize,
     pub shared_memory_backoff: u64,
     pub use_shm: bool,
+    pub recording_backend: RecordingBackend,
+    /// The Zenoh configurations, indexed by name, the connectors can use instead of `session`.
+    pub zenoh_configs: Arc<HashMap<String, zenoh::config::Config>>,
+    /// The GPUs of the machine, assigned to the nodes requesting them.
+    pub gpus: Arc<gpu::GpuInventory>,
 }
 
 /// The context of a Zenoh Flow graph instance.
+///
+/// The `hlc` is the one used by the nodes of the instance: it differs from the one of the runtime
+/// when the instance runs in simulation mode, in which case `simulation` is set.
+///
+/// The `blackboard` is shared by all the nodes of the instance running on this runtime.
+///
+/// The `sessions` are the Zenoh sessions, opened for this instance, that the connectors requested
+/// through their `session` field.
+///
+/// The `host` channels link the built-in Host nodes to the application embedding the instance, see
+/// [`embedded::Runtime`].
 #[derive(Clone)]
 pub struct InstanceContext {
     pub flow_id: FlowId,
     pub instance_id: Uuid,
     pub runtime: RuntimeContext,
+    pub hlc: Arc<HLC>,
+    pub simulation: Option<SimulationClock>,
+    pub blackboard: Arc<Blackboard>,
+    pub sessions: HashMap<String, Arc<Session>>,
+    pub(crate) host: Arc<HostChannels>,
+}
+
+impl InstanceContext {
+    /// Returns the Zenoh session named `name`, or the session of the runtime if no name is
+    /// provided.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if no session named `name` was opened for this instance.
+    pub fn session(&self, name: Option<&str>) -> ZFResult<Arc<Session>> {
+        match name {
+            None => Ok(self.runtime.session.clone()),
+            Some(name) => self.sessions.get(name).cloned().ok_or_else(|| {
+                zferror!(
+                    ErrorKind::ConfigurationError,
+                    "No Zenoh session < {} > was opened for instance < {} >",
+                    name,
+                    self.instance_id
+                )
+                .into()
+            }),
+        }
+    }
 }
 
 /// This function maps a [`FlattenDataFlowDescriptor`](`FlattenDataFlowDescriptor`) into
@@ -73,34 +131,31 @@ pub struct InstanceContext {
 /// An error variant is returned in case of:
 /// - unable to map node to infrastructure
 pub async fn map_to_infrastructure(
-    mut descriptor: FlattenDataFlowDescriptor,
+    descriptor: FlattenDataFlowDescriptor,
     runtime: &str,
 ) -> ZFResult<FlattenDataFlowDescriptor> {
-    log::debug!("[Dataflow mapping] Begin mapping for: {}", descriptor.flow);
-
-    let runtime_id: Arc<str> = runtime.into();
-
-    // Initial "stupid" mapping, if an operator is not mapped, we map to the local runtime.
-    // function is async because it could involve other nodes.
-    let mut mapping = descriptor.mapping.clone().map_or(HashMap::new(), |m| m);
-
-    for o in &descriptor.operators {
-        mapping
-            .entry(o.id.clone())
-            .or_insert_with(|| runtime_id.clone());
-    }
+    map_to_runtimes(descriptor, &[runtime.into()]).await
+}
 
-    for o in &descriptor.sources {
-        mapping
-            .entry(o.id.clone())
-            .or_insert_with(|| runtime_id.clone());
-    }
+/// This function maps a [`FlattenDataFlowDescriptor`](`FlattenDataFlowDescriptor`) on the
+/// `runtimes`, the first one being preferred.
+///
+/// The nodes that are not mapped are assigned to a runtime honouring the `affinity` rules of the
+/// descriptor (see [`AffinityRule`](crate::model::descriptor::AffinityRule)): without rules, they
+/// are all mapped to the first runtime.
+///
+/// # Errors
+/// An error variant is returned in case of:
+/// - the affinity rules contradict each other or the mapping
+/// - there are not enough runtimes to separate the nodes that must be
+pub async fn map_to_runtimes(
+    mut descriptor: FlattenDataFlowDescriptor,
+    runtimes: &[RuntimeId],
+) -> ZFResult<FlattenDataFlowDescriptor> {
+    log::debug!("[Dataflow mapping] Begin mapping for: {}", descriptor.flow);
 
-    for o in &descriptor.sinks {
-        mapping
-            .entry(o.id.clone())
-            .or_insert_with(|| runtime_id.clone());
-    }
+    // Function is async because it could involve other nodes.
+    let mapping = placement::place(&descriptor, runtimes)?;
     log::trace!(
         "[Dataflow mapping] Mapping for: {} is {:?}",
         descriptor.flow,
@@ -203,6 +258,7 @@ pub enum JobKind {
     StopInstance(Uuid),
     StartNode(Uuid, String),
     StopNode(Uuid, String),
+    RestartNode(Uuid, String),
 }
 
 /// The status of a [`Job`](`Job`), associated with a timestamp from when the status change happenend
@@ -306,6 +362,15 @@ impl Job {
         }
     }
 
+    fn new_restart_node(fid: Uuid, node_id: String, id: Uuid, ts: Timestamp) -> Self {
+        Self {
+            id,
+            job: JobKind::RestartNode(fid, node_id),
+            status: JobStatus::Submitted(ts),
+            assignee: None,
+        }
+    }
+
     pub fn get_id(&self) -> &Uuid {
         &self.id
     }
@@ -344,6 +409,86 @@ impl Job {
     }
 }
 
+/// A management operation performed by a daemon, kept in the event log (see
+/// [`DaemonInterface::get_events`](DaemonInterface::get_events)).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Event {
+    pub timestamp: Timestamp,
+    /// Who requested the operation. As the requests carry no identity, this is the name of the
+    /// daemon the request was sent to.
+    pub actor: RuntimeId,
+    /// The daemon that performed the operation.
+    pub runtime: Uuid,
+    pub action: EventAction,
+    pub instance_id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node: Option<String>,
+    pub result: EventResult,
+}
+
+/// The operation of an [`Event`](`Event`).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum EventAction {
+    CreateInstance,
+    DeleteInstance,
+    Instantiate,
+    Teardown,
+    StartInstance,
+    StopInstance,
+    StartNode,
+    StopNode,
+    RestartNode,
+    /// The instance is about to expire, see
+    /// [`ExpiryDescriptor`](crate::model::descriptor::ExpiryDescriptor).
+    ExpiryWarning,
+    /// The instance expired and was torn down.
+    Expire,
+}
+
+/// The outcome of an [`Event`](`Event`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum EventResult {
+    Done,
+    Failed(String),
+}
+
+impl Event {
+    /// Creates the `Event` of the [`Job`](`Job`), once it is done or failed.
+    ///
+    /// Returns `None` if the job is still submitted or started.
+    pub fn from_job(job: &Job, actor: RuntimeId, runtime: Uuid) -> Option<Self> {
+        let (timestamp, result) = match &job.status {
+            JobStatus::Done(timestamp) => (*timestamp, EventResult::Done),
+            JobStatus::Failed(timestamp, error) => (*timestamp, EventResult::Failed(error.clone())),
+            JobStatus::Submitted(_) | JobStatus::Started(_) => return None,
+        };
+
+        let (action, instance_id, node) = match &job.job {
+            JobKind::CreateInstance(_, id) => (EventAction::CreateInstance, *id, None),
+            JobKind::DeleteInstance(id) => (EventAction::DeleteInstance, *id, None),
+            JobKind::Instantiate(_, id) => (EventAction::Instantiate, *id, None),
+            JobKind::Teardown(id) => (EventAction::Teardown, *id, None),
+            JobKind::StartInstance(id) => (EventAction::StartInstance, *id, None),
+            JobKind::StopInstance(id) => (EventAction::StopInstance, *id, None),
+            JobKind::StartNode(id, node) => (EventAction::StartNode, *id, Some(node.clone())),
+            JobKind::StopNode(id, node) => (EventAction::StopNode, *id, Some(node.clone())),
+            JobKind::RestartNode(id, node) => (EventAction::RestartNode, *id, Some(node.clone())),
+        };
+
+        Some(Self {
+            timestamp,
+            actor,
+            runtime,
+            action,
+            instance_id,
+            node,
+            result,
+        })
+    }
+}
+
 /// The interface the Daemon expose to a client
 /// (eg. the cli, or, the mgmt API)[^note]
 /// The service is exposed using zenoh-rpc, the server and client
@@ -472,6 +617,158 @@ pub trait DaemonInterface {
     /// - node already stopped
     async fn stop_node(&self, instance_id: Uuid, node: String) -> DaemonResult<()>;
 
+    /// Restarts the given graph node from the given instance: the node is stopped, re-created
+    /// (its state is thus re-initialized) with the same links and started again.
+    /// A graph node can be a source, a sink, a connector, or an operator.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - instance not found
+    /// - node not found
+    /// - node could not be re-created
+    async fn restart_node(&self, instance_id: Uuid, node: String) -> DaemonResult<()>;
+
+    /// Attaches a debug tap to the given output of the given node: every message sent on that
+    /// output is published on the returned key expression (see
+    /// [`DataFlowInstance::tap`](crate::runtime::dataflow::instance::DataFlowInstance::tap)).
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - instance not found
+    /// - node not found on this daemon
+    /// - output not found
+    async fn tap(
+        &self,
+        instance_id: Uuid,
+        node: String,
+        port: String,
+        decode: bool,
+    ) -> DaemonResult<String>;
+
+    /// Detaches the debug tap attached to the given output of the given node. Returns `true` if a
+    /// tap was detached.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - instance not found
+    async fn untap(&self, instance_id: Uuid, node: String, port: String) -> DaemonResult<bool>;
+
+    /// Sets (`enabled` is true) or removes a breakpoint on the given input of the given node or,
+    /// if no input is provided, on all its inputs (see
+    /// [`DataFlowInstance::set_breakpoint`](crate::runtime::dataflow::instance::DataFlowInstance::set_breakpoint)).
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - instance not found
+    /// - node not found on this daemon or without input
+    /// - input not found
+    async fn breakpoint(
+        &self,
+        instance_id: Uuid,
+        node: String,
+        port: Option<String>,
+        enabled: bool,
+    ) -> DaemonResult<()>;
+
+    /// Resumes the given node, paused on a breakpoint. If `step` is true, the node pauses again on
+    /// the next data message it receives.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - instance not found
+    /// - node not found on this daemon
+    /// - node not paused
+    async fn resume(&self, instance_id: Uuid, node: String, step: bool) -> DaemonResult<()>;
+
+    /// Starts (`enabled` is true) or stops the recording of all the outputs of the given instance,
+    /// on all involved daemons (see
+    /// [`DataFlowInstance::start_recording_all`](crate::runtime::dataflow::instance::DataFlowInstance::start_recording_all)).
+    /// Returns the [`RecordingManifest`](crate::runtime::dataflow::instance::recording::RecordingManifest)
+    /// of each daemon.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - instance not found
+    /// - recording disabled, already in progress or not in progress
+    async fn record(
+        &self,
+        instance_id: Uuid,
+        enabled: bool,
+    ) -> DaemonResult<Vec<RecordingManifest>>;
+
+    /// Returns, for each output of the nodes of the given instance, the number of messages sent on
+    /// it (see
+    /// [`DataFlowInstance::sent_messages`](crate::runtime::dataflow::instance::DataFlowInstance::sent_messages)).
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - instance not found
+    async fn get_sent_messages(
+        &self,
+        instance_id: Uuid,
+    ) -> DaemonResult<Vec<(OutputDescriptor, u64)>>;
+
+    /// Gets the event log: the management operations (instantiation, start, stop, etc.) performed
+    /// by all the daemons, ordered by their timestamp. Only the events of the instance are
+    /// returned if an `instance_id` is provided.
+    ///
+    /// The events are stored in Zenoh: a Zenoh storage must be configured for them to persist.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - error when retrieving the events
+    async fn get_events(&self, instance_id: Option<Uuid>) -> DaemonResult<Vec<Event>>;
+
+    /// Measures the offset between the HLC of this daemon and the one of every other daemon (see
+    /// [`ClockSkew`](clock::ClockSkew)).
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - error when retrieving the runtimes
+    async fn get_clock_skews(&self) -> DaemonResult<Vec<clock::ClockSkew>>;
+
+    /// Connects the given output of the instance of the flow `from_flow` to the given input of the
+    /// instance of the flow `to_flow`, replacing the previous connection of the input (see
+    /// [`DataFlowInstance::connect_import`](crate::runtime::dataflow::instance::DataFlowInstance::connect_import)).
+    /// Returns the key expression on which the output is exposed.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - no instance, or several instances, of one of the flows
+    /// - output not exposed
+    /// - input not imported
+    async fn compose(
+        &self,
+        from_flow: String,
+        output: OutputDescriptor,
+        to_flow: String,
+        input: InputDescriptor,
+    ) -> DaemonResult<String>;
+
+    /// Disconnects the given input of the instance of the flow `to_flow` from the output it is
+    /// connected to, if any. Returns the key expression on which that output is exposed.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - no instance, or several instances, of the flow
+    /// - node not found
+    async fn decompose(
+        &self,
+        to_flow: String,
+        input: InputDescriptor,
+    ) -> DaemonResult<Option<String>>;
+
     // FIXME A source now has several outputs.
 
     // /// Start a recording for the given source.
@@ -610,6 +907,35 @@ pub trait DaemonInterfaceInternal {
     /// - sources already stopped
     async fn stop_sources(&self, instance_id: Uuid) -> DaemonResult<()>;
 
+    /// Connects the imported input of the given instance to the exposed output of the instance
+    /// `from_instance`.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - instance not found
+    /// - input not imported
+    async fn connect_import(
+        &self,
+        instance_id: Uuid,
+        input: InputDescriptor,
+        from_instance: Uuid,
+        output: OutputDescriptor,
+    ) -> DaemonResult<String>;
+
+    /// Disconnects the imported input of the given instance from the exposed output it is
+    /// connected to, if any.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - instance not found
+    async fn disconnect_import(
+        &self,
+        instance_id: Uuid,
+        input: InputDescriptor,
+    ) -> DaemonResult<Option<String>>;
+
     /// Sends the `message` to `node` for the given instance.
     ///
     /// This is useful for sending out-of-band notification to a node (eg. in the case of deadline
@@ -655,4 +981,45 @@ pub trait DaemonInterfaceInternal {
     /// An error variant is returned in case of:
     /// - error on zenoh-rpc
     async fn check_sink_compatibility(&self, sink: SinkDescriptor) -> DaemonResult<bool>;
+
+    /// Reads the HLC of the runtime, to measure its offset with the HLC of another runtime (see
+    /// [`ClockSkew`](clock::ClockSkew)).
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    async fn clock(&self) -> DaemonResult<Timestamp>;
+
+    /// Returns the time elapsed since a node of the given instance running on the runtime last
+    /// sent a message (see
+    /// [`DataFlowInstance::idle_time`](crate::runtime::dataflow::instance::DataFlowInstance::idle_time)).
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - instance not found
+    async fn idle_time(&self, instance_id: Uuid) -> DaemonResult<Duration>;
+
+    /// Starts (`enabled` is true) or stops the recording of all the outputs of the nodes of the
+    /// given instance running on the runtime.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - instance not found
+    /// - recording disabled, already in progress or not in progress
+    async fn record_session(
+        &self,
+        instance_id: Uuid,
+        enabled: bool,
+    ) -> DaemonResult<RecordingManifest>;
+
+    /// Returns, for each output of the nodes of the given instance running on the runtime, the
+    /// number of messages sent on it.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - instance not found
+    async fn sent_messages(&self, instance_id: Uuid) -> DaemonResult<Vec<(OutputDescriptor, u64)>>;
 }
@@ -0,0 +1,235 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::model::descriptor::{AffinityRule, FlattenDataFlowDescriptor};
+use crate::types::{NodeId, RuntimeId};
+use crate::zfresult::ErrorKind;
+use crate::{bail, Result as ZFResult};
+use std::collections::{HashMap, HashSet};
+
+/// Maps every node of the `descriptor` to a runtime, honouring its `mapping` and its `affinity`
+/// rules (see [AffinityRule]).
+///
+/// The nodes to co-locate form groups placed as one. A group containing a mapped node is placed on
+/// the runtime of that node; the other groups are placed on the first of the `runtimes` that no
+/// group they must be separated from already uses --- the first runtime is thus preferred.
+///
+/// # Errors
+///
+/// An error variant is returned if:
+/// - a rule names a node that does not exist,
+/// - nodes to co-locate are mapped to different runtimes, or must also be separated,
+/// - nodes to separate are mapped to the same runtime,
+/// - there are not enough `runtimes` to separate the nodes.
+pub(crate) fn place(
+    descriptor: &FlattenDataFlowDescriptor,
+    runtimes: &[RuntimeId],
+) -> ZFResult<HashMap<NodeId, RuntimeId>> {
+    let nodes = descriptor.node_ids();
+    let index: HashMap<NodeId, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(position, node)| (node.clone(), position))
+        .collect();
+
+    let members = |rule: &AffinityRule| -> ZFResult<Vec<usize>> {
+        let mut members = vec![];
+        for node in rule.nodes() {
+            let ids = descriptor.affinity_members(node);
+            if ids.is_empty() {
+                bail!(
+                    ErrorKind::ConfigurationError,
+                    "The affinity rule < {} > names the unknown node < {} >",
+                    rule,
+                    node
+                )
+            }
+            members.extend(ids.iter().map(|id| index[id]));
+        }
+        Ok(members)
+    };
+
+    // The groups of nodes to co-locate, each node pointing to the first node of its group.
+    let mut groups = Groups::new(nodes.len());
+    for rule in &descriptor.affinity {
+        if let AffinityRule::Colocate(_) = rule {
+            let members = members(rule)?;
+            for pair in members.windows(2) {
+                groups.join(pair[0], pair[1]);
+            }
+        }
+    }
+
+    let mapping = descriptor.mapping.clone().unwrap_or_default();
+    let mut placement: HashMap<usize, (RuntimeId, usize)> = HashMap::new();
+    for (position, node) in nodes.iter().enumerate() {
+        if let Some(runtime) = mapping.get(node) {
+            let group = groups.find(position);
+            match placement.get(&group) {
+                Some((other_runtime, other)) if other_runtime != runtime => bail!(
+                    ErrorKind::ConfigurationError,
+                    "The nodes < {} > and < {} > must be co-located but are mapped to < {} > and < {} >",
+                    nodes[*other],
+                    node,
+                    other_runtime,
+                    runtime
+                ),
+                Some(_) => {}
+                None => {
+                    placement.insert(group, (runtime.clone(), position));
+                }
+            }
+        }
+    }
+
+    // The groups that cannot share a runtime.
+    let mut conflicts: HashMap<usize, HashSet<usize>> = HashMap::new();
+    for rule in &descriptor.affinity {
+        if let AffinityRule::Separate(_) = rule {
+            let members = members(rule)?;
+            for (position, left) in members.iter().enumerate() {
+                for right in members.iter().skip(position + 1) {
+                    let (left_group, right_group) = (groups.find(*left), groups.find(*right));
+                    if left_group == right_group {
+                        bail!(
+                            ErrorKind::ConfigurationError,
+                            "The nodes < {} > and < {} > must be both co-located and separated",
+                            nodes[*left],
+                            nodes[*right]
+                        )
+                    }
+                    conflicts.entry(left_group).or_default().insert(right_group);
+                    conflicts.entry(right_group).or_default().insert(left_group);
+                }
+            }
+        }
+    }
+
+    for (group, (runtime, position)) in placement.iter() {
+        for other in conflicts.get(group).into_iter().flatten() {
+            if let Some((other_runtime, other_position)) = placement.get(other) {
+                if other_runtime == runtime {
+                    bail!(
+                        ErrorKind::ConfigurationError,
+                        "The nodes < {} > and < {} > must be separated but are both mapped to < {} >",
+                        nodes[*position],
+                        nodes[*other_position],
+                        runtime
+                    )
+                }
+            }
+        }
+    }
+
+    for (position, node) in nodes.iter().enumerate() {
+        let group = groups.find(position);
+        if placement.contains_key(&group) {
+            continue;
+        }
+
+        let used = conflicts
+            .get(&group)
+            .into_iter()
+            .flatten()
+            .filter_map(|other| placement.get(other).map(|(runtime, _)| runtime))
+            .collect::<HashSet<_>>();
+        match runtimes.iter().find(|runtime| !used.contains(runtime)) {
+            Some(runtime) => {
+                placement.insert(group, (runtime.clone(), position));
+            }
+            None => bail!(
+                ErrorKind::ConfigurationError,
+                "Unable to place the node < {} >: the {} runtime(s) available already run nodes it must be separated from",
+                node,
+                runtimes.len()
+            ),
+        }
+    }
+
+    Ok(nodes
+        .iter()
+        .enumerate()
+        .map(|(position, node)| (node.clone(), placement[&groups.find(position)].0.clone()))
+        .collect())
+}
+
+/// Disjoint sets of nodes, identified by their position.
+struct Groups {
+    parents: Vec<usize>,
+}
+
+impl Groups {
+    fn new(size: usize) -> Self {
+        Self {
+            parents: (0..size).collect(),
+        }
+    }
+
+    /// Returns the node representing the group of the node `position`.
+    fn find(&self, mut position: usize) -> usize {
+        while self.parents[position] != position {
+            position = self.parents[position];
+        }
+        position
+    }
+
+    /// Merges the groups of the nodes `left` and `right`, the smallest position representing it.
+    fn join(&mut self, left: usize, right: usize) {
+        let (left, right) = (self.find(left), self.find(right));
+        if left < right {
+            self.parents[right] = left;
+        } else {
+            self.parents[left] = right;
+        }
+    }
+}
+
+/// Returns the runtimes the `mapping` of the `descriptor` names that are not among the `runtimes`,
+/// sorted.
+pub fn missing_runtimes(
+    descriptor: &FlattenDataFlowDescriptor,
+    runtimes: &[RuntimeId],
+) -> Vec<RuntimeId> {
+    let mut missing: Vec<RuntimeId> = descriptor
+        .get_runtimes()
+        .into_iter()
+        .filter(|runtime| !runtimes.contains(runtime))
+        .collect();
+    missing.sort();
+    missing
+}
+
+/// Removes from the `mapping` of the `descriptor` the nodes mapped to one of the `missing`
+/// runtimes: they are then placed as the nodes that are not mapped. Returns their ids, sorted.
+pub fn unmap_runtimes(
+    descriptor: &mut FlattenDataFlowDescriptor,
+    missing: &[RuntimeId],
+) -> Vec<NodeId> {
+    let mut unmapped = Vec::new();
+    if let Some(mapping) = descriptor.mapping.as_mut() {
+        mapping.retain(|node, runtime| {
+            let is_missing = missing.contains(runtime);
+            if is_missing {
+                unmapped.push(node.clone());
+            }
+            !is_missing
+        });
+    }
+    unmapped.sort();
+    unmapped
+}
+
+#[cfg(test)]
+#[path = "./tests/placement-tests.rs"]
+mod tests;
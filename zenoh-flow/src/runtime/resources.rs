@@ -25,19 +25,19 @@ extern crate serde_json;
 
 use crate::model::record::DataFlowRecord;
 use crate::model::registry::RegistryNode;
-use crate::runtime::{RuntimeConfig, RuntimeInfo, RuntimeStatus};
+use crate::runtime::{Event, RuntimeConfig, RuntimeInfo, RuntimeStatus};
 use crate::zfresult::ErrorKind;
 use crate::Result;
 use crate::{bail, zferror};
-use async_std::pin::Pin;
-use async_std::stream::Stream;
-use async_std::task::{Context, Poll};
-use futures::StreamExt;
+use futures::{Stream, StreamExt};
 use futures_lite::FutureExt;
 use pin_project_lite::pin_project;
 use serde::{de::DeserializeOwned, Serialize};
 use std::convert::TryFrom;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{SystemTime, UNIX_EPOCH};
 use uhlc::HLC;
 use uuid::Uuid;
 use zenoh::prelude::r#async::*;
@@ -80,6 +80,34 @@ pub static KEY_JOB_SUBMITTED: &str = "sumbitted";
 /// Token for the started jobs job queue in the key expression.
 pub static KEY_JOB_STARTED: &str = "started";
 
+/// Token for the faults injected on the links in the key expression.
+pub static KEY_FAULTS: &str = "faults";
+
+/// Token for the debug taps attached to the outputs in the key expression.
+pub static KEY_TAP: &str = "tap";
+
+/// Token for the exposed outputs in the key expression.
+pub static KEY_EXPOSED: &str = "exposed";
+
+/// Token for the recordings of the outputs in the key expression.
+pub static KEY_RECORDING: &str = "recording";
+
+/// Token for the nodes paused on a breakpoint in the key expression.
+pub static KEY_DEBUG: &str = "debug";
+
+/// Token for the entries of the blackboards shared by the nodes of an instance in the key
+/// expression.
+pub static KEY_BLACKBOARD: &str = "blackboard";
+
+/// Token for the idle notifications of the instances in the key expression.
+pub static KEY_IDLE: &str = "idle";
+
+/// Token for the lifecycle events of the instances in the key expression.
+pub static KEY_LIFECYCLE: &str = "lifecycle";
+
+/// Token for the event log in the key expression.
+pub static KEY_EVENTS: &str = "events";
+
 /// Token for the done jobs job queue in the key expression.
 pub static KEY_JOB_DONE: &str = "done";
 
@@ -127,6 +155,126 @@ macro_rules! RT_CONFIGURATION_PATH {
     };
 }
 
+/// Generates the key expression on which the faults injected on a link can be updated.
+#[macro_export]
+macro_rules! FAULTS_PATH {
+    ($prefix:expr, $iid:expr, $link:expr) => {
+        format!(
+            "{}/{}/{}/{}",
+            $prefix,
+            $crate::runtime::resources::KEY_FAULTS,
+            $iid,
+            $link
+        )
+    };
+}
+
+/// Generates the key expression on which the messages sent on an output are published by a debug
+/// tap.
+#[macro_export]
+macro_rules! TAP_PATH {
+    ($prefix:expr, $iid:expr, $node:expr, $port:expr) => {
+        format!(
+            "{}/{}/{}/{}/{}",
+            $prefix,
+            $crate::runtime::resources::KEY_TAP,
+            $iid,
+            $node,
+            $port
+        )
+    };
+}
+
+/// Generates the key expression on which the messages sent on an exposed output are published:
+/// `zenoh-flow/exposed/<instance id>/<node>/<port>`.
+#[macro_export]
+macro_rules! EXPOSED_PATH {
+    ($prefix:expr, $iid:expr, $node:expr, $port:expr) => {
+        format!(
+            "{}/{}/{}/{}/{}",
+            $prefix,
+            $crate::runtime::resources::KEY_EXPOSED,
+            $iid,
+            $node,
+            $port
+        )
+    };
+}
+
+/// Generates the key expression under which a recording of an output is stored.
+#[macro_export]
+macro_rules! RECORDING_PATH {
+    ($prefix:expr, $iid:expr, $node:expr, $port:expr, $rid:expr) => {
+        format!(
+            "{}/{}/{}/{}/{}/{}",
+            $prefix,
+            $crate::runtime::resources::KEY_RECORDING,
+            $iid,
+            $node,
+            $port,
+            $rid
+        )
+    };
+}
+
+/// Generates the key expression on which the message a node is paused on is published.
+#[macro_export]
+macro_rules! DEBUG_PATH {
+    ($prefix:expr, $iid:expr, $node:expr) => {
+        format!(
+            "{}/{}/{}/{}",
+            $prefix,
+            $crate::runtime::resources::KEY_DEBUG,
+            $iid,
+            $node
+        )
+    };
+}
+
+/// Generates the key expression under which an entry of the blackboard of an instance is shared.
+#[macro_export]
+macro_rules! BLACKBOARD_PATH {
+    ($prefix:expr, $iid:expr, $key:expr) => {
+        format!(
+            "{}/{}/{}/{}",
+            $prefix,
+            $crate::runtime::resources::KEY_BLACKBOARD,
+            $iid,
+            $key
+        )
+    };
+}
+
+/// Generates the key expression on which a runtime publishes when the part of an instance it runs
+/// becomes idle, or active again.
+#[macro_export]
+macro_rules! IDLE_PATH {
+    ($prefix:expr, $iid:expr, $rid:expr) => {
+        format!(
+            "{}/{}/{}/{}",
+            $prefix,
+            $crate::runtime::resources::KEY_IDLE,
+            $iid,
+            $rid
+        )
+    };
+}
+
+/// Generates the key expression on which the lifecycle events of a node of an instance are
+/// published: `zenoh-flow/lifecycle/<instance id>/<node id>`.
+#[macro_export]
+macro_rules! LIFECYCLE_PATH {
+    ($prefix:expr, $iid:expr, $node:expr) => {
+        format!(
+            "{}/{}/{}/{}",
+            $prefix,
+            $crate::runtime::resources::KEY_LIFECYCLE,
+            $iid,
+            $node
+        )
+    };
+}
+
 /// Generates the flow instance key expression.
 #[macro_export]
 macro_rules! RT_FLOW_PATH {
@@ -275,6 +423,35 @@ macro_rules! JQ_STARTED_JOB {
     };
 }
 
+/// Generates the key expression of an event of the event log, identified by the job that performed
+/// the operation.
+#[macro_export]
+macro_rules! EVENT_PATH {
+    ($prefix:expr, $rid:expr, $jid: expr) => {
+        format!(
+            "{}/{}/{}/{}/{}",
+            $prefix,
+            $crate::runtime::resources::KEY_RUNTIMES,
+            $rid,
+            $crate::runtime::resources::KEY_EVENTS,
+            $jid
+        )
+    };
+}
+
+/// Generates the selector of the events of all runtimes.
+#[macro_export]
+macro_rules! EVENTS_SELECTOR {
+    ($prefix:expr) => {
+        format!(
+            "{}/{}/*/{}/*",
+            $prefix,
+            $crate::runtime::resources::KEY_RUNTIMES,
+            $crate::runtime::resources::KEY_EVENTS
+        )
+    };
+}
+
 /// Generates the done job key expression
 #[macro_export]
 macro_rules! JQ_DONE_JOB {
@@ -840,6 +1017,76 @@ impl DataStore {
         self.z.put(&path, encoded_info).res().await
     }
 
+    /// Adds the [`Event`](`Event`) of the job `jid` to the event log.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - fails to serialize
+    /// - zenoh put fails
+    pub async fn add_event(&self, rtid: &Uuid, jid: &Uuid, event: &Event) -> Result<()> {
+        let path = EVENT_PATH!(ROOT_STANDALONE, rtid, jid);
+        let encoded_info = serialize_data(event)?;
+        self.z.put(&path, encoded_info).res().await
+    }
+
+    /// Gets the events of all runtimes, ordered by their timestamp.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - zenoh get fails
+    /// - fails to deserialize
+    pub async fn get_events(&self) -> Result<Vec<Event>> {
+        let selector = EVENTS_SELECTOR!(ROOT_STANDALONE);
+        let mut events = self.get_vec_from_zenoh::<Event>(&selector).await?;
+        events.sort_by_key(|event| event.timestamp);
+        Ok(events)
+    }
+
+    /// Removes, from the event log, the events of the instance `instance_id` performed by the
+    /// runtime `rtid` before `before`. Returns the number of events removed.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - zenoh get fails
+    /// - zenoh delete fails
+    pub async fn remove_events(
+        &self,
+        rtid: &Uuid,
+        instance_id: &Uuid,
+        before: SystemTime,
+    ) -> Result<usize> {
+        let selector = EVENT_PATH!(ROOT_STANDALONE, rtid, "*");
+        let replies = self.z.get(&selector).res().await?;
+
+        let mut removed = 0;
+        for reply in replies.into_iter() {
+            let sample = match reply.sample {
+                Ok(sample) => sample,
+                Err(_) => continue,
+            };
+            let event = match deserialize_data::<Event>(&sample.value.payload.contiguous()) {
+                Ok(event) => event,
+                Err(e) => {
+                    log::warn!(
+                        "Failed to deserialize the event < {} >: {e:?}",
+                        sample.key_expr
+                    );
+                    continue;
+                }
+            };
+            if event.instance_id != *instance_id
+                || UNIX_EPOCH + event.timestamp.get_time().to_duration() >= before
+            {
+                continue;
+            }
+
+            self.z.delete(sample.key_expr.as_str()).res().await?;
+            removed += 1;
+        }
+
+        Ok(removed)
+    }
+
     // Helpers
 
     /// Helper function to get a generic data `T` and deserializing it
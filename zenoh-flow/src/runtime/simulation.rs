@@ -0,0 +1,174 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use event_listener::Event;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+use uhlc::{HLCBuilder, Timestamp, HLC, ID, NTP64};
+
+/// The physical clock given to the [HLC](uhlc::HLC) of the instances running in simulation mode.
+///
+/// The HLC only accepts a function pointer as a physical clock, which cannot hold the simulated
+/// time of a given [SimulationClock]: the HLC is instead updated with the simulated time each
+/// time it changes, see [`SimulationClock::set`].
+fn zero_clock() -> NTP64 {
+    NTP64(0)
+}
+
+/// The state shared by the handles on a simulated time.
+///
+/// - `time` is the simulated time, as a NTP64;
+/// - `advanced` is notified each time the simulated time moves forward;
+/// - `hlcs` are the HLC created from this simulated time, updated when it changes.
+struct SimulatedTime {
+    time: AtomicU64,
+    advanced: Event,
+    hlcs: Mutex<Vec<Weak<HLC>>>,
+}
+
+/// A `SimulationClock` drives the virtual time of the data flow instances running in simulation
+/// mode (see [`DataFlowInstance::try_instantiate_simulated`]).
+///
+/// In simulation mode:
+/// - the timestamps of the messages are derived from the simulated time;
+/// - [`Context::sleep`] waits for the simulated time, not the real one, to elapse: periodic
+///   Sources hence fire in simulated time.
+///
+/// The simulated time only moves forward when [`advance`](SimulationClock::advance) or
+/// [`set`](SimulationClock::set) is called, which allows tests and batch replays to run
+/// deterministically and as fast as the nodes can process the messages.
+///
+/// Each `SimulationClock` created with [`new`](SimulationClock::new) has its own simulated time,
+/// shared by its clones: instances running against different clocks, in the same process, do not
+/// affect each other.
+///
+/// [`DataFlowInstance::try_instantiate_simulated`]: crate::runtime::dataflow::instance::DataFlowInstance::try_instantiate_simulated
+/// [`Context::sleep`]: crate::types::Context::sleep
+#[derive(Clone)]
+pub struct SimulationClock {
+    state: Arc<SimulatedTime>,
+}
+
+impl std::fmt::Debug for SimulationClock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SimulationClock")
+            .field("now", &self.now())
+            .finish()
+    }
+}
+
+impl Default for SimulationClock {
+    fn default() -> Self {
+        Self::new(Duration::ZERO)
+    }
+}
+
+impl SimulationClock {
+    /// Returns a handle on a new simulated time, starting at `start`.
+    pub fn new(start: Duration) -> Self {
+        Self {
+            state: Arc::new(SimulatedTime {
+                time: AtomicU64::new(NTP64::from(start).as_u64()),
+                advanced: Event::new(),
+                hlcs: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// Returns the current simulated time.
+    pub fn now(&self) -> Duration {
+        NTP64(self.state.time.load(Ordering::Acquire)).to_duration()
+    }
+
+    /// Sets the simulated time to `time`, waking up the nodes waiting for it.
+    ///
+    /// Setting a time in the past is allowed but timestamps generated by the
+    /// [HLC](uhlc::HLC) will remain monotonic.
+    pub fn set(&self, time: Duration) {
+        self.state
+            .time
+            .store(NTP64::from(time).as_u64(), Ordering::Release);
+
+        let mut hlcs = self.state.hlcs.lock().unwrap_or_else(|e| e.into_inner());
+        hlcs.retain(|hlc| match hlc.upgrade() {
+            Some(hlc) => {
+                update_hlc(&hlc, time);
+                true
+            }
+            None => false,
+        });
+        drop(hlcs);
+
+        self.state.advanced.notify(usize::MAX);
+    }
+
+    /// Moves the simulated time forward by `duration`, waking up the nodes waiting for it.
+    pub fn advance(&self, duration: Duration) {
+        self.set(self.now() + duration);
+    }
+
+    /// Waits until the simulated time reaches `deadline`.
+    pub async fn sleep_until(&self, deadline: Duration) {
+        loop {
+            if self.now() >= deadline {
+                return;
+            }
+
+            let listener = self.state.advanced.listen();
+            // The time could have moved forward before the listener was registered.
+            if self.now() >= deadline {
+                return;
+            }
+
+            listener.await;
+        }
+    }
+
+    /// Waits until `duration` has elapsed in simulated time.
+    pub async fn sleep(&self, duration: Duration) {
+        self.sleep_until(self.now() + duration).await
+    }
+
+    /// Creates an [HLC](uhlc::HLC) following the simulated time.
+    pub(crate) fn new_hlc(&self, id: ID) -> Arc<HLC> {
+        // The physical clock of the HLC stays at 0: the simulated time is always ahead of it.
+        let hlc = Arc::new(
+            HLCBuilder::new()
+                .with_id(id)
+                .with_clock(zero_clock)
+                .with_max_delta(Duration::from_secs(u32::MAX as u64))
+                .build(),
+        );
+        update_hlc(&hlc, self.now());
+        self.state
+            .hlcs
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(Arc::downgrade(&hlc));
+        hlc
+    }
+}
+
+/// Moves the `hlc` forward to the simulated `time`, the timestamps it generates then follow it.
+fn update_hlc(hlc: &HLC, time: Duration) {
+    let timestamp = Timestamp::new(NTP64::from(time), *hlc.get_id());
+    if let Err(e) = hlc.update_with_timestamp(&timestamp) {
+        log::error!("[Simulation] unable to update the HLC to the simulated time: {e}");
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/simulation-tests.rs"]
+mod tests;
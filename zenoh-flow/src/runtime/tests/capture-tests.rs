@@ -0,0 +1,103 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::{capture_output, forward_output, OutputStream, NODE_LOG_TARGET};
+use std::io::Cursor;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+
+/// Keeps the target, the level and the message of the records it is given.
+struct CapturingLogger(Mutex<Vec<(String, log::Level, String)>>);
+
+impl log::Log for CapturingLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.target().starts_with(NODE_LOG_TARGET)
+    }
+
+    fn log(&self, record: &log::Record) {
+        self.0.lock().unwrap().push((
+            record.target().to_string(),
+            record.level(),
+            record.args().to_string(),
+        ));
+    }
+
+    fn flush(&self) {}
+}
+
+#[test]
+fn test_forward_output() {
+    static LOGGER: CapturingLogger = CapturingLogger(Mutex::new(Vec::new()));
+
+    let output = Cursor::new(b"first\r\nsecond\n\xffthird".to_vec());
+    let handle = forward_output("camera".into(), OutputStream::Stdout, output, &LOGGER).unwrap();
+    assert_eq!(handle.join().unwrap(), 3);
+
+    let output = Cursor::new(b"warning\n".to_vec());
+    let handle = forward_output("camera".into(), OutputStream::Stderr, output, &LOGGER).unwrap();
+    assert_eq!(handle.join().unwrap(), 1);
+
+    let handle = forward_output(
+        "camera".into(),
+        OutputStream::Stderr,
+        Cursor::new(vec![]),
+        &LOGGER,
+    )
+    .unwrap();
+    assert_eq!(handle.join().unwrap(), 0);
+
+    let target = "zenoh_flow::node::camera".to_string();
+    assert_eq!(
+        *LOGGER.0.lock().unwrap(),
+        vec![
+            (
+                target.clone(),
+                log::Level::Info,
+                "[camera] first".to_string()
+            ),
+            (
+                target.clone(),
+                log::Level::Info,
+                "[camera] second".to_string()
+            ),
+            (
+                target.clone(),
+                log::Level::Info,
+                "[camera] \u{fffd}third".to_string()
+            ),
+            (target, log::Level::Warn, "[camera] warning".to_string()),
+        ]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_capture_output() {
+    let mut child = Command::new("sh")
+        .args(["-c", "echo out; echo err >&2; echo again"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let handles = capture_output(&"node".into(), &mut child).unwrap();
+    assert!(child.stdout.is_none() && child.stderr.is_none());
+    child.wait().unwrap();
+
+    let lines = handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(lines, vec![2, 1]);
+}
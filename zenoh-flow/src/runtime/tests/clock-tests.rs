@@ -0,0 +1,35 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::ClockSkew;
+use std::time::Duration;
+use uhlc::{Timestamp, HLC, NTP64};
+
+#[test]
+fn test_clock_skew() {
+    let id = *HLC::default().get_id();
+    let at = |seconds: u64| Timestamp::new(NTP64::from(Duration::from_secs(seconds)), id);
+
+    // The remote clock is read in the middle of the round trip.
+    let skew = ClockSkew::estimate("edge".into(), &at(10), &at(13), &at(12));
+    assert_eq!(skew.offset, Duration::from_secs(2));
+    assert!(skew.ahead);
+    assert_eq!(skew.round_trip, Duration::from_secs(2));
+    assert!(skew.exceeds(Duration::from_secs(1)));
+    assert!(!skew.exceeds(Duration::from_secs(2)));
+
+    let skew = ClockSkew::estimate("edge".into(), &at(10), &at(7), &at(12));
+    assert_eq!(skew.offset, Duration::from_secs(4));
+    assert!(!skew.ahead);
+}
@@ -0,0 +1,123 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::GpuInventory;
+use crate::model::descriptor::GpuDescriptor;
+use crate::types::NodeId;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+fn gpu(count: usize, device: Option<u32>, exclusive: bool) -> GpuDescriptor {
+    GpuDescriptor {
+        count,
+        device,
+        exclusive,
+    }
+}
+
+fn requests(requests: &[(&str, GpuDescriptor)]) -> HashMap<NodeId, GpuDescriptor> {
+    requests
+        .iter()
+        .map(|(node, gpu)| ((*node).into(), *gpu))
+        .collect()
+}
+
+#[test]
+fn test_gpu_exclusive_assignment() {
+    let inventory = Arc::new(GpuInventory::new(vec![0, 1]));
+
+    let first = inventory
+        .allocate(
+            Uuid::new_v4(),
+            &requests(&[("Detector", gpu(1, Some(1), true))]),
+        )
+        .unwrap();
+    assert_eq!(first.devices(&"Detector".into()), Some(&[1][..]));
+
+    // The device 1 is used exclusively: the second instance is assigned the device 0.
+    let second = inventory
+        .allocate(
+            Uuid::new_v4(),
+            &requests(&[("Detector", GpuDescriptor::default())]),
+        )
+        .unwrap();
+    assert_eq!(second.devices(&"Detector".into()), Some(&[0][..]));
+    assert!(second.devices(&"Camera".into()).is_none());
+
+    // No device is left, the failed allocation does not assign any.
+    assert!(inventory
+        .allocate(
+            Uuid::new_v4(),
+            &requests(&[
+                ("Classifier", gpu(1, None, false)),
+                ("Segmenter", GpuDescriptor::default()),
+            ]),
+        )
+        .is_err());
+    assert_eq!(inventory.assignments().len(), 2);
+
+    drop(first);
+    assert_eq!(inventory.assignments().len(), 1);
+    assert!(inventory
+        .allocate(
+            Uuid::new_v4(),
+            &requests(&[("Detector", gpu(1, Some(1), true))]),
+        )
+        .is_ok());
+    assert_eq!(inventory.assignments().len(), 1);
+
+    assert!(inventory
+        .allocate(
+            Uuid::new_v4(),
+            &requests(&[("Detector", gpu(1, Some(2), true))]),
+        )
+        .is_err());
+    assert!(inventory
+        .allocate(
+            Uuid::new_v4(),
+            &requests(&[("Detector", gpu(3, None, true))])
+        )
+        .is_err());
+}
+
+#[test]
+fn test_gpu_shared_assignment() {
+    let inventory = Arc::new(GpuInventory::new(vec![0, 1]));
+    let instance = Uuid::new_v4();
+
+    let lease = inventory
+        .allocate(
+            instance,
+            &requests(&[
+                ("Detector", GpuDescriptor::default()),
+                ("Classifier", gpu(1, None, false)),
+                ("Segmenter", gpu(1, None, false)),
+            ]),
+        )
+        .unwrap();
+
+    // The shared devices are not given to the exclusive request, which is satisfied first.
+    let detector = lease.devices(&"Detector".into()).unwrap().to_vec();
+    let classifier = lease.devices(&"Classifier".into()).unwrap().to_vec();
+    assert_ne!(detector, classifier);
+    assert_eq!(classifier, lease.devices(&"Segmenter".into()).unwrap());
+
+    let assignments = inventory.assignments();
+    assert_eq!(assignments[&classifier[0]].len(), 2);
+    assert!(assignments[&detector[0]][0].exclusive);
+
+    drop(lease);
+    assert!(inventory.assignments().is_empty());
+}
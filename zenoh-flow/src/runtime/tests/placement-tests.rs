@@ -0,0 +1,196 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::{missing_runtimes, place, unmap_runtimes};
+use crate::model::descriptor::{AffinityRule, FlattenDataFlowDescriptor, PreemptionPolicy};
+use crate::model::record::DataFlowRecord;
+use crate::types::{NodeId, RuntimeId};
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use uuid::Uuid;
+
+static DESCRIPTOR: &str = r#"
+flow: Cameras
+sources:
+  - id: Camera-0
+    uri: file://./target/release/libcamera.so
+    outputs: [Frame]
+  - id: Camera-1
+    uri: file://./target/release/libcamera.so
+    outputs: [Frame]
+operators:
+  - id: Detector-0
+    uri: file://./target/release/libdetector.so
+    inputs: [Frame]
+    outputs: [Objects]
+  - id: Detector-1
+    uri: file://./target/release/libdetector.so
+    inputs: [Frame]
+    outputs: [Objects]
+sinks:
+  - id: Display
+    uri: file://./target/release/libdisplay.so
+    inputs: [Objects-0, Objects-1]
+links:
+  - from: { node: Camera-0, output: Frame }
+    to: { node: Detector-0, input: Frame }
+  - from: { node: Camera-1, output: Frame }
+    to: { node: Detector-1, input: Frame }
+  - from: { node: Detector-0, output: Objects }
+    to: { node: Display, input: Objects-0 }
+  - from: { node: Detector-1, output: Objects }
+    to: { node: Display, input: Objects-1 }
+"#;
+
+fn descriptor(affinity: Vec<AffinityRule>) -> FlattenDataFlowDescriptor {
+    let mut descriptor = FlattenDataFlowDescriptor::from_yaml(DESCRIPTOR).unwrap();
+    descriptor.affinity = affinity;
+    descriptor
+}
+
+fn ids(ids: &[&str]) -> Vec<NodeId> {
+    ids.iter().map(|id| (*id).into()).collect()
+}
+
+fn runtime(mapping: &HashMap<NodeId, RuntimeId>, node: &str) -> RuntimeId {
+    mapping[&NodeId::from(node)].clone()
+}
+
+#[test]
+fn test_placement_without_rules() {
+    let runtimes: Vec<RuntimeId> = vec!["local".into(), "remote".into()];
+    let mut descriptor = descriptor(vec![]);
+    descriptor.mapping = Some(HashMap::from([("Display".into(), "remote".into())]));
+
+    let mapping = place(&descriptor, &runtimes).unwrap();
+    assert_eq!(mapping.len(), 5);
+    assert_eq!(runtime(&mapping, "Camera-0"), "local".into());
+    assert_eq!(runtime(&mapping, "Detector-1"), "local".into());
+    assert_eq!(runtime(&mapping, "Display"), "remote".into());
+}
+
+#[test]
+fn test_placement_with_affinity() {
+    let runtimes: Vec<RuntimeId> = vec!["local".into(), "edge-0".into(), "edge-1".into()];
+    let mut descriptor = descriptor(vec![
+        AffinityRule::Colocate(ids(&["Camera-0", "Detector-0"])),
+        AffinityRule::Colocate(ids(&["Camera-1", "Detector-1"])),
+        AffinityRule::Separate(ids(&["Detector-0", "Detector-1"])),
+    ]);
+    descriptor.mapping = Some(HashMap::from([("Camera-1".into(), "edge-1".into())]));
+
+    let mapping = place(&descriptor, &runtimes).unwrap();
+    assert_eq!(runtime(&mapping, "Camera-0"), "local".into());
+    assert_eq!(runtime(&mapping, "Detector-0"), "local".into());
+    assert_eq!(runtime(&mapping, "Camera-1"), "edge-1".into());
+    assert_eq!(runtime(&mapping, "Detector-1"), "edge-1".into());
+    assert_eq!(runtime(&mapping, "Display"), "local".into());
+
+    // The separated nodes need as many runtimes.
+    descriptor.mapping = None;
+    assert!(place(&descriptor, &runtimes[..1]).is_err());
+}
+
+#[test]
+fn test_placement_contradictions() {
+    let runtimes: Vec<RuntimeId> = vec!["local".into(), "remote".into()];
+
+    let mut colocated = descriptor(vec![AffinityRule::Colocate(ids(&[
+        "Camera-0",
+        "Detector-0",
+    ]))]);
+    colocated.mapping = Some(HashMap::from([
+        ("Camera-0".into(), "local".into()),
+        ("Detector-0".into(), "remote".into()),
+    ]));
+    assert!(place(&colocated, &runtimes).is_err());
+
+    let mut separated = descriptor(vec![AffinityRule::Separate(ids(&["Camera-0", "Camera-1"]))]);
+    separated.mapping = Some(HashMap::from([
+        ("Camera-0".into(), "remote".into()),
+        ("Camera-1".into(), "remote".into()),
+    ]));
+    assert!(place(&separated, &runtimes).is_err());
+
+    let both = descriptor(vec![
+        AffinityRule::Colocate(ids(&["Camera-0", "Detector-0"])),
+        AffinityRule::Separate(ids(&["Detector-0", "Camera-0"])),
+    ]);
+    assert!(place(&both, &runtimes).is_err());
+
+    let unknown = descriptor(vec![AffinityRule::Separate(ids(&["Camera-2"]))]);
+    assert!(unknown.validate().is_err());
+    assert!(place(&unknown, &runtimes).is_err());
+}
+
+#[test]
+fn test_relocate_record() {
+    let mut descriptor = descriptor(vec![]);
+    descriptor.priority = 2;
+    descriptor.preemption = PreemptionPolicy::Migrate;
+    descriptor.mapping = Some(HashMap::from([
+        ("Camera-0".into(), "edge".into()),
+        ("Camera-1".into(), "edge".into()),
+        ("Detector-0".into(), "gpu-0".into()),
+        ("Detector-1".into(), "gpu-0".into()),
+        ("Display".into(), "local".into()),
+    ]));
+
+    let mut record = DataFlowRecord::try_from((descriptor, Uuid::new_v4())).unwrap();
+    assert_eq!(record.priority, 2);
+    assert_eq!(record.preemption, PreemptionPolicy::Migrate);
+    let connectors = record.connectors.len();
+
+    record.relocate(&"gpu-0".into(), &"gpu-1".into());
+    assert_eq!(
+        record.runtimes(),
+        HashSet::from(["edge".into(), "gpu-1".into(), "local".into()])
+    );
+    assert_eq!(record.find_node_runtime("Detector-1"), Some("gpu-1".into()));
+    // The connectors of the moved nodes are kept, with them.
+    assert_eq!(record.connectors.len(), connectors);
+    assert!(record
+        .connectors
+        .values()
+        .all(|connector| connector.runtime.as_ref() != "gpu-0"));
+}
+
+#[test]
+fn test_missing_runtimes() {
+    let runtimes: Vec<RuntimeId> = vec!["local".into(), "edge-0".into()];
+    let mut descriptor = descriptor(vec![AffinityRule::Colocate(ids(&[
+        "Camera-1",
+        "Detector-1",
+    ]))]);
+    descriptor.mapping = Some(HashMap::from([
+        ("Camera-0".into(), "edge-0".into()),
+        ("Camera-1".into(), "edge-1".into()),
+        ("Detector-1".into(), "edge-1".into()),
+        ("Display".into(), "cloud".into()),
+    ]));
+
+    let missing = missing_runtimes(&descriptor, &runtimes);
+    assert_eq!(missing, vec![RuntimeId::from("cloud"), "edge-1".into()]);
+
+    // The nodes mapped to the missing runtimes are placed on the ones available.
+    let unmapped = unmap_runtimes(&mut descriptor, &missing);
+    assert_eq!(unmapped, ids(&["Camera-1", "Detector-1", "Display"]));
+    assert!(missing_runtimes(&descriptor, &runtimes).is_empty());
+
+    let mapping = place(&descriptor, &runtimes).unwrap();
+    assert_eq!(runtime(&mapping, "Camera-0"), "edge-0".into());
+    assert_eq!(runtime(&mapping, "Camera-1"), "local".into());
+    assert_eq!(runtime(&mapping, "Detector-1"), "local".into());
+    assert_eq!(runtime(&mapping, "Display"), "local".into());
+}
@@ -0,0 +1,71 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::SimulationClock;
+use std::time::Duration;
+use uhlc::HLC;
+
+#[async_std::test]
+async fn test_simulation_clock() {
+    let clock = SimulationClock::new(Duration::from_secs(100));
+    let hlc = clock.new_hlc(*HLC::default().get_id());
+
+    let ts = hlc.new_timestamp();
+    assert_eq!(ts.get_time().to_duration().as_secs(), 100);
+
+    // The deadline is computed before the task is spawned: it could otherwise only be polled
+    // after the time moved forward.
+    let deadline = clock.now() + Duration::from_secs(10);
+    let sleeper = {
+        let clock = clock.clone();
+        async_std::task::spawn(async move { clock.sleep_until(deadline).await })
+    };
+
+    clock.advance(Duration::from_secs(5));
+    async_std::task::sleep(Duration::from_millis(50)).await;
+    assert_eq!(clock.now(), Duration::from_secs(105));
+
+    clock.advance(Duration::from_secs(5));
+    async_std::future::timeout(Duration::from_secs(1), sleeper)
+        .await
+        .expect("The sleeper should have been woken up");
+
+    let ts = hlc.new_timestamp();
+    assert_eq!(ts.get_time().to_duration().as_secs(), 110);
+}
+
+#[async_std::test]
+async fn test_simulation_clocks_independent() {
+    let first = SimulationClock::new(Duration::from_secs(100));
+    let second = SimulationClock::new(Duration::from_secs(5));
+    let first_hlc = first.new_hlc(*HLC::default().get_id());
+    let second_hlc = second.new_hlc(*HLC::default().get_id());
+
+    first.advance(Duration::from_secs(10));
+    assert_eq!(first.now(), Duration::from_secs(110));
+    assert_eq!(second.now(), Duration::from_secs(5));
+
+    assert_eq!(
+        first_hlc.new_timestamp().get_time().to_duration().as_secs(),
+        110
+    );
+    assert_eq!(
+        second_hlc
+            .new_timestamp()
+            .get_time()
+            .to_duration()
+            .as_secs(),
+        5
+    );
+}
@@ -14,11 +14,11 @@
 
 use std::sync::Arc;
 
+use crate::executor::JoinHandle;
 use crate::model::descriptor::FlattenDataFlowDescriptor;
 use crate::runtime::resources::convert;
 use crate::runtime::{resources::DataStore, Job};
 use crate::Result as ZFResult;
-use async_std::task::JoinHandle;
 use async_trait::async_trait;
 use flume::{unbounded, Receiver, Sender};
 use futures::stream::{AbortHandle, Abortable, Aborted};
@@ -116,7 +116,7 @@ impl WorkerPool {
             let worker_loop = async move { worker.run().await };
 
             let (abort_handle, abort_registration) = AbortHandle::new_pair();
-            let handle = async_std::task::spawn(Abortable::new(worker_loop, abort_registration));
+            let handle = crate::executor::spawn(Abortable::new(worker_loop, abort_registration));
             self.handlers.push(handle);
             self.abort_handlers.push(abort_handle);
         }
@@ -144,7 +144,7 @@ impl WorkerPool {
         };
 
         let (abort_handle, abort_registration) = AbortHandle::new_pair();
-        let handle = async_std::task::spawn(Abortable::new(run_loop, abort_registration));
+        let handle = crate::executor::spawn(Abortable::new(run_loop, abort_registration));
 
         self.handle = Some(handle);
         self.abort_handle = Some(abort_handle);
@@ -261,4 +261,13 @@ impl WorkerPool {
 
         Ok(job)
     }
+
+    pub async fn submit_restart_node(&self, fid: &Uuid, node_id: &str) -> ZFResult<Job> {
+        let jid = Uuid::new_v4();
+        let job = Job::new_restart_node(*fid, node_id.to_owned(), jid, self.hlc.new_timestamp());
+
+        self.session.add_submitted_job(&self.rtid, &job).await?;
+
+        Ok(job)
+    }
 }
@@ -0,0 +1,65 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::{ErrorContext, ErrorKind, WithContext, ZFError, ZFResult};
+use crate::types::{NodeId, PortId};
+use crate::zferror;
+use std::error::Error;
+
+#[test]
+fn test_source_is_preserved() {
+    let io_error = std::io::Error::new(std::io::ErrorKind::NotFound, "no such file");
+    let error: ZFError = io_error.into();
+    assert_eq!(error.get_kind(), &ErrorKind::IOError);
+
+    let source = error.source().expect("The source should be preserved");
+    let source = source
+        .downcast_ref::<std::io::Error>()
+        .expect("The source should be an I/O error");
+    assert_eq!(source.kind(), std::io::ErrorKind::NotFound);
+}
+
+#[test]
+fn test_context() {
+    let node_id: NodeId = "node".into();
+    let port_id: PortId = "port".into();
+
+    let result: ZFResult<()> = Err(zferror!(ErrorKind::InvalidData, "Invalid").into());
+    let error = result
+        .context(ErrorContext::Input(port_id.clone()))
+        .node_context(&node_id)
+        .expect_err("Should be an error");
+    let error = error
+        .downcast::<ZFError>()
+        .expect("Should still be a ZFError");
+    assert_eq!(error.get_kind(), &ErrorKind::InvalidData);
+    assert_eq!(
+        error.get_context(),
+        &[
+            ErrorContext::Input(port_id),
+            ErrorContext::Node(node_id.clone())
+        ]
+    );
+    assert!(error.to_string().contains("in node < node >"));
+
+    // Errors that are not `ZFError` are wrapped, keeping them as source.
+    let result: ZFResult<()> = Err(std::fmt::Error.into());
+    let error = result
+        .node_context(&node_id)
+        .expect_err("Should be an error")
+        .downcast::<ZFError>()
+        .expect("Should be wrapped in a ZFError");
+    assert_eq!(error.get_kind(), &ErrorKind::GenericError);
+    assert!(error.source().unwrap().is::<std::fmt::Error>());
+}
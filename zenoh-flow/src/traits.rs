@@ -13,8 +13,9 @@
 //
 
 use crate::prelude::{Inputs, Outputs};
-use crate::types::{Configuration, Context};
-use crate::Result;
+use crate::types::{Configuration, Context, PortId};
+use crate::zfresult::ErrorKind;
+use crate::{bail, Result};
 
 use async_trait::async_trait;
 use std::any::Any;
@@ -161,6 +162,8 @@ pub trait Source: Node + Send + Sync {
 ///         match message {
 ///             Message::Data(t) => println!("{}", *t),
 ///             Message::Watermark => println!("Watermark"),
+///             Message::EndOfStream => println!("End of stream"),
+///             Message::Live => println!("Live"),
 ///         }
 ///
 ///         Ok(())
@@ -237,11 +240,31 @@ pub trait Sink: Node + Send + Sync {
 ///         match message {
 ///             Message::Data(t) => self.output.send(*t, None).await?,
 ///             Message::Watermark => println!("Watermark"),
+///             Message::EndOfStream => self.output.send_end_of_stream().await?,
+///             Message::Live => self.output.send_live().await?,
 ///         }
 ///         Ok(())
 ///     }
 /// }
 /// ```
+///
+/// ## Emitting several messages per iteration
+///
+/// The outputs are not tied to the `iteration`: an Operator can send any number of messages, on
+/// any of its outputs, each with its own timestamp. Splitting a batch is thus done by sending its
+/// elements one by one, for instance:
+///
+/// ```ignore
+/// async fn iteration(&self) -> Result<()> {
+///     if let (Message::Data(batch), timestamp) = self.input.recv().await? {
+///         let origin = timestamp.get_time().as_u64();
+///         for (offset, element) in batch.iter().enumerate() {
+///             self.output.send(*element, Some(origin + offset as u64)).await?;
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
 #[async_trait]
 pub trait Operator: Node + Send + Sync {
     /// For a `Context`, a `Configuration`, a set of `Inputs` and `Outputs`, produce a new
@@ -275,4 +298,84 @@ pub trait Operator: Node + Send + Sync {
 #[async_trait]
 pub trait Node: Send + Sync {
     async fn iteration(&self) -> Result<()>;
+
+    /// Called, for a flow controlled Source (see
+    /// [`NodeDescriptor`](crate::model::descriptor::NodeDescriptor)), before each `iteration` with
+    /// the number of messages that all its downstream nodes can accept: the `credits`, at least 1.
+    ///
+    /// A Source can use them to size what it produces in the next `iteration` --- e.g. pulling that
+    /// many records from an external system. Sending more messages than the credits is possible but
+    /// blocks until the downstream nodes catch up. The default implementation does nothing.
+    async fn on_demand(&self, _credits: usize) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called, after an `iteration`, when messages sent on the output `port_id` were dropped by the
+    /// queue of a link (see [`QueueDescriptor`](crate::model::descriptor::QueueDescriptor)):
+    /// `dropped` is the number of messages dropped since the last call.
+    ///
+    /// A node can use it to adapt what it produces to the pace of its downstream nodes --- e.g. a
+    /// camera reducing its resolution. The default implementation does nothing.
+    async fn on_drop(&self, _port_id: &PortId, _dropped: u64) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called when a timer, scheduled by the node through
+    /// [`Context::schedule_in`](crate::types::Context::schedule_in), fires. The `token` is the one
+    /// given when scheduling the timer.
+    ///
+    /// `on_timer` is called by the runtime, from the task running the node, in between two polls of
+    /// `iteration`: it is never called concurrently with `iteration`. It can thus be used to
+    /// implement timeouts or periodic housekeeping without spawning tasks. An error is handled as
+    /// an error returned by `iteration`. The default implementation does nothing.
+    async fn on_timer(&self, _token: u64) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called with the `payload` of a control message broadcast to the nodes of the instance (see
+    /// [`DataFlowInstance::broadcast_control`](crate::runtime::dataflow::instance::DataFlowInstance::broadcast_control))
+    /// --- e.g. to flush a cache or rotate an output file.
+    ///
+    /// As `on_timer`, it is called from the task running the node in between two polls of
+    /// `iteration` and an error is handled as an error returned by `iteration`. If the control
+    /// message is a barrier, the node has received all the messages sent before it and none of the
+    /// messages sent after it. The default implementation does nothing.
+    async fn on_control(&self, _payload: &[u8]) -> Result<()> {
+        Ok(())
+    }
+
+    /// Releases the resources held by the node.
+    ///
+    /// `clean` is called once, after the node was stopped, when the data flow instance is stopped
+    /// (see [`DataFlowInstance::stop`](crate::runtime::dataflow::instance::DataFlowInstance::stop))
+    /// or before the node is restarted. As for the creation of the node, the [Context] gives access
+    /// to the services of the runtime, such as its Zenoh session or its HLC, for instance to
+    /// gracefully close a remote resource. The default implementation does nothing.
+    async fn clean(&self, _context: &Context) -> Result<()> {
+        Ok(())
+    }
+
+    /// Returns the state of the node, serialized, to be stored in a snapshot of the data flow
+    /// instance (see
+    /// [`DataFlowInstance::snapshot`](crate::runtime::dataflow::instance::DataFlowInstance::snapshot)).
+    ///
+    /// `checkpoint` is called while the node is stopped or, for a checkpoint barrier (see
+    /// [`DataFlowInstance::begin_checkpoint`](crate::runtime::dataflow::instance::DataFlowInstance::begin_checkpoint)),
+    /// in between two polls of its `iteration` --- as `on_timer`. The default implementation
+    /// returns `None`: the node has no state to save.
+    async fn checkpoint(&self) -> Result<Option<Vec<u8>>> {
+        Ok(None)
+    }
+
+    /// Restores the `state`, returned by `checkpoint`, when a snapshot is restored (see
+    /// [`DataFlowInstance::restore`](crate::runtime::dataflow::instance::DataFlowInstance::restore)).
+    ///
+    /// `restore` is only called while the node is stopped. The default implementation returns an
+    /// error: a node that saves a state must be able to restore it.
+    async fn restore(&self, _state: &[u8]) -> Result<()> {
+        bail!(
+            ErrorKind::Unimplemented,
+            "This node does not implement `restore`"
+        )
+    }
 }
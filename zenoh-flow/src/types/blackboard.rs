@@ -0,0 +1,151 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::runtime::resources::ROOT_STANDALONE;
+use crate::zfresult::ErrorKind;
+use crate::{bail, Result, BLACKBOARD_PATH};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::{Arc, RwLock};
+use uuid::Uuid;
+use zenoh::prelude::r#async::AsyncResolve;
+use zenoh::Session;
+
+/// The `Blackboard` is a key-value store shared by all the nodes of an instance, accessible through
+/// their [`Context`](crate::types::Context).
+///
+/// It allows nodes to share data that do not flow through the links --- calibration data, models,
+/// small configurations --- without adding links to the data flow.
+///
+/// The entries are kept in memory, by each daemon involved in the instance:
+/// - `get`, `put` and `remove` only access the entries of the daemon running the node;
+/// - `put_shared` also publishes the entry on Zenoh, under
+///   `zenoh-flow/blackboard/<instance id>/<key>`, and `get_shared` retrieves it from Zenoh. To
+///   share entries between the nodes running on different daemons, a Zenoh storage must be
+///   configured for these key expressions.
+pub struct Blackboard {
+    instance_id: Uuid,
+    session: Option<Arc<Session>>,
+    entries: RwLock<HashMap<String, Arc<Vec<u8>>>>,
+}
+
+impl Debug for Blackboard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Blackboard")
+            .field("instance_id", &self.instance_id)
+            .field("keys", &self.keys())
+            .finish()
+    }
+}
+
+impl Blackboard {
+    pub(crate) fn new(instance_id: Uuid, session: Option<Arc<Session>>) -> Self {
+        Self {
+            instance_id,
+            session,
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the value of the entry `key`, if any, on the daemon running the node.
+    pub fn get(&self, key: &str) -> Option<Arc<Vec<u8>>> {
+        self.entries
+            .read()
+            .ok()
+            .and_then(|entries| entries.get(key).cloned())
+    }
+
+    /// Sets the `value` of the entry `key` on the daemon running the node, returning its previous
+    /// value, if any.
+    pub fn put(&self, key: impl Into<String>, value: Vec<u8>) -> Option<Arc<Vec<u8>>> {
+        self.entries
+            .write()
+            .ok()
+            .and_then(|mut entries| entries.insert(key.into(), Arc::new(value)))
+    }
+
+    /// Removes the entry `key` from the daemon running the node, returning its value, if any.
+    pub fn remove(&self, key: &str) -> Option<Arc<Vec<u8>>> {
+        self.entries
+            .write()
+            .ok()
+            .and_then(|mut entries| entries.remove(key))
+    }
+
+    /// Returns the keys of the entries of the daemon running the node.
+    pub fn keys(&self) -> Vec<String> {
+        self.entries
+            .read()
+            .map(|entries| entries.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the key expression under which the entry `key` is shared.
+    pub fn key_expr(&self, key: &str) -> String {
+        BLACKBOARD_PATH!(ROOT_STANDALONE, self.instance_id, key)
+    }
+
+    fn session(&self) -> Result<&Session> {
+        match &self.session {
+            Some(session) => Ok(session),
+            None => bail!(
+                ErrorKind::MissingState,
+                "[Blackboard: {}] No Zenoh session to share the entries",
+                self.instance_id
+            ),
+        }
+    }
+
+    /// Sets the `value` of the entry `key` on the daemon running the node and publishes it on
+    /// Zenoh.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the entry could not be published.
+    pub async fn put_shared(&self, key: impl Into<String>, value: Vec<u8>) -> Result<()> {
+        let key = key.into();
+        self.session()?
+            .put(&self.key_expr(&key), value.clone())
+            .res_async()
+            .await?;
+        self.put(key, value);
+        Ok(())
+    }
+
+    /// Retrieves the value of the entry `key` from Zenoh, keeping it on the daemon running the node.
+    ///
+    /// If no value is stored in Zenoh, the value on the daemon running the node, if any, is
+    /// returned.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the query failed.
+    pub async fn get_shared(&self, key: &str) -> Result<Option<Arc<Vec<u8>>>> {
+        let replies = self.session()?.get(&self.key_expr(key)).res_async().await?;
+
+        while let Ok(reply) = replies.recv_async().await {
+            if let Ok(sample) = reply.sample {
+                let value = sample.payload.contiguous().to_vec();
+                self.put(key, value);
+                break;
+            }
+        }
+
+        Ok(self.get(key))
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/blackboard-tests.rs"]
+mod tests;
@@ -12,10 +12,19 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+use crate::runtime::capture;
+use crate::runtime::dataflow::instance::builtin::host::HostChannels;
+use crate::runtime::dataflow::instance::runners::timers::TimerScheduler;
+use crate::runtime::dataflow::instance::runners::watchdog::Watchdog;
 use crate::runtime::InstanceContext;
-use crate::types::{FlowId, RuntimeId};
+use crate::types::{Blackboard, FlowId, KeyState, KeyedState, NodeId, RuntimeId};
+use crate::zfresult::ErrorKind;
+use crate::{bail, Result};
 use std::ops::Deref;
+use std::process::Child;
 use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
 use uhlc::HLC;
 use uuid::Uuid;
 use zenoh::Session;
@@ -32,19 +41,65 @@ use zenoh::Session;
 /// - `shared_memory_elements` : the default total number of shared memory chunks
 /// - `shared_memory_backoff` : the default backoff time when no chunks are available
 ///
-/// The HLC is directly accessible thanks to a `Deref` implementation.
+/// The [Blackboard] of the instance, a key-value store shared by its nodes, is accessible through
+/// `blackboard`.
+///
+/// A node can schedule timers with `schedule_in`: when one fires, the runtime calls its
+/// [`on_timer`](crate::traits::Node::on_timer) callback.
+///
+/// A node with a watchdog signals, with `heartbeat`, that a long computation is progressing.
+///
+/// An Operator accesses the state of a key, in its [KeyedState], with `state_for_key`.
+///
+/// A node running (part of) its computation in another process logs the standard output and error
+/// of that process, tagged with its identifier, with `capture_output`.
+///
+/// The HLC is directly accessible thanks to a `Deref` implementation. When the instance runs in
+/// simulation mode, the HLC is derived from the simulated time.
 #[derive(Clone)]
 pub struct Context {
     instance_ctx: InstanceContext,
+    node_id: Option<NodeId>,
+    timers: Option<TimerScheduler>,
+    watchdog: Option<Arc<Watchdog>>,
+    keyed_state: Option<Arc<KeyedState>>,
 }
 
 impl Context {
     pub(crate) fn new(instance_ctx: &InstanceContext) -> Self {
         Self {
             instance_ctx: instance_ctx.clone(),
+            node_id: None,
+            timers: None,
+            watchdog: None,
+            keyed_state: None,
         }
     }
 
+    /// Sets the identifier of the node to which this `Context` is given.
+    pub(crate) fn with_node_id(mut self, node_id: NodeId) -> Self {
+        self.node_id = Some(node_id);
+        self
+    }
+
+    /// Sets the `timers` of the node to which this `Context` is given.
+    pub(crate) fn with_timers(mut self, timers: TimerScheduler) -> Self {
+        self.timers = Some(timers);
+        self
+    }
+
+    /// Sets the `watchdog` of the node to which this `Context` is given.
+    pub(crate) fn with_watchdog(mut self, watchdog: Option<Arc<Watchdog>>) -> Self {
+        self.watchdog = watchdog;
+        self
+    }
+
+    /// Sets the `keyed_state` of the Operator to which this `Context` is given.
+    pub(crate) fn with_keyed_state(mut self, keyed_state: Option<Arc<KeyedState>>) -> Self {
+        self.keyed_state = keyed_state;
+        self
+    }
+
     /// Returns the (user given) name of the runtime in which the calling node is running.
     ///
     /// Note that, for the same instance of a flow (i.e. the `flow_id` and `instance_id` are equal),
@@ -73,7 +128,24 @@ impl Context {
 
     /// Returns a thread-safe reference over the Zenoh session used by the Zenoh-Flow daemon running
     /// the node.
+    ///
+    /// Nodes that need to perform operations on Zenoh outside of their links (queries, puts,
+    /// subscriptions) should use this session rather than opening their own: all the nodes of all
+    /// the instances managed by a daemon then share a single session.
+    ///
+    /// This method requires the `zenoh-session` feature.
+    ///
+    /// ```ignore
+    /// let session = context.zenoh_session();
+    /// let replies = session.get("calibration/**").res().await?;
+    /// ```
+    #[cfg(feature = "zenoh-session")]
     pub fn zenoh_session(&self) -> Arc<Session> {
+        self.session()
+    }
+
+    /// Returns the Zenoh session of the daemon running the node, whatever the features.
+    pub(crate) fn session(&self) -> Arc<Session> {
         self.instance_ctx.runtime.session.clone()
     }
 
@@ -96,12 +168,152 @@ impl Context {
     pub fn shared_memory_enabled(&self) -> &bool {
         &self.instance_ctx.runtime.use_shm
     }
+
+    /// Returns the [Blackboard] shared by all the nodes of the instance.
+    pub fn blackboard(&self) -> &Blackboard {
+        &self.instance_ctx.blackboard
+    }
+
+    /// Returns the channels between the built-in Host nodes and the application embedding the
+    /// instance.
+    pub(crate) fn host_channels(&self) -> &HostChannels {
+        &self.instance_ctx.host
+    }
+
+    /// Returns `true` if the instance runs in simulation mode, i.e. against a virtual clock.
+    pub fn is_simulated(&self) -> bool {
+        self.instance_ctx.simulation.is_some()
+    }
+
+    /// Waits until `duration` has elapsed.
+    ///
+    /// Nodes that perform periodic tasks should favour this method over the `sleep` of the executor:
+    /// when the instance runs in simulation mode, the duration is measured in simulated time.
+    pub async fn sleep(&self, duration: Duration) {
+        match &self.instance_ctx.simulation {
+            Some(clock) => clock.sleep(duration).await,
+            None => crate::executor::sleep(duration).await,
+        }
+    }
+
+    /// Schedules a timer that fires once `duration` has elapsed: the runtime then calls the
+    /// [`on_timer`](crate::traits::Node::on_timer) callback of the node with the provided `token`.
+    ///
+    /// Several timers can be pending at the same time, the `token` allowing the node to tell them
+    /// apart. As for `sleep`, the duration is measured in simulated time when the instance runs in
+    /// simulation mode. Pending timers are kept when the node is stopped and dropped when it is
+    /// restarted.
+    ///
+    /// ```ignore
+    /// const HOUSEKEEPING: u64 = 0;
+    /// context.schedule_in(Duration::from_secs(1), HOUSEKEEPING)?;
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if this `Context` was not given to a node at its creation --- for
+    /// instance the one passed to `clean` --- or if the node no longer exists.
+    pub fn schedule_in(&self, duration: Duration, token: u64) -> Result<()> {
+        match &self.timers {
+            Some(timers) => timers.schedule_in(duration, token),
+            None => bail!(
+                ErrorKind::MissingState,
+                "This Context cannot schedule timers, only the one given at the creation of a node can"
+            ),
+        }
+    }
+
+    /// Signals that the node is making progress.
+    ///
+    /// A node with a `watchdog` (see
+    /// [`WatchdogDescriptor`](crate::model::descriptor::WatchdogDescriptor)) should call this
+    /// method regularly during long computations: an iteration that exceeds the deadline of the
+    /// watchdog is then considered slow rather than hung, and it is not interrupted. Without a
+    /// watchdog, this method does nothing.
+    ///
+    /// ```ignore
+    /// for batch in batches {
+    ///     process(batch);
+    ///     context.heartbeat();
+    /// }
+    /// ```
+    pub fn heartbeat(&self) {
+        if let Some(watchdog) = &self.watchdog {
+            watchdog.heartbeat();
+        }
+    }
+
+    /// Returns the [KeyedState] of the Operator.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if this `Context` was not given to an Operator at its creation.
+    pub fn keyed_state(&self) -> Result<&KeyedState> {
+        match &self.keyed_state {
+            Some(keyed_state) => Ok(keyed_state),
+            None => bail!(
+                ErrorKind::MissingState,
+                "This Context has no keyed state, only the one given at the creation of an Operator has"
+            ),
+        }
+    }
+
+    /// Returns the state of the `key`, in the [KeyedState] of the Operator.
+    ///
+    /// When the Operator is replicated, each copy only manages the keys of the partitions it owns:
+    /// the messages of a key should be sent to its owner (see
+    /// [`replica_of`](crate::types::replica_of)).
+    ///
+    /// ```ignore
+    /// let state = context.state_for_key(&reading.sensor_id)?;
+    /// let count = state.get().map_or(0, |count| count[0]);
+    /// state.put(vec![count + 1]);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if this `Context` was not given to an Operator at its creation or if
+    /// the `key` is owned by another copy of the Operator.
+    pub fn state_for_key(&self, key: impl AsRef<[u8]>) -> Result<KeyState<'_>> {
+        self.keyed_state()?.for_key(key.as_ref())
+    }
+
+    /// Captures the standard output and error of the `child` process, spawned by the node to run
+    /// (part of) its computation, e.g. a Python interpreter.
+    ///
+    /// Each line written on a stream created with [`Stdio::piped`](std::process::Stdio::piped) is
+    /// logged by the runtime under the target `zenoh_flow::node::<node id>`, at the level `Info`
+    /// for the standard output and `Warn` for the standard error. The handles returned give the
+    /// number of lines of each stream once the process closes it.
+    ///
+    /// ```ignore
+    /// let mut child = Command::new("python3")
+    ///     .arg("detector.py")
+    ///     .stdout(Stdio::piped())
+    ///     .stderr(Stdio::piped())
+    ///     .spawn()?;
+    /// context.capture_output(&mut child)?;
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if this `Context` was not given to a node at its creation or if a
+    /// thread reading a stream could not be spawned.
+    pub fn capture_output(&self, child: &mut Child) -> Result<Vec<JoinHandle<usize>>> {
+        match &self.node_id {
+            Some(node_id) => Ok(capture::capture_output(node_id, child)?),
+            None => bail!(
+                ErrorKind::MissingState,
+                "This Context cannot capture the output of a process, only the one given at the creation of a node can"
+            ),
+        }
+    }
 }
 
 impl Deref for Context {
     type Target = HLC;
 
     fn deref(&self) -> &Self::Target {
-        &self.instance_ctx.runtime.hlc
+        &self.instance_ctx.hlc
     }
 }
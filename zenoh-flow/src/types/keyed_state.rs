@@ -0,0 +1,236 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::zfresult::ErrorKind;
+use crate::{zferror, Result};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::{Arc, RwLock};
+
+/// The number of partitions the keys of a [KeyedState] are spread over.
+///
+/// The partitions, not the keys, are assigned to the replicas of an Operator: it must be (much)
+/// larger than the number of replicas.
+pub const KEY_PARTITIONS: u32 = 128;
+
+/// The entries of a partition.
+type Partition = HashMap<Vec<u8>, Arc<Vec<u8>>>;
+
+/// Returns the partition of the `key`.
+///
+/// The partition only depends on the bytes of the key (FNV-1a hash): it is the same on all the
+/// daemons and across versions.
+pub fn partition_of(key: &[u8]) -> u32 {
+    let hash = key.iter().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    (hash % KEY_PARTITIONS as u64) as u32
+}
+
+/// Returns the index of the replica, out of `replicas`, that owns the partition of the `key`.
+///
+/// An Operator feeding a replicated one can use it to send the messages of a key to the replica
+/// managing its state, on the output of that replica (see
+/// [`DataFlowDescriptor`](crate::model::descriptor::DataFlowDescriptor)).
+pub fn replica_of(key: &[u8], replicas: usize) -> usize {
+    partition_of(key) as usize % replicas.max(1)
+}
+
+/// The `KeyedState` is the state of an Operator partitioned by key, accessible through its
+/// [`Context`](crate::types::Context) with `state_for_key`.
+///
+/// The keys are spread over [KEY_PARTITIONS] partitions. When an Operator declares `replicas`,
+/// each copy owns the partitions whose index, modulo the number of replicas, is its own: the copies
+/// manage disjoint partitions and only access the keys of the ones they own (see [replica_of]). An
+/// Operator that is not replicated owns all of them.
+///
+/// The keyed state is kept by the instance: it survives the restart of the Operator. It is saved,
+/// partition by partition, in the snapshots of the instance and, when a snapshot is restored, each
+/// copy only restores the partitions it owns --- possibly with a different number of replicas.
+pub struct KeyedState {
+    replica: usize,
+    replicas: usize,
+    partitions: RwLock<HashMap<u32, Partition>>,
+}
+
+impl Debug for KeyedState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyedState")
+            .field("replica", &self.replica)
+            .field("replicas", &self.replicas)
+            .field("partitions", &self.partitions().len())
+            .finish()
+    }
+}
+
+impl Default for KeyedState {
+    fn default() -> Self {
+        Self::new(0, 1)
+    }
+}
+
+impl KeyedState {
+    /// Creates the keyed state of the copy `replica` of an Operator having `replicas` copies.
+    pub(crate) fn new(replica: usize, replicas: usize) -> Self {
+        Self {
+            replica,
+            replicas: replicas.max(1),
+            partitions: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the index of the copy of the Operator owning this state.
+    pub fn replica(&self) -> usize {
+        self.replica
+    }
+
+    /// Returns the number of copies of the Operator.
+    pub fn replicas(&self) -> usize {
+        self.replicas
+    }
+
+    /// Returns `true` if the `partition` is owned by this copy of the Operator.
+    pub fn owns_partition(&self, partition: u32) -> bool {
+        partition as usize % self.replicas == self.replica
+    }
+
+    /// Returns `true` if the `key` belongs to a partition owned by this copy of the Operator.
+    pub fn owns(&self, key: &[u8]) -> bool {
+        self.owns_partition(partition_of(key))
+    }
+
+    /// Returns the state of the `key`.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the `key` belongs to a partition owned by another copy of the
+    /// Operator.
+    pub fn for_key(&self, key: &[u8]) -> Result<KeyState<'_>> {
+        let partition = partition_of(key);
+        if !self.owns_partition(partition) {
+            return Err(zferror!(
+                ErrorKind::InvalidState,
+                "The key of partition {} is owned by replica {} of {}, not by replica {}",
+                partition,
+                partition as usize % self.replicas,
+                self.replicas,
+                self.replica
+            )
+            .into());
+        }
+
+        Ok(KeyState {
+            state: self,
+            partition,
+            key: key.to_vec(),
+        })
+    }
+
+    /// Returns the partitions holding at least one entry.
+    pub fn partitions(&self) -> Vec<u32> {
+        self.partitions
+            .read()
+            .map(|partitions| partitions.keys().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the partitions holding at least one entry, serialized with `bincode`.
+    pub(crate) fn checkpoint(&self) -> Result<HashMap<u32, Vec<u8>>> {
+        let partitions = self.partitions.read().unwrap_or_else(|e| e.into_inner());
+        partitions
+            .iter()
+            .map(|(partition, entries)| {
+                bincode::serialize(entries)
+                    .map(|bytes| (*partition, bytes))
+                    .map_err(|e| zferror!(ErrorKind::SerializationError, e).into())
+            })
+            .collect()
+    }
+
+    /// Replaces the state with the `partitions`, returned by `checkpoint`, owned by this copy of
+    /// the Operator. The number of partitions restored is returned.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if a partition could not be deserialized: the state is then left
+    /// untouched.
+    pub(crate) fn restore(&self, partitions: &HashMap<u32, Vec<u8>>) -> Result<usize> {
+        let restored = partitions
+            .iter()
+            .filter(|(partition, _)| self.owns_partition(**partition))
+            .map(|(partition, bytes)| {
+                bincode::deserialize::<Partition>(bytes)
+                    .map(|entries| (*partition, entries))
+                    .map_err(|e| zferror!(ErrorKind::DeserializationError, e).into())
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+
+        let count = restored.len();
+        *self.partitions.write().unwrap_or_else(|e| e.into_inner()) = restored;
+        Ok(count)
+    }
+}
+
+/// The state of a key of a [KeyedState], owned by the copy of the Operator accessing it.
+#[derive(Debug)]
+pub struct KeyState<'a> {
+    state: &'a KeyedState,
+    partition: u32,
+    key: Vec<u8>,
+}
+
+impl<'a> KeyState<'a> {
+    /// Returns the partition of the key.
+    pub fn partition(&self) -> u32 {
+        self.partition
+    }
+
+    /// Returns the value of the key, if any.
+    pub fn get(&self) -> Option<Arc<Vec<u8>>> {
+        self.state.partitions.read().ok().and_then(|partitions| {
+            partitions
+                .get(&self.partition)
+                .and_then(|entries| entries.get(&self.key).cloned())
+        })
+    }
+
+    /// Sets the `value` of the key, returning its previous value, if any.
+    pub fn put(&self, value: Vec<u8>) -> Option<Arc<Vec<u8>>> {
+        self.state
+            .partitions
+            .write()
+            .ok()
+            .and_then(|mut partitions| {
+                partitions
+                    .entry(self.partition)
+                    .or_default()
+                    .insert(self.key.clone(), Arc::new(value))
+            })
+    }
+
+    /// Removes the value of the key, returning it, if any.
+    pub fn remove(&self) -> Option<Arc<Vec<u8>>> {
+        let mut partitions = self.state.partitions.write().ok()?;
+        let entries = partitions.get_mut(&self.partition)?;
+        let value = entries.remove(&self.key);
+        if entries.is_empty() {
+            partitions.remove(&self.partition);
+        }
+        value
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/keyed-state-tests.rs"]
+mod tests;
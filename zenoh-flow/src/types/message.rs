@@ -18,12 +18,17 @@ use crate::traits::SendSyncAny;
 use crate::types::{FlowId, NodeId, PortId};
 use crate::{zferror, Result};
 
-use async_std::sync::Arc;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::ops::Deref;
+use std::sync::Arc;
+use std::time::Duration;
 use std::{cmp::Ordering, fmt::Debug};
 use uhlc::Timestamp;
 use uuid::Uuid;
+use zenoh::prelude::r#async::AsyncResolve;
+use zenoh::prelude::sync::SyncResolve;
+use zenoh::Session;
 
 /// `SerializerFn` is a type-erased version of the serializer function provided by node developer.
 ///
@@ -41,25 +46,111 @@ pub(crate) type SerializerFn =
 /// code.
 pub(crate) type DeserializerFn<T> = dyn Fn(&[u8]) -> anyhow::Result<T> + Send + Sync;
 
+/// The maximum number of serialized bytes a [Payload] holds inline, see [InlineBytes].
+pub const INLINE_CAPACITY: usize = 32;
+
+/// `InlineBytes` are serialized data small enough to be stored in the [Payload] itself: creating,
+/// cloning or dropping them does not allocate.
+///
+/// Small payloads (counters, flags, sensor readings) are the most frequent: storing them in an
+/// `Arc<Vec<u8>>` costs two allocations per message, more than their serialization.
+#[derive(Clone, Copy)]
+pub struct InlineBytes {
+    len: u8,
+    bytes: [u8; INLINE_CAPACITY],
+}
+
+impl InlineBytes {
+    /// Copies the `bytes`, returning `None` if there are more than [INLINE_CAPACITY].
+    pub fn new(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() > INLINE_CAPACITY {
+            return None;
+        }
+
+        let mut inline = Self {
+            len: bytes.len() as u8,
+            bytes: [0; INLINE_CAPACITY],
+        };
+        inline.bytes[..bytes.len()].copy_from_slice(bytes);
+        Some(inline)
+    }
+}
+
+impl Deref for InlineBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.bytes[..self.len as usize]
+    }
+}
+
+impl Debug for InlineBytes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
 /// A `Payload` is Zenoh-Flow's lowest message container.
 ///
 /// It either contains serialized data, i.e. `Bytes` (if received from the network, or from nodes
-/// not written in Rust), or `Typed` data as a tuple `(`[Any](`std::any::Any`)`, SerializerFn)`.
-#[derive(Clone, Serialize, Deserialize)]
+/// not written in Rust) or `Inline` bytes when there are at most [INLINE_CAPACITY] of them, `Typed`
+/// data as a tuple `(`[Any](`std::any::Any`)`, SerializerFn)` or a `Reference` to serialized data
+/// stored in Zenoh.
+///
+/// `Inline` bytes are serialized exactly as `Bytes`: the components on the other side of a link
+/// cannot tell them apart. They are internal to the links: a node never receives them, an
+/// [InputRaw](crate::io::InputRaw) returns them as `Bytes`. The variant is hidden and the enum is
+/// `non_exhaustive`: a `match` outside of Zenoh-Flow must have a wildcard arm.
+#[derive(Clone, Deserialize)]
+#[non_exhaustive]
 pub enum Payload {
     /// Serialized data, coming either from Zenoh of from non-Rust node.
     Bytes(Arc<Vec<u8>>),
-    #[serde(skip_serializing, skip_deserializing)]
+    /// Reference to serialized data stored in Zenoh, see [PayloadReference].
+    Reference(PayloadReference),
+    #[serde(skip_deserializing)]
     /// Data coming from another Rust node located on the same process that can either be downcasted
     /// (provided that its actual type is known) or serialized.
     Typed((Arc<dyn SendSyncAny>, Arc<SerializerFn>)),
+    #[doc(hidden)]
+    #[serde(skip_deserializing)]
+    /// Serialized data small enough to be stored without allocating, see [InlineBytes].
+    Inline(InlineBytes),
+}
+
+// The variants are serialized as the derived implementation would, `Inline` bytes being serialized
+// as `Bytes`.
+impl Serialize for Payload {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            Payload::Bytes(bytes) => {
+                serializer.serialize_newtype_variant("Payload", 0, "Bytes", bytes)
+            }
+            Payload::Inline(bytes) => {
+                serializer.serialize_newtype_variant("Payload", 0, "Bytes", &**bytes)
+            }
+            Payload::Reference(reference) => {
+                serializer.serialize_newtype_variant("Payload", 1, "Reference", reference)
+            }
+            Payload::Typed(_) => Err(serde::ser::Error::custom(
+                "the enum variant Payload::Typed cannot be serialized",
+            )),
+        }
+    }
 }
 
 impl Debug for Payload {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Payload::Bytes(_) => write!(f, "Payload::Bytes"),
+            Payload::Inline(bytes) => write!(f, "Payload::Inline({} bytes)", bytes.len()),
             Payload::Typed(_) => write!(f, "Payload::Typed"),
+            Payload::Reference(reference) => {
+                write!(f, "Payload::Reference({})", reference.key_expr)
+            }
         }
     }
 }
@@ -94,9 +185,18 @@ impl Payload {
                 (**bytes).clone_into(buffer);
                 Ok(())
             }
+            Payload::Inline(bytes) => {
+                buffer.extend_from_slice(bytes);
+                Ok(())
+            }
             Payload::Typed((typed_data, serializer)) => {
                 (serializer)(buffer, Arc::clone(typed_data))
             }
+            Payload::Reference(reference) => bail!(
+                ErrorKind::Unsupported,
+                "The Payload references < {} >, it must first be resolved",
+                reference.key_expr
+            ),
         }
     }
 
@@ -106,17 +206,131 @@ impl Payload {
     ///
     /// This method will only serialize (and thus allocate) the [Payload] if it is typed. Otherwise
     /// the [Arc] is cloned.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the [Payload] is a `Reference`: it must first be resolved (see
+    /// [resolve](Payload::resolve)).
     //
     // NOTE: This method is used by, at least, our Python API.
     pub fn try_as_bytes(&self) -> Result<Arc<Vec<u8>>> {
         match self {
             Payload::Bytes(bytes) => Ok(bytes.clone()),
+            Payload::Inline(bytes) => Ok(Arc::new(bytes.to_vec())),
             Payload::Typed((typed_data, serializer)) => {
                 let mut buffer = Vec::default();
                 (serializer)(&mut buffer, Arc::clone(typed_data))?;
                 Ok(Arc::new(buffer))
             }
+            Payload::Reference(reference) => bail!(
+                ErrorKind::Unsupported,
+                "The Payload references < {} >, it must first be resolved",
+                reference.key_expr
+            ),
+        }
+    }
+
+    /// Returns the [Payload], where a `Reference` is replaced with the `Bytes` it designates.
+    ///
+    /// # Performance
+    ///
+    /// Resolving a `Reference` queries Zenoh and allocates to store the bytes retrieved. The other
+    /// variants are simply cloned.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the query failed or if no data is stored under the key expression.
+    pub async fn resolve(&self, session: &Session) -> Result<Payload> {
+        match self {
+            Payload::Reference(reference) => Ok(Payload::Bytes(reference.resolve(session).await?)),
+            _ => Ok(self.clone()),
+        }
+    }
+}
+
+/// A `PayloadReference` designates serialized data stored in Zenoh, under a key expression.
+///
+/// Sending a reference instead of the data itself is useful when the data is large (e.g. video
+/// frames) and several downstream nodes do not necessarily access it: the data is only retrieved
+/// by the nodes that read it, see [`Input<T>`](crate::io::Input). Nodes that only forward the
+/// messages, for instance through an [InputRaw](crate::io::InputRaw), never retrieve it.
+///
+/// It is the responsibility of the upstream node to store the data in Zenoh (a storage must hence
+/// be configured for the key expression) and to send the reference only once it is stored.
+///
+/// # Example
+///
+/// ```ignore
+/// let key_expr = format!("my-app/frames/{}", frame.id);
+/// context.zenoh_session().put(&key_expr, frame.bytes).res().await?;
+/// output.send_reference(PayloadReference::new(key_expr), None).await?;
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PayloadReference {
+    key_expr: String,
+}
+
+impl PayloadReference {
+    /// Creates a new reference to the data stored under `key_expr`.
+    pub fn new(key_expr: impl Into<String>) -> Self {
+        Self {
+            key_expr: key_expr.into(),
+        }
+    }
+
+    /// Returns the key expression under which the referenced data is stored.
+    pub fn key_expr(&self) -> &str {
+        &self.key_expr
+    }
+
+    /// Retrieves, from Zenoh, the bytes designated by this reference.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the query failed or if no data is stored under the key expression.
+    pub async fn resolve(&self, session: &Session) -> Result<Arc<Vec<u8>>> {
+        let replies = session.get(&self.key_expr).res_async().await?;
+
+        while let Ok(reply) = replies.recv_async().await {
+            if let Ok(sample) = reply.sample {
+                return Ok(Arc::new(sample.payload.contiguous().to_vec()));
+            }
         }
+
+        bail!(
+            ErrorKind::NotFound,
+            "No data stored under the referenced key expression < {} >",
+            self.key_expr
+        )
+    }
+
+    /// Retrieves, from Zenoh, the bytes designated by this reference, *blocking* the current
+    /// thread until the reply is received.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the query failed or if no data is stored under the key expression.
+    pub fn resolve_sync(&self, session: &Session) -> Result<Arc<Vec<u8>>> {
+        let replies = session.get(&self.key_expr).res_sync()?;
+
+        while let Ok(reply) = replies.recv() {
+            if let Ok(sample) = reply.sample {
+                return Ok(Arc::new(sample.payload.contiguous().to_vec()));
+            }
+        }
+
+        bail!(
+            ErrorKind::NotFound,
+            "No data stored under the referenced key expression < {} >",
+            self.key_expr
+        )
+    }
+}
+
+/// Creates a new `Payload` referencing data stored in Zenoh.
+impl From<PayloadReference> for Payload {
+    fn from(reference: PayloadReference) -> Self {
+        Self::Reference(reference)
     }
 }
 
@@ -146,10 +360,24 @@ impl From<DataMessage> for Payload {
 ///
 /// It contains the actual data, the timestamp associated, the end to end deadline, the end to end
 /// deadline misses and loop contexts.
+///
+/// Two times can be associated to the data:
+/// - the `timestamp`, assigned by the runtime (unless explicitly provided) when the message is sent:
+///   the processing time, used to order the messages,
+/// - the `event_time`, optionally assigned by the component that produced the data, e.g. the time
+///   at which a camera captured a frame.
+///
+/// The data sent on a union port (see [PortType](crate::model::descriptor::PortType)) also carries
+/// its `variant`: the index, in the union, of its data type (see
+/// [UnionData](crate::types::UnionData)).
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DataMessage {
     pub(crate) data: Payload,
     pub(crate) timestamp: Timestamp,
+    #[serde(default)]
+    pub(crate) event_time: Option<Timestamp>,
+    #[serde(default)]
+    pub(crate) variant: Option<u32>,
 }
 
 impl Deref for DataMessage {
@@ -168,6 +396,8 @@ impl DataMessage {
         Self {
             data: Payload::Bytes(Arc::new(data)),
             timestamp,
+            event_time: None,
+            variant: None,
         }
     }
 
@@ -177,19 +407,171 @@ impl DataMessage {
     pub fn get_timestamp(&self) -> &Timestamp {
         &self.timestamp
     }
+
+    /// Return the event time assigned to the data by the component that produced it, if any.
+    pub fn get_event_time(&self) -> Option<&Timestamp> {
+        self.event_time.as_ref()
+    }
+
+    /// Return the index, in the union of its port, of the data type of the data, if it was sent on
+    /// a union port.
+    pub fn get_variant(&self) -> Option<u32> {
+        self.variant
+    }
+
+    /// Return the processing time of this [DataMessage], i.e. the [Timestamp] assigned when it was
+    /// sent. It is identical to [`get_timestamp`](DataMessage::get_timestamp).
+    pub fn get_processing_time(&self) -> &Timestamp {
+        &self.timestamp
+    }
+
+    /// Return the latency between the event time and the processing time of this [DataMessage], if
+    /// an event time was assigned and precedes the processing time.
+    pub fn get_event_latency(&self) -> Option<Duration> {
+        self.event_time.and_then(|event_time| {
+            self.timestamp
+                .get_time()
+                .to_duration()
+                .checked_sub(event_time.get_time().to_duration())
+        })
+    }
 }
 
 /// Metadata stored in Zenoh's time series storages.
 /// It contains information about the recording.
 /// Multiple [`RecordingMetadata`](`RecordingMetadata`) can be used
 /// to synchronize the recording from different Ports.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+///
+/// The metadata also index the messages of the recording: how many were recorded and the time range
+/// they cover. They are stored under `<key_expr>/metadata` (see
+/// [`DataFlowInstance::start_recording`](crate::runtime::dataflow::instance::DataFlowInstance::start_recording)).
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct RecordingMetadata {
+    /// When the recording started.
+    pub timestamp: Timestamp,
+    pub port_id: PortId,
+    pub node_id: NodeId,
+    pub flow_id: FlowId,
+    pub instance_id: Uuid,
+    /// The key expression under which the recording is stored.
+    #[serde(default)]
+    pub key_expr: String,
+    /// The identifier of the recording session, if the recording was started along with the
+    /// recordings of all the outputs of the instance.
+    #[serde(default)]
+    pub session_id: Option<Uuid>,
+    /// The name of the session and the tags given to the recording when it was started.
+    #[serde(flatten)]
+    pub labels: RecordingLabels,
+    /// The number of messages recorded, all kinds included.
+    #[serde(default)]
+    pub messages: u64,
+    /// The number of data messages recorded.
+    #[serde(default)]
+    pub data_messages: u64,
+    /// The timestamp of the oldest message recorded, if any.
+    #[serde(default)]
+    pub start: Option<Timestamp>,
+    /// The timestamp of the most recent message recorded, if any.
+    #[serde(default)]
+    pub end: Option<Timestamp>,
+}
+
+/// The name of the recording session and the tags (e.g. the operator, the location or the
+/// scenario) given to a recording such that it can be found among many others (see
+/// [`list_recordings`](crate::runtime::dataflow::instance::recording::list_recordings)).
+///
+/// They are stored in the [RecordingMetadata] of the recording.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RecordingLabels {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_name: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>,
+}
+
+impl RecordingLabels {
+    /// Creates the labels of the recording session `session_name`, without tags.
+    pub fn new(session_name: impl Into<String>) -> Self {
+        Self {
+            session_name: Some(session_name.into()),
+            tags: BTreeMap::new(),
+        }
+    }
+
+    /// Adds the tag `key`, with the value `value`, replacing the previous value if any.
+    pub fn with_tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.insert(key.into(), value.into());
+        self
+    }
+}
+
+impl RecordingMetadata {
+    /// Adds the `message`, the next one of the recording, to the index.
+    pub(crate) fn index(&mut self, message: &LinkMessage) {
+        let timestamp = message.get_timestamp();
+        self.messages += 1;
+        if matches!(message, LinkMessage::Data(_)) {
+            self.data_messages += 1;
+        }
+        if self.start.map_or(true, |start| timestamp < start) {
+            self.start = Some(timestamp);
+        }
+        if self.end.map_or(true, |end| timestamp > end) {
+            self.end = Some(timestamp);
+        }
+    }
+}
+
+/// A control message broadcast, as a barrier, to the nodes of an instance (see
+/// [`DataFlowInstance::broadcast_control`](crate::runtime::dataflow::instance::DataFlowInstance::broadcast_control)).
+///
+/// It is sent by the Sources on all their links and flows through the data flow: each node handles
+/// it once all its channels delivered it, before any message that follows it.
+///
+/// A marker with an `epoch` is a checkpoint barrier (see
+/// [`DataFlowInstance::begin_checkpoint`](crate::runtime::dataflow::instance::DataFlowInstance::begin_checkpoint)):
+/// each node checkpoints its state as soon as the first of its channels delivered it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ControlMarker {
     pub(crate) timestamp: Timestamp,
-    pub(crate) port_id: PortId,
-    pub(crate) node_id: NodeId,
-    pub(crate) flow_id: FlowId,
-    pub(crate) instance_id: Uuid,
+    pub(crate) payload: Arc<Vec<u8>>,
+    #[serde(default)]
+    pub(crate) epoch: Option<u64>,
+}
+
+impl ControlMarker {
+    pub(crate) fn new(timestamp: Timestamp, payload: Arc<Vec<u8>>) -> Self {
+        Self {
+            timestamp,
+            payload,
+            epoch: None,
+        }
+    }
+
+    /// Creates the marker of the checkpoint barrier of `epoch`, without payload.
+    pub(crate) fn checkpoint(timestamp: Timestamp, epoch: u64) -> Self {
+        Self {
+            timestamp,
+            payload: Arc::new(Vec::new()),
+            epoch: Some(epoch),
+        }
+    }
+
+    /// Return the epoch of the checkpoint, if the marker is a checkpoint barrier.
+    pub fn get_epoch(&self) -> Option<u64> {
+        self.epoch
+    }
+
+    /// Return the [Timestamp] at which the control message was broadcast.
+    pub fn get_timestamp(&self) -> &Timestamp {
+        &self.timestamp
+    }
+
+    /// Return the payload of the control message.
+    pub fn get_payload(&self) -> &[u8] {
+        &self.payload
+    }
 }
 
 /// Zenoh Flow control messages.
@@ -209,11 +591,22 @@ pub enum ControlMessage {
 /// The Zenoh-Flow message that is sent across `Link` and across Zenoh.
 ///
 /// It contains either a [`DataMessage`](`DataMessage`) or a [`Timestamp`](`uhlc::Timestamp`),
-/// in such case the `LinkMessage` variant is `Watermark`.
+/// in such case the `LinkMessage` variant is `Watermark` or `EndOfStream`.
+///
+/// An `EndOfStream` signals that no more message will be sent on the link. Operators are expected
+/// to flush their state and propagate it downstream.
+///
+/// A `Live` marks the transition of a Source in backfill mode from the replay of historical data
+/// to live data: the messages that follow it are live.
+///
+/// A `Control` is a [ControlMarker]: it is handled by the runtime and never returned to the nodes.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum LinkMessage {
     Data(DataMessage),
     Watermark(Timestamp),
+    EndOfStream(Timestamp),
+    Live(Timestamp),
+    Control(ControlMarker),
 }
 
 impl LinkMessage {
@@ -222,9 +615,46 @@ impl LinkMessage {
         Self::Data(DataMessage {
             data: output,
             timestamp,
+            event_time: None,
+            variant: None,
         })
     }
 
+    /// Creates a `LinkMessage::Data` from a [`Payload`](`Payload`) to which the component that
+    /// produced it assigned an `event_time`.
+    pub fn from_payload_with_event_time(
+        output: Payload,
+        timestamp: Timestamp,
+        event_time: Option<Timestamp>,
+    ) -> Self {
+        Self::Data(DataMessage {
+            data: output,
+            timestamp,
+            event_time,
+            variant: None,
+        })
+    }
+
+    /// Sets the `variant`, in the union of its port, of the data of a `LinkMessage::Data`. The
+    /// other messages are left untouched.
+    pub(crate) fn with_variant(mut self, variant: Option<u32>) -> Self {
+        if let Self::Data(data_message) = &mut self {
+            data_message.variant = variant;
+        }
+        self
+    }
+
+    /// Returns the [LinkMessage] as it is delivered to a node: `Inline` bytes, internal to the
+    /// links, are copied in `Bytes`. The other messages are left untouched.
+    pub(crate) fn into_delivered(mut self) -> Self {
+        if let Self::Data(data_message) = &mut self {
+            if let Payload::Inline(bytes) = &data_message.data {
+                data_message.data = Payload::Bytes(Arc::new(bytes.to_vec()));
+            }
+        }
+        self
+    }
+
     /// Serializes the [LinkMessage] using [bincode] into the given `buffer`.
     ///
     /// The `inner_buffer` is used to serialize (if need be) the [Payload] contained inside the
@@ -249,13 +679,17 @@ impl LinkMessage {
 
         match &self {
             LinkMessage::Data(data_message) => match &data_message.data {
-                Payload::Bytes(_) => bincode::serialize_into(message_buffer, &self)
-                    .map_err(|e| zferror!(ErrorKind::SerializationError, e).into()),
+                Payload::Bytes(_) | Payload::Inline(_) | Payload::Reference(_) => {
+                    bincode::serialize_into(message_buffer, &self)
+                        .map_err(|e| zferror!(ErrorKind::SerializationError, e).into())
+                }
                 Payload::Typed((data, serializer)) => {
                     (serializer)(payload_buffer, Arc::clone(data))?;
                     let serialized_message = LinkMessage::Data(DataMessage {
                         data: Payload::Bytes(Arc::new(payload_buffer.clone())),
                         timestamp: data_message.timestamp,
+                        event_time: data_message.event_time,
+                        variant: data_message.variant,
                     });
 
                     bincode::serialize_into(message_buffer, &serialized_message)
@@ -292,14 +726,18 @@ impl LinkMessage {
 
         match &self {
             LinkMessage::Data(data_message) => match &data_message.data {
-                Payload::Bytes(_) => bincode::serialize_into(shm_buffer, &self)
-                    .map_err(|e| zferror!(ErrorKind::SerializationError, e).into()),
+                Payload::Bytes(_) | Payload::Inline(_) | Payload::Reference(_) => {
+                    bincode::serialize_into(shm_buffer, &self)
+                        .map_err(|e| zferror!(ErrorKind::SerializationError, e).into())
+                }
                 Payload::Typed(_) => {
                     data_message.try_as_bytes_into(payload_buffer)?;
-                    let serialized_message = LinkMessage::Data(DataMessage::new_serialized(
-                        payload_buffer.clone(),
-                        data_message.timestamp,
-                    ));
+                    let serialized_message = LinkMessage::Data(DataMessage {
+                        data: Payload::Bytes(Arc::new(payload_buffer.clone())),
+                        timestamp: data_message.timestamp,
+                        event_time: data_message.event_time,
+                        variant: data_message.variant,
+                    });
                     bincode::serialize_into(shm_buffer, &serialized_message)
                         .map_err(|e| zferror!(ErrorKind::SerializationError, e).into())
                 }
@@ -314,6 +752,9 @@ impl LinkMessage {
         match self {
             Self::Data(data) => data.timestamp,
             Self::Watermark(ref ts) => *ts,
+            Self::EndOfStream(ref ts) => *ts,
+            Self::Live(ref ts) => *ts,
+            Self::Control(ref marker) => marker.timestamp,
             // Self::Control(ref ctrl) => match ctrl {
             //     ControlMessage::RecordingStart(ref rs) => rs.timestamp,
             //     ControlMessage::RecordingStop(ref ts) => *ts,
@@ -347,11 +788,20 @@ impl Eq for LinkMessage {}
 /// A `Message<T>` is what is received on an `Input<T>`, typically after a call to `try_recv` or
 /// `recv`.
 ///
-/// A `Message<T>` can either contain [`Data<T>`](`Data`), or signal a _Watermark_.
+/// A `Message<T>` can either contain [`Data<T>`](`Data`), or signal a _Watermark_ or the _end of
+/// the stream_.
+///
+/// Once an `EndOfStream` is received, no more message will be received on the `Input<T>`. An
+/// Operator should flush its state and propagate it on its outputs.
+///
+/// A `Live` signals that the historical data replayed upstream was entirely received: the messages
+/// that follow are live.
 #[derive(Debug)]
 pub enum Message<T> {
     Data(Data<T>),
     Watermark,
+    EndOfStream,
+    Live,
 }
 
 /// A `Data<T>` is a convenience wrapper around `T`.
@@ -362,9 +812,15 @@ pub enum Message<T> {
 /// ## Performance
 ///
 /// When deserializing, an allocation is performed.
+///
+/// ## Event time
+///
+/// A received `Data<T>` carries the event time assigned by the component that produced it, if any
+/// (see [`event_time`](Data::event_time)). It is kept when the `Data<T>` is sent again.
 #[derive(Debug)]
 pub struct Data<T> {
     inner: DataInner<T>,
+    event_time: Option<Timestamp>,
 }
 
 /// The `DataInner` enum represents the two ways to send data in an [`Output<T>`](`Output`).
@@ -394,6 +850,7 @@ impl<T: Send + Sync + 'static> From<T> for Data<T> {
     fn from(value: T) -> Self {
         Self {
             inner: DataInner::Data(value),
+            event_time: None,
         }
     }
 }
@@ -470,11 +927,13 @@ impl<T: 'static> Data<T> {
     /// Try to create a new [`Data<T>`](`Data`) based on a [`Payload`](`Payload`).
     ///
     /// Depending on the variant of [`Payload`](`Payload`) different steps are performed:
-    /// - if `Payload::Bytes` then Zenoh-Flow tries to deserialize to an instance of `T` (performing
-    ///   an allocation),
+    /// - if `Payload::Bytes` or `Payload::Inline` then Zenoh-Flow tries to deserialize to an
+    ///   instance of `T` (performing an allocation),
     /// - if `Payload::Typed` then Zenoh-Flow checks that the underlying type matches `T` (relying
     ///   on [`Any`](`Any`)).
     ///
+    /// A `Payload::Reference` must have been resolved beforehand.
+    ///
     /// ## Errors
     ///
     /// An error will be returned if the Payload does not match `T`, i.e. if the deserialization or
@@ -487,6 +946,7 @@ impl<T: 'static> Data<T> {
 
         match payload {
             Payload::Bytes(ref bytes) => typed = Some((deserializer)(bytes.as_slice())?),
+            Payload::Inline(ref bytes) => typed = Some((deserializer)(bytes)?),
             Payload::Typed((ref typed, _)) => {
                 if !(**typed).as_any().is::<T>() {
                     bail!(
@@ -495,6 +955,11 @@ impl<T: 'static> Data<T> {
                     )
                 }
             }
+            Payload::Reference(ref reference) => bail!(
+                ErrorKind::DeserializationError,
+                "The Payload references < {} >, it must first be resolved",
+                reference.key_expr()
+            ),
         }
 
         Ok(Self {
@@ -502,6 +967,18 @@ impl<T: 'static> Data<T> {
                 payload,
                 data: typed,
             },
+            event_time: None,
         })
     }
+
+    /// Sets the event time assigned to the data by the component that produced it.
+    pub(crate) fn with_event_time(mut self, event_time: Option<Timestamp>) -> Self {
+        self.event_time = event_time;
+        self
+    }
+
+    /// Returns the event time assigned to the data by the component that produced it, if any.
+    pub fn event_time(&self) -> Option<&Timestamp> {
+        self.event_time.as_ref()
+    }
 }
@@ -14,18 +14,17 @@
 
 pub(crate) mod message;
 pub use message::*;
+pub(crate) mod blackboard;
+pub use blackboard::Blackboard;
+pub(crate) mod keyed_state;
+pub use keyed_state::{partition_of, replica_of, KeyState, KeyedState, KEY_PARTITIONS};
 pub(crate) mod context;
 pub use context::*;
 pub(crate) mod configuration;
 pub use configuration::Configuration;
+pub(crate) mod serializer;
+pub use serializer::{PoolStatistics, SerializerPool};
+pub(crate) mod union;
+pub use union::UnionData;
 
-use std::sync::Arc;
-
-/// A NodeId identifies a node inside a Zenoh Flow graph
-pub type NodeId = Arc<str>;
-/// A PortId identifies a port within an node.
-pub type PortId = Arc<str>;
-/// A RuntimeId identifies a runtime within the Zenoh Flow infrastructure.
-pub type RuntimeId = Arc<str>;
-/// A FlowId identifies a Zenoh Flow graph within Zenoh Flow
-pub type FlowId = Arc<str>;
+pub use zenoh_flow_core::{FlowId, NodeId, PortId, RuntimeId};
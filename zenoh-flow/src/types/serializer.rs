@@ -0,0 +1,157 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// A `SerializerPool` lends the buffers in which data are serialized, and reuses them once the
+/// serialized data are no longer referenced.
+///
+/// The serialized data are returned shared (`Arc<Vec<u8>>`): they can be handed over, without
+/// being copied, to what sends them (e.g. Zenoh) and kept as long as needed (e.g. to be sent
+/// again). The pool keeps track of at most `capacity` buffers: a buffer is reused, with the memory
+/// it allocated, as soon as all the references to its previous content were dropped. When all of
+/// them are still referenced, a new buffer is allocated.
+///
+/// The pool counts how often a buffer could be reused, see [PoolStatistics].
+///
+/// # Example
+///
+/// ```ignore
+/// let pool = SerializerPool::new(16);
+/// let bytes = pool.serialize(|buffer| bincode::serialize_into(buffer, &reading))?;
+/// ```
+#[derive(Debug)]
+pub struct SerializerPool {
+    buffers: Mutex<Vec<Arc<Vec<u8>>>>,
+    capacity: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+/// The number of times a [SerializerPool] reused one of its buffers (`hits`) or had to allocate a
+/// new one (`misses`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolStatistics {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl PoolStatistics {
+    /// Returns the proportion, between 0 and 1, of the buffers that were reused. `None` is
+    /// returned if the pool was never used.
+    pub fn hit_rate(&self) -> Option<f64> {
+        match self.hits + self.misses {
+            0 => None,
+            total => Some(self.hits as f64 / total as f64),
+        }
+    }
+}
+
+impl std::ops::AddAssign for PoolStatistics {
+    fn add_assign(&mut self, other: Self) {
+        self.hits += other.hits;
+        self.misses += other.misses;
+    }
+}
+
+/// The number of buffers a `SerializerPool` keeps track of by default.
+const DEFAULT_CAPACITY: usize = 16;
+
+impl Default for SerializerPool {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl SerializerPool {
+    /// Creates a `SerializerPool` that keeps track of at most `capacity` buffers.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            buffers: Mutex::new(Vec::with_capacity(capacity)),
+            capacity,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Calls `serialize` on a cleared buffer of the pool and returns the serialized data.
+    ///
+    /// # Errors
+    ///
+    /// The error returned by `serialize`, if any. The buffer is given back to the pool.
+    pub fn serialize<E>(
+        &self,
+        serialize: impl FnOnce(&mut Vec<u8>) -> Result<(), E>,
+    ) -> Result<Arc<Vec<u8>>, E> {
+        let mut buffer = {
+            let mut buffers = self.buffers.lock().unwrap_or_else(|e| e.into_inner());
+            // A buffer only referenced by the pool is no longer referenced anywhere else: only the
+            // pool could lend it again.
+            match buffers
+                .iter()
+                .position(|buffer| Arc::strong_count(buffer) == 1)
+            {
+                Some(index) => {
+                    self.hits.fetch_add(1, Ordering::Relaxed);
+                    buffers.swap_remove(index)
+                }
+                None => {
+                    self.misses.fetch_add(1, Ordering::Relaxed);
+                    Arc::default()
+                }
+            }
+        };
+
+        // The buffer is not shared: it is not copied.
+        let bytes = Arc::make_mut(&mut buffer);
+        bytes.clear();
+        let result = serialize(bytes);
+
+        let mut buffers = self.buffers.lock().unwrap_or_else(|e| e.into_inner());
+        if buffers.len() < self.capacity {
+            buffers.push(buffer.clone());
+        }
+
+        result.map(|()| buffer)
+    }
+
+    /// Returns the number of buffers of the pool that can be reused right away.
+    pub fn available(&self) -> usize {
+        self.buffers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter(|buffer| Arc::strong_count(buffer) == 1)
+            .count()
+    }
+
+    /// Returns the maximum number of buffers the pool keeps track of.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns how often the buffers of the pool were reused so far.
+    pub fn statistics(&self) -> PoolStatistics {
+        PoolStatistics {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/serializer-tests.rs"]
+mod tests;
@@ -0,0 +1,44 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::types::Blackboard;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[test]
+fn test_blackboard_local() {
+    let instance_id = Uuid::new_v4();
+    let blackboard = Blackboard::new(instance_id, None);
+
+    assert!(blackboard.get("calibration").is_none());
+    assert!(blackboard.put("calibration", vec![1, 2, 3]).is_none());
+    assert_eq!(blackboard.get("calibration"), Some(Arc::new(vec![1, 2, 3])));
+
+    assert_eq!(
+        blackboard.put("calibration", vec![4]),
+        Some(Arc::new(vec![1, 2, 3]))
+    );
+    assert_eq!(blackboard.keys(), vec!["calibration".to_string()]);
+    assert_eq!(blackboard.remove("calibration"), Some(Arc::new(vec![4])));
+    assert!(blackboard.keys().is_empty());
+
+    assert_eq!(
+        blackboard.key_expr("calibration"),
+        format!("zenoh-flow/blackboard/{instance_id}/calibration")
+    );
+
+    // Without a Zenoh session, the entries cannot be shared.
+    assert!(async_std::task::block_on(blackboard.put_shared("model", vec![])).is_err());
+    assert!(blackboard.get("model").is_none());
+}
@@ -0,0 +1,87 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::{partition_of, replica_of, KeyedState, KEY_PARTITIONS};
+use std::sync::Arc;
+
+#[test]
+fn test_keyed_state_partitions() {
+    let replicas = (0..3)
+        .map(|replica| KeyedState::new(replica, 3))
+        .collect::<Vec<_>>();
+
+    // Each key is owned by exactly one replica.
+    for key in 0..100u32 {
+        let key = key.to_le_bytes();
+        assert!(partition_of(&key) < KEY_PARTITIONS);
+        let owners = replicas
+            .iter()
+            .filter(|state| state.owns(&key))
+            .map(KeyedState::replica)
+            .collect::<Vec<_>>();
+        assert_eq!(owners, vec![replica_of(&key, 3)]);
+    }
+
+    let key = b"sensor-42";
+    let owner = &replicas[replica_of(key, 3)];
+    let other = &replicas[(replica_of(key, 3) + 1) % 3];
+    assert!(other.for_key(key).is_err());
+
+    let state = owner.for_key(key).unwrap();
+    assert_eq!(state.partition(), partition_of(key));
+    assert!(state.get().is_none());
+    assert!(state.put(vec![1]).is_none());
+    assert_eq!(state.put(vec![2]), Some(Arc::new(vec![1])));
+    assert_eq!(state.get(), Some(Arc::new(vec![2])));
+    assert_eq!(owner.partitions(), vec![partition_of(key)]);
+    assert_eq!(state.remove(), Some(Arc::new(vec![2])));
+    assert!(owner.partitions().is_empty());
+}
+
+// The partitions checkpointed by 2 replicas are restored by the replicas, out of 3, owning them.
+#[test]
+fn test_keyed_state_rescale() {
+    let before = (0..2)
+        .map(|replica| KeyedState::new(replica, 2))
+        .collect::<Vec<_>>();
+    let keys = (0..50u32).map(|key| key.to_le_bytes()).collect::<Vec<_>>();
+    for key in &keys {
+        before[replica_of(key, 2)]
+            .for_key(key)
+            .unwrap()
+            .put(key.to_vec());
+    }
+
+    let mut checkpoint = std::collections::HashMap::new();
+    for state in &before {
+        checkpoint.extend(state.checkpoint().unwrap());
+    }
+
+    let after = (0..3)
+        .map(|replica| KeyedState::new(replica, 3))
+        .collect::<Vec<_>>();
+    let restored = after
+        .iter()
+        .map(|state| state.restore(&checkpoint).unwrap())
+        .sum::<usize>();
+    assert_eq!(restored, checkpoint.len());
+
+    for key in &keys {
+        let state = &after[replica_of(key, 3)];
+        assert_eq!(
+            state.for_key(key).unwrap().get(),
+            Some(Arc::new(key.to_vec()))
+        );
+    }
+}
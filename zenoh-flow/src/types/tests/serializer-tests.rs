@@ -0,0 +1,80 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::{PoolStatistics, SerializerPool};
+
+#[test]
+fn test_serializer_pool_reuse() {
+    let pool = SerializerPool::new(2);
+
+    let first = pool
+        .serialize(|buffer| -> Result<(), ()> {
+            buffer.extend_from_slice(&[1u8; 1024]);
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(first.len(), 1024);
+    // The serialized data are still referenced: the buffer cannot be reused.
+    assert_eq!(pool.available(), 0);
+
+    let address = first.as_ptr();
+    drop(first);
+    assert_eq!(pool.available(), 1);
+
+    // The buffer is cleared and its allocation reused.
+    let second = pool
+        .serialize(|buffer| -> Result<(), ()> {
+            assert!(buffer.is_empty());
+            assert!(buffer.capacity() >= 1024);
+            buffer.push(2);
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(*second, vec![2u8]);
+    assert_eq!(second.as_ptr(), address);
+
+    let statistics = pool.statistics();
+    assert_eq!(statistics, PoolStatistics { hits: 1, misses: 1 });
+    assert_eq!(statistics.hit_rate(), Some(0.5));
+    assert_eq!(PoolStatistics::default().hit_rate(), None);
+}
+
+#[test]
+fn test_serializer_pool_capacity() {
+    let pool = SerializerPool::new(1);
+    let serialize = |value: u8| {
+        pool.serialize(|buffer| -> Result<(), ()> {
+            buffer.push(value);
+            Ok(())
+        })
+    };
+
+    // The pool keeps track of a single buffer, the others are allocated on demand.
+    let held = (0..3)
+        .map(serialize)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(
+        held.iter().map(|bytes| bytes[0]).collect::<Vec<_>>(),
+        vec![0, 1, 2]
+    );
+    drop(held);
+    assert_eq!(pool.available(), 1);
+
+    // A buffer whose serialization failed goes back to the pool.
+    assert!(pool
+        .serialize(|_| -> Result<(), &str> { Err("failed") })
+        .is_err());
+    assert_eq!(pool.available(), 1);
+}
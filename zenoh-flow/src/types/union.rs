@@ -0,0 +1,50 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+/// The data of a union port (see [PortType](crate::model::descriptor::PortType)): an enumeration
+/// with a variant per data type of the union, in the same order.
+///
+/// The index of the variant is carried in the header of the message, such that the receiving side
+/// knows how to deserialize the data (see [`OutputBuilder::union`](crate::io::OutputBuilder::union)
+/// and [`InputBuilder::union`](crate::io::InputBuilder::union)).
+///
+/// It is implemented with `#[derive(UnionData)]`, for an enumeration whose variants each hold a
+/// single value, serialized with `bincode`:
+///
+/// ```ignore
+/// // output_types:
+/// //   Reading: [my.company.Temperature@1.0, my.company.Humidity@1.0]
+/// #[derive(UnionData)]
+/// pub enum Reading {
+///     Temperature(Temperature),
+///     Humidity(Humidity),
+/// }
+///
+/// let output: Output<Reading> = outputs.take("Reading").expect("No output 'Reading'").union();
+/// ```
+pub trait UnionData: Sized + Send + Sync + 'static {
+    /// Returns the index of the variant of `self` in the union.
+    fn variant(&self) -> u32;
+
+    /// Serializes the value held by the variant of `self` into the `buffer`.
+    fn serialize_variant(&self, buffer: &mut Vec<u8>) -> anyhow::Result<()>;
+
+    /// Deserializes the `bytes` of the value held by the variant of index `variant`.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the union has no variant of index `variant` or if the bytes do not
+    /// hold its value.
+    fn deserialize_variant(variant: u32, bytes: &[u8]) -> anyhow::Result<Self>;
+}
@@ -12,12 +12,14 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
-use crate::model::{Middleware, ZFUri};
+use crate::model::descriptor::LinkDescriptor;
+use crate::model::{BuiltinOperator, Middleware, ZFUri};
 use crate::prelude::ErrorKind;
 use crate::{bail, zferror, Result};
-use serde::Deserializer;
+use serde::{Deserialize, Deserializer, Serializer};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::Duration;
 use url::Url;
 
 /// Given a string representing a [`Url`](`url::Url`), transform it into a
@@ -26,13 +28,15 @@ use url::Url;
 /// Supported schemes:
 /// - `file://`
 /// - `builtin://`
+/// - `http://` and `https://`, the library being fetched through the
+///   [`LibraryCache`](`crate::runtime::dataflow::cache::LibraryCache`)
 ///
 /// # Errors
 ///
 /// This function will return an error in the following situations:
 /// - The provided string does not match the syntax of a [`Url`](`url::Url`).
 /// - The scheme is not supported
-/// - In case of `builtin://`, the authority part, `<middleware>`, is not supported.
+/// - In case of `builtin://`, the authority part, `<middleware>` or `<operator>`, is not supported.
 /// - In case of `file://`, the resulting path cannot be [`canonicalized`](`std::fs::canonicalize`).
 pub(crate) fn parse_uri(url_str: &str) -> Result<ZFUri> {
     let uri = Url::parse(url_str).map_err(|err| {
@@ -52,13 +56,17 @@ pub(crate) fn parse_uri(url_str: &str) -> Result<ZFUri> {
     match uri.scheme() {
         "file" => Ok(ZFUri::File(try_make_file_path(&uri_path)?)),
         "builtin" => {
+            if let Ok(operator) = BuiltinOperator::from_str(&uri_path) {
+                return Ok(ZFUri::BuiltinOperator(operator));
+            }
             let mw = Middleware::from_str(&uri_path)?;
             Ok(ZFUri::Builtin(mw))
         }
+        "http" | "https" => Ok(ZFUri::Remote(uri)),
         _ => {
             bail!(
                 ErrorKind::ParsingError,
-                "Scheme: {}:// is not supported. Supported ones are `file://` and `builtin://`",
+                "Scheme: {}:// is not supported. Supported ones are `file://`, `builtin://`, `http://` and `https://`",
                 uri.scheme()
             );
         }
@@ -112,9 +120,10 @@ pub fn deserialize_size<'de, D>(deserializer: D) -> std::result::Result<Option<u
 where
     D: Deserializer<'de>,
 {
-    let buf: &str = serde::de::Deserialize::deserialize(deserializer)?;
+    // The descriptors are deserialized from a `serde_json::Value`, which only yields owned strings.
+    let buf: String = serde::de::Deserialize::deserialize(deserializer)?;
     Ok(Some(
-        bytesize::ByteSize::from_str(buf)
+        bytesize::ByteSize::from_str(&buf)
             .map_err(|_| serde::de::Error::custom(format!("Unable to parse value as bytes {buf}")))?
             .as_u64() as usize,
     ))
@@ -130,3 +139,127 @@ where
         .map_err(serde::de::Error::custom)?;
     Ok(Some(ht.as_nanos() as u64))
 }
+
+/// Deserializes an optional duration expressed in a human readable format (e.g. "100ms").
+pub fn deserialize_duration<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Option<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let buf: Option<String> = serde::de::Deserialize::deserialize(deserializer)?;
+    buf.map(|buf| {
+        buf.parse::<humantime::Duration>()
+            .map(Duration::from)
+            .map_err(serde::de::Error::custom)
+    })
+    .transpose()
+}
+
+/// Serializes an optional duration in a human readable format (e.g. "100ms"), such that it can be
+/// read back by [`deserialize_duration`].
+pub fn serialize_duration<S>(
+    duration: &Option<Duration>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match duration {
+        Some(duration) => {
+            serializer.serialize_str(&humantime::format_duration(*duration).to_string())
+        }
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Serializes an optional size as a number of bytes, such that it can be read back by
+/// [`deserialize_size`].
+pub fn serialize_size<S>(
+    size: &Option<usize>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match size {
+        Some(size) => serializer.serialize_str(&size.to_string()),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Deserializes a duration expressed in a human readable format (e.g. "100ms").
+pub fn deserialize_required_duration<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let buf: String = serde::de::Deserialize::deserialize(deserializer)?;
+    buf.parse::<humantime::Duration>()
+        .map(Duration::from)
+        .map_err(serde::de::Error::custom)
+}
+
+/// Serializes a duration in a human readable format (e.g. "100ms"), such that it can be
+/// deserialized by [deserialize_required_duration].
+pub fn serialize_required_duration<S>(
+    duration: &Duration,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&humantime::format_duration(*duration).to_string())
+}
+
+/// Deserializes the links of a data flow, expanding the links declaring a list of `from` and/or a
+/// list of `to` endpoints into one link per pair of endpoints.
+///
+/// This allows several outputs to feed one input (fan-in) and one output to feed several inputs
+/// (fan-out) without repeating the link entries.
+pub fn deserialize_links<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Vec<LinkDescriptor>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let links: Vec<serde_json::Value> = serde::de::Deserialize::deserialize(deserializer)?;
+
+    let mut expanded = Vec::with_capacity(links.len());
+    for link in links {
+        let endpoints = |key: &str| match link.get(key) {
+            Some(serde_json::Value::Array(endpoints)) => {
+                if endpoints.is_empty() {
+                    return Err(serde::de::Error::custom(format!(
+                        "The list of `{key}` endpoints of a link cannot be empty"
+                    )));
+                }
+                Ok(endpoints.iter().cloned().map(Some).collect::<Vec<_>>())
+            }
+            Some(endpoint) => Ok(vec![Some(endpoint.clone())]),
+            // A missing endpoint is reported when deserializing the link.
+            None => Ok(vec![None]),
+        };
+
+        let (froms, tos) = (endpoints("from")?, endpoints("to")?);
+        for from in froms.iter() {
+            for to in tos.iter() {
+                let mut link = link.clone();
+                if let Some(object) = link.as_object_mut() {
+                    if let Some(from) = from {
+                        object.insert("from".into(), from.clone());
+                    }
+                    if let Some(to) = to {
+                        object.insert("to".into(), to.clone());
+                    }
+                }
+
+                expanded
+                    .push(LinkDescriptor::deserialize(&link).map_err(serde::de::Error::custom)?);
+            }
+        }
+    }
+
+    Ok(expanded)
+}
@@ -12,12 +12,13 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
-use crate::types::{NodeId, PortId};
+use crate::types::{NodeId, PortId, RuntimeId};
 use serde::{Deserialize, Serialize};
 
 use anyhow::Error as AnyError;
 use std::convert::From;
 use std::fmt;
+use std::time::Duration;
 use uhlc::Timestamp;
 use uuid::Uuid;
 
@@ -69,52 +70,135 @@ pub type DaemonResult<T> = Result<T, ZFError>;
 /// The Zenoh Flow error
 /// It contains mapping to most of the errors that could happen within
 /// Zenoh Flow and its dependencies.
-#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Eq)]
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Eq, thiserror::Error)]
 pub enum ErrorKind {
+    #[error("Generic error")]
     GenericError,
+    #[error("Serialization error")]
     SerializationError,
+    #[error("Deserialization error")]
     DeserializationError,
+    #[error("Missing state")]
     MissingState,
+    #[error("Invalid state")]
     InvalidState,
+    #[error("Unimplemented")]
     Unimplemented,
+    #[error("Unsupported")]
     Unsupported,
+    #[error("Empty")]
     Empty,
+    #[error("Not found")]
     NotFound,
+    #[error("Duplicate")]
     Duplicate,
+    #[error("Missing configuration")]
     MissingConfiguration,
+    #[error("Configuration error")]
     ConfigurationError,
+    #[error("Version mismatch")]
     VersionMismatch,
+    #[error("Disconnected")]
     Disconnected,
+    #[error("Uncompleted")]
     Uncompleted,
+    #[error("Receive error")]
     RecvError,
+    #[error("Send error")]
     SendError,
+    #[error("Missing input < {0} >")]
     MissingInput(String),
+    #[error("Missing output < {0} >")]
     MissingOutput(String),
+    #[error("Invalid data")]
     InvalidData,
+    #[error("I/O error")]
     IOError,
+    #[error("Zenoh error")]
     ZenohError,
+    #[error("Loading error")]
     LoadingError,
+    #[error("Parsing error")]
     ParsingError,
+    #[error("Failed to stop a runner")]
     RunnerStopError,
+    #[error("Failed to send the stop signal to a runner")]
     RunnerStopSendError,
+    #[error("Instance < {0} > not found")]
     InstanceNotFound(Uuid),
+    #[error("RPC error")]
     RPCError,
+    #[error("A Source does not have inputs")]
     SourceDoNotHaveInputs,
+    #[error("A Receiver does not have inputs")]
     ReceiverDoNotHaveInputs,
+    #[error("A Sink does not have outputs")]
     SinkDoNotHaveOutputs,
+    #[error("A Sender does not have outputs")]
     SenderDoNotHaveOutputs,
     // Validation Error
+    #[error("Duplicated node < {0} >")]
     DuplicatedNodeId(NodeId),
+    #[error("Duplicated port (node, port): {0:?}")]
     DuplicatedPort((NodeId, PortId)),
+    #[error("Duplicated link (from, to): {0:?}")]
     DuplicatedLink(((NodeId, PortId), (NodeId, PortId))),
+    #[error("Multiple outputs connected to the input (node, port): {0:?}")]
     MultipleOutputsToInput((NodeId, PortId)),
+    #[error("Node < {0} > not found")]
     NodeNotFound(NodeId),
+    #[error("Port not found (node, port): {0:?}")]
     PortNotFound((NodeId, PortId)),
+    #[error("Port not connected (node, port): {0:?}")]
     PortNotConnected((NodeId, PortId)),
+    #[error("Incompatible data types (from, to): {0:?}")]
+    IncompatibleDataTypes(((NodeId, PortId), (NodeId, PortId))),
+    #[error("Incompatible units (from, to): {0:?}")]
+    IncompatibleUnits(((NodeId, PortId), (NodeId, PortId))),
+    #[error("Not recording")]
     NotRecording,
+    #[error("Already recording")]
     AlreadyRecording,
+    #[error("Not ready")]
+    NotReady,
+    #[error("No path between (from, to): {0:?}")]
     NoPathBetweenNodes(((NodeId, PortId), (NodeId, PortId))),
+    #[error("Timestamp < {0} > is below the watermark")]
     BelowWatermarkTimestamp(Timestamp),
+    #[error("Node < {0} > panicked: {1}")]
+    NodePanic(NodeId, String),
+    #[error("Node < {0} > exceeded its maximum run duration of {1:?}")]
+    RunTimeout(NodeId, Duration),
+    #[error("Resource unavailable")]
+    ResourceUnavailable,
+    #[error("Node < {0} > hung for {1:?}")]
+    NodeHung(NodeId, Duration),
+    #[error("The clock of runtime < {0} > is off by {1:?}")]
+    ClockSkew(RuntimeId, Duration),
+    #[error("Runtime(s) not found: {0:?}")]
+    MissingRuntimes(Vec<RuntimeId>),
+}
+
+/// The element of a data flow an error relates to.
+///
+/// Contexts are attached to an error as it is propagated (see [WithContext]), the innermost first.
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Eq)]
+pub enum ErrorContext {
+    Node(NodeId),
+    Input(PortId),
+    Output(PortId),
+    Message(String),
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorContext::Node(node_id) => write!(f, "node < {node_id} >"),
+            ErrorContext::Input(port_id) => write!(f, "input < {port_id} >"),
+            ErrorContext::Output(port_id) => write!(f, "output < {port_id} >"),
+            ErrorContext::Message(message) => write!(f, "{message}"),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -128,6 +212,8 @@ pub struct ZFError {
     #[serde(skip_serializing, skip_deserializing)]
     source: Option<Error>,
     source_desc: Option<String>,
+    #[serde(default)]
+    context: Vec<ErrorContext>,
 }
 
 unsafe impl Send for ZFError {}
@@ -145,6 +231,7 @@ impl ZFError {
             line,
             source: None,
             source_desc: None,
+            context: Vec::new(),
         }
     }
 
@@ -155,9 +242,20 @@ impl ZFError {
         self
     }
 
+    /// Attaches the `context` to the error.
+    pub fn add_context(mut self, context: ErrorContext) -> Self {
+        self.context.push(context);
+        self
+    }
+
     pub fn get_kind(&self) -> &ErrorKind {
         &self.kind
     }
+
+    /// Returns the contexts attached to the error, the innermost first.
+    pub fn get_context(&self) -> &[ErrorContext] {
+        &self.context
+    }
 }
 
 impl std::clone::Clone for ZFError {
@@ -170,6 +268,7 @@ impl std::clone::Clone for ZFError {
             line: self.line,
             source: None,
             source_desc: self.source_desc.clone(),
+            context: self.context.clone(),
         }
     }
 
@@ -180,7 +279,8 @@ impl std::clone::Clone for ZFError {
         self.file = source.file.clone();
         self.line = source.line;
         self.source = None;
-        self.source_desc = self.source_desc.clone();
+        self.source_desc = source.source_desc.clone();
+        self.context = source.context.clone();
     }
 }
 
@@ -207,6 +307,9 @@ impl fmt::Display for ZFError {
         };
 
         write!(f, "{}:{} {:?}: {:?}", self.file, self.line, self.kind, desc)?;
+        for context in self.context.iter() {
+            write!(f, "\n  in {context}")?;
+        }
         if let Some(s) = &self.source {
             write!(f, "\nCaused by {}: {:?}", *s, self.source_desc)?;
         }
@@ -238,12 +341,14 @@ impl From<flume::RecvError> for ZFError {
     }
 }
 
+#[cfg(feature = "async-std")]
 impl From<async_std::channel::RecvError> for ZFError {
     fn from(err: async_std::channel::RecvError) -> Self {
         zferror!(ErrorKind::RunnerStopError, err)
     }
 }
 
+#[cfg(feature = "async-std")]
 impl From<async_std::channel::SendError<()>> for ZFError {
     fn from(err: async_std::channel::SendError<()>) -> Self {
         zferror!(ErrorKind::RunnerStopSendError, err)
@@ -267,33 +372,71 @@ impl<T> From<flume::SendError<T>> for ZFError {
 
 impl From<std::io::Error> for ZFError {
     fn from(err: std::io::Error) -> Self {
-        zferror!(ErrorKind::IOError, err)
+        zferror!(ErrorKind::IOError, err => "{}", err)
     }
 }
 
 impl From<zenoh_util::core::Error> for ZFError {
     fn from(err: zenoh_util::core::Error) -> Self {
-        zferror!(ErrorKind::ZenohError, "{}", err)
+        zferror!(ErrorKind::ZenohError, err => "{}", err)
     }
 }
 
 impl From<libloading::Error> for ZFError {
     fn from(err: libloading::Error) -> Self {
-        zferror!(ErrorKind::LoadingError, err)
+        zferror!(ErrorKind::LoadingError, err => "{}", err)
     }
 }
 
 #[cfg(feature = "data_json")]
 impl From<serde_json::Error> for ZFError {
     fn from(err: serde_json::Error) -> Self {
-        zferror!(ErrorKind::SerializationError, err)
+        zferror!(ErrorKind::SerializationError, err => "{}", err)
     }
 }
 
 #[cfg(feature = "data_json")]
 impl From<std::str::Utf8Error> for ZFError {
     fn from(err: std::str::Utf8Error) -> Self {
-        zferror!(ErrorKind::SerializationError, err)
+        zferror!(ErrorKind::SerializationError, err => "{}", err)
+    }
+}
+
+impl From<serde_yaml::Error> for ZFError {
+    fn from(err: serde_yaml::Error) -> Self {
+        zferror!(ErrorKind::ParsingError, err => "{}", err)
+    }
+}
+
+impl From<bincode::Error> for ZFError {
+    fn from(err: bincode::Error) -> Self {
+        zferror!(ErrorKind::SerializationError, err => "{}", err)
+    }
+}
+
+/// Attaches an [ErrorContext] to the error of a [ZFResult], as it is propagated.
+///
+/// Errors that are not a [ZFError] are wrapped in a `GenericError`, keeping them as source.
+pub trait WithContext<T> {
+    fn context(self, context: ErrorContext) -> ZFResult<T>;
+
+    fn node_context(self, node_id: &NodeId) -> ZFResult<T>
+    where
+        Self: Sized,
+    {
+        self.context(ErrorContext::Node(node_id.clone()))
+    }
+}
+
+impl<T> WithContext<T> for ZFResult<T> {
+    fn context(self, context: ErrorContext) -> ZFResult<T> {
+        self.map_err(|err| {
+            let err = match err.downcast::<ZFError>() {
+                Ok(err) => *err,
+                Err(err) => zferror!(ErrorKind::GenericError, err => "{}", err),
+            };
+            err.add_context(context).into()
+        })
     }
 }
 
@@ -317,3 +460,7 @@ impl From<zrpc::zrpcresult::ZRPCError> for ErrorKind {
         ErrorKind::RPCError
     }
 }
+
+#[cfg(test)]
+#[path = "./tests/zfresult-tests.rs"]
+mod tests;
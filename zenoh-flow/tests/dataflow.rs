@@ -13,6 +13,7 @@
 //
 
 use async_trait::async_trait;
+use std::collections::HashMap;
 use std::ops::Deref;
 use std::sync::Arc;
 use std::time::Duration;
@@ -21,6 +22,7 @@ use zenoh::prelude::r#async::*;
 use zenoh_flow::io::{Inputs, Outputs};
 use zenoh_flow::model::descriptor::{InputDescriptor, OutputDescriptor};
 use zenoh_flow::model::record::{OperatorRecord, PortRecord, SinkRecord, SourceRecord};
+use zenoh_flow::runtime::dataflow::instance::record_sink::RecordingBackend;
 use zenoh_flow::runtime::dataflow::instance::DataFlowInstance;
 use zenoh_flow::runtime::dataflow::loader::{Loader, LoaderConfig};
 use zenoh_flow::runtime::RuntimeContext;
@@ -170,6 +172,10 @@ impl Node for TestOperator {
                 Payload::Typed((_dyn_data, _)) => {
                     panic!("Unexpected typed message")
                 }
+                Payload::Reference(_) => {
+                    panic!("Unexpected reference message")
+                }
+                _ => panic!("Unexpected message"),
             }
 
             // Check the typed input value.
@@ -233,6 +239,9 @@ impl Node for TestSink {
                 Payload::Bytes(_) => {
                     panic!("Unexpected Payload::Bytes")
                 }
+                Payload::Reference(_) => {
+                    panic!("Unexpected Payload::Reference")
+                }
                 Payload::Typed((dyn_data, _)) => {
                     let value = (**dyn_data)
                         .as_any()
@@ -243,6 +252,7 @@ impl Node for TestSink {
                     // compare it with `TYPED_VALUE`.
                     assert_eq!(*value, TYPED_VALUE);
                 }
+                _ => panic!("Unexpected Payload"),
             }
 
             // NOTE: Tricky bit, we connected the raw output of the `TestOperator` to the
@@ -288,6 +298,9 @@ async fn single_runtime() {
         shared_memory_elements: DEFAULT_SHM_TOTAL_ELEMENTS as usize,
         shared_memory_backoff: DEFAULT_SHM_ALLOCATION_BACKOFF_NS,
         use_shm: false,
+        recording_backend: RecordingBackend::default(),
+        zenoh_configs: Arc::default(),
+        gpus: Arc::default(),
     };
 
     let mut dataflow = zenoh_flow::runtime::dataflow::DataFlow::new("test", ctx.clone());
@@ -299,10 +312,12 @@ async fn single_runtime() {
             PortRecord {
                 uid: 0,
                 port_id: OUT_TYPED.into(),
+                data_type: None,
             },
             PortRecord {
                 uid: 1,
                 port_id: OUT_RAW.into(),
+                data_type: None,
             },
         ],
         uri: None,
@@ -327,22 +342,27 @@ async fn single_runtime() {
             PortRecord {
                 uid: 1,
                 port_id: IN_RAW.into(),
+                data_type: None,
             },
             PortRecord {
                 uid: 2,
                 port_id: IN_TYPED.into(),
+                data_type: None,
             },
         ],
         outputs: vec![
             PortRecord {
                 uid: 3,
                 port_id: OUT_RAW.into(),
+                data_type: None,
             },
             PortRecord {
                 uid: 4,
                 port_id: OUT_TYPED.into(),
+                data_type: None,
             },
         ],
+        input_policies: HashMap::new(),
         uri: None,
         configuration: None,
         runtime: runtime_name.clone(),
@@ -368,12 +388,15 @@ async fn single_runtime() {
             PortRecord {
                 uid: 9,
                 port_id: IN_TYPED.into(),
+                data_type: None,
             },
             PortRecord {
                 uid: 10,
                 port_id: IN_RAW.into(),
+                data_type: None,
             },
         ],
+        input_policies: HashMap::new(),
         uri: None,
         configuration: None,
         runtime: runtime_name.clone(),
@@ -441,6 +464,17 @@ async fn single_runtime() {
         .await
         .unwrap();
 
+    let physical_graph = instance.physical_graph();
+    assert_eq!(
+        physical_graph
+            .nodes
+            .iter()
+            .map(|node| node.id.as_ref())
+            .collect::<Vec<_>>(),
+        vec![OPERATOR, SINK, SOURCE]
+    );
+    assert_eq!(physical_graph.links.len(), 4);
+
     for id in instance.get_sinks() {
         instance.start_node(&id).unwrap();
     }
@@ -0,0 +1,243 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use zenoh::prelude::r#async::*;
+
+use zenoh_flow::io::{Inputs, Outputs};
+use zenoh_flow::model::descriptor::{FlattenDataFlowDescriptor, InputDescriptor, OutputDescriptor};
+use zenoh_flow::model::record::{DataFlowRecord, PortRecord, SinkRecord, SourceRecord};
+use zenoh_flow::runtime::dataflow::dry_run::DryRunStage;
+use zenoh_flow::runtime::dataflow::instance::record_sink::RecordingBackend;
+use zenoh_flow::runtime::dataflow::loader::{Loader, LoaderConfig};
+use zenoh_flow::runtime::dataflow::DataFlow;
+use zenoh_flow::runtime::RuntimeContext;
+use zenoh_flow::types::{Configuration, Context};
+use zenoh_flow::{
+    bail, prelude::*, DEFAULT_SHM_ALLOCATION_BACKOFF_NS, DEFAULT_SHM_ELEMENT_SIZE,
+    DEFAULT_SHM_TOTAL_ELEMENTS,
+};
+
+static SOURCE: &str = "dry-run-source";
+static SINK: &str = "dry-run-sink";
+static PORT: &str = "data";
+
+/// The number of nodes that were cleaned.
+static CLEANED: AtomicUsize = AtomicUsize::new(0);
+
+struct DryRunSource {
+    _output: OutputRaw,
+}
+
+#[async_trait]
+impl Source for DryRunSource {
+    async fn new(
+        _context: Context,
+        _configuration: Option<Configuration>,
+        mut outputs: Outputs,
+    ) -> Result<Self> {
+        Ok(DryRunSource {
+            _output: outputs.take(PORT).expect("Missing output").raw(),
+        })
+    }
+}
+
+#[async_trait]
+impl Node for DryRunSource {
+    async fn iteration(&self) -> Result<()> {
+        panic!("A Source must not be iterated during a dry run");
+    }
+
+    async fn clean(&self, _context: &Context) -> Result<()> {
+        CLEANED.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+struct DryRunSink {
+    _input: InputRaw,
+}
+
+#[async_trait]
+impl Sink for DryRunSink {
+    async fn new(
+        _context: Context,
+        configuration: Option<Configuration>,
+        mut inputs: Inputs,
+    ) -> Result<Self> {
+        if configuration.is_none() {
+            bail!(
+                ErrorKind::MissingConfiguration,
+                "The Sink must be configured"
+            );
+        }
+
+        Ok(DryRunSink {
+            _input: inputs.take(PORT).expect("Missing input").raw(),
+        })
+    }
+}
+
+#[async_trait]
+impl Node for DryRunSink {
+    async fn iteration(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+fn runtime_context(session: Arc<zenoh::Session>) -> RuntimeContext {
+    let rt_uuid = uuid::Uuid::new_v4();
+    RuntimeContext {
+        session,
+        hlc: Arc::new(uhlc::HLC::default()),
+        loader: Arc::new(Loader::new(LoaderConfig::new())),
+        runtime_name: format!("test-runtime-{rt_uuid}").into(),
+        runtime_uuid: rt_uuid,
+        shared_memory_element_size: DEFAULT_SHM_ELEMENT_SIZE as usize,
+        shared_memory_elements: DEFAULT_SHM_TOTAL_ELEMENTS as usize,
+        shared_memory_backoff: DEFAULT_SHM_ALLOCATION_BACKOFF_NS,
+        use_shm: false,
+        recording_backend: RecordingBackend::default(),
+        zenoh_configs: Arc::default(),
+        gpus: Arc::default(),
+    }
+}
+
+fn data_flow(ctx: &RuntimeContext) -> DataFlow {
+    let mut dataflow = DataFlow::new("dry-run", ctx.clone());
+
+    dataflow.add_source(
+        SourceRecord {
+            id: SOURCE.into(),
+            uid: 0,
+            outputs: vec![PortRecord {
+                uid: 0,
+                port_id: PORT.into(),
+                data_type: None,
+            }],
+            uri: None,
+            configuration: None,
+            runtime: ctx.runtime_name.clone(),
+        },
+        |context: Context, configuration: Option<Configuration>, outputs: Outputs| {
+            Box::pin(async {
+                let node = DryRunSource::new(context, configuration, outputs).await?;
+                Ok(Arc::new(node) as Arc<dyn Node>)
+            })
+        },
+    );
+
+    dataflow.add_sink(
+        SinkRecord {
+            id: SINK.into(),
+            uid: 1,
+            inputs: vec![PortRecord {
+                uid: 1,
+                port_id: PORT.into(),
+                data_type: None,
+            }],
+            input_policies: HashMap::new(),
+            uri: None,
+            configuration: None,
+            runtime: ctx.runtime_name.clone(),
+        },
+        |context: Context, configuration: Option<Configuration>, inputs: Inputs| {
+            Box::pin(async {
+                let node = DryRunSink::new(context, configuration, inputs).await?;
+                Ok(Arc::new(node) as Arc<dyn Node>)
+            })
+        },
+    );
+
+    dataflow.add_link(
+        OutputDescriptor {
+            node: SOURCE.into(),
+            output: PORT.into(),
+        },
+        InputDescriptor {
+            node: SINK.into(),
+            input: PORT.into(),
+        },
+    );
+
+    dataflow
+}
+
+static MISSING_LIBRARIES: &str = r#"
+flow: DryRun
+sources:
+  - id : Counter
+    uri: file:///nonexistent/libcounter_source.so
+    outputs: [Counter]
+
+sinks:
+  - id : PrintSink
+    uri: file:///nonexistent/libgeneric_sink.so
+    inputs: [Data]
+
+links:
+- from:
+    node : Counter
+    output : Counter
+  to:
+    node : PrintSink
+    input : Data
+"#;
+
+async fn dry_run() {
+    let session = Arc::new(
+        zenoh::open(zenoh::config::Config::default())
+            .res()
+            .await
+            .unwrap(),
+    );
+    let ctx = runtime_context(session);
+
+    // The Source is created and cleaned, the Sink fails as it is not configured.
+    let report = data_flow(&ctx).dry_run_nodes().await.unwrap();
+    assert!(!report.is_ok());
+    assert_eq!(report.nodes.len(), 2);
+    assert!(report.nodes[&NodeId::from(SOURCE)].is_ok());
+    let failures = report.failures().collect::<Vec<_>>();
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].0.as_ref(), SINK);
+    assert_eq!(failures[0].1.stage, DryRunStage::Initialization);
+    assert!(failures[0].1.error.contains("The Sink must be configured"));
+    assert_eq!(CLEANED.load(Ordering::SeqCst), 1);
+
+    // All the libraries are loaded, even if one is missing.
+    let mut descriptor = FlattenDataFlowDescriptor::from_yaml(MISSING_LIBRARIES).unwrap();
+    descriptor.mapping = Some(HashMap::from([
+        ("Counter".into(), ctx.runtime_name.clone()),
+        ("PrintSink".into(), ctx.runtime_name.clone()),
+    ]));
+    let record = DataFlowRecord::try_from((descriptor, uuid::Uuid::new_v4())).unwrap();
+
+    let report = DataFlow::dry_run(record, ctx).await.unwrap();
+    assert_eq!(report.nodes.len(), 2);
+    assert!(report
+        .failures()
+        .all(|(_, failure)| failure.stage == DryRunStage::Loading));
+}
+
+#[test]
+fn run_dry_run() {
+    let _ = env_logger::try_init();
+
+    async_std::task::block_on(dry_run())
+}
@@ -0,0 +1,221 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::time::Duration;
+
+use zenoh_flow::io::{Inputs, Outputs};
+use zenoh_flow::model::descriptor::DataFlowDescriptor;
+use zenoh_flow::prelude::*;
+use zenoh_flow::runtime::Runtime;
+use zenoh_flow::types::LinkMessage;
+
+static FLOW: &str = r#"
+flow: embedded
+
+sources:
+  - id: host-source
+    descriptor: builtin://host
+    configuration:
+      ports: [data]
+
+operators:
+  - id: operator
+    descriptor: file://./src/model/descriptor/tests/operator.yml
+
+sinks:
+  - id: host-sink
+    descriptor: builtin://host
+    configuration:
+      ports: [data]
+
+links:
+  - from:
+      node: host-source
+      output: data
+    to:
+      node: operator
+      input: operator-in
+  - from:
+      node: operator
+      output: operator-out
+    to:
+      node: host-sink
+      input: data
+"#;
+
+// -------------------------------------------------------------------------------------------------
+// -------------------------------------------------------------------------------------------------
+// OPERATOR
+// -------------------------------------------------------------------------------------------------
+// -------------------------------------------------------------------------------------------------
+
+struct ForwardOperator {
+    input: InputRaw,
+    output: OutputRaw,
+}
+
+#[async_trait]
+impl Operator for ForwardOperator {
+    async fn new(
+        _context: Context,
+        _configuration: Option<Configuration>,
+        mut inputs: Inputs,
+        mut outputs: Outputs,
+    ) -> Result<Self> {
+        Ok(ForwardOperator {
+            input: inputs
+                .take("operator-in")
+                .expect("No input `operator-in` for ForwardOperator")
+                .raw(),
+            output: outputs
+                .take("operator-out")
+                .expect("No output `operator-out` for ForwardOperator")
+                .raw(),
+        })
+    }
+}
+
+#[async_trait]
+impl Node for ForwardOperator {
+    async fn iteration(&self) -> Result<()> {
+        if let LinkMessage::Data(data_message) = self.input.recv().await? {
+            self.output.send(data_message.clone(), None).await?;
+        }
+        Ok(())
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// -------------------------------------------------------------------------------------------------
+// EMBEDDED RUNTIME
+// -------------------------------------------------------------------------------------------------
+// -------------------------------------------------------------------------------------------------
+
+async fn embedded_runtime() {
+    let descriptor = DataFlowDescriptor::from_yaml(FLOW).unwrap();
+
+    let mut runtime = Runtime::builder()
+        .descriptor(descriptor)
+        .operator("operator", |context, configuration, inputs, outputs| {
+            Box::pin(async {
+                let node = ForwardOperator::new(context, configuration, inputs, outputs).await?;
+                Ok(Arc::new(node) as Arc<dyn Node>)
+            })
+        })
+        .build()
+        .await
+        .unwrap();
+
+    let input = runtime.take_input("host-source").unwrap();
+    let output = runtime.take_output("host-sink").unwrap();
+    assert!(runtime.take_input("host-source").is_none());
+    assert!(runtime.take_input("host-sink").is_none());
+
+    runtime.start().await.unwrap();
+
+    assert!(input.send("unknown", vec![0u8]).await.is_err());
+
+    for value in 0u8..3 {
+        input.send("data", vec![value; 4]).await.unwrap();
+        let (port, data) = async_std::future::timeout(Duration::from_secs(5), output.recv())
+            .await
+            .expect("No data received by the Host Sink")
+            .unwrap();
+        assert_eq!(port.as_ref(), "data");
+        assert_eq!(data, vec![value; 4]);
+    }
+
+    runtime.stop().await.unwrap();
+}
+
+#[test]
+fn run_embedded_runtime() {
+    let _ = env_logger::try_init();
+
+    async_std::task::block_on(embedded_runtime())
+}
+
+async fn embedded_runtime_with_callbacks() {
+    let descriptor = DataFlowDescriptor::from_yaml(FLOW).unwrap();
+
+    let (source_tx, source_rx) = flume::unbounded();
+    let (sink_tx, sink_rx) = flume::unbounded();
+
+    let mut runtime = Runtime::builder()
+        .descriptor(descriptor)
+        .operator("operator", |context, configuration, inputs, outputs| {
+            Box::pin(async {
+                let node = ForwardOperator::new(context, configuration, inputs, outputs).await?;
+                Ok(Arc::new(node) as Arc<dyn Node>)
+            })
+        })
+        .channel_source("host-source", source_rx)
+        .callback_sink("host-sink", move |port, data| {
+            sink_tx.send((port, data)).unwrap();
+        })
+        .build()
+        .await
+        .unwrap();
+
+    assert!(runtime.take_input("host-source").is_none());
+    assert!(runtime.take_output("host-sink").is_none());
+
+    runtime.start().await.unwrap();
+
+    source_tx.send(("data".into(), vec![42u8])).unwrap();
+    let (port, data) = async_std::future::timeout(Duration::from_secs(5), sink_rx.recv_async())
+        .await
+        .expect("The callback of the Host Sink was not invoked")
+        .unwrap();
+    assert_eq!(port.as_ref(), "data");
+    assert_eq!(data, vec![42u8]);
+
+    runtime.stop().await.unwrap();
+}
+
+#[test]
+fn run_embedded_runtime_with_callbacks() {
+    let _ = env_logger::try_init();
+
+    async_std::task::block_on(embedded_runtime_with_callbacks())
+}
+
+#[test]
+fn registered_node_must_be_declared() {
+    let descriptor = DataFlowDescriptor::from_yaml(FLOW).unwrap();
+
+    let result = async_std::task::block_on(
+        Runtime::builder()
+            .descriptor(descriptor)
+            .sink("unknown-sink", |_, _, _| {
+                Box::pin(async { Err::<Arc<dyn Node>, _>(zferror!(ErrorKind::Unimplemented)) })
+            })
+            .build(),
+    );
+
+    assert!(result.is_err());
+
+    // The operator is not a Host node.
+    let descriptor = DataFlowDescriptor::from_yaml(FLOW).unwrap();
+    let result = async_std::task::block_on(
+        Runtime::builder()
+            .descriptor(descriptor)
+            .callback_sink("operator", |_, _| ())
+            .build(),
+    );
+
+    assert!(result.is_err());
+}
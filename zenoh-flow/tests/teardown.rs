@@ -0,0 +1,226 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+
+use zenoh_flow::io::{Inputs, Outputs};
+use zenoh_flow::model::descriptor::{InputDescriptor, OutputDescriptor};
+use zenoh_flow::model::record::{PortRecord, SinkRecord, SourceRecord};
+use zenoh_flow::runtime::dataflow::instance::record_sink::RecordingBackend;
+use zenoh_flow::runtime::dataflow::instance::DataFlowInstance;
+use zenoh_flow::runtime::dataflow::loader::{Loader, LoaderConfig};
+use zenoh_flow::runtime::dataflow::DataFlow;
+use zenoh_flow::runtime::RuntimeContext;
+use zenoh_flow::types::{Configuration, Context};
+use zenoh_flow::{
+    prelude::*, DEFAULT_SHM_ALLOCATION_BACKOFF_NS, DEFAULT_SHM_ELEMENT_SIZE,
+    DEFAULT_SHM_TOTAL_ELEMENTS,
+};
+
+static SOURCE: &str = "teardown-source";
+static SINK: &str = "teardown-sink";
+static PORT: &str = "data";
+
+static CYCLES: usize = 5;
+
+/// The number of nodes that were created and not yet dropped.
+static ALIVE: AtomicUsize = AtomicUsize::new(0);
+
+struct Alive;
+
+impl Alive {
+    fn new() -> Self {
+        ALIVE.fetch_add(1, Ordering::SeqCst);
+        Self
+    }
+}
+
+impl Drop for Alive {
+    fn drop(&mut self) {
+        ALIVE.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+struct TeardownSource {
+    output: OutputRaw,
+    _alive: Alive,
+}
+
+#[async_trait]
+impl Source for TeardownSource {
+    async fn new(
+        _context: Context,
+        _configuration: Option<Configuration>,
+        mut outputs: Outputs,
+    ) -> Result<Self> {
+        Ok(TeardownSource {
+            output: outputs.take(PORT).expect("Missing output").raw(),
+            _alive: Alive::new(),
+        })
+    }
+}
+
+#[async_trait]
+impl Node for TeardownSource {
+    async fn iteration(&self) -> Result<()> {
+        async_std::task::sleep(Duration::from_millis(10)).await;
+        self.output.send(vec![0u8], None).await
+    }
+}
+
+struct TeardownSink {
+    input: InputRaw,
+    _alive: Alive,
+}
+
+#[async_trait]
+impl Sink for TeardownSink {
+    async fn new(
+        _context: Context,
+        _configuration: Option<Configuration>,
+        mut inputs: Inputs,
+    ) -> Result<Self> {
+        Ok(TeardownSink {
+            input: inputs.take(PORT).expect("Missing input").raw(),
+            _alive: Alive::new(),
+        })
+    }
+}
+
+#[async_trait]
+impl Node for TeardownSink {
+    async fn iteration(&self) -> Result<()> {
+        self.input.recv().await.map(|_| ())
+    }
+}
+
+fn data_flow(ctx: &RuntimeContext) -> DataFlow {
+    let mut dataflow = DataFlow::new("teardown", ctx.clone());
+
+    dataflow.add_source(
+        SourceRecord {
+            id: SOURCE.into(),
+            uid: 0,
+            outputs: vec![PortRecord {
+                uid: 0,
+                port_id: PORT.into(),
+                data_type: None,
+            }],
+            uri: None,
+            configuration: None,
+            runtime: ctx.runtime_name.clone(),
+        },
+        |context: Context, configuration: Option<Configuration>, outputs: Outputs| {
+            Box::pin(async {
+                let node = TeardownSource::new(context, configuration, outputs).await?;
+                Ok(Arc::new(node) as Arc<dyn Node>)
+            })
+        },
+    );
+
+    dataflow.add_sink(
+        SinkRecord {
+            id: SINK.into(),
+            uid: 1,
+            inputs: vec![PortRecord {
+                uid: 1,
+                port_id: PORT.into(),
+                data_type: None,
+            }],
+            input_policies: HashMap::new(),
+            uri: None,
+            configuration: None,
+            runtime: ctx.runtime_name.clone(),
+        },
+        |context: Context, configuration: Option<Configuration>, inputs: Inputs| {
+            Box::pin(async {
+                let node = TeardownSink::new(context, configuration, inputs).await?;
+                Ok(Arc::new(node) as Arc<dyn Node>)
+            })
+        },
+    );
+
+    dataflow.add_link(
+        OutputDescriptor {
+            node: SOURCE.into(),
+            output: PORT.into(),
+        },
+        InputDescriptor {
+            node: SINK.into(),
+            input: PORT.into(),
+        },
+    );
+
+    dataflow
+}
+
+// Repeated instantiations and teardowns must not accumulate nodes nor references to the Zenoh
+// session (held by the resources declared on behalf of the instance).
+async fn repeated_teardown() {
+    let session = Arc::new(
+        zenoh::open(zenoh::config::Config::default())
+            .res()
+            .await
+            .unwrap(),
+    );
+    let hlc = Arc::new(uhlc::HLC::default());
+    let rt_uuid = uuid::Uuid::new_v4();
+    let ctx = RuntimeContext {
+        session: session.clone(),
+        hlc: hlc.clone(),
+        loader: Arc::new(Loader::new(LoaderConfig::new())),
+        runtime_name: format!("test-runtime-{rt_uuid}").into(),
+        runtime_uuid: rt_uuid,
+        shared_memory_element_size: DEFAULT_SHM_ELEMENT_SIZE as usize,
+        shared_memory_elements: DEFAULT_SHM_TOTAL_ELEMENTS as usize,
+        shared_memory_backoff: DEFAULT_SHM_ALLOCATION_BACKOFF_NS,
+        use_shm: false,
+        recording_backend: RecordingBackend::default(),
+        zenoh_configs: Arc::default(),
+        gpus: Arc::default(),
+    };
+    let session_references = Arc::strong_count(&session);
+
+    for _ in 0..CYCLES {
+        let mut instance = DataFlowInstance::try_instantiate(data_flow(&ctx), hlc.clone())
+            .await
+            .unwrap();
+        assert_eq!(ALIVE.load(Ordering::SeqCst), 2);
+
+        for id in instance.get_sinks() {
+            instance.start_node(&id).unwrap();
+        }
+        for id in instance.get_sources() {
+            instance.start_node(&id).unwrap();
+        }
+
+        async_std::task::sleep(Duration::from_millis(100)).await;
+
+        instance.stop().await.unwrap();
+        assert_eq!(ALIVE.load(Ordering::SeqCst), 0);
+        assert_eq!(Arc::strong_count(&session), session_references);
+    }
+}
+
+#[test]
+fn run_repeated_teardown() {
+    let _ = env_logger::try_init();
+
+    async_std::task::block_on(repeated_teardown())
+}
@@ -13,6 +13,9 @@
 //
 
 use zenoh_flow::model::descriptor::FlattenDataFlowDescriptor;
+use zenoh_flow::model::registry::{
+    NodeKind, RegistryNode, RegistryNodeArchitecture, RegistryNodeTag,
+};
 use zenoh_flow::prelude::ErrorKind;
 
 static DESCRIPTOR_OK: &str = r#"
@@ -436,3 +439,369 @@ fn validate_ko_node_not_found() {
     let error = ErrorKind::NodeNotFound("SumOperator_typo".into());
     assert_eq!(ErrorKind::from(r.err().unwrap()), error)
 }
+
+static DESCRIPTOR_OK_EXPOSED: &str = r#"
+flow: SimplePipeline
+operators:
+  - id : SumOperator
+    uri: file://./target/release/libsum_and_send.dylib
+    inputs: [Number]
+
+    outputs: [Sum, Sub]
+
+sources:
+  - id : Counter
+    uri: file://./target/release/libcounter_source.dylib
+    outputs: [Counter]
+
+sinks:
+  - id : PrintSink
+    uri: file://./target/release/libgeneric_sink.dylib
+    inputs: [Data]
+
+
+links:
+- from:
+    node : Counter
+    output : Counter
+  to:
+    node : SumOperator
+    input : Number
+- from:
+    node : SumOperator
+    output : Sum
+  to:
+    node : PrintSink
+    input : Data
+
+exposed:
+- node : SumOperator
+  output : Sub
+- node : Counter
+  output : Counter
+"#;
+
+#[test]
+fn validate_ok_exposed() {
+    let _ = env_logger::try_init();
+    let r = FlattenDataFlowDescriptor::from_yaml(DESCRIPTOR_OK_EXPOSED);
+    assert!(r.is_ok());
+}
+
+#[test]
+fn validate_ko_exposed_not_found() {
+    let _ = env_logger::try_init();
+    let r = FlattenDataFlowDescriptor::from_yaml(
+        &DESCRIPTOR_OK_EXPOSED.replace("output : Sub", "output : Sub_typo"),
+    );
+    let error = ErrorKind::PortNotFound(("SumOperator".into(), "Sub_typo".into()));
+    assert_eq!(ErrorKind::from(r.err().unwrap()), error)
+}
+
+static DESCRIPTOR_OK_IMPORTED: &str = r#"
+flow: SimplePipeline
+operators:
+  - id : SumOperator
+    uri: file://./target/release/libsum_and_send.dylib
+    inputs: [Number]
+
+    outputs: [Sum]
+
+sources:
+  - id : Counter
+    uri: file://./target/release/libcounter_source.dylib
+    outputs: [Counter]
+
+sinks:
+  - id : PrintSink
+    uri: file://./target/release/libgeneric_sink.dylib
+    inputs: [Data, Remote]
+
+
+links:
+- from:
+    node : Counter
+    output : Counter
+  to:
+    node : SumOperator
+    input : Number
+- from:
+    node : SumOperator
+    output : Sum
+  to:
+    node : PrintSink
+    input : Data
+
+imported:
+- node : PrintSink
+  input : Remote
+"#;
+
+#[test]
+fn validate_ok_imported() {
+    let _ = env_logger::try_init();
+    let r = FlattenDataFlowDescriptor::from_yaml(DESCRIPTOR_OK_IMPORTED);
+    assert!(r.is_ok());
+
+    let r = FlattenDataFlowDescriptor::from_yaml(
+        &DESCRIPTOR_OK_IMPORTED.replace("input : Remote", "input : Remote_typo"),
+    );
+    let error = ErrorKind::PortNotFound(("PrintSink".into(), "Remote_typo".into()));
+    assert_eq!(ErrorKind::from(r.err().unwrap()), error)
+}
+
+#[test]
+fn validate_ko_port_typo_suggestion() {
+    let _ = env_logger::try_init();
+    let r = FlattenDataFlowDescriptor::from_yaml(
+        &DESCRIPTOR_OK.replace("input : Number", "input : Numbr"),
+    );
+    let error = r.err().unwrap();
+    assert!(error.to_string().contains("did you mean < Number >?"));
+    assert_eq!(
+        ErrorKind::from(error),
+        ErrorKind::PortNotFound(("SumOperator".into(), "Numbr".into()))
+    )
+}
+
+fn registry_node(uri: &str, inputs: &[&str], outputs: &[&str]) -> RegistryNode {
+    RegistryNode {
+        id: "sum-and-send".into(),
+        kind: NodeKind::Operator,
+        classes: vec![],
+        tags: vec![RegistryNodeTag {
+            name: "latest".into(),
+            requirement_labels: vec![],
+            architectures: vec![RegistryNodeArchitecture {
+                arch: std::env::consts::ARCH.into(),
+                os: std::env::consts::OS.into(),
+                uri: uri.into(),
+                checksum: String::default(),
+                signature: String::default(),
+            }],
+        }],
+        inputs: inputs.iter().map(|&port| port.into()).collect(),
+        outputs: outputs.iter().map(|&port| port.into()).collect(),
+    }
+}
+
+#[test]
+fn validate_with_registry() {
+    let _ = env_logger::try_init();
+    let descriptor = FlattenDataFlowDescriptor::from_yaml(DESCRIPTOR_OK).unwrap();
+    let uri = "file://./target/release/libsum_and_send.dylib";
+
+    let registry = vec![registry_node(uri, &["Number"], &["Sum"])];
+    assert!(descriptor.validate_with_registry(&registry).is_ok());
+
+    // Components that are not used by the data flow are ignored.
+    let registry = vec![registry_node("file://./libother.so", &["Frame"], &[])];
+    assert!(descriptor.validate_with_registry(&registry).is_ok());
+
+    let registry = vec![registry_node(uri, &["number"], &["Sum"])];
+    let error = descriptor.validate_with_registry(&registry).err().unwrap();
+    assert!(error.to_string().contains("did you mean < number >?"));
+    assert_eq!(
+        ErrorKind::from(error),
+        ErrorKind::PortNotFound(("SumOperator".into(), "Number".into()))
+    );
+
+    let registry = vec![registry_node(uri, &["Number"], &["Sum", "Average"])];
+    let error = descriptor.validate_with_registry(&registry).err().unwrap();
+    assert_eq!(
+        ErrorKind::from(error),
+        ErrorKind::PortNotFound(("SumOperator".into(), "Average".into()))
+    );
+}
+
+#[test]
+fn validate_input_policies() {
+    let _ = env_logger::try_init();
+    let descriptor = DESCRIPTOR_OK.replace(
+        "inputs: [Number]\n",
+        "inputs: [Number]\n    input_policies:\n      Number:\n        required: false\n        token: keep\n",
+    );
+    let r = FlattenDataFlowDescriptor::from_yaml(&descriptor);
+    assert!(r.is_ok());
+
+    let error =
+        FlattenDataFlowDescriptor::from_yaml(&descriptor.replace("      Number:", "      Numbr:"))
+            .err()
+            .unwrap();
+    assert!(error.to_string().contains("did you mean < Number >?"));
+    assert_eq!(
+        ErrorKind::from(error),
+        ErrorKind::PortNotFound(("SumOperator".into(), "Numbr".into()))
+    );
+}
+
+#[test]
+fn validate_data_types() {
+    let _ = env_logger::try_init();
+    let descriptor = DESCRIPTOR_OK
+        .replace(
+            "outputs: [Counter]\n",
+            "outputs: [Counter]\n    output_types:\n      Counter: my.company.Number@1.2\n",
+        )
+        .replace(
+            "inputs: [Number]\n",
+            "inputs: [Number]\n    input_types:\n      Number: my.company.Number@1.0\n",
+        );
+    // The minor versions differ, the input of the Sink has no data type.
+    let r = FlattenDataFlowDescriptor::from_yaml(&descriptor);
+    assert!(r.is_ok());
+
+    let error = FlattenDataFlowDescriptor::from_yaml(&descriptor.replace("@1.0", "@2.0"))
+        .err()
+        .unwrap();
+    assert!(error
+        .to_string()
+        .contains("sends < my.company.Number@1.2 > but input < SumOperator.Number > expects < my.company.Number@2.0 >"));
+    assert_eq!(
+        ErrorKind::from(error),
+        ErrorKind::IncompatibleDataTypes((
+            ("Counter".into(), "Counter".into()),
+            ("SumOperator".into(), "Number".into())
+        ))
+    );
+
+    let error = FlattenDataFlowDescriptor::from_yaml(
+        &descriptor.replace("my.company.Number@1.0", "my.company.Integer@1.2"),
+    )
+    .err()
+    .unwrap();
+    assert!(matches!(
+        ErrorKind::from(error),
+        ErrorKind::IncompatibleDataTypes(_)
+    ));
+
+    let error = FlattenDataFlowDescriptor::from_yaml(
+        &descriptor.replace("      Number: my", "      Numbr: my"),
+    )
+    .err()
+    .unwrap();
+    assert_eq!(
+        ErrorKind::from(error),
+        ErrorKind::PortNotFound(("SumOperator".into(), "Numbr".into()))
+    );
+
+    assert!(FlattenDataFlowDescriptor::from_yaml(&descriptor.replace("@1.0", "")).is_err());
+}
+
+#[test]
+fn validate_union_data_types() {
+    let _ = env_logger::try_init();
+    let descriptor = DESCRIPTOR_OK
+        .replace(
+            "outputs: [Counter]\n",
+            "outputs: [Counter]\n    output_types:\n      Counter: [my.company.Number@1.2, my.company.Text@1.0]\n",
+        )
+        .replace(
+            "inputs: [Number]\n",
+            "inputs: [Number]\n    input_types:\n      Number: [my.company.Number@1.0, my.company.Text@1.1]\n",
+        );
+    // The variants are compatible, in order.
+    assert!(FlattenDataFlowDescriptor::from_yaml(&descriptor).is_ok());
+
+    let error = FlattenDataFlowDescriptor::from_yaml(&descriptor.replace(
+        "[my.company.Number@1.0, my.company.Text@1.1]",
+        "[my.company.Text@1.1, my.company.Number@1.0]",
+    ))
+    .err()
+    .unwrap();
+    assert!(error.to_string().contains(
+        "sends < [my.company.Number@1.2|my.company.Text@1.0] > but input < SumOperator.Number > expects < [my.company.Text@1.1|my.company.Number@1.0] >"
+    ));
+    assert!(matches!(
+        ErrorKind::from(error),
+        ErrorKind::IncompatibleDataTypes(_)
+    ));
+
+    // A union is not compatible with one of its variants.
+    let error = FlattenDataFlowDescriptor::from_yaml(&descriptor.replace(
+        "[my.company.Number@1.0, my.company.Text@1.1]",
+        "my.company.Number@1.0",
+    ))
+    .err()
+    .unwrap();
+    assert!(matches!(
+        ErrorKind::from(error),
+        ErrorKind::IncompatibleDataTypes(_)
+    ));
+
+    assert!(FlattenDataFlowDescriptor::from_yaml(
+        &descriptor.replace("[my.company.Number@1.0, my.company.Text@1.1]", "[]",)
+    )
+    .is_err());
+}
+
+#[test]
+fn validate_units() {
+    let _ = env_logger::try_init();
+    let descriptor = DESCRIPTOR_OK
+        .replace(
+            "outputs: [Counter]\n",
+            "outputs: [Counter]\n    output_units:\n      Counter: km/h\n",
+        )
+        .replace(
+            "inputs: [Number]\n",
+            "inputs: [Number]\n    input_units:\n      Number: km/h\n",
+        );
+    // The input of the Sink has no unit.
+    assert!(FlattenDataFlowDescriptor::from_yaml(&descriptor).is_ok());
+
+    // The conversion is only inserted when a data flow descriptor is flattened.
+    let error = FlattenDataFlowDescriptor::from_yaml(
+        &descriptor.replace("      Number: km/h", "      Number: m/s"),
+    )
+    .err()
+    .unwrap();
+    assert!(error
+        .to_string()
+        .contains("sends values in < km/h > but input < SumOperator.Number > expects < m/s >"));
+    assert_eq!(
+        ErrorKind::from(error),
+        ErrorKind::IncompatibleUnits((
+            ("Counter".into(), "Counter".into()),
+            ("SumOperator".into(), "Number".into())
+        ))
+    );
+
+    let error = FlattenDataFlowDescriptor::from_yaml(
+        &descriptor.replace("      Number: km/h", "      Number: deg"),
+    )
+    .err()
+    .unwrap();
+    assert!(error
+        .to_string()
+        .contains("no conversion is known between them"));
+
+    let error = FlattenDataFlowDescriptor::from_yaml(
+        &descriptor.replace("      Number: km/h", "      Numbr: km/h"),
+    )
+    .err()
+    .unwrap();
+    assert_eq!(
+        ErrorKind::from(error),
+        ErrorKind::PortNotFound(("SumOperator".into(), "Numbr".into()))
+    );
+}
+
+#[test]
+fn validate_ko_empty_queue() {
+    let _ = env_logger::try_init();
+    let descriptor = DESCRIPTOR_OK.replace(
+        "    input : Number\n",
+        "    input : Number\n  queue:\n    capacity: 0\n",
+    );
+    let error = FlattenDataFlowDescriptor::from_yaml(&descriptor)
+        .err()
+        .unwrap();
+    assert!(error
+        .to_string()
+        .contains("The capacity of a queue must be at least 1"));
+    assert_eq!(ErrorKind::from(error), ErrorKind::ConfigurationError);
+
+    let descriptor = descriptor.replace("capacity: 0", "capacity: 1");
+    assert!(FlattenDataFlowDescriptor::from_yaml(&descriptor).is_ok());
+}
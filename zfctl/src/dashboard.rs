@@ -0,0 +1,420 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! A minimal web dashboard: a single page, rendering the physical graph of the instances, the
+//! health of their nodes and the throughput of their links, backed by a small JSON API.
+//!
+//! | Method | Path                                  | Response                                   |
+//! |--------|---------------------------------------|--------------------------------------------|
+//! | GET    | `/`                                   | The page.                                  |
+//! | GET    | `/api/instances`                      | The uuid and flow of all the instances.    |
+//! | GET    | `/api/instances/<uuid>`               | The physical graph, the health of the      |
+//! |        |                                       | nodes and daemons, the messages sent.      |
+//! | POST   | `/api/instances/<uuid>/start`         | Starts the instance.                       |
+//! | POST   | `/api/instances/<uuid>/stop`          | Stops the instance.                        |
+//! | POST   | `/api/instances/<uuid>/record`        | Starts recording all its outputs.          |
+//! | POST   | `/api/instances/<uuid>/stop-recording`| Stops recording its outputs.               |
+//!
+//! The dashboard listens on the loopback interface unless told otherwise. All the requests must be
+//! addressed (their `Host` header) to the address it listens on, or to `localhost`, with its port:
+//! a site whose name resolves to the dashboard (DNS rebinding) is thus not served. The `POST`
+//! requests change the state of the instances: they must carry the token generated when the
+//! dashboard starts, embedded in the page, in the `X-Zenoh-Flow-Token` header and, if they have an
+//! `Origin`, come from the dashboard itself. Another site opened in the same browser can thus not
+//! control the instances.
+
+use async_std::io::BufReader;
+use async_std::net::{TcpListener, TcpStream};
+use async_std::prelude::*;
+use serde_json::json;
+use std::collections::HashMap;
+use std::error::Error;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+use zenoh::prelude::r#async::*;
+use zenoh::Session;
+use zenoh_flow::runtime::dataflow::instance::events::{InstanceEvent, InstanceEventKind};
+use zenoh_flow::runtime::dataflow::physical::PhysicalGraph;
+use zenoh_flow::runtime::resources::{DataStore, ROOT_STANDALONE};
+use zenoh_flow::types::NodeId;
+use zenoh_flow::LIFECYCLE_PATH;
+
+type Result<T> = std::result::Result<T, Box<dyn Error + Send + Sync + 'static>>;
+
+const PAGE: &str = include_str!("dashboard.html");
+
+/// The placeholder, in the page, replaced by the token.
+const TOKEN_PLACEHOLDER: &str = "{{token}}";
+
+/// The header carrying the token of the `POST` requests.
+const TOKEN_HEADER: &str = "x-zenoh-flow-token";
+
+/// The last lifecycle event of each node, per instance.
+type Health = Arc<Mutex<HashMap<(Uuid, NodeId), InstanceEvent>>>;
+
+struct Response {
+    status: &'static str,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn json(value: serde_json::Value) -> Self {
+        Self {
+            status: "200 OK",
+            content_type: "application/json",
+            body: value.to_string(),
+        }
+    }
+
+    fn error(status: &'static str, message: impl ToString) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: json!({ "error": message.to_string() }).to_string(),
+        }
+    }
+}
+
+/// A request: its method, its path and its headers, whose names are lowercase.
+struct Request {
+    method: String,
+    path: String,
+    headers: HashMap<String, String>,
+}
+
+impl Request {
+    /// Returns `true` if the request is addressed to one of the `hosts`.
+    fn is_addressed_to(&self, hosts: &[String]) -> bool {
+        self.headers
+            .get("host")
+            .map_or(false, |host| hosts.contains(&host.to_lowercase()))
+    }
+
+    /// Returns `true` if the request carries the `token` and, if it has an `Origin`, was sent by a
+    /// page served by the dashboard.
+    fn is_authorized(&self, token: &str) -> bool {
+        let origin_matches = match (self.headers.get("origin"), self.headers.get("host")) {
+            (None, _) => true,
+            (Some(origin), Some(host)) => {
+                origin.strip_prefix("http://").map_or(false, |o| o == host)
+            }
+            (Some(_), None) => false,
+        };
+
+        origin_matches
+            && self
+                .headers
+                .get(TOKEN_HEADER)
+                .map_or(false, |t| constant_time_eq(t.as_bytes(), token.as_bytes()))
+    }
+}
+
+/// Compares `a` and `b` in a time that does not depend on where they differ, not to leak the token.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Returns the values of the `Host` header of the requests addressed to the dashboard listening on
+/// `<bind>:<port>`: its address, `localhost` and the loopback addresses, with the port (optional
+/// for the port 80).
+fn allowed_hosts(bind: IpAddr, port: u16) -> Vec<String> {
+    let addresses = [
+        bind,
+        IpAddr::V4(Ipv4Addr::LOCALHOST),
+        IpAddr::V6(Ipv6Addr::LOCALHOST),
+    ];
+    let names = addresses
+        .iter()
+        .map(|address| match address {
+            IpAddr::V4(address) => address.to_string(),
+            IpAddr::V6(address) => format!("[{address}]"),
+        })
+        .chain(std::iter::once("localhost".to_string()));
+
+    let mut hosts = Vec::new();
+    for name in names {
+        if port == 80 {
+            hosts.push(name.clone());
+        }
+        hosts.push(format!("{name}:{port}"));
+    }
+    hosts
+}
+
+/// Serves the dashboard on `<bind>:<port>` until the process is stopped.
+pub(crate) async fn serve(
+    bind: IpAddr,
+    port: u16,
+    session: Arc<Session>,
+    store: DataStore,
+) -> Result<()> {
+    let listener = TcpListener::bind((bind, port)).await?;
+    let token = Uuid::new_v4().simple().to_string();
+    let hosts = Arc::new(allowed_hosts(bind, port));
+    let health = track_health(session.clone()).await?;
+    println!("Dashboard available on http://{bind}:{port}");
+
+    let mut incoming = listener.incoming();
+    while let Some(stream) = incoming.next().await {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                log::warn!("[Dashboard] Failed to accept a connection: {e:?}");
+                continue;
+            }
+        };
+
+        let session = session.clone();
+        let store = store.clone();
+        let health = health.clone();
+        let token = token.clone();
+        let hosts = hosts.clone();
+        async_std::task::spawn(async move {
+            if let Err(e) = handle(stream, session, store, health, &token, &hosts).await {
+                log::warn!("[Dashboard] Failed to handle a request: {e:?}");
+            }
+        });
+    }
+
+    Ok(())
+}
+
+/// Keeps, for each node of each instance, its last lifecycle event: the ones stored in Zenoh, if
+/// any, and the ones published from now on.
+async fn track_health(session: Arc<Session>) -> Result<Health> {
+    let health: Health = Arc::default();
+    let key_expr = LIFECYCLE_PATH!(ROOT_STANDALONE, "*", "*");
+    let subscriber = session.declare_subscriber(&key_expr).res().await?;
+
+    let update = |health: &Health, sample: &Sample| match serde_json::from_slice::<InstanceEvent>(
+        &sample.payload.contiguous(),
+    ) {
+        Ok(event) => {
+            if matches!(
+                event.kind,
+                InstanceEventKind::NodeStarted
+                    | InstanceEventKind::NodeStopped
+                    | InstanceEventKind::NodeErrored { .. }
+            ) {
+                let mut health = health.lock().unwrap_or_else(|e| e.into_inner());
+                let key = (event.instance_id, event.node.clone());
+                match health.get(&key) {
+                    Some(last) if last.timestamp > event.timestamp => (),
+                    _ => {
+                        health.insert(key, event);
+                    }
+                }
+            }
+        }
+        Err(e) => log::warn!("[Dashboard] Invalid lifecycle event: {e:?}"),
+    };
+
+    let replies = session.get(&key_expr).res().await?;
+    while let Ok(reply) = replies.recv_async().await {
+        if let Ok(sample) = reply.sample {
+            update(&health, &sample);
+        }
+    }
+
+    let tracked = health.clone();
+    async_std::task::spawn(async move {
+        while let Ok(sample) = subscriber.recv_async().await {
+            update(&tracked, &sample);
+        }
+    });
+
+    Ok(health)
+}
+
+async fn handle(
+    stream: TcpStream,
+    session: Arc<Session>,
+    store: DataStore,
+    health: Health,
+    token: &str,
+    hosts: &[String],
+) -> Result<()> {
+    let mut reader = BufReader::new(stream.clone());
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+    // No request has a body: only the headers are read.
+    let mut headers = HashMap::new();
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).await? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let request = Request {
+        method: parts.next().unwrap_or("").to_string(),
+        path: parts.next().unwrap_or("").to_string(),
+        headers,
+    };
+    let response = if request.is_addressed_to(hosts) {
+        route(&request, session, store, health, token).await
+    } else {
+        Response::error("403 Forbidden", "Unknown host")
+    };
+
+    let mut stream = stream;
+    stream
+        .write_all(
+            format!(
+                "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                response.status,
+                response.content_type,
+                response.body.len(),
+                response.body
+            )
+            .as_bytes(),
+        )
+        .await?;
+    stream.flush().await?;
+    Ok(())
+}
+
+async fn route(
+    request: &Request,
+    session: Arc<Session>,
+    store: DataStore,
+    health: Health,
+    token: &str,
+) -> Response {
+    let path = request.path.as_str();
+    let segments = path
+        .trim_matches('/')
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>();
+
+    let result = match (request.method.as_str(), segments.as_slice()) {
+        ("GET", []) => {
+            return Response {
+                status: "200 OK",
+                content_type: "text/html; charset=utf-8",
+                body: PAGE.replace(TOKEN_PLACEHOLDER, token),
+            }
+        }
+        ("GET", ["api", "instances"]) => instances(&store).await,
+        ("GET", ["api", "instances", id]) => match id.parse::<Uuid>() {
+            Ok(id) => instance(session, &store, &health, &id).await,
+            Err(e) => return Response::error("400 Bad Request", e),
+        },
+        ("POST", ["api", "instances", id, action]) => {
+            if !request.is_authorized(token) {
+                return Response::error("403 Forbidden", "Missing or invalid token");
+            }
+            let id = match id.parse::<Uuid>() {
+                Ok(id) => id,
+                Err(e) => return Response::error("400 Bad Request", e),
+            };
+            let client = crate::get_client(session).await;
+            match *action {
+                "start" => client
+                    .start_instance(id)
+                    .await
+                    .map_err(|e| format!("{e:?}"))
+                    .and_then(|result| result.map_err(|e| format!("{e:?}")))
+                    .map(|_| json!({ "started": id }))
+                    .map_err(Into::into),
+                "stop" => client
+                    .stop_instance(id)
+                    .await
+                    .map_err(|e| format!("{e:?}"))
+                    .and_then(|result| result.map_err(|e| format!("{e:?}")))
+                    .map(|_| json!({ "stopped": id }))
+                    .map_err(Into::into),
+                "record" | "stop-recording" => client
+                    .record(id, *action == "record")
+                    .await
+                    .map_err(|e| format!("{e:?}"))
+                    .and_then(|result| result.map_err(|e| format!("{e:?}")))
+                    .map(|manifests| json!({ "recordings": manifests }))
+                    .map_err(Into::into),
+                _ => return Response::error("404 Not Found", path),
+            }
+        }
+        _ => return Response::error("404 Not Found", path),
+    };
+
+    match result {
+        Ok(value) => Response::json(value),
+        Err(e) => Response::error("500 Internal Server Error", e),
+    }
+}
+
+async fn instances(store: &DataStore) -> Result<serde_json::Value> {
+    let mut instances = store
+        .get_all_instances()
+        .await?
+        .into_iter()
+        .map(|record| (record.uuid, record.flow))
+        .collect::<Vec<_>>();
+    instances.sort();
+    instances.dedup();
+
+    Ok(serde_json::Value::Array(
+        instances
+            .into_iter()
+            .map(|(uuid, flow)| json!({ "uuid": uuid, "flow": flow }))
+            .collect(),
+    ))
+}
+
+async fn instance(
+    session: Arc<Session>,
+    store: &DataStore,
+    health: &Health,
+    id: &Uuid,
+) -> Result<serde_json::Value> {
+    let graph = PhysicalGraph::from(&store.get_flow_by_instance(id).await?);
+    let runtimes = store
+        .get_all_runtime_info()
+        .await?
+        .into_iter()
+        .map(|info| (info.name.to_string(), format!("{:?}", info.status)))
+        .collect::<HashMap<_, _>>();
+    let nodes = health
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .filter(|((instance_id, _), _)| instance_id == id)
+        .map(|((_, node), event)| (node.to_string(), json!(event.kind)))
+        .collect::<serde_json::Map<_, _>>();
+    // A stopped instance has no counters: its links have no throughput.
+    let sent = match crate::get_client(session)
+        .await
+        .get_sent_messages(*id)
+        .await
+    {
+        Ok(Ok(sent)) => sent
+            .into_iter()
+            .map(|(output, count)| (format!("{}.{}", output.node, output.output), json!(count)))
+            .collect::<serde_json::Map<_, _>>(),
+        Ok(Err(e)) => {
+            log::debug!("[Dashboard] No messages counted for < {id} >: {e:?}");
+            serde_json::Map::new()
+        }
+        Err(e) => return Err(format!("{e:?}").into()),
+    };
+
+    Ok(json!({ "graph": graph, "runtimes": runtimes, "nodes": nodes, "sent": sent }))
+}
@@ -22,6 +22,8 @@ extern crate prettytable;
 extern crate base64;
 extern crate exitfailure;
 
+mod dashboard;
+
 use clap::{Parser, Subcommand};
 use git_version::git_version;
 use prettytable::Table;
@@ -32,7 +34,10 @@ use std::fs::read_to_string;
 use std::sync::Arc;
 use uuid::Uuid;
 use zenoh::prelude::r#async::*;
-use zenoh_flow::runtime::resources::DataStore;
+use zenoh_flow::model::descriptor::{
+    InputDescriptor, OutputDescriptor, ParsingMode, WcetDescriptor,
+};
+use zenoh_flow::runtime::resources::{DataStore, ROOT_STANDALONE};
 use zenoh_flow::runtime::DaemonInterfaceClient;
 
 const GIT_VERSION: &str = git_version!(prefix = "v", cargo_prefix = "v");
@@ -69,6 +74,8 @@ pub enum CreateKind {
             help = "Creates a new instance for the given flow"
         )]
         descriptor_path: std::path::PathBuf,
+        #[clap(long, help = "Rejects the descriptor if it contains unknown fields")]
+        strict: bool,
     },
 }
 
@@ -208,6 +215,16 @@ pub enum ListKind {
     Instances,
     #[clap(about = "Lists the runtimes")]
     Runtimes,
+    #[clap(about = "Lists the management operations performed by the runtimes")]
+    Events {
+        #[clap(
+            short,
+            long,
+            name = "instance uuid",
+            help = "Only lists the operations on this instance"
+        )]
+        instance_id: Option<Uuid>,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -225,6 +242,76 @@ pub enum DeleteKind {
     },
 }
 
+#[derive(Subcommand, Debug)]
+#[clap(about = "Restarts entities in Zenoh Flow")]
+pub enum RestartKind {
+    #[clap(about = "Restarts the given node in the given instance")]
+    Node {
+        #[clap(
+            short,
+            long,
+            name = "instance uuid",
+            help = "The instance containing the node"
+        )]
+        instance_id: Uuid,
+        #[clap(short, long, name = "node id", help = "The node identifier")]
+        node_id: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+#[clap(about = "Debugs nodes in Zenoh Flow")]
+pub enum DebugKind {
+    #[clap(
+        about = "Sets a breakpoint on the given input (all inputs if omitted) of the given node and prints the messages it is paused on"
+    )]
+    Break {
+        #[clap(
+            short,
+            long,
+            name = "instance uuid",
+            help = "The instance containing the node"
+        )]
+        instance_id: Uuid,
+        #[clap(short, long, name = "node id", help = "The node identifier")]
+        node_id: String,
+        #[clap(short, long, name = "port id", help = "The input identifier")]
+        port_id: Option<String>,
+    },
+    #[clap(about = "Removes the breakpoint on the given input (all inputs if omitted)")]
+    Clear {
+        #[clap(
+            short,
+            long,
+            name = "instance uuid",
+            help = "The instance containing the node"
+        )]
+        instance_id: Uuid,
+        #[clap(short, long, name = "node id", help = "The node identifier")]
+        node_id: String,
+        #[clap(short, long, name = "port id", help = "The input identifier")]
+        port_id: Option<String>,
+    },
+    #[clap(about = "Resumes the given node, paused on a breakpoint")]
+    Continue {
+        #[clap(
+            short,
+            long,
+            name = "instance uuid",
+            help = "The instance containing the node"
+        )]
+        instance_id: Uuid,
+        #[clap(short, long, name = "node id", help = "The node identifier")]
+        node_id: String,
+        #[clap(
+            short,
+            long,
+            help = "Pauses again on the next message received by the node"
+        )]
+        step: bool,
+    },
+}
+
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
 
@@ -241,16 +328,100 @@ pub enum ZFCtl {
     Start(StartKind),
     #[clap(subcommand)]
     Stop(StopKind),
+    #[clap(subcommand)]
+    Restart(RestartKind),
+    #[clap(subcommand)]
+    Debug(DebugKind),
     #[clap(about = "Creates and starts a flow instance")]
     Launch {
         #[clap(name = "Flow descriptor path", help = "Flow to be started")]
         descriptor_path: std::path::PathBuf,
+        #[clap(long, help = "Rejects the descriptor if it contains unknown fields")]
+        strict: bool,
+    },
+    #[clap(
+        about = "Predicts, from the timing characteristics of a flow, whether its nodes can keep up and its deadlines can be met"
+    )]
+    Analyze {
+        #[clap(name = "Flow descriptor path", help = "Flow to be analyzed")]
+        descriptor_path: std::path::PathBuf,
+        #[clap(
+            long,
+            help = "A YAML list of the measured worst-case execution times of the nodes"
+        )]
+        profile: Option<std::path::PathBuf>,
+        #[clap(long, help = "Rejects the descriptor if it contains unknown fields")]
+        strict: bool,
     },
     #[clap(about = "Stops and deletes a flow instance")]
     Destroy {
         #[clap(name = "instance uuid", help = "The instance to be destroyed")]
         id: Uuid,
     },
+    #[clap(about = "Prints the messages sent on the given output of the given node")]
+    Tap {
+        #[clap(
+            short,
+            long,
+            name = "instance uuid",
+            help = "The instance containing the node"
+        )]
+        instance_id: Uuid,
+        #[clap(short, long, name = "node id", help = "The node identifier")]
+        node_id: String,
+        #[clap(short, long, name = "port id", help = "The output identifier")]
+        port_id: String,
+    },
+    #[clap(about = "Serves a web dashboard rendering the instances and controlling them")]
+    Dashboard {
+        #[clap(short, long, default_value_t = 8080, help = "The port to listen on")]
+        port: u16,
+        #[clap(
+            short,
+            long,
+            default_value = "127.0.0.1",
+            help = "The address to listen on, the loopback interface by default"
+        )]
+        bind: std::net::IpAddr,
+    },
+    #[clap(about = "Detaches the tap from the given output of the given node")]
+    Untap {
+        #[clap(
+            short,
+            long,
+            name = "instance uuid",
+            help = "The instance containing the node"
+        )]
+        instance_id: Uuid,
+        #[clap(short, long, name = "node id", help = "The node identifier")]
+        node_id: String,
+        #[clap(short, long, name = "port id", help = "The output identifier")]
+        port_id: String,
+    },
+    #[clap(about = "Connects an exposed output of a flow to an imported input of another flow")]
+    Compose {
+        #[clap(long, help = "The flow exposing the output")]
+        from_flow: String,
+        #[clap(long, help = "The node of the exposed output")]
+        from_node: String,
+        #[clap(long, help = "The exposed output")]
+        from_port: String,
+        #[clap(long, help = "The flow importing the input")]
+        to_flow: String,
+        #[clap(long, help = "The node of the imported input")]
+        to_node: String,
+        #[clap(long, help = "The imported input")]
+        to_port: String,
+    },
+    #[clap(about = "Disconnects an imported input of a flow from the output it is connected to")]
+    Decompose {
+        #[clap(long, help = "The flow importing the input")]
+        to_flow: String,
+        #[clap(long, help = "The node of the imported input")]
+        to_node: String,
+        #[clap(long, help = "The imported input")]
+        to_port: String,
+    },
 }
 
 #[async_std::main]
@@ -270,16 +441,23 @@ async fn main() {
             CreateKind::Flow { descriptor_path } => {
                 println!("This is going to store the flow described in {descriptor_path:?}");
             }
-            CreateKind::Instance { descriptor_path } => {
+            CreateKind::Instance {
+                descriptor_path,
+                strict,
+            } => {
                 log::trace!(
                     "This is going to store the flow described in {:?}",
                     descriptor_path
                 );
                 let yaml_df = read_to_string(descriptor_path).unwrap();
-                let df =
-                    zenoh_flow::model::descriptor::DataFlowDescriptor::from_yaml(&yaml_df).unwrap();
+                let df = zenoh_flow::model::descriptor::DataFlowDescriptor::from_yaml_with_mode(
+                    &yaml_df,
+                    parsing_mode(strict),
+                )
+                .unwrap();
                 let df = df.flatten().await.unwrap();
-                df.validate().unwrap();
+                let registry = store.get_all_graphs().await.unwrap_or_default();
+                df.validate_with_registry(&registry).unwrap();
 
                 let client = get_client(zsession.clone()).await;
                 let instance_uuid = client.create_instance(df).await.unwrap().unwrap();
@@ -501,6 +679,137 @@ async fn main() {
                 println!("{}", record.uuid);
             }
         },
+        ZFCtl::Restart(rk) => match rk {
+            RestartKind::Node {
+                instance_id,
+                node_id,
+            } => {
+                let mut table = Table::new();
+                let client = get_client(zsession.clone()).await;
+                table.add_row(row!["UUID", "Name", "Status",]);
+                client
+                    .restart_node(instance_id, node_id.clone())
+                    .await
+                    .unwrap()
+                    .unwrap();
+                table.add_row(row![instance_id, node_id, String::from("Running"),]);
+                table.printstd();
+            }
+        },
+        ZFCtl::Debug(dk) => match dk {
+            DebugKind::Break {
+                instance_id,
+                node_id,
+                port_id,
+            } => {
+                let client = get_client(zsession.clone()).await;
+                client
+                    .breakpoint(instance_id, node_id.clone(), port_id, true)
+                    .await
+                    .unwrap()
+                    .unwrap();
+
+                let key_expr = zenoh_flow::DEBUG_PATH!(ROOT_STANDALONE, instance_id, node_id);
+                let subscriber = zsession.declare_subscriber(&key_expr).res().await.unwrap();
+                while let Ok(sample) = subscriber.recv_async().await {
+                    println!("{}", String::from_utf8_lossy(&sample.payload.contiguous()));
+                }
+            }
+            DebugKind::Clear {
+                instance_id,
+                node_id,
+                port_id,
+            } => {
+                let client = get_client(zsession.clone()).await;
+                client
+                    .breakpoint(instance_id, node_id, port_id, false)
+                    .await
+                    .unwrap()
+                    .unwrap();
+            }
+            DebugKind::Continue {
+                instance_id,
+                node_id,
+                step,
+            } => {
+                let client = get_client(zsession.clone()).await;
+                client
+                    .resume(instance_id, node_id, step)
+                    .await
+                    .unwrap()
+                    .unwrap();
+            }
+        },
+        ZFCtl::Tap {
+            instance_id,
+            node_id,
+            port_id,
+        } => {
+            let client = get_client(zsession.clone()).await;
+            let key_expr = client
+                .tap(instance_id, node_id, port_id, true)
+                .await
+                .unwrap()
+                .unwrap();
+            log::debug!("Tapping: {:?}", key_expr);
+
+            let subscriber = zsession.declare_subscriber(&key_expr).res().await.unwrap();
+            while let Ok(sample) = subscriber.recv_async().await {
+                println!("{}", String::from_utf8_lossy(&sample.payload.contiguous()));
+            }
+        }
+        ZFCtl::Untap {
+            instance_id,
+            node_id,
+            port_id,
+        } => {
+            let client = get_client(zsession.clone()).await;
+            let detached = client
+                .untap(instance_id, node_id, port_id)
+                .await
+                .unwrap()
+                .unwrap();
+            println!("{detached}");
+        }
+        ZFCtl::Compose {
+            from_flow,
+            from_node,
+            from_port,
+            to_flow,
+            to_node,
+            to_port,
+        } => {
+            let client = get_client(zsession.clone()).await;
+            let key_expr = client
+                .compose(
+                    from_flow,
+                    OutputDescriptor::new(from_node, from_port),
+                    to_flow,
+                    InputDescriptor::new(to_node, to_port),
+                )
+                .await
+                .unwrap()
+                .unwrap();
+            println!("{key_expr}");
+        }
+        ZFCtl::Decompose {
+            to_flow,
+            to_node,
+            to_port,
+        } => {
+            let client = get_client(zsession.clone()).await;
+            let key_expr = client
+                .decompose(to_flow, InputDescriptor::new(to_node, to_port))
+                .await
+                .unwrap()
+                .unwrap();
+            println!("{key_expr:?}");
+        }
+        ZFCtl::Dashboard { port, bind } => {
+            dashboard::serve(bind, port, zsession.clone(), store)
+                .await
+                .unwrap();
+        }
         ZFCtl::List(lk) => {
             let mut table = Table::new();
             match lk {
@@ -531,6 +840,30 @@ async fn main() {
                         ]);
                     }
                 }
+                ListKind::Events { instance_id } => {
+                    table.add_row(row![
+                        "Timestamp",
+                        "Actor",
+                        "Action",
+                        "Instance",
+                        "Node",
+                        "Result",
+                    ]);
+                    let events = store.get_events().await.unwrap();
+                    for event in events
+                        .iter()
+                        .filter(|event| instance_id.map_or(true, |id| event.instance_id == id))
+                    {
+                        table.add_row(row![
+                            event.timestamp,
+                            event.actor,
+                            format!("{:?}", event.action),
+                            event.instance_id,
+                            event.node.as_deref().unwrap_or(""),
+                            format!("{:?}", event.result),
+                        ]);
+                    }
+                }
                 ListKind::Runtimes => {
                     table.add_row(row!["UUID", "Name", "Status",]);
                     let runtimes = store.get_all_runtime_info().await.unwrap();
@@ -541,22 +874,115 @@ async fn main() {
             };
             table.printstd();
         }
-        ZFCtl::Launch { descriptor_path } => {
+        ZFCtl::Launch {
+            descriptor_path,
+            strict,
+        } => {
             log::debug!(
                 "This is going to launch the flow described in {:?}",
                 descriptor_path
             );
             let yaml_df = read_to_string(descriptor_path).unwrap();
-            let df =
-                zenoh_flow::model::descriptor::DataFlowDescriptor::from_yaml(&yaml_df).unwrap();
+            let df = zenoh_flow::model::descriptor::DataFlowDescriptor::from_yaml_with_mode(
+                &yaml_df,
+                parsing_mode(strict),
+            )
+            .unwrap();
             let df = df.flatten().await.unwrap();
-            df.validate().unwrap();
+            let registry = store.get_all_graphs().await.unwrap_or_default();
+            df.validate_with_registry(&registry).unwrap();
 
             let client = get_client(zsession.clone()).await;
             let instance_uuid = client.instantiate(df).await.unwrap().unwrap();
             log::debug!("Launched: {:?}", instance_uuid);
             println!("{instance_uuid}");
         }
+        ZFCtl::Analyze {
+            descriptor_path,
+            profile,
+            strict,
+        } => {
+            log::debug!(
+                "This is going to analyze the flow described in {:?}",
+                descriptor_path
+            );
+            let yaml_df = read_to_string(descriptor_path).unwrap();
+            let df = zenoh_flow::model::descriptor::DataFlowDescriptor::from_yaml_with_mode(
+                &yaml_df,
+                parsing_mode(strict),
+            )
+            .unwrap();
+            let df = df.flatten().await.unwrap();
+            df.validate().unwrap();
+
+            let measured: Vec<WcetDescriptor> = match profile {
+                Some(profile) => serde_yaml::from_str(&read_to_string(profile).unwrap()).unwrap(),
+                None => vec![],
+            };
+            let report = df.analyze_feasibility(&measured).unwrap();
+
+            let mut table = Table::new();
+            table.add_row(row!["Node", "Rate (msg/s)", "WCET", "Utilization",]);
+            let mut nodes = report.nodes.iter().collect::<Vec<_>>();
+            nodes.sort_by(|(id, _), (other, _)| id.cmp(other));
+            for (node_id, load) in nodes {
+                table.add_row(row![
+                    node_id,
+                    format!("{:.2}", load.rate),
+                    load.wcet
+                        .map_or("-".to_string(), |wcet| format!("{wcet:?}")),
+                    format!("{:.1}%", load.utilization * 100.0),
+                ]);
+            }
+            table.printstd();
+
+            let mut table = Table::new();
+            table.add_row(row![
+                "From",
+                "To",
+                "Deadline",
+                "Latency",
+                "Path",
+                "Satisfiable",
+            ]);
+            for deadline in &report.deadlines {
+                table.add_row(row![
+                    deadline.deadline.from,
+                    deadline.deadline.to,
+                    format!("{:?}", deadline.deadline.deadline),
+                    deadline
+                        .latency
+                        .map_or("unbounded".to_string(), |latency| format!("{latency:?}")),
+                    deadline.path.join(" -> "),
+                    deadline.is_satisfiable(),
+                ]);
+            }
+            table.printstd();
+
+            if !report.unrated.is_empty() {
+                println!(
+                    "Sources without a rate, assumed to send no message: {}",
+                    report.unrated.join(", ")
+                );
+            }
+            if !report.unprofiled.is_empty() {
+                println!(
+                    "Nodes without a WCET, assumed to take no time: {}",
+                    report.unprofiled.join(", ")
+                );
+            }
+            if let (Some(bottleneck), Some(headroom)) = (&report.bottleneck, report.headroom()) {
+                println!(
+                    "Bottleneck: {bottleneck} (the rates can be multiplied by {headroom:.2} before it is overloaded)"
+                );
+            }
+            if report.is_feasible() {
+                println!("The flow is feasible");
+            } else {
+                println!("The flow is NOT feasible");
+                std::process::exit(1);
+            }
+        }
         ZFCtl::Destroy { id } => {
             log::debug!("This is going to destroy the instance {}", id);
             let client = get_client(zsession.clone()).await;
@@ -590,3 +1016,11 @@ async fn get_client(zsession: Arc<Session>) -> DaemonInterfaceClient {
     log::debug!("Selected entrypoint runtime: {:?}", entry_point);
     DaemonInterfaceClient::new(zsession.clone(), *entry_point)
 }
+
+fn parsing_mode(strict: bool) -> ParsingMode {
+    if strict {
+        ParsingMode::Strict
+    } else {
+        ParsingMode::Permissive
+    }
+}